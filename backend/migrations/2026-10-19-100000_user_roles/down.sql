@@ -0,0 +1,3 @@
+ALTER TABLE users
+    DROP COLUMN IF EXISTS session_version,
+    DROP COLUMN IF EXISTS role;
@@ -0,0 +1,6 @@
+--the case-insensitive form of the username, it is unique instead of the username itself
+--fails if two existing usernames differ only in case, rename one of them first
+ALTER TABLE users ADD COLUMN username_key VARCHAR(200);
+UPDATE users SET username_key = lower(username);
+ALTER TABLE users ALTER COLUMN username_key SET NOT NULL;
+ALTER TABLE users ADD CONSTRAINT users_username_key_key UNIQUE (username_key);
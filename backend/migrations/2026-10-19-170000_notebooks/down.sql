@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS posts_notebook_id_idx;
+ALTER TABLE posts DROP COLUMN notebook_id;
+DROP TABLE IF EXISTS notebooks;
@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS important_posts (
+    user_id TEXT NOT NULL REFERENCES users(id),
+    post_id TEXT NOT NULL REFERENCES posts(id),
+    PRIMARY KEY (user_id, post_id)
+);
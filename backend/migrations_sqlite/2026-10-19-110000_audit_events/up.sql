@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS audit_events (
+    id TEXT PRIMARY KEY NOT NULL,
+    actor_id TEXT,
+    actor_name VARCHAR(50),
+    action VARCHAR(32) NOT NULL,
+    target_id TEXT,
+    success BOOLEAN NOT NULL,
+    ip VARCHAR(64),
+    user_agent VARCHAR(256),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS audit_events_created_at_idx ON audit_events (created_at);
+CREATE INDEX IF NOT EXISTS audit_events_actor_id_idx ON audit_events (actor_id);
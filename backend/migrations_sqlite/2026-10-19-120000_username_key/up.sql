@@ -0,0 +1,5 @@
+--the case-insensitive form of the username, it is unique instead of the username itself
+--fails if two existing usernames differ only in case, rename one of them first
+ALTER TABLE users ADD COLUMN username_key VARCHAR(200) NOT NULL DEFAULT '';
+UPDATE users SET username_key = lower(username);
+CREATE UNIQUE INDEX users_username_key ON users (username_key);
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS posts_workspace_id_idx;
+ALTER TABLE posts DROP COLUMN workspace_id;
+DROP TABLE IF EXISTS workspace_members;
+DROP TABLE IF EXISTS workspaces;
@@ -0,0 +1,29 @@
+CREATE TABLE IF NOT EXISTS workspaces (
+    id TEXT PRIMARY KEY NOT NULL,
+    name VARCHAR(64) NOT NULL,
+    personal BOOLEAN NOT NULL DEFAULT 0,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS workspace_members (
+    workspace_id TEXT NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role VARCHAR(16) NOT NULL CHECK (role IN ('owner', 'admin', 'member')),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (workspace_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS workspace_members_user_id_idx ON workspace_members (user_id);
+
+--the existing users get a personal workspace with the id of their user, it holds their posts
+INSERT INTO workspaces (id, name, personal, created_at)
+    SELECT id, username, 1, created_at FROM users;
+INSERT INTO workspace_members (workspace_id, user_id, role, created_at)
+    SELECT id, id, 'owner', created_at FROM users;
+
+--SQLite can only add a column with a foreign key if it may be NULL, the posts of a workspace are
+--deleted by the backend instead
+ALTER TABLE posts ADD COLUMN workspace_id TEXT NOT NULL DEFAULT '';
+UPDATE posts SET workspace_id = user_id;
+
+CREATE INDEX IF NOT EXISTS posts_workspace_id_idx ON posts (workspace_id);
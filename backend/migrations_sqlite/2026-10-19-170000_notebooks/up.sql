@@ -0,0 +1,16 @@
+CREATE TABLE IF NOT EXISTS notebooks (
+    id TEXT PRIMARY KEY NOT NULL,
+    workspace_id TEXT NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    --a notebook is deleted with its parent, the top level notebooks have none
+    parent_id TEXT REFERENCES notebooks(id) ON DELETE CASCADE,
+    name VARCHAR(64) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS notebooks_workspace_id_idx ON notebooks (workspace_id);
+CREATE INDEX IF NOT EXISTS notebooks_parent_id_idx ON notebooks (parent_id);
+
+--the notes of a deleted notebook stay in the workspace, outside of the notebooks
+ALTER TABLE posts ADD COLUMN notebook_id TEXT REFERENCES notebooks(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS posts_notebook_id_idx ON posts (notebook_id);
@@ -0,0 +1,296 @@
+use actix_web::{delete, get, post, web, HttpResponse};
+use common::{ApiResponse, Role, RoleForm, SystemStats};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+use tracing_subscriber::EnvFilter;
+
+use crate::attachments::delete_unused_blobs;
+use crate::audit::{Audit, AuditAction};
+use crate::auth::{AdminRole, ModeratorRole, RequireRole, RoleGuard};
+use crate::db::{user_not_found, AuditFilter, User};
+use crate::{admin::AdminAuth, app::AppState, errors};
+
+/// The `LogFilterForm` struct holds an `EnvFilter` directive, for example `info,backend::db=debug`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LogFilterForm {
+    pub filter: String,
+}
+
+/// The function `log_filter_handle` returns the reload handle of the log filter or an error if the
+/// subscriber was not installed with one.
+fn log_filter_handle(state: &AppState) -> Result<&crate::logging::FilterHandle, errors::Error> {
+    state.log_filter.as_ref().ok_or(errors::Error::from_code(
+        "log.not_reloadable",
+        errors::ErrorTypes::Unavailable,
+    ))
+}
+
+#[get("/admin/logging")]
+#[instrument(skip_all, name = "Get log filter")]
+async fn get_log_filter(
+    state: web::Data<AppState>,
+    _: AdminAuth,
+) -> Result<HttpResponse, errors::Error> {
+    let filter = log_filter_handle(&state)?
+        .with_current(|filter| filter.to_string())
+        .map_err(|e| {
+            errors::Error::from_code("log.read_failed", errors::ErrorTypes::InternalError)
+                .with_cause(e)
+        })?;
+
+    Ok(HttpResponse::Ok().json(LogFilterForm { filter }))
+}
+
+#[post("/admin/logging")]
+#[instrument(skip(state), name = "Change log filter")]
+async fn set_log_filter(
+    state: web::Data<AppState>,
+    _: AdminAuth,
+    data: web::Json<LogFilterForm>,
+) -> Result<HttpResponse, errors::Error> {
+    let new_filter = EnvFilter::try_new(&data.filter).map_err(|e| {
+        errors::Error::from_code("log.invalid_filter", errors::ErrorTypes::ValidationError)
+            .with_cause(e)
+    })?;
+
+    log_filter_handle(&state)?.reload(new_filter).map_err(|e| {
+        errors::Error::from_code("log.reload_failed", errors::ErrorTypes::InternalError)
+            .with_cause(e)
+    })?;
+    tracing::warn!("Log filter changed to: {}", data.filter);
+
+    Ok(HttpResponse::Ok().json(LogFilterForm {
+        filter: data.filter.clone(),
+    }))
+}
+
+/// The function `moderation_target` loads the user that a moderation action is about. Nobody can
+/// moderate themselves, and only admins can moderate moderators and other admins.
+///
+/// Arguments:
+///
+/// * `state`: The `state` parameter is the app state with the repositories.
+/// * `actor`: The `actor` parameter is the authorized user that does the action.
+/// * `user_id`: The `user_id` parameter is the id of the moderated user.
+///
+/// Returns:
+///
+/// a `Result<User, errors::Error>`.
+async fn moderation_target<R: RoleGuard>(
+    state: &AppState,
+    actor: &RequireRole<R>,
+    user_id: uuid::Uuid,
+) -> Result<User, errors::Error> {
+    if actor.user_id == user_id {
+        return Err(errors::Error::from_code(
+            "admin.self_moderation",
+            errors::ErrorTypes::ValidationError,
+        ));
+    }
+
+    //`find_user` reports a missing user as an authentication error for the token checks
+    let user = state
+        .users
+        .find_user(user_id)
+        .await
+        .map_err(|e| match e.error_type {
+            errors::ErrorTypes::Auth(_) => user_not_found(user_id),
+            _ => e,
+        })?;
+    if actor.role != Role::Admin && user.role() >= actor.role {
+        tracing::error!("User {} can not moderate {}", actor.user_id, user.id);
+        return Err(errors::Error::from_code(
+            "auth.forbidden",
+            errors::ErrorTypes::Auth(errors::Auth::Authorization),
+        ));
+    }
+
+    Ok(user)
+}
+
+/// The function `revoke_sessions` logs the user out everywhere: the refresh tokens stop working at
+/// once and the access tokens are rejected by this server.
+async fn revoke_sessions(state: &AppState, user_id: uuid::Uuid) -> Result<(), errors::Error> {
+    let version = state.users.revoke_sessions(user_id).await?;
+    state.jwt.revoked.revoke(user_id, version);
+    Ok(())
+}
+
+fn success() -> HttpResponse {
+    HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_owned(),
+    })
+}
+
+#[get("/admin/users")]
+#[instrument(skip_all, name = "List users")]
+async fn list_users(
+    state: web::Data<AppState>,
+    _: RequireRole<ModeratorRole>,
+) -> Result<HttpResponse, errors::Error> {
+    let users = state.users.list_users().await?;
+    Ok(HttpResponse::Ok().json(users))
+}
+
+#[post("/admin/users/{id}/disable")]
+#[instrument(skip(state, actor, audit), name = "Disable user")]
+async fn disable_user(
+    state: web::Data<AppState>,
+    actor: RequireRole<ModeratorRole>,
+    audit: Audit,
+    path: web::Path<uuid::Uuid>,
+) -> Result<HttpResponse, errors::Error> {
+    let user = moderation_target(&state, &actor, path.into_inner()).await?;
+    state.users.set_disabled(user.id, true).await?;
+    revoke_sessions(&state, user.id).await?;
+    tracing::warn!("User {} disabled by {}", user.id, actor.user_id);
+    audit
+        .record(
+            AuditAction::UserDisable,
+            Some(actor.user_id),
+            None,
+            Some(user.id),
+            true,
+        )
+        .await;
+
+    Ok(success())
+}
+
+#[post("/admin/users/{id}/enable")]
+#[instrument(skip(state, actor, audit), name = "Enable user")]
+async fn enable_user(
+    state: web::Data<AppState>,
+    actor: RequireRole<ModeratorRole>,
+    audit: Audit,
+    path: web::Path<uuid::Uuid>,
+) -> Result<HttpResponse, errors::Error> {
+    let user = moderation_target(&state, &actor, path.into_inner()).await?;
+    state.users.set_disabled(user.id, false).await?;
+    tracing::warn!("User {} enabled by {}", user.id, actor.user_id);
+    audit
+        .record(
+            AuditAction::UserEnable,
+            Some(actor.user_id),
+            None,
+            Some(user.id),
+            true,
+        )
+        .await;
+
+    Ok(success())
+}
+
+#[post("/admin/users/{id}/logout")]
+#[instrument(skip(state, actor, audit), name = "Force user logout")]
+async fn logout_user(
+    state: web::Data<AppState>,
+    actor: RequireRole<ModeratorRole>,
+    audit: Audit,
+    path: web::Path<uuid::Uuid>,
+) -> Result<HttpResponse, errors::Error> {
+    let user = moderation_target(&state, &actor, path.into_inner()).await?;
+    revoke_sessions(&state, user.id).await?;
+    tracing::warn!("User {} logged out by {}", user.id, actor.user_id);
+    audit
+        .record(
+            AuditAction::UserLogout,
+            Some(actor.user_id),
+            None,
+            Some(user.id),
+            true,
+        )
+        .await;
+
+    Ok(success())
+}
+
+#[post("/admin/users/{id}/role")]
+#[instrument(skip(state, actor, audit), name = "Change user's role")]
+async fn set_user_role(
+    state: web::Data<AppState>,
+    actor: RequireRole<AdminRole>,
+    audit: Audit,
+    path: web::Path<uuid::Uuid>,
+    data: web::Json<RoleForm>,
+) -> Result<HttpResponse, errors::Error> {
+    let user = moderation_target(&state, &actor, path.into_inner()).await?;
+    state.users.set_role(user.id, data.role).await?;
+    //the old role is written in the issued tokens
+    revoke_sessions(&state, user.id).await?;
+    tracing::warn!(
+        "Role of user {} changed to {} by {}",
+        user.id,
+        data.role,
+        actor.user_id
+    );
+    audit
+        .record(
+            AuditAction::UserRole,
+            Some(actor.user_id),
+            None,
+            Some(user.id),
+            true,
+        )
+        .await;
+
+    Ok(success())
+}
+
+#[delete("/admin/users/{id}")]
+#[instrument(skip(state, actor, audit), name = "Delete user")]
+async fn delete_user(
+    state: web::Data<AppState>,
+    actor: RequireRole<AdminRole>,
+    audit: Audit,
+    path: web::Path<uuid::Uuid>,
+) -> Result<HttpResponse, errors::Error> {
+    let user = moderation_target(&state, &actor, path.into_inner()).await?;
+    revoke_sessions(&state, user.id).await?;
+    let hashes = state.attachments.get_user_hashes(user.id).await?;
+    state.users.delete_user(user.id).await?;
+    delete_unused_blobs(&state, hashes).await;
+    tracing::warn!("User {} deleted by {}", user.id, actor.user_id);
+    audit
+        .record(
+            AuditAction::UserDelete,
+            Some(actor.user_id),
+            None,
+            Some(user.id),
+            true,
+        )
+        .await;
+
+    Ok(success())
+}
+
+#[get("/admin/stats")]
+#[instrument(skip_all, name = "Get system stats")]
+async fn get_stats(
+    state: web::Data<AppState>,
+    _: RequireRole<AdminRole>,
+) -> Result<HttpResponse, errors::Error> {
+    let users = state.users.list_users().await?;
+    let with_role = |role: Role| users.iter().filter(|u| u.role == role).count();
+
+    Ok(HttpResponse::Ok().json(SystemStats {
+        users: users.len(),
+        disabled_users: users.iter().filter(|u| u.disabled).count(),
+        admins: with_role(Role::Admin),
+        moderators: with_role(Role::Moderator),
+        posts: users.iter().map(|u| u.posts).sum(),
+        uptime: state.started_at.elapsed().as_secs(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+    }))
+}
+
+#[get("/admin/audit")]
+#[instrument(skip(state, _admin), name = "Get audit events")]
+async fn get_audit_events(
+    state: web::Data<AppState>,
+    _admin: RequireRole<AdminRole>,
+    filter: web::Query<AuditFilter>,
+) -> Result<HttpResponse, errors::Error> {
+    let events = state.audit.get_events(filter.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(events))
+}
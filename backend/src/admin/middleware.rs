@@ -0,0 +1,60 @@
+use actix_web::{dev::Payload, http::header, web, FromRequest, HttpRequest};
+
+use tracing::instrument;
+
+use crate::app::AppState;
+use crate::errors::{Auth, Error, ErrorTypes};
+use std::future::{ready, Ready};
+
+/// The `AdminAuth` struct is an extractor for the operator endpoints. The request has to carry the
+/// configured admin token as `Authorization: Bearer <token>`.
+#[derive(Debug)]
+pub struct AdminAuth;
+
+/// The function `tokens_match` compares two tokens in constant time, so the comparison does not leak
+/// how many leading characters were correct.
+fn tokens_match(expected: &str, given: &str) -> bool {
+    expected.len() == given.len()
+        && expected
+            .bytes()
+            .zip(given.bytes())
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+impl FromRequest for AdminAuth {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    #[instrument(skip_all, name = "Check admin authorization", fields(uri = %req.uri(), method = %req.method()))]
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let state = req
+            .app_data::<web::Data<AppState>>()
+            .expect("Can not get app state data");
+
+        if state.admin_token.is_empty() {
+            tracing::error!("Admin token is not configured");
+            return ready(Err(Error::from_code(
+                "admin.disabled",
+                ErrorTypes::Auth(Auth::Authorization),
+            )));
+        }
+
+        let token = req
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+
+        match token {
+            Some(token) if tokens_match(&state.admin_token, token) => ready(Ok(AdminAuth)),
+            _ => {
+                tracing::error!("Invalid admin token");
+                ready(Err(Error::from_code(
+                    "admin.invalid_token",
+                    ErrorTypes::Auth(Auth::Authentication),
+                )))
+            }
+        }
+    }
+}
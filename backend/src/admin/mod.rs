@@ -0,0 +1,18 @@
+pub mod handlers;
+pub mod middleware;
+
+use actix_web::web;
+pub use middleware::*;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(handlers::get_log_filter)
+        .service(handlers::set_log_filter)
+        .service(handlers::list_users)
+        .service(handlers::disable_user)
+        .service(handlers::enable_user)
+        .service(handlers::logout_user)
+        .service(handlers::set_user_role)
+        .service(handlers::delete_user)
+        .service(handlers::get_stats)
+        .service(handlers::get_audit_events);
+}
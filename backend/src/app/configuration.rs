@@ -1,11 +1,32 @@
-use diesel::prelude::*;
-use diesel::r2d2::{ConnectionManager, Pool};
-use r2d2::Error;
+use std::sync::Arc;
+
+use std::time::{Duration, Instant};
+
+use deadpool::managed::{Hook, HookError};
+use deadpool::Runtime;
+use diesel_async::pooled_connection::{
+    deadpool::Pool, AsyncDieselConnectionManager, ManagerConfig, RecyclingMethod,
+};
+use diesel_async::AsyncPgConnection;
 use serde::{Deserialize, Serialize};
 
+use crate::attachments::{BlobLocks, BlobStore, LocalBlobStore, MemoryBlobStore};
+use crate::audit::AuditLog;
+use crate::collab::CollabRooms;
+use crate::db::{
+    sqlite::{self, SqliteRepository},
+    AttachmentRepository, AuditRepository, MemoryRepository, NotebookRepository, NotesRepository,
+    PermissionRepository, PgRepository, SchemaRepository, ShareRepository, UserRepository,
+    WorkspaceRepository,
+};
+use crate::errors::{Error, ErrorTypes};
+use crate::events::EventHub;
+use crate::logging::FilterHandle;
 use crate::schema::jwt::Jwt;
-/// Defining a type alias `DbPool` for a connection pool of `PgConnection` objects.
-pub type DbPool = Pool<ConnectionManager<PgConnection>>;
+use crate::share::ShareAttempts;
+use crate::tasks::Tasks;
+/// Defining a type alias `DbPool` for an async connection pool of `AsyncPgConnection` objects.
+pub type DbPool = Pool<AsyncPgConnection>;
 /// The above code defines a struct called `Settings` with two fields: `database` and `auth`.
 ///
 /// Properties:
@@ -13,10 +34,76 @@ pub type DbPool = Pool<ConnectionManager<PgConnection>>;
 /// * `database`: The `database` property is of type `DbSettings`. It represents the settings for the
 /// database connection and configuration.
 /// * `auth`: The `auth` property is of type `AuthSettings`.
+/// * `telemetry`: The `telemetry` property is of type `TelemetrySettings`. It controls the optional
+/// OpenTelemetry trace export.
+/// * `logging`: The `logging` property is of type `LoggingSettings`. It selects the log level and the
+/// output format.
+/// * `admin`: The `admin` property is of type `AdminSettings`. It protects the operator endpoints.
+/// * `audit`: The `audit` property is of type `AuditSettings`. It sets the retention of the audit log.
+/// * `policy`: The `policy` property is of type `PolicySettings`. It holds the rules for usernames and
+/// passwords and the password hashing parameters.
+/// * `attachments`: The `attachments` property is of type `AttachmentSettings`. It sets where the files
+/// attached to posts are stored and how large they can be.
+/// * `server`: The `server` property is of type `ServerSettings`. It contains the bind address and
+/// the shutdown timeouts.
+/// * `share`: The `share` property is of type `ShareSettings`. It limits the password attempts on share
+/// links.
 #[derive(Deserialize, Serialize)]
 pub struct Settings {
+    #[serde(default)]
+    pub server: ServerSettings,
     pub database: DbSettings,
     pub auth: AuthSettings,
+    #[serde(default)]
+    pub telemetry: TelemetrySettings,
+    #[serde(default)]
+    pub logging: LoggingSettings,
+    #[serde(default)]
+    pub admin: AdminSettings,
+    #[serde(default)]
+    pub audit: AuditSettings,
+    #[serde(default)]
+    pub policy: PolicySettings,
+    #[serde(default)]
+    pub attachments: AttachmentSettings,
+    #[serde(default)]
+    pub share: ShareSettings,
+}
+
+/// The `ServerSettings` struct represents the HTTP server configuration.
+///
+/// Properties:
+///
+/// * `host`: The `host` property is the address the server binds to.
+/// * `port`: The `port` property is the port the server listens on.
+/// * `drain_delay`: The `drain_delay` property is the number of seconds between the shutdown signal and
+/// the moment the server stops accepting connections. During this time `/health/ready` already reports
+/// not-ready, so the load balancer can take the instance out of rotation.
+/// * `shutdown_timeout`: The `shutdown_timeout` property is the number of seconds in-flight requests
+/// and background tasks get to finish before they are cancelled.
+/// * `expose_error_cause`: The `expose_error_cause` property sends the internal cause of the errors
+/// (for example the database error) to the clients. It is meant for development, in production the
+/// cause is only written to the log.
+#[derive(Deserialize, Serialize)]
+pub struct ServerSettings {
+    pub host: String,
+    pub port: u16,
+    pub drain_delay: u64,
+    pub shutdown_timeout: u64,
+    #[serde(default)]
+    pub expose_error_cause: bool,
+}
+
+impl Default for ServerSettings {
+    fn default() -> Self {
+        ServerSettings {
+            host: "127.0.0.1".to_string(),
+            port: 8000,
+            drain_delay: 5,
+            shutdown_timeout: 30,
+            expose_error_cause: false,
+        }
+    }
 }
 
 /// The `DbSettings` struct represents the settings needed to connect to a database.
@@ -32,6 +119,14 @@ pub struct Settings {
 /// * `host`: The `host` property represents the hostname or IP address of the database server.
 /// * `database_name`: The `database_name` property is a string that represents the name of the
 /// database.
+/// * `backend`: The `backend` property selects the database, `postgres` (default), `sqlite` or
+/// `memory`. The connection fields above are used by PostgreSQL only. `memory` keeps the data in the
+/// process and loses it on restart.
+/// * `sqlite_path`: The `sqlite_path` property is the SQLite database file.
+/// * `run_migrations_on_startup`: The `run_migrations_on_startup` property turns on applying the
+/// pending embedded migrations before the server starts. It is off by default.
+/// * `pool`: The `pool` property is of type `PoolSettings`. It configures the PostgreSQL connection
+/// pool.
 #[derive(Deserialize, Serialize)]
 pub struct DbSettings {
     pub username: String,
@@ -39,6 +134,78 @@ pub struct DbSettings {
     pub port: u16,
     pub host: String,
     pub database_name: String,
+    #[serde(default)]
+    pub backend: DbBackend,
+    #[serde(default = "default_sqlite_path")]
+    pub sqlite_path: String,
+    #[serde(default)]
+    pub run_migrations_on_startup: bool,
+    #[serde(default)]
+    pub pool: PoolSettings,
+}
+
+/// The `PoolSettings` struct configures the async PostgreSQL connection pool.
+///
+/// Properties:
+///
+/// * `max_size`: The `max_size` property is the maximum number of open connections.
+/// * `wait_timeout`: The `wait_timeout` property is the number of seconds a request waits for a free
+/// connection before it fails.
+/// * `create_timeout`: The `create_timeout` property is the number of seconds to open a new
+/// connection.
+/// * `recycle_timeout`: The `recycle_timeout` property is the number of seconds to check a returned
+/// connection before it is reused.
+/// * `recycling`: The `recycling` property selects how returned connections are checked: `fast` only
+/// checks for open transactions, `verified` also runs a test query.
+/// * `statement_cache_lifetime`: The `statement_cache_lifetime` property is the number of seconds the
+/// prepared statements of a connection are cached, `0` keeps them as long as the connection. diesel-async
+/// caches the statements of every connection and can not turn it off, so an older connection is
+/// replaced with a new one, with an empty cache, when it is returned.
+#[derive(Deserialize, Serialize)]
+#[serde(default)]
+pub struct PoolSettings {
+    pub max_size: usize,
+    pub wait_timeout: u64,
+    pub create_timeout: u64,
+    pub recycle_timeout: u64,
+    pub recycling: PoolRecycling,
+    pub statement_cache_lifetime: u64,
+}
+
+impl Default for PoolSettings {
+    fn default() -> Self {
+        PoolSettings {
+            max_size: 16,
+            wait_timeout: 30,
+            create_timeout: 5,
+            recycle_timeout: 5,
+            recycling: PoolRecycling::Verified,
+            statement_cache_lifetime: 0,
+        }
+    }
+}
+
+/// The `PoolRecycling` enum lists the checks done on a connection before it is reused.
+#[derive(Deserialize, Serialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PoolRecycling {
+    Fast,
+    #[default]
+    Verified,
+}
+
+/// The `DbBackend` enum lists the supported databases.
+#[derive(Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DbBackend {
+    #[default]
+    Postgres,
+    Sqlite,
+    Memory,
+}
+
+fn default_sqlite_path() -> String {
+    "notes.db".to_string()
 }
 
 /// The TokenConfig struct represents the configuration for a token, including its key, expiration time,
@@ -73,18 +240,474 @@ pub struct AuthSettings {
     pub refresh: TokenConfig,
 }
 
-/// The `AppState` struct represents the state of an application and contains a database connection pool
-/// and a JSON Web Token (JWT) object.
+/// The `TelemetrySettings` struct represents the configuration of the OpenTelemetry exporter.
+///
+/// Properties:
+///
+/// * `enabled`: The `enabled` property turns the OTLP exporter on or off. When it is `false` spans are
+/// only printed to the log output.
+/// * `service_name`: The `service_name` property is the `service.name` resource attribute attached to
+/// every exported span.
+/// * `endpoint`: The `endpoint` property is the OTLP/HTTP traces endpoint of the collector, for example
+/// `http://127.0.0.1:4318/v1/traces`.
+#[derive(Deserialize, Serialize)]
+pub struct TelemetrySettings {
+    pub enabled: bool,
+    pub service_name: String,
+    pub endpoint: String,
+}
+
+impl Default for TelemetrySettings {
+    fn default() -> Self {
+        TelemetrySettings {
+            enabled: false,
+            service_name: "notes-portal".to_string(),
+            endpoint: "http://127.0.0.1:4318/v1/traces".to_string(),
+        }
+    }
+}
+
+/// The `LogFormat` enum selects how log lines are written: human readable (`pretty`) or one JSON object
+/// per line (`json`).
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Pretty,
+    Json,
+}
+
+/// The `LoggingSettings` struct represents the logging configuration.
+///
+/// Properties:
+///
+/// * `level`: The `level` property is the default `EnvFilter` directive, for example `debug` or
+/// `info,backend=debug`. The `RUST_LOG` environment variable takes precedence over it.
+/// * `format`: The `format` property is of type `LogFormat`.
+/// * `stdout`: The `stdout` property writes the logs to the standard output. It can be combined with
+/// the file output to tee the logs.
+/// * `file`: The `file` property is of type `LogFileSettings`.
+#[derive(Deserialize, Serialize)]
+pub struct LoggingSettings {
+    pub level: String,
+    pub format: LogFormat,
+    #[serde(default = "default_true")]
+    pub stdout: bool,
+    #[serde(default)]
+    pub file: LogFileSettings,
+}
+
+impl Default for LoggingSettings {
+    fn default() -> Self {
+        LoggingSettings {
+            level: "debug".to_string(),
+            format: LogFormat::Pretty,
+            stdout: true,
+            file: LogFileSettings::default(),
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// The `LogRotation` enum selects how often a new log file is started.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogRotation {
+    #[default]
+    Daily,
+    Hourly,
+    Never,
+}
+
+/// The `LogFileSettings` struct represents the rolling log file output.
+///
+/// Properties:
+///
+/// * `enabled`: The `enabled` property turns the file output on or off.
+/// * `directory`: The `directory` property is the folder where the log files are written.
+/// * `file_name`: The `file_name` property is the name of the active log file. Rotated files get a
+/// numeric suffix (`backend.log.1`, `backend.log.2`, ...).
+/// * `rotation`: The `rotation` property is of type `LogRotation`.
+/// * `max_size_mb`: The `max_size_mb` property rotates the file when it grows over this size in
+/// megabytes. `0` turns size based rotation off.
+/// * `max_files`: The `max_files` property is the number of rotated files that are kept.
+#[derive(Deserialize, Serialize)]
+pub struct LogFileSettings {
+    pub enabled: bool,
+    pub directory: String,
+    pub file_name: String,
+    pub rotation: LogRotation,
+    pub max_size_mb: u64,
+    pub max_files: usize,
+}
+
+impl Default for LogFileSettings {
+    fn default() -> Self {
+        LogFileSettings {
+            enabled: false,
+            directory: "logs".to_string(),
+            file_name: "backend.log".to_string(),
+            rotation: LogRotation::Daily,
+            max_size_mb: 0,
+            max_files: 7,
+        }
+    }
+}
+
+/// The `AdminSettings` struct represents the configuration of the operator endpoints under `/admin`.
+///
+/// Properties:
+///
+/// * `token`: The `token` property is the secret that has to be sent as `Authorization: Bearer <token>`.
+/// An empty token disables the endpoints.
+#[derive(Deserialize, Serialize, Default)]
+pub struct AdminSettings {
+    pub token: String,
+}
+
+/// The `AuditSettings` struct represents the configuration of the audit log.
 ///
 /// Properties:
 ///
-/// * `connection`: DbPool is a connection pool for a database. It allows multiple connections to be
-/// created and reused, improving performance and scalability.
+/// * `retention_days`: The `retention_days` property is how long the events are kept. `0` keeps them
+/// forever.
+/// * `cleanup_interval`: The `cleanup_interval` property is the number of minutes between the runs that
+/// delete the expired events.
+/// * `trust_proxy_headers`: The `trust_proxy_headers` property takes the client address from the
+/// `Forwarded`/`X-Forwarded-For` headers. Only turn it on behind a proxy that sets them.
+#[derive(Deserialize, Serialize, Clone)]
+pub struct AuditSettings {
+    pub retention_days: u32,
+    pub cleanup_interval: u64,
+    pub trust_proxy_headers: bool,
+}
+
+impl Default for AuditSettings {
+    fn default() -> Self {
+        AuditSettings {
+            retention_days: 90,
+            cleanup_interval: 60,
+            trust_proxy_headers: false,
+        }
+    }
+}
+
+/// The `ShareSettings` struct represents the limits of the passwords sent for share links. Every
+/// password is checked with an argon2 hash, so the attempts are limited by link and by client address.
+///
+/// Properties:
+///
+/// * `max_attempts_per_link`: The `max_attempts_per_link` property is the number of wrong passwords a
+/// link accepts in a window.
+/// * `max_attempts_per_ip`: The `max_attempts_per_ip` property is the number of wrong passwords a
+/// client address can send in a window, for all links together.
+/// * `attempt_window`: The `attempt_window` property is the length of the window in minutes.
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct ShareSettings {
+    pub max_attempts_per_link: u32,
+    pub max_attempts_per_ip: u32,
+    pub attempt_window: u64,
+}
+
+impl Default for ShareSettings {
+    fn default() -> Self {
+        ShareSettings {
+            max_attempts_per_link: 10,
+            max_attempts_per_ip: 30,
+            attempt_window: 15,
+        }
+    }
+}
+
+/// The `CharClass` enum lists the groups of characters the policy allows or requires.
+///
+/// * `latin`: the ASCII letters `a-z` and `A-Z`.
+/// * `letter`: the letters of any alphabet, including the latin ones.
+/// * `digit`: the digits `0-9`.
+/// * `symbol`: punctuation and other symbols, like `!`, `_` or `€`.
+/// * `space`: the space character.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CharClass {
+    Latin,
+    Letter,
+    Digit,
+    Symbol,
+    Space,
+}
+
+impl CharClass {
+    /// The function `contains` checks if the character belongs to the class.
+    pub fn contains(&self, c: char) -> bool {
+        match self {
+            CharClass::Latin => c.is_ascii_alphabetic(),
+            CharClass::Letter => c.is_alphabetic(),
+            CharClass::Digit => c.is_ascii_digit(),
+            CharClass::Symbol => !c.is_alphanumeric() && !c.is_whitespace() && !c.is_control(),
+            CharClass::Space => c == ' ',
+        }
+    }
+}
+
+/// The `PolicySettings` struct represents the rules for new credentials and how passwords are hashed.
+///
+/// Properties:
+///
+/// * `username`: The `username` property is of type `UsernamePolicy`.
+/// * `password`: The `password` property is of type `PasswordPolicy`.
+/// * `argon2`: The `argon2` property is of type `Argon2Settings`.
+#[derive(Deserialize, Serialize, Clone, Default)]
+#[serde(default)]
+pub struct PolicySettings {
+    pub username: UsernamePolicy,
+    pub password: PasswordPolicy,
+    pub argon2: Argon2Settings,
+}
+
+/// The `UsernamePolicy` struct represents the rules for usernames. Usernames are compared without
+/// regard to case, `Alice` and `alice` are the same user.
+///
+/// Properties:
+///
+/// * `min_length`: The `min_length` property is the minimum number of characters.
+/// * `max_length`: The `max_length` property is the maximum number of characters. It can not be more
+/// than 50, the length of the `users.username` column.
+/// * `allowed`: The `allowed` property lists the character classes a username can contain.
+/// * `normalize`: The `normalize` property applies the Unicode NFKC normalization before the checks,
+/// so look-alike forms of a character (for example the full-width `Ａ`) are stored as the same
+/// character.
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct UsernamePolicy {
+    pub min_length: usize,
+    pub max_length: usize,
+    pub allowed: Vec<CharClass>,
+    pub normalize: bool,
+}
+
+impl Default for UsernamePolicy {
+    fn default() -> Self {
+        UsernamePolicy {
+            min_length: 5,
+            max_length: 50,
+            allowed: vec![CharClass::Latin, CharClass::Digit],
+            normalize: true,
+        }
+    }
+}
+
+/// The `PasswordPolicy` struct represents the rules for new passwords. They are checked on signup and
+/// when an operator sets a password, existing passwords keep working after the policy changes.
+///
+/// Properties:
+///
+/// * `min_length`: The `min_length` property is the minimum number of characters.
+/// * `max_length`: The `max_length` property is the maximum number of characters.
+/// * `allowed`: The `allowed` property lists the character classes a password can contain.
+/// * `required`: The `required` property lists the character classes a password must contain at least
+/// one character of.
+/// * `breached_passwords`: The `breached_passwords` property is the folder of a local copy of a breached
+/// password list in the k-anonymity format of Have I Been Pwned: a file `<PREFIX>.txt` for every first
+/// 5 hex characters of the SHA-1 hash, with `SUFFIX:COUNT` lines. Passwords found there are rejected.
+/// The check is off when it is not set.
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub max_length: usize,
+    pub allowed: Vec<CharClass>,
+    pub required: Vec<CharClass>,
+    pub breached_passwords: Option<String>,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        PasswordPolicy {
+            min_length: 8,
+            max_length: 128,
+            allowed: vec![
+                CharClass::Letter,
+                CharClass::Digit,
+                CharClass::Symbol,
+                CharClass::Space,
+            ],
+            required: vec![CharClass::Letter, CharClass::Digit],
+            breached_passwords: None,
+        }
+    }
+}
+
+/// The `Argon2Settings` struct represents the parameters of the argon2id password hashes. Hashes made
+/// with other parameters are replaced on the next successful login.
+///
+/// Properties:
+///
+/// * `memory_kib`: The `memory_kib` property is the memory cost in KiB.
+/// * `iterations`: The `iterations` property is the number of passes over the memory.
+/// * `parallelism`: The `parallelism` property is the number of lanes.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Argon2Settings {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+//the defaults of the argon2 crate, the OWASP recommendation
+impl Default for Argon2Settings {
+    fn default() -> Self {
+        Argon2Settings {
+            memory_kib: 19 * 1024,
+            iterations: 2,
+            parallelism: 1,
+        }
+    }
+}
+
+/// The `AttachmentSettings` struct represents the storage of the files attached to posts.
+///
+/// Properties:
+///
+/// * `directory`: The `directory` property is the folder of the local blob store. The files are
+/// stored once per content, under their SHA-256 hash. With the `memory` database the files are kept in
+/// memory too.
+/// * `max_file_size_mb`: The `max_file_size_mb` property is the largest file that can be attached, in
+/// megabytes.
+/// * `quota_mb`: The `quota_mb` property is how many megabytes of attachments a user can store
+/// together. `0` turns the quota off.
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct AttachmentSettings {
+    pub directory: String,
+    pub max_file_size_mb: u64,
+    pub quota_mb: u64,
+}
+
+impl Default for AttachmentSettings {
+    fn default() -> Self {
+        AttachmentSettings {
+            directory: "attachments".to_string(),
+            max_file_size_mb: 10,
+            quota_mb: 100,
+        }
+    }
+}
+
+impl AttachmentSettings {
+    /// The function `max_file_size` returns the largest file in bytes.
+    pub fn max_file_size(&self) -> usize {
+        (self.max_file_size_mb * 1024 * 1024) as usize
+    }
+
+    /// The function `quota` returns the quota of a user in bytes, `None` if there is no quota.
+    pub fn quota(&self) -> Option<i64> {
+        match self.quota_mb {
+            0 => None,
+            quota_mb => Some((quota_mb * 1024 * 1024) as i64),
+        }
+    }
+}
+
+/// The `AppState` struct represents the state of an application and contains the storage
+/// repositories and a JSON Web Token (JWT) object.
+///
+/// Properties:
+///
+/// * `users`: The `users` property is the user storage. It is PostgreSQL or SQLite, depending on
+/// `DbSettings::backend`.
+/// * `notes`: The `notes` property is the storage of the posts.
+/// * `schema`: The `schema` property runs and checks the migrations of the same database.
+/// * `audit`: The `audit` property is the audit log in the same database.
+/// * `attachments`: The `attachments` property stores the attachment records in the same database.
+/// * `shares`: The `shares` property stores the share links of the posts in the same database.
+/// * `permissions`: The `permissions` property stores the access other users are granted to the
+/// posts.
+/// * `workspaces`: The `workspaces` property stores the workspaces and their members.
+/// * `notebooks`: The `notebooks` property stores the notebooks of the workspaces.
+/// * `blobs`: The `blobs` property stores the content of the attachments.
+/// * `events`: The `events` property sends the changes of the posts to the `/events` streams.
+/// * `collab`: The `collab` property holds the rooms of the posts edited together on this instance.
+/// * `share_attempts`: The `share_attempts` property counts the passwords sent for share links.
+/// * `blob_locks`: The `blob_locks` property keeps an upload and the cleanup of the same content apart.
+/// * `attachment_settings`: The `attachment_settings` property holds the size limits of the
+/// attachments.
 /// * `jwt`: The `jwt` property is an instance of the `Jwt` struct. It is likely used for handling JSON
 /// Web Tokens (JWT) authentication and authorization in the application.
+/// * `admin_token`: The `admin_token` property is the secret for the `/admin/logging` endpoints.
+/// * `policy`: The `policy` property holds the rules for new credentials and the hashing parameters.
+/// * `log_filter`: The `log_filter` property is a handle to the reloadable log filter. It is `None`
+/// until the tracing subscriber is installed.
+/// * `tasks`: The `tasks` property runs the blocking database jobs and the background tasks, and
+/// tracks the shutdown state.
+/// * `started_at`: The `started_at` property is the time the state was created, for the uptime in the
+/// admin stats.
 pub struct AppState {
-    pub connection: DbPool,
+    pub users: Arc<dyn UserRepository>,
+    pub notes: Arc<dyn NotesRepository>,
+    pub schema: Arc<dyn SchemaRepository>,
+    pub audit: AuditLog,
+    pub attachments: Arc<dyn AttachmentRepository>,
+    pub shares: Arc<dyn ShareRepository>,
+    pub permissions: Arc<dyn PermissionRepository>,
+    pub workspaces: Arc<dyn WorkspaceRepository>,
+    pub notebooks: Arc<dyn NotebookRepository>,
+    pub blobs: Arc<dyn BlobStore>,
+    pub events: EventHub,
+    pub collab: CollabRooms,
+    pub share_attempts: ShareAttempts,
+    pub blob_locks: BlobLocks,
+    pub attachment_settings: AttachmentSettings,
     pub jwt: Jwt,
+    pub tasks: Tasks,
+    pub admin_token: String,
+    pub policy: PolicySettings,
+    pub log_filter: Option<FilterHandle>,
+    pub started_at: Instant,
+}
+
+//one repository implements all traits, it is shared between the fields of the app state
+struct Repositories {
+    users: Arc<dyn UserRepository>,
+    notes: Arc<dyn NotesRepository>,
+    attachments: Arc<dyn AttachmentRepository>,
+    shares: Arc<dyn ShareRepository>,
+    permissions: Arc<dyn PermissionRepository>,
+    workspaces: Arc<dyn WorkspaceRepository>,
+    notebooks: Arc<dyn NotebookRepository>,
+    audit: Arc<dyn AuditRepository>,
+    schema: Arc<dyn SchemaRepository>,
+}
+
+impl Repositories {
+    fn new<R>(repository: Arc<R>) -> Self
+    where
+        R: UserRepository
+            + NotesRepository
+            + AttachmentRepository
+            + ShareRepository
+            + PermissionRepository
+            + WorkspaceRepository
+            + NotebookRepository
+            + AuditRepository
+            + SchemaRepository
+            + 'static,
+    {
+        Repositories {
+            users: repository.clone(),
+            notes: repository.clone(),
+            attachments: repository.clone(),
+            shares: repository.clone(),
+            permissions: repository.clone(),
+            workspaces: repository.clone(),
+            notebooks: repository.clone(),
+            audit: repository.clone(),
+            schema: repository,
+        }
+    }
 }
 
 impl Settings {
@@ -112,36 +735,119 @@ impl Settings {
         settings.try_deserialize::<Settings>()
     }
 
-    /// The function creates an application state by obtaining a database connection and initializing a
-    /// JWT object.
+    /// The function creates an application state by creating the repositories for the configured
+    /// database and initializing a JWT object.
     ///
     /// Returns:
     ///
     /// a Result type, which can either be Ok(AppState) if the function executes successfully, or
     /// Err(Error) if there is an error.
     pub fn create_app_state(&self) -> Result<AppState, Error> {
-        let connection = self.database.get_connection_pool()?;
+        let tasks = Tasks::new();
+        //only PostgreSQL is shared by several instances, they exchange the events through it
+        let (repositories, events) = match self.database.backend {
+            DbBackend::Postgres => {
+                let pool = self.database.get_connection_pool()?;
+                let events = EventHub::new(Some((pool.clone(), self.database.connection_url())));
+                let repositories = Repositories::new(Arc::new(PgRepository::new(
+                    pool,
+                    self.database.connection_url(),
+                    tasks.clone(),
+                )));
+                (repositories, events)
+            }
+            DbBackend::Sqlite => {
+                let pool = sqlite::get_connection_pool(&self.database.sqlite_path)?;
+                let repositories =
+                    Repositories::new(Arc::new(SqliteRepository::new(pool, tasks.clone())));
+                (repositories, EventHub::new(None))
+            }
+            DbBackend::Memory => (
+                Repositories::new(Arc::new(MemoryRepository::new())),
+                EventHub::new(None),
+            ),
+        };
+        let blobs: Arc<dyn BlobStore> = match self.database.backend {
+            DbBackend::Memory => Arc::new(MemoryBlobStore::new()),
+            _ => Arc::new(LocalBlobStore::new(&self.attachments.directory)),
+        };
+
         Ok(AppState {
-            connection,
+            users: repositories.users,
+            notes: repositories.notes,
+            schema: repositories.schema,
+            audit: AuditLog::new(repositories.audit, self.audit.clone()),
+            attachments: repositories.attachments,
+            shares: repositories.shares,
+            permissions: repositories.permissions,
+            workspaces: repositories.workspaces,
+            notebooks: repositories.notebooks,
+            blobs,
+            events,
+            collab: CollabRooms::new(),
+            share_attempts: ShareAttempts::new(self.share.clone()),
+            blob_locks: BlobLocks::default(),
+            attachment_settings: self.attachments.clone(),
             jwt: Jwt::new(&self.auth.access, &self.auth.refresh),
+            tasks,
+            admin_token: self.admin.token.clone(),
+            policy: self.policy.clone(),
+            log_filter: None,
+            started_at: Instant::now(),
         })
     }
 }
 
 impl DbSettings {
-    /// The function `get_connection_pool` returns a connection pool for a PostgreSQL database.
+    /// The function `connection_url` returns the PostgreSQL connection url built from the settings.
+    pub fn connection_url(&self) -> String {
+        format!(
+            "postgres://{}:{}@{}:{}/{}",
+            self.username, self.password, self.host, self.port, self.database_name
+        )
+    }
+
+    /// The function `get_connection_pool` returns an async connection pool for a PostgreSQL database.
+    /// Connections are opened on first use.
     ///
     /// Returns:
     ///
     /// a Result type, which can either be Ok(DbPool) or Err(Error).
     fn get_connection_pool(&self) -> Result<DbPool, Error> {
-        let url = format!(
-            "postgres://{}:{}@{}:{}/{}",
-            self.username, self.password, self.host, self.port, self.database_name
+        let settings = &self.pool;
+        let mut config = ManagerConfig::default();
+        config.recycling_method = match settings.recycling {
+            PoolRecycling::Fast => RecyclingMethod::Fast,
+            PoolRecycling::Verified => RecyclingMethod::Verified,
+        };
+        let manager = AsyncDieselConnectionManager::<AsyncPgConnection>::new_with_config(
+            self.connection_url(),
+            config,
         );
 
-        let manager = ConnectionManager::<PgConnection>::new(url);
+        let mut builder = Pool::builder(manager)
+            .max_size(settings.max_size)
+            .wait_timeout(Some(Duration::from_secs(settings.wait_timeout)))
+            .create_timeout(Some(Duration::from_secs(settings.create_timeout)))
+            .recycle_timeout(Some(Duration::from_secs(settings.recycle_timeout)))
+            .runtime(Runtime::Tokio1);
+        if settings.statement_cache_lifetime > 0 {
+            let lifetime = Duration::from_secs(settings.statement_cache_lifetime);
+            //the connection takes its cached statements with it, a new one is opened instead
+            builder = builder.pre_recycle(Hook::sync_fn(move |_, metrics| {
+                match metrics.age() < lifetime {
+                    true => Ok(()),
+                    false => Err(HookError::message("statement cache expired")),
+                }
+            }));
+        }
 
-        r2d2::Pool::builder().test_on_check_out(true).build(manager)
+        builder.build().map_err(|e| {
+            Error::new(
+                Some(e.to_string()),
+                Some("Failed to create the connection pool!".to_string()),
+                ErrorTypes::DbError,
+            )
+        })
     }
 }
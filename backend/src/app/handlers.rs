@@ -1,52 +1,191 @@
-use actix_web::{get, post, web, HttpResponse};
+use actix_web::{delete, get, patch, post, web, HttpResponse};
 
-use common::{ApiResponse, PostsFormData, PostsUpdateForm};
-
-use crate::{app::AppState, auth::JwtMiddleware, db::db_get_posts};
-use crate::{
-    db::{db_add_post, db_update_post},
-    errors,
-    schema::post::NewPost,
+use common::{
+    ApiResponse, Permission, PostEvent, PostEventKind, PostsFormData, PostsUpdateForm,
+    WorkspaceRole,
 };
+
+use crate::attachments::delete_unused_blobs;
+use crate::audit::{Audit, AuditAction};
+use crate::db::Post;
+use crate::events::{self, Recipient};
+use crate::permissions::post_access;
+use crate::workspaces::{workspace_access, workspace_post};
+use crate::{app::AppState, auth::JwtMiddleware};
+use crate::{errors, schema::post::NewPost};
 use tracing::instrument;
 
-#[get("/posts")]
+#[get("/workspaces/{workspace_id}/posts")]
 #[instrument(skip_all, name = "Get all posts")]
 async fn get_posts(
     state: web::Data<AppState>,
     user: JwtMiddleware,
+    path: web::Path<uuid::Uuid>,
 ) -> Result<HttpResponse, errors::Error> {
-    let connection = state.connection.clone();
-
-    let db_posts = web::block(move || db_get_posts(user.user_id, &connection)).await??;
+    let workspace_id = path.into_inner();
+    workspace_access(&state, workspace_id, user.user_id, WorkspaceRole::Member).await?;
+    let db_posts = state.notes.get_posts(user.user_id, workspace_id).await?;
     Ok(HttpResponse::Ok().json(db_posts))
 }
 
-#[post("/posts")]
+#[post("/workspaces/{workspace_id}/posts")]
 #[instrument(skip_all, name = "Create new post")]
 async fn add_post(
     state: web::Data<AppState>,
     auth: JwtMiddleware,
+    audit: Audit,
+    path: web::Path<uuid::Uuid>,
     data: web::Json<PostsFormData>,
 ) -> Result<HttpResponse, errors::Error> {
-    let new_post = NewPost::parse(&data.title, &data.description, auth.user_id)?.build();
-    let connection = state.connection.clone();
-
-    let db_posts = web::block(move || db_add_post(new_post, &connection)).await??;
+    let workspace_id = path.into_inner();
+    workspace_access(&state, workspace_id, auth.user_id, WorkspaceRole::Member).await?;
+    let new_post =
+        NewPost::parse(&data.title, &data.description, auth.user_id, workspace_id)?.build();
+    let db_posts = state.notes.add_post(new_post).await?;
+    audit
+        .record(
+            AuditAction::PostCreate,
+            Some(auth.user_id),
+            None,
+            Some(db_posts.id),
+            true,
+        )
+        .await;
+    events::post_changed(&state, PostEventKind::Created, &db_posts).await;
     Ok(HttpResponse::Ok().json(db_posts))
 }
 
-#[post("/posts/update")]
+#[post("/workspaces/{workspace_id}/posts/update")]
 #[instrument(skip_all, name = "Update post's important field")]
 async fn update_posts(
     state: web::Data<AppState>,
     user: JwtMiddleware,
+    audit: Audit,
+    path: web::Path<uuid::Uuid>,
     data: web::Json<PostsUpdateForm>,
 ) -> Result<HttpResponse, errors::Error> {
-    let update_data = data.clone();
-    let connection = state.connection.clone();
+    let post_id = data.id;
+    let important = data.important;
+    let workspace = workspace_access(
+        &state,
+        path.into_inner(),
+        user.user_id,
+        WorkspaceRole::Member,
+    )
+    .await?;
+    workspace_post(&state, &workspace, post_id, user.user_id).await?;
+    state
+        .notes
+        .update_post(user.user_id, data.into_inner())
+        .await?;
+    audit
+        .record(
+            AuditAction::PostImportant,
+            Some(user.user_id),
+            None,
+            Some(post_id),
+            true,
+        )
+        .await;
+    //the important flag is the user's own, only their other tabs get the event
+    let event = PostEvent {
+        kind: PostEventKind::ImportantChanged,
+        post_id,
+        workspace_id: workspace.id,
+        post: None,
+        important: Some(important),
+    };
+    let recipient = Recipient {
+        user_id: user.user_id,
+        permission: Permission::Viewer,
+    };
+    state.events.publish(event, vec![recipient]).await;
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_owned(),
+    }))
+}
+
+#[patch("/workspaces/{workspace_id}/posts/{id}")]
+#[instrument(skip(state, auth, audit, data), name = "Edit post")]
+async fn edit_post(
+    state: web::Data<AppState>,
+    auth: JwtMiddleware,
+    audit: Audit,
+    path: web::Path<(uuid::Uuid, uuid::Uuid)>,
+    data: web::Json<PostsFormData>,
+) -> Result<HttpResponse, errors::Error> {
+    let (workspace_id, id) = path.into_inner();
+    let workspace =
+        workspace_access(&state, workspace_id, auth.user_id, WorkspaceRole::Member).await?;
+    workspace_post(&state, &workspace, id, auth.user_id).await?;
+    //only the title and the description are changed, the post stays in its workspace
+    let post = Post {
+        id,
+        ..NewPost::parse(&data.title, &data.description, auth.user_id, workspace_id)?.build()
+    };
+    let post_id = post.id;
+    let edited = state.notes.edit_post(auth.user_id, post).await?;
+    state
+        .collab
+        .description_changed(post_id, edited.description.clone())
+        .await;
+    audit
+        .record(
+            AuditAction::PostUpdate,
+            Some(auth.user_id),
+            None,
+            Some(post_id),
+            true,
+        )
+        .await;
+    events::post_changed(&state, PostEventKind::Updated, &edited).await;
+    Ok(HttpResponse::Ok().json(edited))
+}
+
+#[delete("/workspaces/{workspace_id}/posts/{id}")]
+#[instrument(skip(state, auth, audit), name = "Delete post")]
+async fn delete_post(
+    state: web::Data<AppState>,
+    auth: JwtMiddleware,
+    audit: Audit,
+    path: web::Path<(uuid::Uuid, uuid::Uuid)>,
+) -> Result<HttpResponse, errors::Error> {
+    let (workspace_id, id) = path.into_inner();
+    let workspace =
+        workspace_access(&state, workspace_id, auth.user_id, WorkspaceRole::Member).await?;
+    workspace_post(&state, &workspace, id, auth.user_id).await?;
+    post_access(&state, id, auth.user_id, Permission::Owner).await?;
+
+    //the users who saw the post are not known any more after it is deleted
+    let post = state.notes.find_post(id).await?;
+    let recipients = events::recipients(&state, id, post.user_id, post.workspace_id).await?;
+    let hashes = state
+        .attachments
+        .get_post_attachments(id)
+        .await?
+        .into_iter()
+        .map(|attachment| attachment.hash)
+        .collect();
+    state.notes.delete_post(id).await?;
+    delete_unused_blobs(&state, hashes).await;
+    audit
+        .record(
+            AuditAction::PostDelete,
+            Some(auth.user_id),
+            None,
+            Some(id),
+            true,
+        )
+        .await;
+    let event = PostEvent {
+        kind: PostEventKind::Deleted,
+        post_id: id,
+        workspace_id: post.workspace_id,
+        post: None,
+        important: None,
+    };
+    state.events.publish(event, recipients).await;
 
-    web::block(move || db_update_post(user.user_id, update_data, &connection)).await??;
     Ok(HttpResponse::Ok().json(ApiResponse {
         status: "success".to_owned(),
     }))
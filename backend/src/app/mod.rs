@@ -7,5 +7,7 @@ pub use configuration::*;
 pub fn config(cfg: &mut web::ServiceConfig) {
     cfg.service(handlers::get_posts)
         .service(handlers::add_post)
-        .service(handlers::update_posts);
+        .service(handlers::update_posts)
+        .service(handlers::edit_post)
+        .service(handlers::delete_post);
 }
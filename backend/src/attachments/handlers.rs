@@ -0,0 +1,344 @@
+use actix_multipart::form::MultipartForm;
+use actix_web::http::header::{
+    self, ByteRangeSpec, Charset, ContentDisposition, ContentRange, ContentRangeSpec,
+    DispositionParam, DispositionType, EntityTag, ExtendedValue, Header, IfNoneMatch, IfRange,
+    Range,
+};
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse};
+use common::{ApiResponse, Permission, PostEventKind, ResponseAttachment, WorkspaceRole};
+use time::OffsetDateTime;
+use tracing::instrument;
+
+use super::{
+    delete_unused_blobs, embed_uploads, is_inline, owned_attachment, owned_post, prepare_upload,
+    visible_attachment, AttachmentForm, DownloadQuery, ImageSize,
+};
+use crate::audit::{Audit, AuditAction};
+use crate::db::Attachment;
+use crate::errors;
+use crate::events;
+use crate::permissions::post_access;
+use crate::workspaces::{workspace_access, workspace_post};
+use crate::{app::AppState, auth::JwtMiddleware};
+
+//a year, the longest `max-age` that is advised
+const IMMUTABLE_MAX_AGE: u32 = 365 * 24 * 60 * 60;
+
+#[post("/workspaces/{workspace_id}/posts/{id}/attachments")]
+#[instrument(skip(state, auth, audit, form), name = "Add attachments")]
+async fn add_attachments(
+    state: web::Data<AppState>,
+    auth: JwtMiddleware,
+    audit: Audit,
+    path: web::Path<(uuid::Uuid, uuid::Uuid)>,
+    form: Result<MultipartForm<AttachmentForm>, actix_web::Error>,
+) -> Result<HttpResponse, errors::Error> {
+    let MultipartForm(form) = form.map_err(errors::multipart_error)?;
+    let (workspace_id, post_id) = path.into_inner();
+    let workspace =
+        workspace_access(&state, workspace_id, auth.user_id, WorkspaceRole::Member).await?;
+    workspace_post(&state, &workspace, post_id, auth.user_id).await?;
+    let post = owned_post(&state, post_id, auth.user_id).await?;
+    if form.files.is_empty() {
+        return Err(errors::Error::validation(vec![errors::FieldError::new(
+            "file",
+            "attachment.no_files",
+        )]));
+    }
+    let max_file_size = state.attachment_settings.max_file_size();
+    if form
+        .files
+        .iter()
+        .any(|file| file.data.len() > max_file_size)
+    {
+        return Err(errors::Error::from_code(
+            "attachment.too_large",
+            errors::ErrorTypes::PayloadTooLarge,
+        ));
+    }
+
+    //hashing large files and processing images do not block the workers
+    let uploads = web::block(move || {
+        form.files
+            .into_iter()
+            .map(prepare_upload)
+            .collect::<Result<Vec<_>, _>>()
+    })
+    .await??;
+
+    let mut added = Vec::new();
+    for upload in uploads {
+        let hash = upload.hash;
+        let attachment = Attachment {
+            id: uuid::Uuid::new_v4(),
+            post_id: post.id,
+            user_id: auth.user_id,
+            file_name: upload.file_name,
+            content_type: upload.content_type,
+            size: upload.data.len() as i64,
+            hash: hash.clone(),
+            created_at: OffsetDateTime::now_utc(),
+        };
+        //the content is stored first and the lock keeps the cleanup of an earlier attachment with the
+        //same content from deleting it before the record is added
+        let lock = state.blob_locks.lock(&hash).await;
+        for (size, data) in upload.variants {
+            state.blobs.put(&size.blob_hash(&hash), data).await?;
+        }
+        state.blobs.put(&hash, upload.data).await?;
+        let attachment = match state
+            .attachments
+            .add_attachment(attachment, state.attachment_settings.quota())
+            .await
+        {
+            Ok(attachment) => attachment,
+            Err(e) => {
+                drop(lock);
+                delete_unused_blobs(&state, vec![hash]).await;
+                return Err(e);
+            }
+        };
+        drop(lock);
+        audit
+            .record(
+                AuditAction::AttachmentAdd,
+                Some(auth.user_id),
+                None,
+                Some(attachment.id),
+                true,
+            )
+            .await;
+        added.push(attachment);
+    }
+
+    //read again, the description may have been edited during the upload
+    let current = state.notes.find_post(post.id).await?;
+    if let Some(description) = current
+        .description
+        .as_deref()
+        .and_then(|description| embed_uploads(description, &added))
+    {
+        state
+            .notes
+            .update_description(post.id, Some(description.clone()))
+            .await?;
+        state
+            .collab
+            .description_changed(post.id, Some(description))
+            .await;
+        events::stored_post_changed(&state, PostEventKind::Updated, post.id).await;
+    }
+
+    let added: Vec<ResponseAttachment> = added.into_iter().map(ResponseAttachment::from).collect();
+    Ok(HttpResponse::Ok().json(added))
+}
+
+#[get("/workspaces/{workspace_id}/posts/{id}/attachments")]
+#[instrument(skip(state, auth), name = "Get attachments")]
+async fn get_attachments(
+    state: web::Data<AppState>,
+    auth: JwtMiddleware,
+    path: web::Path<(uuid::Uuid, uuid::Uuid)>,
+) -> Result<HttpResponse, errors::Error> {
+    let (workspace_id, post_id) = path.into_inner();
+    let workspace =
+        workspace_access(&state, workspace_id, auth.user_id, WorkspaceRole::Member).await?;
+    workspace_post(&state, &workspace, post_id, auth.user_id).await?;
+    post_access(&state, post_id, auth.user_id, Permission::Viewer).await?;
+    let attachments: Vec<ResponseAttachment> = state
+        .attachments
+        .get_post_attachments(post_id)
+        .await?
+        .into_iter()
+        .map(ResponseAttachment::from)
+        .collect();
+
+    Ok(HttpResponse::Ok().json(attachments))
+}
+
+#[get("/attachments/{id}")]
+#[instrument(skip(state, auth, req), name = "Download attachment")]
+async fn download_attachment(
+    state: web::Data<AppState>,
+    auth: JwtMiddleware,
+    req: HttpRequest,
+    path: web::Path<uuid::Uuid>,
+    query: web::Query<DownloadQuery>,
+) -> Result<HttpResponse, errors::Error> {
+    let attachment = visible_attachment(&state, path.into_inner(), auth.user_id).await?;
+    let (hash, content_type, size) = served_content(&state, &attachment, query.size).await?;
+    //the content never changes, its hash is a strong validator
+    let etag = EntityTag::new_strong(hash.clone());
+
+    if is_not_modified(&req, &etag) {
+        return Ok(HttpResponse::NotModified()
+            .insert_header(header::ETag(etag))
+            .finish());
+    }
+
+    let (mut response, range) = match requested_range(&req, &etag, size) {
+        Requested::Unsatisfiable => {
+            return Ok(HttpResponse::RangeNotSatisfiable()
+                .insert_header(ContentRange(ContentRangeSpec::Bytes {
+                    range: None,
+                    instance_length: Some(size),
+                }))
+                .finish())
+        }
+        Requested::Full => (HttpResponse::Ok(), 0..size),
+        Requested::Part(start, end) => {
+            let mut response = HttpResponse::PartialContent();
+            response.insert_header(ContentRange(ContentRangeSpec::Bytes {
+                range: Some((start, end)),
+                instance_length: Some(size),
+            }));
+            (response, start..end + 1)
+        }
+    };
+    let data = state.blobs.get(&hash, range).await?;
+
+    Ok(response
+        .insert_header((header::CONTENT_TYPE, content_type))
+        .insert_header(content_disposition(&attachment))
+        .insert_header(header::ETag(etag))
+        .insert_header((header::ACCEPT_RANGES, "bytes"))
+        .insert_header((header::X_CONTENT_TYPE_OPTIONS, "nosniff"))
+        //the content of an attachment id never changes, the browser does not ask again
+        .insert_header(header::CacheControl(vec![
+            header::CacheDirective::Private,
+            header::CacheDirective::MaxAge(IMMUTABLE_MAX_AGE),
+            header::CacheDirective::Extension("immutable".to_string(), None),
+        ]))
+        .body(data))
+}
+
+#[delete("/attachments/{id}")]
+#[instrument(skip(state, auth, audit), name = "Delete attachment")]
+async fn delete_attachment(
+    state: web::Data<AppState>,
+    auth: JwtMiddleware,
+    audit: Audit,
+    path: web::Path<uuid::Uuid>,
+) -> Result<HttpResponse, errors::Error> {
+    let attachment = owned_attachment(&state, path.into_inner(), auth.user_id).await?;
+    state.attachments.delete_attachment(attachment.id).await?;
+    delete_unused_blobs(&state, vec![attachment.hash]).await;
+    audit
+        .record(
+            AuditAction::AttachmentDelete,
+            Some(auth.user_id),
+            None,
+            Some(attachment.id),
+            true,
+        )
+        .await;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_owned(),
+    }))
+}
+
+/// The function `served_content` chooses the content a download sends: the requested size of an
+/// image, or the original if the image is not larger than that size or the attachment is not an
+/// image.
+///
+/// Returns:
+///
+/// the key of the content in the `BlobStore`, its MIME type and its size in bytes.
+async fn served_content(
+    state: &AppState,
+    attachment: &Attachment,
+    size: ImageSize,
+) -> Result<(String, String, u64), errors::Error> {
+    if size != ImageSize::Original && attachment.content_type.starts_with("image/") {
+        let hash = size.blob_hash(&attachment.hash);
+        if let Some(length) = state.blobs.size(&hash).await? {
+            return Ok((hash, size.content_type(&attachment.content_type), length));
+        }
+    }
+
+    Ok((
+        attachment.hash.clone(),
+        attachment.content_type.clone(),
+        attachment.size as u64,
+    ))
+}
+
+/// The `Requested` enum is the part of a file a download asks for.
+enum Requested {
+    Full,
+    //the first and the last byte, inclusive like in the `Content-Range` header
+    Part(u64, u64),
+    Unsatisfiable,
+}
+
+/// The function `requested_range` reads the `Range` header of a download. Only single ranges are
+/// served, a request for many ranges gets the whole file. The range is ignored if the `If-Range`
+/// header does not match the `ETag`.
+///
+/// Arguments:
+///
+/// * `req`: The `req` parameter is the download request.
+/// * `etag`: The `etag` parameter is the `ETag` of the file.
+/// * `size`: The `size` parameter is the size of the file in bytes.
+fn requested_range(req: &HttpRequest, etag: &EntityTag, size: u64) -> Requested {
+    let Ok(Range::Bytes(ranges)) = Range::parse(req) else {
+        return Requested::Full;
+    };
+    if req.headers().contains_key(header::IF_RANGE) {
+        match IfRange::parse(req) {
+            Ok(IfRange::EntityTag(tag)) if tag.strong_eq(etag) => {}
+            _ => return Requested::Full,
+        }
+    }
+
+    match ranges.as_slice() {
+        [range] => single_range(range, size),
+        _ => Requested::Full,
+    }
+}
+
+fn single_range(range: &ByteRangeSpec, size: u64) -> Requested {
+    match range.to_satisfiable_range(size) {
+        Some((start, end)) => Requested::Part(start, end),
+        None => Requested::Unsatisfiable,
+    }
+}
+
+//`If-None-Match` with the `ETag` of the file, or `*`
+fn is_not_modified(req: &HttpRequest, etag: &EntityTag) -> bool {
+    match IfNoneMatch::parse(req) {
+        Ok(IfNoneMatch::Any) => true,
+        Ok(IfNoneMatch::Items(tags)) => tags.iter().any(|tag| tag.weak_eq(etag)),
+        Err(_) => false,
+    }
+}
+
+/// The function `content_disposition` returns the `Content-Disposition` header of a download:
+/// `inline` for the types the browser can show safely, `attachment` for the others. The name is sent
+/// as ASCII and as UTF-8, for the clients that do not read the UTF-8 one.
+fn content_disposition(attachment: &Attachment) -> ContentDisposition {
+    let ascii_name: String = attachment
+        .file_name
+        .chars()
+        .map(|c| match c.is_ascii() && !c.is_ascii_control() {
+            true => c,
+            false => '_',
+        })
+        .collect();
+
+    ContentDisposition {
+        disposition: match is_inline(&attachment.content_type) {
+            true => DispositionType::Inline,
+            false => DispositionType::Attachment,
+        },
+        parameters: vec![
+            DispositionParam::Filename(ascii_name),
+            DispositionParam::FilenameExt(ExtendedValue {
+                charset: Charset::Ext("UTF-8".to_string()),
+                language_tag: None,
+                value: attachment.file_name.as_bytes().to_vec(),
+            }),
+        ],
+    }
+}
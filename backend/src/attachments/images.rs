@@ -0,0 +1,202 @@
+use std::io::Cursor;
+
+use actix_web::web::Bytes;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::error::ImageError;
+use image::metadata::Orientation;
+use image::{DynamicImage, ImageDecoder, ImageEncoder, ImageFormat, ImageReader, Limits};
+use serde::Deserialize;
+
+use super::content_hash;
+use crate::errors;
+
+//the widest and the tallest image that is decoded, larger ones are refused before their pixels are
+//allocated
+const MAX_DIMENSION: u32 = 12_000;
+const MAX_ALLOC: u64 = 512 * 1024 * 1024;
+const JPEG_QUALITY: u8 = 90;
+
+/// The `ImageSize` enum is a size an image attachment is served in, the `size` query parameter of
+/// `GET /attachments/{id}`.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageSize {
+    Thumbnail,
+    Medium,
+    #[default]
+    Original,
+}
+
+impl ImageSize {
+    /// The sizes that are made when an image is uploaded.
+    pub const VARIANTS: [ImageSize; 2] = [ImageSize::Thumbnail, ImageSize::Medium];
+
+    //the longest edge of the size in pixels
+    fn max_edge(self) -> Option<u32> {
+        match self {
+            ImageSize::Thumbnail => Some(320),
+            ImageSize::Medium => Some(1280),
+            ImageSize::Original => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            ImageSize::Thumbnail => "thumbnail",
+            ImageSize::Medium => "medium",
+            ImageSize::Original => "original",
+        }
+    }
+
+    /// The function `blob_hash` returns the key of the size in the `BlobStore`. It is derived from the
+    /// hash of the original, so the same image uploaded twice shares its smaller sizes too.
+    ///
+    /// Arguments:
+    ///
+    /// * `hash`: The `hash` parameter is the hash of the original content.
+    pub fn blob_hash(self, hash: &str) -> String {
+        match self {
+            ImageSize::Original => hash.to_string(),
+            size => content_hash(format!("{}:{}", hash, size.as_str()).as_bytes()),
+        }
+    }
+
+    /// The function `content_type` returns the MIME type of the size of an image: the smaller sizes of
+    /// a JPEG are JPEGs, the ones of the other formats are PNGs, to keep their transparency.
+    ///
+    /// Arguments:
+    ///
+    /// * `original`: The `original` parameter is the MIME type of the uploaded image.
+    pub fn content_type(self, original: &str) -> String {
+        match self {
+            ImageSize::Original => original.to_string(),
+            _ if original == ImageFormat::Jpeg.to_mime_type() => original.to_string(),
+            _ => ImageFormat::Png.to_mime_type().to_string(),
+        }
+    }
+}
+
+/// The `ProcessedImage` struct is an uploaded image ready to be stored.
+///
+/// Properties:
+///
+/// * `data`: The `data` property is the image without its metadata, turned as the EXIF orientation
+/// says.
+/// * `content_type`: The `content_type` property is the MIME type of the detected format.
+/// * `variants`: The `variants` property holds the smaller sizes. A size is left out if the image is
+/// not larger than it, the original is served instead.
+pub struct ProcessedImage {
+    pub data: Bytes,
+    pub content_type: String,
+    pub variants: Vec<(ImageSize, Bytes)>,
+}
+
+/// The function `image_format` detects an image that is processed on upload from its first bytes,
+/// the MIME type sent by the browser is not trusted. Returns `None` for the other files.
+pub fn image_format(data: &[u8]) -> Option<ImageFormat> {
+    match image::guess_format(data) {
+        Ok(
+            format @ (ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::Gif | ImageFormat::WebP),
+        ) => Some(format),
+        _ => None,
+    }
+}
+
+/// The function `process_image` strips the metadata of an uploaded image and makes its smaller
+/// sizes. The pixels are decoded and encoded again, so nothing of the original file but the image
+/// and its color profile is kept, EXIF with the GPS position of a photo included. GIFs are kept as
+/// they are, to keep their animation, they have no EXIF. It is CPU heavy and runs in `web::block`.
+///
+/// Arguments:
+///
+/// * `data`: The `data` parameter is the uploaded file.
+/// * `format`: The `format` parameter is the format detected by `image_format`.
+///
+/// Returns:
+///
+/// the `ProcessedImage`, `attachment.invalid_image` if the file can not be decoded or
+/// `attachment.image_too_large` if it is larger than 12000 pixels.
+pub fn process_image(data: Bytes, format: ImageFormat) -> Result<ProcessedImage, errors::Error> {
+    let mut reader = ImageReader::with_format(Cursor::new(&data[..]), format);
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_DIMENSION);
+    limits.max_image_height = Some(MAX_DIMENSION);
+    limits.max_alloc = Some(MAX_ALLOC);
+    reader.limits(limits);
+
+    let mut decoder = reader.into_decoder().map_err(image_error)?;
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    let icc_profile = decoder.icc_profile().ok().flatten();
+    let mut image = DynamicImage::from_decoder(decoder).map_err(image_error)?;
+    image.apply_orientation(orientation);
+
+    let stored = match format {
+        ImageFormat::Gif => data,
+        _ => encode(&image, format, icc_profile)?,
+    };
+    let variant_format = match format {
+        ImageFormat::Jpeg => ImageFormat::Jpeg,
+        _ => ImageFormat::Png,
+    };
+    let variants = ImageSize::VARIANTS
+        .iter()
+        .filter_map(|size| {
+            let edge = size.max_edge()?;
+            (image.width() > edge || image.height() > edge)
+                .then(|| encode(&image.thumbnail(edge, edge), variant_format, None))
+                .map(|variant| variant.map(|variant| (*size, variant)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(ProcessedImage {
+        data: stored,
+        content_type: format.to_mime_type().to_string(),
+        variants,
+    })
+}
+
+//encodes the pixels without any metadata but the color profile
+fn encode(
+    image: &DynamicImage,
+    format: ImageFormat,
+    icc_profile: Option<Vec<u8>>,
+) -> Result<Bytes, errors::Error> {
+    let mut data = Vec::new();
+    let written = match format {
+        ImageFormat::Jpeg => {
+            let mut encoder = JpegEncoder::new_with_quality(&mut data, JPEG_QUALITY);
+            if let Some(icc_profile) = icc_profile {
+                if encoder.set_icc_profile(icc_profile).is_err() {
+                    tracing::warn!("The color profile of the image is not kept!");
+                }
+            }
+            DynamicImage::ImageRgb8(image.to_rgb8()).write_with_encoder(encoder)
+        }
+        ImageFormat::WebP => DynamicImage::ImageRgba8(image.to_rgba8())
+            .write_with_encoder(WebPEncoder::new_lossless(&mut data)),
+        _ => {
+            let mut encoder = PngEncoder::new(&mut data);
+            if let Some(icc_profile) = icc_profile {
+                if encoder.set_icc_profile(icc_profile).is_err() {
+                    tracing::warn!("The color profile of the image is not kept!");
+                }
+            }
+            image.write_with_encoder(encoder)
+        }
+    };
+
+    written.map(|_| Bytes::from(data)).map_err(|e| {
+        tracing::error!("Failed to encode the image!");
+        errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::InternalError)
+    })
+}
+
+fn image_error(error: ImageError) -> errors::Error {
+    let code = match error {
+        ImageError::Limits(_) => "attachment.image_too_large",
+        _ => "attachment.invalid_image",
+    };
+    errors::Error::validation(vec![errors::FieldError::new("file", code)]).with_cause(error)
+}
@@ -0,0 +1,35 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+
+/// The `BlobLocks` struct serializes the work on the content of one hash. An upload holds the lock
+/// from storing the content until its record is added, the cleanup holds it from checking that no
+/// record uses the hash until the content is deleted, so the cleanup can not delete the content of an
+/// upload that is still being added. The locks are kept by this process only.
+#[derive(Default)]
+pub struct BlobLocks {
+    locks: Mutex<HashMap<String, Arc<AsyncMutex<()>>>>,
+}
+
+impl BlobLocks {
+    /// The function `lock` waits until nobody else works on the content of the hash.
+    ///
+    /// Arguments:
+    ///
+    /// * `hash`: The `hash` parameter is the hex SHA-256 hash of the content.
+    ///
+    /// Returns:
+    ///
+    /// a guard, the lock is held until it is dropped.
+    pub async fn lock(&self, hash: &str) -> OwnedMutexGuard<()> {
+        let lock = {
+            //the locks stay usable after a panic of another request
+            let mut locks = self.locks.lock().unwrap_or_else(|e| e.into_inner());
+            //a lock that nobody holds or waits for is not needed any more
+            locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+            locks.entry(hash.to_string()).or_default().clone()
+        };
+        lock.lock_owned().await
+    }
+}
@@ -0,0 +1,278 @@
+pub mod handlers;
+pub mod images;
+pub mod locks;
+pub mod store;
+
+use actix_multipart::form::{bytes::Bytes, MultipartForm};
+use actix_web::web;
+pub use images::*;
+pub use locks::*;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+pub use store::*;
+
+use crate::app::AppState;
+use crate::db::{attachment_not_found, Attachment, Post};
+use crate::errors;
+
+//the longest file name, the length of the `attachments.file_name` column
+const MAX_FILE_NAME: usize = 255;
+//the MIME types that are shown in the browser, everything else is downloaded
+const INLINE_TYPES: [&str; 6] = [
+    "image/png",
+    "image/jpeg",
+    "image/gif",
+    "image/webp",
+    "application/pdf",
+    "text/plain",
+];
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(handlers::add_attachments)
+        .service(handlers::get_attachments)
+        .service(handlers::download_attachment)
+        .service(handlers::delete_attachment);
+}
+
+/// The `AttachmentForm` struct is the multipart body of `POST /posts/{id}/attachments`: one or more
+/// `file` fields.
+#[derive(MultipartForm, Debug)]
+pub struct AttachmentForm {
+    #[multipart(rename = "file")]
+    pub files: Vec<Bytes>,
+}
+
+/// The `DownloadQuery` struct is the query of `GET /attachments/{id}`.
+///
+/// Properties:
+///
+/// * `size`: The `size` property is the size of an image, `thumbnail`, `medium` or `original`, the
+/// default. Other files are always sent as they are.
+#[derive(Deserialize, Debug)]
+pub struct DownloadQuery {
+    #[serde(default)]
+    pub size: ImageSize,
+}
+
+/// The `Upload` struct is an uploaded file ready to be stored.
+///
+/// Properties:
+///
+/// * `file_name`: The `file_name` property is the sanitized name of the file.
+/// * `content_type`: The `content_type` property is the MIME type, the detected one for images.
+/// * `hash`: The `hash` property is the hash of `data`.
+/// * `data`: The `data` property is the content to store, images without their metadata.
+/// * `variants`: The `variants` property holds the smaller sizes of an image.
+pub struct Upload {
+    pub file_name: String,
+    pub content_type: String,
+    pub hash: String,
+    pub data: web::Bytes,
+    pub variants: Vec<(ImageSize, web::Bytes)>,
+}
+
+/// The function `prepare_upload` hashes an uploaded file. Images are processed first, by
+/// `process_image`. It is CPU heavy and runs in `web::block`.
+pub fn prepare_upload(file: Bytes) -> Result<Upload, errors::Error> {
+    let file_name = file_name(file.file_name.as_deref());
+    let (data, content_type, variants) = match image_format(&file.data) {
+        Some(format) => {
+            let image = process_image(file.data, format)?;
+            (image.data, image.content_type, image.variants)
+        }
+        None => {
+            let content_type = content_type(&file);
+            (file.data, content_type, Vec::new())
+        }
+    };
+
+    Ok(Upload {
+        file_name,
+        content_type,
+        hash: content_hash(&data),
+        data,
+        variants,
+    })
+}
+
+/// The function `embed_uploads` embeds uploaded images in the description of their post. The editor
+/// inserts `![name](upload:<file name>)` where an image is pasted, before it is uploaded, and the
+/// placeholder becomes the address of the attachment with that name.
+///
+/// Arguments:
+///
+/// * `description`: The `description` parameter is the description of the post.
+/// * `added`: The `added` parameter lists the attachments that were just uploaded.
+///
+/// Returns:
+///
+/// the new description, or `None` if there was no placeholder of the attachments.
+pub fn embed_uploads(description: &str, added: &[Attachment]) -> Option<String> {
+    let mut embedded = description.to_string();
+    for attachment in added {
+        embedded = embedded.replace(
+            &format!("](upload:{})", attachment.file_name),
+            &format!("](/attachments/{})", attachment.id),
+        );
+    }
+
+    (embedded != description).then_some(embedded)
+}
+
+/// The function `content_hash` returns the hex SHA-256 hash of the content, its key in the
+/// `BlobStore`.
+pub fn content_hash(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// The function `file_name` returns the name to store for an uploaded file: without the folders some
+/// browsers send, without control characters and at most 255 characters long.
+///
+/// Arguments:
+///
+/// * `name`: The `name` parameter is the file name of the multipart field, if it has one.
+pub fn file_name(name: Option<&str>) -> String {
+    let name: String = name
+        .unwrap_or_default()
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_FILE_NAME)
+        .collect();
+
+    match name.trim().is_empty() {
+        true => "attachment".to_string(),
+        false => name,
+    }
+}
+
+/// The function `content_type` returns the MIME type to store for an uploaded file, the one of the
+/// multipart field or `application/octet-stream`.
+pub fn content_type(file: &Bytes) -> String {
+    file.content_type
+        .as_ref()
+        .map(|mime| mime.to_string())
+        .filter(|mime| mime.len() <= MAX_FILE_NAME)
+        .unwrap_or_else(|| "application/octet-stream".to_string())
+}
+
+/// The function `is_inline` checks if a file is shown in the browser instead of downloaded. Only
+/// images, PDFs and plain text are, a HTML or SVG file could run scripts on the site.
+pub fn is_inline(content_type: &str) -> bool {
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
+    INLINE_TYPES.contains(&essence.as_str())
+}
+
+/// The function `owned_post` finds a post the user can attach files to, their own one.
+///
+/// Arguments:
+///
+/// * `state`: The `state` parameter is the app state with the repositories.
+/// * `post_id`: The `post_id` parameter is the id of the post.
+/// * `user_id`: The `user_id` parameter is the logged in user.
+///
+/// Returns:
+///
+/// the post, `post.not_found` if there is none, or `auth.forbidden` if it belongs to another user.
+pub async fn owned_post(
+    state: &AppState,
+    post_id: uuid::Uuid,
+    user_id: uuid::Uuid,
+) -> Result<Post, errors::Error> {
+    let post = state.notes.find_post(post_id).await?;
+    if post.user_id != user_id {
+        tracing::warn!("User {} can not change post {}", user_id, post_id);
+        return Err(errors::Error::from_code(
+            "auth.forbidden",
+            errors::ErrorTypes::Auth(errors::Auth::Authorization),
+        ));
+    }
+    Ok(post)
+}
+
+/// The function `owned_attachment` finds an attachment of the user. The attachments of other users
+/// are not found, so their ids can not be probed.
+///
+/// Arguments:
+///
+/// * `state`: The `state` parameter is the app state with the repositories.
+/// * `attachment_id`: The `attachment_id` parameter is the id of the attachment.
+/// * `user_id`: The `user_id` parameter is the logged in user.
+pub async fn owned_attachment(
+    state: &AppState,
+    attachment_id: uuid::Uuid,
+    user_id: uuid::Uuid,
+) -> Result<Attachment, errors::Error> {
+    let attachment = state.attachments.find_attachment(attachment_id).await?;
+    if attachment.user_id != user_id {
+        return Err(attachment_not_found(attachment_id));
+    }
+    Ok(attachment)
+}
+
+/// The function `visible_attachment` finds an attachment of a post the user can see, their own one or
+/// one shared with them. The other attachments are not found, like in `owned_attachment`.
+///
+/// Arguments:
+///
+/// * `state`: The `state` parameter is the app state with the repositories.
+/// * `attachment_id`: The `attachment_id` parameter is the id of the attachment.
+/// * `user_id`: The `user_id` parameter is the logged in user.
+pub async fn visible_attachment(
+    state: &AppState,
+    attachment_id: uuid::Uuid,
+    user_id: uuid::Uuid,
+) -> Result<Attachment, errors::Error> {
+    let attachment = state.attachments.find_attachment(attachment_id).await?;
+    if attachment.user_id != user_id
+        && state
+            .permissions
+            .get_permission(attachment.post_id, user_id)
+            .await?
+            .is_none()
+    {
+        return Err(attachment_not_found(attachment_id));
+    }
+    Ok(attachment)
+}
+
+/// The function `delete_unused_blobs` deletes the content of the hashes no attachment points to any
+/// more, with the smaller sizes of the images. It runs after the records were deleted, failures are
+/// only logged, the records are gone already. The check and the delete hold the lock of the hash, so
+/// an upload of the same content can not add its record in between.
+///
+/// Arguments:
+///
+/// * `state`: The `state` parameter is the app state with the repositories and the blob store.
+/// * `hashes`: The `hashes` parameter lists the content that may not be needed any more.
+pub async fn delete_unused_blobs(state: &AppState, hashes: Vec<String>) {
+    for hash in hashes {
+        let _lock = state.blob_locks.lock(&hash).await;
+        let deleted = match state.attachments.is_hash_used(&hash).await {
+            Ok(true) => continue,
+            Ok(false) => delete_blobs(state, &hash).await,
+            Err(e) => Err(e),
+        };
+        if deleted.is_err() {
+            tracing::error!("Failed to delete the unused attachment content {}", hash);
+        }
+    }
+}
+
+//deletes the content and its smaller sizes, which exist only for images
+async fn delete_blobs(state: &AppState, hash: &str) -> Result<(), errors::Error> {
+    for size in ImageSize::VARIANTS {
+        state.blobs.delete(&size.blob_hash(hash)).await?;
+    }
+    state.blobs.delete(hash).await
+}
@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::io::{ErrorKind, SeekFrom};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+
+use actix_web::web::Bytes;
+use async_trait::async_trait;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+use crate::errors;
+
+/// The `BlobStore` trait keeps the content of the attachments, addressed by the hex SHA-256 hash of
+/// the content. A file attached twice is stored once, the records of the `AttachmentRepository` decide
+/// when a blob is not needed any more.
+#[async_trait]
+pub trait BlobStore: Send + Sync {
+    /// Stores the content under its hash. Storing a hash that is already there does nothing.
+    async fn put(&self, hash: &str, data: Bytes) -> Result<(), errors::Error>;
+
+    /// Reads the bytes in `range` of the content. Fails with `attachment.not_found` if there is no
+    /// content with the hash.
+    async fn get(&self, hash: &str, range: Range<u64>) -> Result<Bytes, errors::Error>;
+
+    /// Returns the size of the content in bytes, or `None` if there is no content with the hash.
+    async fn size(&self, hash: &str) -> Result<Option<u64>, errors::Error>;
+
+    /// Deletes the content. Deleting a hash that is not there does nothing.
+    async fn delete(&self, hash: &str) -> Result<(), errors::Error>;
+}
+
+/// The function `storage_error` is the error for a blob that can not be written or read.
+fn storage_error(error: std::io::Error, message: &str) -> errors::Error {
+    tracing::error!("{}", message);
+    errors::Error::new(
+        Some(error.to_string()),
+        Some(message.to_string()),
+        errors::ErrorTypes::DbError,
+    )
+}
+
+/// The function `missing_blob` is the error for a record whose content is not in the store.
+fn missing_blob(hash: &str) -> errors::Error {
+    tracing::error!("The content {} of an attachment is missing!", hash);
+    errors::Error::from_code("attachment.not_found", errors::ErrorTypes::NotFound)
+}
+
+/// The `LocalBlobStore` struct stores the blobs as files on the local disk, in two levels of folders
+/// named after the start of the hash: `<directory>/ab/cd/abcd…`.
+///
+/// Properties:
+///
+/// * `root`: The `root` property is the folder of the store. It is created on the first upload.
+pub struct LocalBlobStore {
+    root: PathBuf,
+}
+
+impl LocalBlobStore {
+    pub fn new(directory: &str) -> Self {
+        LocalBlobStore {
+            root: PathBuf::from(directory),
+        }
+    }
+
+    //only a hex hash becomes a path, so a bad record can not point outside of the store
+    fn path(&self, hash: &str) -> Result<PathBuf, errors::Error> {
+        if hash.len() != 64 || !hash.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) {
+            tracing::error!("Invalid attachment hash {}!", hash);
+            return Err(errors::Error::new(
+                Some(format!("Invalid attachment hash {}", hash)),
+                None,
+                errors::ErrorTypes::DbError,
+            ));
+        }
+        Ok(self.root.join(&hash[0..2]).join(&hash[2..4]).join(hash))
+    }
+}
+
+#[async_trait]
+impl BlobStore for LocalBlobStore {
+    async fn put(&self, hash: &str, data: Bytes) -> Result<(), errors::Error> {
+        let path = self.path(hash)?;
+        if tokio::fs::try_exists(&path).await.unwrap_or(false) {
+            return Ok(());
+        }
+        if let Some(folder) = path.parent() {
+            tokio::fs::create_dir_all(folder)
+                .await
+                .map_err(|e| storage_error(e, "Failed to create the attachment folder!"))?;
+        }
+
+        //written next to the target and renamed, so a blob is never read half written
+        let partial = path.with_extension(format!("{}.partial", uuid::Uuid::new_v4()));
+        tokio::fs::write(&partial, &data)
+            .await
+            .map_err(|e| storage_error(e, "Failed to write the attachment!"))?;
+        if let Err(e) = tokio::fs::rename(&partial, &path).await {
+            let _ = tokio::fs::remove_file(&partial).await;
+            return Err(storage_error(e, "Failed to write the attachment!"));
+        }
+
+        Ok(())
+    }
+
+    async fn get(&self, hash: &str, range: Range<u64>) -> Result<Bytes, errors::Error> {
+        let path = self.path(hash)?;
+        let mut file = match tokio::fs::File::open(&path).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Err(missing_blob(hash)),
+            Err(e) => return Err(storage_error(e, "Failed to read the attachment!")),
+        };
+
+        let mut data = Vec::new();
+        file.seek(SeekFrom::Start(range.start))
+            .await
+            .map_err(|e| storage_error(e, "Failed to read the attachment!"))?;
+        file.take(range.end.saturating_sub(range.start))
+            .read_to_end(&mut data)
+            .await
+            .map_err(|e| storage_error(e, "Failed to read the attachment!"))?;
+
+        Ok(Bytes::from(data))
+    }
+
+    async fn size(&self, hash: &str) -> Result<Option<u64>, errors::Error> {
+        match tokio::fs::metadata(self.path(hash)?).await {
+            Ok(metadata) => Ok(Some(metadata.len())),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(storage_error(e, "Failed to read the attachment!")),
+        }
+    }
+
+    async fn delete(&self, hash: &str) -> Result<(), errors::Error> {
+        match tokio::fs::remove_file(self.path(hash)?).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => {
+                Err(storage_error(e, "Failed to delete the attachment!"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The `MemoryBlobStore` struct keeps the blobs in the process, next to the `memory` database. They
+/// are lost when the process stops.
+#[derive(Default)]
+pub struct MemoryBlobStore {
+    blobs: Mutex<HashMap<String, Bytes>>,
+}
+
+impl MemoryBlobStore {
+    pub fn new() -> Self {
+        MemoryBlobStore::default()
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, HashMap<String, Bytes>>, errors::Error> {
+        self.blobs.lock().map_err(|e| {
+            tracing::error!("In-memory blob store is poisoned!");
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })
+    }
+}
+
+#[async_trait]
+impl BlobStore for MemoryBlobStore {
+    async fn put(&self, hash: &str, data: Bytes) -> Result<(), errors::Error> {
+        self.lock()?.entry(hash.to_string()).or_insert(data);
+        Ok(())
+    }
+
+    async fn get(&self, hash: &str, range: Range<u64>) -> Result<Bytes, errors::Error> {
+        let blobs = self.lock()?;
+        let data = blobs.get(hash).ok_or_else(|| missing_blob(hash))?;
+        let end = (range.end as usize).min(data.len());
+        let start = (range.start as usize).min(end);
+
+        Ok(data.slice(start..end))
+    }
+
+    async fn size(&self, hash: &str) -> Result<Option<u64>, errors::Error> {
+        Ok(self.lock()?.get(hash).map(|data| data.len() as u64))
+    }
+
+    async fn delete(&self, hash: &str) -> Result<(), errors::Error> {
+        self.lock()?.remove(hash);
+        Ok(())
+    }
+}
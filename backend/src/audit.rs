@@ -0,0 +1,240 @@
+use std::future::{ready, Ready};
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix_web::{dev::Payload, http::header, web, FromRequest, HttpRequest};
+use time::OffsetDateTime;
+use tracing::instrument;
+
+use crate::app::{AppState, AuditSettings};
+use crate::db::{AuditEvent, AuditFilter, AuditRepository};
+use crate::errors;
+use crate::tasks::Tasks;
+
+/// The `AuditAction` enum lists the actions that are written to the audit log.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AuditAction {
+    Signup,
+    Login,
+    Logout,
+    Refresh,
+    PostCreate,
+    PostImportant,
+    PostUpdate,
+    PostDelete,
+    PostExport,
+    PostImport,
+    AttachmentAdd,
+    AttachmentDelete,
+    ShareCreate,
+    ShareRevoke,
+    PermissionGrant,
+    PermissionChange,
+    PermissionRevoke,
+    WorkspaceCreate,
+    WorkspaceRename,
+    MemberAdd,
+    MemberChange,
+    MemberRemove,
+    NotebookCreate,
+    NotebookUpdate,
+    NotebookDelete,
+    PostMove,
+    UserDisable,
+    UserEnable,
+    UserLogout,
+    UserRole,
+    UserDelete,
+}
+
+impl AuditAction {
+    /// The function `as_str` returns the name of the action as it is stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::Signup => "auth.signup",
+            AuditAction::Login => "auth.login",
+            AuditAction::Logout => "auth.logout",
+            AuditAction::Refresh => "auth.refresh",
+            AuditAction::PostCreate => "post.create",
+            AuditAction::PostImportant => "post.important",
+            AuditAction::PostUpdate => "post.update",
+            AuditAction::PostDelete => "post.delete",
+            AuditAction::PostExport => "post.export",
+            AuditAction::PostImport => "post.import",
+            AuditAction::AttachmentAdd => "attachment.add",
+            AuditAction::AttachmentDelete => "attachment.delete",
+            AuditAction::ShareCreate => "share.create",
+            AuditAction::ShareRevoke => "share.revoke",
+            AuditAction::PermissionGrant => "permission.grant",
+            AuditAction::PermissionChange => "permission.change",
+            AuditAction::PermissionRevoke => "permission.revoke",
+            AuditAction::WorkspaceCreate => "workspace.create",
+            AuditAction::WorkspaceRename => "workspace.rename",
+            AuditAction::MemberAdd => "workspace.member_add",
+            AuditAction::MemberChange => "workspace.member_change",
+            AuditAction::MemberRemove => "workspace.member_remove",
+            AuditAction::NotebookCreate => "notebook.create",
+            AuditAction::NotebookUpdate => "notebook.update",
+            AuditAction::NotebookDelete => "notebook.delete",
+            AuditAction::PostMove => "post.move",
+            AuditAction::UserDisable => "admin.user_disable",
+            AuditAction::UserEnable => "admin.user_enable",
+            AuditAction::UserLogout => "admin.user_logout",
+            AuditAction::UserRole => "admin.user_role",
+            AuditAction::UserDelete => "admin.user_delete",
+        }
+    }
+}
+
+/// The `AuditLog` struct is the audit log of the app state: the storage and the settings.
+///
+/// Properties:
+///
+/// * `repository`: The `repository` property stores the events.
+/// * `settings`: The `settings` property holds the retention and whether the proxy headers are
+/// trusted for the client address.
+#[derive(Clone)]
+pub struct AuditLog {
+    repository: Arc<dyn AuditRepository>,
+    settings: Arc<AuditSettings>,
+}
+
+impl AuditLog {
+    pub fn new(repository: Arc<dyn AuditRepository>, settings: AuditSettings) -> Self {
+        AuditLog {
+            repository,
+            settings: Arc::new(settings),
+        }
+    }
+
+    /// The function `client_ip` returns the address of the client of a request. It is the peer address,
+    /// or the one of the `Forwarded`/`X-Forwarded-For` headers when `trust_proxy_headers` is set.
+    pub fn client_ip(&self, req: &HttpRequest) -> Option<String> {
+        if self.settings.trust_proxy_headers {
+            req.connection_info()
+                .realip_remote_addr()
+                .map(|ip| truncate(ip, 64))
+        } else {
+            req.peer_addr().map(|addr| addr.ip().to_string())
+        }
+    }
+
+    /// The function `get_events` returns the events that match the filter, newest first.
+    pub async fn get_events(&self, filter: AuditFilter) -> Result<Vec<AuditEvent>, errors::Error> {
+        self.repository.get_events(filter).await
+    }
+
+    /// The function `spawn_retention` starts the background task that deletes the events older than
+    /// `retention_days`. Nothing is started when the retention is 0, the events are kept forever then.
+    ///
+    /// Arguments:
+    ///
+    /// * `tasks`: The `tasks` parameter runs the background task until the shutdown.
+    pub fn spawn_retention(&self, tasks: &Tasks) {
+        if self.settings.retention_days == 0 {
+            tracing::info!("Audit events are kept forever");
+            return;
+        }
+
+        let repository = self.repository.clone();
+        let retention = time::Duration::days(self.settings.retention_days.into());
+        let mut interval = tokio::time::interval(Duration::from_secs(
+            60 * self.settings.cleanup_interval.max(1),
+        ));
+
+        tasks.spawn("audit retention", async move {
+            loop {
+                interval.tick().await;
+                match repository
+                    .delete_events_before(OffsetDateTime::now_utc() - retention)
+                    .await
+                {
+                    Ok(deleted) => tracing::info!("Deleted {} expired audit events", deleted),
+                    Err(e) => tracing::error!("Failed to delete expired audit events: {}", e),
+                }
+            }
+        });
+    }
+}
+
+/// The `Audit` struct is an extractor that writes events to the audit log. It takes the client
+/// address and the user agent from the request.
+///
+/// Properties:
+///
+/// * `repository`: The `repository` property stores the events.
+/// * `ip`: The `ip` property is the client address. The `Forwarded` and `X-Forwarded-For` headers are
+/// only used when `audit.trust_proxy_headers` is set, otherwise it is the peer address.
+/// * `user_agent`: The `user_agent` property is the `User-Agent` header.
+pub struct Audit {
+    repository: Arc<dyn AuditRepository>,
+    ip: Option<String>,
+    user_agent: Option<String>,
+}
+
+impl Audit {
+    /// The function `record` writes an event. A failed write is logged but does not fail the request.
+    ///
+    /// Arguments:
+    ///
+    /// * `action`: The `action` parameter is what happened.
+    /// * `actor_id`: The `actor_id` parameter is the user who did it, if known.
+    /// * `actor_name`: The `actor_name` parameter is the username of the actor or the username that
+    /// was tried.
+    /// * `target_id`: The `target_id` parameter is the post or user that was changed.
+    /// * `success`: The `success` parameter is `false` for rejected attempts.
+    #[instrument(skip(self), name = "Write audit event")]
+    pub async fn record(
+        &self,
+        action: AuditAction,
+        actor_id: Option<uuid::Uuid>,
+        actor_name: Option<&str>,
+        target_id: Option<uuid::Uuid>,
+        success: bool,
+    ) {
+        let event = AuditEvent {
+            id: uuid::Uuid::new_v4(),
+            actor_id,
+            actor_name: actor_name.map(|name| truncate(name, 50)),
+            action: action.as_str().to_string(),
+            target_id,
+            success,
+            ip: self.ip.clone(),
+            user_agent: self.user_agent.clone(),
+            created_at: OffsetDateTime::now_utc(),
+        };
+
+        if let Err(e) = self.repository.add_event(event).await {
+            tracing::error!("Failed to write audit event {}: {}", action.as_str(), e);
+        }
+    }
+}
+
+//the columns have a maximum length
+fn truncate(value: &str, max: usize) -> String {
+    value.chars().take(max).collect()
+}
+
+impl FromRequest for Audit {
+    type Error = errors::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let state = req
+            .app_data::<web::Data<AppState>>()
+            .expect("Can not get app state data");
+
+        let ip = state.audit.client_ip(req);
+        let user_agent = req
+            .headers()
+            .get(header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| truncate(value, 256));
+
+        ready(Ok(Audit {
+            repository: state.audit.repository.clone(),
+            ip,
+            user_agent,
+        }))
+    }
+}
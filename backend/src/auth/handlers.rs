@@ -3,14 +3,15 @@ use actix_web::{
     get, post, web, HttpRequest, HttpResponse, Responder,
 };
 
+use crate::audit::{Audit, AuditAction};
 use crate::errors;
 use crate::{
     app::AppState,
     auth::JwtMiddleware,
-    db::{db_add_user, db_check_user, db_find_user},
+    db::{hash_password, needs_rehash, User},
     schema::{
         jwt::{TokenClaims, TokenType},
-        user::NewUser,
+        user::{NewUser, Password},
     },
 };
 use common::{ApiResponse, UserFormData};
@@ -19,35 +20,126 @@ use time::Duration;
 use tracing::instrument;
 
 #[post("/auth/signup")]
-#[instrument(skip(state), name = "Sign up user")]
+#[instrument(skip(state, audit), name = "Sign up user")]
 pub async fn signup_user(
     data: web::Json<UserFormData>,
     state: web::Data<AppState>,
+    audit: Audit,
 ) -> Result<HttpResponse, errors::Error> {
-    let new_user = NewUser::parse(&data.username, &data.password)?.build()?;
-
-    let db_user = web::block(move || db_add_user(new_user, &state.connection)).await??;
+    let result = match NewUser::parse(&data.username, &data.password, &state.policy).await {
+        Ok(new_user) => {
+            //hashing is CPU heavy, it does not hold up the async worker
+            let settings = state.policy.argon2.clone();
+            state.tasks.block(move || new_user.build(&settings)).await
+        }
+        Err(e) => Err(e),
+    };
+    let result = match result {
+        //every user gets a personal workspace for their notes, it is added with them
+        Ok(Ok(new_user)) => state.users.add_user(new_user).await,
+        Ok(Err(e)) | Err(e) => Err(e),
+    };
+    let db_user = match result {
+        Ok(db_user) => db_user,
+        Err(e) => {
+            audit
+                .record(AuditAction::Signup, None, Some(&data.username), None, false)
+                .await;
+            return Err(e);
+        }
+    };
+    audit
+        .record(
+            AuditAction::Signup,
+            Some(db_user.id),
+            Some(&db_user.username),
+            Some(db_user.id),
+            true,
+        )
+        .await;
 
     Ok(HttpResponse::Ok().json(db_user))
 }
 
+/// The function `ensure_enabled` stops disabled accounts from getting new tokens.
+fn ensure_enabled(user: &User) -> Result<(), errors::Error> {
+    if user.disabled {
+        tracing::info!("User {} is disabled!", user.id);
+        return Err(errors::Error::from_code(
+            "auth.disabled",
+            errors::ErrorTypes::Auth(errors::Auth::Authentication),
+        ));
+    }
+    Ok(())
+}
+
+/// The function `check_login` validates the credentials and returns the user if they may log in. The
+/// password policy is not applied, so accounts created under an older policy can still log in.
+async fn check_login(data: &UserFormData, state: &AppState) -> Result<User, errors::Error> {
+    let password = Password(data.password.clone());
+    let db_user = state.users.check_user(&data.username, password).await?;
+    ensure_enabled(&db_user)?;
+    upgrade_password_hash(&db_user, &data.password, state).await;
+    Ok(db_user)
+}
+
+/// The function `upgrade_password_hash` replaces a password hash made with other argon2 parameters
+/// than the configured ones. The password is only known on login, so the hashes are upgraded then. A
+/// failed upgrade is logged, the login still succeeds.
+async fn upgrade_password_hash(user: &User, password: &str, state: &AppState) {
+    let settings = state.policy.argon2.clone();
+    if !needs_rehash(&user.password, &settings) {
+        return;
+    }
+
+    let password = password.to_owned();
+    let result = match state
+        .tasks
+        .block(move || hash_password(&password, &settings))
+        .await
+    {
+        Ok(Ok(password_hash)) => state.users.update_password(user.id, password_hash).await,
+        Ok(Err(e)) | Err(e) => Err(e),
+    };
+    match result {
+        Ok(()) => tracing::info!("Password hash of user {} upgraded", user.id),
+        Err(e) => tracing::error!("Failed to upgrade the password hash of {}: {}", user.id, e),
+    }
+}
+
 #[post("/auth/login")]
-#[instrument(skip(state), name = "User log in")]
+#[instrument(skip(state, audit), name = "User log in")]
 async fn login_user(
     data: web::Json<UserFormData>,
     state: web::Data<AppState>,
+    audit: Audit,
 ) -> Result<HttpResponse, errors::Error> {
-    let new_user = NewUser::parse(&data.username, &data.password)?;
-    let connection = state.connection.clone();
-    let db_user = web::block(move || db_check_user(new_user, &connection)).await??;
+    let db_user = match check_login(&data, &state).await {
+        Ok(db_user) => db_user,
+        Err(e) => {
+            audit
+                .record(AuditAction::Login, None, Some(&data.username), None, false)
+                .await;
+            return Err(e);
+        }
+    };
+    audit
+        .record(
+            AuditAction::Login,
+            Some(db_user.id),
+            Some(&db_user.username),
+            Some(db_user.id),
+            true,
+        )
+        .await;
 
     let access_token = state.jwt.encode(
-        &TokenClaims::new(db_user.id.to_string(), state.jwt.access.exp),
+        &TokenClaims::new(&db_user, state.jwt.access.exp),
         TokenType::Access,
     )?;
 
     let refresh_token = state.jwt.encode(
-        &TokenClaims::new(db_user.id.to_string(), state.jwt.refresh.exp),
+        &TokenClaims::new(&db_user, state.jwt.refresh.exp),
         TokenType::Refresh,
     )?;
 
@@ -69,22 +161,57 @@ async fn login_user(
         .json(db_user))
 }
 
+/// The function `check_refresh` validates the refresh token and returns the user if they may get a
+/// new access token.
+async fn check_refresh(req: &HttpRequest, state: &AppState) -> Result<User, errors::Error> {
+    //check refresh token and find User's id
+    let (user_id, claims) = state.jwt.refresh(req)?;
+
+    //check if user exists and Uuid valid
+    let db_user = state.users.find_user(user_id).await?;
+    ensure_enabled(&db_user)?;
+
+    //the sessions could be revoked after the refresh token was issued
+    if claims.ver != db_user.session_version {
+        tracing::error!("Refresh token of user {} was revoked", user_id);
+        return Err(errors::Error::from_code(
+            "auth.session_expired",
+            errors::ErrorTypes::Auth(errors::Auth::Authorization),
+        ));
+    }
+
+    Ok(db_user)
+}
+
 #[get("/auth/refresh")]
 #[instrument(skip_all, name = "User refresh authorization")]
 async fn refresh_auth(
     req: HttpRequest,
     state: web::Data<AppState>,
-    _: JwtMiddleware,
+    auth: JwtMiddleware,
+    audit: Audit,
 ) -> Result<HttpResponse, errors::Error> {
-    //check refresh token and find User's id
-    let user_id = state.jwt.refresh(&req)?;
-
-    //check if user exists and Uuid valid
-    let connection = state.connection.clone();
-    let db_user = web::block(move || db_find_user(user_id, &connection)).await??;
+    let db_user = match check_refresh(&req, &state).await {
+        Ok(db_user) => db_user,
+        Err(e) => {
+            audit
+                .record(AuditAction::Refresh, Some(auth.user_id), None, None, false)
+                .await;
+            return Err(e);
+        }
+    };
+    audit
+        .record(
+            AuditAction::Refresh,
+            Some(db_user.id),
+            Some(&db_user.username),
+            Some(db_user.id),
+            true,
+        )
+        .await;
 
     let new_token = state.jwt.encode(
-        &TokenClaims::new(db_user.id.to_string(), Duration::minutes(1)),
+        &TokenClaims::new(&db_user, Duration::minutes(1)),
         TokenType::Access,
     )?;
 
@@ -98,9 +225,29 @@ async fn refresh_auth(
         .json(json!({"status": "success", "new_access": new_token})))
 }
 
+#[get("/auth/me")]
+#[instrument(skip_all, name = "Get current user")]
+async fn current_user(
+    state: web::Data<AppState>,
+    auth: JwtMiddleware,
+) -> Result<HttpResponse, errors::Error> {
+    let db_user = state.users.find_user(auth.user_id).await?;
+    Ok(HttpResponse::Ok().json(db_user))
+}
+
 #[get("/auth/logout")]
 #[instrument(name = "User logout", skip_all)]
-async fn logout_handler(_: JwtMiddleware) -> impl Responder {
+async fn logout_handler(auth: JwtMiddleware, audit: Audit) -> impl Responder {
+    audit
+        .record(
+            AuditAction::Logout,
+            Some(auth.user_id),
+            None,
+            Some(auth.user_id),
+            true,
+        )
+        .await;
+
     let refresh_cookie = Cookie::build("refresh_token", "")
         .path("/")
         .max_age(ActixWebDuration::new(-1, 0))
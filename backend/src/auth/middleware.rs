@@ -5,7 +5,9 @@ use tracing::instrument;
 
 use crate::errors::{Auth, Error, ErrorTypes};
 use crate::{app::AppState, schema::jwt::TokenType};
+use common::Role;
 use std::future::{ready, Ready};
+use std::marker::PhantomData;
 
 /// The `JwtMiddleware` struct represents a middleware for handling JSON Web Tokens (JWTs) with an
 /// associated user ID.
@@ -14,9 +16,11 @@ use std::future::{ready, Ready};
 ///
 /// * `user_id`: The `user_id` property is of type `uuid::Uuid`, which represents a Universally Unique
 /// Identifier (UUID). UUIDs are commonly used to uniquely identify entities in a distributed system.
+/// * `role`: The `role` property is the role of the user from the access token.
 #[derive(Debug)]
 pub struct JwtMiddleware {
     pub user_id: uuid::Uuid,
+    pub role: Role,
 }
 
 impl FromRequest for JwtMiddleware {
@@ -34,9 +38,8 @@ impl FromRequest for JwtMiddleware {
             Some(token) => token,
             None => {
                 tracing::error!("Access token not found");
-                return ready(Err(Error::new(
-                    None,
-                    Some("Access token not found. Log in first!".into()),
+                return ready(Err(Error::from_code(
+                    "auth.access_token_missing",
                     ErrorTypes::Auth(Auth::Authorization),
                 )));
             }
@@ -53,18 +56,94 @@ impl FromRequest for JwtMiddleware {
         {
             tracing::error!("Log in timed out");
 
-            return ready(Err(Error::new(
-                None,
-                Some("Login timed out".into()),
-                ErrorTypes::Auth(Auth::Authorization),
+            return ready(Err(Error::from_code(
+                "auth.login_expired",
+                ErrorTypes::Auth(Auth::Authentication),
             )));
         }
 
         //insert Uuid to request
         let user_id = uuid::Uuid::parse_str(token.sub.as_str()).unwrap();
+
+        if state.jwt.revoked.is_revoked(user_id, &token) {
+            tracing::error!("Session of user {} was revoked", user_id);
+
+            return ready(Err(Error::from_code(
+                "auth.session_expired",
+                ErrorTypes::Auth(Auth::Authorization),
+            )));
+        }
+
         req.extensions_mut()
             .insert::<uuid::Uuid>(user_id.to_owned());
 
-        ready(Ok(JwtMiddleware { user_id }))
+        ready(Ok(JwtMiddleware {
+            user_id,
+            role: token.role,
+        }))
+    }
+}
+
+/// The `RoleGuard` trait names the lowest role that a `RequireRole` extractor lets through.
+pub trait RoleGuard {
+    const ROLE: Role;
+}
+
+/// Lets through moderators and admins.
+#[derive(Debug)]
+pub struct ModeratorRole;
+
+impl RoleGuard for ModeratorRole {
+    const ROLE: Role = Role::Moderator;
+}
+
+/// Lets through admins only.
+#[derive(Debug)]
+pub struct AdminRole;
+
+impl RoleGuard for AdminRole {
+    const ROLE: Role = Role::Admin;
+}
+
+/// The `RequireRole` struct is an extractor that checks the access token like `JwtMiddleware` and
+/// additionally requires the role of the user to be at least `R::ROLE`, for example
+/// `RequireRole<AdminRole>`.
+///
+/// Properties:
+///
+/// * `user_id`: The `user_id` property is the id of the user from the access token.
+/// * `role`: The `role` property is the role of the user from the access token.
+#[derive(Debug)]
+pub struct RequireRole<R: RoleGuard> {
+    pub user_id: uuid::Uuid,
+    pub role: Role,
+    guard: PhantomData<R>,
+}
+
+impl<R: RoleGuard> FromRequest for RequireRole<R> {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    #[instrument(skip_all, name = "Check user's role", fields(required = %R::ROLE))]
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let auth = match JwtMiddleware::from_request(req, payload).into_inner() {
+            Ok(auth) => auth,
+            Err(e) => return ready(Err(e)),
+        };
+
+        if auth.role < R::ROLE {
+            tracing::error!("User {} has role {}", auth.user_id, auth.role);
+
+            return ready(Err(Error::from_code(
+                "auth.forbidden",
+                ErrorTypes::Auth(Auth::Authorization),
+            )));
+        }
+
+        ready(Ok(RequireRole {
+            user_id: auth.user_id,
+            role: auth.role,
+            guard: PhantomData,
+        }))
     }
 }
@@ -8,5 +8,6 @@ pub fn config(cfg: &mut web::ServiceConfig) {
     cfg.service(handlers::signup_user)
         .service(handlers::login_user)
         .service(handlers::logout_handler)
-        .service(handlers::refresh_auth);
+        .service(handlers::refresh_auth)
+        .service(handlers::current_user);
 }
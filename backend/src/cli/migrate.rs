@@ -0,0 +1,105 @@
+use clap::Subcommand;
+use diesel::{backend::Backend, Connection, PgConnection, SqliteConnection};
+use diesel_migrations::{EmbeddedMigrations, MigrationHarness};
+
+use serde_json::json;
+
+use super::print;
+use crate::app::{DbBackend, DbSettings};
+use crate::{db, errors};
+
+#[derive(Subcommand)]
+pub enum MigrateAction {
+    /// Apply all pending migrations
+    Up,
+    /// Revert the last applied migration
+    Down,
+    /// List the migrations and whether they are applied
+    Status,
+}
+
+/// The function `run` connects to the database from the settings and runs the migration action with
+/// the migrations of the configured backend.
+///
+/// Arguments:
+///
+/// * `action`: The `action` parameter is the migration subcommand.
+/// * `settings`: The `settings` parameter is of type `&DbSettings`.
+/// * `json`: The `json` parameter prints the result as JSON instead of text.
+///
+/// Returns:
+///
+/// a `Result<(), errors::Error>`.
+pub fn run(action: MigrateAction, settings: &DbSettings, json: bool) -> Result<(), errors::Error> {
+    match settings.backend {
+        DbBackend::Postgres => {
+            let mut conn = connect::<PgConnection>(&settings.connection_url())?;
+            apply(action, &mut conn, db::MIGRATIONS, json)
+        }
+        DbBackend::Sqlite => {
+            let mut conn = connect::<SqliteConnection>(&settings.sqlite_path)?;
+            apply(action, &mut conn, db::sqlite::MIGRATIONS, json)
+        }
+        DbBackend::Memory => {
+            print(
+                json,
+                json!({"migrations": []}),
+                "The memory backend has no migrations".to_string(),
+            );
+            Ok(())
+        }
+    }
+}
+
+fn connect<C: Connection>(url: &str) -> Result<C, errors::Error> {
+    C::establish(url).map_err(|e| {
+        errors::Error::new(
+            Some(e.to_string()),
+            Some("Failed to connect to the database!".to_string()),
+            errors::ErrorTypes::DbError,
+        )
+    })
+}
+
+fn apply<DB: Backend>(
+    action: MigrateAction,
+    conn: &mut impl MigrationHarness<DB>,
+    source: EmbeddedMigrations,
+    json: bool,
+) -> Result<(), errors::Error> {
+    match action {
+        MigrateAction::Up => {
+            let applied = db::run_pending_migrations(conn, source)?;
+            let text = if applied.is_empty() {
+                "The database is up to date".to_string()
+            } else {
+                applied
+                    .iter()
+                    .map(|version| format!("Applied {}", version))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            };
+            print(json, json!({ "applied": applied }), text);
+        }
+        MigrateAction::Down => {
+            let version = db::revert_last_migration(conn, source)?;
+            let text = format!("Reverted {}", version);
+            print(json, json!({ "reverted": version }), text);
+        }
+        MigrateAction::Status => {
+            let status = db::migration_status(conn, source)?;
+            let text = status
+                .iter()
+                .map(|(name, applied)| format!("[{}] {}", if *applied { "X" } else { " " }, name))
+                .collect::<Vec<_>>()
+                .join("\n");
+            let value: Vec<_> = status
+                .iter()
+                .map(|(name, applied)| json!({"migration": name, "applied": applied}))
+                .collect();
+            print(json, json!(value), text);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,61 @@
+pub mod migrate;
+pub mod user;
+
+use clap::{Parser, Subcommand};
+
+use crate::app::Settings;
+use crate::errors;
+
+/// The `Cli` struct represents the command line of the backend binary. Without a subcommand the HTTP
+/// server is started.
+#[derive(Parser)]
+#[command(version, about = "Notes portal backend")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Print the result of a subcommand as JSON
+    #[arg(long, global = true)]
+    pub json: bool,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Manage the database schema with the embedded migrations
+    Migrate {
+        #[command(subcommand)]
+        action: migrate::MigrateAction,
+    },
+    /// Manage the user accounts
+    User {
+        #[command(subcommand)]
+        action: user::UserAction,
+    },
+}
+
+/// The function `run` executes a subcommand and returns when it is done.
+///
+/// Arguments:
+///
+/// * `command`: The `command` parameter is the parsed subcommand.
+/// * `config`: The `config` parameter is the loaded configuration.
+/// * `json`: The `json` parameter prints the result as JSON instead of text.
+///
+/// Returns:
+///
+/// a `Result<(), errors::Error>`.
+pub async fn run(command: Command, config: &Settings, json: bool) -> Result<(), errors::Error> {
+    match command {
+        Command::Migrate { action } => migrate::run(action, &config.database, json),
+        Command::User { action } => user::run(action, config, json).await,
+    }
+}
+
+/// The function `print` writes the result of a command as JSON or as text.
+pub fn print(json: bool, value: serde_json::Value, text: String) {
+    if json {
+        println!("{}", value);
+    } else {
+        println!("{}", text);
+    }
+}
@@ -0,0 +1,203 @@
+use std::io::BufRead;
+
+use clap::Subcommand;
+use common::Role;
+use serde_json::json;
+use time::format_description::well_known::Rfc3339;
+
+use super::print;
+use crate::app::Settings;
+use crate::attachments::delete_unused_blobs;
+use crate::db::{hash_password, UserRepository};
+use crate::errors;
+use crate::schema::user::{NewUser, Password};
+
+#[derive(Subcommand)]
+pub enum UserAction {
+    /// Create a new user. The password is read from stdin if it is not given
+    Create {
+        username: String,
+        #[arg(long)]
+        password: Option<String>,
+    },
+    /// Replace the password of a user. The password is read from stdin if it is not given
+    ResetPassword {
+        username: String,
+        #[arg(long)]
+        password: Option<String>,
+    },
+    /// Disable an account, so it can not log in
+    Disable { username: String },
+    /// Enable a disabled account
+    Enable { username: String },
+    /// Change the role of a user: user, moderator or admin
+    SetRole { username: String, role: Role },
+    /// Log a user out of all sessions
+    Logout { username: String },
+    /// List the users with the number of their notes
+    List,
+    /// Delete a user together with their notes
+    Delete {
+        username: String,
+        /// Confirm the deletion
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
+/// The function `run` executes a user management action against the configured database. The
+/// passwords are validated with the configured policy and hashed the same way as on signup.
+///
+/// Arguments:
+///
+/// * `action`: The `action` parameter is the user subcommand.
+/// * `config`: The `config` parameter is the loaded configuration.
+/// * `json`: The `json` parameter prints the result as JSON instead of text.
+///
+/// Returns:
+///
+/// a `Result<(), errors::Error>`.
+pub async fn run(action: UserAction, config: &Settings, json: bool) -> Result<(), errors::Error> {
+    let state = config.create_app_state()?;
+    let users = state.users.clone();
+    let policy = &config.policy;
+
+    match action {
+        UserAction::Create { username, password } => {
+            let password = password_or_stdin(password)?;
+            let user = users
+                .add_user(
+                    NewUser::parse(&username, &password, policy)
+                        .await?
+                        .build(&policy.argon2)?,
+                )
+                .await?;
+            print(
+                json,
+                json!({"id": user.id, "username": user.username, "disabled": user.disabled}),
+                format!("Created user {} ({})", user.username, user.id),
+            );
+        }
+        UserAction::ResetPassword { username, password } => {
+            let password = password_or_stdin(password)?;
+            let user = users.find_user_by_username(&username).await?;
+            //only the password is checked, the username may be from an older policy
+            let password = Password::parse(&password, &policy.password)
+                .await
+                .map_err(errors::Error::validation)?;
+            let password_hash = hash_password(&password.0, &policy.argon2)?;
+            users.update_password(user.id, password_hash).await?;
+            print(
+                json,
+                json!({"status": "success", "username": username}),
+                format!("Password of {} updated", username),
+            );
+        }
+        UserAction::Disable { username } => {
+            set_disabled(users.as_ref(), &username, true, json).await?;
+        }
+        UserAction::Enable { username } => {
+            set_disabled(users.as_ref(), &username, false, json).await?;
+        }
+        UserAction::SetRole { username, role } => {
+            let user = users.find_user_by_username(&username).await?;
+            users.set_role(user.id, role).await?;
+            //the old role is written in the issued tokens
+            users.revoke_sessions(user.id).await?;
+            print(
+                json,
+                json!({"status": "success", "username": username, "role": role}),
+                format!("Role of {} changed to {}", username, role),
+            );
+        }
+        UserAction::Logout { username } => {
+            let user = users.find_user_by_username(&username).await?;
+            users.revoke_sessions(user.id).await?;
+            print(
+                json,
+                json!({"status": "success", "username": username}),
+                format!("Sessions of {} revoked", username),
+            );
+        }
+        UserAction::List => {
+            let summaries = users.list_users().await?;
+            let mut text = format!(
+                "{:<36}  {:<20}  {:<9}  {:<8}  {:>5}  {}",
+                "ID", "USERNAME", "ROLE", "DISABLED", "NOTES", "CREATED"
+            );
+            for user in &summaries {
+                text.push_str(&format!(
+                    "\n{:<36}  {:<20}  {:<9}  {:<8}  {:>5}  {}",
+                    user.id,
+                    user.username,
+                    user.role.as_str(),
+                    user.disabled,
+                    user.posts,
+                    user.created_at.format(&Rfc3339).unwrap_or_default()
+                ));
+            }
+            print(json, json!(summaries), text);
+        }
+        UserAction::Delete { username, yes } => {
+            if !yes {
+                return Err(errors::Error::new(
+                    None,
+                    Some("Pass --yes to delete the user and all of their notes!".to_string()),
+                    errors::ErrorTypes::ValidationError,
+                ));
+            }
+            let user = users.find_user_by_username(&username).await?;
+            let hashes = state.attachments.get_user_hashes(user.id).await?;
+            users.delete_user(user.id).await?;
+            delete_unused_blobs(&state, hashes).await;
+            print(
+                json,
+                json!({"status": "success", "username": username}),
+                format!("Deleted user {} and their notes", username),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+async fn set_disabled(
+    users: &dyn UserRepository,
+    username: &str,
+    disabled: bool,
+    json: bool,
+) -> Result<(), errors::Error> {
+    let user = users.find_user_by_username(username).await?;
+    users.set_disabled(user.id, disabled).await?;
+    if disabled {
+        users.revoke_sessions(user.id).await?;
+    }
+    print(
+        json,
+        json!({"status": "success", "username": username, "disabled": disabled}),
+        format!(
+            "User {} {}",
+            username,
+            if disabled { "disabled" } else { "enabled" }
+        ),
+    );
+    Ok(())
+}
+
+/// The function `password_or_stdin` returns the password from the flag, or reads the first line of
+/// stdin, so the password does not have to end up in the shell history.
+fn password_or_stdin(password: Option<String>) -> Result<String, errors::Error> {
+    if let Some(password) = password {
+        return Ok(password);
+    }
+
+    let mut line = String::new();
+    std::io::stdin().lock().read_line(&mut line).map_err(|e| {
+        errors::Error::new(
+            Some(e.to_string()),
+            Some("Failed to read the password from stdin!".to_string()),
+            errors::ErrorTypes::ValidationError,
+        )
+    })?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
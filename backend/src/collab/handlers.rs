@@ -0,0 +1,214 @@
+use std::time::Duration;
+
+use actix_http::ws;
+use actix_web::{
+    get,
+    http::header,
+    web::{self, Bytes, BytesMut},
+    HttpRequest, HttpResponse,
+};
+use common::{CollabClientMessage, Permission, WorkspaceRole};
+use futures::{stream, Stream, StreamExt};
+use tokio::sync::mpsc;
+use tokio::time::Interval;
+use tokio_util::codec::{Decoder, Encoder};
+use tracing::instrument;
+
+use super::{encode, error, RoomMessage, Session, SESSION_CAPACITY};
+use crate::errors;
+use crate::permissions::post_access;
+use crate::workspaces::{workspace_access, workspace_post};
+use crate::{app::AppState, auth::JwtMiddleware};
+
+//the largest frame of an editor, a long pasted text fits
+const MAX_FRAME: usize = 1024 * 1024;
+//a ping is sent this often, so proxies keep the idle connection open
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+#[get("/workspaces/{workspace_id}/posts/{id}/collab")]
+#[instrument(skip(req, payload, state, auth), name = "Join collaborative editing")]
+async fn collab(
+    req: HttpRequest,
+    payload: web::Payload,
+    state: web::Data<AppState>,
+    auth: JwtMiddleware,
+    path: web::Path<(uuid::Uuid, uuid::Uuid)>,
+) -> Result<HttpResponse, errors::Error> {
+    let (workspace_id, post_id) = path.into_inner();
+    let workspace =
+        workspace_access(&state, workspace_id, auth.user_id, WorkspaceRole::Member).await?;
+    workspace_post(&state, &workspace, post_id, auth.user_id).await?;
+    let permission = post_access(&state, post_id, auth.user_id, Permission::Viewer).await?;
+    ws::verify_handshake(req.head()).map_err(|e| {
+        errors::Error::from_code(
+            "collab.websocket_required",
+            errors::ErrorTypes::ValidationError,
+        )
+        .with_cause(e)
+    })?;
+    //the key is there, the handshake was checked
+    let accept = req
+        .headers()
+        .get(header::SEC_WEBSOCKET_KEY)
+        .map(|key| ws::hash_key(key.as_bytes()))
+        .unwrap_or_default();
+    let username = state.users.find_user(auth.user_id).await?.username;
+
+    let (sender, outgoing) = mpsc::channel(SESSION_CAPACITY);
+    let client_id = state.collab.next_client_id();
+    let session = Session {
+        client_id,
+        user_id: auth.user_id,
+        username,
+        read_only: permission < Permission::Editor,
+        sender,
+    };
+    let room = state.collab.join(&state, post_id, session)?;
+    let connection = Connection {
+        payload,
+        outgoing,
+        keep_alive: tokio::time::interval(KEEP_ALIVE),
+        codec: ws::Codec::new().max_size(MAX_FRAME),
+        buffer: BytesMut::new(),
+        membership: Membership { room, client_id },
+        closed: false,
+    };
+
+    Ok(HttpResponse::SwitchingProtocols()
+        .upgrade("websocket")
+        .insert_header((
+            header::SEC_WEBSOCKET_ACCEPT,
+            String::from_utf8_lossy(&accept).into_owned(),
+        ))
+        .streaming(frames(connection)))
+}
+
+/// The `Membership` struct is a session in a room. The session leaves the room when it is dropped,
+/// also when the editor disconnects without closing the WebSocket.
+struct Membership {
+    room: mpsc::Sender<RoomMessage>,
+    client_id: u64,
+}
+
+impl Drop for Membership {
+    fn drop(&mut self) {
+        //a full room finds the closed session by itself
+        let _ = self.room.try_send(RoomMessage::Leave {
+            client_id: self.client_id,
+        });
+    }
+}
+
+/// The `Connection` struct is the WebSocket of an editor.
+///
+/// Properties:
+///
+/// * `payload`: The `payload` property is the data of the editor after the handshake.
+/// * `outgoing`: The `outgoing` property gets the messages of the room.
+/// * `keep_alive`: The `keep_alive` property times the pings.
+/// * `codec`: The `codec` property reads and writes the WebSocket frames.
+/// * `buffer`: The `buffer` property holds the data of the frames that did not arrive whole yet.
+/// * `membership`: The `membership` property sends the messages of the editor to the room.
+/// * `closed`: The `closed` property is `true` after the close frame was sent.
+struct Connection {
+    payload: web::Payload,
+    outgoing: mpsc::Receiver<String>,
+    keep_alive: Interval,
+    codec: ws::Codec,
+    buffer: BytesMut,
+    membership: Membership,
+    closed: bool,
+}
+
+/// The function `frames` returns the frames sent to an editor. The stream ends after the close
+/// frame, or when the editor is gone.
+fn frames(connection: Connection) -> impl Stream<Item = Result<Bytes, errors::Error>> {
+    stream::unfold(connection, |mut connection| async move {
+        loop {
+            let message = connection.next_message().await?;
+            let mut frame = BytesMut::new();
+            match connection.codec.encode(message, &mut frame) {
+                Ok(()) => return Some((Ok(frame.freeze()), connection)),
+                Err(e) => tracing::error!("Failed to encode WebSocket frame: {}", e),
+            }
+        }
+    })
+}
+
+impl Connection {
+    //the next message for the editor, the frames of the editor are handled meanwhile
+    async fn next_message(&mut self) -> Option<ws::Message> {
+        loop {
+            if self.closed {
+                return None;
+            }
+            match self.codec.decode(&mut self.buffer) {
+                Ok(Some(frame)) => match self.receive(frame) {
+                    Some(reply) => return Some(reply),
+                    None => continue,
+                },
+                Ok(None) => {}
+                Err(e) => {
+                    tracing::warn!("Invalid WebSocket frame: {}", e);
+                    self.closed = true;
+                    return Some(ws::Message::Close(Some(ws::CloseCode::Protocol.into())));
+                }
+            }
+
+            tokio::select! {
+                chunk = self.payload.next() => match chunk {
+                    Some(Ok(chunk)) => self.buffer.extend_from_slice(&chunk),
+                    //the editor is gone, there is no one to send the close frame to
+                    _ => return None,
+                },
+                text = self.outgoing.recv() => {
+                    return match text {
+                        Some(text) => Some(ws::Message::Text(text.into())),
+                        None => {
+                            self.closed = true;
+                            Some(ws::Message::Close(Some(ws::CloseCode::Away.into())))
+                        }
+                    };
+                }
+                _ = self.keep_alive.tick() => return Some(ws::Message::Ping(Bytes::new())),
+            }
+        }
+    }
+
+    //the messages go to the room, the reply is for the editor
+    fn receive(&mut self, frame: ws::Frame) -> Option<ws::Message> {
+        match frame {
+            ws::Frame::Text(data) => match serde_json::from_slice::<CollabClientMessage>(&data) {
+                Ok(message) => {
+                    let sent = self.membership.room.try_send(RoomMessage::Client {
+                        client_id: self.membership.client_id,
+                        message,
+                    });
+                    match sent {
+                        Ok(()) => None,
+                        //the editor sends faster than the room applies, it loads the note again
+                        Err(e) => {
+                            tracing::warn!("Collaborative editing session dropped: {}", e);
+                            self.closed = true;
+                            Some(ws::Message::Close(Some(ws::CloseCode::Again.into())))
+                        }
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!("Invalid collaborative editing message: {}", e);
+                    encode(&error("collab.invalid_message"))
+                        .map(|text| ws::Message::Text(text.into()))
+                }
+            },
+            ws::Frame::Ping(data) => Some(ws::Message::Pong(data)),
+            ws::Frame::Pong(_) => None,
+            ws::Frame::Close(reason) => {
+                self.closed = true;
+                Some(ws::Message::Close(reason))
+            }
+            ws::Frame::Binary(_) | ws::Frame::Continuation(_) => {
+                encode(&error("collab.invalid_message")).map(|text| ws::Message::Text(text.into()))
+            }
+        }
+    }
+}
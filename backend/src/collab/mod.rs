@@ -0,0 +1,200 @@
+pub mod handlers;
+pub mod room;
+
+use std::collections::HashMap;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex, MutexGuard,
+};
+
+use actix_web::web;
+use common::{CollabClientMessage, CollabServerMessage};
+use tokio::sync::mpsc;
+
+use crate::app::AppState;
+use crate::errors;
+
+//the messages that wait for a room, a room that falls further behind refuses new editors
+const ROOM_CAPACITY: usize = 256;
+//the messages that wait for an editor, an editor who falls further behind is dropped
+pub const SESSION_CAPACITY: usize = 256;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(handlers::collab);
+}
+
+/// The `Session` struct is an editor connected to the room of a post.
+///
+/// Properties:
+///
+/// * `client_id`: The `client_id` property is the id of the session in the documents, unique in the
+/// instance.
+/// * `user_id`: The `user_id` property is the user.
+/// * `username`: The `username` property is shown to the other editors.
+/// * `read_only`: The `read_only` property is `true` for the viewers of the post, their changes are
+/// refused.
+/// * `sender`: The `sender` property sends the encoded messages of the room to the WebSocket, the
+/// session is dropped when it is full.
+pub struct Session {
+    pub client_id: u64,
+    pub user_id: uuid::Uuid,
+    pub username: String,
+    pub read_only: bool,
+    pub sender: mpsc::Sender<String>,
+}
+
+/// The `RoomMessage` enum is a message of an editing session to the room of its post. `Replace` is a
+/// description that was changed without the editors.
+pub enum RoomMessage {
+    Join(Session),
+    Client {
+        client_id: u64,
+        message: CollabClientMessage,
+    },
+    Leave {
+        client_id: u64,
+    },
+    Replace {
+        text: String,
+    },
+}
+
+type Rooms = HashMap<uuid::Uuid, mpsc::Sender<RoomMessage>>;
+
+/// The `CollabRooms` struct holds the rooms of the posts edited on this instance. A room is a
+/// background task with the merged document, it is opened by the first editor and closed after the
+/// last one left.
+///
+/// Properties:
+///
+/// * `rooms`: The `rooms` property maps the posts to the channels of their rooms. The sessions send to
+/// a room while holding the lock, so a room that closes sees every message sent to it.
+/// * `next_client`: The `next_client` property counts the sessions, `common::SEED_CLIENT` is never
+/// given out.
+#[derive(Clone, Default)]
+pub struct CollabRooms {
+    rooms: Arc<Mutex<Rooms>>,
+    next_client: Arc<AtomicU64>,
+}
+
+impl CollabRooms {
+    pub fn new() -> Self {
+        CollabRooms::default()
+    }
+
+    /// The function `next_client_id` returns the id of a new session.
+    pub fn next_client_id(&self) -> u64 {
+        self.next_client.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    /// The function `join` adds a session to the room of a post. The room is opened if the post is
+    /// not edited yet, it reads the description itself, after a closing room stored it.
+    ///
+    /// Arguments:
+    ///
+    /// * `state`: The `state` parameter is the app state the room stores the post with.
+    /// * `post_id`: The `post_id` parameter is the id of the post.
+    /// * `session`: The `session` parameter is the editor who joins.
+    ///
+    /// Returns:
+    ///
+    /// the channel the session sends its messages to the room with, or `collab.busy` if the room can
+    /// not keep up with its messages.
+    pub fn join(
+        &self,
+        state: &web::Data<AppState>,
+        post_id: uuid::Uuid,
+        session: Session,
+    ) -> Result<mpsc::Sender<RoomMessage>, errors::Error> {
+        let mut rooms = self.lock()?;
+        let room = match rooms.get(&post_id) {
+            Some(room) if !room.is_closed() => room.clone(),
+            _ => {
+                let (sender, receiver) = mpsc::channel(ROOM_CAPACITY);
+                state
+                    .tasks
+                    .spawn("collab room", room::run(state.clone(), post_id, receiver));
+                rooms.insert(post_id, sender.clone());
+                sender
+            }
+        };
+        //the room is alive, it is removed only with the lock
+        room.try_send(RoomMessage::Join(session)).map_err(|e| {
+            tracing::warn!("Room of post {} is full", post_id);
+            errors::Error::from_code("collab.busy", errors::ErrorTypes::Unavailable).with_cause(e)
+        })?;
+        Ok(room)
+    }
+
+    /// The function `description_changed` sends a description that was changed without the editors,
+    /// with `PATCH` or by embedded uploads, to the room of the post. The room changes its document to
+    /// the new text, so it does not store the old one over it, and the changes the editors make
+    /// meanwhile are merged. Nothing happens if the post is not edited on this instance.
+    ///
+    /// Arguments:
+    ///
+    /// * `post_id`: The `post_id` parameter is the id of the post.
+    /// * `description`: The `description` parameter is the stored description.
+    pub async fn description_changed(&self, post_id: uuid::Uuid, description: Option<String>) {
+        let room = match self.lock() {
+            Ok(rooms) => rooms.get(&post_id).cloned(),
+            Err(_) => None,
+        };
+        if let Some(room) = room {
+            //a closed room stored its text before, the new description stays
+            let _ = room
+                .send(RoomMessage::Replace {
+                    text: description.unwrap_or_default(),
+                })
+                .await;
+        }
+    }
+
+    /// The function `close_if_idle` removes the room of a post that has no sessions any more, unless
+    /// a message arrived in the meantime.
+    ///
+    /// Returns:
+    ///
+    /// the message that keeps the room open, or `None` if the room is closed.
+    pub fn close_if_idle(
+        &self,
+        post_id: uuid::Uuid,
+        receiver: &mut mpsc::Receiver<RoomMessage>,
+    ) -> Option<RoomMessage> {
+        //the sessions of a broken registry can not reach the room anyway
+        let mut rooms = self.lock().ok()?;
+        match receiver.try_recv() {
+            Ok(message) => Some(message),
+            Err(_) => {
+                rooms.remove(&post_id);
+                None
+            }
+        }
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, Rooms>, errors::Error> {
+        self.rooms.lock().map_err(|e| {
+            tracing::error!("Collaborative editing rooms are poisoned!");
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::Unavailable)
+        })
+    }
+}
+
+/// The function `encode` returns the text frame of a message of the server, `None` if it could not
+/// be encoded.
+pub fn encode(message: &CollabServerMessage) -> Option<String> {
+    serde_json::to_string(message)
+        .map_err(|e| tracing::error!("Failed to encode collaborative editing message: {}", e))
+        .ok()
+}
+
+/// The function `error` returns the message that refuses a message of an editor.
+///
+/// Arguments:
+///
+/// * `code`: The `code` parameter is the translation key of the reason.
+pub fn error(code: &str) -> CollabServerMessage {
+    CollabServerMessage::Error {
+        code: code.to_owned(),
+    }
+}
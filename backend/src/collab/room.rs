@@ -0,0 +1,365 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use actix_web::web;
+use common::{
+    CollabClientMessage, CollabCursor, CollabDoc, CollabOp, CollabPeer, CollabServerMessage,
+    Permission, PostEventKind, SEED_CLIENT,
+};
+use tokio::sync::mpsc;
+
+use super::{encode, error, RoomMessage, Session};
+use crate::app::AppState;
+use crate::errors;
+use crate::events;
+
+//the merged document is stored this often while it changes, and when the last editor leaves, the
+//access of the editors is checked as often
+const PERSIST_INTERVAL: Duration = Duration::from_secs(2);
+
+/// The `Room` struct is the merged document of a post and its editors.
+///
+/// Properties:
+///
+/// * `post_id`: The `post_id` property is the id of the post.
+/// * `doc`: The `doc` property is the document, it starts with the stored description.
+/// * `sessions`: The `sessions` property maps the client ids to the editors with their selections.
+/// * `dirty`: The `dirty` property is `true` if the document changed since it was stored.
+struct Room {
+    post_id: uuid::Uuid,
+    doc: CollabDoc,
+    sessions: HashMap<u64, (Session, Option<CollabCursor>)>,
+    dirty: bool,
+}
+
+/// The function `run` is the task of a room. It applies the changes of the editors, sends them to
+/// the others and stores the text as the description of the post. The task ends after the last
+/// editor left, or when the server shuts down.
+///
+/// Arguments:
+///
+/// * `state`: The `state` parameter is the app state with the repositories and the rooms.
+/// * `post_id`: The `post_id` parameter is the id of the post.
+/// * `receiver`: The `receiver` parameter gets the messages of the sessions.
+pub async fn run(
+    state: web::Data<AppState>,
+    post_id: uuid::Uuid,
+    mut receiver: mpsc::Receiver<RoomMessage>,
+) {
+    let description = match state.notes.find_post(post_id).await {
+        Ok(post) => post.description.unwrap_or_default(),
+        Err(e) => {
+            tracing::error!("Failed to open the room of post {}: {}", post_id, e);
+            //the sessions waiting in the channel are closed with it
+            while state.collab.close_if_idle(post_id, &mut receiver).is_some() {}
+            return;
+        }
+    };
+    let mut room = Room {
+        post_id,
+        doc: CollabDoc::from_text(&description),
+        sessions: HashMap::new(),
+        dirty: false,
+    };
+    tracing::info!("Room of post {} opened", post_id);
+
+    let mut persist = tokio::time::interval(PERSIST_INTERVAL);
+    let mut next = None;
+    loop {
+        let message = match next.take() {
+            Some(message) => Some(message),
+            None => tokio::select! {
+                message = receiver.recv() => {
+                    if message.is_none() {
+                        //no session can reach the room any more
+                        room.sessions.clear();
+                    }
+                    message
+                }
+                _ = persist.tick() => {
+                    if state.tasks.is_draining() {
+                        //the sessions end with their channels
+                        room.sessions.clear();
+                    }
+                    room.prune();
+                    room.check_access(&state).await;
+                    room.persist(&state).await;
+                    None
+                }
+            },
+        };
+        if let Some(message) = message {
+            room.handle(message);
+        }
+
+        if room.sessions.is_empty() {
+            room.persist(&state).await;
+            next = state.collab.close_if_idle(post_id, &mut receiver);
+            if next.is_none() {
+                break;
+            }
+        }
+    }
+    tracing::info!("Room of post {} closed", post_id);
+}
+
+impl Room {
+    fn handle(&mut self, message: RoomMessage) {
+        match message {
+            RoomMessage::Join(session) => self.join(session),
+            RoomMessage::Client {
+                client_id,
+                message: CollabClientMessage::Update { ops },
+            } => self.update(client_id, ops),
+            RoomMessage::Client {
+                client_id,
+                message: CollabClientMessage::Presence { cursor },
+            } => {
+                if let Some((_, current)) = self.sessions.get_mut(&client_id) {
+                    *current = cursor;
+                    if let Some(peer) = self.peer(client_id) {
+                        self.broadcast(client_id, &CollabServerMessage::Presence { peer });
+                    }
+                }
+            }
+            RoomMessage::Leave { client_id } => self.remove(client_id),
+            RoomMessage::Replace { text } => self.replace(&text),
+        }
+    }
+
+    //the new editor gets the document and the others, the others get the new editor
+    fn join(&mut self, session: Session) {
+        let client_id = session.client_id;
+        let welcome = CollabServerMessage::Welcome {
+            client_id,
+            ops: self.doc.state(),
+            peers: self
+                .sessions
+                .keys()
+                .filter_map(|client_id| self.peer(*client_id))
+                .collect(),
+            read_only: session.read_only,
+        };
+        tracing::info!(
+            "User {} joined the room of post {}",
+            session.user_id,
+            self.post_id
+        );
+        //the editor is gone already
+        if !send(&session, &welcome) {
+            return;
+        }
+        self.sessions.insert(client_id, (session, None));
+        if let Some(peer) = self.peer(client_id) {
+            self.broadcast(client_id, &CollabServerMessage::Presence { peer });
+        }
+    }
+
+    //the changes are applied until one does not fit, the applied ones are sent to the others
+    fn update(&mut self, client_id: u64, ops: Vec<CollabOp>) {
+        let Some((session, _)) = self.sessions.get(&client_id) else {
+            return;
+        };
+        if session.read_only {
+            self.send(client_id, &error("collab.read_only"));
+            return;
+        }
+
+        let mut applied = Vec::new();
+        let mut refused = false;
+        for op in ops {
+            //an editor inserts only characters with its own ids
+            let own = match &op {
+                CollabOp::Insert { id, .. } => id.client == client_id,
+                CollabOp::Delete { .. } => true,
+            };
+            if !own || self.doc.apply(&op).is_err() {
+                tracing::warn!(
+                    "Change of session {} does not fit post {}",
+                    client_id,
+                    self.post_id
+                );
+                refused = true;
+                break;
+            }
+            applied.push(op);
+        }
+        if !applied.is_empty() {
+            self.dirty = true;
+            self.broadcast(client_id, &CollabServerMessage::Update { ops: applied });
+        }
+        if refused {
+            self.send(client_id, &error("collab.invalid_update"));
+        }
+    }
+
+    //the text that changed without the editors replaces the changed part of the document, as changes
+    //of the room that the editors merge with their own
+    fn replace(&mut self, text: &str) {
+        let current: Vec<char> = self.doc.text().chars().collect();
+        let new: Vec<char> = text.chars().collect();
+        let prefix = current
+            .iter()
+            .zip(&new)
+            .take_while(|(current, new)| current == new)
+            .count();
+        let suffix = current[prefix..]
+            .iter()
+            .rev()
+            .zip(new[prefix..].iter().rev())
+            .take_while(|(current, new)| current == new)
+            .count();
+        let mut ops = self.doc.delete(prefix, current.len() - prefix - suffix);
+        let inserted: String = new[prefix..new.len() - suffix].iter().collect();
+        ops.extend(self.doc.insert(prefix, &inserted));
+        if ops.is_empty() {
+            return;
+        }
+        tracing::info!(
+            "Description of post {} changed during editing",
+            self.post_id
+        );
+        //a text the room stored in the meantime is replaced again
+        self.dirty = true;
+        self.broadcast(SEED_CLIENT, &CollabServerMessage::Update { ops });
+    }
+
+    //stores the text and tells the readers of the posts list, a failure is tried again later
+    async fn persist(&mut self, state: &AppState) {
+        if !self.dirty {
+            return;
+        }
+        let text = self.doc.text();
+        let description = (!text.is_empty()).then_some(text);
+        match state
+            .notes
+            .update_description(self.post_id, description)
+            .await
+        {
+            Ok(()) => {
+                self.dirty = false;
+                tracing::info!("Merged description of post {} stored", self.post_id);
+                events::stored_post_changed(state, PostEventKind::Updated, self.post_id).await;
+            }
+            Err(e) => tracing::error!(
+                "Failed to store the description of post {}: {}",
+                self.post_id,
+                e
+            ),
+        }
+    }
+
+    fn peer(&self, client_id: u64) -> Option<CollabPeer> {
+        let (session, cursor) = self.sessions.get(&client_id)?;
+        Some(CollabPeer {
+            client_id,
+            user_id: session.user_id,
+            username: session.username.clone(),
+            cursor: *cursor,
+        })
+    }
+
+    //to everyone but the editor the message is about, the editors who can not keep up are dropped
+    fn broadcast(&mut self, from: u64, message: &CollabServerMessage) {
+        let Some(text) = encode(message) else {
+            return;
+        };
+        let behind: Vec<u64> = self
+            .sessions
+            .iter()
+            .filter(|(client_id, (session, _))| {
+                **client_id != from && session.sender.try_send(text.clone()).is_err()
+            })
+            .map(|(client_id, _)| *client_id)
+            .collect();
+        for client_id in behind {
+            self.remove(client_id);
+        }
+    }
+
+    //to one editor, who is dropped if they can not keep up
+    fn send(&mut self, client_id: u64, message: &CollabServerMessage) {
+        let sent = match self.sessions.get(&client_id) {
+            Some((session, _)) => send(session, message),
+            None => return,
+        };
+        if !sent {
+            self.remove(client_id);
+        }
+    }
+
+    //the WebSocket of a removed session closes with its channel
+    fn remove(&mut self, client_id: u64) {
+        if self.sessions.remove(&client_id).is_some() {
+            self.broadcast(client_id, &CollabServerMessage::Left { client_id });
+        }
+    }
+
+    //the editors whose access changed are sent away, they load the note again with the new one
+    async fn check_access(&mut self, state: &AppState) {
+        let client_ids: Vec<u64> = self.sessions.keys().copied().collect();
+        for client_id in client_ids {
+            self.recheck(state, client_id).await;
+        }
+    }
+
+    //the session is removed from the room if its access changed
+    async fn recheck(&mut self, state: &AppState, client_id: u64) {
+        let Some((session, _)) = self.sessions.get(&client_id) else {
+            return;
+        };
+        let user_id = session.user_id;
+        let read_only = session.read_only;
+        let permission = match state
+            .permissions
+            .get_permission(self.post_id, user_id)
+            .await
+        {
+            Ok(permission) => permission,
+            //the post was deleted
+            Err(e) if e.error_type == errors::ErrorTypes::NotFound => None,
+            //the last known access stays until the next check
+            Err(e) => {
+                tracing::error!(
+                    "Failed to check the access of user {} to post {}: {}",
+                    user_id,
+                    self.post_id,
+                    e
+                );
+                return;
+            }
+        };
+        if permission.is_some_and(|permission| (permission < Permission::Editor) == read_only) {
+            return;
+        }
+        tracing::info!(
+            "Access of user {} to post {} changed, session {} closed",
+            user_id,
+            self.post_id,
+            client_id
+        );
+        self.send(client_id, &error("collab.access_changed"));
+        self.remove(client_id);
+    }
+
+    //the sessions whose leave message did not fit in the channel
+    fn prune(&mut self) {
+        let closed: Vec<u64> = self
+            .sessions
+            .iter()
+            .filter(|(_, (session, _))| session.sender.is_closed())
+            .map(|(client_id, _)| *client_id)
+            .collect();
+        for client_id in closed {
+            self.remove(client_id);
+        }
+    }
+}
+
+//`false` if the channel of the editor is full or closed
+fn send(session: &Session, message: &CollabServerMessage) -> bool {
+    match encode(message) {
+        Some(text) => session.sender.try_send(text).is_ok(),
+        None => true,
+    }
+}
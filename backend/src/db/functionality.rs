@@ -1,17 +1,47 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-use super::{verify_password_hash, Post, User};
+use super::{
+    effective_permission, member_role, verify_password_hash, Attachment, AuditEvent, AuditFilter,
+    Notebook, Post, PostPermission, ShareLink, User, UserSummary, Workspace, WorkspaceMember,
+};
 use crate::app::DbPool;
 
 use crate::errors;
-use crate::schema::user::NewUser;
-use common::{PostsUpdateForm, ResponsePost};
-use diesel::prelude::*;
+use crate::schema::user::{username_key, Password};
+use crate::tasks::Tasks;
+use common::{
+    Permission, PostsUpdateForm, ResponseMember, ResponsePermission, ResponsePost,
+    ResponseWorkspace, Role, WorkspaceRole,
+};
+use diesel::{
+    migration::MigrationSource, BoolExpressionMethods, ExpressionMethods, OptionalExtension,
+    QueryDsl, SelectableHelper,
+};
+use diesel_async::pooled_connection::deadpool::Object;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use diesel_migrations::EmbeddedMigrations;
 
 use tracing::instrument;
 
+/// The number of rows inserted by one statement of a batch insert.
+pub(crate) const INSERT_CHUNK: usize = 1000;
+
+/// The function `get_connection` takes a connection out of the pool.
+async fn get_connection(connection: &DbPool) -> Result<Object<AsyncPgConnection>, errors::Error> {
+    connection.get().await.map_err(|e| {
+        tracing::error!("Failed to get db connection pool!");
+        errors::Error::new(
+            Some(e.to_string()),
+            Some("Failed to get db connection pool!".to_string()),
+            errors::ErrorTypes::DbError,
+        )
+    })
+}
+
 /// The function `db_add_user` adds a new user to a database, checking if the username already exists
-/// before inserting the user.
+/// before inserting the user. The personal workspace of the user and their owner membership are added
+/// in the same transaction, so there is no user without one.
 ///
 /// Arguments:
 ///
@@ -26,9 +56,9 @@ use tracing::instrument;
 /// The function `db_add_user` returns a `Result<User, errors::Error>`.
 
 #[instrument(name = "Add new user", skip(connection))]
-pub fn db_add_user(user: User, connection: &DbPool) -> Result<User, errors::Error> {
-    use super::schema::users::dsl::{username, users};
-    let mut conn = connection.get().map_err(|e| {
+pub async fn db_add_user(user: User, connection: &DbPool) -> Result<User, errors::Error> {
+    use super::schema::users::dsl::{username_key, users};
+    let mut conn = connection.get().await.map_err(|e| {
         tracing::error!("Failed to get db connection pool!");
 
         errors::Error::new(
@@ -39,26 +69,51 @@ pub fn db_add_user(user: User, connection: &DbPool) -> Result<User, errors::Erro
     })?;
 
     let result = users
-        .filter(username.eq(&user.username))
+        .filter(username_key.eq(&user.username_key))
         .select(User::as_select())
-        .first(&mut conn);
+        .first(&mut conn)
+        .await;
 
     if result.is_ok() {
         tracing::error!("The username already exists!");
-        return Err(errors::Error::new(
-            None,
-            Some("The username already exists!".to_string()),
-            errors::ErrorTypes::Auth(errors::Auth::Authentication),
+        return Err(errors::Error::from_code(
+            "auth.username_taken",
+            errors::ErrorTypes::Conflict,
         ));
     }
 
-    diesel::insert_into(users)
-        .values(&user)
-        .execute(&mut conn)
-        .map_err(|e| {
-            tracing::error!("Failed to add user: {:?} to the database!", user);
-            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
-        })?;
+    let workspace = Workspace::personal(&user);
+    let member = WorkspaceMember {
+        workspace_id: workspace.id,
+        user_id: user.id,
+        role: WorkspaceRole::Owner.to_string(),
+        created_at: workspace.created_at,
+    };
+    let new_user = &user;
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        async move {
+            use super::schema::{workspace_members, workspaces};
+            diesel::insert_into(users)
+                .values(new_user)
+                .execute(conn)
+                .await?;
+            diesel::insert_into(workspaces::table)
+                .values(&workspace)
+                .execute(conn)
+                .await?;
+            diesel::insert_into(workspace_members::table)
+                .values(&member)
+                .execute(conn)
+                .await?;
+            Ok(())
+        }
+        .scope_boxed()
+    })
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to add user: {:?} to the database!", user);
+        errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+    })?;
     tracing::info!("User: {:?} added successfully!", user);
 
     Ok(user)
@@ -68,19 +123,26 @@ pub fn db_add_user(user: User, connection: &DbPool) -> Result<User, errors::Erro
 ///
 /// Arguments:
 ///
-/// * `user`: The `user` parameter is of type `NewUser`, which represents the data of a new user that
-/// needs to be checked in the database. It contains the username and password of the user.
+/// * `name`: The `name` parameter is the username to look for, it is compared in any case.
+/// * `password`: The `password` parameter is the password to verify. The password policy is not
+/// applied, so passwords set under an older policy keep working.
 /// * `connection`: The `connection` parameter is of type `DbPool`, which is a connection pool to the
 /// database. It is used to establish a connection to the database and perform database operations.
+/// * `tasks`: The `tasks` parameter runs the password check on the thread pool.
 ///
 /// Returns:
 ///
 /// The function `db_check_user` returns a `Result<User, errors::Error>`.
 
-#[instrument(name = "Check logged in data", skip(connection))]
-pub fn db_check_user(user: NewUser, connection: &DbPool) -> Result<User, errors::Error> {
-    use super::schema::users::dsl::{username, users};
-    let mut conn = connection.get().map_err(|e| {
+#[instrument(name = "Check logged in data", skip(connection, tasks))]
+pub async fn db_check_user(
+    name: &str,
+    password: Password,
+    connection: &DbPool,
+    tasks: &Tasks,
+) -> Result<User, errors::Error> {
+    use super::schema::users::dsl::users;
+    let mut conn = connection.get().await.map_err(|e| {
         tracing::error!("Failed to get db connection pool!");
         errors::Error::new(
             Some(e.to_string()),
@@ -90,19 +152,24 @@ pub fn db_check_user(user: NewUser, connection: &DbPool) -> Result<User, errors:
     })?;
 
     let founded_user = users
-        .filter(username.eq(&user.username.0))
+        .filter(super::schema::users::username_key.eq(username_key(name)))
         .select(User::as_select())
         .first(&mut conn)
+        .await
         .map_err(|e| {
-            tracing::info!("User with username {} not found!", &user.username.0);
-            errors::Error::new(
-                Some(e.to_string()),
-                Some("User not found. Try to check your username!".to_string()),
+            tracing::info!("User with username {} not found!", name);
+            errors::Error::from_code(
+                "auth.unknown_user",
                 errors::ErrorTypes::Auth(errors::Auth::Authentication),
             )
+            .with_cause(e)
         })?;
 
-    verify_password_hash(&founded_user.password, &user.password.0)?;
+    //CPU heavy, it does not hold up the async worker
+    let hash = founded_user.password.clone();
+    tasks
+        .block(move || verify_password_hash(&hash, &password.0))
+        .await??;
     tracing::info!("User: {:?} verified!", founded_user);
 
     Ok(founded_user)
@@ -122,9 +189,9 @@ pub fn db_check_user(user: NewUser, connection: &DbPool) -> Result<User, errors:
 ///
 /// The function `db_find_user` returns a `Result<User, errors::Error>`.
 #[instrument(name = "Find the user in db", skip(connection))]
-pub fn db_find_user(user_id: uuid::Uuid, connection: &DbPool) -> Result<User, errors::Error> {
+pub async fn db_find_user(user_id: uuid::Uuid, connection: &DbPool) -> Result<User, errors::Error> {
     use super::schema::users::dsl::{id, users};
-    let mut conn = connection.get().map_err(|e| {
+    let mut conn = connection.get().await.map_err(|e| {
         tracing::error!("Failed to get db connection pool!");
 
         errors::Error::new(
@@ -138,6 +205,7 @@ pub fn db_find_user(user_id: uuid::Uuid, connection: &DbPool) -> Result<User, er
         .filter(id.eq(&user_id))
         .select(User::as_returning())
         .first(&mut conn)
+        .await
         .map_err(|e| {
             tracing::error!("Failed to find user with id: {}", user_id);
             errors::Error::new(
@@ -151,13 +219,15 @@ pub fn db_find_user(user_id: uuid::Uuid, connection: &DbPool) -> Result<User, er
     Ok(user)
 }
 
-/// The function `db_get_posts` retrieves all posts from the database, along with information about
-/// whether each post is marked as important for a specific user.
+/// The function `db_get_posts` retrieves the posts of a workspace, along with information about
+/// whether each post is marked as important for the user. The personal workspace of the user also
+/// lists the posts other users shared with them.
 ///
 /// Arguments:
 ///
 /// * `user_id`: The `user_id` parameter is of type `uuid::Uuid` and represents the ID of the user for
 /// whom we want to retrieve the posts.
+/// * `workspace_id`: The `workspace_id` parameter is the workspace, the user is a member of it.
 /// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
 /// connection pool. It is used to establish a connection to the database and execute queries.
 ///
@@ -167,28 +237,59 @@ pub fn db_find_user(user_id: uuid::Uuid, connection: &DbPool) -> Result<User, er
 /// `errors::Error` if there was an error retrieving the posts from the database.
 
 #[instrument(name = "Get all posts", skip(connection))]
-pub fn db_get_posts(
+pub async fn db_get_posts(
     user_id: uuid::Uuid,
+    workspace_id: uuid::Uuid,
     connection: &DbPool,
 ) -> Result<Vec<ResponsePost>, errors::Error> {
-    use super::schema::important_posts;
-    use super::schema::posts;
+    use super::schema::{important_posts, post_permissions, posts, users, workspaces};
+    let mut conn = get_connection(connection).await?;
 
-    let mut conn = connection.get().map_err(|e| {
-        tracing::error!("Failed to get db connection pool!");
+    //the roles of the posts shared with the user
+    let roles: HashMap<uuid::Uuid, String> = post_permissions::table
+        .filter(post_permissions::user_id.eq(user_id))
+        .select((post_permissions::post_id, post_permissions::role))
+        .load::<(uuid::Uuid, String)>(&mut conn)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get the posts shared with user {}", user_id);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?
+        .into_iter()
+        .collect();
+    let members = db_memberships(&mut conn, user_id).await?;
 
-        errors::Error::new(
-            Some(e.to_string()),
-            Some("Failed to get db connection pool!".to_string()),
-            errors::ErrorTypes::DbError,
-        )
-    })?;
+    let personal: bool = workspaces::table
+        .find(workspace_id)
+        .select(workspaces::personal)
+        .first(&mut conn)
+        .await
+        .optional()
+        .map_err(|e| errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError))?
+        .ok_or_else(workspace_not_found)?;
 
-    //get all available posts
-    let all_posts: Vec<Post> = posts::table
-        .select(Post::as_returning())
+    //get the posts of the workspace, and the shared ones in the personal workspace, with their authors
+    let mut query = posts::table
+        .inner_join(users::table)
+        .select((Post::as_select(), users::username))
+        .into_boxed();
+    query = match personal {
+        true => {
+            let shared = post_permissions::table
+                .filter(post_permissions::user_id.eq(user_id))
+                .select(post_permissions::post_id);
+            query.filter(
+                posts::workspace_id
+                    .eq(workspace_id)
+                    .or(posts::id.eq_any(shared)),
+            )
+        }
+        false => query.filter(posts::workspace_id.eq(workspace_id)),
+    };
+    let all_posts: Vec<(Post, String)> = query
         .order(posts::created_at.desc())
         .load(&mut conn)
+        .await
         .map_err(|e| {
             tracing::error!("Failed to get all posts");
             errors::Error::new(
@@ -203,6 +304,7 @@ pub fn db_get_posts(
         .select(important_posts::post_id)
         .filter(important_posts::user_id.eq(user_id))
         .load(&mut conn)
+        .await
         .map_err(|e| {
             tracing::error!("Failed to get important posts");
             errors::Error::new(
@@ -217,25 +319,190 @@ pub fn db_get_posts(
     //group important and not posts
     let mut response_posts: Vec<ResponsePost> = all_posts
         .into_iter()
-        .map(|post| {
+        .filter_map(|(post, owner)| {
             let important = important_posts.contains(&post.id);
-            ResponsePost {
-                id: post.id,
-                title: post.title,
-                description: post.description,
-                important,
-                created_at: post.created_at,
-            }
+            let permission = effective_permission(
+                post.user_id == user_id,
+                roles.get(&post.id).map(String::as_str),
+                members.get(&post.workspace_id).map(String::as_str),
+            )?;
+            Some(post.response(owner, important, permission))
         })
         .collect();
 
     //sort the posts so that important posts come first
     response_posts.sort_by(|a, b| b.important.cmp(&a.important));
 
-    tracing::info!("Got posts:{:?} from db!", response_posts);
+    tracing::info!("Got {} posts from db!", response_posts.len());
 
     Ok(response_posts)
 }
+/// The function `db_get_user_posts` returns a page of the posts of a workspace. The pages are ordered
+/// by the creation time, so they can be read one after another without loading all posts.
+///
+/// Arguments:
+///
+/// * `user_id`: The `user_id` parameter is the member who reads the posts, the important flags are the
+/// ones of this user.
+/// * `workspace_id`: The `workspace_id` parameter is the workspace.
+/// * `offset`: The `offset` parameter is the number of posts to skip.
+/// * `limit`: The `limit` parameter is the maximum number of posts in the page.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_get_user_posts` returns a `Result<Vec<ResponsePost>, errors::Error>`, oldest post
+/// first.
+#[instrument(name = "Get a page of workspace's posts", skip(connection))]
+pub async fn db_get_user_posts(
+    user_id: uuid::Uuid,
+    workspace_id: uuid::Uuid,
+    offset: i64,
+    limit: i64,
+    connection: &DbPool,
+) -> Result<Vec<ResponsePost>, errors::Error> {
+    use super::schema::{important_posts, posts, users};
+    let mut conn = get_connection(connection).await?;
+    let members = db_memberships(&mut conn, user_id).await?;
+    let member = members.get(&workspace_id).map(String::as_str);
+
+    let page: Vec<(Post, String)> = posts::table
+        .inner_join(users::table)
+        .filter(posts::workspace_id.eq(workspace_id))
+        .select((Post::as_select(), users::username))
+        .order((posts::created_at.asc(), posts::id.asc()))
+        .limit(limit)
+        .offset(offset)
+        .load(&mut conn)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get the posts of workspace {}", workspace_id);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    let ids: Vec<uuid::Uuid> = page.iter().map(|(post, _)| post.id).collect();
+    let important: HashSet<uuid::Uuid> = important_posts::table
+        .select(important_posts::post_id)
+        .filter(important_posts::user_id.eq(user_id))
+        .filter(important_posts::post_id.eq_any(ids))
+        .load(&mut conn)
+        .await
+        .map_err(|e| errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError))?
+        .into_iter()
+        .collect();
+
+    Ok(page
+        .into_iter()
+        .filter_map(|(post, owner)| {
+            let important = important.contains(&post.id);
+            let permission = effective_permission(post.user_id == user_id, None, member)?;
+            Some(post.response(owner, important, permission))
+        })
+        .collect())
+}
+
+/// The function `db_find_post` finds a post by id.
+///
+/// Arguments:
+///
+/// * `post_id`: The `post_id` parameter is the id of the post.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_find_post` returns a `Result<Post, errors::Error>`, `post.not_found` if there is
+/// no such post.
+#[instrument(name = "Find post", skip(connection))]
+pub async fn db_find_post(post_id: uuid::Uuid, connection: &DbPool) -> Result<Post, errors::Error> {
+    use super::schema::posts::dsl::{id, posts};
+    let mut conn = get_connection(connection).await?;
+
+    posts
+        .filter(id.eq(post_id))
+        .select(Post::as_select())
+        .first(&mut conn)
+        .await
+        .optional()
+        .map_err(|e| {
+            tracing::error!("Failed to find post {}", post_id);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?
+        .ok_or_else(|| post_not_found(post_id))
+}
+
+/// The function `db_update_description` replaces the description of a post, for example when the
+/// images uploaded for it are embedded.
+///
+/// Arguments:
+///
+/// * `post_id`: The `post_id` parameter is the id of the post.
+/// * `description`: The `description` parameter is the new description.
+/// * `connection`: The `connection` parameter is the database connection pool.
+#[instrument(name = "Update post description", skip(description, connection))]
+pub async fn db_update_description(
+    post_id: uuid::Uuid,
+    description: Option<String>,
+    connection: &DbPool,
+) -> Result<(), errors::Error> {
+    use super::schema::posts::dsl;
+    let mut conn = get_connection(connection).await?;
+
+    let updated = diesel::update(dsl::posts.filter(dsl::id.eq(post_id)))
+        .set(dsl::description.eq(description))
+        .execute(&mut conn)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to update the description of post {}", post_id);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    match updated {
+        0 => Err(post_not_found(post_id)),
+        _ => Ok(()),
+    }
+}
+
+/// The function `db_delete_post` deletes a post. The attachments, share links and permissions are
+/// deleted with it by the foreign keys.
+///
+/// Arguments:
+///
+/// * `post_id`: The `post_id` parameter is the id of the post.
+/// * `connection`: The `connection` parameter is the database connection pool.
+#[instrument(name = "Delete post", skip(connection))]
+pub async fn db_delete_post(post_id: uuid::Uuid, connection: &DbPool) -> Result<(), errors::Error> {
+    use super::schema::{important_posts, posts};
+    let mut conn = get_connection(connection).await?;
+
+    let deleted = conn
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            async move {
+                diesel::delete(important_posts::table.filter(important_posts::post_id.eq(post_id)))
+                    .execute(conn)
+                    .await?;
+                diesel::delete(posts::table.filter(posts::id.eq(post_id)))
+                    .execute(conn)
+                    .await
+            }
+            .scope_boxed()
+        })
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to delete post {}", post_id);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    match deleted {
+        0 => Err(post_not_found(post_id)),
+        _ => {
+            tracing::info!("Post {} deleted", post_id);
+            Ok(())
+        }
+    }
+}
+
 /// The function `db_add_post` adds a new post to the database using a connection pool.
 ///
 /// Arguments:
@@ -251,9 +518,9 @@ pub fn db_get_posts(
 /// The function `db_add_post` returns a `Result<Post, errors::Error>`.
 
 #[instrument(name = "Add new post", skip(connection))]
-pub fn db_add_post(post: Post, connection: &DbPool) -> Result<ResponsePost, errors::Error> {
+pub async fn db_add_post(post: Post, connection: &DbPool) -> Result<ResponsePost, errors::Error> {
     use super::schema::posts::dsl::*;
-    let mut conn = connection.get().map_err(|e| {
+    let mut conn = connection.get().await.map_err(|e| {
         tracing::error!("Failed to get db connection pool!");
 
         errors::Error::new(
@@ -266,23 +533,83 @@ pub fn db_add_post(post: Post, connection: &DbPool) -> Result<ResponsePost, erro
     let db_post: Post = diesel::insert_into(posts)
         .values(&post)
         .get_result(&mut conn)
+        .await
         .map_err(|e| {
-            tracing::error!("Failed to add new post: {:?} to the database!", post);
+            tracing::error!("Failed to add new post: {} to the database!", post.id);
             errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
         })?;
 
-    let post = ResponsePost {
-        id: db_post.id,
-        important: false,
-        title: db_post.title,
-        description: db_post.description,
-        created_at: db_post.created_at,
-    };
-    tracing::info!("Post: {:?} added successfully!", post);
+    let owner = db_username(&mut conn, db_post.user_id).await?;
+    let post = db_post.response(owner, false, Permission::Owner);
+    tracing::info!("Post: {} added successfully!", post.id);
 
     Ok(post)
 }
 
+/// The function `db_add_posts` adds many posts and their important flags in one transaction, so
+/// either all posts are added or none.
+///
+/// Arguments:
+///
+/// * `new_posts`: The `new_posts` parameter lists the posts, the ones paired with `true` are marked
+/// important for their author.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_add_posts` returns a `Result<usize, errors::Error>` with the number of added posts.
+#[instrument(name = "Add many posts", skip_all, fields(count = new_posts.len()))]
+pub async fn db_add_posts(
+    new_posts: Vec<(Post, bool)>,
+    connection: &DbPool,
+) -> Result<usize, errors::Error> {
+    use super::schema::{important_posts, posts};
+    let mut conn = get_connection(connection).await?;
+
+    let important: Vec<_> = new_posts
+        .iter()
+        .filter(|(_, important)| *important)
+        .map(|(post, _)| {
+            (
+                important_posts::user_id.eq(post.user_id),
+                important_posts::post_id.eq(post.id),
+            )
+        })
+        .collect();
+    let new_posts: Vec<Post> = new_posts.into_iter().map(|(post, _)| post).collect();
+
+    let added = conn
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            async move {
+                let mut added = 0;
+                //stay below the limit of bind parameters of a statement
+                for chunk in new_posts.chunks(INSERT_CHUNK) {
+                    added += diesel::insert_into(posts::table)
+                        .values(chunk)
+                        .execute(conn)
+                        .await?;
+                }
+                for chunk in important.chunks(INSERT_CHUNK) {
+                    diesel::insert_into(important_posts::table)
+                        .values(chunk)
+                        .execute(conn)
+                        .await?;
+                }
+                Ok(added)
+            }
+            .scope_boxed()
+        })
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to add the posts!");
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+    tracing::info!("{} posts added successfully!", added);
+
+    Ok(added)
+}
+
 /// The function `db_update_post` updates the important field of a post in the database based on the
 /// provided user ID and post data.
 ///
@@ -300,13 +627,13 @@ pub fn db_add_post(post: Post, connection: &DbPool) -> Result<ResponsePost, erro
 ///
 /// The function `db_update_post` returns a `Result<(), errors::Error>`.
 #[instrument(name = "Update posts's impotant field!", skip(connection))]
-pub fn db_update_post(
+pub async fn db_update_post(
     user_id: uuid::Uuid,
     data: PostsUpdateForm,
     connection: &DbPool,
 ) -> Result<(), errors::Error> {
     use super::schema::important_posts;
-    let mut conn = connection.get().map_err(|e| {
+    let mut conn = connection.get().await.map_err(|e| {
         tracing::error!("Failed to get db connection pool!");
 
         errors::Error::new(
@@ -316,6 +643,11 @@ pub fn db_update_post(
         )
     })?;
 
+    //only the posts the user can see are marked
+    if db_permission(&mut conn, data.id, user_id).await?.is_none() {
+        return Err(post_not_found(data.id));
+    }
+
     if data.important {
         // Insert into important_posts if the post should be marked as important
         diesel::insert_into(important_posts::table)
@@ -324,6 +656,7 @@ pub fn db_update_post(
                 important_posts::post_id.eq(data.id),
             ))
             .execute(&mut conn)
+            .await
             .map_err(|e| {
                 tracing::error!("Failed to make current post important!");
                 errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
@@ -336,6 +669,7 @@ pub fn db_update_post(
                 .filter(important_posts::post_id.eq(data.id)),
         )
         .execute(&mut conn)
+        .await
         .map_err(|e| {
             tracing::error!("Failed to make current post not important!");
             errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
@@ -345,3 +679,1919 @@ pub fn db_update_post(
 
     Ok(())
 }
+
+/// The function `user_not_found` is the error for admin operations on a missing user.
+pub(crate) fn user_not_found(user: impl std::fmt::Display) -> errors::Error {
+    tracing::error!("User {} not found!", user);
+    errors::Error {
+        message: Some(format!("User {} not found!", user)),
+        ..errors::Error::from_code("user.not_found", errors::ErrorTypes::NotFound)
+    }
+}
+
+/// The function `post_not_found` is the error for a post that does not exist.
+pub(crate) fn post_not_found(post_id: uuid::Uuid) -> errors::Error {
+    tracing::info!("Post {} not found!", post_id);
+    errors::Error::from_code("post.not_found", errors::ErrorTypes::NotFound)
+}
+
+/// The function `attachment_not_found` is the error for an attachment that does not exist.
+pub(crate) fn attachment_not_found(attachment_id: uuid::Uuid) -> errors::Error {
+    tracing::info!("Attachment {} not found!", attachment_id);
+    errors::Error::from_code("attachment.not_found", errors::ErrorTypes::NotFound)
+}
+
+/// The function `permission_not_found` is the error for a user who has no granted access to a post.
+pub(crate) fn permission_not_found() -> errors::Error {
+    errors::Error::from_code("permission.not_found", errors::ErrorTypes::NotFound)
+}
+
+/// The function `permission_exists` is the error for granting access to a user who already has it.
+pub(crate) fn permission_exists() -> errors::Error {
+    errors::Error::from_code("permission.exists", errors::ErrorTypes::Conflict)
+}
+
+/// The function `workspace_not_found` is the error for a workspace that does not exist or the user is
+/// not a member of.
+pub(crate) fn workspace_not_found() -> errors::Error {
+    errors::Error::from_code("workspace.not_found", errors::ErrorTypes::NotFound)
+}
+
+/// The function `member_not_found` is the error for a user who is not a member of the workspace.
+pub(crate) fn member_not_found() -> errors::Error {
+    errors::Error::from_code("workspace.member_not_found", errors::ErrorTypes::NotFound)
+}
+
+/// The function `member_exists` is the error for inviting a user who is a member already.
+pub(crate) fn member_exists() -> errors::Error {
+    errors::Error::from_code("workspace.member_exists", errors::ErrorTypes::Conflict)
+}
+
+/// The function `notebook_not_found` is the error for a notebook that does not exist.
+pub(crate) fn notebook_not_found() -> errors::Error {
+    errors::Error::from_code("notebook.not_found", errors::ErrorTypes::NotFound)
+}
+
+/// The function `share_link_not_found` is the error for a share link that does not exist. Expired
+/// links get it too, so a visitor can not tell them apart.
+pub(crate) fn share_link_not_found() -> errors::Error {
+    tracing::info!("Share link not found!");
+    errors::Error::from_code("share.not_found", errors::ErrorTypes::NotFound)
+}
+
+/// The function `quota_exceeded` is the error for an upload that does not fit in the quota of the
+/// user.
+pub(crate) fn quota_exceeded(user_id: uuid::Uuid) -> errors::Error {
+    tracing::info!("Storage quota of user {} exceeded!", user_id);
+    errors::Error::from_code(
+        "attachment.quota_exceeded",
+        errors::ErrorTypes::PayloadTooLarge,
+    )
+}
+
+/// The function `db_find_user_by_username` finds a user by username without checking the password.
+///
+/// Arguments:
+///
+/// * `name`: The `name` parameter is the username to look for, it is compared in any case.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_find_user_by_username` returns a `Result<User, errors::Error>`.
+#[instrument(name = "Find the user by username", skip(connection))]
+pub async fn db_find_user_by_username(
+    name: &str,
+    connection: &DbPool,
+) -> Result<User, errors::Error> {
+    use super::schema::users::dsl::users;
+    let mut conn = get_connection(connection).await?;
+
+    users
+        .filter(super::schema::users::username_key.eq(username_key(name)))
+        .select(User::as_select())
+        .first(&mut conn)
+        .await
+        .optional()
+        .map_err(|e| errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError))?
+        .ok_or_else(|| user_not_found(name))
+}
+
+/// The function `db_list_users` lists all users with the number of their posts.
+///
+/// Arguments:
+///
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_list_users` returns a `Result<Vec<UserSummary>, errors::Error>`.
+#[instrument(name = "List users", skip(connection))]
+pub async fn db_list_users(connection: &DbPool) -> Result<Vec<UserSummary>, errors::Error> {
+    use super::schema::{posts, users};
+    let mut conn = get_connection(connection).await?;
+
+    let all_users: Vec<User> = users::table
+        .select(User::as_select())
+        .order(users::created_at.asc())
+        .load(&mut conn)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get all users");
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    let counts: HashMap<uuid::Uuid, i64> = posts::table
+        .group_by(posts::user_id)
+        .select((posts::user_id, diesel::dsl::count_star()))
+        .load(&mut conn)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to count posts");
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?
+        .into_iter()
+        .collect();
+
+    Ok(summarize_users(all_users, &counts))
+}
+
+/// The function `summarize_users` joins the users with their post counts.
+pub(crate) fn summarize_users(
+    all_users: Vec<User>,
+    counts: &HashMap<uuid::Uuid, i64>,
+) -> Vec<UserSummary> {
+    all_users
+        .into_iter()
+        .map(|user| UserSummary {
+            posts: counts.get(&user.id).copied().unwrap_or(0),
+            id: user.id,
+            role: user.role(),
+            username: user.username,
+            disabled: user.disabled,
+            created_at: user.created_at,
+        })
+        .collect()
+}
+
+/// The function `db_update_password` replaces the password hash of a user.
+///
+/// Arguments:
+///
+/// * `user_id`: The `user_id` parameter is the id of the user.
+/// * `password_hash`: The `password_hash` parameter is the new argon2 hash.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_update_password` returns a `Result<(), errors::Error>`.
+#[instrument(name = "Update user's password", skip(password_hash, connection))]
+pub async fn db_update_password(
+    user_id: uuid::Uuid,
+    password_hash: String,
+    connection: &DbPool,
+) -> Result<(), errors::Error> {
+    use super::schema::users::dsl::{id, password, updated_at, users};
+    let mut conn = get_connection(connection).await?;
+
+    let updated = diesel::update(users.filter(id.eq(user_id)))
+        .set((
+            password.eq(password_hash),
+            updated_at.eq(time::OffsetDateTime::now_utc()),
+        ))
+        .execute(&mut conn)
+        .await
+        .map_err(|e| errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError))?;
+
+    if updated == 0 {
+        return Err(user_not_found(user_id));
+    }
+    tracing::info!("Password of user {} updated", user_id);
+
+    Ok(())
+}
+
+/// The function `db_set_disabled` disables or enables a user account.
+///
+/// Arguments:
+///
+/// * `user_id`: The `user_id` parameter is the id of the user.
+/// * `value`: The `value` parameter is `true` to disable the account.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_set_disabled` returns a `Result<(), errors::Error>`.
+#[instrument(name = "Disable user", skip(connection))]
+pub async fn db_set_disabled(
+    user_id: uuid::Uuid,
+    value: bool,
+    connection: &DbPool,
+) -> Result<(), errors::Error> {
+    use super::schema::users::dsl::{disabled, id, updated_at, users};
+    let mut conn = get_connection(connection).await?;
+
+    let updated = diesel::update(users.filter(id.eq(user_id)))
+        .set((
+            disabled.eq(value),
+            updated_at.eq(time::OffsetDateTime::now_utc()),
+        ))
+        .execute(&mut conn)
+        .await
+        .map_err(|e| errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError))?;
+
+    if updated == 0 {
+        return Err(user_not_found(user_id));
+    }
+    tracing::info!("User {} disabled: {}", user_id, value);
+
+    Ok(())
+}
+
+/// The function `db_delete_user` deletes a user, their posts, personal workspace, attachment records
+/// and all important flags that point to the user or to their posts, in one transaction.
+///
+/// Arguments:
+///
+/// * `user_id`: The `user_id` parameter is the id of the user.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_delete_user` returns a `Result<(), errors::Error>`.
+#[instrument(name = "Delete user", skip(connection))]
+pub async fn db_delete_user(user_id: uuid::Uuid, connection: &DbPool) -> Result<(), errors::Error> {
+    use super::schema::{
+        attachments, important_posts, posts, users, workspace_members, workspaces,
+    };
+    let mut conn = get_connection(connection).await?;
+
+    let deleted = conn
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            async move {
+                diesel::delete(attachments::table.filter(attachments::user_id.eq(user_id)))
+                    .execute(conn)
+                    .await?;
+                let user_posts = posts::table
+                    .filter(posts::user_id.eq(user_id))
+                    .select(posts::id);
+                diesel::delete(
+                    important_posts::table.filter(
+                        important_posts::user_id
+                            .eq(user_id)
+                            .or(important_posts::post_id.eq_any(user_posts)),
+                    ),
+                )
+                .execute(conn)
+                .await?;
+                diesel::delete(posts::table.filter(posts::user_id.eq(user_id)))
+                    .execute(conn)
+                    .await?;
+                //the personal workspace goes with its owner, the team ones stay
+                let memberships = workspace_members::table
+                    .filter(workspace_members::user_id.eq(user_id))
+                    .select(workspace_members::workspace_id);
+                diesel::delete(
+                    workspaces::table
+                        .filter(workspaces::personal.eq(true))
+                        .filter(workspaces::id.eq_any(memberships)),
+                )
+                .execute(conn)
+                .await?;
+                diesel::delete(users::table.filter(users::id.eq(user_id)))
+                    .execute(conn)
+                    .await
+            }
+            .scope_boxed()
+        })
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to delete user {}", user_id);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    if deleted == 0 {
+        return Err(user_not_found(user_id));
+    }
+    tracing::info!("User {} deleted", user_id);
+
+    Ok(())
+}
+
+/// The function `db_set_role` changes the role of a user.
+///
+/// Arguments:
+///
+/// * `user_id`: The `user_id` parameter is the id of the user.
+/// * `value`: The `value` parameter is the new role.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_set_role` returns a `Result<(), errors::Error>`.
+#[instrument(name = "Set user's role", skip(connection))]
+pub async fn db_set_role(
+    user_id: uuid::Uuid,
+    value: Role,
+    connection: &DbPool,
+) -> Result<(), errors::Error> {
+    use super::schema::users::dsl::{id, role, updated_at, users};
+    let mut conn = get_connection(connection).await?;
+
+    let updated = diesel::update(users.filter(id.eq(user_id)))
+        .set((
+            role.eq(value.as_str()),
+            updated_at.eq(time::OffsetDateTime::now_utc()),
+        ))
+        .execute(&mut conn)
+        .await
+        .map_err(|e| errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError))?;
+
+    if updated == 0 {
+        return Err(user_not_found(user_id));
+    }
+    tracing::info!("Role of user {} changed to {}", user_id, value);
+
+    Ok(())
+}
+
+/// The function `db_revoke_sessions` increases the session version of a user, so the tokens that were
+/// issued before are rejected.
+///
+/// Arguments:
+///
+/// * `user_id`: The `user_id` parameter is the id of the user.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_revoke_sessions` returns the new session version.
+#[instrument(name = "Revoke user's sessions", skip(connection))]
+pub async fn db_revoke_sessions(
+    user_id: uuid::Uuid,
+    connection: &DbPool,
+) -> Result<i32, errors::Error> {
+    use super::schema::users::dsl::{id, session_version, users};
+    let mut conn = get_connection(connection).await?;
+
+    let version = diesel::update(users.filter(id.eq(user_id)))
+        .set(session_version.eq(session_version + 1))
+        .returning(session_version)
+        .get_result(&mut conn)
+        .await
+        .optional()
+        .map_err(|e| errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError))?
+        .ok_or_else(|| user_not_found(user_id))?;
+    tracing::info!("Sessions of user {} revoked", user_id);
+
+    Ok(version)
+}
+
+/// The function `db_add_audit_event` writes an event to the audit log.
+///
+/// Arguments:
+///
+/// * `event`: The `event` parameter is the event to write.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_add_audit_event` returns a `Result<(), errors::Error>`.
+#[instrument(name = "Add audit event", skip(connection))]
+pub async fn db_add_audit_event(
+    event: AuditEvent,
+    connection: &DbPool,
+) -> Result<(), errors::Error> {
+    use super::schema::audit_events::dsl::audit_events;
+    let mut conn = get_connection(connection).await?;
+
+    diesel::insert_into(audit_events)
+        .values(&event)
+        .execute(&mut conn)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to write audit event!");
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    Ok(())
+}
+
+/// The function `db_get_audit_events` returns the audit events that match the filter, newest first.
+///
+/// Arguments:
+///
+/// * `filter`: The `filter` parameter holds the optional conditions and the page.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_get_audit_events` returns a `Result<Vec<AuditEvent>, errors::Error>`.
+#[instrument(name = "Get audit events", skip(connection))]
+pub async fn db_get_audit_events(
+    filter: AuditFilter,
+    connection: &DbPool,
+) -> Result<Vec<AuditEvent>, errors::Error> {
+    use super::schema::audit_events::dsl::*;
+    let mut conn = get_connection(connection).await?;
+
+    let mut query = audit_events.select(AuditEvent::as_select()).into_boxed();
+    if let Some(value) = filter.actor_id {
+        query = query.filter(actor_id.eq(value));
+    }
+    if let Some(value) = &filter.action {
+        query = query.filter(action.eq(value.clone()));
+    }
+    if let Some(value) = filter.target_id {
+        query = query.filter(target_id.eq(value));
+    }
+    if let Some(value) = filter.success {
+        query = query.filter(success.eq(value));
+    }
+    if let Some(value) = filter.since {
+        query = query.filter(created_at.ge(value));
+    }
+    if let Some(value) = filter.until {
+        query = query.filter(created_at.lt(value));
+    }
+
+    query
+        .order(created_at.desc())
+        .limit(filter.limit())
+        .offset(filter.offset())
+        .load(&mut conn)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get audit events!");
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })
+}
+
+/// The function `db_delete_audit_events_before` deletes the audit events older than `before`.
+///
+/// Arguments:
+///
+/// * `before`: The `before` parameter is the oldest time that is kept.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_delete_audit_events_before` returns the number of deleted events.
+#[instrument(name = "Delete old audit events", skip(connection))]
+pub async fn db_delete_audit_events_before(
+    before: time::OffsetDateTime,
+    connection: &DbPool,
+) -> Result<usize, errors::Error> {
+    use super::schema::audit_events::dsl::{audit_events, created_at};
+    let mut conn = get_connection(connection).await?;
+
+    diesel::delete(audit_events.filter(created_at.lt(before)))
+        .execute(&mut conn)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to delete old audit events!");
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })
+}
+
+/// The function `db_add_attachment` adds an attachment record. The row of the owner is locked while
+/// the size of their attachments is summed, so parallel uploads can not exceed the quota together.
+///
+/// Arguments:
+///
+/// * `attachment`: The `attachment` parameter is the new record.
+/// * `quota`: The `quota` parameter is the most bytes the owner can store, `None` is no limit.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_add_attachment` returns the added record, or `attachment.quota_exceeded`.
+#[instrument(name = "Add attachment", skip(connection))]
+pub async fn db_add_attachment(
+    attachment: Attachment,
+    quota: Option<i64>,
+    connection: &DbPool,
+) -> Result<Attachment, errors::Error> {
+    use super::schema::{attachments, users};
+    let mut conn = get_connection(connection).await?;
+    let owner = attachment.user_id;
+
+    let added = conn
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            async move {
+                if let Some(quota) = quota {
+                    users::table
+                        .filter(users::id.eq(owner))
+                        .select(users::id)
+                        .for_update()
+                        .first::<uuid::Uuid>(conn)
+                        .await?;
+                    let sizes: Vec<i64> = attachments::table
+                        .filter(attachments::user_id.eq(owner))
+                        .select(attachments::size)
+                        .load(conn)
+                        .await?;
+                    if sizes.iter().sum::<i64>() + attachment.size > quota {
+                        return Ok(None);
+                    }
+                }
+                diesel::insert_into(attachments::table)
+                    .values(&attachment)
+                    .returning(Attachment::as_returning())
+                    .get_result(conn)
+                    .await
+                    .map(Some)
+            }
+            .scope_boxed()
+        })
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to add attachment!");
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?
+        .ok_or_else(|| quota_exceeded(owner))?;
+    tracing::info!("Attachment {} added to post {}", added.id, added.post_id);
+
+    Ok(added)
+}
+
+/// The function `db_find_attachment` finds an attachment record by id.
+///
+/// Arguments:
+///
+/// * `attachment_id`: The `attachment_id` parameter is the id of the attachment.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_find_attachment` returns the record, or `attachment.not_found`.
+#[instrument(name = "Find attachment", skip(connection))]
+pub async fn db_find_attachment(
+    attachment_id: uuid::Uuid,
+    connection: &DbPool,
+) -> Result<Attachment, errors::Error> {
+    use super::schema::attachments::dsl::{attachments, id};
+    let mut conn = get_connection(connection).await?;
+
+    attachments
+        .filter(id.eq(attachment_id))
+        .select(Attachment::as_select())
+        .first(&mut conn)
+        .await
+        .optional()
+        .map_err(|e| {
+            tracing::error!("Failed to find attachment {}", attachment_id);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?
+        .ok_or_else(|| attachment_not_found(attachment_id))
+}
+
+/// The function `db_get_post_attachments` returns the attachment records of a post, oldest first.
+///
+/// Arguments:
+///
+/// * `post`: The `post` parameter is the id of the post.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_get_post_attachments` returns a `Result<Vec<Attachment>, errors::Error>`.
+#[instrument(name = "Get post's attachments", skip(connection))]
+pub async fn db_get_post_attachments(
+    post: uuid::Uuid,
+    connection: &DbPool,
+) -> Result<Vec<Attachment>, errors::Error> {
+    use super::schema::attachments::dsl::{attachments, created_at, id, post_id};
+    let mut conn = get_connection(connection).await?;
+
+    attachments
+        .filter(post_id.eq(post))
+        .select(Attachment::as_select())
+        .order((created_at.asc(), id.asc()))
+        .load(&mut conn)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get the attachments of post {}", post);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })
+}
+
+/// The function `db_delete_attachment` deletes an attachment record. The content stays in the blob
+/// store, see `AttachmentRepository::is_hash_used`.
+///
+/// Arguments:
+///
+/// * `attachment_id`: The `attachment_id` parameter is the id of the attachment.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_delete_attachment` returns a `Result<(), errors::Error>`.
+#[instrument(name = "Delete attachment", skip(connection))]
+pub async fn db_delete_attachment(
+    attachment_id: uuid::Uuid,
+    connection: &DbPool,
+) -> Result<(), errors::Error> {
+    use super::schema::attachments::dsl::{attachments, id};
+    let mut conn = get_connection(connection).await?;
+
+    let deleted = diesel::delete(attachments.filter(id.eq(attachment_id)))
+        .execute(&mut conn)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to delete attachment {}", attachment_id);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    if deleted == 0 {
+        return Err(attachment_not_found(attachment_id));
+    }
+    tracing::info!("Attachment {} deleted", attachment_id);
+
+    Ok(())
+}
+
+/// The function `db_get_user_hashes` returns the content hashes of the attachments of a user.
+///
+/// Arguments:
+///
+/// * `owner`: The `owner` parameter is the id of the user.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_get_user_hashes` returns the hashes without duplicates.
+#[instrument(name = "Get user's attachment hashes", skip(connection))]
+pub async fn db_get_user_hashes(
+    owner: uuid::Uuid,
+    connection: &DbPool,
+) -> Result<Vec<String>, errors::Error> {
+    use super::schema::attachments::dsl::{attachments, hash, user_id};
+    let mut conn = get_connection(connection).await?;
+
+    attachments
+        .filter(user_id.eq(owner))
+        .select(hash)
+        .distinct()
+        .load(&mut conn)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get the attachments of user {}", owner);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })
+}
+
+/// The function `db_is_hash_used` checks if any attachment has the content with the hash.
+///
+/// Arguments:
+///
+/// * `value`: The `value` parameter is the hex SHA-256 hash of the content.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_is_hash_used` returns a `Result<bool, errors::Error>`.
+#[instrument(name = "Check attachment hash", skip(connection))]
+pub async fn db_is_hash_used(value: &str, connection: &DbPool) -> Result<bool, errors::Error> {
+    use super::schema::attachments::dsl::{attachments, hash};
+    let mut conn = get_connection(connection).await?;
+
+    diesel::select(diesel::dsl::exists(attachments.filter(hash.eq(value))))
+        .get_result(&mut conn)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to check attachment hash!");
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })
+}
+
+/// The function `db_add_share_link` adds a share link of a post.
+///
+/// Arguments:
+///
+/// * `link`: The `link` parameter is the new link.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_add_share_link` returns the inserted link.
+#[instrument(name = "Add share link", skip(connection))]
+pub async fn db_add_share_link(
+    link: ShareLink,
+    connection: &DbPool,
+) -> Result<ShareLink, errors::Error> {
+    use super::schema::share_links::dsl::share_links;
+    let mut conn = get_connection(connection).await?;
+
+    diesel::insert_into(share_links)
+        .values(&link)
+        .returning(ShareLink::as_returning())
+        .get_result(&mut conn)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to add a share link of post {}", link.post_id);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })
+}
+
+/// The function `db_find_share_link` finds a share link by id.
+///
+/// Arguments:
+///
+/// * `link_id`: The `link_id` parameter is the id of the link.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_find_share_link` returns the link, or `share.not_found`.
+#[instrument(name = "Find share link", skip(connection))]
+pub async fn db_find_share_link(
+    link_id: uuid::Uuid,
+    connection: &DbPool,
+) -> Result<ShareLink, errors::Error> {
+    use super::schema::share_links::dsl::{id, share_links};
+    let mut conn = get_connection(connection).await?;
+
+    share_links
+        .filter(id.eq(link_id))
+        .select(ShareLink::as_select())
+        .first(&mut conn)
+        .await
+        .optional()
+        .map_err(|e| {
+            tracing::error!("Failed to find share link {}", link_id);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?
+        .ok_or_else(share_link_not_found)
+}
+
+/// The function `db_find_share_link_by_token` finds a share link by the hash of its token.
+///
+/// Arguments:
+///
+/// * `value`: The `value` parameter is the hex SHA-256 hash of the token.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_find_share_link_by_token` returns the link, or `share.not_found`.
+#[instrument(name = "Find share link by token", skip_all)]
+pub async fn db_find_share_link_by_token(
+    value: &str,
+    connection: &DbPool,
+) -> Result<ShareLink, errors::Error> {
+    use super::schema::share_links::dsl::{share_links, token_hash};
+    let mut conn = get_connection(connection).await?;
+
+    share_links
+        .filter(token_hash.eq(value))
+        .select(ShareLink::as_select())
+        .first(&mut conn)
+        .await
+        .optional()
+        .map_err(|e| {
+            tracing::error!("Failed to find a share link!");
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?
+        .ok_or_else(share_link_not_found)
+}
+
+/// The function `db_get_post_share_links` returns the share links of a post, oldest first.
+///
+/// Arguments:
+///
+/// * `post`: The `post` parameter is the id of the post.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_get_post_share_links` returns a `Result<Vec<ShareLink>, errors::Error>`.
+#[instrument(name = "Get post's share links", skip(connection))]
+pub async fn db_get_post_share_links(
+    post: uuid::Uuid,
+    connection: &DbPool,
+) -> Result<Vec<ShareLink>, errors::Error> {
+    use super::schema::share_links::dsl::{created_at, id, post_id, share_links};
+    let mut conn = get_connection(connection).await?;
+
+    share_links
+        .filter(post_id.eq(post))
+        .select(ShareLink::as_select())
+        .order((created_at.asc(), id.asc()))
+        .load(&mut conn)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get the share links of post {}", post);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })
+}
+
+/// The function `db_delete_share_link` deletes a share link.
+///
+/// Arguments:
+///
+/// * `link_id`: The `link_id` parameter is the id of the link.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_delete_share_link` returns `share.not_found` if there was no such link.
+#[instrument(name = "Delete share link", skip(connection))]
+pub async fn db_delete_share_link(
+    link_id: uuid::Uuid,
+    connection: &DbPool,
+) -> Result<(), errors::Error> {
+    use super::schema::share_links::dsl::{id, share_links};
+    let mut conn = get_connection(connection).await?;
+
+    let deleted = diesel::delete(share_links.filter(id.eq(link_id)))
+        .execute(&mut conn)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to delete share link {}", link_id);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    if deleted == 0 {
+        return Err(share_link_not_found());
+    }
+    tracing::info!("Share link {} deleted", link_id);
+
+    Ok(())
+}
+
+/// The function `db_username` returns the username of a user, the owner shown with the posts.
+async fn db_username(
+    conn: &mut AsyncPgConnection,
+    user: uuid::Uuid,
+) -> Result<String, errors::Error> {
+    use super::schema::users::dsl::{username, users};
+
+    users
+        .find(user)
+        .select(username)
+        .first(conn)
+        .await
+        .optional()
+        .map_err(|e| {
+            tracing::error!("Failed to find user {}", user);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?
+        .ok_or_else(|| user_not_found(user))
+}
+
+/// The function `db_permission` returns what a user may do with a post: `Owner` for the author, the
+/// higher one of the granted role and the one the membership in the workspace of the post gives for
+/// the other users, and `None` if they have neither.
+async fn db_permission(
+    conn: &mut AsyncPgConnection,
+    post: uuid::Uuid,
+    user: uuid::Uuid,
+) -> Result<Option<Permission>, errors::Error> {
+    use super::schema::{post_permissions, posts, workspace_members};
+
+    let (owner, workspace): (uuid::Uuid, uuid::Uuid) = posts::table
+        .find(post)
+        .select((posts::user_id, posts::workspace_id))
+        .first(conn)
+        .await
+        .optional()
+        .map_err(|e| {
+            tracing::error!("Failed to find post {}", post);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?
+        .ok_or_else(|| post_not_found(post))?;
+    if owner == user {
+        return Ok(Some(Permission::Owner));
+    }
+
+    let role: Option<String> = post_permissions::table
+        .find((post, user))
+        .select(post_permissions::role)
+        .first(conn)
+        .await
+        .optional()
+        .map_err(|e| {
+            tracing::error!("Failed to get the permission of user {} to {}", user, post);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+    let member: Option<String> = workspace_members::table
+        .find((workspace, user))
+        .select(workspace_members::role)
+        .first(conn)
+        .await
+        .optional()
+        .map_err(|e| {
+            tracing::error!("Failed to get the membership of user {}", user);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    Ok(effective_permission(
+        false,
+        role.as_deref(),
+        member.as_deref(),
+    ))
+}
+
+/// The function `db_memberships` returns the roles of a user by the workspaces they are a member of.
+async fn db_memberships(
+    conn: &mut AsyncPgConnection,
+    user: uuid::Uuid,
+) -> Result<HashMap<uuid::Uuid, String>, errors::Error> {
+    use super::schema::workspace_members;
+
+    Ok(workspace_members::table
+        .filter(workspace_members::user_id.eq(user))
+        .select((workspace_members::workspace_id, workspace_members::role))
+        .load::<(uuid::Uuid, String)>(conn)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get the workspaces of user {}", user);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?
+        .into_iter()
+        .collect())
+}
+
+/// The function `db_get_permission` returns what a user may do with a post.
+///
+/// Arguments:
+///
+/// * `post`: The `post` parameter is the id of the post.
+/// * `user`: The `user` parameter is the id of the user.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_get_permission` returns `Owner` for the author, the granted role for the users the
+/// post is shared with, `None` for the others and `post.not_found` if there is no such post.
+#[instrument(name = "Get permission", skip(connection))]
+pub async fn db_get_permission(
+    post: uuid::Uuid,
+    user: uuid::Uuid,
+    connection: &DbPool,
+) -> Result<Option<Permission>, errors::Error> {
+    let mut conn = get_connection(connection).await?;
+    db_permission(&mut conn, post, user).await
+}
+
+/// The function `db_edit_post` changes the title and the description of a post.
+///
+/// Arguments:
+///
+/// * `user_id`: The `user_id` parameter is the user who edits the post, its author or an editor.
+/// * `post`: The `post` parameter holds the id and the new content of the post.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_edit_post` returns the post as the user sees it, `post.not_found` if they can not
+/// see it and `auth.forbidden` if they may only view it.
+#[instrument(name = "Edit post", skip(connection))]
+pub async fn db_edit_post(
+    user_id: uuid::Uuid,
+    post: Post,
+    connection: &DbPool,
+) -> Result<ResponsePost, errors::Error> {
+    use super::schema::{important_posts, posts};
+    let mut conn = get_connection(connection).await?;
+
+    let permission = db_permission(&mut conn, post.id, user_id)
+        .await?
+        .ok_or_else(|| post_not_found(post.id))?;
+    if permission < Permission::Editor {
+        return Err(errors::Error::from_code(
+            "auth.forbidden",
+            errors::ErrorTypes::Auth(errors::Auth::Authorization),
+        ));
+    }
+
+    let edited: Post = diesel::update(posts::table.find(post.id))
+        .set((
+            posts::title.eq(post.title),
+            posts::description.eq(post.description),
+        ))
+        .returning(Post::as_returning())
+        .get_result(&mut conn)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to edit post {}", post.id);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+    let important = important_posts::table
+        .find((user_id, edited.id))
+        .count()
+        .get_result::<i64>(&mut conn)
+        .await
+        .map_err(|e| errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError))?
+        > 0;
+    let owner = db_username(&mut conn, edited.user_id).await?;
+    tracing::info!("Post {} edited by user {}", edited.id, user_id);
+
+    Ok(edited.response(owner, important, permission))
+}
+
+/// The function `db_grant_permission` gives a user access to a post.
+///
+/// Arguments:
+///
+/// * `permission`: The `permission` parameter is the granted access.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_grant_permission` returns the inserted permission, or `permission.exists` if the
+/// user already has access.
+#[instrument(name = "Grant permission", skip(connection))]
+pub async fn db_grant_permission(
+    permission: PostPermission,
+    connection: &DbPool,
+) -> Result<PostPermission, errors::Error> {
+    use super::schema::post_permissions::dsl::post_permissions;
+    let mut conn = get_connection(connection).await?;
+
+    diesel::insert_into(post_permissions)
+        .values(&permission)
+        .on_conflict_do_nothing()
+        .returning(PostPermission::as_returning())
+        .get_result(&mut conn)
+        .await
+        .optional()
+        .map_err(|e| {
+            tracing::error!("Failed to share post {}", permission.post_id);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?
+        .ok_or_else(permission_exists)
+}
+
+/// The function `db_update_permission` changes the role of a user who has access to a post.
+///
+/// Arguments:
+///
+/// * `post`: The `post` parameter is the id of the post.
+/// * `user`: The `user` parameter is the id of the user.
+/// * `role`: The `role` parameter is the new role.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_update_permission` returns `permission.not_found` if the user has no access.
+#[instrument(name = "Update permission", skip(connection))]
+pub async fn db_update_permission(
+    post: uuid::Uuid,
+    user: uuid::Uuid,
+    role: Permission,
+    connection: &DbPool,
+) -> Result<(), errors::Error> {
+    use super::schema::post_permissions::dsl;
+    let mut conn = get_connection(connection).await?;
+
+    let updated = diesel::update(dsl::post_permissions.find((post, user)))
+        .set(dsl::role.eq(role.as_str()))
+        .execute(&mut conn)
+        .await
+        .map_err(|e| {
+            tracing::error!(
+                "Failed to change the permission of user {} to {}",
+                user,
+                post
+            );
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    match updated {
+        0 => Err(permission_not_found()),
+        _ => Ok(()),
+    }
+}
+
+/// The function `db_revoke_permission` takes the access to a post away from a user.
+///
+/// Arguments:
+///
+/// * `post`: The `post` parameter is the id of the post.
+/// * `user`: The `user` parameter is the id of the user.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_revoke_permission` returns `permission.not_found` if the user had no access.
+#[instrument(name = "Revoke permission", skip(connection))]
+pub async fn db_revoke_permission(
+    post: uuid::Uuid,
+    user: uuid::Uuid,
+    connection: &DbPool,
+) -> Result<(), errors::Error> {
+    use super::schema::{important_posts, post_permissions};
+    let mut conn = get_connection(connection).await?;
+
+    let deleted = conn
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            async move {
+                //the post leaves the list of the user with its important flag
+                diesel::delete(important_posts::table.find((user, post)))
+                    .execute(conn)
+                    .await?;
+                diesel::delete(post_permissions::table.find((post, user)))
+                    .execute(conn)
+                    .await
+            }
+            .scope_boxed()
+        })
+        .await
+        .map_err(|e| {
+            tracing::error!(
+                "Failed to revoke the permission of user {} to {}",
+                user,
+                post
+            );
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    if deleted == 0 {
+        return Err(permission_not_found());
+    }
+    tracing::info!("Permission of user {} to post {} revoked", user, post);
+
+    Ok(())
+}
+
+/// The function `db_get_post_permissions` returns the users a post is shared with, in the order they
+/// got the access.
+///
+/// Arguments:
+///
+/// * `post`: The `post` parameter is the id of the post.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_get_post_permissions` returns a `Result<Vec<ResponsePermission>, errors::Error>`.
+#[instrument(name = "Get post's permissions", skip(connection))]
+pub async fn db_get_post_permissions(
+    post: uuid::Uuid,
+    connection: &DbPool,
+) -> Result<Vec<ResponsePermission>, errors::Error> {
+    use super::schema::{post_permissions, users};
+    let mut conn = get_connection(connection).await?;
+
+    let rows: Vec<(PostPermission, String)> = post_permissions::table
+        .inner_join(users::table)
+        .filter(post_permissions::post_id.eq(post))
+        .select((PostPermission::as_select(), users::username))
+        .order((post_permissions::created_at.asc(), users::username.asc()))
+        .load(&mut conn)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get the permissions of post {}", post);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(permission, username)| permission.response(username))
+        .collect())
+}
+
+/// The function `db_add_workspace` adds a workspace with its owner as the first member, in one
+/// transaction.
+///
+/// Arguments:
+///
+/// * `workspace`: The `workspace` parameter is the new workspace.
+/// * `owner`: The `owner` parameter is the id of the user who creates it.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_add_workspace` returns a `Result<Workspace, errors::Error>`.
+#[instrument(name = "Add workspace", skip(connection))]
+pub async fn db_add_workspace(
+    workspace: Workspace,
+    owner: uuid::Uuid,
+    connection: &DbPool,
+) -> Result<Workspace, errors::Error> {
+    use super::schema::{workspace_members, workspaces};
+    let mut conn = get_connection(connection).await?;
+
+    let member = WorkspaceMember {
+        workspace_id: workspace.id,
+        user_id: owner,
+        role: WorkspaceRole::Owner.to_string(),
+        created_at: workspace.created_at,
+    };
+    let workspace = conn
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            async move {
+                let workspace: Workspace = diesel::insert_into(workspaces::table)
+                    .values(&workspace)
+                    .returning(Workspace::as_returning())
+                    .get_result(conn)
+                    .await?;
+                diesel::insert_into(workspace_members::table)
+                    .values(&member)
+                    .execute(conn)
+                    .await?;
+                Ok(workspace)
+            }
+            .scope_boxed()
+        })
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to add a workspace of user {}", owner);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+    tracing::info!("Workspace {} added successfully!", workspace.id);
+
+    Ok(workspace)
+}
+
+/// The function `db_get_user_workspaces` returns the workspaces a user is a member of.
+///
+/// Arguments:
+///
+/// * `user`: The `user` parameter is the id of the user.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_get_user_workspaces` returns the workspaces with the role of the user, the personal
+/// one first and then the oldest first.
+#[instrument(name = "Get user's workspaces", skip(connection))]
+pub async fn db_get_user_workspaces(
+    user: uuid::Uuid,
+    connection: &DbPool,
+) -> Result<Vec<ResponseWorkspace>, errors::Error> {
+    use super::schema::{workspace_members, workspaces};
+    let mut conn = get_connection(connection).await?;
+
+    let rows: Vec<(Workspace, String)> = workspaces::table
+        .inner_join(workspace_members::table)
+        .filter(workspace_members::user_id.eq(user))
+        .select((Workspace::as_select(), workspace_members::role))
+        .order((
+            workspaces::personal.desc(),
+            workspaces::created_at.asc(),
+            workspaces::id.asc(),
+        ))
+        .load(&mut conn)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get the workspaces of user {}", user);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(workspace, role)| workspace.response(member_role(&role)))
+        .collect())
+}
+
+/// The function `db_find_membership` returns a workspace as a member sees it.
+///
+/// Arguments:
+///
+/// * `workspace`: The `workspace` parameter is the id of the workspace.
+/// * `user`: The `user` parameter is the id of the user.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_find_membership` returns the workspace with the role of the user, `None` if the
+/// user is not a member or there is no such workspace.
+#[instrument(name = "Find membership", skip(connection))]
+pub async fn db_find_membership(
+    workspace: uuid::Uuid,
+    user: uuid::Uuid,
+    connection: &DbPool,
+) -> Result<Option<ResponseWorkspace>, errors::Error> {
+    use super::schema::{workspace_members, workspaces};
+    let mut conn = get_connection(connection).await?;
+
+    let row: Option<(Workspace, String)> = workspaces::table
+        .inner_join(workspace_members::table)
+        .filter(workspaces::id.eq(workspace))
+        .filter(workspace_members::user_id.eq(user))
+        .select((Workspace::as_select(), workspace_members::role))
+        .first(&mut conn)
+        .await
+        .optional()
+        .map_err(|e| {
+            tracing::error!("Failed to find the membership of user {}", user);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    Ok(row.map(|(workspace, role)| workspace.response(member_role(&role))))
+}
+
+/// The function `db_rename_workspace` changes the name of a workspace.
+///
+/// Arguments:
+///
+/// * `workspace`: The `workspace` parameter is the id of the workspace.
+/// * `value`: The `value` parameter is the new name.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_rename_workspace` returns `workspace.not_found` if there is no such workspace.
+#[instrument(name = "Rename workspace", skip(connection))]
+pub async fn db_rename_workspace(
+    workspace: uuid::Uuid,
+    value: String,
+    connection: &DbPool,
+) -> Result<(), errors::Error> {
+    use super::schema::workspaces::dsl::{name, workspaces};
+    let mut conn = get_connection(connection).await?;
+
+    let updated = diesel::update(workspaces.find(workspace))
+        .set(name.eq(value))
+        .execute(&mut conn)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to rename workspace {}", workspace);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    match updated {
+        0 => Err(workspace_not_found()),
+        _ => Ok(()),
+    }
+}
+
+/// The function `db_add_member` adds a user to a workspace.
+///
+/// Arguments:
+///
+/// * `member`: The `member` parameter is the new membership.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_add_member` returns the membership, `workspace.member_exists` if the user is a
+/// member already.
+#[instrument(name = "Add workspace member", skip(connection))]
+pub async fn db_add_member(
+    member: WorkspaceMember,
+    connection: &DbPool,
+) -> Result<WorkspaceMember, errors::Error> {
+    use super::schema::workspace_members::dsl::workspace_members;
+    let mut conn = get_connection(connection).await?;
+
+    diesel::insert_into(workspace_members)
+        .values(&member)
+        .on_conflict_do_nothing()
+        .returning(WorkspaceMember::as_returning())
+        .get_result(&mut conn)
+        .await
+        .optional()
+        .map_err(|e| {
+            tracing::error!(
+                "Failed to add a member to workspace {}",
+                member.workspace_id
+            );
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?
+        .ok_or_else(member_exists)
+}
+
+/// The function `db_update_member` changes the role of a member of a workspace.
+///
+/// Arguments:
+///
+/// * `workspace`: The `workspace` parameter is the id of the workspace.
+/// * `user`: The `user` parameter is the id of the member.
+/// * `role`: The `role` parameter is the new role.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_update_member` returns `workspace.member_not_found` if the user is not a member.
+#[instrument(name = "Update workspace member", skip(connection))]
+pub async fn db_update_member(
+    workspace: uuid::Uuid,
+    user: uuid::Uuid,
+    role: WorkspaceRole,
+    connection: &DbPool,
+) -> Result<(), errors::Error> {
+    use super::schema::workspace_members::dsl;
+    let mut conn = get_connection(connection).await?;
+
+    let updated = diesel::update(dsl::workspace_members.find((workspace, user)))
+        .set(dsl::role.eq(role.as_str()))
+        .execute(&mut conn)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to change the role of user {}", user);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    match updated {
+        0 => Err(member_not_found()),
+        _ => Ok(()),
+    }
+}
+
+/// The function `db_remove_member` removes a user from a workspace. The posts they wrote stay in it.
+///
+/// Arguments:
+///
+/// * `workspace`: The `workspace` parameter is the id of the workspace.
+/// * `user`: The `user` parameter is the id of the member.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_remove_member` returns `workspace.member_not_found` if the user was not a member.
+#[instrument(name = "Remove workspace member", skip(connection))]
+pub async fn db_remove_member(
+    workspace: uuid::Uuid,
+    user: uuid::Uuid,
+    connection: &DbPool,
+) -> Result<(), errors::Error> {
+    use super::schema::workspace_members::dsl::workspace_members;
+    let mut conn = get_connection(connection).await?;
+
+    let deleted = diesel::delete(workspace_members.find((workspace, user)))
+        .execute(&mut conn)
+        .await
+        .map_err(|e| {
+            tracing::error!(
+                "Failed to remove user {} from workspace {}",
+                user,
+                workspace
+            );
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    if deleted == 0 {
+        return Err(member_not_found());
+    }
+    tracing::info!("User {} removed from workspace {}", user, workspace);
+
+    Ok(())
+}
+
+/// The function `db_get_members` returns the members of a workspace, in the order they joined.
+///
+/// Arguments:
+///
+/// * `workspace`: The `workspace` parameter is the id of the workspace.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_get_members` returns a `Result<Vec<ResponseMember>, errors::Error>`.
+#[instrument(name = "Get workspace members", skip(connection))]
+pub async fn db_get_members(
+    workspace: uuid::Uuid,
+    connection: &DbPool,
+) -> Result<Vec<ResponseMember>, errors::Error> {
+    use super::schema::{users, workspace_members};
+    let mut conn = get_connection(connection).await?;
+
+    let rows: Vec<(WorkspaceMember, String)> = workspace_members::table
+        .inner_join(users::table)
+        .filter(workspace_members::workspace_id.eq(workspace))
+        .select((WorkspaceMember::as_select(), users::username))
+        .order((workspace_members::created_at.asc(), users::username.asc()))
+        .load(&mut conn)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get the members of workspace {}", workspace);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(member, username)| member.response(username))
+        .collect())
+}
+
+/// The function `db_add_notebook` adds a notebook to a workspace.
+///
+/// Arguments:
+///
+/// * `notebook`: The `notebook` parameter is the new notebook.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_add_notebook` returns a `Result<Notebook, errors::Error>`.
+#[instrument(name = "Add notebook", skip(connection))]
+pub async fn db_add_notebook(
+    notebook: Notebook,
+    connection: &DbPool,
+) -> Result<Notebook, errors::Error> {
+    use super::schema::notebooks;
+    let mut conn = get_connection(connection).await?;
+
+    let notebook: Notebook = diesel::insert_into(notebooks::table)
+        .values(&notebook)
+        .returning(Notebook::as_returning())
+        .get_result(&mut conn)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to add notebook {}", notebook.id);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+    tracing::info!("Notebook {} added successfully!", notebook.id);
+
+    Ok(notebook)
+}
+
+/// The function `db_get_notebooks` returns the notebooks of a workspace.
+///
+/// Arguments:
+///
+/// * `workspace`: The `workspace` parameter is the id of the workspace.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_get_notebooks` returns the notebooks, oldest first, with the number of the posts
+/// right in each of them.
+#[instrument(name = "Get notebooks", skip(connection))]
+pub async fn db_get_notebooks(
+    workspace: uuid::Uuid,
+    connection: &DbPool,
+) -> Result<Vec<(Notebook, i64)>, errors::Error> {
+    use super::schema::{notebooks, posts};
+    let mut conn = get_connection(connection).await?;
+
+    let notebooks: Vec<Notebook> = notebooks::table
+        .filter(notebooks::workspace_id.eq(workspace))
+        .select(Notebook::as_select())
+        .order((notebooks::created_at.asc(), notebooks::id.asc()))
+        .load(&mut conn)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get the notebooks of workspace {}", workspace);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    //the number of the posts of every notebook that has some
+    let counts: HashMap<Option<uuid::Uuid>, i64> = posts::table
+        .filter(posts::workspace_id.eq(workspace))
+        .filter(posts::notebook_id.is_not_null())
+        .group_by(posts::notebook_id)
+        .select((posts::notebook_id, diesel::dsl::count_star()))
+        .load::<(Option<uuid::Uuid>, i64)>(&mut conn)
+        .await
+        .map_err(|e| errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError))?
+        .into_iter()
+        .collect();
+
+    Ok(notebooks
+        .into_iter()
+        .map(|notebook| {
+            let notes = counts.get(&Some(notebook.id)).copied().unwrap_or(0);
+            (notebook, notes)
+        })
+        .collect())
+}
+
+/// The function `db_find_notebook` finds a notebook by id.
+///
+/// Arguments:
+///
+/// * `notebook`: The `notebook` parameter is the id of the notebook.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_find_notebook` returns the notebook, `notebook.not_found` if there is no such
+/// notebook.
+#[instrument(name = "Find notebook", skip(connection))]
+pub async fn db_find_notebook(
+    notebook: uuid::Uuid,
+    connection: &DbPool,
+) -> Result<Notebook, errors::Error> {
+    use super::schema::notebooks;
+    let mut conn = get_connection(connection).await?;
+
+    notebooks::table
+        .find(notebook)
+        .select(Notebook::as_select())
+        .first(&mut conn)
+        .await
+        .optional()
+        .map_err(|e| {
+            tracing::error!("Failed to find notebook {}", notebook);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?
+        .ok_or_else(notebook_not_found)
+}
+
+/// The function `db_update_notebook` renames a notebook and moves it into another one.
+///
+/// Arguments:
+///
+/// * `notebook`: The `notebook` parameter is the id of the notebook.
+/// * `value`: The `value` parameter is the new name.
+/// * `parent`: The `parent` parameter is the new parent, `None` for the top level.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_update_notebook` returns the changed notebook, `notebook.not_found` if there is no
+/// such notebook.
+#[instrument(name = "Update notebook", skip(connection))]
+pub async fn db_update_notebook(
+    notebook: uuid::Uuid,
+    value: String,
+    parent: Option<uuid::Uuid>,
+    connection: &DbPool,
+) -> Result<Notebook, errors::Error> {
+    use super::schema::notebooks;
+    let mut conn = get_connection(connection).await?;
+
+    diesel::update(notebooks::table.find(notebook))
+        .set((notebooks::name.eq(value), notebooks::parent_id.eq(parent)))
+        .returning(Notebook::as_returning())
+        .get_result(&mut conn)
+        .await
+        .optional()
+        .map_err(|e| {
+            tracing::error!("Failed to update notebook {}", notebook);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?
+        .ok_or_else(notebook_not_found)
+}
+
+/// The function `db_delete_notebook` deletes a notebook. The database deletes the notebooks inside it
+/// and takes their posts out of the notebooks.
+///
+/// Arguments:
+///
+/// * `notebook`: The `notebook` parameter is the id of the notebook.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_delete_notebook` returns `notebook.not_found` if there is no such notebook.
+#[instrument(name = "Delete notebook", skip(connection))]
+pub async fn db_delete_notebook(
+    notebook: uuid::Uuid,
+    connection: &DbPool,
+) -> Result<(), errors::Error> {
+    use super::schema::notebooks;
+    let mut conn = get_connection(connection).await?;
+
+    let deleted = diesel::delete(notebooks::table.find(notebook))
+        .execute(&mut conn)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to delete notebook {}", notebook);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    match deleted {
+        0 => Err(notebook_not_found()),
+        _ => Ok(()),
+    }
+}
+
+/// The function `db_move_post` puts a post into a notebook.
+///
+/// Arguments:
+///
+/// * `post`: The `post` parameter is the id of the post.
+/// * `notebook`: The `notebook` parameter is the new notebook, `None` takes the post out of the
+/// notebooks.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_move_post` returns `post.not_found` if there is no such post.
+#[instrument(name = "Move post", skip(connection))]
+pub async fn db_move_post(
+    post: uuid::Uuid,
+    notebook: Option<uuid::Uuid>,
+    connection: &DbPool,
+) -> Result<(), errors::Error> {
+    use super::schema::posts;
+    let mut conn = get_connection(connection).await?;
+
+    let updated = diesel::update(posts::table.find(post))
+        .set(posts::notebook_id.eq(notebook))
+        .execute(&mut conn)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to move post {}", post);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    match updated {
+        0 => Err(post_not_found(post)),
+        _ => Ok(()),
+    }
+}
+
+/// The function `db_get_notebook_posts` returns the posts of a workspace that are in the given
+/// notebooks.
+///
+/// Arguments:
+///
+/// * `user_id`: The `user_id` parameter is the member who reads the posts, the important flags are the
+/// ones of this user.
+/// * `workspace_id`: The `workspace_id` parameter is the workspace.
+/// * `notebook_ids`: The `notebook_ids` parameter lists the notebooks, usually one with all notebooks
+/// inside it.
+/// * `connection`: The `connection` parameter is of type `&DbPool`, which is a reference to a database
+/// connection pool.
+///
+/// Returns:
+///
+/// The function `db_get_notebook_posts` returns the posts, the important ones first and then the newest
+/// first.
+#[instrument(name = "Get notebook posts", skip(connection))]
+pub async fn db_get_notebook_posts(
+    user_id: uuid::Uuid,
+    workspace_id: uuid::Uuid,
+    notebook_ids: Vec<uuid::Uuid>,
+    connection: &DbPool,
+) -> Result<Vec<ResponsePost>, errors::Error> {
+    use super::schema::{important_posts, post_permissions, posts, users};
+    let mut conn = get_connection(connection).await?;
+    let members = db_memberships(&mut conn, user_id).await?;
+    let member = members.get(&workspace_id).map(String::as_str);
+
+    let found: Vec<(Post, String)> = posts::table
+        .inner_join(users::table)
+        .filter(posts::workspace_id.eq(workspace_id))
+        .filter(posts::notebook_id.eq_any(notebook_ids))
+        .select((Post::as_select(), users::username))
+        .order(posts::created_at.desc())
+        .load(&mut conn)
+        .await
+        .map_err(|e| {
+            tracing::error!(
+                "Failed to get the notebook posts of workspace {}",
+                workspace_id
+            );
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    let ids: Vec<uuid::Uuid> = found.iter().map(|(post, _)| post.id).collect();
+    let important: HashSet<uuid::Uuid> = important_posts::table
+        .select(important_posts::post_id)
+        .filter(important_posts::user_id.eq(user_id))
+        .filter(important_posts::post_id.eq_any(&ids))
+        .load(&mut conn)
+        .await
+        .map_err(|e| errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError))?
+        .into_iter()
+        .collect();
+    //a post of the workspace can be shared with the member too, with a higher role
+    let roles: HashMap<uuid::Uuid, String> = post_permissions::table
+        .filter(post_permissions::user_id.eq(user_id))
+        .filter(post_permissions::post_id.eq_any(&ids))
+        .select((post_permissions::post_id, post_permissions::role))
+        .load::<(uuid::Uuid, String)>(&mut conn)
+        .await
+        .map_err(|e| errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError))?
+        .into_iter()
+        .collect();
+
+    let mut response_posts: Vec<ResponsePost> = found
+        .into_iter()
+        .filter_map(|(post, owner)| {
+            let important = important.contains(&post.id);
+            let permission = effective_permission(
+                post.user_id == user_id,
+                roles.get(&post.id).map(String::as_str),
+                member,
+            )?;
+            Some(post.response(owner, important, permission))
+        })
+        .collect();
+    response_posts.sort_by(|a, b| b.important.cmp(&a.important));
+
+    Ok(response_posts)
+}
+
+/// The function `db_notify` sends a notification to the listeners of a channel, the other instances
+/// of the server.
+///
+/// Arguments:
+///
+/// * `channel`: The `channel` parameter is the name of the channel.
+/// * `payload`: The `payload` parameter is the text of the notification, shorter than 8000 bytes.
+/// * `connection`: The `connection` parameter is the database connection pool.
+#[instrument(name = "Notify", skip(payload, connection))]
+pub async fn db_notify(
+    channel: &str,
+    payload: String,
+    connection: &DbPool,
+) -> Result<(), errors::Error> {
+    let mut conn = get_connection(connection).await?;
+
+    diesel::sql_query("SELECT pg_notify($1, $2)")
+        .bind::<diesel::sql_types::Text, _>(channel)
+        .bind::<diesel::sql_types::Text, _>(payload)
+        .execute(&mut conn)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to notify channel {}", channel);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    Ok(())
+}
+
+/// The `AppliedMigration` struct is a row of the migrations table of `diesel_migrations`.
+#[derive(diesel::QueryableByName)]
+struct AppliedMigration {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    version: String,
+}
+
+/// The function `db_has_pending_migrations` checks if the database schema is behind the binary, like
+/// `has_pending_migrations`, with a connection of the pool. The readiness probes use it, so they do
+/// not open a connection of their own.
+///
+/// Arguments:
+///
+/// * `source`: The `source` parameter is the set of embedded migrations.
+/// * `connection`: The `connection` parameter is the database connection pool.
+///
+/// Returns:
+///
+/// a `Result` with `true` if some embedded migrations are not applied, or an `errors::Error`.
+#[instrument(name = "Check pending migrations", skip_all)]
+pub async fn db_has_pending_migrations(
+    source: EmbeddedMigrations,
+    connection: &DbPool,
+) -> Result<bool, errors::Error> {
+    let mut conn = get_connection(connection).await?;
+    let query_error = |e: diesel::result::Error| {
+        tracing::error!("Failed to check pending migrations!");
+        errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+    };
+
+    //the table is created by the first migration run
+    let present: bool = diesel::select(diesel::dsl::sql::<diesel::sql_types::Bool>(
+        "to_regclass('__diesel_schema_migrations') IS NOT NULL",
+    ))
+    .get_result(&mut conn)
+    .await
+    .map_err(query_error)?;
+    let applied: HashSet<String> = match present {
+        true => diesel::sql_query("SELECT version FROM __diesel_schema_migrations")
+            .load::<AppliedMigration>(&mut conn)
+            .await
+            .map_err(query_error)?
+            .into_iter()
+            .map(|migration| migration.version)
+            .collect(),
+        false => HashSet::new(),
+    };
+
+    let migrations = MigrationSource::<diesel::pg::Pg>::migrations(&source)
+        .map_err(|e| errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError))?;
+    Ok(migrations
+        .iter()
+        .any(|migration| !applied.contains(&migration.name().version().to_string())))
+}
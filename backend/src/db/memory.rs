@@ -0,0 +1,1145 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, MutexGuard};
+
+use async_trait::async_trait;
+use common::{
+    Permission, PostsUpdateForm, ResponseMember, ResponsePermission, ResponsePost,
+    ResponseWorkspace, Role, WorkspaceRole,
+};
+use tracing::instrument;
+
+use super::{
+    attachment_not_found, effective_permission, member_exists, member_not_found, member_role,
+    notebook_not_found, notebook_subtree, permission_exists, permission_not_found, post_not_found,
+    quota_exceeded, share_link_not_found, summarize_users, user_not_found, verify_password_hash,
+    workspace_not_found, Attachment, AttachmentRepository, AuditEvent, AuditFilter,
+    AuditRepository, Notebook, NotebookRepository, NotesRepository, PermissionRepository, Post,
+    PostPermission, SchemaRepository, ShareLink, ShareRepository, User, UserRepository,
+    UserSummary, Workspace, WorkspaceMember, WorkspaceRepository,
+};
+use crate::errors;
+use crate::schema::user::{username_key, Password};
+
+/// The `MemoryData` struct holds the tables of the in-memory storage.
+#[derive(Default)]
+struct MemoryData {
+    users: Vec<User>,
+    posts: Vec<Post>,
+    important_posts: HashSet<(uuid::Uuid, uuid::Uuid)>,
+    attachments: Vec<Attachment>,
+    share_links: Vec<ShareLink>,
+    post_permissions: Vec<PostPermission>,
+    workspaces: Vec<Workspace>,
+    workspace_members: Vec<WorkspaceMember>,
+    notebooks: Vec<Notebook>,
+    audit_events: Vec<AuditEvent>,
+}
+
+impl MemoryData {
+    //the username of a user, the owner shown with the posts
+    fn username(&self, user_id: uuid::Uuid) -> Result<String, errors::Error> {
+        self.users
+            .iter()
+            .find(|u| u.id == user_id)
+            .map(|u| u.username.clone())
+            .ok_or_else(|| user_not_found(user_id))
+    }
+
+    //what a user may do with a post, like `db::db_get_permission`
+    fn permission(
+        &self,
+        post_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+    ) -> Result<Option<Permission>, errors::Error> {
+        let post = self
+            .posts
+            .iter()
+            .find(|p| p.id == post_id)
+            .ok_or_else(|| post_not_found(post_id))?;
+        let granted = self
+            .post_permissions
+            .iter()
+            .find(|p| p.post_id == post_id && p.user_id == user_id)
+            .map(|p| p.role.as_str());
+
+        Ok(effective_permission(
+            post.user_id == user_id,
+            granted,
+            self.member_role(post.workspace_id, user_id),
+        ))
+    }
+
+    //the role of a user in a workspace, `None` if they are not a member
+    fn member_role(&self, workspace_id: uuid::Uuid, user_id: uuid::Uuid) -> Option<&str> {
+        self.workspace_members
+            .iter()
+            .find(|m| m.workspace_id == workspace_id && m.user_id == user_id)
+            .map(|m| m.role.as_str())
+    }
+}
+
+/// The `MemoryRepository` struct implements the repositories without a database. The data is lost
+/// when the process stops, so it is meant for tests and quick local runs.
+#[derive(Default)]
+pub struct MemoryRepository {
+    data: Mutex<MemoryData>,
+}
+
+impl MemoryRepository {
+    pub fn new() -> Self {
+        MemoryRepository::default()
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, MemoryData>, errors::Error> {
+        self.data.lock().map_err(|e| {
+            tracing::error!("In-memory storage is poisoned!");
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })
+    }
+}
+
+#[async_trait]
+impl UserRepository for MemoryRepository {
+    #[instrument(name = "Add new user", skip(self))]
+    async fn add_user(&self, user: User) -> Result<User, errors::Error> {
+        let mut data = self.lock()?;
+
+        if data
+            .users
+            .iter()
+            .any(|u| u.username_key == user.username_key)
+        {
+            tracing::error!("The username already exists!");
+            return Err(errors::Error::from_code(
+                "auth.username_taken",
+                errors::ErrorTypes::Conflict,
+            ));
+        }
+
+        let workspace = Workspace::personal(&user);
+        data.workspace_members.push(WorkspaceMember {
+            workspace_id: workspace.id,
+            user_id: user.id,
+            role: WorkspaceRole::Owner.to_string(),
+            created_at: workspace.created_at,
+        });
+        data.workspaces.push(workspace);
+        data.users.push(user.clone());
+        tracing::info!("User: {:?} added successfully!", user);
+
+        Ok(user)
+    }
+
+    #[instrument(name = "Check logged in data", skip(self))]
+    async fn check_user(&self, username: &str, password: Password) -> Result<User, errors::Error> {
+        let key = username_key(username);
+        let founded_user = self
+            .lock()?
+            .users
+            .iter()
+            .find(|u| u.username_key == key)
+            .cloned()
+            .ok_or_else(|| {
+                tracing::info!("User with username {} not found!", username);
+                errors::Error::from_code(
+                    "auth.unknown_user",
+                    errors::ErrorTypes::Auth(errors::Auth::Authentication),
+                )
+            })?;
+
+        verify_password_hash(&founded_user.password, &password.0)?;
+        tracing::info!("User: {:?} verified!", founded_user);
+
+        Ok(founded_user)
+    }
+
+    #[instrument(name = "Find the user in db", skip(self))]
+    async fn find_user(&self, user_id: uuid::Uuid) -> Result<User, errors::Error> {
+        self.lock()?
+            .users
+            .iter()
+            .find(|u| u.id == user_id)
+            .cloned()
+            .ok_or_else(|| {
+                tracing::error!("Failed to find user with id: {}", user_id);
+                errors::Error::new(
+                    None,
+                    Some("Invalid Uuid inside refresh token!".to_string()),
+                    errors::ErrorTypes::Auth(errors::Auth::Authentication),
+                )
+            })
+    }
+
+    #[instrument(name = "Find the user by username", skip(self))]
+    async fn find_user_by_username(&self, username: &str) -> Result<User, errors::Error> {
+        let key = username_key(username);
+        self.lock()?
+            .users
+            .iter()
+            .find(|u| u.username_key == key)
+            .cloned()
+            .ok_or_else(|| user_not_found(username))
+    }
+
+    #[instrument(name = "List users", skip(self))]
+    async fn list_users(&self) -> Result<Vec<UserSummary>, errors::Error> {
+        let data = self.lock()?;
+
+        let mut counts: HashMap<uuid::Uuid, i64> = HashMap::new();
+        for post in &data.posts {
+            *counts.entry(post.user_id).or_default() += 1;
+        }
+        let mut all_users = data.users.clone();
+        all_users.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+
+        Ok(summarize_users(all_users, &counts))
+    }
+
+    #[instrument(name = "Update user's password", skip(self, password_hash))]
+    async fn update_password(
+        &self,
+        user_id: uuid::Uuid,
+        password_hash: String,
+    ) -> Result<(), errors::Error> {
+        let mut data = self.lock()?;
+        let user = data
+            .users
+            .iter_mut()
+            .find(|u| u.id == user_id)
+            .ok_or_else(|| user_not_found(user_id))?;
+
+        user.password = password_hash;
+        user.updated_at = time::OffsetDateTime::now_utc();
+        tracing::info!("Password of user {} updated", user_id);
+
+        Ok(())
+    }
+
+    #[instrument(name = "Disable user", skip(self))]
+    async fn set_disabled(&self, user_id: uuid::Uuid, disabled: bool) -> Result<(), errors::Error> {
+        let mut data = self.lock()?;
+        let user = data
+            .users
+            .iter_mut()
+            .find(|u| u.id == user_id)
+            .ok_or_else(|| user_not_found(user_id))?;
+
+        user.disabled = disabled;
+        user.updated_at = time::OffsetDateTime::now_utc();
+        tracing::info!("User {} disabled: {}", user_id, disabled);
+
+        Ok(())
+    }
+
+    #[instrument(name = "Delete user", skip(self))]
+    async fn delete_user(&self, user_id: uuid::Uuid) -> Result<(), errors::Error> {
+        let mut data = self.lock()?;
+        if !data.users.iter().any(|u| u.id == user_id) {
+            return Err(user_not_found(user_id));
+        }
+
+        let user_posts: HashSet<uuid::Uuid> = data
+            .posts
+            .iter()
+            .filter(|p| p.user_id == user_id)
+            .map(|p| p.id)
+            .collect();
+        data.important_posts
+            .retain(|(user, post)| *user != user_id && !user_posts.contains(post));
+        data.attachments.retain(|a| a.user_id != user_id);
+        data.share_links
+            .retain(|l| !user_posts.contains(&l.post_id));
+        data.post_permissions
+            .retain(|p| p.user_id != user_id && !user_posts.contains(&p.post_id));
+        data.posts.retain(|p| p.user_id != user_id);
+        //the personal workspace goes with its owner, the team ones stay
+        let personal: HashSet<uuid::Uuid> = data
+            .workspaces
+            .iter()
+            .filter(|w| w.personal && data.member_role(w.id, user_id).is_some())
+            .map(|w| w.id)
+            .collect();
+        data.workspaces.retain(|w| !personal.contains(&w.id));
+        data.workspace_members
+            .retain(|m| m.user_id != user_id && !personal.contains(&m.workspace_id));
+        data.posts.retain(|p| !personal.contains(&p.workspace_id));
+        data.notebooks
+            .retain(|n| !personal.contains(&n.workspace_id));
+        data.users.retain(|u| u.id != user_id);
+        tracing::info!("User {} deleted", user_id);
+
+        Ok(())
+    }
+
+    #[instrument(name = "Set user's role", skip(self))]
+    async fn set_role(&self, user_id: uuid::Uuid, role: Role) -> Result<(), errors::Error> {
+        let mut data = self.lock()?;
+        let user = data
+            .users
+            .iter_mut()
+            .find(|u| u.id == user_id)
+            .ok_or_else(|| user_not_found(user_id))?;
+
+        user.role = role.to_string();
+        user.updated_at = time::OffsetDateTime::now_utc();
+        tracing::info!("Role of user {} changed to {}", user_id, role);
+
+        Ok(())
+    }
+
+    #[instrument(name = "Revoke user's sessions", skip(self))]
+    async fn revoke_sessions(&self, user_id: uuid::Uuid) -> Result<i32, errors::Error> {
+        let mut data = self.lock()?;
+        let user = data
+            .users
+            .iter_mut()
+            .find(|u| u.id == user_id)
+            .ok_or_else(|| user_not_found(user_id))?;
+
+        user.session_version += 1;
+        tracing::info!("Sessions of user {} revoked", user_id);
+
+        Ok(user.session_version)
+    }
+}
+
+#[async_trait]
+impl NotesRepository for MemoryRepository {
+    #[instrument(name = "Get all posts", skip(self))]
+    async fn get_posts(
+        &self,
+        user_id: uuid::Uuid,
+        workspace_id: uuid::Uuid,
+    ) -> Result<Vec<ResponsePost>, errors::Error> {
+        let data = self.lock()?;
+        let personal = data
+            .workspaces
+            .iter()
+            .find(|w| w.id == workspace_id)
+            .map(|w| w.personal)
+            .ok_or_else(workspace_not_found)?;
+
+        let mut response_posts = Vec::new();
+        for post in &data.posts {
+            //the personal workspace lists the posts shared with the user too
+            let shared = personal
+                && data
+                    .post_permissions
+                    .iter()
+                    .any(|p| p.post_id == post.id && p.user_id == user_id);
+            if post.workspace_id != workspace_id && !shared {
+                continue;
+            }
+            let Some(permission) = data.permission(post.id, user_id)? else {
+                continue;
+            };
+            let important = data.important_posts.contains(&(user_id, post.id));
+            let owner = data.username(post.user_id)?;
+            response_posts.push(post.clone().response(owner, important, permission));
+        }
+
+        //newest first, then important posts on top like the SQL backends
+        response_posts.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        response_posts.sort_by(|a, b| b.important.cmp(&a.important));
+
+        tracing::info!("Got {} posts from db!", response_posts.len());
+
+        Ok(response_posts)
+    }
+
+    #[instrument(name = "Get a page of workspace's posts", skip(self))]
+    async fn get_user_posts(
+        &self,
+        user_id: uuid::Uuid,
+        workspace_id: uuid::Uuid,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<ResponsePost>, errors::Error> {
+        let data = self.lock()?;
+        let member = data.member_role(workspace_id, user_id);
+
+        let mut posts: Vec<&Post> = data
+            .posts
+            .iter()
+            .filter(|post| post.workspace_id == workspace_id)
+            .collect();
+        posts.sort_by_key(|post| (post.created_at, post.id));
+
+        let mut page = Vec::new();
+        for post in posts
+            .into_iter()
+            .skip(offset.max(0) as usize)
+            .take(limit.max(0) as usize)
+        {
+            let Some(permission) = effective_permission(post.user_id == user_id, None, member)
+            else {
+                continue;
+            };
+            let important = data.important_posts.contains(&(user_id, post.id));
+            let owner = data.username(post.user_id)?;
+            page.push(post.clone().response(owner, important, permission));
+        }
+
+        Ok(page)
+    }
+
+    #[instrument(name = "Find post", skip(self))]
+    async fn find_post(&self, post_id: uuid::Uuid) -> Result<Post, errors::Error> {
+        self.lock()?
+            .posts
+            .iter()
+            .find(|p| p.id == post_id)
+            .cloned()
+            .ok_or_else(|| post_not_found(post_id))
+    }
+
+    #[instrument(name = "Add new post", skip(self))]
+    async fn add_post(&self, post: Post) -> Result<ResponsePost, errors::Error> {
+        let mut data = self.lock()?;
+
+        if !data.users.iter().any(|u| u.id == post.user_id) {
+            tracing::error!("Failed to add new post: {} to the database!", post.id);
+            return Err(errors::Error::new(
+                Some("posts.user_id references a missing user".to_string()),
+                None,
+                errors::ErrorTypes::DbError,
+            ));
+        }
+
+        let owner = data.username(post.user_id)?;
+        let response = post.clone().response(owner, false, Permission::Owner);
+        data.posts.push(post);
+        tracing::info!("Post: {} added successfully!", response.id);
+
+        Ok(response)
+    }
+
+    #[instrument(name = "Add many posts", skip_all, fields(count = posts.len()))]
+    async fn add_posts(&self, posts: Vec<(Post, bool)>) -> Result<usize, errors::Error> {
+        let mut data = self.lock()?;
+
+        //check everything first, so nothing is added when a post is rejected
+        let missing_user = posts
+            .iter()
+            .any(|(post, _)| !data.users.iter().any(|u| u.id == post.user_id));
+        if missing_user {
+            tracing::error!("Failed to add the posts!");
+            return Err(errors::Error::new(
+                Some("posts.user_id references a missing user".to_string()),
+                None,
+                errors::ErrorTypes::DbError,
+            ));
+        }
+
+        let added = posts.len();
+        for (post, important) in posts {
+            if important {
+                data.important_posts.insert((post.user_id, post.id));
+            }
+            data.posts.push(post);
+        }
+        tracing::info!("{} posts added successfully!", added);
+
+        Ok(added)
+    }
+
+    #[instrument(name = "Update posts's impotant field!", skip(self))]
+    async fn update_post(
+        &self,
+        user_id: uuid::Uuid,
+        data: PostsUpdateForm,
+    ) -> Result<(), errors::Error> {
+        let mut storage = self.lock()?;
+
+        //only the posts the user can see are marked
+        if storage.permission(data.id, user_id)?.is_none() {
+            return Err(post_not_found(data.id));
+        }
+
+        if data.important {
+            //same as the primary key of `important_posts`
+            if !storage.important_posts.insert((user_id, data.id)) {
+                tracing::error!("Failed to make current post important!");
+                return Err(errors::Error::new(
+                    Some("important_posts insert violates a constraint".to_string()),
+                    None,
+                    errors::ErrorTypes::DbError,
+                ));
+            }
+        } else {
+            storage.important_posts.remove(&(user_id, data.id));
+        }
+        tracing::info!("Updated post with id:{:?}", data.id);
+
+        Ok(())
+    }
+
+    #[instrument(name = "Update post description", skip(self, description))]
+    async fn update_description(
+        &self,
+        post_id: uuid::Uuid,
+        description: Option<String>,
+    ) -> Result<(), errors::Error> {
+        let mut storage = self.lock()?;
+        let post = storage
+            .posts
+            .iter_mut()
+            .find(|p| p.id == post_id)
+            .ok_or_else(|| post_not_found(post_id))?;
+        post.description = description;
+
+        Ok(())
+    }
+
+    #[instrument(name = "Edit post", skip(self))]
+    async fn edit_post(
+        &self,
+        user_id: uuid::Uuid,
+        post: Post,
+    ) -> Result<ResponsePost, errors::Error> {
+        let mut storage = self.lock()?;
+
+        let permission = storage
+            .permission(post.id, user_id)?
+            .ok_or_else(|| post_not_found(post.id))?;
+        if permission < Permission::Editor {
+            return Err(errors::Error::from_code(
+                "auth.forbidden",
+                errors::ErrorTypes::Auth(errors::Auth::Authorization),
+            ));
+        }
+
+        let important = storage.important_posts.contains(&(user_id, post.id));
+        let edited = storage
+            .posts
+            .iter_mut()
+            .find(|p| p.id == post.id)
+            .ok_or_else(|| post_not_found(post.id))?;
+        edited.title = post.title;
+        edited.description = post.description;
+        let edited = edited.clone();
+        let owner = storage.username(edited.user_id)?;
+        tracing::info!("Post {} edited by user {}", edited.id, user_id);
+
+        Ok(edited.response(owner, important, permission))
+    }
+
+    #[instrument(name = "Delete post", skip(self))]
+    async fn delete_post(&self, post_id: uuid::Uuid) -> Result<(), errors::Error> {
+        let mut data = self.lock()?;
+        if !data.posts.iter().any(|p| p.id == post_id) {
+            return Err(post_not_found(post_id));
+        }
+
+        data.important_posts.retain(|(_, post)| *post != post_id);
+        data.attachments.retain(|a| a.post_id != post_id);
+        data.share_links.retain(|l| l.post_id != post_id);
+        data.post_permissions.retain(|p| p.post_id != post_id);
+        data.posts.retain(|p| p.id != post_id);
+        tracing::info!("Post {} deleted", post_id);
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AttachmentRepository for MemoryRepository {
+    #[instrument(name = "Add attachment", skip(self))]
+    async fn add_attachment(
+        &self,
+        attachment: Attachment,
+        quota: Option<i64>,
+    ) -> Result<Attachment, errors::Error> {
+        let mut data = self.lock()?;
+
+        if !data.posts.iter().any(|p| p.id == attachment.post_id) {
+            tracing::error!("Failed to add attachment!");
+            return Err(errors::Error::new(
+                Some("attachments.post_id references a missing post".to_string()),
+                None,
+                errors::ErrorTypes::DbError,
+            ));
+        }
+        let used: i64 = data
+            .attachments
+            .iter()
+            .filter(|a| a.user_id == attachment.user_id)
+            .map(|a| a.size)
+            .sum();
+        if quota.is_some_and(|quota| used + attachment.size > quota) {
+            return Err(quota_exceeded(attachment.user_id));
+        }
+
+        data.attachments.push(attachment.clone());
+        tracing::info!(
+            "Attachment {} added to post {}",
+            attachment.id,
+            attachment.post_id
+        );
+
+        Ok(attachment)
+    }
+
+    async fn find_attachment(
+        &self,
+        attachment_id: uuid::Uuid,
+    ) -> Result<Attachment, errors::Error> {
+        self.lock()?
+            .attachments
+            .iter()
+            .find(|a| a.id == attachment_id)
+            .cloned()
+            .ok_or_else(|| attachment_not_found(attachment_id))
+    }
+
+    async fn get_post_attachments(
+        &self,
+        post_id: uuid::Uuid,
+    ) -> Result<Vec<Attachment>, errors::Error> {
+        //the attachments are appended in upload order
+        Ok(self
+            .lock()?
+            .attachments
+            .iter()
+            .filter(|a| a.post_id == post_id)
+            .cloned()
+            .collect())
+    }
+
+    #[instrument(name = "Delete attachment", skip(self))]
+    async fn delete_attachment(&self, attachment_id: uuid::Uuid) -> Result<(), errors::Error> {
+        let mut data = self.lock()?;
+        let count = data.attachments.len();
+        data.attachments.retain(|a| a.id != attachment_id);
+
+        if data.attachments.len() == count {
+            return Err(attachment_not_found(attachment_id));
+        }
+        tracing::info!("Attachment {} deleted", attachment_id);
+
+        Ok(())
+    }
+
+    async fn get_user_hashes(&self, user_id: uuid::Uuid) -> Result<Vec<String>, errors::Error> {
+        let data = self.lock()?;
+        let hashes: HashSet<&String> = data
+            .attachments
+            .iter()
+            .filter(|a| a.user_id == user_id)
+            .map(|a| &a.hash)
+            .collect();
+
+        Ok(hashes.into_iter().cloned().collect())
+    }
+
+    async fn is_hash_used(&self, hash: &str) -> Result<bool, errors::Error> {
+        Ok(self.lock()?.attachments.iter().any(|a| a.hash == hash))
+    }
+}
+
+#[async_trait]
+impl PermissionRepository for MemoryRepository {
+    #[instrument(name = "Get permission", skip(self))]
+    async fn get_permission(
+        &self,
+        post_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+    ) -> Result<Option<Permission>, errors::Error> {
+        self.lock()?.permission(post_id, user_id)
+    }
+
+    #[instrument(name = "Grant permission", skip(self))]
+    async fn grant_permission(
+        &self,
+        permission: PostPermission,
+    ) -> Result<PostPermission, errors::Error> {
+        let mut data = self.lock()?;
+
+        if !data.posts.iter().any(|p| p.id == permission.post_id)
+            || !data.users.iter().any(|u| u.id == permission.user_id)
+        {
+            tracing::error!("Failed to share post {}", permission.post_id);
+            return Err(errors::Error::new(
+                Some("post_permissions references a missing post or user".to_string()),
+                None,
+                errors::ErrorTypes::DbError,
+            ));
+        }
+        //same as the primary key of `post_permissions`
+        if data
+            .post_permissions
+            .iter()
+            .any(|p| p.post_id == permission.post_id && p.user_id == permission.user_id)
+        {
+            return Err(permission_exists());
+        }
+
+        data.post_permissions.push(permission.clone());
+        Ok(permission)
+    }
+
+    #[instrument(name = "Update permission", skip(self))]
+    async fn update_permission(
+        &self,
+        post_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+        role: Permission,
+    ) -> Result<(), errors::Error> {
+        let mut data = self.lock()?;
+        let permission = data
+            .post_permissions
+            .iter_mut()
+            .find(|p| p.post_id == post_id && p.user_id == user_id)
+            .ok_or_else(permission_not_found)?;
+
+        permission.role = role.to_string();
+        Ok(())
+    }
+
+    #[instrument(name = "Revoke permission", skip(self))]
+    async fn revoke_permission(
+        &self,
+        post_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+    ) -> Result<(), errors::Error> {
+        let mut data = self.lock()?;
+        let count = data.post_permissions.len();
+        data.post_permissions
+            .retain(|p| p.post_id != post_id || p.user_id != user_id);
+
+        if data.post_permissions.len() == count {
+            return Err(permission_not_found());
+        }
+        data.important_posts.remove(&(user_id, post_id));
+        tracing::info!("Permission of user {} to post {} revoked", user_id, post_id);
+
+        Ok(())
+    }
+
+    async fn get_post_permissions(
+        &self,
+        post_id: uuid::Uuid,
+    ) -> Result<Vec<ResponsePermission>, errors::Error> {
+        let data = self.lock()?;
+
+        //the permissions are appended in the order they are granted
+        data.post_permissions
+            .iter()
+            .filter(|p| p.post_id == post_id)
+            .map(|p| Ok(p.clone().response(data.username(p.user_id)?)))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl NotebookRepository for MemoryRepository {
+    #[instrument(name = "Add notebook", skip(self))]
+    async fn add_notebook(&self, notebook: Notebook) -> Result<Notebook, errors::Error> {
+        let mut data = self.lock()?;
+
+        data.notebooks.push(notebook.clone());
+        tracing::info!("Notebook {} added successfully!", notebook.id);
+
+        Ok(notebook)
+    }
+
+    #[instrument(name = "Get notebooks", skip(self))]
+    async fn get_notebooks(
+        &self,
+        workspace_id: uuid::Uuid,
+    ) -> Result<Vec<(Notebook, i64)>, errors::Error> {
+        let data = self.lock()?;
+
+        let mut notebooks: Vec<(Notebook, i64)> = data
+            .notebooks
+            .iter()
+            .filter(|n| n.workspace_id == workspace_id)
+            .map(|n| {
+                let notes = data
+                    .posts
+                    .iter()
+                    .filter(|p| p.workspace_id == workspace_id && p.notebook_id == Some(n.id))
+                    .count();
+                (n.clone(), notes as i64)
+            })
+            .collect();
+        notebooks.sort_by_key(|(n, _)| (n.created_at, n.id));
+
+        Ok(notebooks)
+    }
+
+    #[instrument(name = "Find notebook", skip(self))]
+    async fn find_notebook(&self, notebook_id: uuid::Uuid) -> Result<Notebook, errors::Error> {
+        self.lock()?
+            .notebooks
+            .iter()
+            .find(|n| n.id == notebook_id)
+            .cloned()
+            .ok_or_else(notebook_not_found)
+    }
+
+    #[instrument(name = "Update notebook", skip(self))]
+    async fn update_notebook(
+        &self,
+        notebook_id: uuid::Uuid,
+        name: String,
+        parent_id: Option<uuid::Uuid>,
+    ) -> Result<Notebook, errors::Error> {
+        let mut data = self.lock()?;
+        let notebook = data
+            .notebooks
+            .iter_mut()
+            .find(|n| n.id == notebook_id)
+            .ok_or_else(notebook_not_found)?;
+
+        notebook.name = name;
+        notebook.parent_id = parent_id;
+
+        Ok(notebook.clone())
+    }
+
+    #[instrument(name = "Delete notebook", skip(self))]
+    async fn delete_notebook(&self, notebook_id: uuid::Uuid) -> Result<(), errors::Error> {
+        let mut data = self.lock()?;
+        if !data.notebooks.iter().any(|n| n.id == notebook_id) {
+            return Err(notebook_not_found());
+        }
+
+        //like the foreign keys of the SQL backends
+        let deleted = notebook_subtree(&data.notebooks, notebook_id);
+        data.notebooks.retain(|n| !deleted.contains(&n.id));
+        for post in data.posts.iter_mut() {
+            if post.notebook_id.is_some_and(|id| deleted.contains(&id)) {
+                post.notebook_id = None;
+            }
+        }
+        tracing::info!("Notebook {} deleted", notebook_id);
+
+        Ok(())
+    }
+
+    #[instrument(name = "Move post", skip(self))]
+    async fn move_post(
+        &self,
+        post_id: uuid::Uuid,
+        notebook_id: Option<uuid::Uuid>,
+    ) -> Result<(), errors::Error> {
+        let mut data = self.lock()?;
+        let post = data
+            .posts
+            .iter_mut()
+            .find(|p| p.id == post_id)
+            .ok_or_else(|| post_not_found(post_id))?;
+
+        post.notebook_id = notebook_id;
+
+        Ok(())
+    }
+
+    #[instrument(name = "Get notebook posts", skip(self))]
+    async fn get_notebook_posts(
+        &self,
+        user_id: uuid::Uuid,
+        workspace_id: uuid::Uuid,
+        notebook_ids: Vec<uuid::Uuid>,
+    ) -> Result<Vec<ResponsePost>, errors::Error> {
+        let data = self.lock()?;
+
+        let mut posts: Vec<&Post> = data
+            .posts
+            .iter()
+            .filter(|p| {
+                p.workspace_id == workspace_id
+                    && p.notebook_id.is_some_and(|id| notebook_ids.contains(&id))
+            })
+            .collect();
+        posts.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+
+        let mut response_posts = Vec::new();
+        for post in posts {
+            let Some(permission) = data.permission(post.id, user_id)? else {
+                continue;
+            };
+            let important = data.important_posts.contains(&(user_id, post.id));
+            let owner = data.username(post.user_id)?;
+            response_posts.push(post.clone().response(owner, important, permission));
+        }
+        response_posts.sort_by(|a, b| b.important.cmp(&a.important));
+
+        Ok(response_posts)
+    }
+}
+
+#[async_trait]
+impl WorkspaceRepository for MemoryRepository {
+    #[instrument(name = "Add workspace", skip(self))]
+    async fn add_workspace(
+        &self,
+        workspace: Workspace,
+        owner_id: uuid::Uuid,
+    ) -> Result<Workspace, errors::Error> {
+        let mut data = self.lock()?;
+
+        data.workspace_members.push(WorkspaceMember {
+            workspace_id: workspace.id,
+            user_id: owner_id,
+            role: WorkspaceRole::Owner.to_string(),
+            created_at: workspace.created_at,
+        });
+        data.workspaces.push(workspace.clone());
+        tracing::info!("Workspace {} added successfully!", workspace.id);
+
+        Ok(workspace)
+    }
+
+    #[instrument(name = "Get user's workspaces", skip(self))]
+    async fn get_user_workspaces(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> Result<Vec<ResponseWorkspace>, errors::Error> {
+        let data = self.lock()?;
+
+        let mut workspaces: Vec<ResponseWorkspace> = data
+            .workspaces
+            .iter()
+            .filter_map(|w| {
+                let role = data.member_role(w.id, user_id)?;
+                Some(w.clone().response(member_role(role)))
+            })
+            .collect();
+        //the personal workspace first, then the oldest first like the SQL backends
+        workspaces.sort_by_key(|w| (!w.personal, w.created_at, w.id));
+
+        Ok(workspaces)
+    }
+
+    #[instrument(name = "Find membership", skip(self))]
+    async fn find_membership(
+        &self,
+        workspace_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+    ) -> Result<Option<ResponseWorkspace>, errors::Error> {
+        let data = self.lock()?;
+
+        Ok(data
+            .workspaces
+            .iter()
+            .find(|w| w.id == workspace_id)
+            .and_then(|w| {
+                let role = data.member_role(w.id, user_id)?;
+                Some(w.clone().response(member_role(role)))
+            }))
+    }
+
+    #[instrument(name = "Rename workspace", skip(self))]
+    async fn rename_workspace(
+        &self,
+        workspace_id: uuid::Uuid,
+        name: String,
+    ) -> Result<(), errors::Error> {
+        let mut data = self.lock()?;
+        let workspace = data
+            .workspaces
+            .iter_mut()
+            .find(|w| w.id == workspace_id)
+            .ok_or_else(workspace_not_found)?;
+
+        workspace.name = name;
+        Ok(())
+    }
+
+    #[instrument(name = "Add workspace member", skip(self))]
+    async fn add_member(&self, member: WorkspaceMember) -> Result<WorkspaceMember, errors::Error> {
+        let mut data = self.lock()?;
+
+        if !data.workspaces.iter().any(|w| w.id == member.workspace_id)
+            || !data.users.iter().any(|u| u.id == member.user_id)
+        {
+            tracing::error!(
+                "Failed to add a member to workspace {}",
+                member.workspace_id
+            );
+            return Err(errors::Error::new(
+                Some("workspace_members references a missing workspace or user".to_string()),
+                None,
+                errors::ErrorTypes::DbError,
+            ));
+        }
+        //same as the primary key of `workspace_members`
+        if data
+            .member_role(member.workspace_id, member.user_id)
+            .is_some()
+        {
+            return Err(member_exists());
+        }
+
+        data.workspace_members.push(member.clone());
+        Ok(member)
+    }
+
+    #[instrument(name = "Update workspace member", skip(self))]
+    async fn update_member(
+        &self,
+        workspace_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+        role: WorkspaceRole,
+    ) -> Result<(), errors::Error> {
+        let mut data = self.lock()?;
+        let member = data
+            .workspace_members
+            .iter_mut()
+            .find(|m| m.workspace_id == workspace_id && m.user_id == user_id)
+            .ok_or_else(member_not_found)?;
+
+        member.role = role.to_string();
+        Ok(())
+    }
+
+    #[instrument(name = "Remove workspace member", skip(self))]
+    async fn remove_member(
+        &self,
+        workspace_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+    ) -> Result<(), errors::Error> {
+        let mut data = self.lock()?;
+        let count = data.workspace_members.len();
+        data.workspace_members
+            .retain(|m| m.workspace_id != workspace_id || m.user_id != user_id);
+
+        if data.workspace_members.len() == count {
+            return Err(member_not_found());
+        }
+        tracing::info!("User {} removed from workspace {}", user_id, workspace_id);
+
+        Ok(())
+    }
+
+    async fn get_members(
+        &self,
+        workspace_id: uuid::Uuid,
+    ) -> Result<Vec<ResponseMember>, errors::Error> {
+        let data = self.lock()?;
+
+        //the members are appended in the order they join
+        data.workspace_members
+            .iter()
+            .filter(|m| m.workspace_id == workspace_id)
+            .map(|m| Ok(m.clone().response(data.username(m.user_id)?)))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl ShareRepository for MemoryRepository {
+    #[instrument(name = "Add share link", skip(self))]
+    async fn add_share_link(&self, link: ShareLink) -> Result<ShareLink, errors::Error> {
+        let mut data = self.lock()?;
+
+        if !data.posts.iter().any(|p| p.id == link.post_id) {
+            tracing::error!("Failed to add a share link of post {}", link.post_id);
+            return Err(errors::Error::new(
+                Some("share_links.post_id references a missing post".to_string()),
+                None,
+                errors::ErrorTypes::DbError,
+            ));
+        }
+
+        data.share_links.push(link.clone());
+        Ok(link)
+    }
+
+    async fn find_share_link(&self, link_id: uuid::Uuid) -> Result<ShareLink, errors::Error> {
+        self.lock()?
+            .share_links
+            .iter()
+            .find(|l| l.id == link_id)
+            .cloned()
+            .ok_or_else(share_link_not_found)
+    }
+
+    async fn find_share_link_by_token(&self, token_hash: &str) -> Result<ShareLink, errors::Error> {
+        self.lock()?
+            .share_links
+            .iter()
+            .find(|l| l.token_hash == token_hash)
+            .cloned()
+            .ok_or_else(share_link_not_found)
+    }
+
+    async fn get_post_share_links(
+        &self,
+        post_id: uuid::Uuid,
+    ) -> Result<Vec<ShareLink>, errors::Error> {
+        //the links are appended in creation order
+        Ok(self
+            .lock()?
+            .share_links
+            .iter()
+            .filter(|l| l.post_id == post_id)
+            .cloned()
+            .collect())
+    }
+
+    #[instrument(name = "Delete share link", skip(self))]
+    async fn delete_share_link(&self, link_id: uuid::Uuid) -> Result<(), errors::Error> {
+        let mut data = self.lock()?;
+        let count = data.share_links.len();
+        data.share_links.retain(|l| l.id != link_id);
+
+        if data.share_links.len() == count {
+            return Err(share_link_not_found());
+        }
+        tracing::info!("Share link {} deleted", link_id);
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AuditRepository for MemoryRepository {
+    async fn add_event(&self, event: AuditEvent) -> Result<(), errors::Error> {
+        self.lock()?.audit_events.push(event);
+        Ok(())
+    }
+
+    async fn get_events(&self, filter: AuditFilter) -> Result<Vec<AuditEvent>, errors::Error> {
+        let data = self.lock()?;
+
+        //the events are appended in time order, so newest first is the reverse
+        Ok(data
+            .audit_events
+            .iter()
+            .rev()
+            .filter(|e| filter.actor_id.is_none_or(|v| e.actor_id == Some(v)))
+            .filter(|e| filter.action.as_ref().is_none_or(|v| &e.action == v))
+            .filter(|e| filter.target_id.is_none_or(|v| e.target_id == Some(v)))
+            .filter(|e| filter.success.is_none_or(|v| e.success == v))
+            .filter(|e| filter.since.is_none_or(|v| e.created_at >= v))
+            .filter(|e| filter.until.is_none_or(|v| e.created_at < v))
+            .skip(filter.offset() as usize)
+            .take(filter.limit() as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn delete_events_before(
+        &self,
+        before: time::OffsetDateTime,
+    ) -> Result<usize, errors::Error> {
+        let mut data = self.lock()?;
+        let count = data.audit_events.len();
+        data.audit_events.retain(|e| e.created_at >= before);
+        Ok(count - data.audit_events.len())
+    }
+}
+
+#[async_trait]
+impl SchemaRepository for MemoryRepository {
+    async fn run_pending_migrations(&self) -> Result<Vec<String>, errors::Error> {
+        Ok(Vec::new())
+    }
+
+    async fn has_pending_migrations(&self) -> Result<bool, errors::Error> {
+        Ok(false)
+    }
+}
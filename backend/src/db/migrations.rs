@@ -0,0 +1,118 @@
+use diesel::{backend::Backend, migration::MigrationSource};
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+use tracing::instrument;
+
+use crate::errors;
+
+/// The PostgreSQL migrations from the `backend/migrations` directory, embedded into the binary at
+/// compile time. The functions below work with these and with `sqlite::MIGRATIONS`.
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
+fn migration_error(e: impl std::fmt::Display, message: &str) -> errors::Error {
+    tracing::error!("{}: {}", message, e);
+    errors::Error::new(
+        Some(e.to_string()),
+        Some(message.to_string()),
+        errors::ErrorTypes::DbError,
+    )
+}
+
+/// The function `run_pending_migrations` applies all embedded migrations that are not in the database
+/// yet.
+///
+/// Arguments:
+///
+/// * `conn`: The `conn` parameter is a mutable reference to a database connection.
+/// * `source`: The `source` parameter is the set of embedded migrations for the connection's backend.
+///
+/// Returns:
+///
+/// a `Result` with the names of the applied migrations, or an `errors::Error`.
+#[instrument(name = "Run pending migrations", skip(conn, source))]
+pub fn run_pending_migrations<DB: Backend>(
+    conn: &mut impl MigrationHarness<DB>,
+    source: EmbeddedMigrations,
+) -> Result<Vec<String>, errors::Error> {
+    let applied = conn
+        .run_pending_migrations(source)
+        .map_err(|e| migration_error(e, "Failed to run migrations!"))?
+        .iter()
+        .map(|version| version.to_string())
+        .collect::<Vec<_>>();
+
+    tracing::info!("Applied {} migrations", applied.len());
+    Ok(applied)
+}
+
+/// The function `revert_last_migration` reverts the most recently applied migration.
+///
+/// Arguments:
+///
+/// * `conn`: The `conn` parameter is a mutable reference to a database connection.
+/// * `source`: The `source` parameter is the set of embedded migrations for the connection's backend.
+///
+/// Returns:
+///
+/// a `Result` with the version of the reverted migration, or an `errors::Error`.
+#[instrument(name = "Revert last migration", skip(conn, source))]
+pub fn revert_last_migration<DB: Backend>(
+    conn: &mut impl MigrationHarness<DB>,
+    source: EmbeddedMigrations,
+) -> Result<String, errors::Error> {
+    let version = conn
+        .revert_last_migration(source)
+        .map_err(|e| migration_error(e, "Failed to revert the last migration!"))?
+        .to_string();
+
+    tracing::info!("Reverted migration {}", version);
+    Ok(version)
+}
+
+/// The function `migration_status` lists all embedded migrations and whether they are applied.
+///
+/// Arguments:
+///
+/// * `conn`: The `conn` parameter is a mutable reference to a database connection.
+/// * `source`: The `source` parameter is the set of embedded migrations for the connection's backend.
+///
+/// Returns:
+///
+/// a `Result` with `(name, applied)` pairs in the order the migrations run, or an `errors::Error`.
+pub fn migration_status<DB: Backend>(
+    conn: &mut impl MigrationHarness<DB>,
+    source: EmbeddedMigrations,
+) -> Result<Vec<(String, bool)>, errors::Error> {
+    let applied = conn
+        .applied_migrations()
+        .map_err(|e| migration_error(e, "Failed to read applied migrations!"))?;
+
+    let mut migrations = MigrationSource::<DB>::migrations(&source)
+        .map_err(|e| migration_error(e, "Failed to read embedded migrations!"))?;
+    migrations.sort_by(|a, b| a.name().version().cmp(&b.name().version()));
+
+    Ok(migrations
+        .iter()
+        .map(|migration| {
+            let name = migration.name();
+            (name.to_string(), applied.contains(&name.version()))
+        })
+        .collect())
+}
+
+/// The function `has_pending_migrations` checks if the database schema is behind the binary.
+///
+/// Arguments:
+///
+/// * `conn`: The `conn` parameter is a mutable reference to a database connection.
+/// * `source`: The `source` parameter is the set of embedded migrations for the connection's backend.
+///
+/// Returns:
+///
+/// a `Result` with `true` if some embedded migrations are not applied, or an `errors::Error`.
+pub fn has_pending_migrations<DB: Backend>(
+    conn: &mut impl MigrationHarness<DB>,
+    source: EmbeddedMigrations,
+) -> Result<bool, errors::Error> {
+    conn.has_pending_migration(source)
+        .map_err(|e| migration_error(e, "Failed to check pending migrations!"))
+}
@@ -1,40 +1,97 @@
 pub mod functionality;
+pub mod memory;
+pub mod migrations;
 pub mod models;
+pub mod postgres;
+pub mod repository;
 pub mod schema;
+pub mod sqlite;
+use crate::app::Argon2Settings;
 use crate::errors;
 use argon2::{
     password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
-    Argon2,
+    Algorithm, Argon2, Params, Version,
 };
 pub use functionality::*;
+pub use memory::MemoryRepository;
+pub use migrations::*;
 pub use models::*;
+pub use postgres::PgRepository;
+pub use repository::*;
 
-/// The function `hash_password` takes a password as input, generates a salt, and uses the Argon2
-/// algorithm to hash the password, returning the hashed password as a string.
+/// The function `hash_password` takes a password as input, generates a salt, and uses the Argon2id
+/// algorithm with the configured parameters to hash the password, returning the hashed password as a
+/// string.
 ///
 /// Arguments:
 ///
 /// * `password`: The `password` parameter is a string that represents the user's password that needs to
 /// be hashed.
+/// * `settings`: The `settings` parameter holds the memory cost, the iterations and the parallelism.
 ///
 /// Returns:
 ///
 /// a `Result` type with the success variant containing a `String` representing the hashed password, and
 /// the error variant containing an `errors::Error` object.
-pub fn hash_password(password: &str) -> Result<String, errors::Error> {
+pub fn hash_password(password: &str, settings: &Argon2Settings) -> Result<String, errors::Error> {
     let salt = SaltString::generate(&mut OsRng);
-    let hashed_password = Argon2::default()
+    let hashed_password = hasher(settings)?
         .hash_password(password.as_bytes(), &salt)
-        .map_err(|e| errors::Error {
-            cause: Some(e.to_string()),
-            message: Some("Password hashing error".into()),
-            error_type: errors::ErrorTypes::DbError,
+        .map_err(|e| {
+            errors::Error::new(
+                Some(e.to_string()),
+                Some("Password hashing error".into()),
+                errors::ErrorTypes::DbError,
+            )
         })?
         .to_string();
 
     Ok(hashed_password)
 }
 
+fn hasher(settings: &Argon2Settings) -> Result<Argon2<'static>, errors::Error> {
+    let params = Params::new(
+        settings.memory_kib,
+        settings.iterations,
+        settings.parallelism,
+        None,
+    )
+    .map_err(|e| {
+        errors::Error::new(
+            Some(e.to_string()),
+            Some("Invalid argon2 parameters".into()),
+            errors::ErrorTypes::DbError,
+        )
+    })?;
+    Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+}
+
+/// The function `needs_rehash` checks if a password hash was made with other parameters than the
+/// configured ones, so it should be replaced when the password is known.
+///
+/// Arguments:
+///
+/// * `hashed_password`: The `hashed_password` parameter is the stored PHC string of the hash.
+/// * `settings`: The `settings` parameter holds the configured argon2 parameters.
+///
+/// Returns:
+///
+/// `true` if the algorithm, the version or one of the parameters differs, or the hash can not be read.
+pub fn needs_rehash(hashed_password: &str, settings: &Argon2Settings) -> bool {
+    let Ok(parsed_hash) = PasswordHash::new(hashed_password) else {
+        return true;
+    };
+    let Ok(params) = Params::try_from(&parsed_hash) else {
+        return true;
+    };
+
+    parsed_hash.algorithm != Algorithm::Argon2id.ident()
+        || parsed_hash.version != Some(Version::V0x13.into())
+        || params.m_cost() != settings.memory_kib
+        || params.t_cost() != settings.iterations
+        || params.p_cost() != settings.parallelism
+}
+
 /// The function `verify_password_hash` verifies if a given password matches a hashed password using the
 /// Argon2 algorithm.
 ///
@@ -50,21 +107,23 @@ pub fn hash_password(password: &str) -> Result<String, errors::Error> {
 /// The function `verify_password_hash` returns a `Result<(), errors::Error>`.
 
 pub fn verify_password_hash(hashed_password: &str, password: &str) -> Result<(), errors::Error> {
-    let parsed_hash = PasswordHash::new(hashed_password).map_err(|e| errors::Error {
-        cause: Some(e.to_string()),
-        message: Some("Password hashing error".into()),
-        error_type: errors::ErrorTypes::DbError,
+    let parsed_hash = PasswordHash::new(hashed_password).map_err(|e| {
+        errors::Error::new(
+            Some(e.to_string()),
+            Some("Password hashing error".into()),
+            errors::ErrorTypes::DbError,
+        )
     })?;
 
     Argon2::default()
         .verify_password(password.as_bytes(), &parsed_hash)
         .map_err(|e| {
             tracing::error!("Failed to validate password!");
-            errors::Error::new(
-                Some(e.to_string()),
-                Some("Incorrect password. Try again!".into()),
+            errors::Error::from_code(
+                "auth.wrong_password",
                 errors::ErrorTypes::Auth(errors::Auth::Authentication),
             )
+            .with_cause(e)
         })?;
 
     Ok(())
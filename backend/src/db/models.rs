@@ -2,7 +2,15 @@ use diesel::prelude::*;
 use time::OffsetDateTime;
 use uuid::Uuid;
 
-use super::schema::{posts, users};
+use super::schema::{
+    attachments, audit_events, notebooks, post_permissions, posts, share_links, users,
+    workspace_members, workspaces,
+};
+pub use common::UserSummary;
+use common::{
+    Permission, ResponseAttachment, ResponseMember, ResponseNotebook, ResponsePermission,
+    ResponsePost, ResponseShareLink, ResponseWorkspace, Role, SharedPost, WorkspaceRole, REDACTED,
+};
 use serde::{Deserialize, Serialize};
 
 /// The DB User model.
@@ -21,7 +29,15 @@ use serde::{Deserialize, Serialize};
 /// * `updated_at`: The `updated_at` property is a field of type `OffsetDateTime` that represents the
 /// date and time when the user was last updated. It is used to keep track of when the user's
 /// information was last modified.
-#[derive(Queryable, Selectable, Serialize, Insertable, Debug)]
+/// * `disabled`: The `disabled` property is `true` for accounts that an operator has disabled. They can
+/// not log in.
+/// * `role`: The `role` property is the name of the user's `common::Role`.
+/// * `session_version`: The `session_version` property is written into the tokens of the user. It is
+/// increased to log the user out everywhere, the tokens with an older version are rejected.
+/// * `username_key`: The `username_key` property is the normalized lowercase username, see
+/// `schema::user::username_key`. It is unique, so usernames that differ only in case can not be
+/// registered twice, and the lookups by username use it.
+#[derive(Queryable, Selectable, Serialize, Insertable, Clone)]
 #[diesel(table_name =users)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct User {
@@ -31,6 +47,36 @@ pub struct User {
     pub password: String,
     pub created_at: OffsetDateTime,
     pub updated_at: OffsetDateTime,
+    pub disabled: bool,
+    pub role: String,
+    #[serde(skip_serializing)]
+    pub session_version: i32,
+    #[serde(skip_serializing)]
+    pub username_key: String,
+}
+
+impl User {
+    /// The function `role` returns the role of the user. Unknown values fall back to `Role::User`.
+    pub fn role(&self) -> Role {
+        self.role.parse().unwrap_or_default()
+    }
+}
+
+//never write the password hash to the logs
+impl std::fmt::Debug for User {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("User")
+            .field("id", &self.id)
+            .field("username", &self.username)
+            .field("password", &REDACTED)
+            .field("created_at", &self.created_at)
+            .field("updated_at", &self.updated_at)
+            .field("disabled", &self.disabled)
+            .field("role", &self.role)
+            .field("session_version", &self.session_version)
+            .field("username_key", &self.username_key)
+            .finish()
+    }
 }
 
 /// The DB Post model.
@@ -47,7 +93,10 @@ pub struct User {
 /// * `created_at`: The `created_at` property is of type `OffsetDateTime` and is annotated with
 /// `#[serde(with="time::serde::rfc3339")]`. This annotation specifies that the `created_at` property
 /// should be serialized and deserialized using the `rfc3339` format provided by the
-#[derive(Queryable, Selectable, Insertable, Serialize, Deserialize, Debug)]
+/// * `workspace_id`: The `workspace_id` property is the workspace the post belongs to. Its members
+/// see the post.
+/// * `notebook_id`: The `notebook_id` property is the notebook of the workspace the post is in, if any.
+#[derive(Queryable, Selectable, Insertable, Serialize, Deserialize, Clone)]
 #[diesel(table_name = posts)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
 
@@ -58,4 +107,430 @@ pub struct Post {
     pub description: Option<String>,
     #[serde(with = "time::serde::rfc3339")]
     pub created_at: OffsetDateTime,
+    pub workspace_id: Uuid,
+    pub notebook_id: Option<Uuid>,
+}
+
+//note bodies are user data and stay out of the logs
+impl std::fmt::Debug for Post {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Post")
+            .field("id", &self.id)
+            .field("user_id", &self.user_id)
+            .field("title", &self.title)
+            .field("description", &self.description.as_ref().map(|_| REDACTED))
+            .field("created_at", &self.created_at)
+            .field("workspace_id", &self.workspace_id)
+            .field("notebook_id", &self.notebook_id)
+            .finish()
+    }
+}
+
+impl Post {
+    /// The function `response` returns the post as a user sees it.
+    ///
+    /// Arguments:
+    ///
+    /// * `owner`: The `owner` parameter is the username of the author.
+    /// * `important`: The `important` parameter is the important flag of the user.
+    /// * `permission`: The `permission` parameter is what the user may do with the post.
+    pub fn response(self, owner: String, important: bool, permission: Permission) -> ResponsePost {
+        ResponsePost {
+            id: self.id,
+            important,
+            title: self.title,
+            description: self.description,
+            created_at: self.created_at,
+            owner_id: self.user_id,
+            owner,
+            permission,
+            workspace_id: self.workspace_id,
+            notebook_id: self.notebook_id,
+        }
+    }
+}
+
+/// The DB PostPermission model, the access of another user than the author to a post.
+///
+/// Properties:
+///
+/// * `post_id`: The `post_id` property is the shared post. The permissions are deleted with it.
+/// * `user_id`: The `user_id` property is the user who got the access.
+/// * `role`: The `role` property is the name of the granted `common::Permission`, `viewer` or
+/// `editor`.
+/// * `created_at`: The `created_at` property is the time the access was granted.
+#[derive(Queryable, Selectable, Insertable, Debug, Clone)]
+#[diesel(table_name = post_permissions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PostPermission {
+    pub post_id: Uuid,
+    pub user_id: Uuid,
+    pub role: String,
+    pub created_at: OffsetDateTime,
+}
+
+impl PostPermission {
+    /// The function `response` returns the permission with the username of the user who has it.
+    pub fn response(self, username: String) -> ResponsePermission {
+        ResponsePermission {
+            post_id: self.post_id,
+            user_id: self.user_id,
+            username,
+            role: granted_permission(&self.role),
+            created_at: self.created_at,
+        }
+    }
+}
+
+/// The function `granted_permission` reads the role of a `PostPermission`. Unknown values fall back
+/// to `Permission::Viewer`, the one with the fewest rights, and a stored role never makes a user an
+/// owner.
+pub fn granted_permission(role: &str) -> Permission {
+    match role.parse() {
+        Ok(Permission::Owner) | Err(_) => Permission::Viewer,
+        Ok(permission) => permission,
+    }
+}
+
+/// The function `effective_permission` returns what a user may do with a post: `Owner` for its
+/// author, otherwise the higher one of the granted permission and the one their role in the workspace
+/// of the post gives. `None` if they have neither.
+///
+/// Arguments:
+///
+/// * `author`: The `author` parameter is `true` if the user wrote the post.
+/// * `granted`: The `granted` parameter is the role of the `PostPermission` of the user, if any.
+/// * `member`: The `member` parameter is the role of the user in the workspace of the post, if any.
+pub fn effective_permission(
+    author: bool,
+    granted: Option<&str>,
+    member: Option<&str>,
+) -> Option<Permission> {
+    if author {
+        return Some(Permission::Owner);
+    }
+    let granted = granted.map(granted_permission);
+    let member = member.map(|role| member_role(role).post_permission());
+    granted.max(member)
+}
+
+/// The DB Workspace model. Its posts are seen by its members.
+///
+/// Properties:
+///
+/// * `id`: The `id` property is the unique identifier of the workspace.
+/// * `name`: The `name` property is the name of the workspace.
+/// * `personal`: The `personal` property is `true` for the workspace every user gets at signup. It
+/// has only its owner as member.
+/// * `created_at`: The `created_at` property is the time the workspace was created.
+#[derive(Queryable, Selectable, Insertable, Debug, Clone)]
+#[diesel(table_name = workspaces)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Workspace {
+    pub id: Uuid,
+    pub name: String,
+    pub personal: bool,
+    pub created_at: OffsetDateTime,
+}
+
+impl Workspace {
+    /// The function `personal` returns the personal workspace of a new user, named after them.
+    pub fn personal(user: &User) -> Self {
+        Workspace {
+            id: Uuid::new_v4(),
+            name: user.username.clone(),
+            personal: true,
+            created_at: user.created_at,
+        }
+    }
+
+    /// The function `response` returns the workspace as a member with `role` sees it.
+    pub fn response(self, role: WorkspaceRole) -> ResponseWorkspace {
+        ResponseWorkspace {
+            id: self.id,
+            name: self.name,
+            personal: self.personal,
+            role,
+            created_at: self.created_at,
+        }
+    }
+}
+
+/// The DB WorkspaceMember model, a user who sees the posts of a workspace.
+///
+/// Properties:
+///
+/// * `workspace_id`: The `workspace_id` property is the workspace.
+/// * `user_id`: The `user_id` property is the member.
+/// * `role`: The `role` property is the name of the `common::WorkspaceRole` of the member.
+/// * `created_at`: The `created_at` property is the time the user joined.
+#[derive(Queryable, Selectable, Insertable, Debug, Clone)]
+#[diesel(table_name = workspace_members)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct WorkspaceMember {
+    pub workspace_id: Uuid,
+    pub user_id: Uuid,
+    pub role: String,
+    pub created_at: OffsetDateTime,
+}
+
+impl WorkspaceMember {
+    /// The function `response` returns the member with their username.
+    pub fn response(self, username: String) -> ResponseMember {
+        ResponseMember {
+            user_id: self.user_id,
+            username,
+            role: member_role(&self.role),
+            created_at: self.created_at,
+        }
+    }
+}
+
+/// The DB Notebook model, a folder of the notes of a workspace. Notebooks can be nested.
+///
+/// Properties:
+///
+/// * `id`: The `id` property is the unique identifier of the notebook.
+/// * `workspace_id`: The `workspace_id` property is the workspace of the notebook and its notes.
+/// * `parent_id`: The `parent_id` property is the notebook it is in, `None` for a top level notebook.
+/// * `name`: The `name` property is the name of the notebook.
+/// * `created_at`: The `created_at` property is the time the notebook was created.
+#[derive(Queryable, Selectable, Insertable, Debug, Clone)]
+#[diesel(table_name = notebooks)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Notebook {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub parent_id: Option<Uuid>,
+    pub name: String,
+    pub created_at: OffsetDateTime,
+}
+
+impl Notebook {
+    /// The function `response` returns the notebook with the number of its own notes and of all
+    /// notes inside it.
+    pub fn response(self, notes: i64, total: i64) -> ResponseNotebook {
+        ResponseNotebook {
+            id: self.id,
+            parent_id: self.parent_id,
+            name: self.name,
+            notes,
+            total,
+            created_at: self.created_at,
+        }
+    }
+}
+
+/// The function `notebook_subtree` returns a notebook with all notebooks inside it, at any depth.
+///
+/// Arguments:
+///
+/// * `notebooks`: The `notebooks` parameter lists the notebooks of the workspace.
+/// * `root`: The `root` parameter is the id of the notebook.
+///
+/// Returns:
+///
+/// the ids of `root` and of the notebooks inside it, `root` first.
+pub fn notebook_subtree(notebooks: &[Notebook], root: Uuid) -> Vec<Uuid> {
+    let mut subtree = vec![root];
+    let mut next = 0;
+    //breadth first, a notebook that is in the list already is not added again
+    while let Some(&parent) = subtree.get(next) {
+        subtree.extend(
+            notebooks
+                .iter()
+                .filter(|n| n.parent_id == Some(parent) && !subtree.contains(&n.id))
+                .map(|n| n.id)
+                .collect::<Vec<_>>(),
+        );
+        next += 1;
+    }
+    subtree
+}
+
+/// The function `member_role` reads the role of a `WorkspaceMember`. Unknown values fall back to
+/// `WorkspaceRole::Member`, the one with the fewest rights.
+pub fn member_role(role: &str) -> WorkspaceRole {
+    role.parse().unwrap_or_default()
+}
+
+/// The DB Attachment model. The content is in the `BlobStore` under its hash, so the same file
+/// attached twice is stored once.
+///
+/// Properties:
+///
+/// * `id`: The `id` property is the unique identifier of the attachment.
+/// * `post_id`: The `post_id` property is the post the file is attached to.
+/// * `user_id`: The `user_id` property is the owner, the author of the post. The size counts towards
+/// their quota.
+/// * `file_name`: The `file_name` property is the name of the uploaded file, without the folders.
+/// * `content_type`: The `content_type` property is the MIME type of the file.
+/// * `size`: The `size` property is the size of the file in bytes.
+/// * `hash`: The `hash` property is the hex SHA-256 hash of the content, the key in the `BlobStore`.
+/// * `created_at`: The `created_at` property is the time of the upload.
+#[derive(Queryable, Selectable, Insertable, Debug, Clone)]
+#[diesel(table_name = attachments)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Attachment {
+    pub id: Uuid,
+    pub post_id: Uuid,
+    pub user_id: Uuid,
+    pub file_name: String,
+    pub content_type: String,
+    pub size: i64,
+    pub hash: String,
+    pub created_at: OffsetDateTime,
+}
+
+impl From<Attachment> for ResponseAttachment {
+    fn from(attachment: Attachment) -> Self {
+        ResponseAttachment {
+            id: attachment.id,
+            post_id: attachment.post_id,
+            file_name: attachment.file_name,
+            content_type: attachment.content_type,
+            size: attachment.size,
+            created_at: attachment.created_at,
+        }
+    }
+}
+
+/// The DB ShareLink model. A link shows one post to visitors without an account.
+///
+/// Properties:
+///
+/// * `id`: The `id` property is the unique identifier of the link.
+/// * `post_id`: The `post_id` property is the shared post. The links are deleted with it.
+/// * `token_hash`: The `token_hash` property is the hex SHA-256 hash of the token in the address. The
+/// token is random, so a fast hash is enough, and a leaked database does not leak the links.
+/// * `password_hash`: The `password_hash` property is the argon2 hash of the password of the link, if
+/// it has one.
+/// * `expires_at`: The `expires_at` property is the time the link stops working, if any.
+/// * `created_at`: The `created_at` property is the time the link was created.
+#[derive(Queryable, Selectable, Insertable, Clone)]
+#[diesel(table_name = share_links)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ShareLink {
+    pub id: Uuid,
+    pub post_id: Uuid,
+    pub token_hash: String,
+    pub password_hash: Option<String>,
+    pub expires_at: Option<OffsetDateTime>,
+    pub created_at: OffsetDateTime,
+}
+
+impl ShareLink {
+    /// The function `is_expired` checks if the link stopped working at `now`.
+    pub fn is_expired(&self, now: OffsetDateTime) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+impl std::fmt::Debug for ShareLink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ShareLink")
+            .field("id", &self.id)
+            .field("post_id", &self.post_id)
+            .field("token_hash", &REDACTED)
+            .field(
+                "password_hash",
+                &self.password_hash.as_ref().map(|_| REDACTED),
+            )
+            .field("expires_at", &self.expires_at)
+            .field("created_at", &self.created_at)
+            .finish()
+    }
+}
+
+impl From<ShareLink> for ResponseShareLink {
+    fn from(link: ShareLink) -> Self {
+        ResponseShareLink {
+            id: link.id,
+            post_id: link.post_id,
+            token: None,
+            has_password: link.password_hash.is_some(),
+            expires_at: link.expires_at,
+            created_at: link.created_at,
+        }
+    }
+}
+
+impl From<Post> for SharedPost {
+    fn from(post: Post) -> Self {
+        SharedPost {
+            title: post.title,
+            description: post.description,
+            created_at: post.created_at,
+        }
+    }
+}
+
+/// The DB AuditEvent model. One row is written for every security-relevant or data-changing request.
+///
+/// Properties:
+///
+/// * `id`: The `id` property is the unique identifier of the event.
+/// * `actor_id`: The `actor_id` property is the id of the user who did the action. It is `None` when the
+/// user is not known, for example for a failed login.
+/// * `actor_name`: The `actor_name` property is the username of the actor, or the username that was
+/// tried when the login failed. Events are kept after the user is deleted, so the name is stored too.
+/// * `action`: The `action` property is the name of the action, see `audit::AuditAction`.
+/// * `target_id`: The `target_id` property is the id of the post or user the action changed.
+/// * `success`: The `success` property is `false` for rejected attempts.
+/// * `ip`: The `ip` property is the address of the client.
+/// * `user_agent`: The `user_agent` property is the `User-Agent` header of the request.
+/// * `created_at`: The `created_at` property is the time of the event.
+#[derive(Queryable, Selectable, Insertable, Serialize, Debug, Clone)]
+#[diesel(table_name = audit_events)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AuditEvent {
+    pub id: Uuid,
+    pub actor_id: Option<Uuid>,
+    pub actor_name: Option<String>,
+    pub action: String,
+    pub target_id: Option<Uuid>,
+    pub success: bool,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+/// The `AuditFilter` struct holds the query parameters of `GET /admin/audit`. Every field is optional,
+/// the events match all of the given ones.
+///
+/// Properties:
+///
+/// * `actor_id`: The `actor_id` property selects the events of one user.
+/// * `action`: The `action` property selects one action, for example `auth.login`.
+/// * `target_id`: The `target_id` property selects the events about one post or user.
+/// * `success`: The `success` property selects the successful or the failed attempts.
+/// * `since`: The `since` property is the earliest time (RFC 3339), inclusive.
+/// * `until`: The `until` property is the latest time (RFC 3339), exclusive.
+/// * `limit`: The `limit` property is the page size, 100 by default and at most 1000.
+/// * `offset`: The `offset` property is the number of events to skip. The newest events come first.
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct AuditFilter {
+    pub actor_id: Option<Uuid>,
+    pub action: Option<String>,
+    pub target_id: Option<Uuid>,
+    pub success: Option<bool>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub since: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub until: Option<OffsetDateTime>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+impl AuditFilter {
+    /// The function `limit` returns the page size within the allowed range.
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(100).clamp(1, 1000)
+    }
+
+    /// The function `offset` returns the number of events to skip.
+    pub fn offset(&self) -> i64 {
+        self.offset.unwrap_or(0).max(0)
+    }
 }
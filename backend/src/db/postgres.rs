@@ -0,0 +1,427 @@
+use async_trait::async_trait;
+use common::{
+    Permission, PostsUpdateForm, ResponseMember, ResponsePermission, ResponsePost,
+    ResponseWorkspace, Role, WorkspaceRole,
+};
+use diesel::{Connection, PgConnection};
+
+use super::{
+    db_add_attachment, db_add_audit_event, db_add_member, db_add_notebook, db_add_post,
+    db_add_posts, db_add_share_link, db_add_user, db_add_workspace, db_check_user,
+    db_delete_attachment, db_delete_audit_events_before, db_delete_notebook, db_delete_post,
+    db_delete_share_link, db_delete_user, db_edit_post, db_find_attachment, db_find_membership,
+    db_find_notebook, db_find_post, db_find_share_link, db_find_share_link_by_token, db_find_user,
+    db_find_user_by_username, db_get_audit_events, db_get_members, db_get_notebook_posts,
+    db_get_notebooks, db_get_permission, db_get_post_attachments, db_get_post_permissions,
+    db_get_post_share_links, db_get_posts, db_get_user_hashes, db_get_user_posts,
+    db_get_user_workspaces, db_grant_permission, db_has_pending_migrations, db_is_hash_used,
+    db_list_users, db_move_post, db_remove_member, db_rename_workspace, db_revoke_permission,
+    db_revoke_sessions, db_set_disabled, db_set_role, db_update_description, db_update_member,
+    db_update_notebook, db_update_password, db_update_permission, db_update_post,
+    run_pending_migrations, Attachment, AttachmentRepository, AuditEvent, AuditFilter,
+    AuditRepository, Notebook, NotebookRepository, NotesRepository, PermissionRepository, Post,
+    PostPermission, SchemaRepository, ShareLink, ShareRepository, User, UserRepository,
+    UserSummary, Workspace, WorkspaceMember, WorkspaceRepository, MIGRATIONS,
+};
+use crate::app::DbPool;
+use crate::errors;
+use crate::schema::user::Password;
+use crate::tasks::Tasks;
+
+/// The `PgRepository` struct implements the repositories on top of the async `db_*` functions and a
+/// PostgreSQL connection pool.
+///
+/// Properties:
+///
+/// * `pool`: The `pool` property is the async connection pool.
+/// * `url`: The `url` property is the connection url. The migrations use their own blocking
+/// connection, because `diesel_migrations` is not async.
+/// * `tasks`: The `tasks` property runs the blocking migration calls and the password checks on the
+/// thread pool.
+#[derive(Clone)]
+pub struct PgRepository {
+    pool: DbPool,
+    url: String,
+    tasks: Tasks,
+}
+
+impl PgRepository {
+    pub fn new(pool: DbPool, url: String, tasks: Tasks) -> Self {
+        PgRepository { pool, url, tasks }
+    }
+}
+
+/// The function `connect` opens a blocking connection for the migrations.
+///
+/// Arguments:
+///
+/// * `url`: The `url` parameter is the PostgreSQL connection url.
+///
+/// Returns:
+///
+/// a `Result<PgConnection, errors::Error>`.
+fn connect(url: &str) -> Result<PgConnection, errors::Error> {
+    PgConnection::establish(url).map_err(|e| {
+        tracing::error!("Failed to connect to the database!");
+        errors::Error::new(
+            Some(e.to_string()),
+            Some("Failed to connect to the database!".to_string()),
+            errors::ErrorTypes::DbError,
+        )
+    })
+}
+
+#[async_trait]
+impl UserRepository for PgRepository {
+    async fn add_user(&self, user: User) -> Result<User, errors::Error> {
+        db_add_user(user, &self.pool).await
+    }
+
+    async fn check_user(&self, username: &str, password: Password) -> Result<User, errors::Error> {
+        db_check_user(username, password, &self.pool, &self.tasks).await
+    }
+
+    async fn find_user(&self, user_id: uuid::Uuid) -> Result<User, errors::Error> {
+        db_find_user(user_id, &self.pool).await
+    }
+
+    async fn find_user_by_username(&self, username: &str) -> Result<User, errors::Error> {
+        db_find_user_by_username(username, &self.pool).await
+    }
+
+    async fn list_users(&self) -> Result<Vec<UserSummary>, errors::Error> {
+        db_list_users(&self.pool).await
+    }
+
+    async fn update_password(
+        &self,
+        user_id: uuid::Uuid,
+        password_hash: String,
+    ) -> Result<(), errors::Error> {
+        db_update_password(user_id, password_hash, &self.pool).await
+    }
+
+    async fn set_disabled(&self, user_id: uuid::Uuid, disabled: bool) -> Result<(), errors::Error> {
+        db_set_disabled(user_id, disabled, &self.pool).await
+    }
+
+    async fn delete_user(&self, user_id: uuid::Uuid) -> Result<(), errors::Error> {
+        db_delete_user(user_id, &self.pool).await
+    }
+
+    async fn set_role(&self, user_id: uuid::Uuid, role: Role) -> Result<(), errors::Error> {
+        db_set_role(user_id, role, &self.pool).await
+    }
+
+    async fn revoke_sessions(&self, user_id: uuid::Uuid) -> Result<i32, errors::Error> {
+        db_revoke_sessions(user_id, &self.pool).await
+    }
+}
+
+#[async_trait]
+impl NotesRepository for PgRepository {
+    async fn get_posts(
+        &self,
+        user_id: uuid::Uuid,
+        workspace_id: uuid::Uuid,
+    ) -> Result<Vec<ResponsePost>, errors::Error> {
+        db_get_posts(user_id, workspace_id, &self.pool).await
+    }
+
+    async fn get_user_posts(
+        &self,
+        user_id: uuid::Uuid,
+        workspace_id: uuid::Uuid,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<ResponsePost>, errors::Error> {
+        db_get_user_posts(user_id, workspace_id, offset, limit, &self.pool).await
+    }
+
+    async fn find_post(&self, post_id: uuid::Uuid) -> Result<Post, errors::Error> {
+        db_find_post(post_id, &self.pool).await
+    }
+
+    async fn add_post(&self, post: Post) -> Result<ResponsePost, errors::Error> {
+        db_add_post(post, &self.pool).await
+    }
+
+    async fn add_posts(&self, posts: Vec<(Post, bool)>) -> Result<usize, errors::Error> {
+        db_add_posts(posts, &self.pool).await
+    }
+
+    async fn update_post(
+        &self,
+        user_id: uuid::Uuid,
+        data: PostsUpdateForm,
+    ) -> Result<(), errors::Error> {
+        db_update_post(user_id, data, &self.pool).await
+    }
+
+    async fn update_description(
+        &self,
+        post_id: uuid::Uuid,
+        description: Option<String>,
+    ) -> Result<(), errors::Error> {
+        db_update_description(post_id, description, &self.pool).await
+    }
+
+    async fn edit_post(
+        &self,
+        user_id: uuid::Uuid,
+        post: Post,
+    ) -> Result<ResponsePost, errors::Error> {
+        db_edit_post(user_id, post, &self.pool).await
+    }
+
+    async fn delete_post(&self, post_id: uuid::Uuid) -> Result<(), errors::Error> {
+        db_delete_post(post_id, &self.pool).await
+    }
+}
+
+#[async_trait]
+impl AttachmentRepository for PgRepository {
+    async fn add_attachment(
+        &self,
+        attachment: Attachment,
+        quota: Option<i64>,
+    ) -> Result<Attachment, errors::Error> {
+        db_add_attachment(attachment, quota, &self.pool).await
+    }
+
+    async fn find_attachment(
+        &self,
+        attachment_id: uuid::Uuid,
+    ) -> Result<Attachment, errors::Error> {
+        db_find_attachment(attachment_id, &self.pool).await
+    }
+
+    async fn get_post_attachments(
+        &self,
+        post_id: uuid::Uuid,
+    ) -> Result<Vec<Attachment>, errors::Error> {
+        db_get_post_attachments(post_id, &self.pool).await
+    }
+
+    async fn delete_attachment(&self, attachment_id: uuid::Uuid) -> Result<(), errors::Error> {
+        db_delete_attachment(attachment_id, &self.pool).await
+    }
+
+    async fn get_user_hashes(&self, user_id: uuid::Uuid) -> Result<Vec<String>, errors::Error> {
+        db_get_user_hashes(user_id, &self.pool).await
+    }
+
+    async fn is_hash_used(&self, hash: &str) -> Result<bool, errors::Error> {
+        db_is_hash_used(hash, &self.pool).await
+    }
+}
+
+#[async_trait]
+impl PermissionRepository for PgRepository {
+    async fn get_permission(
+        &self,
+        post_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+    ) -> Result<Option<Permission>, errors::Error> {
+        db_get_permission(post_id, user_id, &self.pool).await
+    }
+
+    async fn grant_permission(
+        &self,
+        permission: PostPermission,
+    ) -> Result<PostPermission, errors::Error> {
+        db_grant_permission(permission, &self.pool).await
+    }
+
+    async fn update_permission(
+        &self,
+        post_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+        role: Permission,
+    ) -> Result<(), errors::Error> {
+        db_update_permission(post_id, user_id, role, &self.pool).await
+    }
+
+    async fn revoke_permission(
+        &self,
+        post_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+    ) -> Result<(), errors::Error> {
+        db_revoke_permission(post_id, user_id, &self.pool).await
+    }
+
+    async fn get_post_permissions(
+        &self,
+        post_id: uuid::Uuid,
+    ) -> Result<Vec<ResponsePermission>, errors::Error> {
+        db_get_post_permissions(post_id, &self.pool).await
+    }
+}
+
+#[async_trait]
+impl NotebookRepository for PgRepository {
+    async fn add_notebook(&self, notebook: Notebook) -> Result<Notebook, errors::Error> {
+        db_add_notebook(notebook, &self.pool).await
+    }
+
+    async fn get_notebooks(
+        &self,
+        workspace_id: uuid::Uuid,
+    ) -> Result<Vec<(Notebook, i64)>, errors::Error> {
+        db_get_notebooks(workspace_id, &self.pool).await
+    }
+
+    async fn find_notebook(&self, notebook_id: uuid::Uuid) -> Result<Notebook, errors::Error> {
+        db_find_notebook(notebook_id, &self.pool).await
+    }
+
+    async fn update_notebook(
+        &self,
+        notebook_id: uuid::Uuid,
+        name: String,
+        parent_id: Option<uuid::Uuid>,
+    ) -> Result<Notebook, errors::Error> {
+        db_update_notebook(notebook_id, name, parent_id, &self.pool).await
+    }
+
+    async fn delete_notebook(&self, notebook_id: uuid::Uuid) -> Result<(), errors::Error> {
+        db_delete_notebook(notebook_id, &self.pool).await
+    }
+
+    async fn move_post(
+        &self,
+        post_id: uuid::Uuid,
+        notebook_id: Option<uuid::Uuid>,
+    ) -> Result<(), errors::Error> {
+        db_move_post(post_id, notebook_id, &self.pool).await
+    }
+
+    async fn get_notebook_posts(
+        &self,
+        user_id: uuid::Uuid,
+        workspace_id: uuid::Uuid,
+        notebook_ids: Vec<uuid::Uuid>,
+    ) -> Result<Vec<ResponsePost>, errors::Error> {
+        db_get_notebook_posts(user_id, workspace_id, notebook_ids, &self.pool).await
+    }
+}
+
+#[async_trait]
+impl WorkspaceRepository for PgRepository {
+    async fn add_workspace(
+        &self,
+        workspace: Workspace,
+        owner_id: uuid::Uuid,
+    ) -> Result<Workspace, errors::Error> {
+        db_add_workspace(workspace, owner_id, &self.pool).await
+    }
+
+    async fn get_user_workspaces(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> Result<Vec<ResponseWorkspace>, errors::Error> {
+        db_get_user_workspaces(user_id, &self.pool).await
+    }
+
+    async fn find_membership(
+        &self,
+        workspace_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+    ) -> Result<Option<ResponseWorkspace>, errors::Error> {
+        db_find_membership(workspace_id, user_id, &self.pool).await
+    }
+
+    async fn rename_workspace(
+        &self,
+        workspace_id: uuid::Uuid,
+        name: String,
+    ) -> Result<(), errors::Error> {
+        db_rename_workspace(workspace_id, name, &self.pool).await
+    }
+
+    async fn add_member(&self, member: WorkspaceMember) -> Result<WorkspaceMember, errors::Error> {
+        db_add_member(member, &self.pool).await
+    }
+
+    async fn update_member(
+        &self,
+        workspace_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+        role: WorkspaceRole,
+    ) -> Result<(), errors::Error> {
+        db_update_member(workspace_id, user_id, role, &self.pool).await
+    }
+
+    async fn remove_member(
+        &self,
+        workspace_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+    ) -> Result<(), errors::Error> {
+        db_remove_member(workspace_id, user_id, &self.pool).await
+    }
+
+    async fn get_members(
+        &self,
+        workspace_id: uuid::Uuid,
+    ) -> Result<Vec<ResponseMember>, errors::Error> {
+        db_get_members(workspace_id, &self.pool).await
+    }
+}
+
+#[async_trait]
+impl ShareRepository for PgRepository {
+    async fn add_share_link(&self, link: ShareLink) -> Result<ShareLink, errors::Error> {
+        db_add_share_link(link, &self.pool).await
+    }
+
+    async fn find_share_link(&self, link_id: uuid::Uuid) -> Result<ShareLink, errors::Error> {
+        db_find_share_link(link_id, &self.pool).await
+    }
+
+    async fn find_share_link_by_token(&self, token_hash: &str) -> Result<ShareLink, errors::Error> {
+        db_find_share_link_by_token(token_hash, &self.pool).await
+    }
+
+    async fn get_post_share_links(
+        &self,
+        post_id: uuid::Uuid,
+    ) -> Result<Vec<ShareLink>, errors::Error> {
+        db_get_post_share_links(post_id, &self.pool).await
+    }
+
+    async fn delete_share_link(&self, link_id: uuid::Uuid) -> Result<(), errors::Error> {
+        db_delete_share_link(link_id, &self.pool).await
+    }
+}
+
+#[async_trait]
+impl AuditRepository for PgRepository {
+    async fn add_event(&self, event: AuditEvent) -> Result<(), errors::Error> {
+        db_add_audit_event(event, &self.pool).await
+    }
+
+    async fn get_events(&self, filter: AuditFilter) -> Result<Vec<AuditEvent>, errors::Error> {
+        db_get_audit_events(filter, &self.pool).await
+    }
+
+    async fn delete_events_before(
+        &self,
+        before: time::OffsetDateTime,
+    ) -> Result<usize, errors::Error> {
+        db_delete_audit_events_before(before, &self.pool).await
+    }
+}
+
+#[async_trait]
+impl SchemaRepository for PgRepository {
+    async fn run_pending_migrations(&self) -> Result<Vec<String>, errors::Error> {
+        let url = self.url.clone();
+        self.tasks
+            .block(move || run_pending_migrations(&mut connect(&url)?, MIGRATIONS))
+            .await?
+    }
+
+    async fn has_pending_migrations(&self) -> Result<bool, errors::Error> {
+        db_has_pending_migrations(MIGRATIONS, &self.pool).await
+    }
+}
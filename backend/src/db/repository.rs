@@ -0,0 +1,337 @@
+use async_trait::async_trait;
+use common::{
+    Permission, PostsUpdateForm, ResponseMember, ResponsePermission, ResponsePost,
+    ResponseWorkspace, Role, WorkspaceRole,
+};
+
+use super::{
+    Attachment, AuditEvent, AuditFilter, Notebook, Post, PostPermission, ShareLink, User,
+    UserSummary, Workspace, WorkspaceMember,
+};
+use crate::errors;
+use crate::schema::user::Password;
+
+/// The `UserRepository` trait is the storage of the user accounts. `AppState` holds it as a trait
+/// object, so the handlers do not depend on the database that is used.
+#[async_trait]
+pub trait UserRepository: Send + Sync {
+    /// Adds a new user with their personal workspace, in one transaction. Fails if the username
+    /// already exists, in any case.
+    async fn add_user(&self, user: User) -> Result<User, errors::Error>;
+
+    /// Finds the user by username, in any case, and verifies the password.
+    async fn check_user(&self, username: &str, password: Password) -> Result<User, errors::Error>;
+
+    /// Finds the user by id.
+    async fn find_user(&self, user_id: uuid::Uuid) -> Result<User, errors::Error>;
+
+    /// Finds the user by username, in any case, without checking the password.
+    async fn find_user_by_username(&self, username: &str) -> Result<User, errors::Error>;
+
+    /// Lists all users with the number of their posts, oldest account first.
+    async fn list_users(&self) -> Result<Vec<UserSummary>, errors::Error>;
+
+    /// Replaces the password hash of the user.
+    async fn update_password(
+        &self,
+        user_id: uuid::Uuid,
+        password_hash: String,
+    ) -> Result<(), errors::Error>;
+
+    /// Disables or enables the account. Disabled users can not log in.
+    async fn set_disabled(&self, user_id: uuid::Uuid, disabled: bool) -> Result<(), errors::Error>;
+
+    /// Deletes the user together with their posts, personal workspace and important flags.
+    async fn delete_user(&self, user_id: uuid::Uuid) -> Result<(), errors::Error>;
+
+    /// Changes the role of the user.
+    async fn set_role(&self, user_id: uuid::Uuid, role: Role) -> Result<(), errors::Error>;
+
+    /// Increases the session version of the user, so all of their tokens are rejected. Returns the
+    /// new version.
+    async fn revoke_sessions(&self, user_id: uuid::Uuid) -> Result<i32, errors::Error>;
+}
+
+/// The `NotesRepository` trait is the storage of the posts and the important flags.
+#[async_trait]
+pub trait NotesRepository: Send + Sync {
+    /// Returns the posts of the workspace, and in the personal workspace of `user_id` also the posts
+    /// shared with them, the important ones first. Fails with `workspace.not_found` if there is no
+    /// such workspace.
+    async fn get_posts(
+        &self,
+        user_id: uuid::Uuid,
+        workspace_id: uuid::Uuid,
+    ) -> Result<Vec<ResponsePost>, errors::Error>;
+
+    /// Returns a page of the posts of the workspace, oldest first, with the important flags of
+    /// `user_id`, a member of it.
+    async fn get_user_posts(
+        &self,
+        user_id: uuid::Uuid,
+        workspace_id: uuid::Uuid,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<ResponsePost>, errors::Error>;
+
+    /// Finds the post by id.
+    async fn find_post(&self, post_id: uuid::Uuid) -> Result<Post, errors::Error>;
+
+    /// Adds a new post.
+    async fn add_post(&self, post: Post) -> Result<ResponsePost, errors::Error>;
+
+    /// Adds the posts in one transaction, either all of them or none. The posts paired with `true` are
+    /// marked important for their author. Returns the number of added posts.
+    async fn add_posts(&self, posts: Vec<(Post, bool)>) -> Result<usize, errors::Error>;
+
+    /// Marks the post important or not important for `user_id`. Fails with `post.not_found` if the
+    /// user can not see the post.
+    async fn update_post(
+        &self,
+        user_id: uuid::Uuid,
+        data: PostsUpdateForm,
+    ) -> Result<(), errors::Error>;
+
+    /// Replaces the description of the post.
+    async fn update_description(
+        &self,
+        post_id: uuid::Uuid,
+        description: Option<String>,
+    ) -> Result<(), errors::Error>;
+
+    /// Replaces the title and the description of `post` for `user_id`, its author or an editor. Fails
+    /// with `post.not_found` if the user can not see the post and `auth.forbidden` if they may only
+    /// view it.
+    async fn edit_post(
+        &self,
+        user_id: uuid::Uuid,
+        post: Post,
+    ) -> Result<ResponsePost, errors::Error>;
+
+    /// Deletes the post with its important flags, attachments, share links and permissions. Fails with
+    /// `post.not_found` if there is no such post.
+    async fn delete_post(&self, post_id: uuid::Uuid) -> Result<(), errors::Error>;
+}
+
+/// The `PermissionRepository` trait is the storage of the access other users are granted to posts.
+#[async_trait]
+pub trait PermissionRepository: Send + Sync {
+    /// Returns what the user may do with the post: `Owner` for its author, the higher one of the
+    /// granted role and the one their role in the workspace of the post gives for the other users and
+    /// `None` for everybody else. Fails with `post.not_found` if there is
+    /// no such post.
+    async fn get_permission(
+        &self,
+        post_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+    ) -> Result<Option<Permission>, errors::Error>;
+
+    /// Grants the access. Fails with `permission.exists` if the user already has it.
+    async fn grant_permission(
+        &self,
+        permission: PostPermission,
+    ) -> Result<PostPermission, errors::Error>;
+
+    /// Changes the role of a user. Fails with `permission.not_found` if they have no access.
+    async fn update_permission(
+        &self,
+        post_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+        role: Permission,
+    ) -> Result<(), errors::Error>;
+
+    /// Takes the access away, with the important flag of the user on the post.
+    async fn revoke_permission(
+        &self,
+        post_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+    ) -> Result<(), errors::Error>;
+
+    /// Returns the users the post is shared with, in the order they got the access.
+    async fn get_post_permissions(
+        &self,
+        post_id: uuid::Uuid,
+    ) -> Result<Vec<ResponsePermission>, errors::Error>;
+}
+
+/// The `WorkspaceRepository` trait is the storage of the workspaces and their members.
+#[async_trait]
+pub trait WorkspaceRepository: Send + Sync {
+    /// Adds a workspace with `owner_id` as its owner.
+    async fn add_workspace(
+        &self,
+        workspace: Workspace,
+        owner_id: uuid::Uuid,
+    ) -> Result<Workspace, errors::Error>;
+
+    /// Returns the workspaces the user is a member of, the personal one first.
+    async fn get_user_workspaces(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> Result<Vec<ResponseWorkspace>, errors::Error>;
+
+    /// Returns the workspace with the role of the user, `None` if they are not a member of it.
+    async fn find_membership(
+        &self,
+        workspace_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+    ) -> Result<Option<ResponseWorkspace>, errors::Error>;
+
+    /// Changes the name of the workspace.
+    async fn rename_workspace(
+        &self,
+        workspace_id: uuid::Uuid,
+        name: String,
+    ) -> Result<(), errors::Error>;
+
+    /// Adds a member. Fails with `workspace.member_exists` if the user is a member already.
+    async fn add_member(&self, member: WorkspaceMember) -> Result<WorkspaceMember, errors::Error>;
+
+    /// Changes the role of a member. Fails with `workspace.member_not_found` if the user is not one.
+    async fn update_member(
+        &self,
+        workspace_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+        role: WorkspaceRole,
+    ) -> Result<(), errors::Error>;
+
+    /// Removes a member. Fails with `workspace.member_not_found` if the user is not one.
+    async fn remove_member(
+        &self,
+        workspace_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+    ) -> Result<(), errors::Error>;
+
+    /// Returns the members of the workspace, in the order they joined.
+    async fn get_members(
+        &self,
+        workspace_id: uuid::Uuid,
+    ) -> Result<Vec<ResponseMember>, errors::Error>;
+}
+
+/// The `NotebookRepository` trait is the storage of the notebooks of the workspaces and of the
+/// notebook each post is in.
+#[async_trait]
+pub trait NotebookRepository: Send + Sync {
+    /// Adds a notebook.
+    async fn add_notebook(&self, notebook: Notebook) -> Result<Notebook, errors::Error>;
+
+    /// Returns the notebooks of the workspace, oldest first, each with the number of the posts right
+    /// in it.
+    async fn get_notebooks(
+        &self,
+        workspace_id: uuid::Uuid,
+    ) -> Result<Vec<(Notebook, i64)>, errors::Error>;
+
+    /// Finds the notebook by id. Fails with `notebook.not_found` if there is no such notebook.
+    async fn find_notebook(&self, notebook_id: uuid::Uuid) -> Result<Notebook, errors::Error>;
+
+    /// Changes the name and the parent of the notebook.
+    async fn update_notebook(
+        &self,
+        notebook_id: uuid::Uuid,
+        name: String,
+        parent_id: Option<uuid::Uuid>,
+    ) -> Result<Notebook, errors::Error>;
+
+    /// Deletes the notebook with the notebooks inside it. Their posts stay in the workspace, outside
+    /// of the notebooks.
+    async fn delete_notebook(&self, notebook_id: uuid::Uuid) -> Result<(), errors::Error>;
+
+    /// Puts the post into the notebook, `None` takes it out of the notebooks.
+    async fn move_post(
+        &self,
+        post_id: uuid::Uuid,
+        notebook_id: Option<uuid::Uuid>,
+    ) -> Result<(), errors::Error>;
+
+    /// Returns the posts of the workspace that are in one of the notebooks, with the important flags
+    /// of `user_id`, a member of it, the important ones first.
+    async fn get_notebook_posts(
+        &self,
+        user_id: uuid::Uuid,
+        workspace_id: uuid::Uuid,
+        notebook_ids: Vec<uuid::Uuid>,
+    ) -> Result<Vec<ResponsePost>, errors::Error>;
+}
+
+/// The `AttachmentRepository` trait is the storage of the attachment records. The content of the
+/// files is kept by the `attachments::BlobStore`.
+#[async_trait]
+pub trait AttachmentRepository: Send + Sync {
+    /// Adds an attachment. Fails with `attachment.quota_exceeded` if the attachments of the owner would
+    /// take more than `quota` bytes together, `None` is no limit.
+    async fn add_attachment(
+        &self,
+        attachment: Attachment,
+        quota: Option<i64>,
+    ) -> Result<Attachment, errors::Error>;
+
+    /// Finds the attachment by id.
+    async fn find_attachment(&self, attachment_id: uuid::Uuid)
+        -> Result<Attachment, errors::Error>;
+
+    /// Returns the attachments of the post, oldest first.
+    async fn get_post_attachments(
+        &self,
+        post_id: uuid::Uuid,
+    ) -> Result<Vec<Attachment>, errors::Error>;
+
+    /// Deletes the attachment.
+    async fn delete_attachment(&self, attachment_id: uuid::Uuid) -> Result<(), errors::Error>;
+
+    /// Returns the hashes of the files the user has attached, each one once.
+    async fn get_user_hashes(&self, user_id: uuid::Uuid) -> Result<Vec<String>, errors::Error>;
+
+    /// Returns `true` if an attachment has the content with the hash.
+    async fn is_hash_used(&self, hash: &str) -> Result<bool, errors::Error>;
+}
+
+/// The `ShareRepository` trait is the storage of the share links of the posts.
+#[async_trait]
+pub trait ShareRepository: Send + Sync {
+    /// Adds a share link.
+    async fn add_share_link(&self, link: ShareLink) -> Result<ShareLink, errors::Error>;
+
+    /// Finds the share link by id.
+    async fn find_share_link(&self, link_id: uuid::Uuid) -> Result<ShareLink, errors::Error>;
+
+    /// Finds the share link by the hash of its token, expired links too.
+    async fn find_share_link_by_token(&self, token_hash: &str) -> Result<ShareLink, errors::Error>;
+
+    /// Returns the share links of the post, oldest first.
+    async fn get_post_share_links(
+        &self,
+        post_id: uuid::Uuid,
+    ) -> Result<Vec<ShareLink>, errors::Error>;
+
+    /// Deletes the share link, so it stops working.
+    async fn delete_share_link(&self, link_id: uuid::Uuid) -> Result<(), errors::Error>;
+}
+
+/// The `AuditRepository` trait is the storage of the audit log.
+#[async_trait]
+pub trait AuditRepository: Send + Sync {
+    /// Writes an event.
+    async fn add_event(&self, event: AuditEvent) -> Result<(), errors::Error>;
+
+    /// Returns the events that match the filter, newest first.
+    async fn get_events(&self, filter: AuditFilter) -> Result<Vec<AuditEvent>, errors::Error>;
+
+    /// Deletes the events older than `before` and returns how many were deleted.
+    async fn delete_events_before(
+        &self,
+        before: time::OffsetDateTime,
+    ) -> Result<usize, errors::Error>;
+}
+
+/// The `SchemaRepository` trait gives access to the migrations of the database behind the
+/// repositories.
+#[async_trait]
+pub trait SchemaRepository: Send + Sync {
+    /// Applies the pending migrations and returns their versions.
+    async fn run_pending_migrations(&self) -> Result<Vec<String>, errors::Error>;
+
+    /// Returns `true` if the schema is behind the binary. Fails if the database is not reachable.
+    async fn has_pending_migrations(&self) -> Result<bool, errors::Error>;
+}
@@ -1,5 +1,39 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    attachments (id) {
+        id -> Uuid,
+        post_id -> Uuid,
+        user_id -> Uuid,
+        #[max_length = 255]
+        file_name -> Varchar,
+        #[max_length = 255]
+        content_type -> Varchar,
+        size -> Int8,
+        #[max_length = 64]
+        hash -> Varchar,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    audit_events (id) {
+        id -> Uuid,
+        actor_id -> Nullable<Uuid>,
+        #[max_length = 50]
+        actor_name -> Nullable<Varchar>,
+        #[max_length = 32]
+        action -> Varchar,
+        target_id -> Nullable<Uuid>,
+        success -> Bool,
+        #[max_length = 64]
+        ip -> Nullable<Varchar>,
+        #[max_length = 256]
+        user_agent -> Nullable<Varchar>,
+        created_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     important_posts (user_id, post_id) {
         user_id -> Uuid,
@@ -7,6 +41,27 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    notebooks (id) {
+        id -> Uuid,
+        workspace_id -> Uuid,
+        parent_id -> Nullable<Uuid>,
+        #[max_length = 64]
+        name -> Varchar,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    post_permissions (post_id, user_id) {
+        post_id -> Uuid,
+        user_id -> Uuid,
+        #[max_length = 16]
+        role -> Varchar,
+        created_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     posts (id) {
         id -> Uuid,
@@ -14,6 +69,21 @@ diesel::table! {
         title -> Text,
         description -> Nullable<Text>,
         created_at -> Timestamptz,
+        workspace_id -> Uuid,
+        notebook_id -> Nullable<Uuid>,
+    }
+}
+
+diesel::table! {
+    share_links (id) {
+        id -> Uuid,
+        post_id -> Uuid,
+        #[max_length = 64]
+        token_hash -> Varchar,
+        #[max_length = 255]
+        password_hash -> Nullable<Varchar>,
+        expires_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
     }
 }
 
@@ -26,11 +96,58 @@ diesel::table! {
         password -> Varchar,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
+        disabled -> Bool,
+        #[max_length = 16]
+        role -> Varchar,
+        session_version -> Int4,
+        #[max_length = 200]
+        username_key -> Varchar,
+    }
+}
+
+diesel::table! {
+    workspace_members (workspace_id, user_id) {
+        workspace_id -> Uuid,
+        user_id -> Uuid,
+        #[max_length = 16]
+        role -> Varchar,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    workspaces (id) {
+        id -> Uuid,
+        #[max_length = 64]
+        name -> Varchar,
+        personal -> Bool,
+        created_at -> Timestamptz,
     }
 }
 
+diesel::joinable!(attachments -> posts (post_id));
+diesel::joinable!(attachments -> users (user_id));
 diesel::joinable!(important_posts -> posts (post_id));
 diesel::joinable!(important_posts -> users (user_id));
+diesel::joinable!(notebooks -> workspaces (workspace_id));
+diesel::joinable!(post_permissions -> posts (post_id));
+diesel::joinable!(post_permissions -> users (user_id));
+diesel::joinable!(posts -> notebooks (notebook_id));
 diesel::joinable!(posts -> users (user_id));
+diesel::joinable!(posts -> workspaces (workspace_id));
+diesel::joinable!(share_links -> posts (post_id));
+diesel::joinable!(workspace_members -> users (user_id));
+diesel::joinable!(workspace_members -> workspaces (workspace_id));
 
-diesel::allow_tables_to_appear_in_same_query!(important_posts, posts, users,);
+diesel::allow_tables_to_appear_in_same_query!(
+    attachments,
+    audit_events,
+    important_posts,
+    notebooks,
+    post_permissions,
+    posts,
+    share_links,
+    users,
+    workspace_members,
+    workspaces,
+);
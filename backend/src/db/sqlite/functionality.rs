@@ -0,0 +1,1755 @@
+use std::collections::{HashMap, HashSet};
+
+use common::{
+    Permission, PostsUpdateForm, ResponseMember, ResponsePermission, ResponsePost,
+    ResponseWorkspace, Role, WorkspaceRole,
+};
+use diesel::prelude::*;
+use tracing::instrument;
+
+use super::models::{
+    parse_id, SqliteAttachment, SqliteAuditEvent, SqliteNotebook, SqlitePost, SqlitePostPermission,
+    SqliteShareLink, SqliteUser, SqliteWorkspace, SqliteWorkspaceMember,
+};
+use super::SqlitePool;
+use crate::db::{
+    attachment_not_found, effective_permission, member_exists, member_not_found, member_role,
+    notebook_not_found, permission_exists, permission_not_found, post_not_found, quota_exceeded,
+    share_link_not_found, summarize_users, user_not_found, verify_password_hash,
+    workspace_not_found, Attachment, AuditEvent, AuditFilter, Notebook, Post, PostPermission,
+    ShareLink, User, UserSummary, Workspace, WorkspaceMember, INSERT_CHUNK,
+};
+use crate::errors;
+use crate::schema::user::{username_key, Password};
+
+fn get_connection(
+    connection: &SqlitePool,
+) -> Result<
+    diesel::r2d2::PooledConnection<diesel::r2d2::ConnectionManager<SqliteConnection>>,
+    errors::Error,
+> {
+    connection.get().map_err(|e| {
+        tracing::error!("Failed to get db connection pool!");
+        errors::Error::new(
+            Some(e.to_string()),
+            Some("Failed to get db connection pool!".to_string()),
+            errors::ErrorTypes::DbError,
+        )
+    })
+}
+
+/// The SQLite version of `db::db_add_user`.
+#[instrument(name = "Add new user", skip(connection))]
+pub fn sqlite_add_user(user: User, connection: &SqlitePool) -> Result<User, errors::Error> {
+    use super::schema::users::dsl::{username_key, users};
+    use super::schema::{workspace_members, workspaces};
+    let mut conn = get_connection(connection)?;
+
+    let exists = users
+        .filter(username_key.eq(&user.username_key))
+        .select(SqliteUser::as_select())
+        .first(&mut conn)
+        .optional()
+        .map_err(|e| errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError))?
+        .is_some();
+
+    if exists {
+        tracing::error!("The username already exists!");
+        return Err(errors::Error::from_code(
+            "auth.username_taken",
+            errors::ErrorTypes::Conflict,
+        ));
+    }
+
+    let workspace = Workspace::personal(&user);
+    let member = SqliteWorkspaceMember::from(WorkspaceMember {
+        workspace_id: workspace.id,
+        user_id: user.id,
+        role: WorkspaceRole::Owner.to_string(),
+        created_at: workspace.created_at,
+    });
+    let row = conn
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            let row: SqliteUser = diesel::insert_into(users)
+                .values(SqliteUser::from(user))
+                .returning(SqliteUser::as_returning())
+                .get_result(conn)?;
+            diesel::insert_into(workspaces::table)
+                .values(&SqliteWorkspace::from(workspace))
+                .execute(conn)?;
+            diesel::insert_into(workspace_members::table)
+                .values(&member)
+                .execute(conn)?;
+            Ok(row)
+        })
+        .map_err(|e| {
+            tracing::error!("Failed to add user to the database!");
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    let user = User::try_from(row)?;
+    tracing::info!("User: {:?} added successfully!", user);
+
+    Ok(user)
+}
+
+/// The SQLite version of `db::db_check_user`.
+#[instrument(name = "Check logged in data", skip(connection))]
+pub fn sqlite_check_user(
+    name: &str,
+    password: Password,
+    connection: &SqlitePool,
+) -> Result<User, errors::Error> {
+    use super::schema::users::dsl::users;
+    let mut conn = get_connection(connection)?;
+
+    let founded_user = users
+        .filter(super::schema::users::username_key.eq(username_key(name)))
+        .select(SqliteUser::as_select())
+        .first(&mut conn)
+        .map_err(|e| {
+            tracing::info!("User with username {} not found!", name);
+            errors::Error::from_code(
+                "auth.unknown_user",
+                errors::ErrorTypes::Auth(errors::Auth::Authentication),
+            )
+            .with_cause(e)
+        })?;
+    let founded_user = User::try_from(founded_user)?;
+
+    verify_password_hash(&founded_user.password, &password.0)?;
+    tracing::info!("User: {:?} verified!", founded_user);
+
+    Ok(founded_user)
+}
+
+/// The SQLite version of `db::db_find_user`.
+#[instrument(name = "Find the user in db", skip(connection))]
+pub fn sqlite_find_user(
+    user_id: uuid::Uuid,
+    connection: &SqlitePool,
+) -> Result<User, errors::Error> {
+    use super::schema::users::dsl::{id, users};
+    let mut conn = get_connection(connection)?;
+
+    let user = users
+        .filter(id.eq(user_id.to_string()))
+        .select(SqliteUser::as_select())
+        .first(&mut conn)
+        .map_err(|e| {
+            tracing::error!("Failed to find user with id: {}", user_id);
+            errors::Error::new(
+                Some(e.to_string()),
+                Some("Invalid Uuid inside refresh token!".to_string()),
+                errors::ErrorTypes::Auth(errors::Auth::Authentication),
+            )
+        })?;
+    let user = User::try_from(user)?;
+    tracing::info!("Founded user: {:?} successfully!", user);
+
+    Ok(user)
+}
+
+/// The SQLite version of `db::db_get_posts`.
+#[instrument(name = "Get all posts", skip(connection))]
+pub fn sqlite_get_posts(
+    user_id: uuid::Uuid,
+    workspace_id: uuid::Uuid,
+    connection: &SqlitePool,
+) -> Result<Vec<ResponsePost>, errors::Error> {
+    use super::schema::{important_posts, post_permissions, posts, users, workspaces};
+    let mut conn = get_connection(connection)?;
+    let user_id_text = user_id.to_string();
+    let workspace_id_text = workspace_id.to_string();
+
+    //the roles of the posts shared with the user
+    let roles: HashMap<String, String> = post_permissions::table
+        .filter(post_permissions::user_id.eq(&user_id_text))
+        .select((post_permissions::post_id, post_permissions::role))
+        .load::<(String, String)>(&mut conn)
+        .map_err(|e| {
+            tracing::error!("Failed to get the posts shared with user {}", user_id);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?
+        .into_iter()
+        .collect();
+    let members = sqlite_memberships(&mut conn, user_id)?;
+
+    let personal: bool = workspaces::table
+        .filter(workspaces::id.eq(&workspace_id_text))
+        .select(workspaces::personal)
+        .first(&mut conn)
+        .optional()
+        .map_err(|e| errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError))?
+        .ok_or_else(workspace_not_found)?;
+
+    let mut query = posts::table
+        .inner_join(users::table)
+        .select((SqlitePost::as_select(), users::username))
+        .into_boxed();
+    query = match personal {
+        true => {
+            let shared = post_permissions::table
+                .filter(post_permissions::user_id.eq(user_id_text.clone()))
+                .select(post_permissions::post_id);
+            query.filter(
+                posts::workspace_id
+                    .eq(workspace_id_text)
+                    .or(posts::id.eq_any(shared)),
+            )
+        }
+        false => query.filter(posts::workspace_id.eq(workspace_id_text)),
+    };
+    let all_posts: Vec<(SqlitePost, String)> = query
+        .order(posts::created_at.desc())
+        .load(&mut conn)
+        .map_err(|e| {
+            tracing::error!("Failed to get all posts");
+            errors::Error::new(
+                Some(e.to_string()),
+                Some("Can not get all items from table posts!".to_string()),
+                errors::ErrorTypes::DbError,
+            )
+        })?;
+
+    let important: HashSet<String> = important_posts::table
+        .select(important_posts::post_id)
+        .filter(important_posts::user_id.eq(&user_id_text))
+        .load(&mut conn)
+        .map_err(|e| {
+            tracing::error!("Failed to get important posts");
+            errors::Error::new(
+                Some(e.to_string()),
+                Some("Can not get important items from table important_posts!".to_string()),
+                errors::ErrorTypes::DbError,
+            )
+        })?
+        .into_iter()
+        .collect();
+
+    let mut response_posts = Vec::new();
+    for (row, owner) in all_posts {
+        let important = important.contains(&row.id);
+        let Some(permission) = effective_permission(
+            row.user_id == user_id_text,
+            roles.get(&row.id).map(String::as_str),
+            members.get(&row.workspace_id).map(String::as_str),
+        ) else {
+            continue;
+        };
+        response_posts.push(Post::try_from(row)?.response(owner, important, permission));
+    }
+
+    //sort the posts so that important posts come first
+    response_posts.sort_by(|a, b| b.important.cmp(&a.important));
+
+    tracing::info!("Got {} posts from db!", response_posts.len());
+
+    Ok(response_posts)
+}
+
+/// The SQLite version of `db::db_get_user_posts`.
+#[instrument(name = "Get a page of workspace's posts", skip(connection))]
+pub fn sqlite_get_user_posts(
+    user_id: uuid::Uuid,
+    workspace_id: uuid::Uuid,
+    offset: i64,
+    limit: i64,
+    connection: &SqlitePool,
+) -> Result<Vec<ResponsePost>, errors::Error> {
+    use super::schema::{important_posts, posts, users};
+    let mut conn = get_connection(connection)?;
+    let user_id_text = user_id.to_string();
+    let members = sqlite_memberships(&mut conn, user_id)?;
+    let member = members.get(&workspace_id.to_string()).map(String::as_str);
+
+    let page: Vec<(SqlitePost, String)> = posts::table
+        .inner_join(users::table)
+        .filter(posts::workspace_id.eq(workspace_id.to_string()))
+        .select((SqlitePost::as_select(), users::username))
+        .order((posts::created_at.asc(), posts::id.asc()))
+        .limit(limit)
+        .offset(offset)
+        .load(&mut conn)
+        .map_err(|e| {
+            tracing::error!("Failed to get the posts of workspace {}", workspace_id);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    let ids: Vec<&str> = page.iter().map(|(row, _)| row.id.as_str()).collect();
+    let important: HashSet<String> = important_posts::table
+        .select(important_posts::post_id)
+        .filter(important_posts::user_id.eq(&user_id_text))
+        .filter(important_posts::post_id.eq_any(ids))
+        .load(&mut conn)
+        .map_err(|e| errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError))?
+        .into_iter()
+        .collect();
+
+    let mut posts = Vec::new();
+    for (row, owner) in page {
+        let important = important.contains(&row.id);
+        let Some(permission) = effective_permission(row.user_id == user_id_text, None, member)
+        else {
+            continue;
+        };
+        posts.push(Post::try_from(row)?.response(owner, important, permission));
+    }
+
+    Ok(posts)
+}
+
+/// The SQLite version of `db::db_find_post`.
+#[instrument(name = "Find post", skip(connection))]
+pub fn sqlite_find_post(
+    post_id: uuid::Uuid,
+    connection: &SqlitePool,
+) -> Result<Post, errors::Error> {
+    use super::schema::posts::dsl::{id, posts};
+    let mut conn = get_connection(connection)?;
+
+    posts
+        .filter(id.eq(post_id.to_string()))
+        .select(SqlitePost::as_select())
+        .first(&mut conn)
+        .optional()
+        .map_err(|e| {
+            tracing::error!("Failed to find post {}", post_id);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?
+        .ok_or_else(|| post_not_found(post_id))
+        .and_then(Post::try_from)
+}
+
+/// The SQLite version of `db::db_update_description`.
+#[instrument(name = "Update post description", skip(description, connection))]
+pub fn sqlite_update_description(
+    post_id: uuid::Uuid,
+    description: Option<String>,
+    connection: &SqlitePool,
+) -> Result<(), errors::Error> {
+    use super::schema::posts::dsl;
+    let mut conn = get_connection(connection)?;
+
+    let updated = diesel::update(dsl::posts.filter(dsl::id.eq(post_id.to_string())))
+        .set(dsl::description.eq(description))
+        .execute(&mut conn)
+        .map_err(|e| {
+            tracing::error!("Failed to update the description of post {}", post_id);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    match updated {
+        0 => Err(post_not_found(post_id)),
+        _ => Ok(()),
+    }
+}
+
+/// The SQLite version of `db::db_delete_post`.
+#[instrument(name = "Delete post", skip(connection))]
+pub fn sqlite_delete_post(
+    post_id: uuid::Uuid,
+    connection: &SqlitePool,
+) -> Result<(), errors::Error> {
+    use super::schema::{important_posts, posts};
+    let mut conn = get_connection(connection)?;
+    let post_id_text = post_id.to_string();
+
+    let deleted = conn
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            diesel::delete(
+                important_posts::table.filter(important_posts::post_id.eq(&post_id_text)),
+            )
+            .execute(conn)?;
+            diesel::delete(posts::table.filter(posts::id.eq(&post_id_text))).execute(conn)
+        })
+        .map_err(|e| {
+            tracing::error!("Failed to delete post {}", post_id);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    match deleted {
+        0 => Err(post_not_found(post_id)),
+        _ => {
+            tracing::info!("Post {} deleted", post_id);
+            Ok(())
+        }
+    }
+}
+
+/// The SQLite version of `db::db_add_post`.
+#[instrument(name = "Add new post", skip(connection))]
+pub fn sqlite_add_post(post: Post, connection: &SqlitePool) -> Result<ResponsePost, errors::Error> {
+    use super::schema::posts::dsl::posts;
+    let mut conn = get_connection(connection)?;
+    let post_id = post.id;
+
+    let row: SqlitePost = diesel::insert_into(posts)
+        .values(SqlitePost::from(post))
+        .returning(SqlitePost::as_returning())
+        .get_result(&mut conn)
+        .map_err(|e| {
+            tracing::error!("Failed to add new post: {} to the database!", post_id);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+    let db_post = Post::try_from(row)?;
+
+    let owner = sqlite_username(&mut conn, db_post.user_id)?;
+    let post = db_post.response(owner, false, Permission::Owner);
+    tracing::info!("Post: {} added successfully!", post.id);
+
+    Ok(post)
+}
+
+/// The SQLite version of `db::db_add_posts`.
+#[instrument(name = "Add many posts", skip_all, fields(count = new_posts.len()))]
+pub fn sqlite_add_posts(
+    new_posts: Vec<(Post, bool)>,
+    connection: &SqlitePool,
+) -> Result<usize, errors::Error> {
+    use super::schema::{important_posts, posts};
+    let mut conn = get_connection(connection)?;
+
+    let important: Vec<_> = new_posts
+        .iter()
+        .filter(|(_, important)| *important)
+        .map(|(post, _)| {
+            (
+                important_posts::user_id.eq(post.user_id.to_string()),
+                important_posts::post_id.eq(post.id.to_string()),
+            )
+        })
+        .collect();
+    let rows: Vec<SqlitePost> = new_posts
+        .into_iter()
+        .map(|(post, _)| SqlitePost::from(post))
+        .collect();
+
+    let added = conn
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            let mut added = 0;
+            for chunk in rows.chunks(INSERT_CHUNK) {
+                added += diesel::insert_into(posts::table)
+                    .values(chunk)
+                    .execute(conn)?;
+            }
+            for chunk in important.chunks(INSERT_CHUNK) {
+                diesel::insert_into(important_posts::table)
+                    .values(chunk)
+                    .execute(conn)?;
+            }
+            Ok(added)
+        })
+        .map_err(|e| {
+            tracing::error!("Failed to add the posts!");
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+    tracing::info!("{} posts added successfully!", added);
+
+    Ok(added)
+}
+
+/// The SQLite version of `db::db_update_post`.
+#[instrument(name = "Update posts's impotant field!", skip(connection))]
+pub fn sqlite_update_post(
+    user_id: uuid::Uuid,
+    data: PostsUpdateForm,
+    connection: &SqlitePool,
+) -> Result<(), errors::Error> {
+    use super::schema::important_posts;
+    let mut conn = get_connection(connection)?;
+
+    //only the posts the user can see are marked
+    if sqlite_permission(&mut conn, data.id, user_id)?.is_none() {
+        return Err(post_not_found(data.id));
+    }
+
+    if data.important {
+        diesel::insert_into(important_posts::table)
+            .values((
+                important_posts::user_id.eq(user_id.to_string()),
+                important_posts::post_id.eq(data.id.to_string()),
+            ))
+            .execute(&mut conn)
+            .map_err(|e| {
+                tracing::error!("Failed to make current post important!");
+                errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+            })?;
+    } else {
+        diesel::delete(
+            important_posts::table
+                .filter(important_posts::user_id.eq(user_id.to_string()))
+                .filter(important_posts::post_id.eq(data.id.to_string())),
+        )
+        .execute(&mut conn)
+        .map_err(|e| {
+            tracing::error!("Failed to make current post not important!");
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+    }
+    tracing::info!("Updated post with id:{:?}", data.id);
+
+    Ok(())
+}
+
+/// The SQLite version of `db::db_find_user_by_username`.
+#[instrument(name = "Find the user by username", skip(connection))]
+pub fn sqlite_find_user_by_username(
+    name: &str,
+    connection: &SqlitePool,
+) -> Result<User, errors::Error> {
+    use super::schema::users::dsl::users;
+    let mut conn = get_connection(connection)?;
+
+    let user = users
+        .filter(super::schema::users::username_key.eq(username_key(name)))
+        .select(SqliteUser::as_select())
+        .first(&mut conn)
+        .optional()
+        .map_err(|e| errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError))?
+        .ok_or_else(|| user_not_found(name))?;
+
+    User::try_from(user)
+}
+
+/// The SQLite version of `db::db_list_users`.
+#[instrument(name = "List users", skip(connection))]
+pub fn sqlite_list_users(connection: &SqlitePool) -> Result<Vec<UserSummary>, errors::Error> {
+    use super::schema::{posts, users};
+    let mut conn = get_connection(connection)?;
+
+    let all_users = users::table
+        .select(SqliteUser::as_select())
+        .order(users::created_at.asc())
+        .load(&mut conn)
+        .map_err(|e| {
+            tracing::error!("Failed to get all users");
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?
+        .into_iter()
+        .map(User::try_from)
+        .collect::<Result<Vec<User>, errors::Error>>()?;
+
+    let counts = posts::table
+        .group_by(posts::user_id)
+        .select((posts::user_id, diesel::dsl::count_star()))
+        .load::<(String, i64)>(&mut conn)
+        .map_err(|e| {
+            tracing::error!("Failed to count posts");
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?
+        .into_iter()
+        .map(|(user_id, count)| Ok((parse_id(&user_id)?, count)))
+        .collect::<Result<HashMap<uuid::Uuid, i64>, errors::Error>>()?;
+
+    Ok(summarize_users(all_users, &counts))
+}
+
+/// The SQLite version of `db::db_update_password`.
+#[instrument(name = "Update user's password", skip(password_hash, connection))]
+pub fn sqlite_update_password(
+    user_id: uuid::Uuid,
+    password_hash: String,
+    connection: &SqlitePool,
+) -> Result<(), errors::Error> {
+    use super::schema::users::dsl::{id, password, updated_at, users};
+    let mut conn = get_connection(connection)?;
+
+    let updated = diesel::update(users.filter(id.eq(user_id.to_string())))
+        .set((
+            password.eq(password_hash),
+            updated_at.eq(time::OffsetDateTime::now_utc()),
+        ))
+        .execute(&mut conn)
+        .map_err(|e| errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError))?;
+
+    if updated == 0 {
+        return Err(user_not_found(user_id));
+    }
+    tracing::info!("Password of user {} updated", user_id);
+
+    Ok(())
+}
+
+/// The SQLite version of `db::db_set_disabled`.
+#[instrument(name = "Disable user", skip(connection))]
+pub fn sqlite_set_disabled(
+    user_id: uuid::Uuid,
+    value: bool,
+    connection: &SqlitePool,
+) -> Result<(), errors::Error> {
+    use super::schema::users::dsl::{disabled, id, updated_at, users};
+    let mut conn = get_connection(connection)?;
+
+    let updated = diesel::update(users.filter(id.eq(user_id.to_string())))
+        .set((
+            disabled.eq(value),
+            updated_at.eq(time::OffsetDateTime::now_utc()),
+        ))
+        .execute(&mut conn)
+        .map_err(|e| errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError))?;
+
+    if updated == 0 {
+        return Err(user_not_found(user_id));
+    }
+    tracing::info!("User {} disabled: {}", user_id, value);
+
+    Ok(())
+}
+
+/// The SQLite version of `db::db_delete_user`.
+#[instrument(name = "Delete user", skip(connection))]
+pub fn sqlite_delete_user(
+    user_id: uuid::Uuid,
+    connection: &SqlitePool,
+) -> Result<(), errors::Error> {
+    use super::schema::{
+        attachments, important_posts, posts, users, workspace_members, workspaces,
+    };
+    let mut conn = get_connection(connection)?;
+    let user_id_text = user_id.to_string();
+
+    let deleted = conn
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            diesel::delete(attachments::table.filter(attachments::user_id.eq(&user_id_text)))
+                .execute(conn)?;
+            let user_posts = posts::table
+                .filter(posts::user_id.eq(&user_id_text))
+                .select(posts::id);
+            diesel::delete(
+                important_posts::table.filter(
+                    important_posts::user_id
+                        .eq(&user_id_text)
+                        .or(important_posts::post_id.eq_any(user_posts)),
+                ),
+            )
+            .execute(conn)?;
+            diesel::delete(posts::table.filter(posts::user_id.eq(&user_id_text))).execute(conn)?;
+            //the personal workspace goes with its owner, the team ones stay
+            let personal: Vec<String> = workspaces::table
+                .inner_join(workspace_members::table)
+                .filter(workspaces::personal.eq(true))
+                .filter(workspace_members::user_id.eq(&user_id_text))
+                .select(workspaces::id)
+                .load(conn)?;
+            //posts.workspace_id has no foreign key in SQLite
+            diesel::delete(posts::table.filter(posts::workspace_id.eq_any(&personal)))
+                .execute(conn)?;
+            diesel::delete(workspaces::table.filter(workspaces::id.eq_any(&personal)))
+                .execute(conn)?;
+            diesel::delete(users::table.filter(users::id.eq(&user_id_text))).execute(conn)
+        })
+        .map_err(|e| {
+            tracing::error!("Failed to delete user {}", user_id);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    if deleted == 0 {
+        return Err(user_not_found(user_id));
+    }
+    tracing::info!("User {} deleted", user_id);
+
+    Ok(())
+}
+
+/// The SQLite version of `db::db_set_role`.
+#[instrument(name = "Set user's role", skip(connection))]
+pub fn sqlite_set_role(
+    user_id: uuid::Uuid,
+    value: Role,
+    connection: &SqlitePool,
+) -> Result<(), errors::Error> {
+    use super::schema::users::dsl::{id, role, updated_at, users};
+    let mut conn = get_connection(connection)?;
+
+    let updated = diesel::update(users.filter(id.eq(user_id.to_string())))
+        .set((
+            role.eq(value.as_str()),
+            updated_at.eq(time::OffsetDateTime::now_utc()),
+        ))
+        .execute(&mut conn)
+        .map_err(|e| errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError))?;
+
+    if updated == 0 {
+        return Err(user_not_found(user_id));
+    }
+    tracing::info!("Role of user {} changed to {}", user_id, value);
+
+    Ok(())
+}
+
+/// The SQLite version of `db::db_revoke_sessions`.
+#[instrument(name = "Revoke user's sessions", skip(connection))]
+pub fn sqlite_revoke_sessions(
+    user_id: uuid::Uuid,
+    connection: &SqlitePool,
+) -> Result<i32, errors::Error> {
+    use super::schema::users::dsl::{id, session_version, users};
+    let mut conn = get_connection(connection)?;
+
+    let version = diesel::update(users.filter(id.eq(user_id.to_string())))
+        .set(session_version.eq(session_version + 1))
+        .returning(session_version)
+        .get_result(&mut conn)
+        .optional()
+        .map_err(|e| errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError))?
+        .ok_or_else(|| user_not_found(user_id))?;
+    tracing::info!("Sessions of user {} revoked", user_id);
+
+    Ok(version)
+}
+
+/// The SQLite version of `db::db_add_audit_event`.
+#[instrument(name = "Add audit event", skip(connection))]
+pub fn sqlite_add_audit_event(
+    event: AuditEvent,
+    connection: &SqlitePool,
+) -> Result<(), errors::Error> {
+    use super::schema::audit_events::dsl::audit_events;
+    let mut conn = get_connection(connection)?;
+
+    diesel::insert_into(audit_events)
+        .values(SqliteAuditEvent::from(event))
+        .execute(&mut conn)
+        .map_err(|e| {
+            tracing::error!("Failed to write audit event!");
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    Ok(())
+}
+
+/// The SQLite version of `db::db_get_audit_events`.
+#[instrument(name = "Get audit events", skip(connection))]
+pub fn sqlite_get_audit_events(
+    filter: AuditFilter,
+    connection: &SqlitePool,
+) -> Result<Vec<AuditEvent>, errors::Error> {
+    use super::schema::audit_events::dsl::*;
+    let mut conn = get_connection(connection)?;
+
+    let mut query = audit_events
+        .select(SqliteAuditEvent::as_select())
+        .into_boxed();
+    if let Some(value) = filter.actor_id {
+        query = query.filter(actor_id.eq(value.to_string()));
+    }
+    if let Some(value) = &filter.action {
+        query = query.filter(action.eq(value.clone()));
+    }
+    if let Some(value) = filter.target_id {
+        query = query.filter(target_id.eq(value.to_string()));
+    }
+    if let Some(value) = filter.success {
+        query = query.filter(success.eq(value));
+    }
+    if let Some(value) = filter.since {
+        query = query.filter(created_at.ge(value));
+    }
+    if let Some(value) = filter.until {
+        query = query.filter(created_at.lt(value));
+    }
+
+    query
+        .order(created_at.desc())
+        .limit(filter.limit())
+        .offset(filter.offset())
+        .load(&mut conn)
+        .map_err(|e| {
+            tracing::error!("Failed to get audit events!");
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?
+        .into_iter()
+        .map(AuditEvent::try_from)
+        .collect()
+}
+
+/// The SQLite version of `db::db_delete_audit_events_before`.
+#[instrument(name = "Delete old audit events", skip(connection))]
+pub fn sqlite_delete_audit_events_before(
+    before: time::OffsetDateTime,
+    connection: &SqlitePool,
+) -> Result<usize, errors::Error> {
+    use super::schema::audit_events::dsl::{audit_events, created_at};
+    let mut conn = get_connection(connection)?;
+
+    diesel::delete(audit_events.filter(created_at.lt(before)))
+        .execute(&mut conn)
+        .map_err(|e| {
+            tracing::error!("Failed to delete old audit events!");
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })
+}
+
+/// The SQLite version of `db::db_add_attachment`. The transaction takes the write lock right away, so
+/// parallel uploads can not exceed the quota together.
+#[instrument(name = "Add attachment", skip(connection))]
+pub fn sqlite_add_attachment(
+    attachment: Attachment,
+    quota: Option<i64>,
+    connection: &SqlitePool,
+) -> Result<Attachment, errors::Error> {
+    use super::schema::attachments;
+    let mut conn = get_connection(connection)?;
+    let owner = attachment.user_id;
+    let size = attachment.size;
+    let row = SqliteAttachment::from(attachment);
+
+    let added = conn
+        .immediate_transaction::<_, diesel::result::Error, _>(|conn| {
+            if let Some(quota) = quota {
+                let sizes: Vec<i64> = attachments::table
+                    .filter(attachments::user_id.eq(owner.to_string()))
+                    .select(attachments::size)
+                    .load(conn)?;
+                if sizes.iter().sum::<i64>() + size > quota {
+                    return Ok(None);
+                }
+            }
+            diesel::insert_into(attachments::table)
+                .values(&row)
+                .returning(SqliteAttachment::as_returning())
+                .get_result(conn)
+                .map(Some)
+        })
+        .map_err(|e| {
+            tracing::error!("Failed to add attachment!");
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?
+        .ok_or_else(|| quota_exceeded(owner))
+        .and_then(Attachment::try_from)?;
+    tracing::info!("Attachment {} added to post {}", added.id, added.post_id);
+
+    Ok(added)
+}
+
+/// The SQLite version of `db::db_find_attachment`.
+#[instrument(name = "Find attachment", skip(connection))]
+pub fn sqlite_find_attachment(
+    attachment_id: uuid::Uuid,
+    connection: &SqlitePool,
+) -> Result<Attachment, errors::Error> {
+    use super::schema::attachments::dsl::{attachments, id};
+    let mut conn = get_connection(connection)?;
+
+    attachments
+        .filter(id.eq(attachment_id.to_string()))
+        .select(SqliteAttachment::as_select())
+        .first(&mut conn)
+        .optional()
+        .map_err(|e| {
+            tracing::error!("Failed to find attachment {}", attachment_id);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?
+        .ok_or_else(|| attachment_not_found(attachment_id))
+        .and_then(Attachment::try_from)
+}
+
+/// The SQLite version of `db::db_get_post_attachments`.
+#[instrument(name = "Get post's attachments", skip(connection))]
+pub fn sqlite_get_post_attachments(
+    post: uuid::Uuid,
+    connection: &SqlitePool,
+) -> Result<Vec<Attachment>, errors::Error> {
+    use super::schema::attachments::dsl::{attachments, created_at, id, post_id};
+    let mut conn = get_connection(connection)?;
+
+    attachments
+        .filter(post_id.eq(post.to_string()))
+        .select(SqliteAttachment::as_select())
+        .order((created_at.asc(), id.asc()))
+        .load(&mut conn)
+        .map_err(|e| {
+            tracing::error!("Failed to get the attachments of post {}", post);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?
+        .into_iter()
+        .map(Attachment::try_from)
+        .collect()
+}
+
+/// The SQLite version of `db::db_delete_attachment`.
+#[instrument(name = "Delete attachment", skip(connection))]
+pub fn sqlite_delete_attachment(
+    attachment_id: uuid::Uuid,
+    connection: &SqlitePool,
+) -> Result<(), errors::Error> {
+    use super::schema::attachments::dsl::{attachments, id};
+    let mut conn = get_connection(connection)?;
+
+    let deleted = diesel::delete(attachments.filter(id.eq(attachment_id.to_string())))
+        .execute(&mut conn)
+        .map_err(|e| {
+            tracing::error!("Failed to delete attachment {}", attachment_id);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    if deleted == 0 {
+        return Err(attachment_not_found(attachment_id));
+    }
+    tracing::info!("Attachment {} deleted", attachment_id);
+
+    Ok(())
+}
+
+/// The SQLite version of `db::db_get_user_hashes`.
+#[instrument(name = "Get user's attachment hashes", skip(connection))]
+pub fn sqlite_get_user_hashes(
+    owner: uuid::Uuid,
+    connection: &SqlitePool,
+) -> Result<Vec<String>, errors::Error> {
+    use super::schema::attachments::dsl::{attachments, hash, user_id};
+    let mut conn = get_connection(connection)?;
+
+    attachments
+        .filter(user_id.eq(owner.to_string()))
+        .select(hash)
+        .distinct()
+        .load(&mut conn)
+        .map_err(|e| {
+            tracing::error!("Failed to get the attachments of user {}", owner);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })
+}
+
+/// The SQLite version of `db::db_is_hash_used`.
+#[instrument(name = "Check attachment hash", skip(connection))]
+pub fn sqlite_is_hash_used(value: &str, connection: &SqlitePool) -> Result<bool, errors::Error> {
+    use super::schema::attachments::dsl::{attachments, hash};
+    let mut conn = get_connection(connection)?;
+
+    diesel::select(diesel::dsl::exists(attachments.filter(hash.eq(value))))
+        .get_result(&mut conn)
+        .map_err(|e| {
+            tracing::error!("Failed to check attachment hash!");
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })
+}
+
+/// The SQLite version of `db::db_add_share_link`.
+#[instrument(name = "Add share link", skip(connection))]
+pub fn sqlite_add_share_link(
+    link: ShareLink,
+    connection: &SqlitePool,
+) -> Result<ShareLink, errors::Error> {
+    use super::schema::share_links::dsl::share_links;
+    let mut conn = get_connection(connection)?;
+    let post = link.post_id;
+
+    diesel::insert_into(share_links)
+        .values(&SqliteShareLink::from(link))
+        .returning(SqliteShareLink::as_returning())
+        .get_result(&mut conn)
+        .map_err(|e| {
+            tracing::error!("Failed to add a share link of post {}", post);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })
+        .and_then(ShareLink::try_from)
+}
+
+/// The SQLite version of `db::db_find_share_link`.
+#[instrument(name = "Find share link", skip(connection))]
+pub fn sqlite_find_share_link(
+    link_id: uuid::Uuid,
+    connection: &SqlitePool,
+) -> Result<ShareLink, errors::Error> {
+    use super::schema::share_links::dsl::{id, share_links};
+    let mut conn = get_connection(connection)?;
+
+    share_links
+        .filter(id.eq(link_id.to_string()))
+        .select(SqliteShareLink::as_select())
+        .first(&mut conn)
+        .optional()
+        .map_err(|e| {
+            tracing::error!("Failed to find share link {}", link_id);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?
+        .ok_or_else(share_link_not_found)
+        .and_then(ShareLink::try_from)
+}
+
+/// The SQLite version of `db::db_find_share_link_by_token`.
+#[instrument(name = "Find share link by token", skip_all)]
+pub fn sqlite_find_share_link_by_token(
+    value: &str,
+    connection: &SqlitePool,
+) -> Result<ShareLink, errors::Error> {
+    use super::schema::share_links::dsl::{share_links, token_hash};
+    let mut conn = get_connection(connection)?;
+
+    share_links
+        .filter(token_hash.eq(value))
+        .select(SqliteShareLink::as_select())
+        .first(&mut conn)
+        .optional()
+        .map_err(|e| {
+            tracing::error!("Failed to find a share link!");
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?
+        .ok_or_else(share_link_not_found)
+        .and_then(ShareLink::try_from)
+}
+
+/// The SQLite version of `db::db_get_post_share_links`.
+#[instrument(name = "Get post's share links", skip(connection))]
+pub fn sqlite_get_post_share_links(
+    post: uuid::Uuid,
+    connection: &SqlitePool,
+) -> Result<Vec<ShareLink>, errors::Error> {
+    use super::schema::share_links::dsl::{created_at, id, post_id, share_links};
+    let mut conn = get_connection(connection)?;
+
+    share_links
+        .filter(post_id.eq(post.to_string()))
+        .select(SqliteShareLink::as_select())
+        .order((created_at.asc(), id.asc()))
+        .load(&mut conn)
+        .map_err(|e| {
+            tracing::error!("Failed to get the share links of post {}", post);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?
+        .into_iter()
+        .map(ShareLink::try_from)
+        .collect()
+}
+
+/// The SQLite version of `db::db_delete_share_link`.
+#[instrument(name = "Delete share link", skip(connection))]
+pub fn sqlite_delete_share_link(
+    link_id: uuid::Uuid,
+    connection: &SqlitePool,
+) -> Result<(), errors::Error> {
+    use super::schema::share_links::dsl::{id, share_links};
+    let mut conn = get_connection(connection)?;
+
+    let deleted = diesel::delete(share_links.filter(id.eq(link_id.to_string())))
+        .execute(&mut conn)
+        .map_err(|e| {
+            tracing::error!("Failed to delete share link {}", link_id);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    if deleted == 0 {
+        return Err(share_link_not_found());
+    }
+    tracing::info!("Share link {} deleted", link_id);
+
+    Ok(())
+}
+
+type SqliteConn = diesel::r2d2::PooledConnection<diesel::r2d2::ConnectionManager<SqliteConnection>>;
+
+//the username of a user, the owner shown with the posts
+fn sqlite_username(conn: &mut SqliteConn, user: uuid::Uuid) -> Result<String, errors::Error> {
+    use super::schema::users::dsl::{id, username, users};
+
+    users
+        .filter(id.eq(user.to_string()))
+        .select(username)
+        .first(conn)
+        .optional()
+        .map_err(|e| {
+            tracing::error!("Failed to find user {}", user);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?
+        .ok_or_else(|| user_not_found(user))
+}
+
+//what a user may do with a post, like `db::db_get_permission`
+fn sqlite_permission(
+    conn: &mut SqliteConn,
+    post: uuid::Uuid,
+    user: uuid::Uuid,
+) -> Result<Option<Permission>, errors::Error> {
+    use super::schema::{post_permissions, posts, workspace_members};
+
+    let (owner, workspace): (String, String) = posts::table
+        .filter(posts::id.eq(post.to_string()))
+        .select((posts::user_id, posts::workspace_id))
+        .first(conn)
+        .optional()
+        .map_err(|e| {
+            tracing::error!("Failed to find post {}", post);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?
+        .ok_or_else(|| post_not_found(post))?;
+    if owner == user.to_string() {
+        return Ok(Some(Permission::Owner));
+    }
+
+    let role: Option<String> = post_permissions::table
+        .filter(post_permissions::post_id.eq(post.to_string()))
+        .filter(post_permissions::user_id.eq(user.to_string()))
+        .select(post_permissions::role)
+        .first(conn)
+        .optional()
+        .map_err(|e| {
+            tracing::error!("Failed to get the permission of user {} to {}", user, post);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+    let member: Option<String> = workspace_members::table
+        .filter(workspace_members::workspace_id.eq(workspace))
+        .filter(workspace_members::user_id.eq(user.to_string()))
+        .select(workspace_members::role)
+        .first(conn)
+        .optional()
+        .map_err(|e| {
+            tracing::error!("Failed to get the membership of user {}", user);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    Ok(effective_permission(
+        false,
+        role.as_deref(),
+        member.as_deref(),
+    ))
+}
+
+//the roles of a user by the workspaces they are a member of, like `db::db_memberships`
+fn sqlite_memberships(
+    conn: &mut SqliteConn,
+    user: uuid::Uuid,
+) -> Result<HashMap<String, String>, errors::Error> {
+    use super::schema::workspace_members;
+
+    Ok(workspace_members::table
+        .filter(workspace_members::user_id.eq(user.to_string()))
+        .select((workspace_members::workspace_id, workspace_members::role))
+        .load::<(String, String)>(conn)
+        .map_err(|e| {
+            tracing::error!("Failed to get the workspaces of user {}", user);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?
+        .into_iter()
+        .collect())
+}
+
+/// The SQLite version of `db::db_get_permission`.
+#[instrument(name = "Get permission", skip(connection))]
+pub fn sqlite_get_permission(
+    post: uuid::Uuid,
+    user: uuid::Uuid,
+    connection: &SqlitePool,
+) -> Result<Option<Permission>, errors::Error> {
+    let mut conn = get_connection(connection)?;
+    sqlite_permission(&mut conn, post, user)
+}
+
+/// The SQLite version of `db::db_edit_post`.
+#[instrument(name = "Edit post", skip(connection))]
+pub fn sqlite_edit_post(
+    user_id: uuid::Uuid,
+    post: Post,
+    connection: &SqlitePool,
+) -> Result<ResponsePost, errors::Error> {
+    use super::schema::{important_posts, posts};
+    let mut conn = get_connection(connection)?;
+
+    let permission =
+        sqlite_permission(&mut conn, post.id, user_id)?.ok_or_else(|| post_not_found(post.id))?;
+    if permission < Permission::Editor {
+        return Err(errors::Error::from_code(
+            "auth.forbidden",
+            errors::ErrorTypes::Auth(errors::Auth::Authorization),
+        ));
+    }
+
+    let row: SqlitePost = diesel::update(posts::table.filter(posts::id.eq(post.id.to_string())))
+        .set((
+            posts::title.eq(post.title),
+            posts::description.eq(post.description),
+        ))
+        .returning(SqlitePost::as_returning())
+        .get_result(&mut conn)
+        .map_err(|e| {
+            tracing::error!("Failed to edit post {}", post.id);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+    let important = important_posts::table
+        .filter(important_posts::user_id.eq(user_id.to_string()))
+        .filter(important_posts::post_id.eq(&row.id))
+        .count()
+        .get_result::<i64>(&mut conn)
+        .map_err(|e| errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError))?
+        > 0;
+    let edited = Post::try_from(row)?;
+    let owner = sqlite_username(&mut conn, edited.user_id)?;
+    tracing::info!("Post {} edited by user {}", edited.id, user_id);
+
+    Ok(edited.response(owner, important, permission))
+}
+
+/// The SQLite version of `db::db_grant_permission`.
+#[instrument(name = "Grant permission", skip(connection))]
+pub fn sqlite_grant_permission(
+    permission: PostPermission,
+    connection: &SqlitePool,
+) -> Result<PostPermission, errors::Error> {
+    use super::schema::post_permissions::dsl::post_permissions;
+    let mut conn = get_connection(connection)?;
+    let post = permission.post_id;
+
+    diesel::insert_into(post_permissions)
+        .values(&SqlitePostPermission::from(permission))
+        .on_conflict_do_nothing()
+        .returning(SqlitePostPermission::as_returning())
+        .get_result(&mut conn)
+        .optional()
+        .map_err(|e| {
+            tracing::error!("Failed to share post {}", post);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?
+        .ok_or_else(permission_exists)
+        .and_then(PostPermission::try_from)
+}
+
+/// The SQLite version of `db::db_update_permission`.
+#[instrument(name = "Update permission", skip(connection))]
+pub fn sqlite_update_permission(
+    post: uuid::Uuid,
+    user: uuid::Uuid,
+    role: Permission,
+    connection: &SqlitePool,
+) -> Result<(), errors::Error> {
+    use super::schema::post_permissions::dsl;
+    let mut conn = get_connection(connection)?;
+
+    let updated = diesel::update(
+        dsl::post_permissions
+            .filter(dsl::post_id.eq(post.to_string()))
+            .filter(dsl::user_id.eq(user.to_string())),
+    )
+    .set(dsl::role.eq(role.as_str()))
+    .execute(&mut conn)
+    .map_err(|e| {
+        tracing::error!(
+            "Failed to change the permission of user {} to {}",
+            user,
+            post
+        );
+        errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+    })?;
+
+    match updated {
+        0 => Err(permission_not_found()),
+        _ => Ok(()),
+    }
+}
+
+/// The SQLite version of `db::db_revoke_permission`.
+#[instrument(name = "Revoke permission", skip(connection))]
+pub fn sqlite_revoke_permission(
+    post: uuid::Uuid,
+    user: uuid::Uuid,
+    connection: &SqlitePool,
+) -> Result<(), errors::Error> {
+    use super::schema::{important_posts, post_permissions};
+    let mut conn = get_connection(connection)?;
+    let (post_text, user_text) = (post.to_string(), user.to_string());
+
+    let deleted = conn
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            //the post leaves the list of the user with its important flag
+            diesel::delete(
+                important_posts::table
+                    .filter(important_posts::user_id.eq(&user_text))
+                    .filter(important_posts::post_id.eq(&post_text)),
+            )
+            .execute(conn)?;
+            diesel::delete(
+                post_permissions::table
+                    .filter(post_permissions::post_id.eq(&post_text))
+                    .filter(post_permissions::user_id.eq(&user_text)),
+            )
+            .execute(conn)
+        })
+        .map_err(|e| {
+            tracing::error!(
+                "Failed to revoke the permission of user {} to {}",
+                user,
+                post
+            );
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    if deleted == 0 {
+        return Err(permission_not_found());
+    }
+    tracing::info!("Permission of user {} to post {} revoked", user, post);
+
+    Ok(())
+}
+
+/// The SQLite version of `db::db_get_post_permissions`.
+#[instrument(name = "Get post's permissions", skip(connection))]
+pub fn sqlite_get_post_permissions(
+    post: uuid::Uuid,
+    connection: &SqlitePool,
+) -> Result<Vec<ResponsePermission>, errors::Error> {
+    use super::schema::{post_permissions, users};
+    let mut conn = get_connection(connection)?;
+
+    let rows: Vec<(SqlitePostPermission, String)> = post_permissions::table
+        .inner_join(users::table)
+        .filter(post_permissions::post_id.eq(post.to_string()))
+        .select((SqlitePostPermission::as_select(), users::username))
+        .order((post_permissions::created_at.asc(), users::username.asc()))
+        .load(&mut conn)
+        .map_err(|e| {
+            tracing::error!("Failed to get the permissions of post {}", post);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    rows.into_iter()
+        .map(|(row, username)| Ok(PostPermission::try_from(row)?.response(username)))
+        .collect()
+}
+
+/// The SQLite version of `db::db_add_workspace`.
+#[instrument(name = "Add workspace", skip(connection))]
+pub fn sqlite_add_workspace(
+    workspace: Workspace,
+    owner: uuid::Uuid,
+    connection: &SqlitePool,
+) -> Result<Workspace, errors::Error> {
+    use super::schema::{workspace_members, workspaces};
+    let mut conn = get_connection(connection)?;
+
+    let member = SqliteWorkspaceMember::from(WorkspaceMember {
+        workspace_id: workspace.id,
+        user_id: owner,
+        role: WorkspaceRole::Owner.to_string(),
+        created_at: workspace.created_at,
+    });
+    let row = conn
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            let row: SqliteWorkspace = diesel::insert_into(workspaces::table)
+                .values(&SqliteWorkspace::from(workspace))
+                .returning(SqliteWorkspace::as_returning())
+                .get_result(conn)?;
+            diesel::insert_into(workspace_members::table)
+                .values(&member)
+                .execute(conn)?;
+            Ok(row)
+        })
+        .map_err(|e| {
+            tracing::error!("Failed to add a workspace of user {}", owner);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+    let workspace = Workspace::try_from(row)?;
+    tracing::info!("Workspace {} added successfully!", workspace.id);
+
+    Ok(workspace)
+}
+
+/// The SQLite version of `db::db_get_user_workspaces`.
+#[instrument(name = "Get user's workspaces", skip(connection))]
+pub fn sqlite_get_user_workspaces(
+    user: uuid::Uuid,
+    connection: &SqlitePool,
+) -> Result<Vec<ResponseWorkspace>, errors::Error> {
+    use super::schema::{workspace_members, workspaces};
+    let mut conn = get_connection(connection)?;
+
+    let rows: Vec<(SqliteWorkspace, String)> = workspaces::table
+        .inner_join(workspace_members::table)
+        .filter(workspace_members::user_id.eq(user.to_string()))
+        .select((SqliteWorkspace::as_select(), workspace_members::role))
+        .order((
+            workspaces::personal.desc(),
+            workspaces::created_at.asc(),
+            workspaces::id.asc(),
+        ))
+        .load(&mut conn)
+        .map_err(|e| {
+            tracing::error!("Failed to get the workspaces of user {}", user);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    rows.into_iter()
+        .map(|(row, role)| Ok(Workspace::try_from(row)?.response(member_role(&role))))
+        .collect()
+}
+
+/// The SQLite version of `db::db_find_membership`.
+#[instrument(name = "Find membership", skip(connection))]
+pub fn sqlite_find_membership(
+    workspace: uuid::Uuid,
+    user: uuid::Uuid,
+    connection: &SqlitePool,
+) -> Result<Option<ResponseWorkspace>, errors::Error> {
+    use super::schema::{workspace_members, workspaces};
+    let mut conn = get_connection(connection)?;
+
+    let row: Option<(SqliteWorkspace, String)> = workspaces::table
+        .inner_join(workspace_members::table)
+        .filter(workspaces::id.eq(workspace.to_string()))
+        .filter(workspace_members::user_id.eq(user.to_string()))
+        .select((SqliteWorkspace::as_select(), workspace_members::role))
+        .first(&mut conn)
+        .optional()
+        .map_err(|e| {
+            tracing::error!("Failed to find the membership of user {}", user);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    row.map(|(row, role)| Ok(Workspace::try_from(row)?.response(member_role(&role))))
+        .transpose()
+}
+
+/// The SQLite version of `db::db_rename_workspace`.
+#[instrument(name = "Rename workspace", skip(connection))]
+pub fn sqlite_rename_workspace(
+    workspace: uuid::Uuid,
+    value: String,
+    connection: &SqlitePool,
+) -> Result<(), errors::Error> {
+    use super::schema::workspaces::dsl::{id, name, workspaces};
+    let mut conn = get_connection(connection)?;
+
+    let updated = diesel::update(workspaces.filter(id.eq(workspace.to_string())))
+        .set(name.eq(value))
+        .execute(&mut conn)
+        .map_err(|e| {
+            tracing::error!("Failed to rename workspace {}", workspace);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    match updated {
+        0 => Err(workspace_not_found()),
+        _ => Ok(()),
+    }
+}
+
+/// The SQLite version of `db::db_add_member`.
+#[instrument(name = "Add workspace member", skip(connection))]
+pub fn sqlite_add_member(
+    member: WorkspaceMember,
+    connection: &SqlitePool,
+) -> Result<WorkspaceMember, errors::Error> {
+    use super::schema::workspace_members::dsl::workspace_members;
+    let mut conn = get_connection(connection)?;
+    let workspace = member.workspace_id;
+
+    diesel::insert_into(workspace_members)
+        .values(&SqliteWorkspaceMember::from(member))
+        .on_conflict_do_nothing()
+        .returning(SqliteWorkspaceMember::as_returning())
+        .get_result(&mut conn)
+        .optional()
+        .map_err(|e| {
+            tracing::error!("Failed to add a member to workspace {}", workspace);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?
+        .ok_or_else(member_exists)
+        .and_then(WorkspaceMember::try_from)
+}
+
+/// The SQLite version of `db::db_update_member`.
+#[instrument(name = "Update workspace member", skip(connection))]
+pub fn sqlite_update_member(
+    workspace: uuid::Uuid,
+    user: uuid::Uuid,
+    role: WorkspaceRole,
+    connection: &SqlitePool,
+) -> Result<(), errors::Error> {
+    use super::schema::workspace_members::dsl;
+    let mut conn = get_connection(connection)?;
+
+    let updated = diesel::update(
+        dsl::workspace_members
+            .filter(dsl::workspace_id.eq(workspace.to_string()))
+            .filter(dsl::user_id.eq(user.to_string())),
+    )
+    .set(dsl::role.eq(role.as_str()))
+    .execute(&mut conn)
+    .map_err(|e| {
+        tracing::error!("Failed to change the role of user {}", user);
+        errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+    })?;
+
+    match updated {
+        0 => Err(member_not_found()),
+        _ => Ok(()),
+    }
+}
+
+/// The SQLite version of `db::db_remove_member`.
+#[instrument(name = "Remove workspace member", skip(connection))]
+pub fn sqlite_remove_member(
+    workspace: uuid::Uuid,
+    user: uuid::Uuid,
+    connection: &SqlitePool,
+) -> Result<(), errors::Error> {
+    use super::schema::workspace_members::dsl;
+    let mut conn = get_connection(connection)?;
+
+    let deleted = diesel::delete(
+        dsl::workspace_members
+            .filter(dsl::workspace_id.eq(workspace.to_string()))
+            .filter(dsl::user_id.eq(user.to_string())),
+    )
+    .execute(&mut conn)
+    .map_err(|e| {
+        tracing::error!(
+            "Failed to remove user {} from workspace {}",
+            user,
+            workspace
+        );
+        errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+    })?;
+
+    if deleted == 0 {
+        return Err(member_not_found());
+    }
+    tracing::info!("User {} removed from workspace {}", user, workspace);
+
+    Ok(())
+}
+
+/// The SQLite version of `db::db_get_members`.
+#[instrument(name = "Get workspace members", skip(connection))]
+pub fn sqlite_get_members(
+    workspace: uuid::Uuid,
+    connection: &SqlitePool,
+) -> Result<Vec<ResponseMember>, errors::Error> {
+    use super::schema::{users, workspace_members};
+    let mut conn = get_connection(connection)?;
+
+    let rows: Vec<(SqliteWorkspaceMember, String)> = workspace_members::table
+        .inner_join(users::table)
+        .filter(workspace_members::workspace_id.eq(workspace.to_string()))
+        .select((SqliteWorkspaceMember::as_select(), users::username))
+        .order((workspace_members::created_at.asc(), users::username.asc()))
+        .load(&mut conn)
+        .map_err(|e| {
+            tracing::error!("Failed to get the members of workspace {}", workspace);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    rows.into_iter()
+        .map(|(row, username)| Ok(WorkspaceMember::try_from(row)?.response(username)))
+        .collect()
+}
+
+/// The SQLite version of `db::db_add_notebook`.
+#[instrument(name = "Add notebook", skip(connection))]
+pub fn sqlite_add_notebook(
+    notebook: Notebook,
+    connection: &SqlitePool,
+) -> Result<Notebook, errors::Error> {
+    use super::schema::notebooks::dsl::notebooks;
+    let mut conn = get_connection(connection)?;
+    let notebook_id = notebook.id;
+
+    let row: SqliteNotebook = diesel::insert_into(notebooks)
+        .values(SqliteNotebook::from(notebook))
+        .returning(SqliteNotebook::as_returning())
+        .get_result(&mut conn)
+        .map_err(|e| {
+            tracing::error!("Failed to add notebook {}", notebook_id);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+    tracing::info!("Notebook {} added successfully!", notebook_id);
+
+    Notebook::try_from(row)
+}
+
+/// The SQLite version of `db::db_get_notebooks`.
+#[instrument(name = "Get notebooks", skip(connection))]
+pub fn sqlite_get_notebooks(
+    workspace: uuid::Uuid,
+    connection: &SqlitePool,
+) -> Result<Vec<(Notebook, i64)>, errors::Error> {
+    use super::schema::{notebooks, posts};
+    let mut conn = get_connection(connection)?;
+    let workspace_text = workspace.to_string();
+
+    let rows: Vec<SqliteNotebook> = notebooks::table
+        .filter(notebooks::workspace_id.eq(&workspace_text))
+        .select(SqliteNotebook::as_select())
+        .order((notebooks::created_at.asc(), notebooks::id.asc()))
+        .load(&mut conn)
+        .map_err(|e| {
+            tracing::error!("Failed to get the notebooks of workspace {}", workspace);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    let counts: HashMap<Option<String>, i64> = posts::table
+        .filter(posts::workspace_id.eq(&workspace_text))
+        .filter(posts::notebook_id.is_not_null())
+        .group_by(posts::notebook_id)
+        .select((posts::notebook_id, diesel::dsl::count_star()))
+        .load::<(Option<String>, i64)>(&mut conn)
+        .map_err(|e| errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError))?
+        .into_iter()
+        .collect();
+
+    rows.into_iter()
+        .map(|row| {
+            let notes = counts.get(&Some(row.id.clone())).copied().unwrap_or(0);
+            Ok((Notebook::try_from(row)?, notes))
+        })
+        .collect()
+}
+
+/// The SQLite version of `db::db_find_notebook`.
+#[instrument(name = "Find notebook", skip(connection))]
+pub fn sqlite_find_notebook(
+    notebook: uuid::Uuid,
+    connection: &SqlitePool,
+) -> Result<Notebook, errors::Error> {
+    use super::schema::notebooks;
+    let mut conn = get_connection(connection)?;
+
+    notebooks::table
+        .filter(notebooks::id.eq(notebook.to_string()))
+        .select(SqliteNotebook::as_select())
+        .first(&mut conn)
+        .optional()
+        .map_err(|e| {
+            tracing::error!("Failed to find notebook {}", notebook);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?
+        .ok_or_else(notebook_not_found)
+        .and_then(Notebook::try_from)
+}
+
+/// The SQLite version of `db::db_update_notebook`.
+#[instrument(name = "Update notebook", skip(connection))]
+pub fn sqlite_update_notebook(
+    notebook: uuid::Uuid,
+    value: String,
+    parent: Option<uuid::Uuid>,
+    connection: &SqlitePool,
+) -> Result<Notebook, errors::Error> {
+    use super::schema::notebooks;
+    let mut conn = get_connection(connection)?;
+
+    diesel::update(notebooks::table.filter(notebooks::id.eq(notebook.to_string())))
+        .set((
+            notebooks::name.eq(value),
+            notebooks::parent_id.eq(parent.map(|id| id.to_string())),
+        ))
+        .returning(SqliteNotebook::as_returning())
+        .get_result(&mut conn)
+        .optional()
+        .map_err(|e| {
+            tracing::error!("Failed to update notebook {}", notebook);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?
+        .ok_or_else(notebook_not_found)
+        .and_then(Notebook::try_from)
+}
+
+/// The SQLite version of `db::db_delete_notebook`.
+#[instrument(name = "Delete notebook", skip(connection))]
+pub fn sqlite_delete_notebook(
+    notebook: uuid::Uuid,
+    connection: &SqlitePool,
+) -> Result<(), errors::Error> {
+    use super::schema::notebooks;
+    let mut conn = get_connection(connection)?;
+
+    let deleted = diesel::delete(notebooks::table.filter(notebooks::id.eq(notebook.to_string())))
+        .execute(&mut conn)
+        .map_err(|e| {
+            tracing::error!("Failed to delete notebook {}", notebook);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    match deleted {
+        0 => Err(notebook_not_found()),
+        _ => Ok(()),
+    }
+}
+
+/// The SQLite version of `db::db_move_post`.
+#[instrument(name = "Move post", skip(connection))]
+pub fn sqlite_move_post(
+    post: uuid::Uuid,
+    notebook: Option<uuid::Uuid>,
+    connection: &SqlitePool,
+) -> Result<(), errors::Error> {
+    use super::schema::posts;
+    let mut conn = get_connection(connection)?;
+
+    let updated = diesel::update(posts::table.filter(posts::id.eq(post.to_string())))
+        .set(posts::notebook_id.eq(notebook.map(|id| id.to_string())))
+        .execute(&mut conn)
+        .map_err(|e| {
+            tracing::error!("Failed to move post {}", post);
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    match updated {
+        0 => Err(post_not_found(post)),
+        _ => Ok(()),
+    }
+}
+
+/// The SQLite version of `db::db_get_notebook_posts`.
+#[instrument(name = "Get notebook posts", skip(connection))]
+pub fn sqlite_get_notebook_posts(
+    user_id: uuid::Uuid,
+    workspace_id: uuid::Uuid,
+    notebook_ids: Vec<uuid::Uuid>,
+    connection: &SqlitePool,
+) -> Result<Vec<ResponsePost>, errors::Error> {
+    use super::schema::{important_posts, post_permissions, posts, users};
+    let mut conn = get_connection(connection)?;
+    let user_id_text = user_id.to_string();
+    let members = sqlite_memberships(&mut conn, user_id)?;
+    let member = members.get(&workspace_id.to_string()).map(String::as_str);
+    let notebook_ids: Vec<String> = notebook_ids.iter().map(|id| id.to_string()).collect();
+
+    let found: Vec<(SqlitePost, String)> = posts::table
+        .inner_join(users::table)
+        .filter(posts::workspace_id.eq(workspace_id.to_string()))
+        .filter(posts::notebook_id.eq_any(notebook_ids))
+        .select((SqlitePost::as_select(), users::username))
+        .order(posts::created_at.desc())
+        .load(&mut conn)
+        .map_err(|e| {
+            tracing::error!(
+                "Failed to get the notebook posts of workspace {}",
+                workspace_id
+            );
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+
+    let ids: Vec<&str> = found.iter().map(|(row, _)| row.id.as_str()).collect();
+    let important: HashSet<String> = important_posts::table
+        .select(important_posts::post_id)
+        .filter(important_posts::user_id.eq(&user_id_text))
+        .filter(important_posts::post_id.eq_any(&ids))
+        .load(&mut conn)
+        .map_err(|e| errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError))?
+        .into_iter()
+        .collect();
+    let roles: HashMap<String, String> = post_permissions::table
+        .filter(post_permissions::user_id.eq(&user_id_text))
+        .filter(post_permissions::post_id.eq_any(&ids))
+        .select((post_permissions::post_id, post_permissions::role))
+        .load::<(String, String)>(&mut conn)
+        .map_err(|e| errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError))?
+        .into_iter()
+        .collect();
+
+    let mut posts = Vec::new();
+    for (row, owner) in found {
+        let important = important.contains(&row.id);
+        let Some(permission) = effective_permission(
+            row.user_id == user_id_text,
+            roles.get(&row.id).map(String::as_str),
+            member,
+        ) else {
+            continue;
+        };
+        posts.push(Post::try_from(row)?.response(owner, important, permission));
+    }
+    posts.sort_by(|a, b| b.important.cmp(&a.important));
+
+    Ok(posts)
+}
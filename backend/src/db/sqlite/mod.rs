@@ -0,0 +1,634 @@
+pub mod functionality;
+pub mod models;
+pub mod schema;
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use common::{
+    Permission, PostsUpdateForm, ResponseMember, ResponsePermission, ResponsePost,
+    ResponseWorkspace, Role, WorkspaceRole,
+};
+use diesel::connection::SimpleConnection;
+use diesel::r2d2::{ConnectionManager, CustomizeConnection, Pool};
+use diesel::SqliteConnection;
+use diesel_migrations::{embed_migrations, EmbeddedMigrations};
+
+use super::{
+    has_pending_migrations, run_pending_migrations, Attachment, AttachmentRepository, AuditEvent,
+    AuditFilter, AuditRepository, Notebook, NotebookRepository, NotesRepository,
+    PermissionRepository, Post, PostPermission, SchemaRepository, ShareLink, ShareRepository, User,
+    UserRepository, UserSummary, Workspace, WorkspaceMember, WorkspaceRepository,
+};
+use crate::errors;
+use crate::schema::user::Password;
+use crate::tasks::Tasks;
+pub use functionality::*;
+
+/// The SQLite migrations from the `backend/migrations_sqlite` directory.
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations_sqlite");
+
+/// The path of a database that is only kept in memory.
+pub const MEMORY_PATH: &str = ":memory:";
+
+/// Defining a type alias `SqlitePool` for a connection pool of `SqliteConnection` objects.
+pub type SqlitePool = Pool<ConnectionManager<SqliteConnection>>;
+
+/// The `SqlitePragmas` struct configures every new SQLite connection: foreign keys are checked, the
+/// journal is in WAL mode, and writers wait for a lock instead of failing right away.
+#[derive(Debug)]
+struct SqlitePragmas;
+
+impl CustomizeConnection<SqliteConnection, diesel::r2d2::Error> for SqlitePragmas {
+    fn on_acquire(&self, conn: &mut SqliteConnection) -> Result<(), diesel::r2d2::Error> {
+        conn.batch_execute(
+            "PRAGMA foreign_keys = ON; PRAGMA journal_mode = WAL; PRAGMA busy_timeout = 5000;",
+        )
+        .map_err(diesel::r2d2::Error::QueryError)
+    }
+}
+
+/// The function `get_connection_pool` returns a connection pool for the SQLite database file.
+///
+/// Arguments:
+///
+/// * `path`: The `path` parameter is the database file. It is created if it does not exist. With
+/// `:memory:` the database is kept in memory until the pool is dropped, for the tests.
+///
+/// Returns:
+///
+/// a Result type, which can either be Ok(SqlitePool) or Err(errors::Error).
+pub fn get_connection_pool(path: &str) -> Result<SqlitePool, errors::Error> {
+    let manager = ConnectionManager::<SqliteConnection>::new(path);
+
+    let mut builder = r2d2::Pool::builder()
+        .connection_customizer(Box::new(SqlitePragmas))
+        .test_on_check_out(true);
+    if path == MEMORY_PATH {
+        //every connection to `:memory:` opens a database of its own, so the pool keeps only one
+        builder = builder.max_size(1).idle_timeout(None).max_lifetime(None);
+    }
+    builder.build(manager).map_err(|e| {
+        errors::Error::new(
+            Some(e.to_string()),
+            Some("Failed to create the connection pool!".to_string()),
+            errors::ErrorTypes::DbError,
+        )
+    })
+}
+
+/// The `SqliteRepository` struct implements the repositories on top of a SQLite file database, so the
+/// backend can run as a single binary.
+///
+/// Properties:
+///
+/// * `pool`: The `pool` property is the r2d2 connection pool.
+/// * `tasks`: The `tasks` property runs the blocking diesel calls on the thread pool.
+#[derive(Clone)]
+pub struct SqliteRepository {
+    pool: SqlitePool,
+    tasks: Tasks,
+}
+
+impl SqliteRepository {
+    pub fn new(pool: SqlitePool, tasks: Tasks) -> Self {
+        SqliteRepository { pool, tasks }
+    }
+}
+
+#[async_trait]
+impl UserRepository for SqliteRepository {
+    async fn add_user(&self, user: User) -> Result<User, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_add_user(user, &pool))
+            .await?
+    }
+
+    async fn check_user(&self, username: &str, password: Password) -> Result<User, errors::Error> {
+        let pool = self.pool.clone();
+        let username = username.to_string();
+        self.tasks
+            .block(move || sqlite_check_user(&username, password, &pool))
+            .await?
+    }
+
+    async fn find_user(&self, user_id: uuid::Uuid) -> Result<User, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_find_user(user_id, &pool))
+            .await?
+    }
+
+    async fn find_user_by_username(&self, username: &str) -> Result<User, errors::Error> {
+        let pool = self.pool.clone();
+        let username = username.to_string();
+        self.tasks
+            .block(move || sqlite_find_user_by_username(&username, &pool))
+            .await?
+    }
+
+    async fn list_users(&self) -> Result<Vec<UserSummary>, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks.block(move || sqlite_list_users(&pool)).await?
+    }
+
+    async fn update_password(
+        &self,
+        user_id: uuid::Uuid,
+        password_hash: String,
+    ) -> Result<(), errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_update_password(user_id, password_hash, &pool))
+            .await?
+    }
+
+    async fn set_disabled(&self, user_id: uuid::Uuid, disabled: bool) -> Result<(), errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_set_disabled(user_id, disabled, &pool))
+            .await?
+    }
+
+    async fn delete_user(&self, user_id: uuid::Uuid) -> Result<(), errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_delete_user(user_id, &pool))
+            .await?
+    }
+
+    async fn set_role(&self, user_id: uuid::Uuid, role: Role) -> Result<(), errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_set_role(user_id, role, &pool))
+            .await?
+    }
+
+    async fn revoke_sessions(&self, user_id: uuid::Uuid) -> Result<i32, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_revoke_sessions(user_id, &pool))
+            .await?
+    }
+}
+
+#[async_trait]
+impl NotesRepository for SqliteRepository {
+    async fn get_posts(
+        &self,
+        user_id: uuid::Uuid,
+        workspace_id: uuid::Uuid,
+    ) -> Result<Vec<ResponsePost>, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_get_posts(user_id, workspace_id, &pool))
+            .await?
+    }
+
+    async fn get_user_posts(
+        &self,
+        user_id: uuid::Uuid,
+        workspace_id: uuid::Uuid,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<ResponsePost>, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_get_user_posts(user_id, workspace_id, offset, limit, &pool))
+            .await?
+    }
+
+    async fn find_post(&self, post_id: uuid::Uuid) -> Result<Post, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_find_post(post_id, &pool))
+            .await?
+    }
+
+    async fn add_post(&self, post: Post) -> Result<ResponsePost, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_add_post(post, &pool))
+            .await?
+    }
+
+    async fn add_posts(&self, posts: Vec<(Post, bool)>) -> Result<usize, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_add_posts(posts, &pool))
+            .await?
+    }
+
+    async fn update_post(
+        &self,
+        user_id: uuid::Uuid,
+        data: PostsUpdateForm,
+    ) -> Result<(), errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_update_post(user_id, data, &pool))
+            .await?
+    }
+
+    async fn update_description(
+        &self,
+        post_id: uuid::Uuid,
+        description: Option<String>,
+    ) -> Result<(), errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_update_description(post_id, description, &pool))
+            .await?
+    }
+
+    async fn edit_post(
+        &self,
+        user_id: uuid::Uuid,
+        post: Post,
+    ) -> Result<ResponsePost, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_edit_post(user_id, post, &pool))
+            .await?
+    }
+
+    async fn delete_post(&self, post_id: uuid::Uuid) -> Result<(), errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_delete_post(post_id, &pool))
+            .await?
+    }
+}
+
+#[async_trait]
+impl AttachmentRepository for SqliteRepository {
+    async fn add_attachment(
+        &self,
+        attachment: Attachment,
+        quota: Option<i64>,
+    ) -> Result<Attachment, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_add_attachment(attachment, quota, &pool))
+            .await?
+    }
+
+    async fn find_attachment(
+        &self,
+        attachment_id: uuid::Uuid,
+    ) -> Result<Attachment, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_find_attachment(attachment_id, &pool))
+            .await?
+    }
+
+    async fn get_post_attachments(
+        &self,
+        post_id: uuid::Uuid,
+    ) -> Result<Vec<Attachment>, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_get_post_attachments(post_id, &pool))
+            .await?
+    }
+
+    async fn delete_attachment(&self, attachment_id: uuid::Uuid) -> Result<(), errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_delete_attachment(attachment_id, &pool))
+            .await?
+    }
+
+    async fn get_user_hashes(&self, user_id: uuid::Uuid) -> Result<Vec<String>, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_get_user_hashes(user_id, &pool))
+            .await?
+    }
+
+    async fn is_hash_used(&self, hash: &str) -> Result<bool, errors::Error> {
+        let pool = self.pool.clone();
+        let hash = hash.to_string();
+        self.tasks
+            .block(move || sqlite_is_hash_used(&hash, &pool))
+            .await?
+    }
+}
+
+#[async_trait]
+impl PermissionRepository for SqliteRepository {
+    async fn get_permission(
+        &self,
+        post_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+    ) -> Result<Option<Permission>, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_get_permission(post_id, user_id, &pool))
+            .await?
+    }
+
+    async fn grant_permission(
+        &self,
+        permission: PostPermission,
+    ) -> Result<PostPermission, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_grant_permission(permission, &pool))
+            .await?
+    }
+
+    async fn update_permission(
+        &self,
+        post_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+        role: Permission,
+    ) -> Result<(), errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_update_permission(post_id, user_id, role, &pool))
+            .await?
+    }
+
+    async fn revoke_permission(
+        &self,
+        post_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+    ) -> Result<(), errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_revoke_permission(post_id, user_id, &pool))
+            .await?
+    }
+
+    async fn get_post_permissions(
+        &self,
+        post_id: uuid::Uuid,
+    ) -> Result<Vec<ResponsePermission>, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_get_post_permissions(post_id, &pool))
+            .await?
+    }
+}
+
+#[async_trait]
+impl NotebookRepository for SqliteRepository {
+    async fn add_notebook(&self, notebook: Notebook) -> Result<Notebook, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_add_notebook(notebook, &pool))
+            .await?
+    }
+
+    async fn get_notebooks(
+        &self,
+        workspace_id: uuid::Uuid,
+    ) -> Result<Vec<(Notebook, i64)>, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_get_notebooks(workspace_id, &pool))
+            .await?
+    }
+
+    async fn find_notebook(&self, notebook_id: uuid::Uuid) -> Result<Notebook, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_find_notebook(notebook_id, &pool))
+            .await?
+    }
+
+    async fn update_notebook(
+        &self,
+        notebook_id: uuid::Uuid,
+        name: String,
+        parent_id: Option<uuid::Uuid>,
+    ) -> Result<Notebook, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_update_notebook(notebook_id, name, parent_id, &pool))
+            .await?
+    }
+
+    async fn delete_notebook(&self, notebook_id: uuid::Uuid) -> Result<(), errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_delete_notebook(notebook_id, &pool))
+            .await?
+    }
+
+    async fn move_post(
+        &self,
+        post_id: uuid::Uuid,
+        notebook_id: Option<uuid::Uuid>,
+    ) -> Result<(), errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_move_post(post_id, notebook_id, &pool))
+            .await?
+    }
+
+    async fn get_notebook_posts(
+        &self,
+        user_id: uuid::Uuid,
+        workspace_id: uuid::Uuid,
+        notebook_ids: Vec<uuid::Uuid>,
+    ) -> Result<Vec<ResponsePost>, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_get_notebook_posts(user_id, workspace_id, notebook_ids, &pool))
+            .await?
+    }
+}
+
+#[async_trait]
+impl WorkspaceRepository for SqliteRepository {
+    async fn add_workspace(
+        &self,
+        workspace: Workspace,
+        owner_id: uuid::Uuid,
+    ) -> Result<Workspace, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_add_workspace(workspace, owner_id, &pool))
+            .await?
+    }
+
+    async fn get_user_workspaces(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> Result<Vec<ResponseWorkspace>, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_get_user_workspaces(user_id, &pool))
+            .await?
+    }
+
+    async fn find_membership(
+        &self,
+        workspace_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+    ) -> Result<Option<ResponseWorkspace>, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_find_membership(workspace_id, user_id, &pool))
+            .await?
+    }
+
+    async fn rename_workspace(
+        &self,
+        workspace_id: uuid::Uuid,
+        name: String,
+    ) -> Result<(), errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_rename_workspace(workspace_id, name, &pool))
+            .await?
+    }
+
+    async fn add_member(&self, member: WorkspaceMember) -> Result<WorkspaceMember, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_add_member(member, &pool))
+            .await?
+    }
+
+    async fn update_member(
+        &self,
+        workspace_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+        role: WorkspaceRole,
+    ) -> Result<(), errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_update_member(workspace_id, user_id, role, &pool))
+            .await?
+    }
+
+    async fn remove_member(
+        &self,
+        workspace_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+    ) -> Result<(), errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_remove_member(workspace_id, user_id, &pool))
+            .await?
+    }
+
+    async fn get_members(
+        &self,
+        workspace_id: uuid::Uuid,
+    ) -> Result<Vec<ResponseMember>, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_get_members(workspace_id, &pool))
+            .await?
+    }
+}
+
+#[async_trait]
+impl ShareRepository for SqliteRepository {
+    async fn add_share_link(&self, link: ShareLink) -> Result<ShareLink, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_add_share_link(link, &pool))
+            .await?
+    }
+
+    async fn find_share_link(&self, link_id: uuid::Uuid) -> Result<ShareLink, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_find_share_link(link_id, &pool))
+            .await?
+    }
+
+    async fn find_share_link_by_token(&self, token_hash: &str) -> Result<ShareLink, errors::Error> {
+        let pool = self.pool.clone();
+        let token_hash = token_hash.to_string();
+        self.tasks
+            .block(move || sqlite_find_share_link_by_token(&token_hash, &pool))
+            .await?
+    }
+
+    async fn get_post_share_links(
+        &self,
+        post_id: uuid::Uuid,
+    ) -> Result<Vec<ShareLink>, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_get_post_share_links(post_id, &pool))
+            .await?
+    }
+
+    async fn delete_share_link(&self, link_id: uuid::Uuid) -> Result<(), errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_delete_share_link(link_id, &pool))
+            .await?
+    }
+}
+
+#[async_trait]
+impl AuditRepository for SqliteRepository {
+    async fn add_event(&self, event: AuditEvent) -> Result<(), errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_add_audit_event(event, &pool))
+            .await?
+    }
+
+    async fn get_events(&self, filter: AuditFilter) -> Result<Vec<AuditEvent>, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_get_audit_events(filter, &pool))
+            .await?
+    }
+
+    async fn delete_events_before(
+        &self,
+        before: time::OffsetDateTime,
+    ) -> Result<usize, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || sqlite_delete_audit_events_before(before, &pool))
+            .await?
+    }
+}
+
+#[async_trait]
+impl SchemaRepository for SqliteRepository {
+    async fn run_pending_migrations(&self) -> Result<Vec<String>, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || {
+                let mut conn = pool.get().map_err(|e| {
+                    errors::Error::new(
+                        Some(e.to_string()),
+                        Some("Failed to get db connection pool!".to_string()),
+                        errors::ErrorTypes::DbError,
+                    )
+                })?;
+                run_pending_migrations(&mut conn, MIGRATIONS)
+            })
+            .await?
+    }
+
+    async fn has_pending_migrations(&self) -> Result<bool, errors::Error> {
+        let pool = self.pool.clone();
+        self.tasks
+            .block(move || {
+                let mut conn = pool.get_timeout(Duration::from_secs(2)).map_err(|e| {
+                    errors::Error::new(
+                        Some(e.to_string()),
+                        Some("Failed to get db connection pool!".to_string()),
+                        errors::ErrorTypes::DbError,
+                    )
+                })?;
+                has_pending_migrations(&mut conn, MIGRATIONS)
+            })
+            .await?
+    }
+}
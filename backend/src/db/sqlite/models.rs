@@ -0,0 +1,406 @@
+use diesel::prelude::*;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use super::schema::{
+    attachments, audit_events, notebooks, post_permissions, posts, share_links, users,
+    workspace_members, workspaces,
+};
+use crate::db::{
+    Attachment, AuditEvent, Notebook, Post, PostPermission, ShareLink, User, Workspace,
+    WorkspaceMember,
+};
+use crate::errors;
+
+/// The SQLite row of the `users` table. It mirrors `db::User` with the id stored as text.
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = users)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct SqliteUser {
+    pub id: String,
+    pub username: String,
+    pub password: String,
+    pub created_at: OffsetDateTime,
+    pub updated_at: OffsetDateTime,
+    pub disabled: bool,
+    pub role: String,
+    pub session_version: i32,
+    pub username_key: String,
+}
+
+/// The SQLite row of the `posts` table. It mirrors `db::Post` with the ids stored as text.
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = posts)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct SqlitePost {
+    pub id: String,
+    pub user_id: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub created_at: OffsetDateTime,
+    pub workspace_id: String,
+    pub notebook_id: Option<String>,
+}
+
+/// The SQLite row of the `workspaces` table. It mirrors `db::Workspace` with the id stored as text.
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = workspaces)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct SqliteWorkspace {
+    pub id: String,
+    pub name: String,
+    pub personal: bool,
+    pub created_at: OffsetDateTime,
+}
+
+/// The SQLite row of the `notebooks` table. It mirrors `db::Notebook` with the ids stored as text.
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = notebooks)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct SqliteNotebook {
+    pub id: String,
+    pub workspace_id: String,
+    pub parent_id: Option<String>,
+    pub name: String,
+    pub created_at: OffsetDateTime,
+}
+
+/// The SQLite row of the `workspace_members` table. It mirrors `db::WorkspaceMember` with the ids
+/// stored as text.
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = workspace_members)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct SqliteWorkspaceMember {
+    pub workspace_id: String,
+    pub user_id: String,
+    pub role: String,
+    pub created_at: OffsetDateTime,
+}
+
+/// The SQLite row of the `post_permissions` table. It mirrors `db::PostPermission` with the ids
+/// stored as text.
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = post_permissions)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct SqlitePostPermission {
+    pub post_id: String,
+    pub user_id: String,
+    pub role: String,
+    pub created_at: OffsetDateTime,
+}
+
+/// The SQLite row of the `audit_events` table. It mirrors `db::AuditEvent` with the ids stored as text.
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = audit_events)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct SqliteAuditEvent {
+    pub id: String,
+    pub actor_id: Option<String>,
+    pub actor_name: Option<String>,
+    pub action: String,
+    pub target_id: Option<String>,
+    pub success: bool,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: OffsetDateTime,
+}
+
+/// The SQLite row of the `attachments` table. It mirrors `db::Attachment` with the ids stored as text.
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = attachments)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct SqliteAttachment {
+    pub id: String,
+    pub post_id: String,
+    pub user_id: String,
+    pub file_name: String,
+    pub content_type: String,
+    pub size: i64,
+    pub hash: String,
+    pub created_at: OffsetDateTime,
+}
+
+/// The SQLite row of the `share_links` table. It mirrors `db::ShareLink` with the ids stored as text.
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = share_links)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct SqliteShareLink {
+    pub id: String,
+    pub post_id: String,
+    pub token_hash: String,
+    pub password_hash: Option<String>,
+    pub expires_at: Option<OffsetDateTime>,
+    pub created_at: OffsetDateTime,
+}
+
+/// The function `parse_id` reads a uuid stored as text.
+///
+/// Arguments:
+///
+/// * `id`: The `id` parameter is the stored id.
+///
+/// Returns:
+///
+/// a `Result<Uuid, errors::Error>`.
+pub fn parse_id(id: &str) -> Result<Uuid, errors::Error> {
+    Uuid::parse_str(id).map_err(|e| {
+        tracing::error!("Invalid uuid {} in the database!", id);
+        errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+    })
+}
+
+impl From<User> for SqliteUser {
+    fn from(user: User) -> Self {
+        SqliteUser {
+            id: user.id.to_string(),
+            username: user.username,
+            password: user.password,
+            created_at: user.created_at,
+            updated_at: user.updated_at,
+            disabled: user.disabled,
+            role: user.role,
+            session_version: user.session_version,
+            username_key: user.username_key,
+        }
+    }
+}
+
+impl TryFrom<SqliteUser> for User {
+    type Error = errors::Error;
+
+    fn try_from(user: SqliteUser) -> Result<Self, Self::Error> {
+        Ok(User {
+            id: parse_id(&user.id)?,
+            username: user.username,
+            password: user.password,
+            created_at: user.created_at,
+            updated_at: user.updated_at,
+            disabled: user.disabled,
+            role: user.role,
+            session_version: user.session_version,
+            username_key: user.username_key,
+        })
+    }
+}
+
+impl From<Post> for SqlitePost {
+    fn from(post: Post) -> Self {
+        SqlitePost {
+            id: post.id.to_string(),
+            user_id: post.user_id.to_string(),
+            title: post.title,
+            description: post.description,
+            created_at: post.created_at,
+            workspace_id: post.workspace_id.to_string(),
+            notebook_id: post.notebook_id.map(|id| id.to_string()),
+        }
+    }
+}
+
+impl TryFrom<SqlitePost> for Post {
+    type Error = errors::Error;
+
+    fn try_from(post: SqlitePost) -> Result<Self, Self::Error> {
+        Ok(Post {
+            id: parse_id(&post.id)?,
+            user_id: parse_id(&post.user_id)?,
+            title: post.title,
+            description: post.description,
+            created_at: post.created_at,
+            workspace_id: parse_id(&post.workspace_id)?,
+            notebook_id: post.notebook_id.as_deref().map(parse_id).transpose()?,
+        })
+    }
+}
+
+impl From<Workspace> for SqliteWorkspace {
+    fn from(workspace: Workspace) -> Self {
+        SqliteWorkspace {
+            id: workspace.id.to_string(),
+            name: workspace.name,
+            personal: workspace.personal,
+            created_at: workspace.created_at,
+        }
+    }
+}
+
+impl TryFrom<SqliteWorkspace> for Workspace {
+    type Error = errors::Error;
+
+    fn try_from(workspace: SqliteWorkspace) -> Result<Self, Self::Error> {
+        Ok(Workspace {
+            id: parse_id(&workspace.id)?,
+            name: workspace.name,
+            personal: workspace.personal,
+            created_at: workspace.created_at,
+        })
+    }
+}
+
+impl From<WorkspaceMember> for SqliteWorkspaceMember {
+    fn from(member: WorkspaceMember) -> Self {
+        SqliteWorkspaceMember {
+            workspace_id: member.workspace_id.to_string(),
+            user_id: member.user_id.to_string(),
+            role: member.role,
+            created_at: member.created_at,
+        }
+    }
+}
+
+impl TryFrom<SqliteWorkspaceMember> for WorkspaceMember {
+    type Error = errors::Error;
+
+    fn try_from(member: SqliteWorkspaceMember) -> Result<Self, Self::Error> {
+        Ok(WorkspaceMember {
+            workspace_id: parse_id(&member.workspace_id)?,
+            user_id: parse_id(&member.user_id)?,
+            role: member.role,
+            created_at: member.created_at,
+        })
+    }
+}
+
+impl From<Notebook> for SqliteNotebook {
+    fn from(notebook: Notebook) -> Self {
+        SqliteNotebook {
+            id: notebook.id.to_string(),
+            workspace_id: notebook.workspace_id.to_string(),
+            parent_id: notebook.parent_id.map(|id| id.to_string()),
+            name: notebook.name,
+            created_at: notebook.created_at,
+        }
+    }
+}
+
+impl TryFrom<SqliteNotebook> for Notebook {
+    type Error = errors::Error;
+
+    fn try_from(notebook: SqliteNotebook) -> Result<Self, Self::Error> {
+        Ok(Notebook {
+            id: parse_id(&notebook.id)?,
+            workspace_id: parse_id(&notebook.workspace_id)?,
+            parent_id: notebook.parent_id.as_deref().map(parse_id).transpose()?,
+            name: notebook.name,
+            created_at: notebook.created_at,
+        })
+    }
+}
+
+impl From<PostPermission> for SqlitePostPermission {
+    fn from(permission: PostPermission) -> Self {
+        SqlitePostPermission {
+            post_id: permission.post_id.to_string(),
+            user_id: permission.user_id.to_string(),
+            role: permission.role,
+            created_at: permission.created_at,
+        }
+    }
+}
+
+impl TryFrom<SqlitePostPermission> for PostPermission {
+    type Error = errors::Error;
+
+    fn try_from(permission: SqlitePostPermission) -> Result<Self, Self::Error> {
+        Ok(PostPermission {
+            post_id: parse_id(&permission.post_id)?,
+            user_id: parse_id(&permission.user_id)?,
+            role: permission.role,
+            created_at: permission.created_at,
+        })
+    }
+}
+
+impl From<AuditEvent> for SqliteAuditEvent {
+    fn from(event: AuditEvent) -> Self {
+        SqliteAuditEvent {
+            id: event.id.to_string(),
+            actor_id: event.actor_id.map(|id| id.to_string()),
+            actor_name: event.actor_name,
+            action: event.action,
+            target_id: event.target_id.map(|id| id.to_string()),
+            success: event.success,
+            ip: event.ip,
+            user_agent: event.user_agent,
+            created_at: event.created_at,
+        }
+    }
+}
+
+impl TryFrom<SqliteAuditEvent> for AuditEvent {
+    type Error = errors::Error;
+
+    fn try_from(event: SqliteAuditEvent) -> Result<Self, Self::Error> {
+        Ok(AuditEvent {
+            id: parse_id(&event.id)?,
+            actor_id: event.actor_id.as_deref().map(parse_id).transpose()?,
+            actor_name: event.actor_name,
+            action: event.action,
+            target_id: event.target_id.as_deref().map(parse_id).transpose()?,
+            success: event.success,
+            ip: event.ip,
+            user_agent: event.user_agent,
+            created_at: event.created_at,
+        })
+    }
+}
+
+impl From<Attachment> for SqliteAttachment {
+    fn from(attachment: Attachment) -> Self {
+        SqliteAttachment {
+            id: attachment.id.to_string(),
+            post_id: attachment.post_id.to_string(),
+            user_id: attachment.user_id.to_string(),
+            file_name: attachment.file_name,
+            content_type: attachment.content_type,
+            size: attachment.size,
+            hash: attachment.hash,
+            created_at: attachment.created_at,
+        }
+    }
+}
+
+impl TryFrom<SqliteAttachment> for Attachment {
+    type Error = errors::Error;
+
+    fn try_from(attachment: SqliteAttachment) -> Result<Self, Self::Error> {
+        Ok(Attachment {
+            id: parse_id(&attachment.id)?,
+            post_id: parse_id(&attachment.post_id)?,
+            user_id: parse_id(&attachment.user_id)?,
+            file_name: attachment.file_name,
+            content_type: attachment.content_type,
+            size: attachment.size,
+            hash: attachment.hash,
+            created_at: attachment.created_at,
+        })
+    }
+}
+
+impl From<ShareLink> for SqliteShareLink {
+    fn from(link: ShareLink) -> Self {
+        SqliteShareLink {
+            id: link.id.to_string(),
+            post_id: link.post_id.to_string(),
+            token_hash: link.token_hash,
+            password_hash: link.password_hash,
+            expires_at: link.expires_at,
+            created_at: link.created_at,
+        }
+    }
+}
+
+impl TryFrom<SqliteShareLink> for ShareLink {
+    type Error = errors::Error;
+
+    fn try_from(link: SqliteShareLink) -> Result<Self, Self::Error> {
+        Ok(ShareLink {
+            id: parse_id(&link.id)?,
+            post_id: parse_id(&link.post_id)?,
+            token_hash: link.token_hash,
+            password_hash: link.password_hash,
+            expires_at: link.expires_at,
+            created_at: link.created_at,
+        })
+    }
+}
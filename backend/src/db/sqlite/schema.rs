@@ -0,0 +1,136 @@
+//SQLite has no uuid type, ids are stored as text
+
+diesel::table! {
+    attachments (id) {
+        id -> Text,
+        post_id -> Text,
+        user_id -> Text,
+        file_name -> Text,
+        content_type -> Text,
+        size -> BigInt,
+        hash -> Text,
+        created_at -> TimestamptzSqlite,
+    }
+}
+
+diesel::table! {
+    audit_events (id) {
+        id -> Text,
+        actor_id -> Nullable<Text>,
+        actor_name -> Nullable<Text>,
+        action -> Text,
+        target_id -> Nullable<Text>,
+        success -> Bool,
+        ip -> Nullable<Text>,
+        user_agent -> Nullable<Text>,
+        created_at -> TimestamptzSqlite,
+    }
+}
+
+diesel::table! {
+    important_posts (user_id, post_id) {
+        user_id -> Text,
+        post_id -> Text,
+    }
+}
+
+diesel::table! {
+    notebooks (id) {
+        id -> Text,
+        workspace_id -> Text,
+        parent_id -> Nullable<Text>,
+        name -> Text,
+        created_at -> TimestamptzSqlite,
+    }
+}
+
+diesel::table! {
+    post_permissions (post_id, user_id) {
+        post_id -> Text,
+        user_id -> Text,
+        role -> Text,
+        created_at -> TimestamptzSqlite,
+    }
+}
+
+diesel::table! {
+    posts (id) {
+        id -> Text,
+        user_id -> Text,
+        title -> Text,
+        description -> Nullable<Text>,
+        created_at -> TimestamptzSqlite,
+        workspace_id -> Text,
+        notebook_id -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    share_links (id) {
+        id -> Text,
+        post_id -> Text,
+        token_hash -> Text,
+        password_hash -> Nullable<Text>,
+        expires_at -> Nullable<TimestamptzSqlite>,
+        created_at -> TimestamptzSqlite,
+    }
+}
+
+diesel::table! {
+    users (id) {
+        id -> Text,
+        username -> Text,
+        password -> Text,
+        created_at -> TimestamptzSqlite,
+        updated_at -> TimestamptzSqlite,
+        disabled -> Bool,
+        role -> Text,
+        session_version -> Integer,
+        username_key -> Text,
+    }
+}
+
+diesel::table! {
+    workspace_members (workspace_id, user_id) {
+        workspace_id -> Text,
+        user_id -> Text,
+        role -> Text,
+        created_at -> TimestamptzSqlite,
+    }
+}
+
+diesel::table! {
+    workspaces (id) {
+        id -> Text,
+        name -> Text,
+        personal -> Bool,
+        created_at -> TimestamptzSqlite,
+    }
+}
+
+diesel::joinable!(attachments -> posts (post_id));
+diesel::joinable!(attachments -> users (user_id));
+diesel::joinable!(important_posts -> posts (post_id));
+diesel::joinable!(important_posts -> users (user_id));
+diesel::joinable!(notebooks -> workspaces (workspace_id));
+diesel::joinable!(post_permissions -> posts (post_id));
+diesel::joinable!(post_permissions -> users (user_id));
+diesel::joinable!(posts -> notebooks (notebook_id));
+diesel::joinable!(posts -> users (user_id));
+diesel::joinable!(posts -> workspaces (workspace_id));
+diesel::joinable!(share_links -> posts (post_id));
+diesel::joinable!(workspace_members -> users (user_id));
+diesel::joinable!(workspace_members -> workspaces (workspace_id));
+
+diesel::allow_tables_to_appear_in_same_query!(
+    attachments,
+    audit_events,
+    important_posts,
+    notebooks,
+    post_permissions,
+    posts,
+    share_links,
+    users,
+    workspace_members,
+    workspaces,
+);
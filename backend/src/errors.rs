@@ -1,77 +1,149 @@
-use actix_web::error::BlockingError;
-use actix_web::http::StatusCode;
-use actix_web::{HttpResponse, ResponseError};
-use serde::{Deserialize, Serialize};
+use actix_multipart::MultipartError;
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    error::{JsonPayloadError, PayloadError, QueryPayloadError},
+    http::header::{self, HeaderMap, HeaderValue},
+    middleware::Next,
+    HttpRequest, HttpResponse,
+};
+use common::Locale;
 
-#[derive(Debug, Serialize, Deserialize)]
-pub enum Auth {
-    Authentication,
-    Authorization,
-}
+//the error model is shared with the frontend
+pub use common::errors::{Auth, ErrorResponse as Error, ErrorTypes, FieldError};
+
+/// The middleware `hide_error_cause` removes the internal `cause` (for example a raw database error)
+/// from the error responses. The cause is written to the log instead, with the request id of the
+/// request. It is used unless `server.expose_error_cause` is set.
+pub async fn hide_error_cause(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, actix_web::Error> {
+    let response = next.call(req).await?;
+
+    let public = response
+        .response()
+        .error()
+        .and_then(|e| e.as_error::<Error>())
+        .filter(|e| e.cause.is_some())
+        .map(|e| {
+            tracing::error!("Error cause: {}", e.cause.as_deref().unwrap_or_default());
+            Error {
+                cause: None,
+                ..e.clone()
+            }
+        });
 
-#[derive(Debug, Serialize, Deserialize)]
-pub enum ErrorTypes {
-    ValidationError,
-    DbError,
-    Auth(Auth),
-    JwtError,
+    Ok(replace_error(response, public))
 }
 
-/// The `Error` struct represents an error with optional cause, message, and error type.
-///
-/// Properties:
-///
-/// * `cause`: An optional string that represents the cause of the error. It provides additional
-/// information about why the error occurred.
-/// * `message`: The `message` property is an optional string that represents a human-readable
-/// description of the error. It can be used to provide additional information about the error to the
-/// user or developer.
-/// * `error_type`: The `error_type` property is of type `ErrorTypes`.
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Error {
-    pub cause: Option<String>,
-    pub message: Option<String>,
-    pub error_type: ErrorTypes,
+/// The function `request_locale` picks the language of the client from the `Accept-Language` header.
+/// The frontend sends the language selected by the user there. English is used when none of the
+/// accepted languages is supported.
+pub fn request_locale(headers: &HeaderMap) -> Locale {
+    headers
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(Locale::negotiate)
+        .unwrap_or_default()
 }
 
-impl Error {
-    pub fn new(cause: Option<String>, message: Option<String>, error_type: ErrorTypes) -> Self {
-        Error {
-            cause,
-            message,
-            error_type,
-        }
+/// The middleware `localize_errors` translates the messages of the error responses that have a code
+/// to the language of the client, see `request_locale`.
+pub async fn localize_errors(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, actix_web::Error> {
+    let locale = request_locale(req.headers());
+    let response = next.call(req).await?;
+
+    //the messages are written in English
+    let localized = response
+        .response()
+        .error()
+        .and_then(|e| e.as_error::<Error>())
+        .filter(|e| locale != Locale::default() && (e.code.is_some() || !e.errors.is_empty()))
+        .map(|e| {
+            let mut error = e.clone();
+            error.localize(locale);
+            error
+        });
+
+    let translated = localized.is_some();
+    let mut response = replace_error(response, localized);
+    if translated {
+        let value = HeaderValue::from_static(locale.code());
+        response
+            .headers_mut()
+            .insert(header::CONTENT_LANGUAGE, value);
     }
+    Ok(response)
 }
 
-impl std::fmt::Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let json_string = serde_json::to_string_pretty(self).map_err(|_| std::fmt::Error)?;
-        write!(f, "{}", json_string)
+//the new response keeps the error, so the outer middlewares can still read it
+fn replace_error<B>(
+    response: ServiceResponse<B>,
+    error: Option<Error>,
+) -> ServiceResponse<EitherBody<B>> {
+    let Some(error) = error else {
+        return response.map_into_left_body();
+    };
+
+    let mut new_response = HttpResponse::from_error(error);
+    //keep the headers set by the handler or the inner middlewares
+    for (name, value) in response.headers() {
+        if !new_response.headers().contains_key(name) {
+            new_response
+                .headers_mut()
+                .append(name.clone(), value.clone());
+        }
     }
+    response.into_response(new_response).map_into_right_body()
 }
 
-impl ResponseError for Error {
-    fn status_code(&self) -> StatusCode {
-        match &self.error_type {
-            ErrorTypes::ValidationError => StatusCode::BAD_REQUEST,
-            ErrorTypes::DbError => StatusCode::INTERNAL_SERVER_ERROR,
-            ErrorTypes::Auth(auth) => match auth {
-                Auth::Authentication => StatusCode::UNAUTHORIZED,
-                Auth::Authorization => StatusCode::FORBIDDEN,
-            },
-            ErrorTypes::JwtError => StatusCode::INTERNAL_SERVER_ERROR,
+/// The function `json_error` converts the errors of the JSON extractor to the shared error model. A
+/// too large body is a `PayloadTooLarge` error, everything else is a `ValidationError`.
+///
+/// Arguments:
+///
+/// * `error`: The `error` parameter is the error of `web::Json`.
+/// * `_req`: The `_req` parameter is the request, it is required by `JsonConfig::error_handler`.
+pub fn json_error(error: JsonPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    let (code, error_type) = match error {
+        JsonPayloadError::Overflow { .. } | JsonPayloadError::OverflowKnownLength { .. } => {
+            ("request.too_large", ErrorTypes::PayloadTooLarge)
         }
-    }
+        _ => ("request.invalid_json", ErrorTypes::ValidationError),
+    };
+    Error::from_code(code, error_type).with_cause(error).into()
+}
 
-    fn error_response(&self) -> HttpResponse {
-        HttpResponse::build(self.status_code()).json(self)
-    }
+/// The function `query_error` converts the errors of the query string extractor to a `ValidationError`
+/// of the shared error model.
+///
+/// Arguments:
+///
+/// * `error`: The `error` parameter is the error of `web::Query`.
+/// * `_req`: The `_req` parameter is the request, it is required by `QueryConfig::error_handler`.
+pub fn query_error(error: QueryPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    Error::from_code("request.invalid_query", ErrorTypes::ValidationError)
+        .with_cause(error)
+        .into()
 }
 
-//convert error from 'web::block(||).await' to custom Error
-impl From<BlockingError> for Error {
-    fn from(value: BlockingError) -> Self {
-        Error::new(Some(value.to_string()), None, ErrorTypes::DbError)
-    }
+/// The function `multipart_error` converts the errors of the multipart form extractor to the shared
+/// error model, like `json_error`. It is applied in the handler, because `MultipartFormConfig` does not
+/// pass every error to its error handler, a wrong `Content-Type` for example.
+///
+/// Arguments:
+///
+/// * `error`: The `error` parameter is the error of `MultipartForm`.
+pub fn multipart_error(error: actix_web::Error) -> Error {
+    let (code, error_type) = match error.as_error::<MultipartError>() {
+        Some(MultipartError::Payload(PayloadError::Overflow)) => {
+            ("request.too_large", ErrorTypes::PayloadTooLarge)
+        }
+        _ => ("request.invalid_multipart", ErrorTypes::ValidationError),
+    };
+    Error::from_code(code, error_type).with_cause(error)
 }
@@ -0,0 +1,95 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix_web::{
+    get,
+    http::header,
+    web::{self, Bytes},
+    HttpResponse,
+};
+use common::PostEvent;
+use futures::{stream, Stream};
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+use tracing::instrument;
+
+use super::Delivery;
+use crate::errors;
+use crate::tasks::Tasks;
+use crate::{app::AppState, auth::JwtMiddleware};
+
+//a comment is sent this often, so proxies keep the idle stream open and a shutdown ends it
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+#[get("/events")]
+#[instrument(skip_all, name = "Subscribe to events")]
+async fn get_events(state: web::Data<AppState>, auth: JwtMiddleware) -> HttpResponse {
+    tracing::info!("User {} subscribed to the events", auth.user_id);
+    let events = event_stream(state.events.subscribe(), auth.user_id, state.tasks.clone());
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        .insert_header((header::HeaderName::from_static("x-accel-buffering"), "no"))
+        .streaming(events)
+}
+
+/// The function `event_stream` returns the Server-Sent Events of a user. The stream ends when the
+/// server shuts down.
+///
+/// Arguments:
+///
+/// * `receiver`: The `receiver` parameter gets all events of the instance.
+/// * `user_id`: The `user_id` parameter is the subscriber, only the events of the posts they can see
+/// are sent.
+/// * `tasks`: The `tasks` parameter tells when the server shuts down.
+///
+/// Returns:
+///
+/// a stream of `Result<Bytes, errors::Error>` with one event or keep-alive comment per chunk.
+fn event_stream(
+    receiver: Receiver<Arc<Delivery>>,
+    user_id: uuid::Uuid,
+    tasks: Tasks,
+) -> impl Stream<Item = Result<Bytes, errors::Error>> {
+    let keep_alive = tokio::time::interval(KEEP_ALIVE);
+    stream::unfold(
+        (receiver, keep_alive),
+        move |(mut receiver, mut keep_alive)| {
+            let tasks = tasks.clone();
+            async move {
+                loop {
+                    if tasks.is_draining() {
+                        return None;
+                    }
+                    let chunk = tokio::select! {
+                        _ = keep_alive.tick() => Some(Bytes::from_static(b": keep-alive\n\n")),
+                        received = receiver.recv() => match received {
+                            Ok(delivery) => delivery.for_user(user_id).and_then(|event| frame(&event)),
+                            Err(RecvError::Lagged(missed)) => {
+                                tracing::warn!("User {} missed {} events", user_id, missed);
+                                None
+                            }
+                            Err(RecvError::Closed) => return None,
+                        },
+                    };
+                    if let Some(chunk) = chunk {
+                        return Some((Ok(chunk), (receiver, keep_alive)));
+                    }
+                }
+            }
+        },
+    )
+}
+
+//one event in the text/event-stream format, the kind is the event name
+fn frame(event: &PostEvent) -> Option<Bytes> {
+    match serde_json::to_string(event) {
+        Ok(data) => Some(Bytes::from(format!(
+            "event: {}\ndata: {}\n\n",
+            event.kind, data
+        ))),
+        Err(e) => {
+            tracing::error!("Failed to encode event: {}", e);
+            None
+        }
+    }
+}
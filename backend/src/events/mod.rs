@@ -0,0 +1,218 @@
+pub mod handlers;
+pub mod postgres;
+
+use std::sync::Arc;
+
+use actix_web::web;
+use common::{Permission, PostEvent, PostEventKind, ResponsePost};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+use crate::app::{AppState, DbPool};
+use crate::db::db_notify;
+use crate::errors;
+use crate::tasks::Tasks;
+
+//the events kept for the slow subscribers, the ones that fall behind more miss events
+const CAPACITY: usize = 1024;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(handlers::get_events);
+}
+
+/// The `Recipient` struct is a user who gets an event, with what they may do with the post.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Recipient {
+    pub user_id: uuid::Uuid,
+    pub permission: Permission,
+}
+
+/// The `Delivery` struct is an event with the users who get it.
+///
+/// Properties:
+///
+/// * `event`: The `event` property is the event as its author sees it.
+/// * `recipients`: The `recipients` property lists the users who can see the post.
+#[derive(Debug, Clone)]
+pub struct Delivery {
+    pub event: PostEvent,
+    pub recipients: Vec<Recipient>,
+}
+
+impl Delivery {
+    /// The function `for_user` returns the event as a user sees it, with their permission. The
+    /// important flag of the post is personal, the one of the user who made the change is not sent,
+    /// the lists keep their own.
+    ///
+    /// Returns:
+    ///
+    /// the event, or `None` if the user is not a recipient.
+    pub fn for_user(&self, user_id: uuid::Uuid) -> Option<PostEvent> {
+        let recipient = self.recipients.iter().find(|r| r.user_id == user_id)?;
+        let mut event = self.event.clone();
+        if let Some(post) = event.post.as_mut() {
+            post.permission = recipient.permission;
+            post.important = false;
+        }
+        Some(event)
+    }
+}
+
+/// The `EventHub` struct fans the events of the posts out to the `/events` streams of this instance.
+/// With PostgreSQL the events are also sent to the other instances with `NOTIFY`, and the ones they
+/// send are received with `LISTEN`.
+///
+/// Properties:
+///
+/// * `sender`: The `sender` property is the channel of the subscribers of this instance.
+/// * `origin`: The `origin` property is the id of this instance.
+/// * `relay`: The `relay` property is the PostgreSQL pool and connection url, `None` for the other
+/// databases, which serve one instance only.
+#[derive(Clone)]
+pub struct EventHub {
+    sender: broadcast::Sender<Arc<Delivery>>,
+    origin: uuid::Uuid,
+    relay: Option<(DbPool, String)>,
+}
+
+impl EventHub {
+    pub fn new(relay: Option<(DbPool, String)>) -> Self {
+        let (sender, _) = broadcast::channel(CAPACITY);
+        EventHub {
+            sender,
+            origin: uuid::Uuid::new_v4(),
+            relay,
+        }
+    }
+
+    /// The function `subscribe` returns a receiver of all events published from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<Delivery>> {
+        self.sender.subscribe()
+    }
+
+    /// The function `publish` sends an event to the subscribers of this instance and notifies the
+    /// other instances. Failures are logged, the change that caused the event is done anyway.
+    ///
+    /// Arguments:
+    ///
+    /// * `event`: The `event` parameter is the event as its author sees it.
+    /// * `recipients`: The `recipients` parameter lists the users who get it.
+    pub async fn publish(&self, event: PostEvent, recipients: Vec<Recipient>) {
+        let delivery = Delivery { event, recipients };
+        if let Some((pool, _)) = &self.relay {
+            match postgres::payload(self.origin, &delivery) {
+                Ok(payload) => {
+                    if let Err(e) = db_notify(postgres::CHANNEL, payload, pool).await {
+                        tracing::error!("Failed to notify the other instances: {}", e);
+                    }
+                }
+                Err(e) => tracing::error!("Failed to encode event: {}", e),
+            }
+        }
+        self.deliver(delivery);
+    }
+
+    /// The function `spawn_listener` starts the background task that receives the events of the other
+    /// instances. Nothing is started without PostgreSQL.
+    ///
+    /// Arguments:
+    ///
+    /// * `tasks`: The `tasks` parameter runs the background task until the shutdown.
+    /// * `state`: The `state` parameter reads the posts and the users who can see them, the
+    /// notifications only have their ids.
+    pub fn spawn_listener(&self, tasks: &Tasks, state: web::Data<AppState>) {
+        if let Some((_, url)) = &self.relay {
+            tasks.spawn(
+                "event listener",
+                postgres::listen(self.clone(), url.clone(), state),
+            );
+        }
+    }
+
+    //sends to the subscribers of this instance, it is fine if there are none
+    fn deliver(&self, delivery: Delivery) {
+        let kind = delivery.event.kind;
+        let subscribers = self.sender.send(Arc::new(delivery)).unwrap_or_default();
+        tracing::debug!("Event {} sent to {} subscribers", kind, subscribers);
+    }
+}
+
+/// The function `recipients` returns the users who can see a post: its author, the members of its
+/// workspace and the users it is shared with.
+///
+/// Arguments:
+///
+/// * `state`: The `state` parameter is the app state with the repositories.
+/// * `post_id`: The `post_id` parameter is the id of the post.
+/// * `owner_id`: The `owner_id` parameter is the author of the post.
+/// * `workspace_id`: The `workspace_id` parameter is the workspace of the post.
+pub async fn recipients(
+    state: &AppState,
+    post_id: uuid::Uuid,
+    owner_id: uuid::Uuid,
+    workspace_id: uuid::Uuid,
+) -> Result<Vec<Recipient>, errors::Error> {
+    let mut recipients = vec![Recipient {
+        user_id: owner_id,
+        permission: Permission::Owner,
+    }];
+    let members = state.workspaces.get_members(workspace_id).await?;
+    let granted = state.permissions.get_post_permissions(post_id).await?;
+    let others = members
+        .into_iter()
+        .map(|member| (member.user_id, member.role.post_permission()))
+        .chain(granted.into_iter().map(|grant| (grant.user_id, grant.role)));
+    for (user_id, permission) in others {
+        match recipients.iter_mut().find(|r| r.user_id == user_id) {
+            Some(recipient) => recipient.permission = recipient.permission.max(permission),
+            None => recipients.push(Recipient {
+                user_id,
+                permission,
+            }),
+        }
+    }
+    Ok(recipients)
+}
+
+/// The function `post_changed` publishes `post.created` or `post.updated` with the post to the users
+/// who can see it.
+///
+/// Arguments:
+///
+/// * `state`: The `state` parameter is the app state with the repositories and the hub.
+/// * `kind`: The `kind` parameter is `PostEventKind::Created` or `PostEventKind::Updated`.
+/// * `post`: The `post` parameter is the post as its author or editor sees it.
+pub async fn post_changed(state: &AppState, kind: PostEventKind, post: &ResponsePost) {
+    match recipients(state, post.id, post.owner_id, post.workspace_id).await {
+        Ok(recipients) => {
+            let event = PostEvent {
+                kind,
+                post_id: post.id,
+                workspace_id: post.workspace_id,
+                post: Some(post.clone()),
+                important: None,
+            };
+            state.events.publish(event, recipients).await;
+        }
+        Err(e) => tracing::error!("Failed to find the recipients of post {}: {}", post.id, e),
+    }
+}
+
+/// The function `stored_post_changed` publishes `post.created` or `post.updated` with the post read
+/// from the repositories, for changes that do not return the post.
+pub async fn stored_post_changed(state: &AppState, kind: PostEventKind, post_id: uuid::Uuid) {
+    match stored_post(state, post_id).await {
+        Ok(post) => post_changed(state, kind, &post).await,
+        Err(e) => tracing::error!("Failed to read post {} for the event: {}", post_id, e),
+    }
+}
+
+/// The function `stored_post` reads a post as its author sees it, with the username of the author.
+pub async fn stored_post(
+    state: &AppState,
+    post_id: uuid::Uuid,
+) -> Result<ResponsePost, errors::Error> {
+    let post = state.notes.find_post(post_id).await?;
+    let owner = state.users.find_user(post.user_id).await?.username;
+    Ok(post.response(owner, false, Permission::Owner))
+}
@@ -0,0 +1,242 @@
+use std::time::Duration;
+
+use actix_web::web;
+use common::{Permission, PostEvent, PostEventKind};
+use futures::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tokio_postgres::{AsyncMessage, NoTls};
+
+use super::{recipients, stored_post, Delivery, EventHub, Recipient};
+use crate::app::AppState;
+use crate::errors;
+
+/// The PostgreSQL channel the instances send the events on.
+pub const CHANNEL: &str = "post_events";
+//the wait before the listener connects again after the connection was lost
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+//the largest payload of a notification PostgreSQL accepts
+const MAX_PAYLOAD: usize = 7999;
+
+/// The `Notification` struct is the payload of the notifications between the instances. It only has
+/// ids, so it stays below the 8000 bytes of a notification however long the post is; the receiving
+/// instance reads the post and its recipients itself.
+///
+/// Properties:
+///
+/// * `origin`: The `origin` property is the instance that published the event, it ignores its own
+/// notifications.
+/// * `kind`: The `kind` property is what happened.
+/// * `post_id`: The `post_id` property is the id of the post.
+/// * `workspace_id`: The `workspace_id` property is the workspace of the post, a deleted post can not
+/// be read for it.
+/// * `user_id`: The `user_id` property is the user whose important flag changed, for
+/// `post.important_changed` only.
+/// * `important`: The `important` property is the new important flag of that user.
+/// * `recipients`: The `recipients` property lists the users who could see a deleted post, for
+/// `post.deleted` only, they can not be read after the delete. Without it, when there are too many of
+/// them for a notification, the members of the workspace get the event.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub origin: uuid::Uuid,
+    pub kind: PostEventKind,
+    pub post_id: uuid::Uuid,
+    pub workspace_id: uuid::Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<uuid::Uuid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub important: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recipients: Option<Vec<uuid::Uuid>>,
+}
+
+/// The function `payload` encodes a delivery of this instance as the text of a notification.
+///
+/// Arguments:
+///
+/// * `origin`: The `origin` parameter is the id of this instance.
+/// * `delivery`: The `delivery` parameter is the published event with its recipients.
+///
+/// Returns:
+///
+/// the JSON text, or the error if it could not be encoded.
+pub fn payload(origin: uuid::Uuid, delivery: &Delivery) -> Result<String, serde_json::Error> {
+    let event = &delivery.event;
+    //the important flag is personal, its event has one recipient
+    let user_id = match event.kind {
+        PostEventKind::ImportantChanged => delivery.recipients.first().map(|r| r.user_id),
+        _ => None,
+    };
+    let recipients = match event.kind {
+        PostEventKind::Deleted => Some(delivery.recipients.iter().map(|r| r.user_id).collect()),
+        _ => None,
+    };
+    let mut notification = Notification {
+        origin,
+        kind: event.kind,
+        post_id: event.post_id,
+        workspace_id: event.workspace_id,
+        user_id,
+        important: event.important,
+        recipients,
+    };
+    let payload = serde_json::to_string(&notification)?;
+    if payload.len() <= MAX_PAYLOAD {
+        return Ok(payload);
+    }
+    tracing::warn!(
+        "Too many recipients for the event of post {}, the workspace members get it",
+        event.post_id
+    );
+    notification.recipients = None;
+    serde_json::to_string(&notification)
+}
+
+/// The function `listen` receives the events of the other instances and sends them to the
+/// subscribers of this one. The connection is opened again when it is lost.
+///
+/// Arguments:
+///
+/// * `hub`: The `hub` parameter is the hub of this instance.
+/// * `url`: The `url` parameter is the PostgreSQL connection url.
+/// * `state`: The `state` parameter reads the posts and the users who can see them.
+pub async fn listen(hub: EventHub, url: String, state: web::Data<AppState>) {
+    loop {
+        match listen_once(&hub, &url, &state).await {
+            Ok(()) => tracing::warn!("Event listener connection closed"),
+            Err(e) => tracing::error!("Event listener failed: {}", e),
+        }
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+//one connection: the messages are read while the notifications are handled
+async fn listen_once(
+    hub: &EventHub,
+    url: &str,
+    state: &AppState,
+) -> Result<(), tokio_postgres::Error> {
+    let (client, mut connection) = tokio_postgres::connect(url, NoTls).await?;
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+
+    let messages = async move {
+        let mut messages = stream::poll_fn(move |cx| connection.poll_message(cx));
+        while let Some(message) = messages.next().await {
+            if let AsyncMessage::Notification(notification) = message? {
+                //the receiver is gone only when the listener stops
+                if sender.send(notification.payload().to_owned()).is_err() {
+                    break;
+                }
+            }
+        }
+        Ok::<_, tokio_postgres::Error>(())
+    };
+    let notifications = async {
+        client.batch_execute(&format!("LISTEN {}", CHANNEL)).await?;
+        tracing::info!("Listening for the events of the other instances");
+        while let Some(payload) = receiver.recv().await {
+            receive(hub, &payload, state).await;
+        }
+        Ok::<_, tokio_postgres::Error>(())
+    };
+
+    tokio::try_join!(messages, notifications).map(|_| ())
+}
+
+//a notification of another instance, the own ones were delivered when they were published
+async fn receive(hub: &EventHub, payload: &str, state: &AppState) {
+    let notification: Notification = match serde_json::from_str(payload) {
+        Ok(notification) => notification,
+        Err(e) => {
+            tracing::error!("Failed to decode event: {}", e);
+            return;
+        }
+    };
+    if notification.origin == hub.origin {
+        return;
+    }
+
+    match delivery(state, &notification).await {
+        Ok(Some(delivery)) => hub.deliver(delivery),
+        Ok(None) => {}
+        Err(e) => tracing::error!(
+            "Failed to read post {} for the event: {}",
+            notification.post_id,
+            e
+        ),
+    }
+}
+
+//the event of a notification with its recipients, `None` if the post was deleted in the meantime
+async fn delivery(
+    state: &AppState,
+    notification: &Notification,
+) -> Result<Option<Delivery>, errors::Error> {
+    let mut event = PostEvent {
+        kind: notification.kind,
+        post_id: notification.post_id,
+        workspace_id: notification.workspace_id,
+        post: None,
+        important: notification.important,
+    };
+    let delivery = match notification.kind {
+        PostEventKind::Created | PostEventKind::Updated => {
+            let post = match stored_post(state, notification.post_id).await {
+                Ok(post) => post,
+                Err(e) if e.error_type == errors::ErrorTypes::NotFound => {
+                    tracing::debug!(
+                        "Post {} is gone, its event is dropped",
+                        notification.post_id
+                    );
+                    return Ok(None);
+                }
+                Err(e) => return Err(e),
+            };
+            //an editing room on this instance does not store the old text over the change
+            if notification.kind == PostEventKind::Updated {
+                state
+                    .collab
+                    .description_changed(post.id, post.description.clone())
+                    .await;
+            }
+            let recipients = recipients(state, post.id, post.owner_id, post.workspace_id).await?;
+            event.post = Some(post);
+            Delivery { event, recipients }
+        }
+        PostEventKind::ImportantChanged => {
+            let Some(user_id) = notification.user_id else {
+                return Ok(None);
+            };
+            Delivery {
+                event,
+                recipients: vec![Recipient {
+                    user_id,
+                    permission: Permission::Viewer,
+                }],
+            }
+        }
+        PostEventKind::Deleted => {
+            let user_ids = match &notification.recipients {
+                Some(user_ids) => user_ids.clone(),
+                None => state
+                    .workspaces
+                    .get_members(notification.workspace_id)
+                    .await?
+                    .into_iter()
+                    .map(|member| member.user_id)
+                    .collect(),
+            };
+            Delivery {
+                event,
+                recipients: user_ids
+                    .into_iter()
+                    .map(|user_id| Recipient {
+                        user_id,
+                        permission: Permission::Viewer,
+                    })
+                    .collect(),
+            }
+        }
+    };
+    Ok(Some(delivery))
+}
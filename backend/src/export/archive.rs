@@ -0,0 +1,166 @@
+use std::io::{self, ErrorKind, Seek, SeekFrom, Write};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use time::OffsetDateTime;
+use zip::{write::SimpleFileOptions, CompressionMethod, DateTime, ZipWriter};
+
+use crate::errors;
+
+/// The `ZipStream` struct writes a zip archive with the `zip` crate piece by piece, so it can be
+/// streamed. The writer of the crate goes back to fill in the header of a file when the file is done,
+/// so the bytes of a file are returned when the next file is added, and the rest by `finish`.
+///
+/// Properties:
+///
+/// * `zip`: The `zip` property is the writer of the archive.
+/// * `spool`: The `spool` property holds the bytes that were written but not returned yet.
+pub struct ZipStream {
+    zip: ZipWriter<Spool>,
+    spool: Spool,
+}
+
+impl Default for ZipStream {
+    fn default() -> Self {
+        let spool = Spool::default();
+        ZipStream {
+            zip: ZipWriter::new(spool.clone()),
+            spool,
+        }
+    }
+}
+
+impl ZipStream {
+    pub fn new() -> Self {
+        ZipStream::default()
+    }
+
+    /// The function `add_file` compresses a file into the archive.
+    ///
+    /// Arguments:
+    ///
+    /// * `name`: The `name` parameter is the path of the file in the archive.
+    /// * `modified`: The `modified` parameter is the modification time of the file.
+    /// * `content`: The `content` parameter is the content of the file.
+    ///
+    /// Returns:
+    ///
+    /// a `Result<Vec<u8>, errors::Error>` with the bytes to append to the archive, the files before
+    /// this one.
+    pub fn add_file(
+        &mut self,
+        name: &str,
+        modified: OffsetDateTime,
+        content: &[u8],
+    ) -> Result<Vec<u8>, errors::Error> {
+        //the earlier files are done once the next one is started
+        let done = self.spool.end();
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .last_modified_time(zip_time(modified));
+        self.zip.start_file(name, options).map_err(zip_error)?;
+        self.zip.write_all(content).map_err(zip_error)?;
+        Ok(self.spool.take(done))
+    }
+
+    /// The function `finish` returns the rest of the archive: the last file, the central directory
+    /// and the end record.
+    pub fn finish(self) -> Result<Vec<u8>, errors::Error> {
+        self.zip.finish().map_err(zip_error)?;
+        Ok(self.spool.take(u64::MAX))
+    }
+}
+
+//the bytes of the archive from `start` on, the bytes before were returned already
+#[derive(Default)]
+struct Buffer {
+    data: Vec<u8>,
+    start: u64,
+    position: u64,
+}
+
+//shared by the zip writer and the `ZipStream`, which takes the bytes that are done
+#[derive(Clone, Default)]
+struct Spool(Arc<Mutex<Buffer>>);
+
+impl Spool {
+    fn buffer(&self) -> MutexGuard<'_, Buffer> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn end(&self) -> u64 {
+        let buffer = self.buffer();
+        buffer.start + buffer.data.len() as u64
+    }
+
+    //removes and returns the bytes before `until`
+    fn take(&self, until: u64) -> Vec<u8> {
+        let mut buffer = self.buffer();
+        let count = until
+            .saturating_sub(buffer.start)
+            .min(buffer.data.len() as u64);
+        buffer.start += count;
+        buffer.data.drain(..count as usize).collect()
+    }
+}
+
+impl Write for Spool {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        let mut buffer = self.buffer();
+        let at = (buffer.position - buffer.start) as usize;
+        //the bytes after the end are appended, the ones before overwrite what is there
+        let overwritten = (buffer.data.len() - at).min(bytes.len());
+        buffer.data[at..at + overwritten].copy_from_slice(&bytes[..overwritten]);
+        buffer.data.extend_from_slice(&bytes[overwritten..]);
+        buffer.position += bytes.len() as u64;
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for Spool {
+    fn seek(&mut self, to: SeekFrom) -> io::Result<u64> {
+        let mut buffer = self.buffer();
+        let end = buffer.start + buffer.data.len() as u64;
+        let position = match to {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => end.checked_add_signed(offset),
+            SeekFrom::Current(offset) => buffer.position.checked_add_signed(offset),
+        };
+        match position {
+            Some(position) if (buffer.start..=end).contains(&position) => {
+                buffer.position = position;
+                Ok(position)
+            }
+            _ => Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "The position is not in the spooled part of the archive",
+            )),
+        }
+    }
+}
+
+//the zip time starts in 1980 and ends in 2107, the times outside become 1980-01-01 00:00
+fn zip_time(time: OffsetDateTime) -> DateTime {
+    let year = u16::try_from(time.year()).unwrap_or(0);
+    DateTime::from_date_and_time(
+        year,
+        time.month().into(),
+        time.day(),
+        time.hour(),
+        time.minute(),
+        time.second(),
+    )
+    .unwrap_or_default()
+}
+
+fn zip_error(error: impl ToString) -> errors::Error {
+    tracing::error!("Failed to write the archive!");
+    errors::Error::new(
+        Some(error.to_string()),
+        Some("Failed to write the archive!".to_string()),
+        errors::ErrorTypes::InternalError,
+    )
+}
@@ -0,0 +1,79 @@
+use actix_web::{
+    get,
+    http::header::{ContentDisposition, DispositionParam, DispositionType},
+    web::{self, Bytes},
+    HttpResponse,
+};
+use common::WorkspaceRole;
+use futures::Stream;
+use time::OffsetDateTime;
+use tracing::instrument;
+
+use super::{export_stream, ExportFormat, ExportQuery, JsonEncoder, MarkdownEncoder};
+use crate::audit::{Audit, AuditAction};
+use crate::errors;
+use crate::workspaces::workspace_access;
+use crate::{app::AppState, auth::JwtMiddleware};
+
+#[get("/workspaces/{workspace_id}/export")]
+#[instrument(skip(state, auth, audit), name = "Export notes")]
+async fn export_notes(
+    state: web::Data<AppState>,
+    auth: JwtMiddleware,
+    audit: Audit,
+    path: web::Path<uuid::Uuid>,
+    query: web::Query<ExportQuery>,
+) -> Result<HttpResponse, errors::Error> {
+    let workspace_id = path.into_inner();
+    workspace_access(&state, workspace_id, auth.user_id, WorkspaceRole::Member).await?;
+    let now = OffsetDateTime::now_utc();
+    let notes = state.notes.clone();
+    audit
+        .record(
+            AuditAction::PostExport,
+            Some(auth.user_id),
+            None,
+            None,
+            true,
+        )
+        .await;
+
+    let response = match query.format {
+        ExportFormat::Json => attachment(
+            "application/json",
+            format!("notes-{}.json", now.date()),
+            export_stream(notes, auth.user_id, workspace_id, JsonEncoder::new(now)),
+        ),
+        ExportFormat::Markdown => attachment(
+            "application/zip",
+            format!("notes-{}.zip", now.date()),
+            export_stream(
+                notes,
+                auth.user_id,
+                workspace_id,
+                MarkdownEncoder::default(),
+            ),
+        ),
+    };
+    Ok(response)
+}
+
+/// The function `attachment` streams a file download.
+///
+/// Arguments:
+///
+/// * `content_type`: The `content_type` parameter is the media type of the file.
+/// * `file_name`: The `file_name` parameter is the name the browser saves the file as.
+/// * `body`: The `body` parameter is the stream of the file content.
+fn attachment<S>(content_type: &str, file_name: String, body: S) -> HttpResponse
+where
+    S: Stream<Item = Result<Bytes, errors::Error>> + 'static,
+{
+    HttpResponse::Ok()
+        .content_type(content_type)
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(file_name)],
+        })
+        .streaming(body)
+}
@@ -0,0 +1,287 @@
+pub mod archive;
+pub mod handlers;
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use actix_web::web::{self, Bytes};
+use common::ResponsePost;
+use futures::{stream, Stream};
+use serde::{Deserialize, Serialize};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use crate::db::NotesRepository;
+use crate::errors;
+use archive::ZipStream;
+
+/// The version of the JSON export document. It is increased when the shape of the document changes.
+pub const EXPORT_VERSION: u32 = 1;
+//the number of notes read from the database at once
+const PAGE_SIZE: i64 = 100;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(handlers::export_notes);
+}
+
+/// The `ExportFormat` enum lists the formats of `GET /export`: one JSON document, or a zip of Markdown
+/// files.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    Markdown,
+}
+
+/// The `ExportQuery` struct is the query string of `GET /export`.
+///
+/// Properties:
+///
+/// * `format`: The `format` property is of type `ExportFormat`, JSON by default.
+#[derive(Deserialize, Debug)]
+pub struct ExportQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+/// The `NotesExport` struct is the JSON export document.
+///
+/// Properties:
+///
+/// * `version`: The `version` property is `EXPORT_VERSION` of the backend that wrote the document.
+/// * `exported_at`: The `exported_at` property is the time of the export.
+/// * `notes`: The `notes` property lists the notes of the user, oldest first, with their important
+/// flag.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NotesExport {
+    pub version: u32,
+    #[serde(with = "time::serde::rfc3339")]
+    pub exported_at: OffsetDateTime,
+    pub notes: Vec<ResponsePost>,
+}
+
+/// The `Encoder` trait writes the notes in one export format. The export is streamed, so the notes
+/// are passed one page at a time.
+pub trait Encoder: Send + 'static {
+    /// Returns the bytes before the first note.
+    fn start(&mut self) -> Result<Vec<u8>, errors::Error>;
+
+    /// Appends a note to `out`.
+    fn note(&mut self, note: &ResponsePost, out: &mut Vec<u8>) -> Result<(), errors::Error>;
+
+    /// Returns the bytes after the last note.
+    fn finish(&mut self) -> Result<Vec<u8>, errors::Error>;
+}
+
+/// The `JsonEncoder` struct writes the `NotesExport` document.
+///
+/// Properties:
+///
+/// * `exported_at`: The `exported_at` property is the time written to the document.
+/// * `first`: The `first` property is `true` until the first note is written, the other notes are
+/// separated by commas.
+pub struct JsonEncoder {
+    exported_at: OffsetDateTime,
+    first: bool,
+}
+
+impl JsonEncoder {
+    pub fn new(exported_at: OffsetDateTime) -> Self {
+        JsonEncoder {
+            exported_at,
+            first: true,
+        }
+    }
+}
+
+impl Encoder for JsonEncoder {
+    fn start(&mut self) -> Result<Vec<u8>, errors::Error> {
+        //the same document as `NotesExport`, with the notes left open
+        let exported_at = self.exported_at.format(&Rfc3339).map_err(|e| {
+            errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError)
+        })?;
+        Ok(format!(
+            r#"{{"version":{},"exported_at":"{}","notes":["#,
+            EXPORT_VERSION, exported_at
+        )
+        .into_bytes())
+    }
+
+    fn note(&mut self, note: &ResponsePost, out: &mut Vec<u8>) -> Result<(), errors::Error> {
+        if !self.first {
+            out.push(b',');
+        }
+        self.first = false;
+        serde_json::to_writer(out, note)
+            .map_err(|e| errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError))
+    }
+
+    fn finish(&mut self) -> Result<Vec<u8>, errors::Error> {
+        Ok(b"]}".to_vec())
+    }
+}
+
+/// The `MarkdownEncoder` struct writes a zip archive with one Markdown file per note, see
+/// `markdown_note`.
+///
+/// Properties:
+///
+/// * `zip`: The `zip` property is the archive that is written. It is `None` after `finish`.
+/// * `names`: The `names` property holds the file names that are already used.
+pub struct MarkdownEncoder {
+    zip: Option<ZipStream>,
+    names: HashSet<String>,
+}
+
+impl Default for MarkdownEncoder {
+    fn default() -> Self {
+        MarkdownEncoder {
+            zip: Some(ZipStream::new()),
+            names: HashSet::new(),
+        }
+    }
+}
+
+impl MarkdownEncoder {
+    //`<title>-<start of the id>.md`, the whole id is only used if two notes have the same name
+    fn file_name(&mut self, note: &ResponsePost) -> String {
+        let slug = slug(&note.title);
+        let id = note.id.to_string();
+        let short = format!("{}-{}.md", slug, &id[..8]);
+        let name = match self.names.contains(&short) {
+            true => format!("{}-{}.md", slug, id),
+            false => short,
+        };
+        self.names.insert(name.clone());
+        name
+    }
+}
+
+impl Encoder for MarkdownEncoder {
+    fn start(&mut self) -> Result<Vec<u8>, errors::Error> {
+        Ok(Vec::new())
+    }
+
+    fn note(&mut self, note: &ResponsePost, out: &mut Vec<u8>) -> Result<(), errors::Error> {
+        let name = self.file_name(note);
+        let content = markdown_note(note)?;
+        let zip = self.zip.as_mut().ok_or_else(finished)?;
+        out.extend(zip.add_file(&name, note.created_at, content.as_bytes())?);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<Vec<u8>, errors::Error> {
+        self.zip.take().ok_or_else(finished)?.finish()
+    }
+}
+
+fn finished() -> errors::Error {
+    errors::Error::new(
+        None,
+        Some("The archive is already finished!".to_string()),
+        errors::ErrorTypes::DbError,
+    )
+}
+
+/// The function `markdown_note` writes a note as Markdown with a YAML front matter: the id, the
+/// title, the creation time, the important flag and the tags (notes have no tags yet, the list is
+/// always empty). The description is the body.
+///
+/// Arguments:
+///
+/// * `note`: The `note` parameter is the note to write.
+///
+/// Returns:
+///
+/// a `Result<String, errors::Error>` with the content of the file.
+pub fn markdown_note(note: &ResponsePost) -> Result<String, errors::Error> {
+    let created_at = note
+        .created_at
+        .format(&Rfc3339)
+        .map_err(|e| errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError))?;
+    //a JSON string is a valid double-quoted YAML string
+    let title = serde_json::to_string(&note.title)
+        .map_err(|e| errors::Error::new(Some(e.to_string()), None, errors::ErrorTypes::DbError))?;
+
+    Ok(format!(
+        "---\nid: {}\ntitle: {}\ncreated_at: {}\nimportant: {}\ntags: []\n---\n\n{}\n",
+        note.id,
+        title,
+        created_at,
+        note.important,
+        note.description.as_deref().unwrap_or_default()
+    ))
+}
+
+//lowercase letters and digits of the title, the rest becomes single dashes
+fn slug(title: &str) -> String {
+    let slug = title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    match slug.is_empty() {
+        true => "note".to_string(),
+        false => slug
+            .chars()
+            .take(50)
+            .collect::<String>()
+            .trim_end_matches('-')
+            .to_string(),
+    }
+}
+
+enum Step<E> {
+    Start(E),
+    Page(E, i64),
+    Finish(E),
+    Done,
+}
+
+/// The function `export_stream` returns the export of the notes of a workspace as a stream of chunks.
+/// The notes are read a page at a time, so the whole export is never held in memory.
+///
+/// Arguments:
+///
+/// * `notes`: The `notes` parameter is the storage of the notes.
+/// * `user_id`: The `user_id` parameter is the user who exports the notes, with their important flags.
+/// * `workspace_id`: The `workspace_id` parameter is the workspace whose notes are exported.
+/// * `encoder`: The `encoder` parameter writes the export format.
+///
+/// Returns:
+///
+/// a stream of `Result<Bytes, errors::Error>`. An error ends the stream, the client gets a truncated
+/// file then.
+pub fn export_stream<E: Encoder>(
+    notes: Arc<dyn NotesRepository>,
+    user_id: uuid::Uuid,
+    workspace_id: uuid::Uuid,
+    encoder: E,
+) -> impl Stream<Item = Result<Bytes, errors::Error>> {
+    stream::try_unfold(Step::Start(encoder), move |step| {
+        let notes = notes.clone();
+        async move {
+            let (chunk, next) = match step {
+                Step::Start(mut encoder) => (encoder.start()?, Step::Page(encoder, 0)),
+                Step::Page(mut encoder, offset) => {
+                    let page = notes
+                        .get_user_posts(user_id, workspace_id, offset, PAGE_SIZE)
+                        .await?;
+                    let mut chunk = Vec::new();
+                    for note in &page {
+                        encoder.note(note, &mut chunk)?;
+                    }
+                    let next = match (page.len() as i64) < PAGE_SIZE {
+                        true => Step::Finish(encoder),
+                        false => Step::Page(encoder, offset + PAGE_SIZE),
+                    };
+                    (chunk, next)
+                }
+                Step::Finish(mut encoder) => (encoder.finish()?, Step::Done),
+                Step::Done => return Ok(None),
+            };
+            Ok(Some((Bytes::from(chunk), next)))
+        }
+    })
+}
@@ -0,0 +1,35 @@
+use actix_web::{get, web, HttpResponse};
+use serde_json::json;
+
+use crate::app::AppState;
+
+#[get("/health/live")]
+async fn liveness() -> HttpResponse {
+    HttpResponse::Ok().json(json!({"status": "alive"}))
+}
+
+/// The `readiness` handler reports if the instance should get traffic. It is not ready while the server
+/// is draining after a shutdown signal, when the database is not reachable, or when
+/// the database schema is behind the embedded migrations.
+#[get("/health/ready")]
+async fn readiness(state: web::Data<AppState>) -> HttpResponse {
+    if state.tasks.is_draining() {
+        return HttpResponse::ServiceUnavailable().json(json!({"status": "draining"}));
+    }
+
+    let schema = state.schema.has_pending_migrations().await.ok();
+
+    match schema {
+        None => {
+            tracing::error!("Readiness check failed: database is not reachable");
+            HttpResponse::ServiceUnavailable().json(json!({"status": "database unavailable"}))
+        }
+        Some(true) => {
+            tracing::error!(
+                "Readiness check failed: database schema is behind, run the migrations"
+            );
+            HttpResponse::ServiceUnavailable().json(json!({"status": "pending migrations"}))
+        }
+        Some(false) => HttpResponse::Ok().json(json!({"status": "ready"})),
+    }
+}
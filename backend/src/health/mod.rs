@@ -0,0 +1,7 @@
+pub mod handlers;
+
+use actix_web::web;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(handlers::liveness).service(handlers::readiness);
+}
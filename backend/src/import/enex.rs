@@ -0,0 +1,214 @@
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use time::{format_description, PrimitiveDateTime};
+
+use super::{description, failed, first_line, unreadable, Entry, ImportedNote};
+use crate::errors;
+
+//the elements of a note that are read
+#[derive(Clone, Copy, PartialEq)]
+enum Field {
+    Title,
+    Content,
+    Created,
+}
+
+#[derive(Default)]
+struct EnexNote {
+    title: String,
+    content: String,
+    created: String,
+}
+
+/// The function `read_enex` reads the notes of an Evernote export. The title, the text and the
+/// creation time are kept, the tags, the attributes and the attachments of the notes are not.
+///
+/// Arguments:
+///
+/// * `name`: The `name` parameter is the file name, the notes are `<name>#<position>`.
+/// * `data`: The `data` parameter is the content of the file.
+///
+/// Returns:
+///
+/// the notes in order. The notes before a syntax error are kept, the error is one failed entry.
+pub fn read_enex(name: &str, data: &[u8]) -> Vec<Entry> {
+    let Ok(xml) = std::str::from_utf8(data) else {
+        return vec![Entry::Rejected(unreadable(name, "import.invalid_file"))];
+    };
+
+    let mut reader = Reader::from_str(xml);
+    let mut entries = Vec::new();
+    let mut note: Option<EnexNote> = None;
+    let mut field: Option<Field> = None;
+    //the position of the note that is read
+    let source = |entries: &Vec<Entry>| format!("{}#{}", name, entries.len() + 1);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(element)) => match element.local_name().as_ref() {
+                b"note" => note = Some(EnexNote::default()),
+                b"title" if note.is_some() => field = Some(Field::Title),
+                b"content" if note.is_some() => field = Some(Field::Content),
+                b"created" if note.is_some() => field = Some(Field::Created),
+                _ => {}
+            },
+            Ok(Event::End(element)) => match element.local_name().as_ref() {
+                b"note" => {
+                    if let Some(note) = note.take() {
+                        entries.push(convert(source(&entries), note));
+                    }
+                }
+                b"title" | b"content" | b"created" => field = None,
+                _ => {}
+            },
+            Ok(Event::Text(text)) => match (note.as_mut(), field, text.unescape()) {
+                (Some(note), Some(field), Ok(text)) => note.field(field).push_str(&text),
+                (Some(_), Some(_), Err(_)) => {
+                    entries.push(Entry::Rejected(unreadable(
+                        &source(&entries),
+                        "import.invalid_file",
+                    )));
+                    return entries;
+                }
+                _ => {}
+            },
+            Ok(Event::CData(text)) => {
+                if let (Some(note), Some(field)) = (note.as_mut(), field) {
+                    note.field(field).push_str(&String::from_utf8_lossy(&text));
+                }
+            }
+            Ok(Event::Eof) => return entries,
+            Err(_) => {
+                entries.push(Entry::Rejected(unreadable(
+                    &source(&entries),
+                    "import.invalid_file",
+                )));
+                return entries;
+            }
+            _ => {}
+        }
+    }
+}
+
+impl EnexNote {
+    fn field(&mut self, field: Field) -> &mut String {
+        match field {
+            Field::Title => &mut self.title,
+            Field::Content => &mut self.content,
+            Field::Created => &mut self.created,
+        }
+    }
+}
+
+fn convert(source: String, note: EnexNote) -> Entry {
+    let Some(text) = enml_text(&note.content) else {
+        return Entry::Rejected(failed(
+            &source,
+            Some(note.title),
+            errors::Error::from_code("import.invalid_file", errors::ErrorTypes::ValidationError),
+        ));
+    };
+    //`20230115T093000Z`
+    let created_at =
+        format_description::parse_borrowed::<1>("[year][month][day]T[hour][minute][second]Z")
+            .ok()
+            .and_then(|format| PrimitiveDateTime::parse(note.created.trim(), &format).ok())
+            .map(PrimitiveDateTime::assume_utc);
+
+    Entry::Note(ImportedNote {
+        source,
+        title: match note.title.trim().is_empty() {
+            true => first_line(&text),
+            false => note.title.trim().to_string(),
+        },
+        description: description(&text),
+        created_at,
+        important: false,
+    })
+}
+
+/// The function `enml_text` converts the content of an Evernote note (ENML, a kind of XHTML) to plain
+/// text. Blocks and line breaks become new lines, list items and checkboxes become Markdown lists.
+///
+/// Arguments:
+///
+/// * `content`: The `content` parameter is the ENML document.
+///
+/// Returns:
+///
+/// the text, or `None` if the content is not valid XML.
+pub fn enml_text(content: &str) -> Option<String> {
+    let mut reader = Reader::from_str(content);
+    let mut text = String::new();
+    loop {
+        match reader.read_event().ok()? {
+            Event::Start(element) => {
+                if is_block(&element) {
+                    new_line(&mut text);
+                }
+                if element.local_name().as_ref() == b"li" {
+                    text.push_str("- ");
+                }
+            }
+            Event::Empty(element) => match element.local_name().as_ref() {
+                b"en-todo" => {
+                    let checked = element
+                        .try_get_attribute("checked")
+                        .ok()
+                        .flatten()
+                        .is_some_and(|checked| checked.value.as_ref() == b"true");
+                    text.push_str(if checked { "[x] " } else { "[ ] " });
+                }
+                b"br" => text.push('\n'),
+                _ if is_block(&element) => new_line(&mut text),
+                _ => {}
+            },
+            Event::End(element) => {
+                if matches!(
+                    element.local_name().as_ref(),
+                    b"div" | b"p" | b"li" | b"h1" | b"h2" | b"h3" | b"h4" | b"h5" | b"h6" | b"tr"
+                ) {
+                    new_line(&mut text);
+                }
+            }
+            Event::Text(part) => {
+                let part = part.unescape().ok()?;
+                //the indentation between the tags
+                if !(part.trim().is_empty() && part.contains('\n')) {
+                    text.push_str(&part);
+                }
+            }
+            Event::CData(part) => text.push_str(&String::from_utf8_lossy(&part)),
+            Event::Eof => return Some(text),
+            _ => {}
+        }
+    }
+}
+
+fn is_block(element: &BytesStart) -> bool {
+    matches!(
+        element.local_name().as_ref(),
+        b"div"
+            | b"p"
+            | b"li"
+            | b"ul"
+            | b"ol"
+            | b"h1"
+            | b"h2"
+            | b"h3"
+            | b"h4"
+            | b"h5"
+            | b"h6"
+            | b"tr"
+            | b"hr"
+            | b"blockquote"
+            | b"pre"
+            | b"table"
+    )
+}
+
+//blocks start on a new line
+fn new_line(text: &mut String) {
+    if !text.is_empty() && !text.ends_with('\n') {
+        text.push('\n');
+    }
+}
@@ -0,0 +1,314 @@
+use std::io::{Cursor, Read};
+
+use serde::Deserialize;
+use serde_json::Value;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+use yaml_rust::{Yaml, YamlLoader};
+
+use super::{description, enex, first_line, skipped, unreadable, Entry, ImportedNote, MAX_UPLOAD};
+use crate::export::EXPORT_VERSION;
+
+/// The function `read_upload` reads the notes of an uploaded file. The format is picked by the
+/// extension of the file name:
+///
+/// * `.json`: the JSON export of the portal, or a note of a Google Keep Takeout.
+/// * `.md`, `.markdown`, `.txt`: a Markdown note, with the front matter of the Markdown export.
+/// * `.enex`: an Evernote export.
+/// * `.zip`: an archive with files of the formats above, for example the Markdown export or a Google
+/// Keep Takeout. The other files of the archive, like the attachments of Keep, are ignored.
+///
+/// Arguments:
+///
+/// * `name`: The `name` parameter is the file name of the upload.
+/// * `data`: The `data` parameter is the content of the file.
+///
+/// Returns:
+///
+/// the notes of the file in order. A file that can not be read is one failed entry.
+pub fn read_upload(name: &str, data: &[u8]) -> Vec<Entry> {
+    match extension(name).as_str() {
+        "zip" => read_zip(name, data),
+        _ if supported(name) => read_file(name, data),
+        _ => vec![Entry::Rejected(unreadable(
+            name,
+            "import.unsupported_format",
+        ))],
+    }
+}
+
+//the formats of single files, also inside an archive
+fn supported(name: &str) -> bool {
+    matches!(
+        extension(name).as_str(),
+        "json" | "md" | "markdown" | "txt" | "enex"
+    )
+}
+
+fn read_file(name: &str, data: &[u8]) -> Vec<Entry> {
+    match extension(name).as_str() {
+        "json" => read_json(name, data),
+        "enex" => enex::read_enex(name, data),
+        _ => vec![read_markdown(name, data)],
+    }
+}
+
+fn extension(name: &str) -> String {
+    name.rsplit_once('.')
+        .map(|(_, extension)| extension.to_lowercase())
+        .unwrap_or_default()
+}
+
+/// The function `read_zip` reads the supported files of a zip archive. The files are read in the
+/// order of the archive and the total size of the extracted files is limited to `MAX_UPLOAD`, so a
+/// small archive can not fill the memory.
+///
+/// Arguments:
+///
+/// * `name`: The `name` parameter is the file name of the archive.
+/// * `data`: The `data` parameter is the content of the archive.
+pub fn read_zip(name: &str, data: &[u8]) -> Vec<Entry> {
+    let Ok(mut archive) = zip::ZipArchive::new(Cursor::new(data)) else {
+        return vec![Entry::Rejected(unreadable(name, "import.invalid_file"))];
+    };
+
+    let mut entries = Vec::new();
+    let mut remaining = MAX_UPLOAD as u64;
+    for index in 0..archive.len() {
+        let file = match archive.by_index(index) {
+            Ok(file) => file,
+            Err(_) => {
+                entries.push(Entry::Rejected(unreadable(name, "import.invalid_file")));
+                continue;
+            }
+        };
+        let source = format!("{}/{}", name, file.name());
+        if file.is_dir() || !supported(&source) {
+            continue;
+        }
+
+        let mut content = Vec::new();
+        let read = file.take(remaining + 1).read_to_end(&mut content);
+        match read {
+            Ok(size) if size as u64 > remaining => {
+                entries.push(Entry::Rejected(unreadable(&source, "import.too_large")));
+                return entries;
+            }
+            Ok(size) => {
+                remaining -= size as u64;
+                entries.extend(read_file(&source, &content));
+            }
+            Err(_) => entries.push(Entry::Rejected(unreadable(&source, "import.invalid_file"))),
+        }
+    }
+    entries
+}
+
+/// The `PortalNote` struct is a note of the JSON export, see `export::NotesExport`. The id is not
+/// read, imported notes get a new one.
+#[derive(Deserialize)]
+struct PortalNote {
+    title: String,
+    description: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    created_at: OffsetDateTime,
+    #[serde(default)]
+    important: bool,
+}
+
+/// The `KeepNote` struct is a note of a Google Keep Takeout, one JSON file per note.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KeepNote {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    text_content: String,
+    #[serde(default)]
+    list_content: Vec<KeepListItem>,
+    #[serde(default)]
+    is_trashed: bool,
+    #[serde(default)]
+    is_pinned: bool,
+    created_timestamp_usec: Option<i64>,
+    user_edited_timestamp_usec: Option<i64>,
+}
+
+/// The `KeepListItem` struct is an item of a Google Keep checklist.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KeepListItem {
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    is_checked: bool,
+}
+
+/// The function `read_json` reads the JSON export of the portal or a Google Keep note. The notes of
+/// the export are read one by one, so a broken note does not fail the others.
+///
+/// Arguments:
+///
+/// * `name`: The `name` parameter is the file name.
+/// * `data`: The `data` parameter is the content of the file.
+pub fn read_json(name: &str, data: &[u8]) -> Vec<Entry> {
+    let Ok(document) = serde_json::from_slice::<Value>(data) else {
+        return vec![Entry::Rejected(unreadable(name, "import.invalid_file"))];
+    };
+
+    if let Some(notes) = document.get("notes").and_then(Value::as_array) {
+        let version = document.get("version").and_then(Value::as_u64);
+        if version.is_none_or(|version| version > u64::from(EXPORT_VERSION)) {
+            return vec![Entry::Rejected(unreadable(
+                name,
+                "import.unsupported_version",
+            ))];
+        }
+        return notes
+            .iter()
+            .enumerate()
+            .map(|(index, note)| {
+                let source = format!("{}#{}", name, index + 1);
+                match PortalNote::deserialize(note) {
+                    Ok(note) => Entry::Note(ImportedNote {
+                        source,
+                        title: note.title,
+                        description: note.description,
+                        created_at: Some(note.created_at),
+                        important: note.important,
+                    }),
+                    Err(_) => Entry::Rejected(unreadable(&source, "import.invalid_file")),
+                }
+            })
+            .collect();
+    }
+
+    let is_keep = ["textContent", "listContent", "userEditedTimestampUsec"]
+        .iter()
+        .any(|key| document.get(key).is_some());
+    if !is_keep {
+        return vec![Entry::Rejected(unreadable(
+            name,
+            "import.unsupported_format",
+        ))];
+    }
+    vec![read_keep(name, document)]
+}
+
+fn read_keep(name: &str, document: Value) -> Entry {
+    let Ok(note) = KeepNote::deserialize(document) else {
+        return Entry::Rejected(unreadable(name, "import.invalid_file"));
+    };
+    if note.is_trashed {
+        return Entry::Rejected(skipped(name, Some(note.title), "import.trashed"));
+    }
+
+    //checklists become Markdown task lists
+    let list = note
+        .list_content
+        .iter()
+        .map(|item| match item.is_checked {
+            true => format!("- [x] {}", item.text),
+            false => format!("- [ ] {}", item.text),
+        })
+        .collect::<Vec<_>>();
+    let text = match list.is_empty() {
+        true => note.text_content,
+        false => [note.text_content, list.join("\n")].join("\n"),
+    };
+    let created_at = note
+        .created_timestamp_usec
+        .or(note.user_edited_timestamp_usec)
+        .and_then(|usec| OffsetDateTime::from_unix_timestamp_nanos(i128::from(usec) * 1000).ok());
+
+    Entry::Note(ImportedNote {
+        source: name.to_string(),
+        title: match note.title.trim().is_empty() {
+            true => first_line(&text),
+            false => note.title,
+        },
+        description: description(&text),
+        created_at,
+        important: note.is_pinned,
+    })
+}
+
+/// The function `read_markdown` reads a Markdown note. The front matter of the Markdown export is
+/// optional: without a `title` the first `# ` heading is the title, or the file name if there is
+/// none.
+///
+/// Arguments:
+///
+/// * `name`: The `name` parameter is the file name, or the path in the archive.
+/// * `data`: The `data` parameter is the content of the file.
+pub fn read_markdown(name: &str, data: &[u8]) -> Entry {
+    match markdown_note(name, data) {
+        Some(note) => Entry::Note(note),
+        None => Entry::Rejected(unreadable(name, "import.invalid_file")),
+    }
+}
+
+//`None` if the file is not UTF-8 or the front matter is not valid YAML
+fn markdown_note(name: &str, data: &[u8]) -> Option<ImportedNote> {
+    let text = std::str::from_utf8(data).ok()?;
+    let text = text.replace("\r\n", "\n");
+    let (front_matter, body) = split_front_matter(&text);
+
+    let mut note = ImportedNote {
+        source: name.to_string(),
+        ..ImportedNote::default()
+    };
+    if let Some(front_matter) = front_matter {
+        let documents = YamlLoader::load_from_str(front_matter).ok()?;
+        let document = documents.first().unwrap_or(&Yaml::Null);
+        note.title = document["title"].as_str().unwrap_or_default().to_string();
+        note.important = document["important"].as_bool().unwrap_or_default();
+        note.created_at = document["created_at"]
+            .as_str()
+            .and_then(|created_at| OffsetDateTime::parse(created_at, &Rfc3339).ok());
+    }
+
+    let mut body = body;
+    if note.title.trim().is_empty() {
+        match take_heading(body) {
+            Some((heading, rest)) => {
+                note.title = heading;
+                body = rest;
+            }
+            None => note.title = file_stem(name),
+        }
+    }
+    note.description = description(body);
+    Some(note)
+}
+
+//the front matter is between two `---` lines at the start of the file
+fn split_front_matter(text: &str) -> (Option<&str>, &str) {
+    let Some(rest) = text.strip_prefix("---\n") else {
+        return (None, text);
+    };
+    match rest.split_once("\n---\n") {
+        Some((front_matter, body)) => (Some(front_matter), body),
+        None => (None, text),
+    }
+}
+
+//the text of a `# ` heading on the first line that is not empty, and the text after it
+fn take_heading(body: &str) -> Option<(String, &str)> {
+    let mut start = 0;
+    for line in body.split_inclusive('\n') {
+        if line.trim().is_empty() {
+            start += line.len();
+            continue;
+        }
+        let heading = line.strip_prefix("# ")?;
+        return Some((heading.trim().to_string(), &body[start + line.len()..]));
+    }
+    None
+}
+
+fn file_stem(name: &str) -> String {
+    let file = name.rsplit('/').next().unwrap_or(name);
+    file.rsplit_once('.')
+        .map_or(file, |(stem, _)| stem)
+        .to_string()
+}
@@ -0,0 +1,85 @@
+use actix_multipart::form::MultipartForm;
+use actix_web::{post, web, HttpRequest, HttpResponse};
+use common::{PostEventKind, WorkspaceRole};
+use tracing::instrument;
+
+use super::{existing_notes, formats, plan, ImportForm, ImportQuery};
+use crate::audit::{Audit, AuditAction};
+use crate::errors::{self, request_locale};
+use crate::events;
+use crate::workspaces::workspace_access;
+use crate::{app::AppState, auth::JwtMiddleware};
+
+#[post("/workspaces/{workspace_id}/import")]
+#[instrument(skip(state, auth, audit, req, form), name = "Import notes")]
+async fn import_notes(
+    state: web::Data<AppState>,
+    auth: JwtMiddleware,
+    audit: Audit,
+    req: HttpRequest,
+    path: web::Path<uuid::Uuid>,
+    query: web::Query<ImportQuery>,
+    form: Result<MultipartForm<ImportForm>, actix_web::Error>,
+) -> Result<HttpResponse, errors::Error> {
+    let workspace_id = path.into_inner();
+    workspace_access(&state, workspace_id, auth.user_id, WorkspaceRole::Member).await?;
+    let MultipartForm(form) = form.map_err(errors::multipart_error)?;
+    if form.files.is_empty() {
+        return Err(errors::Error::validation(vec![errors::FieldError::new(
+            "file",
+            "import.no_files",
+        )]));
+    }
+
+    //unzipping and parsing do not block the workers
+    let entries = web::block(move || {
+        form.files
+            .iter()
+            .flat_map(|file| {
+                let name = file.file_name.as_deref().unwrap_or("upload");
+                formats::read_upload(name, &file.data)
+            })
+            .collect::<Vec<_>>()
+    })
+    .await?;
+
+    let existing = existing_notes(state.notes.as_ref(), auth.user_id, workspace_id).await?;
+    let (mut report, posts) = plan(entries, existing, auth.user_id, workspace_id, query.dry_run);
+    if !query.dry_run {
+        let imported: Vec<uuid::Uuid> = posts.iter().map(|(post, _)| post.id).collect();
+        if !posts.is_empty() {
+            state.notes.add_posts(posts).await?;
+        }
+        //the notes are added in one transaction, the lists of the others show them after it
+        for post_id in imported {
+            events::stored_post_changed(&state, PostEventKind::Created, post_id).await;
+        }
+        audit
+            .record(
+                AuditAction::PostImport,
+                Some(auth.user_id),
+                None,
+                None,
+                true,
+            )
+            .await;
+    }
+    tracing::info!(
+        "Import of user {} into workspace {}: {} imported, {} skipped, {} failed",
+        auth.user_id,
+        workspace_id,
+        report.imported,
+        report.skipped,
+        report.failed
+    );
+
+    let locale = request_locale(req.headers());
+    for error in report
+        .items
+        .iter_mut()
+        .filter_map(|item| item.error.as_mut())
+    {
+        error.localize(locale);
+    }
+    Ok(HttpResponse::Ok().json(report))
+}
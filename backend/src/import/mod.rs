@@ -0,0 +1,237 @@
+pub mod enex;
+pub mod formats;
+pub mod handlers;
+
+use std::collections::HashSet;
+
+use actix_multipart::form::{bytes::Bytes, MultipartForm};
+use actix_web::web;
+use common::{ImportItem, ImportReport, ImportStatus};
+use serde::Deserialize;
+use time::OffsetDateTime;
+
+use crate::db::{NotesRepository, Post};
+use crate::errors;
+use crate::schema::post::NewPost;
+
+/// The largest upload of `POST /import`, all files together.
+pub const MAX_UPLOAD: usize = 20 * 1024 * 1024;
+//the number of existing notes read at once to find the duplicates
+const PAGE_SIZE: i64 = 500;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(handlers::import_notes);
+}
+
+/// The `ImportQuery` struct is the query string of `POST /import`.
+///
+/// Properties:
+///
+/// * `dry_run`: The `dry_run` property is `true` to only check the upload. The report is the same, but
+/// nothing is written.
+#[derive(Deserialize, Debug)]
+pub struct ImportQuery {
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// The `ImportForm` struct is the multipart body of `POST /import`: one or more `file` fields, see
+/// `formats::read_upload` for the supported files.
+#[derive(MultipartForm, Debug)]
+pub struct ImportForm {
+    #[multipart(rename = "file")]
+    pub files: Vec<Bytes>,
+}
+
+/// The `ImportedNote` struct is a note read from an upload, before it is validated.
+///
+/// Properties:
+///
+/// * `source`: The `source` property is where the note was found, see `common::ImportItem`.
+/// * `title`: The `title` property is the title of the note.
+/// * `description`: The `description` property is the text of the note.
+/// * `created_at`: The `created_at` property is the creation time of the original note, if the
+/// format has one. Notes without it are created at the time of the import.
+/// * `important`: The `important` property is `true` for notes that were important or pinned.
+#[derive(Debug, Default, PartialEq)]
+pub struct ImportedNote {
+    pub source: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub created_at: Option<OffsetDateTime>,
+    pub important: bool,
+}
+
+/// The `Entry` enum is a note of an upload: either the note that was read, or the report item of a
+/// note that can not be imported.
+#[derive(Debug)]
+pub enum Entry {
+    Note(ImportedNote),
+    Rejected(ImportItem),
+}
+
+/// The function `skipped` returns the report item of a note that is left out on purpose.
+///
+/// Arguments:
+///
+/// * `source`: The `source` parameter is where the note was found.
+/// * `title`: The `title` parameter is the title of the note, if it is known.
+/// * `code`: The `code` parameter is the reason, for example `import.duplicate`.
+pub fn skipped(source: &str, title: Option<String>, code: &str) -> ImportItem {
+    ImportItem {
+        source: source.to_string(),
+        title,
+        status: ImportStatus::Skipped,
+        error: Some(errors::Error::from_code(
+            code,
+            errors::ErrorTypes::ValidationError,
+        )),
+    }
+}
+
+/// The function `failed` returns the report item of a note that could not be imported.
+///
+/// Arguments:
+///
+/// * `source`: The `source` parameter is where the note was found.
+/// * `title`: The `title` parameter is the title of the note, if it is known.
+/// * `error`: The `error` parameter is the reason.
+pub fn failed(source: &str, title: Option<String>, error: errors::Error) -> ImportItem {
+    ImportItem {
+        source: source.to_string(),
+        title,
+        status: ImportStatus::Failed,
+        error: Some(error),
+    }
+}
+
+/// The function `unreadable` returns the failed report item of a file or a note that can not be read.
+///
+/// Arguments:
+///
+/// * `source`: The `source` parameter is the file or the note.
+/// * `code`: The `code` parameter is the reason, for example `import.invalid_file`.
+pub fn unreadable(source: &str, code: &str) -> ImportItem {
+    failed(
+        source,
+        None,
+        errors::Error::from_code(code, errors::ErrorTypes::ValidationError),
+    )
+}
+
+//notes with the same title and text are duplicates
+type NoteKey = (String, Option<String>);
+
+/// The function `existing_notes` returns the title and text of every note of the workspace, to skip
+/// the notes that are imported again.
+///
+/// Arguments:
+///
+/// * `notes`: The `notes` parameter is the storage of the notes.
+/// * `user_id`: The `user_id` parameter is the user who imports the notes.
+/// * `workspace_id`: The `workspace_id` parameter is the workspace the notes are imported into.
+pub async fn existing_notes(
+    notes: &dyn NotesRepository,
+    user_id: uuid::Uuid,
+    workspace_id: uuid::Uuid,
+) -> Result<HashSet<NoteKey>, errors::Error> {
+    let mut keys = HashSet::new();
+    let mut offset = 0;
+    loop {
+        let page = notes
+            .get_user_posts(user_id, workspace_id, offset, PAGE_SIZE)
+            .await?;
+        let last = (page.len() as i64) < PAGE_SIZE;
+        keys.extend(page.into_iter().map(|note| (note.title, note.description)));
+        if last {
+            return Ok(keys);
+        }
+        offset += PAGE_SIZE;
+    }
+}
+
+/// The function `plan` validates the notes of an upload with `NewPost::parse` and decides what
+/// happens to each of them. A note is skipped when the workspace already has a note with the same
+/// title and text, or when it was already in the upload.
+///
+/// Arguments:
+///
+/// * `entries`: The `entries` parameter lists the notes of the upload, see `formats::read_upload`.
+/// * `existing`: The `existing` parameter holds the notes of the workspace, see `existing_notes`.
+/// * `user_id`: The `user_id` parameter is the user who imports the notes.
+/// * `workspace_id`: The `workspace_id` parameter is the workspace the notes are imported into.
+/// * `dry_run`: The `dry_run` parameter is copied to the report.
+///
+/// Returns:
+///
+/// the `ImportReport` and the posts to add, paired with their important flag.
+pub fn plan(
+    entries: Vec<Entry>,
+    mut existing: HashSet<NoteKey>,
+    user_id: uuid::Uuid,
+    workspace_id: uuid::Uuid,
+    dry_run: bool,
+) -> (ImportReport, Vec<(Post, bool)>) {
+    let now = OffsetDateTime::now_utc();
+    let mut posts = Vec::new();
+    let items: Vec<ImportItem> = entries
+        .into_iter()
+        .map(|entry| {
+            let note = match entry {
+                Entry::Note(note) => note,
+                Entry::Rejected(item) => return item,
+            };
+            let title = Some(note.title.clone());
+            let new_post =
+                match NewPost::parse(&note.title, &note.description, user_id, workspace_id) {
+                    Ok(new_post) => new_post,
+                    Err(e) => return failed(&note.source, title, e),
+                };
+            if !existing.insert((note.title, note.description)) {
+                return skipped(&note.source, title, "import.duplicate");
+            }
+            posts.push((
+                new_post.build_at(note.created_at.unwrap_or(now)),
+                note.important,
+            ));
+            ImportItem {
+                source: note.source,
+                title,
+                status: ImportStatus::Imported,
+                error: None,
+            }
+        })
+        .collect();
+
+    let count = |status| items.iter().filter(|item| item.status == status).count();
+    let report = ImportReport {
+        dry_run,
+        imported: count(ImportStatus::Imported),
+        skipped: count(ImportStatus::Skipped),
+        failed: count(ImportStatus::Failed),
+        items,
+    };
+    (report, posts)
+}
+
+/// The function `first_line` returns the first line of a text that is not empty, at most 100
+/// characters long. It is the title of notes that have none.
+pub fn first_line(text: &str) -> String {
+    text.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default()
+        .chars()
+        .take(100)
+        .collect()
+}
+
+/// The function `description` returns the text of a note without the blank lines around it, or
+/// `None` if it is empty.
+pub fn description(text: &str) -> Option<String> {
+    let text = text.trim_matches(['\n', '\r']).trim_end();
+    match text.trim().is_empty() {
+        true => None,
+        false => Some(text.to_string()),
+    }
+}
@@ -0,0 +1,21 @@
+pub mod admin;
+pub mod app;
+pub mod attachments;
+pub mod audit;
+pub mod auth;
+pub mod cli;
+pub mod collab;
+pub mod db;
+pub mod errors;
+pub mod events;
+pub mod export;
+pub mod health;
+pub mod import;
+pub mod logging;
+pub mod notebooks;
+pub mod permissions;
+pub mod schema;
+pub mod share;
+pub mod tasks;
+pub mod telemetry;
+pub mod workspaces;
@@ -1,35 +1,166 @@
+use actix_web::{
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    http::header::{HeaderName, HeaderValue},
+    middleware::Next,
+    HttpMessage,
+};
+use opentelemetry_sdk::trace::SdkTracer;
+use rolling_file::{BasicRollingFileAppender, RollingConditionBasic};
 use tracing::{subscriber::set_global_default, Subscriber};
+use tracing_appender::non_blocking::WorkerGuard;
 use tracing_log::LogTracer;
 use tracing_subscriber::{
-    fmt::{format::FmtSpan, MakeWriter},
-    EnvFilter, FmtSubscriber,
+    fmt::{self, format::FmtSpan, writer::BoxMakeWriter},
+    layer::SubscriberExt,
+    registry::LookupSpan,
+    reload, EnvFilter, Layer, Registry,
 };
+
+use crate::app::{LogFormat, LogRotation, LoggingSettings};
+
+/// Handle to the log filter of the global subscriber. It is used to change the log levels at runtime.
+pub type FilterHandle = reload::Handle<EnvFilter, Registry>;
+
+/// Name of the header that carries the request id.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// The `LogWriters` struct holds the sinks of the logs, see `get_log_writers`.
+///
+/// Properties:
+///
+/// * `stdout`: The `stdout` property is the standard output, `None` if the logs only go to the file.
+/// * `file`: The `file` property is the rolling log file, `None` if it is not enabled.
+pub struct LogWriters {
+    pub stdout: Option<BoxMakeWriter>,
+    pub file: Option<BoxMakeWriter>,
+}
+
 /// The function `get_tracing_subscriber` returns a tracing subscriber with a specified environment
-/// filter and sink.
+/// filter and sinks.
 ///
 /// Arguments:
 ///
 /// * `env_filter`: The `env_filter` parameter is a string that specifies the log level filter. It
 /// determines which log messages will be included based on their log level. For example, if the
 /// `env_filter` is set to "info", only log messages with a log level of "info" or higher will be
-/// * `sink`: The `sink` parameter is a generic type that represents a writer where log events will be
-/// written to. It must implement the `MakeWriter` trait, which allows creating a writer for log events.
-/// The `MakeWriter` trait is defined as follows:
+/// * `format`: The `format` parameter is of type `LogFormat`. It selects the pretty or the JSON
+/// formatter.
+/// * `writers`: The `writers` parameter is of type `LogWriters`. Every sink gets its own formatter, the
+/// colors of the pretty format are only written to the standard output.
+/// * `tracer`: The `tracer` parameter is an optional OpenTelemetry tracer. When it is `Some`, every span
+/// is also exported through it.
+///
+/// Returns:
+///
+/// the subscriber and a `FilterHandle` that can replace its filter later.
 
-pub fn get_tracing_subscriber<Sink>(env_filter: &str, sink: Sink) -> impl Subscriber + Sync + Send
-where
-    Sink: for<'a> MakeWriter<'a> + Send + Sync + 'static,
-{
+pub fn get_tracing_subscriber(
+    env_filter: &str,
+    format: LogFormat,
+    writers: LogWriters,
+    tracer: Option<SdkTracer>,
+) -> (impl Subscriber + Sync + Send, FilterHandle) {
     //parse env variable, use info by default
     let env_filter =
         EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(env_filter));
+    let (env_filter, filter_handle) = reload::Layer::new(env_filter);
+
+    //the escape codes of the colors would end up in the log files
+    let fmt_layers: Vec<_> = [
+        writers.stdout.map(|writer| fmt_layer(format, writer, true)),
+        writers.file.map(|writer| fmt_layer(format, writer, false)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    //export spans to the OpenTelemetry collector if it is configured
+    let otel_layer = tracer.map(|tracer| tracing_opentelemetry::layer().with_tracer(tracer));
+
+    let subscriber = Registry::default()
+        .with(env_filter)
+        .with(fmt_layers)
+        .with(otel_layer);
+
+    (subscriber, filter_handle)
+}
+
+//the formatter of one sink
+fn fmt_layer<S>(
+    format: LogFormat,
+    writer: BoxMakeWriter,
+    ansi: bool,
+) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    match format {
+        //pretty log data format
+        LogFormat::Pretty => fmt::layer()
+            .with_ansi(ansi)
+            .with_writer(writer)
+            .with_span_events(FmtSpan::NEW | FmtSpan::CLOSE)
+            .boxed(),
+        //one JSON object per line, every event carries the fields of all its parent spans
+        LogFormat::Json => fmt::layer()
+            .json()
+            .with_current_span(true)
+            .with_span_list(true)
+            .with_writer(writer)
+            .with_span_events(FmtSpan::NEW | FmtSpan::CLOSE)
+            .boxed(),
+    }
+}
+
+/// The function `get_log_writers` builds the log sinks from the `logging` settings: the standard
+/// output, a rolling log file or both of them.
+///
+/// Arguments:
+///
+/// * `settings`: The `settings` parameter is of type `&LoggingSettings`.
+///
+/// Returns:
+///
+/// a `Result` with the writers and an optional `WorkerGuard`. The guard flushes the file writer when it
+/// is dropped, so it has to live until the end of `main`.
+pub fn get_log_writers(
+    settings: &LoggingSettings,
+) -> Result<(LogWriters, Option<WorkerGuard>), String> {
+    if !settings.file.enabled {
+        let writers = LogWriters {
+            stdout: Some(BoxMakeWriter::new(std::io::stdout)),
+            file: None,
+        };
+        return Ok((writers, None));
+    }
+
+    let file = &settings.file;
+    std::fs::create_dir_all(&file.directory)
+        .map_err(|e| format!("Failed to create log directory {}: {}", file.directory, e))?;
+
+    let mut condition = match file.rotation {
+        LogRotation::Daily => RollingConditionBasic::new().daily(),
+        LogRotation::Hourly => RollingConditionBasic::new().hourly(),
+        LogRotation::Never => RollingConditionBasic::new(),
+    };
+    if file.max_size_mb > 0 {
+        condition = condition.max_size(file.max_size_mb * 1024 * 1024);
+    }
+
+    let path = std::path::Path::new(&file.directory).join(&file.file_name);
+    let appender = BasicRollingFileAppender::new(path, condition, file.max_files)
+        .map_err(|e| format!("Failed to open log file: {}", e))?;
 
-    //pretty log data format
-    FmtSubscriber::builder()
-        .with_env_filter(env_filter)
-        .with_writer(sink)
-        .with_span_events(FmtSpan::NEW | FmtSpan::CLOSE)
-        .finish()
+    //write to the file from a background thread
+    let (file_writer, guard) = tracing_appender::non_blocking(appender);
+
+    let writers = LogWriters {
+        stdout: settings.stdout.then(|| BoxMakeWriter::new(std::io::stdout)),
+        file: Some(BoxMakeWriter::new(file_writer)),
+    };
+
+    Ok((writers, Some(guard)))
 }
 /// The function initializes a tracing subscriber for logging in Rust.
 ///
@@ -49,3 +180,46 @@ pub fn init_tracing_subscriber(subscriber: impl Subscriber + Sync + Send) -> Res
         .map_err(|_e| String::from("Logger has already been initialized"))?;
     Ok(())
 }
+
+/// The `RequestId` struct holds the id of the current request. It is stored in the request extensions
+/// by the `request_id` middleware.
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+/// The function `is_valid_request_id` checks that a request id taken from a client header is short and
+/// only contains safe characters, so it can be written to the logs as is.
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= 128
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// The middleware `request_id` takes the `X-Request-Id` header from the request or generates a new id.
+/// The id is stored in the request extensions, written back to the request headers for the access log
+/// and returned in the response headers.
+pub async fn request_id(
+    mut req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let header_name = HeaderName::from_static(REQUEST_ID_HEADER);
+
+    let id = req
+        .headers()
+        .get(&header_name)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| is_valid_request_id(value))
+        .map(str::to_owned)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    let header_value = HeaderValue::from_str(&id).expect("Request id is a valid header value");
+    req.headers_mut()
+        .insert(header_name.clone(), header_value.clone());
+    req.extensions_mut().insert(RequestId(id));
+
+    let mut response = next.call(req).await?;
+    response.headers_mut().insert(header_name, header_value);
+
+    Ok(response)
+}
@@ -1,29 +1,85 @@
-mod app;
-mod auth;
-mod db;
-mod errors;
-mod logging;
-mod schema;
+use std::time::Duration;
 
 use actix_cors::Cors;
-use actix_web::{http::header, middleware::Logger, web, App, HttpServer};
+use actix_multipart::form::MultipartFormConfig;
+use actix_web::{
+    http::header,
+    middleware::{from_fn, Condition, Logger},
+    web, App, HttpServer,
+};
+use backend::{
+    admin, app, attachments, auth, cli, collab, errors, events, export, health, import, logging,
+    notebooks, permissions, share, tasks, telemetry, workspaces,
+};
+use clap::Parser;
+use tokio::{sync::oneshot, time::Instant};
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     dotenv::dotenv().ok();
-    let subscriber = logging::get_tracing_subscriber("debug", std::io::stdout);
+    let cli = cli::Cli::parse();
+    let config = app::Settings::get_configuration().unwrap();
+
+    //subcommands run without the server and exit
+    if let Some(command) = cli.command {
+        if let Err(e) = cli::run(command, &config, cli.json).await {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    //optional OpenTelemetry export
+    let tracer_provider = if config.telemetry.enabled {
+        telemetry::get_tracer_provider(&config.telemetry)
+            .map_err(|e| eprintln!("{}", e))
+            .ok()
+    } else {
+        None
+    };
+    let tracer = tracer_provider.as_ref().map(telemetry::get_tracer);
+
+    let (log_writers, _log_guard) = logging::get_log_writers(&config.logging)
+        .unwrap_or_else(|e| panic!("Failed to set up log output: {}", e));
+    let (subscriber, log_filter) = logging::get_tracing_subscriber(
+        &config.logging.level,
+        config.logging.format,
+        log_writers,
+        tracer,
+    );
     logging::init_tracing_subscriber(subscriber).unwrap_or_else(|e| tracing::error!(e));
+    telemetry::init_query_tracing();
 
     //database setup
-    let config = app::Settings::get_configuration().unwrap();
-    let app_state = config
+    let mut app_state = config
         .create_app_state()
         .expect("Failed to establish database connection");
+    app_state.log_filter = Some(log_filter);
+
+    if config.database.run_migrations_on_startup {
+        app_state
+            .schema
+            .run_pending_migrations()
+            .await
+            .expect("Failed to run database migrations");
+    }
 
     let app_state = web::Data::new(app_state);
+    let tasks = app_state.tasks.clone();
+    app_state.audit.spawn_retention(&tasks);
+    app_state.events.spawn_listener(&tasks, app_state.clone());
+    let shutdown_timeout = Duration::from_secs(config.server.shutdown_timeout);
+    let drain_delay = Duration::from_secs(config.server.drain_delay);
+    let expose_error_cause = config.server.expose_error_cause;
+    //the import and the attachments share the multipart limits
+    let upload_limit = import::MAX_UPLOAD.max(config.attachments.max_file_size());
 
-    tracing::info!("Server started on 127.0.0.1:8000");
-    HttpServer::new(move || {
+    tracing::info!(
+        "Server started on {}:{}",
+        config.server.host,
+        config.server.port
+    );
+    let server = HttpServer::new(move || {
         let cors = Cors::default()
             .allowed_origin("http://127.0.0.1:8080")
             .supports_credentials()
@@ -32,15 +88,81 @@ async fn main() -> std::io::Result<()> {
                 header::CONTENT_TYPE,
                 header::AUTHORIZATION,
                 header::ACCEPT,
-            ]);
+                header::ACCEPT_LANGUAGE,
+                header::HeaderName::from_static(logging::REQUEST_ID_HEADER),
+                header::HeaderName::from_static("traceparent"),
+                header::HeaderName::from_static(share::SHARE_PASSWORD_HEADER),
+            ])
+            .expose_headers(vec![header::HeaderName::from_static(
+                logging::REQUEST_ID_HEADER,
+            )]);
         App::new()
             .app_data(app_state.clone())
+            .app_data(web::JsonConfig::default().error_handler(errors::json_error))
+            .app_data(web::QueryConfig::default().error_handler(errors::query_error))
+            .app_data(
+                MultipartFormConfig::default()
+                    .total_limit(upload_limit)
+                    .memory_limit(upload_limit),
+            )
+            .wrap(Condition::new(
+                !expose_error_cause,
+                from_fn(errors::hide_error_cause),
+            ))
+            .wrap(from_fn(errors::localize_errors))
             .wrap(cors)
-            .wrap(Logger::default())
+            .wrap(Logger::new(
+                r#"%a "%r" %s %b "%{Referer}i" "%{User-Agent}i" %T request_id=%{x-request-id}i"#,
+            ))
+            .wrap(from_fn(telemetry::propagate_trace_context))
+            .wrap(from_fn(logging::request_id))
             .configure(auth::config)
             .configure(app::config)
+            .configure(export::config)
+            .configure(import::config)
+            .configure(attachments::config)
+            .configure(share::config)
+            .configure(permissions::config)
+            .configure(workspaces::config)
+            .configure(notebooks::config)
+            .configure(events::config)
+            .configure(collab::config)
+            .configure(admin::config)
+            .configure(health::config)
     })
-    .bind(("127.0.0.1", 8000))?
-    .run()
-    .await
+    .bind((config.server.host.as_str(), config.server.port))?
+    .shutdown_timeout(config.server.shutdown_timeout)
+    //signals are handled below, so the readiness check can fail before the listener closes
+    .disable_signals()
+    .run();
+
+    let handle = server.handle();
+    let drain = tasks.clone();
+    let (deadline_sender, mut deadline) = oneshot::channel();
+    tasks.spawn("shutdown signal", async move {
+        tasks::wait_for_shutdown_signal().await;
+        tracing::info!("Draining for {:?} before shutdown", drain_delay);
+        drain.begin_drain();
+        tokio::time::sleep(drain_delay).await;
+        //the in-flight requests and the background tasks share one shutdown timeout
+        let _ = deadline_sender.send(Instant::now() + shutdown_timeout);
+        //stop accepting connections and wait for the in-flight requests
+        handle.stop(true).await;
+    });
+
+    server.await?;
+    tracing::info!("Server stopped, cancelling background tasks");
+    let deadline = deadline
+        .try_recv()
+        .unwrap_or_else(|_| Instant::now() + shutdown_timeout);
+    tasks.shutdown(deadline).await;
+
+    //flush spans that are still waiting in the batch processor
+    if let Some(provider) = tracer_provider {
+        provider
+            .shutdown()
+            .unwrap_or_else(|e| tracing::error!("Failed to shut down tracer provider: {}", e));
+    }
+
+    Ok(())
 }
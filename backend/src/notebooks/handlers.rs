@@ -0,0 +1,203 @@
+use actix_web::{delete, get, patch, post, web, HttpResponse};
+use common::{
+    ApiResponse, NotebookForm, NotebookMoveForm, Permission, PostEventKind, ResponseNotebook,
+    WorkspaceRole,
+};
+use tracing::instrument;
+
+use super::{check_parent, notebook_tree, workspace_notebook};
+use crate::audit::{Audit, AuditAction};
+use crate::db::{notebook_not_found, notebook_subtree, post_not_found, Notebook};
+use crate::errors;
+use crate::events;
+use crate::schema::notebook::NotebookName;
+use crate::workspaces::workspace_access;
+use crate::{app::AppState, auth::JwtMiddleware};
+
+#[get("/workspaces/{workspace_id}/notebooks")]
+#[instrument(skip(state, auth), name = "Get notebooks")]
+async fn get_notebooks(
+    state: web::Data<AppState>,
+    auth: JwtMiddleware,
+    path: web::Path<uuid::Uuid>,
+) -> Result<HttpResponse, errors::Error> {
+    let workspace_id = path.into_inner();
+    workspace_access(&state, workspace_id, auth.user_id, WorkspaceRole::Member).await?;
+    let notebooks = state.notebooks.get_notebooks(workspace_id).await?;
+
+    Ok(HttpResponse::Ok().json(notebook_tree(notebooks)))
+}
+
+#[post("/workspaces/{workspace_id}/notebooks")]
+#[instrument(skip(state, auth, audit), name = "Create notebook")]
+async fn add_notebook(
+    state: web::Data<AppState>,
+    auth: JwtMiddleware,
+    audit: Audit,
+    path: web::Path<uuid::Uuid>,
+    body: web::Json<NotebookForm>,
+) -> Result<HttpResponse, errors::Error> {
+    let workspace_id = path.into_inner();
+    workspace_access(&state, workspace_id, auth.user_id, WorkspaceRole::Member).await?;
+    let NotebookForm { name, parent_id } = body.into_inner();
+    let name = NotebookName::parse(&name)?;
+    if let Some(parent_id) = parent_id {
+        workspace_notebook(&state, workspace_id, parent_id).await?;
+    }
+
+    let notebook = state
+        .notebooks
+        .add_notebook(name.build(workspace_id, parent_id))
+        .await?;
+    audit
+        .record(
+            AuditAction::NotebookCreate,
+            Some(auth.user_id),
+            None,
+            Some(notebook.id),
+            true,
+        )
+        .await;
+
+    let response: ResponseNotebook = notebook.response(0, 0);
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[patch("/workspaces/{workspace_id}/notebooks/{id}")]
+#[instrument(skip(state, auth, audit), name = "Update notebook")]
+async fn update_notebook(
+    state: web::Data<AppState>,
+    auth: JwtMiddleware,
+    audit: Audit,
+    path: web::Path<(uuid::Uuid, uuid::Uuid)>,
+    body: web::Json<NotebookForm>,
+) -> Result<HttpResponse, errors::Error> {
+    let (workspace_id, id) = path.into_inner();
+    workspace_access(&state, workspace_id, auth.user_id, WorkspaceRole::Member).await?;
+    workspace_notebook(&state, workspace_id, id).await?;
+    let NotebookForm { name, parent_id } = body.into_inner();
+    let name = NotebookName::parse(&name)?.0;
+    let notebooks = state.notebooks.get_notebooks(workspace_id).await?;
+    let all: Vec<Notebook> = notebooks
+        .iter()
+        .map(|(notebook, _)| notebook.clone())
+        .collect();
+    check_parent(&all, id, parent_id)?;
+
+    state.notebooks.update_notebook(id, name, parent_id).await?;
+    audit
+        .record(
+            AuditAction::NotebookUpdate,
+            Some(auth.user_id),
+            None,
+            Some(id),
+            true,
+        )
+        .await;
+
+    //the numbers of the notes inside it are the same, they moved with it
+    let notebooks = state.notebooks.get_notebooks(workspace_id).await?;
+    let response = notebook_tree(notebooks)
+        .into_iter()
+        .find(|notebook| notebook.id == id)
+        .ok_or_else(notebook_not_found)?;
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[delete("/workspaces/{workspace_id}/notebooks/{id}")]
+#[instrument(skip(state, auth, audit), name = "Delete notebook")]
+async fn delete_notebook(
+    state: web::Data<AppState>,
+    auth: JwtMiddleware,
+    audit: Audit,
+    path: web::Path<(uuid::Uuid, uuid::Uuid)>,
+) -> Result<HttpResponse, errors::Error> {
+    let (workspace_id, id) = path.into_inner();
+    workspace_access(&state, workspace_id, auth.user_id, WorkspaceRole::Member).await?;
+    workspace_notebook(&state, workspace_id, id).await?;
+
+    state.notebooks.delete_notebook(id).await?;
+    audit
+        .record(
+            AuditAction::NotebookDelete,
+            Some(auth.user_id),
+            None,
+            Some(id),
+            true,
+        )
+        .await;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_owned(),
+    }))
+}
+
+#[get("/workspaces/{workspace_id}/notebooks/{id}/posts")]
+#[instrument(skip(state, auth), name = "Get notebook posts")]
+async fn get_notebook_posts(
+    state: web::Data<AppState>,
+    auth: JwtMiddleware,
+    path: web::Path<(uuid::Uuid, uuid::Uuid)>,
+) -> Result<HttpResponse, errors::Error> {
+    let (workspace_id, id) = path.into_inner();
+    workspace_access(&state, workspace_id, auth.user_id, WorkspaceRole::Member).await?;
+    workspace_notebook(&state, workspace_id, id).await?;
+
+    //the notes of the notebook and of all notebooks inside it
+    let notebooks = state.notebooks.get_notebooks(workspace_id).await?;
+    let all: Vec<Notebook> = notebooks
+        .into_iter()
+        .map(|(notebook, _)| notebook)
+        .collect();
+    let posts = state
+        .notebooks
+        .get_notebook_posts(auth.user_id, workspace_id, notebook_subtree(&all, id))
+        .await?;
+
+    Ok(HttpResponse::Ok().json(posts))
+}
+
+#[patch("/workspaces/{workspace_id}/posts/{id}/notebook")]
+#[instrument(skip(state, auth, audit), name = "Move post to notebook")]
+async fn move_post(
+    state: web::Data<AppState>,
+    auth: JwtMiddleware,
+    audit: Audit,
+    path: web::Path<(uuid::Uuid, uuid::Uuid)>,
+    body: web::Json<NotebookMoveForm>,
+) -> Result<HttpResponse, errors::Error> {
+    let (workspace_id, id) = path.into_inner();
+    workspace_access(&state, workspace_id, auth.user_id, WorkspaceRole::Member).await?;
+    //the notebooks hold the notes of their own workspace, not the ones shared into it
+    let post = state.notes.find_post(id).await?;
+    if post.workspace_id != workspace_id {
+        return Err(post_not_found(id));
+    }
+    let permission = state.permissions.get_permission(id, auth.user_id).await?;
+    if permission < Some(Permission::Editor) {
+        return Err(errors::Error::from_code(
+            "auth.forbidden",
+            errors::ErrorTypes::Auth(errors::Auth::Authorization),
+        ));
+    }
+    let notebook_id = body.notebook_id;
+    if let Some(notebook_id) = notebook_id {
+        workspace_notebook(&state, workspace_id, notebook_id).await?;
+    }
+
+    state.notebooks.move_post(id, notebook_id).await?;
+    audit
+        .record(
+            AuditAction::PostMove,
+            Some(auth.user_id),
+            None,
+            Some(id),
+            true,
+        )
+        .await;
+    events::stored_post_changed(&state, PostEventKind::Updated, id).await;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_owned(),
+    }))
+}
@@ -0,0 +1,105 @@
+pub mod handlers;
+
+use actix_web::web;
+use common::ResponseNotebook;
+
+use crate::app::AppState;
+use crate::db::{notebook_not_found, notebook_subtree, Notebook};
+use crate::errors;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(handlers::get_notebooks)
+        .service(handlers::add_notebook)
+        .service(handlers::update_notebook)
+        .service(handlers::delete_notebook)
+        .service(handlers::get_notebook_posts)
+        .service(handlers::move_post);
+}
+
+/// The function `workspace_notebook` finds a notebook of the workspace in the path.
+///
+/// Arguments:
+///
+/// * `state`: The `state` parameter is the app state with the repositories.
+/// * `workspace_id`: The `workspace_id` parameter is the id of the workspace, the user is a member of
+/// it.
+/// * `notebook_id`: The `notebook_id` parameter is the id of the notebook.
+///
+/// Returns:
+///
+/// the notebook, `notebook.not_found` if there is none or it belongs to another workspace.
+pub async fn workspace_notebook(
+    state: &AppState,
+    workspace_id: uuid::Uuid,
+    notebook_id: uuid::Uuid,
+) -> Result<Notebook, errors::Error> {
+    let notebook = state.notebooks.find_notebook(notebook_id).await?;
+    match notebook.workspace_id == workspace_id {
+        true => Ok(notebook),
+        false => Err(notebook_not_found()),
+    }
+}
+
+/// The function `check_parent` checks the new parent of a notebook.
+///
+/// Arguments:
+///
+/// * `notebooks`: The `notebooks` parameter lists the notebooks of the workspace.
+/// * `notebook_id`: The `notebook_id` parameter is the notebook that is moved.
+/// * `parent_id`: The `parent_id` parameter is the new parent, `None` for the top level.
+///
+/// Returns:
+///
+/// `notebook.not_found` if the parent is not a notebook of the workspace, or `notebook.cycle` as a
+/// field error of `parent_id` if it is the notebook itself or a notebook inside it.
+pub fn check_parent(
+    notebooks: &[Notebook],
+    notebook_id: uuid::Uuid,
+    parent_id: Option<uuid::Uuid>,
+) -> Result<(), errors::Error> {
+    let Some(parent_id) = parent_id else {
+        return Ok(());
+    };
+    if !notebooks.iter().any(|notebook| notebook.id == parent_id) {
+        return Err(notebook_not_found());
+    }
+    if notebook_subtree(notebooks, notebook_id).contains(&parent_id) {
+        return Err(errors::Error::validation(vec![errors::FieldError::new(
+            "parent_id",
+            "notebook.cycle",
+        )]));
+    }
+    Ok(())
+}
+
+/// The function `notebook_tree` adds the number of the notes inside every notebook, in the notebook
+/// itself and in the notebooks inside it at any depth.
+///
+/// Arguments:
+///
+/// * `notebooks`: The `notebooks` parameter lists the notebooks of a workspace with the number of the
+/// notes right in them.
+///
+/// Returns:
+///
+/// the notebooks in the same order, as the members see them.
+pub fn notebook_tree(notebooks: Vec<(Notebook, i64)>) -> Vec<ResponseNotebook> {
+    let (all, notes): (Vec<Notebook>, Vec<i64>) = notebooks.into_iter().unzip();
+    let totals: Vec<i64> = all
+        .iter()
+        .map(|notebook| {
+            let subtree = notebook_subtree(&all, notebook.id);
+            all.iter()
+                .zip(&notes)
+                .filter(|(other, _)| subtree.contains(&other.id))
+                .map(|(_, notes)| notes)
+                .sum()
+        })
+        .collect();
+
+    all.into_iter()
+        .zip(notes)
+        .zip(totals)
+        .map(|((notebook, notes), total)| notebook.response(notes, total))
+        .collect()
+}
@@ -0,0 +1,147 @@
+use actix_web::{delete, get, patch, post, web, HttpResponse};
+use common::{
+    ApiResponse, Permission, PermissionForm, PermissionUpdateForm, ResponsePermission,
+    WorkspaceRole,
+};
+use time::OffsetDateTime;
+use tracing::instrument;
+
+use super::{granted_role, grantee, post_access};
+use crate::audit::{Audit, AuditAction};
+use crate::db::PostPermission;
+use crate::errors;
+use crate::workspaces::{workspace_access, workspace_post};
+use crate::{app::AppState, auth::JwtMiddleware};
+
+#[get("/workspaces/{workspace_id}/posts/{id}/permissions")]
+#[instrument(skip(state, auth), name = "Get permissions")]
+async fn get_permissions(
+    state: web::Data<AppState>,
+    auth: JwtMiddleware,
+    path: web::Path<(uuid::Uuid, uuid::Uuid)>,
+) -> Result<HttpResponse, errors::Error> {
+    let (workspace_id, post_id) = path.into_inner();
+    let workspace =
+        workspace_access(&state, workspace_id, auth.user_id, WorkspaceRole::Member).await?;
+    workspace_post(&state, &workspace, post_id, auth.user_id).await?;
+    post_access(&state, post_id, auth.user_id, Permission::Owner).await?;
+    let permissions = state.permissions.get_post_permissions(post_id).await?;
+
+    Ok(HttpResponse::Ok().json(permissions))
+}
+
+#[post("/workspaces/{workspace_id}/posts/{id}/permissions")]
+#[instrument(skip(state, auth, audit), name = "Grant permission")]
+async fn grant_permission(
+    state: web::Data<AppState>,
+    auth: JwtMiddleware,
+    audit: Audit,
+    path: web::Path<(uuid::Uuid, uuid::Uuid)>,
+    body: web::Json<PermissionForm>,
+) -> Result<HttpResponse, errors::Error> {
+    let (workspace_id, post_id) = path.into_inner();
+    let workspace =
+        workspace_access(&state, workspace_id, auth.user_id, WorkspaceRole::Member).await?;
+    workspace_post(&state, &workspace, post_id, auth.user_id).await?;
+    post_access(&state, post_id, auth.user_id, Permission::Owner).await?;
+    let PermissionForm { username, role } = body.into_inner();
+    let role = granted_role(role)?;
+    let user = grantee(&state, &username, auth.user_id).await?;
+
+    let permission = state
+        .permissions
+        .grant_permission(PostPermission {
+            post_id,
+            user_id: user.id,
+            role: role.to_string(),
+            created_at: OffsetDateTime::now_utc(),
+        })
+        .await?;
+    audit
+        .record(
+            AuditAction::PermissionGrant,
+            Some(auth.user_id),
+            None,
+            Some(post_id),
+            true,
+        )
+        .await;
+
+    let response: ResponsePermission = permission.response(user.username);
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[patch("/workspaces/{workspace_id}/posts/{id}/permissions/{username}")]
+#[instrument(skip(state, auth, audit), name = "Change permission")]
+async fn update_permission(
+    state: web::Data<AppState>,
+    auth: JwtMiddleware,
+    audit: Audit,
+    path: web::Path<(uuid::Uuid, uuid::Uuid, String)>,
+    body: web::Json<PermissionUpdateForm>,
+) -> Result<HttpResponse, errors::Error> {
+    let (workspace_id, post_id, username) = path.into_inner();
+    let workspace =
+        workspace_access(&state, workspace_id, auth.user_id, WorkspaceRole::Member).await?;
+    workspace_post(&state, &workspace, post_id, auth.user_id).await?;
+    post_access(&state, post_id, auth.user_id, Permission::Owner).await?;
+    let role = granted_role(body.role)?;
+    let user = grantee(&state, &username, auth.user_id).await?;
+
+    state
+        .permissions
+        .update_permission(post_id, user.id, role)
+        .await?;
+    audit
+        .record(
+            AuditAction::PermissionChange,
+            Some(auth.user_id),
+            None,
+            Some(post_id),
+            true,
+        )
+        .await;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_owned(),
+    }))
+}
+
+#[delete("/workspaces/{workspace_id}/posts/{id}/permissions/{username}")]
+#[instrument(skip(state, auth, audit), name = "Revoke permission")]
+async fn revoke_permission(
+    state: web::Data<AppState>,
+    auth: JwtMiddleware,
+    audit: Audit,
+    path: web::Path<(uuid::Uuid, uuid::Uuid, String)>,
+) -> Result<HttpResponse, errors::Error> {
+    let (workspace_id, post_id, username) = path.into_inner();
+    let workspace =
+        workspace_access(&state, workspace_id, auth.user_id, WorkspaceRole::Member).await?;
+    workspace_post(&state, &workspace, post_id, auth.user_id).await?;
+    let user = state.users.find_user_by_username(&username).await?;
+    //the owner takes the access away, or the user leaves the post
+    let needed = match user.id == auth.user_id {
+        true => Permission::Viewer,
+        false => Permission::Owner,
+    };
+    post_access(&state, post_id, auth.user_id, needed).await?;
+
+    state
+        .permissions
+        .revoke_permission(post_id, user.id)
+        .await?;
+    audit
+        .record(
+            AuditAction::PermissionRevoke,
+            Some(auth.user_id),
+            None,
+            Some(post_id),
+            true,
+        )
+        .await;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_owned(),
+    }))
+}
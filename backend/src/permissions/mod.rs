@@ -0,0 +1,94 @@
+pub mod handlers;
+
+use actix_web::web;
+use common::Permission;
+
+use crate::app::AppState;
+use crate::db::User;
+use crate::errors;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(handlers::get_permissions)
+        .service(handlers::grant_permission)
+        .service(handlers::update_permission)
+        .service(handlers::revoke_permission);
+}
+
+/// The function `post_access` checks that a user may do something with a post.
+///
+/// Arguments:
+///
+/// * `state`: The `state` parameter is the app state with the repositories.
+/// * `post_id`: The `post_id` parameter is the id of the post.
+/// * `user_id`: The `user_id` parameter is the logged in user.
+/// * `needed`: The `needed` parameter is the lowest permission that is enough, `Viewer` to read the
+/// post, `Editor` to change it and `Owner` to manage who has access.
+///
+/// Returns:
+///
+/// the permission of the user, `post.not_found` if there is no such post, or `auth.forbidden` if the
+/// user has no access or not enough of it.
+pub async fn post_access(
+    state: &AppState,
+    post_id: uuid::Uuid,
+    user_id: uuid::Uuid,
+    needed: Permission,
+) -> Result<Permission, errors::Error> {
+    match state.permissions.get_permission(post_id, user_id).await? {
+        Some(permission) if permission >= needed => Ok(permission),
+        _ => {
+            tracing::warn!(
+                "User {} has no {} access to post {}",
+                user_id,
+                needed,
+                post_id
+            );
+            Err(errors::Error::from_code(
+                "auth.forbidden",
+                errors::ErrorTypes::Auth(errors::Auth::Authorization),
+            ))
+        }
+    }
+}
+
+/// The function `grantee` finds the user a post is shared with by their username.
+///
+/// Arguments:
+///
+/// * `state`: The `state` parameter is the app state with the repositories.
+/// * `username`: The `username` parameter is the username from the request.
+/// * `owner`: The `owner` parameter is the author of the post.
+///
+/// Returns:
+///
+/// the user, `user.not_found` if there is none, or `permission.self` as a field error of `username`
+/// if it is the author, who has all the rights already.
+pub async fn grantee(
+    state: &AppState,
+    username: &str,
+    owner: uuid::Uuid,
+) -> Result<User, errors::Error> {
+    let user = state.users.find_user_by_username(username).await?;
+    if user.id == owner {
+        return Err(errors::Error::validation(vec![errors::FieldError::new(
+            "username",
+            "permission.self",
+        )]));
+    }
+    Ok(user)
+}
+
+/// The function `granted_role` checks the role of a request, the author is the only owner of a post.
+///
+/// Returns:
+///
+/// `permission.invalid_role` as a field error of `role` for `owner`.
+pub fn granted_role(role: Permission) -> Result<Permission, errors::Error> {
+    match role {
+        Permission::Owner => Err(errors::Error::validation(vec![errors::FieldError::new(
+            "role",
+            "permission.invalid_role",
+        )])),
+        role => Ok(role),
+    }
+}
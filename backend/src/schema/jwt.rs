@@ -1,8 +1,13 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
 use crate::app::TokenConfig;
+use crate::db::User;
 use crate::errors::{Auth, Error, ErrorTypes};
 use actix_web::{HttpMessage, HttpRequest};
 
 use actix_web::cookie::time::Duration as ActixWebDuration;
+use common::Role;
 use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
 
@@ -20,29 +25,69 @@ use tracing::instrument;
 /// * `exp`: The `exp` property in the `TokenClaims` struct represents the expiration time of the token.
 /// It is of type `usize`, which means it stores a non-negative integer value. The value represents the
 /// number of seconds since January 1, 1970 (also known as the Unix timestamp)
+/// * `role`: The `role` property is the role of the user when the token was issued. Tokens without it
+/// are read as `Role::User`.
+/// * `ver`: The `ver` property is the session version of the user when the token was issued. Tokens
+/// with an older version than the user's current one are rejected.
 pub struct TokenClaims {
     pub sub: String,
     pub exp: usize,
+    #[serde(default)]
+    pub role: Role,
+    #[serde(default)]
+    pub ver: i32,
 }
 
 impl TokenClaims {
-    /// The function creates a new token with a specified subject and expiration time.
+    /// The function creates a new token for the user with a specified expiration time. The token
+    /// carries the user's id, role and session version.
     ///
     /// Arguments:
     ///
-    /// * `sub`: The `sub` parameter is a string that represents the subject of the token. It typically
-    /// identifies the user or entity that the token is being issued for.
+    /// * `user`: The `user` parameter is the user that the token is being issued for.
     /// * `time`: The `time` parameter is of type `Duration`. It represents a duration of time, such as
     /// 5 minutes, 1 hour, etc. It is used to calculate the expiration time of the token.
     ///
     /// Returns:
     ///
     /// The `new` function returns an instance of the `TokenClaims` struct.
-    pub fn new(sub: String, time: Duration) -> Self {
+    pub fn new(user: &User, time: Duration) -> Self {
         //create token
         let now = OffsetDateTime::now_utc();
         let exp = (now + time).unix_timestamp() as usize;
-        TokenClaims { sub, exp }
+        TokenClaims {
+            sub: user.id.to_string(),
+            exp,
+            role: user.role(),
+            ver: user.session_version,
+        }
+    }
+}
+
+/// The `RevokedSessions` struct remembers the latest session version of the users whose sessions were
+/// revoked by this process, so their access tokens are rejected right away and not only on the next
+/// refresh. The database stays the source of truth: refresh tokens are checked against it.
+#[derive(Default)]
+pub struct RevokedSessions {
+    versions: RwLock<HashMap<uuid::Uuid, i32>>,
+}
+
+impl RevokedSessions {
+    /// The function `revoke` rejects the tokens of the user with a session version below `version`.
+    pub fn revoke(&self, user_id: uuid::Uuid, version: i32) {
+        if let Ok(mut versions) = self.versions.write() {
+            let current = versions.entry(user_id).or_insert(version);
+            *current = (*current).max(version);
+        }
+    }
+
+    /// The function `is_revoked` checks if the token was issued before the sessions of its user were
+    /// revoked.
+    pub fn is_revoked(&self, user_id: uuid::Uuid, claims: &TokenClaims) -> bool {
+        self.versions
+            .read()
+            .map(|versions| versions.get(&user_id).is_some_and(|v| claims.ver < *v))
+            .unwrap_or(false)
     }
 }
 /// The `TokenSettings` struct represents the settings for token encoding and decoding in Rust.
@@ -73,9 +118,11 @@ pub struct TokenSettings {
 /// * `access`: The `access` property is of type `TokenSettings`. It represents the settings for the
 /// access token in the JWT (JSON Web Token).
 /// * `refresh`: The `refresh` property is of type `TokenSettings`.
+/// * `revoked`: The `revoked` property holds the sessions that were revoked while the server runs.
 pub struct Jwt {
     pub access: TokenSettings,
     pub refresh: TokenSettings,
+    pub revoked: RevokedSessions,
 }
 
 pub enum TokenType {
@@ -111,6 +158,7 @@ impl Jwt {
                 exp: Duration::minutes(refresh_config.exp),
                 maxage: ActixWebDuration::new(60 * refresh_config.maxage, 0),
             },
+            revoked: RevokedSessions::default(),
         }
     }
 
@@ -136,22 +184,16 @@ impl Jwt {
                     jsonwebtoken::encode(&Header::default(), token, &self.access.encode_key)
                         .map_err(|e| {
                             tracing::error!("Error creating new access token");
-                            Error::new(
-                                Some(e.to_string()),
-                                Some("Can not create token".into()),
-                                ErrorTypes::JwtError,
-                            )
+                            Error::from_code("auth.token_not_created", ErrorTypes::JwtError)
+                                .with_cause(e)
                         })?
                 }
                 TokenType::Refresh => {
                     jsonwebtoken::encode(&Header::default(), token, &self.refresh.encode_key)
                         .map_err(|e| {
                             tracing::error!("Error creating new refresh token");
-                            Error::new(
-                                Some(e.to_string()),
-                                Some("Can not create token".into()),
-                                ErrorTypes::JwtError,
-                            )
+                            Error::from_code("auth.token_not_created", ErrorTypes::JwtError)
+                                .with_cause(e)
                         })?
                 }
             };
@@ -182,25 +224,13 @@ impl Jwt {
                 &self.access.decode_key,
                 &Validation::new(jsonwebtoken::Algorithm::HS256),
             )
-            .map_err(|e| {
-                Error::new(
-                    Some(e.to_string()),
-                    Some("Can not decode token".into()),
-                    ErrorTypes::JwtError,
-                )
-            })?,
+            .map_err(invalid_token)?,
             TokenType::Refresh => jsonwebtoken::decode::<TokenClaims>(
                 claim,
                 &self.refresh.decode_key,
                 &Validation::new(jsonwebtoken::Algorithm::HS256),
             )
-            .map_err(|e| {
-                Error::new(
-                    Some(e.to_string()),
-                    Some("Can not decode token".into()),
-                    ErrorTypes::JwtError,
-                )
-            })?,
+            .map_err(invalid_token)?,
         };
         Ok(token.claims)
     }
@@ -216,19 +246,18 @@ impl Jwt {
     ///
     /// Returns:
     ///
-    /// The function `refresh` returns a `Result` with the type `uuid::Uuid` as the success variant and
-    /// `Error` as the error variant.
+    /// The function `refresh` returns a `Result` with the user's `uuid::Uuid` and the claims of the
+    /// refresh token as the success variant and `Error` as the error variant.
     #[instrument(skip_all, name = "Refresh jwt token")]
-    pub fn refresh(&self, req: &HttpRequest) -> Result<uuid::Uuid, Error> {
+    pub fn refresh(&self, req: &HttpRequest) -> Result<(uuid::Uuid, TokenClaims), Error> {
         tracing::info!("Get jwt refresh token from cookies");
 
         let tokens = match req.cookie("refresh_token").map(|c| c.value().to_string()) {
             Some(token) => token,
             None => {
                 tracing::error!("JWT refresh token not found");
-                return Err(Error::new(
-                    None,
-                    Some("Refresh jwt token not found. Log in first!".into()),
+                return Err(Error::from_code(
+                    "auth.refresh_token_missing",
                     ErrorTypes::Auth(Auth::Authorization),
                 ));
             }
@@ -238,12 +267,7 @@ impl Jwt {
             Ok(c) => c,
             Err(e) => {
                 tracing::error!("Invalid refresh jwt token");
-
-                return Err(Error::new(
-                    Some(e.to_string()),
-                    Some("Invalid refresh jwt tokens".into()),
-                    ErrorTypes::JwtError,
-                ));
+                return Err(e);
             }
         };
 
@@ -251,22 +275,25 @@ impl Jwt {
         if OffsetDateTime::now_utc().unix_timestamp() as usize > token.exp {
             tracing::error!("Refresh token timed out");
 
-            return Err(Error::new(
-                None,
-                Some("Refresh token timed out".into()),
-                ErrorTypes::Auth(Auth::Authorization),
+            return Err(Error::from_code(
+                "auth.refresh_expired",
+                ErrorTypes::Auth(Auth::Authentication),
             ));
         }
 
         //Get user id
         let req_ext = req.extensions();
 
-        let user_id = req_ext.get::<uuid::Uuid>().ok_or(Error::new(
-            None,
-            Some("Can not find user's id".into()),
-            ErrorTypes::Auth(Auth::Authentication),
-        ))?;
+        let user_id = req_ext.get::<uuid::Uuid>().ok_or_else(|| {
+            Error::from_code("auth.invalid_token", ErrorTypes::Auth(Auth::Authentication))
+        })?;
 
-        Ok(*user_id)
+        Ok((*user_id, token))
     }
 }
+
+//a token that was not signed by this server, or is broken
+fn invalid_token(e: jsonwebtoken::errors::Error) -> Error {
+    tracing::error!("Can not decode token");
+    Error::from_code("auth.invalid_token", ErrorTypes::Auth(Auth::Authentication)).with_cause(e)
+}
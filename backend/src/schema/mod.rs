@@ -1,3 +1,5 @@
 pub mod jwt;
+pub mod notebook;
 pub mod post;
 pub mod user;
+pub mod workspace;
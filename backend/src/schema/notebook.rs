@@ -0,0 +1,57 @@
+use crate::{db::Notebook, errors};
+
+use common::FieldError;
+use time::OffsetDateTime;
+
+/// The longest name of a notebook, the length of the `notebooks.name` column.
+pub const MAX_NAME_LENGTH: usize = 64;
+
+#[derive(Debug)]
+pub struct NotebookName(pub String);
+
+impl NotebookName {
+    /// The `parse` function checks the name of a notebook.
+    ///
+    /// Arguments:
+    ///
+    /// * `name`: The `name` parameter is the name from the request. The spaces around it are removed.
+    ///
+    /// Returns:
+    ///
+    /// the `NotebookName`, or the `notebook.name_required` or `notebook.name_too_long` field error of
+    /// `name`.
+    pub fn parse(name: &str) -> Result<NotebookName, errors::Error> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(errors::Error::validation(vec![FieldError::new(
+                "name",
+                "notebook.name_required",
+            )]));
+        }
+        if name.chars().count() > MAX_NAME_LENGTH {
+            return Err(errors::Error::validation(vec![FieldError::new(
+                "name",
+                "notebook.name_too_long",
+            )
+            .with_param("max", MAX_NAME_LENGTH)]));
+        }
+
+        Ok(NotebookName(name.to_owned()))
+    }
+
+    /// The function `build` returns a new notebook with the name.
+    ///
+    /// Arguments:
+    ///
+    /// * `workspace_id`: The `workspace_id` parameter is the workspace of the notebook.
+    /// * `parent_id`: The `parent_id` parameter is the notebook it is in, `None` for a top level one.
+    pub fn build(self, workspace_id: uuid::Uuid, parent_id: Option<uuid::Uuid>) -> Notebook {
+        Notebook {
+            id: uuid::Uuid::new_v4(),
+            workspace_id,
+            parent_id,
+            name: self.0,
+            created_at: OffsetDateTime::now_utc(),
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{db::Post, errors};
 
+use common::FieldError;
 use time::OffsetDateTime;
 
 #[derive(Debug)]
@@ -18,15 +19,10 @@ impl Title {
     ///
     /// The function `parse` returns a `Result` type. If the `title` is not empty, it returns
     /// `Ok(Title(title.to_owned()))`, where `Title` is a struct that takes ownership of the `title`
-    /// string. If the `title` is empty, it returns `Err(errors::Error::new(None,Some("Ivalid post's
-    /// title! It can not be
-    fn parse(title: &str) -> Result<Title, errors::Error> {
+    /// string. If the `title` is empty, it returns the `title.required` field error.
+    fn parse(title: &str) -> Result<Title, FieldError> {
         if title.trim().is_empty() {
-            return Err(errors::Error::new(
-                None,
-                Some("Ivalid post's title! It can not be empty!".to_string()),
-                errors::ErrorTypes::ValidationError,
-            ));
+            return Err(FieldError::new("title", "title.required"));
         }
 
         Ok(Title(title.to_owned()))
@@ -44,10 +40,12 @@ impl Title {
 /// provided, or `None` if no description is given.
 /// * `user_id`: The `user_id` property is of type `uuid::Uuid`, which represents a universally unique
 /// identifier. It is used to uniquely identify a user.
+/// * `workspace_id`: The `workspace_id` property is the workspace the post is written in.
 pub struct NewPost {
     pub title: Title,
     pub description: Option<String>,
     pub user_id: uuid::Uuid,
+    pub workspace_id: uuid::Uuid,
 }
 
 impl NewPost {
@@ -61,6 +59,7 @@ impl NewPost {
     /// type, which means it can either be `Some(string)` or `None`.
     /// * `user_id`: The `user_id` parameter is of type `uuid::Uuid`, which represents a universally unique
     /// identifier (UUID). It is used to uniquely identify a user.
+    /// * `workspace_id`: The `workspace_id` parameter is the workspace the post is written in.
     ///
     /// Returns:
     ///
@@ -70,12 +69,14 @@ impl NewPost {
         title: &str,
         description: &Option<String>,
         user_id: uuid::Uuid,
+        workspace_id: uuid::Uuid,
     ) -> Result<NewPost, errors::Error> {
-        let title = Title::parse(title)?;
+        let title = Title::parse(title).map_err(|e| errors::Error::validation(vec![e]))?;
         Ok(NewPost {
             title,
             description: description.to_owned(),
             user_id: user_id.to_owned(),
+            workspace_id,
         })
     }
     /// The function builds a DB Post object by converting data from the current object.
@@ -84,13 +85,25 @@ impl NewPost {
     ///
     /// The function `build` returns a `Post` object.
     pub fn build(&self) -> Post {
+        self.build_at(OffsetDateTime::now_utc())
+    }
+
+    /// The function `build_at` builds a DB Post object that was created at the given time. It is used
+    /// for imported notes, which keep the time of the original note.
+    ///
+    /// Arguments:
+    ///
+    /// * `created_at`: The `created_at` parameter is the creation time of the post.
+    pub fn build_at(&self, created_at: OffsetDateTime) -> Post {
         tracing::info!("Converting data to DB Post!");
         Post {
             id: uuid::Uuid::new_v4(),
             user_id: self.user_id,
             title: self.title.0.clone(),
             description: self.description.clone(),
-            created_at: OffsetDateTime::now_utc(),
+            created_at,
+            workspace_id: self.workspace_id,
+            notebook_id: None,
         }
     }
 }
@@ -1,19 +1,26 @@
-use crate::db::{hash_password, User};
+use std::path::Path;
 
-use lazy_static::lazy_static;
-use onig::Regex;
+use common::FieldError;
+use sha1::{Digest, Sha1};
 use time::OffsetDateTime;
+use unicode_normalization::UnicodeNormalization;
 
+use crate::app::{Argon2Settings, CharClass, PasswordPolicy, PolicySettings, UsernamePolicy};
+use crate::db::{hash_password, User};
 use crate::errors;
 
-//regexes for name and password validations
-lazy_static! {
-    //username should conatines only latters and digits and minimal length is 5
-    static ref USERNAME_REGEX: Regex =
-        Regex::new(r"^[a-zA-Z0-9]{5,}$").expect("Ivalid regular expression");
-    //password must have minimum one number and minimum 8 symbols
-    static ref PASSWORD_REGEX: Regex =
-        Regex::new(r#"^(?=.*[A-Za-z])(?=.*\d)[A-Za-z\d]{8,}$"#).expect("Ivalid regular expression");
+//the length of the `users.username` column
+const USERNAME_COLUMN_LENGTH: usize = 50;
+
+/// The function `username_key` returns the form of the username that is compared: NFKC normalized and
+/// lowercase. It is stored as `users.username_key`, so `Alice` and `alice` are the same user.
+pub fn username_key(username: &str) -> String {
+    username.nfkc().collect::<String>().to_lowercase()
+}
+
+//a character is allowed if one of the classes contains it
+fn is_allowed(classes: &[CharClass], c: char) -> bool {
+    classes.iter().any(|class| class.contains(c))
 }
 
 #[derive(Debug)]
@@ -21,29 +28,59 @@ pub struct Username(pub String);
 
 impl Username {
     /// The `parse` function takes a username as input and returns a `Result` containing a `Username` if
-    /// the username is valid, or an `Error` if the username is invalid.
+    /// the username is valid, or the list of failed checks if it is invalid.
     ///
     /// Arguments:
     ///
     /// * `username`: The `username` parameter is a string that represents the username that needs to be
     /// parsed.
+    /// * `policy`: The `policy` parameter holds the length limits, the allowed characters and whether
+    /// the username is normalized first.
     ///
     /// Returns:
     ///
-    /// The `parse` function returns a `Result` type. If the `USERNAME_REGEX` matches the `username`
-    /// string, it returns an `Ok` variant containing a `Username` struct with the `username` string as
-    /// its value. If the `USERNAME_REGEX` does not match the `username` string, it returns an `Err`
-    /// variant containing an `errors::Error` struct with an
-    fn parse(username: &str) -> Result<Username, errors::Error> {
-        match USERNAME_REGEX.is_match(username){
-            true=>Ok(Username(username.to_string())),
-            false=>Err(errors::Error::new(None,Some("Ivalid username! Must containes minimum 5 symbols and only letters and numbers!".to_string()),errors::ErrorTypes::ValidationError))
+    /// The `parse` function returns a `Result` type. If every check passes, it returns an `Ok` variant
+    /// containing a `Username` struct with the (normalized) username as its value. Otherwise it returns
+    /// an `Err` variant with a `FieldError` for each failed check: `username.too_short`,
+    /// `username.too_long` and `username.invalid_characters`.
+    fn parse(username: &str, policy: &UsernamePolicy) -> Result<Username, Vec<FieldError>> {
+        let username = match policy.normalize {
+            true => username.nfkc().collect::<String>(),
+            false => username.to_string(),
+        };
+        let mut errors = Vec::new();
+        let length = username.chars().count();
+        let max_length = policy.max_length.min(USERNAME_COLUMN_LENGTH);
+
+        if length < policy.min_length {
+            errors.push(
+                FieldError::new("username", "username.too_short")
+                    .with_param("min", policy.min_length),
+            );
+        }
+        if length > max_length {
+            errors.push(
+                FieldError::new("username", "username.too_long").with_param("max", max_length),
+            );
+        }
+        if !username.chars().all(|c| is_allowed(&policy.allowed, c)) {
+            errors.push(FieldError::new("username", "username.invalid_characters"));
+        }
+
+        match errors.is_empty() {
+            true => Ok(Username(username)),
+            false => Err(errors),
         }
     }
 }
-#[derive(Debug)]
 pub struct Password(pub String);
 
+impl std::fmt::Debug for Password {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Password").field(&common::REDACTED).finish()
+    }
+}
+
 impl Password {
     /// The `parse` function takes a password as input and returns a `Result` indicating whether the
     /// password is valid or not.
@@ -52,17 +89,104 @@ impl Password {
     ///
     /// * `password`: The `password` parameter is a string that represents the password that needs to be
     /// parsed.
+    /// * `policy`: The `policy` parameter holds the length limits, the allowed and required characters
+    /// and the breached password list.
     ///
     /// Returns:
     ///
-    /// The function `parse` returns a `Result` type. If the `PASSWORD_REGEX` matches the given
-    /// `password`, it returns `Ok(Password(password.to_string()))`, where `Password` is a struct that
-    /// wraps the password string. If the `PASSWORD_REGEX` does not match the given `password`, it
-    /// returns `Err(errors::Error::new(None,Some("Invalid password! Must contain
-    fn parse(password: &str) -> Result<Password, errors::Error> {
-        match PASSWORD_REGEX.is_match(password){
-            true=>Ok(Password(password.to_string())),
-            false=>Err(errors::Error::new(None,Some("Invalid password! Must contain at least 8 characters, one or more of which are numbers, the rest are letters!".to_string()),errors::ErrorTypes::ValidationError))
+    /// The function `parse` returns a `Result` type. If every check passes, it returns
+    /// `Ok(Password(password.to_string()))`, where `Password` is a struct that wraps the password
+    /// string. Otherwise it returns a `FieldError` for each failed check: `password.too_short`,
+    /// `password.too_long`, `password.no_letter`, `password.no_digit`, `password.no_symbol`,
+    /// `password.no_space`, `password.invalid_characters` and `password.breached`. The breached list
+    /// is only read when the other checks pass.
+    pub async fn parse(
+        password: &str,
+        policy: &PasswordPolicy,
+    ) -> Result<Password, Vec<FieldError>> {
+        let mut errors = Vec::new();
+        let length = password.chars().count();
+
+        if length < policy.min_length {
+            errors.push(
+                FieldError::new("password", "password.too_short")
+                    .with_param("min", policy.min_length),
+            );
+        }
+        if length > policy.max_length {
+            errors.push(
+                FieldError::new("password", "password.too_long")
+                    .with_param("max", policy.max_length),
+            );
+        }
+        for class in &policy.required {
+            if !password.chars().any(|c| class.contains(c)) {
+                errors.push(FieldError::new("password", missing_class_code(*class)));
+            }
+        }
+        if !password.chars().all(|c| is_allowed(&policy.allowed, c)) {
+            errors.push(FieldError::new("password", "password.invalid_characters"));
+        }
+
+        if let (true, Some(directory)) = (errors.is_empty(), &policy.breached_passwords) {
+            if is_breached(password, Path::new(directory)).await {
+                tracing::info!("Rejected a breached password!");
+                errors.push(FieldError::new("password", "password.breached"));
+            }
+        }
+
+        match errors.is_empty() {
+            true => Ok(Password(password.to_string())),
+            false => Err(errors),
+        }
+    }
+}
+
+//the error code of a required character class that is missing
+fn missing_class_code(class: CharClass) -> &'static str {
+    match class {
+        CharClass::Latin | CharClass::Letter => "password.no_letter",
+        CharClass::Digit => "password.no_digit",
+        CharClass::Symbol => "password.no_symbol",
+        CharClass::Space => "password.no_space",
+    }
+}
+
+/// The function `is_breached` looks the password up in a local breached password list. The list is
+/// split by the first 5 hex characters of the SHA-1 hash (the k-anonymity range format of Have I Been
+/// Pwned), so only one small file is read.
+///
+/// Arguments:
+///
+/// * `password`: The `password` parameter is the password to look up.
+/// * `directory`: The `directory` parameter is the folder with the `<PREFIX>.txt` files.
+///
+/// Returns:
+///
+/// `true` if the rest of the hash is listed in the file of its prefix. A missing file means the
+/// password is not listed, a file that can not be read is logged and the password is accepted.
+async fn is_breached(password: &str, directory: &Path) -> bool {
+    let hash: String = Sha1::digest(password.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect();
+    let (prefix, suffix) = hash.split_at(5);
+    let path = directory.join(format!("{}.txt", prefix));
+
+    match tokio::fs::read_to_string(&path).await {
+        Ok(list) => list.lines().any(|line| {
+            line.split(':')
+                .next()
+                .is_some_and(|listed| listed.trim().eq_ignore_ascii_case(suffix))
+        }),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => false,
+        Err(e) => {
+            tracing::error!(
+                "Failed to read the breached password list {}: {}",
+                path.display(),
+                e
+            );
+            false
         }
     }
 }
@@ -91,33 +215,57 @@ impl NewUser {
     /// * `password`: The `password` parameter is of type `T`, which is a generic type that implements
     /// the `AsRef<str>` trait. This means that it can accept any type that can be converted into a
     /// string reference.
+    /// * `policy`: The `policy` parameter holds the rules for the username and the password.
     ///
     /// Returns:
     ///
     /// The function `parse` returns a `Result` type with the success case containing a `NewUser` struct
-    /// and the error case containing an `errors::Error` type.
-    pub fn parse<T: AsRef<str>>(username: T, password: T) -> Result<NewUser, errors::Error> {
+    /// and the error case containing an `errors::Error` type. Both fields are checked, the error lists
+    /// the failed checks of the username and of the password.
+    pub async fn parse<T: AsRef<str>>(
+        username: T,
+        password: T,
+        policy: &PolicySettings,
+    ) -> Result<NewUser, errors::Error> {
         tracing::info!("Starting data validation!");
-        let username = Username::parse(username.as_ref())?;
-        let password = Password::parse(password.as_ref())?;
-        tracing::info!("Successfully validated!");
-        Ok(NewUser { username, password })
+        match (
+            Username::parse(username.as_ref(), &policy.username),
+            Password::parse(password.as_ref(), &policy.password).await,
+        ) {
+            (Ok(username), Ok(password)) => {
+                tracing::info!("Successfully validated!");
+                Ok(NewUser { username, password })
+            }
+            (username, password) => {
+                let mut errors = username.err().unwrap_or_default();
+                errors.extend(password.err().unwrap_or_default());
+                Err(errors::Error::validation(errors))
+            }
+        }
     }
     /// The function builds a DB User by converting data and hashing the password.
     ///
+    /// Arguments:
+    ///
+    /// * `settings`: The `settings` parameter holds the argon2 parameters of the hash.
+    ///
     /// Returns:
     ///
     /// a `Result` type with the success variant containing a `User` object and the error variant
     /// containing an `errors::Error` object.
-    pub fn build(&self) -> Result<User, errors::Error> {
+    pub fn build(&self, settings: &Argon2Settings) -> Result<User, errors::Error> {
         tracing::info!("Converting data to DB User!");
-        let hashed_password = hash_password(&self.password.0)?;
+        let hashed_password = hash_password(&self.password.0, settings)?;
         Ok(User {
             id: uuid::Uuid::new_v4(),
             username: self.username.0.to_string(),
             password: hashed_password,
             created_at: OffsetDateTime::now_utc(),
             updated_at: OffsetDateTime::now_utc(),
+            disabled: false,
+            role: common::Role::User.to_string(),
+            session_version: 0,
+            username_key: username_key(&self.username.0),
         })
     }
 }
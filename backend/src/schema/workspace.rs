@@ -0,0 +1,51 @@
+use crate::{db::Workspace, errors};
+
+use common::FieldError;
+use time::OffsetDateTime;
+
+/// The longest name of a workspace, the length of the `workspaces.name` column.
+pub const MAX_NAME_LENGTH: usize = 64;
+
+#[derive(Debug)]
+pub struct WorkspaceName(pub String);
+
+impl WorkspaceName {
+    /// The `parse` function checks the name of a workspace.
+    ///
+    /// Arguments:
+    ///
+    /// * `name`: The `name` parameter is the name from the request. The spaces around it are removed.
+    ///
+    /// Returns:
+    ///
+    /// the `WorkspaceName`, or the `workspace.name_required` or `workspace.name_too_long` field error
+    /// of `name`.
+    pub fn parse(name: &str) -> Result<WorkspaceName, errors::Error> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(errors::Error::validation(vec![FieldError::new(
+                "name",
+                "workspace.name_required",
+            )]));
+        }
+        if name.chars().count() > MAX_NAME_LENGTH {
+            return Err(errors::Error::validation(vec![FieldError::new(
+                "name",
+                "workspace.name_too_long",
+            )
+            .with_param("max", MAX_NAME_LENGTH)]));
+        }
+
+        Ok(WorkspaceName(name.to_owned()))
+    }
+
+    /// The function `build` returns a new team workspace with the name.
+    pub fn build(self) -> Workspace {
+        Workspace {
+            id: uuid::Uuid::new_v4(),
+            name: self.0,
+            personal: false,
+            created_at: OffsetDateTime::now_utc(),
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::app::ShareSettings;
+use crate::errors;
+
+//the number of counted keys above which the finished windows are dropped
+const PRUNE_ABOVE: usize = 1024;
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum AttemptKey {
+    Link(uuid::Uuid),
+    Ip(String),
+}
+
+//the attempts of one key since the start of its window
+struct Window {
+    started: Instant,
+    count: u32,
+}
+
+/// The `ShareAttempts` struct counts the passwords sent for share links, by link and by client address,
+/// so the password of a link can not be guessed. Every guess costs an argon2 hash, the attempt is
+/// counted before it is checked and given back when the password is right. The counts are kept by this
+/// process only, each instance limits on its own.
+pub struct ShareAttempts {
+    settings: ShareSettings,
+    windows: Mutex<HashMap<AttemptKey, Window>>,
+}
+
+impl ShareAttempts {
+    pub fn new(settings: ShareSettings) -> Self {
+        ShareAttempts {
+            settings,
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The function `attempt` counts a password sent for a link.
+    ///
+    /// Arguments:
+    ///
+    /// * `link_id`: The `link_id` parameter is the id of the visited link.
+    /// * `ip`: The `ip` parameter is the client address, if it is known.
+    ///
+    /// Returns:
+    ///
+    /// `share.too_many_attempts` as `RateLimited` if the link or the address used up its attempts, the
+    /// attempt is not counted then.
+    pub fn attempt(&self, link_id: uuid::Uuid, ip: Option<&str>) -> Result<(), errors::Error> {
+        let keys = self.keys(link_id, ip);
+        let window = Duration::from_secs(self.settings.attempt_window * 60);
+        let now = Instant::now();
+        //the counts stay usable after a panic of another request
+        let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+        if windows.len() > PRUNE_ABOVE {
+            windows.retain(|_, counted| now.duration_since(counted.started) < window);
+        }
+
+        for (key, limit) in &keys {
+            let counted = windows.entry(key.clone()).or_insert(Window {
+                started: now,
+                count: 0,
+            });
+            if now.duration_since(counted.started) >= window {
+                *counted = Window {
+                    started: now,
+                    count: 0,
+                };
+            }
+            if counted.count >= *limit {
+                tracing::warn!("Too many password attempts for share link {}", link_id);
+                return Err(errors::Error::from_code(
+                    "share.too_many_attempts",
+                    errors::ErrorTypes::RateLimited,
+                ));
+            }
+        }
+        for (key, _) in keys {
+            if let Some(counted) = windows.get_mut(&key) {
+                counted.count += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// The function `succeeded` gives back the attempt of a right password, the visitors who know it
+    /// are not limited.
+    pub fn succeeded(&self, link_id: uuid::Uuid, ip: Option<&str>) {
+        let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+        for (key, _) in self.keys(link_id, ip) {
+            if let Some(counted) = windows.get_mut(&key) {
+                counted.count = counted.count.saturating_sub(1);
+            }
+        }
+    }
+
+    //the counted keys with their limits, the address only when it is known
+    fn keys(&self, link_id: uuid::Uuid, ip: Option<&str>) -> Vec<(AttemptKey, u32)> {
+        let mut keys = vec![(
+            AttemptKey::Link(link_id),
+            self.settings.max_attempts_per_link,
+        )];
+        if let Some(ip) = ip {
+            keys.push((
+                AttemptKey::Ip(ip.to_owned()),
+                self.settings.max_attempts_per_ip,
+            ));
+        }
+        keys
+    }
+}
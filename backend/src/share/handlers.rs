@@ -0,0 +1,164 @@
+use actix_web::http::header;
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse};
+use common::{ApiResponse, ResponseShareLink, ShareLinkForm, SharedPost, WorkspaceRole};
+use time::OffsetDateTime;
+use tracing::instrument;
+
+use super::{
+    check_password, new_token, owned_share_link, token_hash, validate_expiry, SHARE_PASSWORD_HEADER,
+};
+use crate::attachments::owned_post;
+use crate::audit::{Audit, AuditAction};
+use crate::db::{hash_password, share_link_not_found, ShareLink};
+use crate::errors;
+use crate::workspaces::{workspace_access, workspace_post};
+use crate::{app::AppState, auth::JwtMiddleware};
+
+#[post("/workspaces/{workspace_id}/posts/{id}/share")]
+#[instrument(skip(state, auth, audit), name = "Create share link")]
+async fn create_share_link(
+    state: web::Data<AppState>,
+    auth: JwtMiddleware,
+    audit: Audit,
+    path: web::Path<(uuid::Uuid, uuid::Uuid)>,
+    body: web::Json<ShareLinkForm>,
+) -> Result<HttpResponse, errors::Error> {
+    let (workspace_id, post_id) = path.into_inner();
+    let workspace =
+        workspace_access(&state, workspace_id, auth.user_id, WorkspaceRole::Member).await?;
+    workspace_post(&state, &workspace, post_id, auth.user_id).await?;
+    let post = owned_post(&state, post_id, auth.user_id).await?;
+    let ShareLinkForm {
+        expires_at,
+        password,
+    } = body.into_inner();
+    let now = OffsetDateTime::now_utc();
+    validate_expiry(expires_at, now)?;
+
+    //a blank password is no password
+    let password_hash = match password.filter(|password| !password.is_empty()) {
+        Some(password) => {
+            let settings = state.policy.argon2.clone();
+            Some(
+                state
+                    .tasks
+                    .block(move || hash_password(&password, &settings))
+                    .await??,
+            )
+        }
+        None => None,
+    };
+    let token = new_token();
+    let link = state
+        .shares
+        .add_share_link(ShareLink {
+            id: uuid::Uuid::new_v4(),
+            post_id: post.id,
+            token_hash: token_hash(&token),
+            password_hash,
+            expires_at,
+            created_at: now,
+        })
+        .await?;
+    audit
+        .record(
+            AuditAction::ShareCreate,
+            Some(auth.user_id),
+            None,
+            Some(link.id),
+            true,
+        )
+        .await;
+
+    //the token is only known now, it is not stored
+    let response = ResponseShareLink {
+        token: Some(token),
+        ..ResponseShareLink::from(link)
+    };
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[get("/workspaces/{workspace_id}/posts/{id}/share")]
+#[instrument(skip(state, auth), name = "Get share links")]
+async fn get_share_links(
+    state: web::Data<AppState>,
+    auth: JwtMiddleware,
+    path: web::Path<(uuid::Uuid, uuid::Uuid)>,
+) -> Result<HttpResponse, errors::Error> {
+    let (workspace_id, post_id) = path.into_inner();
+    let workspace =
+        workspace_access(&state, workspace_id, auth.user_id, WorkspaceRole::Member).await?;
+    workspace_post(&state, &workspace, post_id, auth.user_id).await?;
+    let post = owned_post(&state, post_id, auth.user_id).await?;
+    let links: Vec<ResponseShareLink> = state
+        .shares
+        .get_post_share_links(post.id)
+        .await?
+        .into_iter()
+        .map(ResponseShareLink::from)
+        .collect();
+
+    Ok(HttpResponse::Ok().json(links))
+}
+
+#[delete("/share/{id}")]
+#[instrument(skip(state, auth, audit), name = "Revoke share link")]
+async fn delete_share_link(
+    state: web::Data<AppState>,
+    auth: JwtMiddleware,
+    audit: Audit,
+    path: web::Path<uuid::Uuid>,
+) -> Result<HttpResponse, errors::Error> {
+    let link = owned_share_link(&state, path.into_inner(), auth.user_id).await?;
+    state.shares.delete_share_link(link.id).await?;
+    audit
+        .record(
+            AuditAction::ShareRevoke,
+            Some(auth.user_id),
+            None,
+            Some(link.id),
+            true,
+        )
+        .await;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_owned(),
+    }))
+}
+
+#[get("/s/{token}")]
+#[instrument(skip_all, name = "Get shared post")]
+async fn get_shared_post(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<String>,
+) -> Result<HttpResponse, errors::Error> {
+    let link = state
+        .shares
+        .find_share_link_by_token(&token_hash(&path.into_inner()))
+        .await?;
+    if link.is_expired(OffsetDateTime::now_utc()) {
+        return Err(share_link_not_found());
+    }
+    let password = req
+        .headers()
+        .get(SHARE_PASSWORD_HEADER)
+        .and_then(|value| String::from_utf8(value.as_bytes().to_vec()).ok());
+    //only a sent password is a guess, the visitors of open links are not counted
+    let ip = state.audit.client_ip(&req);
+    let guess = link.password_hash.is_some() && password.is_some();
+    if guess {
+        state.share_attempts.attempt(link.id, ip.as_deref())?;
+    }
+    check_password(&state, &link, password).await?;
+    if guess {
+        state.share_attempts.succeeded(link.id, ip.as_deref());
+    }
+    let post = state.notes.find_post(link.post_id).await?;
+
+    Ok(HttpResponse::Ok()
+        //the note may be private, it is not kept by caches nor indexed
+        .insert_header(header::CacheControl(vec![header::CacheDirective::NoStore]))
+        .insert_header(("X-Robots-Tag", "noindex"))
+        .json(SharedPost::from(post)))
+}
@@ -0,0 +1,126 @@
+mod attempts;
+pub mod handlers;
+
+pub use attempts::ShareAttempts;
+
+use actix_web::web;
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use time::OffsetDateTime;
+
+use crate::app::AppState;
+use crate::attachments::content_hash;
+use crate::db::{share_link_not_found, verify_password_hash, ShareLink};
+use crate::errors;
+
+/// The header a visitor sends the password of a share link in. It is not in the address, so it does
+/// not end up in the access logs and the history of the browser.
+pub const SHARE_PASSWORD_HEADER: &str = "x-share-password";
+//the random bytes of a token, 256 bits can not be guessed
+const TOKEN_BYTES: usize = 32;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(handlers::create_share_link)
+        .service(handlers::get_share_links)
+        .service(handlers::delete_share_link)
+        .service(handlers::get_shared_post);
+}
+
+/// The function `new_token` returns a random token for the address of a share link, hex encoded.
+pub fn new_token() -> String {
+    let mut bytes = [0u8; TOKEN_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// The function `token_hash` returns the hash of a token that is stored instead of it.
+pub fn token_hash(token: &str) -> String {
+    content_hash(token.as_bytes())
+}
+
+/// The function `validate_expiry` checks the expiry time of a new link.
+///
+/// Arguments:
+///
+/// * `expires_at`: The `expires_at` parameter is the requested expiry time, if any.
+/// * `now`: The `now` parameter is the current time.
+///
+/// Returns:
+///
+/// `share.expires_in_past` as a field error of `expires_at` if the link would never work.
+pub fn validate_expiry(
+    expires_at: Option<OffsetDateTime>,
+    now: OffsetDateTime,
+) -> Result<(), errors::Error> {
+    match expires_at {
+        Some(expires_at) if expires_at <= now => {
+            Err(errors::Error::validation(vec![errors::FieldError::new(
+                "expires_at",
+                "share.expires_in_past",
+            )]))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The function `check_password` checks the password a visitor sent for a link. The argon2 hash is
+/// CPU heavy, it runs on the blocking pool.
+///
+/// Arguments:
+///
+/// * `state`: The `state` parameter is the app state with the blocking pool.
+/// * `link`: The `link` parameter is the visited link.
+/// * `password`: The `password` parameter is the sent password, if any.
+///
+/// Returns:
+///
+/// `share.password_required` if the link has a password and none was sent, `share.wrong_password` if
+/// it does not match.
+pub async fn check_password(
+    state: &AppState,
+    link: &ShareLink,
+    password: Option<String>,
+) -> Result<(), errors::Error> {
+    let Some(password_hash) = link.password_hash.clone() else {
+        return Ok(());
+    };
+    let Some(password) = password else {
+        return Err(errors::Error::from_code(
+            "share.password_required",
+            errors::ErrorTypes::Auth(errors::Auth::Authentication),
+        ));
+    };
+
+    state
+        .tasks
+        .block(move || verify_password_hash(&password_hash, &password))
+        .await?
+        .map_err(|e| {
+            tracing::warn!("Wrong password for share link {}", link.id);
+            errors::Error::from_code(
+                "share.wrong_password",
+                errors::ErrorTypes::Auth(errors::Auth::Authentication),
+            )
+            .with_cause(e)
+        })
+}
+
+/// The function `owned_share_link` finds a share link of a post of the user. The links of other users
+/// are not found, so their ids can not be probed.
+///
+/// Arguments:
+///
+/// * `state`: The `state` parameter is the app state with the repositories.
+/// * `link_id`: The `link_id` parameter is the id of the link.
+/// * `user_id`: The `user_id` parameter is the logged in user.
+pub async fn owned_share_link(
+    state: &AppState,
+    link_id: uuid::Uuid,
+    user_id: uuid::Uuid,
+) -> Result<ShareLink, errors::Error> {
+    let link = state.shares.find_share_link(link_id).await?;
+    let post = state.notes.find_post(link.post_id).await?;
+    if post.user_id != user_id {
+        return Err(share_link_not_found());
+    }
+    Ok(link)
+}
@@ -0,0 +1,146 @@
+use std::future::Future;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use actix_web::web;
+use tokio::time::Instant;
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
+use tracing::{Instrument, Span};
+
+use crate::errors;
+
+/// The `Tasks` struct coordinates the background work of the server and the graceful shutdown.
+///
+/// Properties:
+///
+/// * `token`: The `token` property is cancelled when the server has stopped. Background tasks and
+/// database jobs that have not started yet check it and stop.
+/// * `tracker`: The `tracker` property keeps track of the spawned background tasks, so the shutdown can
+/// wait for them.
+/// * `draining`: The `draining` property is set as soon as a shutdown signal arrives. The readiness
+/// endpoint reports not-ready from then on.
+#[derive(Clone, Default)]
+pub struct Tasks {
+    token: CancellationToken,
+    tracker: TaskTracker,
+    draining: Arc<AtomicBool>,
+}
+
+impl Tasks {
+    pub fn new() -> Self {
+        Tasks::default()
+    }
+
+    /// The function `block` runs blocking work (database queries) on the actix thread pool like
+    /// `web::block`. The current span stays the parent of the work, and jobs that were still waiting
+    /// for a thread when the server stopped are skipped.
+    ///
+    /// Arguments:
+    ///
+    /// * `f`: The `f` parameter is the blocking closure.
+    ///
+    /// Returns:
+    ///
+    /// a `Result` with the value returned by `f`, or an `errors::Error` if the job was cancelled or the
+    /// thread pool failed.
+    pub async fn block<F, R>(&self, f: F) -> Result<R, errors::Error>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let span = Span::current();
+        let token = self.token.clone();
+
+        web::block(move || {
+            if token.is_cancelled() {
+                return None;
+            }
+            Some(span.in_scope(f))
+        })
+        .await?
+        .ok_or_else(|| {
+            tracing::warn!("Blocking job cancelled by shutdown");
+            errors::Error::from_code("server.shutting_down", errors::ErrorTypes::Unavailable)
+        })
+    }
+
+    /// The function `spawn` starts a background task, for example a scheduler. The future is dropped
+    /// when the shutdown cancels the tasks, so it should not hold work that must not be interrupted.
+    ///
+    /// Arguments:
+    ///
+    /// * `name`: The `name` parameter is used for the task span and the logs.
+    /// * `task`: The `task` parameter is the future to run.
+    pub fn spawn<F>(&self, name: &'static str, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let token = self.token.clone();
+        self.tracker.spawn(
+            async move {
+                tokio::select! {
+                    _ = token.cancelled() => tracing::info!("Background task {} cancelled", name),
+                    _ = task => tracing::info!("Background task {} finished", name),
+                }
+            }
+            .instrument(tracing::info_span!("Background task", name)),
+        );
+    }
+
+    /// The function `begin_drain` marks the server as not ready. Requests are still served.
+    pub fn begin_drain(&self) {
+        self.draining.store(true, Ordering::SeqCst);
+    }
+
+    /// The function `is_draining` returns `true` after a shutdown signal was received.
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::SeqCst)
+    }
+
+    /// The function `shutdown` cancels the background tasks and waits for them to finish.
+    ///
+    /// Arguments:
+    ///
+    /// * `deadline`: The `deadline` parameter is the latest moment to wait for the tasks until. The
+    /// server stops its in-flight requests within the same deadline, so both fit the shutdown timeout.
+    pub async fn shutdown(&self, deadline: Instant) {
+        self.draining.store(true, Ordering::SeqCst);
+        self.token.cancel();
+        self.tracker.close();
+
+        if tokio::time::timeout_at(deadline, self.tracker.wait())
+            .await
+            .is_err()
+        {
+            tracing::error!(
+                "{} background tasks did not stop before the shutdown deadline",
+                self.tracker.len()
+            );
+        }
+    }
+}
+
+/// The function `wait_for_shutdown_signal` completes when the process receives SIGTERM or SIGINT
+/// (Ctrl+C).
+pub async fn wait_for_shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut terminate = signal(SignalKind::terminate()).expect("Failed to listen for SIGTERM");
+        tokio::select! {
+            _ = terminate.recv() => tracing::info!("SIGTERM received"),
+            _ = tokio::signal::ctrl_c() => tracing::info!("SIGINT received"),
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to listen for Ctrl+C");
+        tracing::info!("Ctrl+C received");
+    }
+}
@@ -0,0 +1,162 @@
+use actix_web::{
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    http::header::HeaderMap,
+    middleware::Next,
+    HttpMessage,
+};
+use diesel::connection::{Instrumentation, InstrumentationEvent};
+use opentelemetry::{global, propagation::Extractor, trace::TracerProvider};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{
+    propagation::TraceContextPropagator,
+    trace::{SdkTracer, SdkTracerProvider, SpanExporter},
+    Resource,
+};
+use tracing::{Instrument, Span};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+use crate::{app::TelemetrySettings, logging::RequestId};
+
+/// The function `get_tracer_provider` creates an OpenTelemetry tracer provider that exports spans to
+/// the OTLP/HTTP endpoint from the settings.
+///
+/// Arguments:
+///
+/// * `settings`: The `settings` parameter is of type `&TelemetrySettings`. It contains the collector
+/// endpoint and the service name.
+///
+/// Returns:
+///
+/// a `Result<SdkTracerProvider, String>`.
+pub fn get_tracer_provider(settings: &TelemetrySettings) -> Result<SdkTracerProvider, String> {
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_endpoint(settings.endpoint.as_str())
+        .build()
+        .map_err(|e| format!("Failed to create OTLP exporter: {}", e))?;
+
+    Ok(build_tracer_provider(exporter, &settings.service_name))
+}
+
+/// The function `build_tracer_provider` creates a tracer provider around any span exporter. It is
+/// used by `get_tracer_provider` and can be given an in-memory exporter to inspect exported spans.
+///
+/// Arguments:
+///
+/// * `exporter`: The `exporter` parameter is any OpenTelemetry `SpanExporter`.
+/// * `service_name`: The `service_name` parameter is the value of the `service.name` resource
+/// attribute.
+///
+/// Returns:
+///
+/// The function `build_tracer_provider` returns a `SdkTracerProvider`.
+pub fn build_tracer_provider<E>(exporter: E, service_name: &str) -> SdkTracerProvider
+where
+    E: SpanExporter + 'static,
+{
+    //incoming `traceparent` headers are parsed with the W3C propagator
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
+    SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(
+            Resource::builder()
+                .with_service_name(service_name.to_owned())
+                .build(),
+        )
+        .build()
+}
+
+/// The function `get_tracer` returns the tracer used by the `tracing-opentelemetry` layer.
+pub fn get_tracer(provider: &SdkTracerProvider) -> SdkTracer {
+    provider.tracer("backend")
+}
+
+/// The `HeaderExtractor` struct lets the OpenTelemetry propagator read actix request headers.
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl<'a> Extractor for HeaderExtractor<'a> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|key| key.as_str()).collect()
+    }
+}
+
+/// The middleware `propagate_trace_context` opens a span for every request. If the request has a W3C
+/// `traceparent` header the span continues that trace, so the handler spans become part of the
+/// caller's trace. The span also carries the request id set by `logging::request_id`.
+pub async fn propagate_trace_context(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let parent_context = global::get_text_map_propagator(|propagator| {
+        propagator.extract(&HeaderExtractor(req.headers()))
+    });
+
+    let request_id = req
+        .extensions()
+        .get::<RequestId>()
+        .map(|id| id.0.clone())
+        .unwrap_or_default();
+
+    let span = tracing::info_span!(
+        "HTTP request",
+        otel.kind = "server",
+        request_id = %request_id,
+        http.method = %req.method(),
+        http.target = %req.uri(),
+        http.status_code = tracing::field::Empty,
+    );
+    if let Err(e) = span.set_parent(parent_context) {
+        tracing::warn!("Failed to set parent trace context: {}", e);
+    }
+
+    let response = next.call(req).instrument(span.clone()).await?;
+    span.record("http.status_code", response.status().as_u16());
+
+    Ok(response)
+}
+
+/// The `QueryTracing` struct is a diesel instrumentation that wraps every executed query into a
+/// `db.query` span. Bind values are not recorded.
+#[derive(Default)]
+pub struct QueryTracing {
+    spans: Vec<Span>,
+}
+
+impl Instrumentation for QueryTracing {
+    fn on_connection_event(&mut self, event: InstrumentationEvent<'_>) {
+        match event {
+            InstrumentationEvent::StartQuery { query, .. } => {
+                //drop the `-- binds: [...]` part, it may contain passwords and note contents
+                let query = query.to_string();
+                let statement = query.split(" -- binds:").next().unwrap_or_default();
+                self.spans.push(tracing::info_span!(
+                    "db.query",
+                    otel.kind = "client",
+                    db.system = "postgresql",
+                    db.statement = statement,
+                    error = tracing::field::Empty,
+                ));
+            }
+            InstrumentationEvent::FinishQuery { error, .. } => {
+                if let Some(span) = self.spans.pop() {
+                    if let Some(e) = error {
+                        span.record("error", e.to_string());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The function `init_query_tracing` installs `QueryTracing` on every new diesel connection.
+pub fn init_query_tracing() {
+    diesel::connection::set_default_instrumentation(|| Some(Box::new(QueryTracing::default())))
+        .unwrap_or_else(|e| tracing::error!("Failed to set diesel instrumentation: {}", e));
+}
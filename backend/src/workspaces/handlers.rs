@@ -0,0 +1,204 @@
+use actix_web::{delete, get, patch, post, web, HttpResponse};
+use common::{
+    ApiResponse, MemberForm, MemberUpdateForm, ResponseMember, ResponseWorkspace, WorkspaceForm,
+    WorkspaceRole,
+};
+use time::OffsetDateTime;
+use tracing::instrument;
+
+use super::{invitee, managed_member, member_role, workspace_access};
+use crate::audit::{Audit, AuditAction};
+use crate::db::WorkspaceMember;
+use crate::errors;
+use crate::schema::workspace::WorkspaceName;
+use crate::{app::AppState, auth::JwtMiddleware};
+
+#[get("/workspaces")]
+#[instrument(skip(state, auth), name = "Get workspaces")]
+async fn get_workspaces(
+    state: web::Data<AppState>,
+    auth: JwtMiddleware,
+) -> Result<HttpResponse, errors::Error> {
+    let workspaces = state.workspaces.get_user_workspaces(auth.user_id).await?;
+    Ok(HttpResponse::Ok().json(workspaces))
+}
+
+#[post("/workspaces")]
+#[instrument(skip(state, auth, audit), name = "Create workspace")]
+async fn add_workspace(
+    state: web::Data<AppState>,
+    auth: JwtMiddleware,
+    audit: Audit,
+    body: web::Json<WorkspaceForm>,
+) -> Result<HttpResponse, errors::Error> {
+    let workspace = WorkspaceName::parse(&body.name)?.build();
+    let workspace = state
+        .workspaces
+        .add_workspace(workspace, auth.user_id)
+        .await?;
+    audit
+        .record(
+            AuditAction::WorkspaceCreate,
+            Some(auth.user_id),
+            None,
+            Some(workspace.id),
+            true,
+        )
+        .await;
+
+    let response: ResponseWorkspace = workspace.response(WorkspaceRole::Owner);
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[patch("/workspaces/{workspace_id}")]
+#[instrument(skip(state, auth, audit), name = "Rename workspace")]
+async fn rename_workspace(
+    state: web::Data<AppState>,
+    auth: JwtMiddleware,
+    audit: Audit,
+    path: web::Path<uuid::Uuid>,
+    body: web::Json<WorkspaceForm>,
+) -> Result<HttpResponse, errors::Error> {
+    let workspace_id = path.into_inner();
+    let mut workspace =
+        workspace_access(&state, workspace_id, auth.user_id, WorkspaceRole::Admin).await?;
+    let name = WorkspaceName::parse(&body.name)?.0;
+
+    state
+        .workspaces
+        .rename_workspace(workspace_id, name.clone())
+        .await?;
+    audit
+        .record(
+            AuditAction::WorkspaceRename,
+            Some(auth.user_id),
+            None,
+            Some(workspace_id),
+            true,
+        )
+        .await;
+
+    workspace.name = name;
+    Ok(HttpResponse::Ok().json(workspace))
+}
+
+#[get("/workspaces/{workspace_id}/members")]
+#[instrument(skip(state, auth), name = "Get workspace members")]
+async fn get_members(
+    state: web::Data<AppState>,
+    auth: JwtMiddleware,
+    path: web::Path<uuid::Uuid>,
+) -> Result<HttpResponse, errors::Error> {
+    let workspace_id = path.into_inner();
+    workspace_access(&state, workspace_id, auth.user_id, WorkspaceRole::Member).await?;
+    let members = state.workspaces.get_members(workspace_id).await?;
+
+    Ok(HttpResponse::Ok().json(members))
+}
+
+#[post("/workspaces/{workspace_id}/members")]
+#[instrument(skip(state, auth, audit), name = "Add workspace member")]
+async fn add_member(
+    state: web::Data<AppState>,
+    auth: JwtMiddleware,
+    audit: Audit,
+    path: web::Path<uuid::Uuid>,
+    body: web::Json<MemberForm>,
+) -> Result<HttpResponse, errors::Error> {
+    let workspace_id = path.into_inner();
+    let workspace =
+        workspace_access(&state, workspace_id, auth.user_id, WorkspaceRole::Admin).await?;
+    let MemberForm { username, role } = body.into_inner();
+    let role = member_role(role)?;
+    let user = invitee(&state, &username, &workspace).await?;
+
+    let member = state
+        .workspaces
+        .add_member(WorkspaceMember {
+            workspace_id,
+            user_id: user.id,
+            role: role.to_string(),
+            created_at: OffsetDateTime::now_utc(),
+        })
+        .await?;
+    audit
+        .record(
+            AuditAction::MemberAdd,
+            Some(auth.user_id),
+            None,
+            Some(workspace_id),
+            true,
+        )
+        .await;
+
+    let response: ResponseMember = member.response(user.username);
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[patch("/workspaces/{workspace_id}/members/{username}")]
+#[instrument(skip(state, auth, audit), name = "Change workspace member")]
+async fn update_member(
+    state: web::Data<AppState>,
+    auth: JwtMiddleware,
+    audit: Audit,
+    path: web::Path<(uuid::Uuid, String)>,
+    body: web::Json<MemberUpdateForm>,
+) -> Result<HttpResponse, errors::Error> {
+    let (workspace_id, username) = path.into_inner();
+    workspace_access(&state, workspace_id, auth.user_id, WorkspaceRole::Admin).await?;
+    let role = member_role(body.role)?;
+    let user = managed_member(&state, workspace_id, &username).await?;
+
+    state
+        .workspaces
+        .update_member(workspace_id, user.id, role)
+        .await?;
+    audit
+        .record(
+            AuditAction::MemberChange,
+            Some(auth.user_id),
+            None,
+            Some(workspace_id),
+            true,
+        )
+        .await;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_owned(),
+    }))
+}
+
+#[delete("/workspaces/{workspace_id}/members/{username}")]
+#[instrument(skip(state, auth, audit), name = "Remove workspace member")]
+async fn remove_member(
+    state: web::Data<AppState>,
+    auth: JwtMiddleware,
+    audit: Audit,
+    path: web::Path<(uuid::Uuid, String)>,
+) -> Result<HttpResponse, errors::Error> {
+    let (workspace_id, username) = path.into_inner();
+    workspace_access(&state, workspace_id, auth.user_id, WorkspaceRole::Member).await?;
+    let user = managed_member(&state, workspace_id, &username).await?;
+    //an admin removes a member, or the member leaves the workspace
+    if user.id != auth.user_id {
+        workspace_access(&state, workspace_id, auth.user_id, WorkspaceRole::Admin).await?;
+    }
+
+    state
+        .workspaces
+        .remove_member(workspace_id, user.id)
+        .await?;
+    audit
+        .record(
+            AuditAction::MemberRemove,
+            Some(auth.user_id),
+            None,
+            Some(workspace_id),
+            true,
+        )
+        .await;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        status: "success".to_owned(),
+    }))
+}
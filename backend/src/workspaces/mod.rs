@@ -0,0 +1,170 @@
+pub mod handlers;
+
+use actix_web::web;
+use common::{ResponseWorkspace, WorkspaceRole};
+
+use crate::app::AppState;
+use crate::db::{member_not_found, post_not_found, workspace_not_found, User};
+use crate::errors;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(handlers::get_workspaces)
+        .service(handlers::add_workspace)
+        .service(handlers::rename_workspace)
+        .service(handlers::get_members)
+        .service(handlers::add_member)
+        .service(handlers::update_member)
+        .service(handlers::remove_member);
+}
+
+/// The function `workspace_access` checks that a user may do something in a workspace.
+///
+/// Arguments:
+///
+/// * `state`: The `state` parameter is the app state with the repositories.
+/// * `workspace_id`: The `workspace_id` parameter is the id of the workspace from the path.
+/// * `user_id`: The `user_id` parameter is the logged in user.
+/// * `needed`: The `needed` parameter is the lowest role that is enough, `Member` to read and write
+/// notes and `Admin` to manage the workspace and its members.
+///
+/// Returns:
+///
+/// the workspace with the role of the user, `workspace.not_found` if the user is not a member, so the
+/// workspaces of others can not be told apart from missing ones, or `auth.forbidden` if the role is
+/// too low.
+pub async fn workspace_access(
+    state: &AppState,
+    workspace_id: uuid::Uuid,
+    user_id: uuid::Uuid,
+    needed: WorkspaceRole,
+) -> Result<ResponseWorkspace, errors::Error> {
+    let workspace = state
+        .workspaces
+        .find_membership(workspace_id, user_id)
+        .await?
+        .ok_or_else(workspace_not_found)?;
+    if workspace.role < needed {
+        tracing::warn!(
+            "User {} is no {} of workspace {}",
+            user_id,
+            needed,
+            workspace_id
+        );
+        return Err(errors::Error::from_code(
+            "auth.forbidden",
+            errors::ErrorTypes::Auth(errors::Auth::Authorization),
+        ));
+    }
+    Ok(workspace)
+}
+
+/// The function `workspace_post` checks that a post is reached through the workspace in the path.
+/// The personal workspace also reaches the posts shared with the user, like it lists them.
+///
+/// Arguments:
+///
+/// * `state`: The `state` parameter is the app state with the repositories.
+/// * `workspace`: The `workspace` parameter is the workspace from `workspace_access`.
+/// * `post_id`: The `post_id` parameter is the id of the post.
+/// * `user_id`: The `user_id` parameter is the logged in user.
+///
+/// Returns:
+///
+/// `post.not_found` if there is no such post or it belongs to another workspace.
+pub async fn workspace_post(
+    state: &AppState,
+    workspace: &ResponseWorkspace,
+    post_id: uuid::Uuid,
+    user_id: uuid::Uuid,
+) -> Result<(), errors::Error> {
+    let post = state.notes.find_post(post_id).await?;
+    if post.workspace_id == workspace.id {
+        return Ok(());
+    }
+    let shared = workspace.personal
+        && state
+            .permissions
+            .get_post_permissions(post_id)
+            .await?
+            .iter()
+            .any(|permission| permission.user_id == user_id);
+    match shared {
+        true => Ok(()),
+        false => Err(post_not_found(post_id)),
+    }
+}
+
+/// The function `invitee` finds a user by their username to make them a member.
+///
+/// Arguments:
+///
+/// * `state`: The `state` parameter is the app state with the repositories.
+/// * `username`: The `username` parameter is the username from the request.
+/// * `workspace`: The `workspace` parameter is the workspace the user joins.
+///
+/// Returns:
+///
+/// the user, `user.not_found` if there is none, or `workspace.personal` as a field error of
+/// `username` if the workspace is a personal one, which only has its owner.
+pub async fn invitee(
+    state: &AppState,
+    username: &str,
+    workspace: &ResponseWorkspace,
+) -> Result<User, errors::Error> {
+    if workspace.personal {
+        return Err(errors::Error::validation(vec![errors::FieldError::new(
+            "username",
+            "workspace.personal",
+        )]));
+    }
+    state.users.find_user_by_username(username).await
+}
+
+/// The function `member_role` checks the role of a request, the creator is the only owner of a
+/// workspace.
+///
+/// Returns:
+///
+/// `workspace.invalid_role` as a field error of `role` for `owner`.
+pub fn member_role(role: WorkspaceRole) -> Result<WorkspaceRole, errors::Error> {
+    match role {
+        WorkspaceRole::Owner => Err(errors::Error::validation(vec![errors::FieldError::new(
+            "role",
+            "workspace.invalid_role",
+        )])),
+        role => Ok(role),
+    }
+}
+
+/// The function `managed_member` finds a member whose role is changed or who is removed.
+///
+/// Arguments:
+///
+/// * `state`: The `state` parameter is the app state with the repositories.
+/// * `workspace_id`: The `workspace_id` parameter is the id of the workspace.
+/// * `username`: The `username` parameter is the username from the path.
+///
+/// Returns:
+///
+/// the user, `user.not_found` if there is none, `workspace.member_not_found` if they are not a member
+/// and `auth.forbidden` for the owner, who stays in the workspace with all rights.
+pub async fn managed_member(
+    state: &AppState,
+    workspace_id: uuid::Uuid,
+    username: &str,
+) -> Result<User, errors::Error> {
+    let user = state.users.find_user_by_username(username).await?;
+    let membership = state
+        .workspaces
+        .find_membership(workspace_id, user.id)
+        .await?
+        .ok_or_else(member_not_found)?;
+    if membership.role == WorkspaceRole::Owner {
+        tracing::warn!("The owner of workspace {} can not be changed", workspace_id);
+        return Err(errors::Error::from_code(
+            "auth.forbidden",
+            errors::ErrorTypes::Auth(errors::Auth::Authorization),
+        ));
+    }
+    Ok(user)
+}
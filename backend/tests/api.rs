@@ -0,0 +1,2879 @@
+//! End to end tests of the auth and posts endpoints. The app runs in-process with the in-memory
+//! repository and with an in-memory SQLite database, so no database server is needed.
+
+use actix_http::ws;
+use actix_web::{
+    cookie::Cookie,
+    http::{header, StatusCode},
+    middleware::from_fn,
+    test::{self, TestRequest},
+    web::{self, Bytes, BytesMut},
+    App,
+};
+use backend::app::{self, AppState, Settings};
+use backend::{
+    admin, attachments, auth, collab, errors, events, export, import, notebooks, permissions,
+    share, telemetry, workspaces,
+};
+use common::{CollabDoc, CollabOp, Role};
+use diesel::{sql_types::Text, Connection, RunQueryDsl, SqliteConnection};
+use futures::{channel::mpsc, StreamExt};
+use opentelemetry_sdk::trace::InMemorySpanExporter;
+use serde_json::{json, Value};
+use tokio_util::codec::{Decoder, Encoder};
+use tracing_subscriber::layer::SubscriberExt;
+
+const CONFIG: &str = r#"
+database:
+  backend: "memory"
+  host: "127.0.0.1"
+  port: 5432
+  username: "test"
+  password: "test"
+  database_name: "test"
+auth:
+  access:
+    key: "access-test-key"
+    exp: 15
+    maxage: 15
+  refresh:
+    key: "refresh-test-key"
+    exp: 60
+    maxage: 60
+attachments:
+  max_file_size_mb: 1
+  quota_mb: 2
+share:
+  max_attempts_per_link: 3
+  max_attempts_per_ip: 4
+"#;
+
+const USERNAME: &str = "alice42";
+const PASSWORD: &str = "Password123";
+
+/// The `Backend` enum is the database a test runs against, every test runs against each of them.
+#[derive(Clone, Copy, Debug)]
+enum Backend {
+    Memory,
+    Sqlite,
+}
+
+async fn test_state(backend: Backend) -> web::Data<AppState> {
+    let mut builder = config::Config::builder()
+        .add_source(config::File::from_str(CONFIG, config::FileFormat::Yaml));
+    if let Backend::Sqlite = backend {
+        //the attachments of the SQLite backend are files, every test gets its own folder
+        let attachments = std::path::Path::new(env!("CARGO_TARGET_TMPDIR"))
+            .join(format!("attachments-{}", uuid::Uuid::new_v4()));
+        builder = builder
+            .set_override("database.backend", "sqlite")
+            .and_then(|builder| builder.set_override("database.sqlite_path", ":memory:"))
+            .and_then(|builder| {
+                builder.set_override("attachments.directory", attachments.to_str().unwrap())
+            })
+            .expect("Invalid test configuration");
+    }
+    let settings: Settings = builder
+        .build()
+        .and_then(|config| config.try_deserialize())
+        .expect("Invalid test configuration");
+
+    let state = settings
+        .create_app_state()
+        .expect("Failed to create app state");
+    state
+        .schema
+        .run_pending_migrations()
+        .await
+        .expect("Failed to run the migrations");
+    web::Data::new(state)
+}
+
+//`init_service` returns an opaque type, so the app is built by a macro
+macro_rules! test_app {
+    ($backend:expr) => {
+        test::init_service(
+            App::new()
+                .app_data(test_state($backend).await)
+                .configure(auth::config)
+                .configure(app::config)
+                .configure(export::config)
+                .configure(import::config)
+                .configure(attachments::config)
+                .configure(share::config)
+                .configure(permissions::config)
+                .configure(workspaces::config)
+                .configure(notebooks::config)
+                .configure(events::config)
+                .configure(collab::config),
+        )
+        .await
+    };
+}
+
+fn credentials(username: &str, password: &str) -> Value {
+    json!({"username": username, "password": password})
+}
+
+fn owned_cookies<B>(response: &actix_web::dev::ServiceResponse<B>) -> Vec<Cookie<'static>> {
+    response
+        .response()
+        .cookies()
+        .map(|cookie| cookie.into_owned())
+        .collect()
+}
+
+//the data of the next event of a `/events` stream, `None` if no event comes within a second
+async fn next_event<B>(body: &mut B) -> Option<Value>
+where
+    B: actix_web::body::MessageBody + Unpin,
+    B::Error: std::fmt::Debug,
+{
+    loop {
+        let next = std::future::poll_fn(|cx| std::pin::Pin::new(&mut *body).poll_next(cx));
+        let chunk = tokio::time::timeout(std::time::Duration::from_secs(1), next)
+            .await
+            .ok()??
+            .unwrap();
+        let text = String::from_utf8(chunk.to_vec()).unwrap();
+        if let Some(data) = text.lines().find_map(|line| line.strip_prefix("data: ")) {
+            return Some(serde_json::from_str(data).unwrap());
+        }
+    }
+}
+
+//a WebSocket of the collaborative editing channel: the frames of the editor and the response body
+struct Socket<B> {
+    frames: mpsc::UnboundedSender<Bytes>,
+    body: B,
+    codec: ws::Codec,
+    buffer: BytesMut,
+}
+
+impl<B> Socket<B>
+where
+    B: actix_web::body::MessageBody + Unpin,
+    B::Error: std::fmt::Debug,
+{
+    fn send(&mut self, message: Value) {
+        let mut frame = BytesMut::new();
+        self.codec
+            .encode(ws::Message::Text(message.to_string().into()), &mut frame)
+            .unwrap();
+        self.frames.unbounded_send(frame.freeze()).unwrap();
+    }
+
+    //the next message of the server, `None` if none comes within a second
+    async fn receive(&mut self) -> Option<Value> {
+        loop {
+            while let Some(frame) = self.codec.decode(&mut self.buffer).unwrap() {
+                if let ws::Frame::Text(text) = frame {
+                    return Some(serde_json::from_slice(&text).unwrap());
+                }
+            }
+            let body = &mut self.body;
+            let next = std::future::poll_fn(|cx| std::pin::Pin::new(&mut *body).poll_next(cx));
+            let chunk = tokio::time::timeout(std::time::Duration::from_secs(1), next)
+                .await
+                .ok()??
+                .unwrap();
+            self.buffer.extend_from_slice(&chunk);
+        }
+    }
+}
+
+//the copy of an editor, made from the welcome message
+fn collab_doc(welcome: &Value) -> CollabDoc {
+    let ops: Vec<CollabOp> = serde_json::from_value(welcome["ops"].clone()).unwrap();
+    CollabDoc::from_state(welcome["client_id"].as_u64().unwrap(), &ops).unwrap()
+}
+
+fn apply_update(doc: &mut CollabDoc, update: &Value) {
+    assert_eq!(update["type"], "update");
+    let ops: Vec<CollabOp> = serde_json::from_value(update["ops"].clone()).unwrap();
+    for op in &ops {
+        doc.apply(op).unwrap();
+    }
+}
+
+fn with_cookies(mut request: TestRequest, cookies: &[Cookie<'static>]) -> TestRequest {
+    for cookie in cookies {
+        request = request.cookie(cookie.clone());
+    }
+    request
+}
+
+macro_rules! signup_and_login {
+    ($app:expr) => {
+        signup_and_login!($app, USERNAME)
+    };
+    ($app:expr, $username:expr) => {{
+        let response = test::call_service(
+            &$app,
+            TestRequest::post()
+                .uri("/auth/signup")
+                .set_json(credentials($username, PASSWORD))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        login!($app, $username)
+    }};
+}
+
+macro_rules! login {
+    ($app:expr, $username:expr) => {{
+        let response = test::call_service(
+            &$app,
+            TestRequest::post()
+                .uri("/auth/login")
+                .set_json(credentials($username, PASSWORD))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        owned_cookies(&response)
+    }};
+}
+
+//opens the collaborative editing channel of a post with the WebSocket handshake
+macro_rules! collab_socket {
+    ($app:expr, $uri:expr, $cookies:expr) => {{
+        let request = with_cookies(TestRequest::get().uri($uri), $cookies)
+            .insert_header((header::CONNECTION, "Upgrade"))
+            .insert_header((header::UPGRADE, "websocket"))
+            .insert_header((header::SEC_WEBSOCKET_VERSION, "13"))
+            .insert_header((header::SEC_WEBSOCKET_KEY, "dGhlIHNhbXBsZSBub25jZQ=="))
+            .to_request();
+        let (frames, receiver) = mpsc::unbounded::<Bytes>();
+        let payload: actix_http::BoxedPayloadStream = Box::pin(receiver.map(Ok));
+        let (request, _) = request.replace_payload(payload.into());
+        let response = test::call_service(&$app, request).await;
+        assert_eq!(response.status(), StatusCode::SWITCHING_PROTOCOLS);
+        assert_eq!(
+            response
+                .headers()
+                .get(header::SEC_WEBSOCKET_ACCEPT)
+                .unwrap(),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+        Socket {
+            frames,
+            body: response.into_body(),
+            codec: ws::Codec::new().client_mode(),
+            buffer: BytesMut::new(),
+        }
+    }};
+}
+
+//the tests of the endpoints run against every backend, in the modules `memory` and `sqlite`
+macro_rules! backend_tests {
+    ($($name:ident),* $(,)?) => {
+        mod memory {
+            $(
+                #[actix_web::test]
+                async fn $name() {
+                    super::$name(super::Backend::Memory).await
+                }
+            )*
+        }
+
+        mod sqlite {
+            $(
+                #[actix_web::test]
+                async fn $name() {
+                    super::$name(super::Backend::Sqlite).await
+                }
+            )*
+        }
+    };
+}
+
+//the path of the personal workspace of the logged in user, the notes endpoints are under it
+macro_rules! personal_workspace {
+    ($app:expr, $cookies:expr) => {{
+        let workspaces: Value = test::call_and_read_body_json(
+            &$app,
+            with_cookies(TestRequest::get().uri("/workspaces"), $cookies).to_request(),
+        )
+        .await;
+        assert_eq!(workspaces[0]["personal"], true);
+        format!("/workspaces/{}", workspaces[0]["id"].as_str().unwrap())
+    }};
+}
+
+async fn signup_returns_the_user_without_password(backend: Backend) {
+    let app = test_app!(backend);
+
+    let response = test::call_service(
+        &app,
+        TestRequest::post()
+            .uri("/auth/signup")
+            .set_json(credentials(USERNAME, PASSWORD))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body: Value = test::read_body_json(response).await;
+    assert_eq!(body["username"], USERNAME);
+    assert!(body.get("id").is_some());
+    assert!(body.get("password").is_none());
+}
+
+async fn signup_rejects_duplicate_username(backend: Backend) {
+    let app = test_app!(backend);
+    let request = || {
+        TestRequest::post()
+            .uri("/auth/signup")
+            .set_json(credentials(USERNAME, PASSWORD))
+            .to_request()
+    };
+
+    let response = test::call_service(&app, request()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = test::call_service(&app, request()).await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let body: Value = test::read_body_json(response).await;
+    assert_eq!(body["message"], "The username already exists!");
+    assert_eq!(body["error_type"], "Conflict");
+}
+
+async fn signup_rejects_invalid_credentials(backend: Backend) {
+    let app = test_app!(backend);
+
+    for (username, password) in [("ab", PASSWORD), (USERNAME, "short"), (USERNAME, "letters")] {
+        let response = test::call_service(
+            &app,
+            TestRequest::post()
+                .uri("/auth/signup")
+                .set_json(credentials(username, password))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    //every failed check is returned with its field and code
+    let error: Value = test::call_and_read_body_json(
+        &app,
+        TestRequest::post()
+            .uri("/auth/signup")
+            .set_json(credentials("a_b", "short"))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(error["error_type"], "ValidationError");
+    let codes: Vec<(&str, &str)> = error["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| (e["field"].as_str().unwrap(), e["code"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        codes,
+        [
+            ("username", "username.too_short"),
+            ("username", "username.invalid_characters"),
+            ("password", "password.too_short"),
+            ("password", "password.no_digit"),
+        ]
+    );
+    assert_eq!(error["errors"][0]["params"]["min"], "5");
+}
+
+async fn usernames_are_normalized_and_compared_in_any_case(backend: Backend) {
+    let app = test_app!(backend);
+    signup_and_login!(app);
+    login!(app, "Alice42");
+
+    let signup = |username: &str| {
+        TestRequest::post()
+            .uri("/auth/signup")
+            .set_json(credentials(username, "Pass word!23"))
+            .to_request()
+    };
+    let response = test::call_service(&app, signup("ALICE42")).await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    //full-width characters are stored in their NFKC form
+    let user: Value = test::call_and_read_body_json(&app, signup("ｂｏｂｂｙ1")).await;
+    assert_eq!(user["username"], "bobby1");
+}
+
+async fn login_sets_access_and_refresh_cookies(backend: Backend) {
+    let app = test_app!(backend);
+    let cookies = signup_and_login!(app);
+
+    for name in ["access_token", "refresh_token"] {
+        let cookie = cookies
+            .iter()
+            .find(|cookie| cookie.name() == name)
+            .unwrap_or_else(|| panic!("{} cookie is missing", name));
+        assert!(!cookie.value().is_empty());
+        assert_eq!(cookie.http_only(), Some(true));
+    }
+}
+
+async fn login_rejects_wrong_password_and_unknown_user(backend: Backend) {
+    let app = test_app!(backend);
+    signup_and_login!(app);
+
+    for (username, password) in [(USERNAME, "Password999"), ("nobody42", PASSWORD)] {
+        let response = test::call_service(
+            &app,
+            TestRequest::post()
+                .uri("/auth/login")
+                .set_json(credentials(username, password))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert!(owned_cookies(&response).is_empty());
+    }
+}
+
+async fn error_cause_is_hidden_from_clients(backend: Backend) {
+    let app = test::init_service(
+        App::new()
+            .app_data(test_state(backend).await)
+            .wrap(from_fn(errors::hide_error_cause))
+            .configure(auth::config),
+    )
+    .await;
+    signup_and_login!(app);
+
+    let response = test::call_service(
+        &app,
+        TestRequest::post()
+            .uri("/auth/login")
+            .set_json(credentials(USERNAME, "Password999"))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let body: Value = test::read_body_json(response).await;
+    assert_eq!(body["cause"], Value::Null);
+    assert_eq!(body["message"], "Incorrect password. Try again!");
+}
+
+async fn errors_are_translated_to_the_accepted_language(backend: Backend) {
+    let app = test::init_service(
+        App::new()
+            .app_data(test_state(backend).await)
+            .wrap(from_fn(errors::localize_errors))
+            .configure(auth::config),
+    )
+    .await;
+    signup_and_login!(app);
+
+    let response = test::call_service(
+        &app,
+        TestRequest::post()
+            .uri("/auth/signup")
+            .insert_header(("Accept-Language", "de;q=1, uk-UA;q=0.9, en;q=0.8"))
+            .set_json(credentials(USERNAME, PASSWORD))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    assert_eq!(response.headers().get("Content-Language").unwrap(), "uk");
+    let body: Value = test::read_body_json(response).await;
+    assert_eq!(body["code"], "auth.username_taken");
+    assert_eq!(body["message"], "Це ім'я користувача вже зайняте!");
+
+    let body: Value = test::call_and_read_body_json(
+        &app,
+        TestRequest::post()
+            .uri("/auth/signup")
+            .insert_header(("Accept-Language", "uk"))
+            .set_json(credentials("bobby42", "letters"))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(body["errors"][0]["code"], "password.too_short");
+    assert_eq!(
+        body["errors"][0]["message"],
+        "Пароль має містити щонайменше 8 символів!"
+    );
+
+    //without a supported language the messages stay English
+    let body: Value = test::call_and_read_body_json(
+        &app,
+        TestRequest::post()
+            .uri("/auth/signup")
+            .insert_header(("Accept-Language", "de"))
+            .set_json(credentials(USERNAME, PASSWORD))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(body["message"], "The username already exists!");
+}
+
+#[actix_web::test]
+async fn requests_continue_the_caller_trace_and_queries_hide_their_binds() {
+    let exporter = InMemorySpanExporter::default();
+    let provider = telemetry::build_tracer_provider(exporter.clone(), "backend-test");
+    let subscriber = tracing_subscriber::registry()
+        .with(tracing_opentelemetry::layer().with_tracer(telemetry::get_tracer(&provider)));
+    //the test runtime is single threaded, the spans of the request are made on this thread
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let app = test::init_service(
+        App::new()
+            .wrap(from_fn(telemetry::propagate_trace_context))
+            .route("/", web::get().to(|| async { "ok" })),
+    )
+    .await;
+    let response = test::call_service(
+        &app,
+        TestRequest::get()
+            .uri("/")
+            .insert_header((
+                "traceparent",
+                "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            ))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let mut connection = SqliteConnection::establish(":memory:").unwrap();
+    connection.set_instrumentation(telemetry::QueryTracing::default());
+    diesel::sql_query("SELECT ? AS secret")
+        .bind::<Text, _>("Password123")
+        .execute(&mut connection)
+        .unwrap();
+
+    provider.force_flush().unwrap();
+    let spans = exporter.get_finished_spans().unwrap();
+    let request = spans
+        .iter()
+        .find(|span| span.name == "HTTP request")
+        .unwrap();
+    assert_eq!(
+        request.span_context.trace_id().to_string(),
+        "4bf92f3577b34da6a3ce929d0e0e4736"
+    );
+    assert_eq!(request.parent_span_id.to_string(), "00f067aa0ba902b7");
+
+    let query = spans.iter().find(|span| span.name == "db.query").unwrap();
+    let statement = query
+        .attributes
+        .iter()
+        .find(|attribute| attribute.key.as_str() == "db.statement")
+        .unwrap();
+    assert_eq!(statement.value.as_str(), "SELECT ? AS secret");
+}
+
+async fn refresh_issues_a_new_access_token(backend: Backend) {
+    let app = test_app!(backend);
+    let cookies = signup_and_login!(app);
+
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::get().uri("/auth/refresh"), &cookies).to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let new_cookies = owned_cookies(&response);
+    let body: Value = test::read_body_json(response).await;
+    assert_eq!(body["status"], "success");
+    let access = new_cookies
+        .iter()
+        .find(|cookie| cookie.name() == "access_token")
+        .expect("access_token cookie is missing");
+    assert_eq!(body["new_access"], access.value());
+}
+
+async fn refresh_requires_refresh_token(backend: Backend) {
+    let app = test_app!(backend);
+    let cookies: Vec<_> = signup_and_login!(app)
+        .into_iter()
+        .filter(|cookie| cookie.name() == "access_token")
+        .collect();
+
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::get().uri("/auth/refresh"), &cookies).to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+async fn logout_clears_the_cookies(backend: Backend) {
+    let app = test_app!(backend);
+    let cookies = signup_and_login!(app);
+
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::get().uri("/auth/logout"), &cookies).to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let cleared = owned_cookies(&response);
+    assert_eq!(cleared.len(), 2);
+    for cookie in cleared {
+        assert!(cookie.value().is_empty());
+        assert!(cookie.max_age().is_some_and(|age| !age.is_positive()));
+    }
+}
+
+async fn posts_require_authorization(backend: Backend) {
+    let app = test_app!(backend);
+
+    let response =
+        test::call_service(&app, TestRequest::get().uri("/workspaces").to_request()).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = test::call_service(
+        &app,
+        TestRequest::post()
+            .uri(&format!("/workspaces/{}/posts", uuid::Uuid::new_v4()))
+            .set_json(json!({"title": "Title", "description": null}))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    //a token the server did not sign is not a server error
+    let response = test::call_service(
+        &app,
+        TestRequest::get()
+            .uri("/workspaces")
+            .cookie(Cookie::new("access_token", "not.a.token"))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let body: Value = test::read_body_json(response).await;
+    assert_eq!(body["code"], "auth.invalid_token");
+}
+
+async fn create_and_list_posts(backend: Backend) {
+    let app = test_app!(backend);
+    let cookies = signup_and_login!(app);
+    let workspace = personal_workspace!(app, &cookies);
+
+    let response = test::call_service(
+        &app,
+        with_cookies(
+            TestRequest::post().uri(&format!("{}/posts", workspace)),
+            &cookies,
+        )
+        .set_json(json!({"title": "First", "description": "Body"}))
+        .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let created: Value = test::read_body_json(response).await;
+    assert_eq!(created["title"], "First");
+    assert_eq!(created["description"], "Body");
+    assert_eq!(created["important"], false);
+
+    let response = test::call_service(
+        &app,
+        with_cookies(
+            TestRequest::post().uri(&format!("{}/posts", workspace)),
+            &cookies,
+        )
+        .set_json(json!({"title": "  ", "description": null}))
+        .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = test::call_service(
+        &app,
+        with_cookies(
+            TestRequest::get().uri(&format!("{}/posts", workspace)),
+            &cookies,
+        )
+        .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let posts: Value = test::read_body_json(response).await;
+    assert_eq!(posts.as_array().map(Vec::len), Some(1));
+    assert_eq!(posts[0]["id"], created["id"]);
+}
+
+async fn export_returns_the_notes_as_json_and_markdown_zip(backend: Backend) {
+    let app = test_app!(backend);
+    let cookies = signup_and_login!(app);
+    let workspace = personal_workspace!(app, &cookies);
+
+    let mut ids = Vec::new();
+    for title in ["First", "Second"] {
+        let post: Value = test::call_and_read_body_json(
+            &app,
+            with_cookies(
+                TestRequest::post().uri(&format!("{}/posts", workspace)),
+                &cookies,
+            )
+            .set_json(json!({"title": title, "description": "Body"}))
+            .to_request(),
+        )
+        .await;
+        ids.push(post["id"].clone());
+    }
+    let response = test::call_service(
+        &app,
+        with_cookies(
+            TestRequest::post().uri(&format!("{}/posts/update", workspace)),
+            &cookies,
+        )
+        .set_json(json!({"id": ids[0], "important": true}))
+        .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let document: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(
+            TestRequest::get().uri(&format!("{}/export?format=json", workspace)),
+            &cookies,
+        )
+        .to_request(),
+    )
+    .await;
+    assert_eq!(document["version"], export::EXPORT_VERSION);
+    assert_eq!(document["notes"].as_array().map(Vec::len), Some(2));
+    assert_eq!(document["notes"][0]["id"], ids[0]);
+    assert_eq!(document["notes"][0]["important"], true);
+    assert_eq!(document["notes"][1]["title"], "Second");
+
+    let response = test::call_service(
+        &app,
+        with_cookies(
+            TestRequest::get().uri(&format!("{}/export?format=markdown", workspace)),
+            &cookies,
+        )
+        .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get("Content-Type").unwrap(),
+        "application/zip"
+    );
+    let archive = test::read_body(response).await;
+
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(archive.to_vec())).unwrap();
+    assert_eq!(archive.len(), 2);
+    let mut file = archive.by_index(0).unwrap();
+    assert!(file.name().starts_with("first-") && file.name().ends_with(".md"));
+    let mut note = String::new();
+    std::io::Read::read_to_string(&mut file, &mut note).unwrap();
+    assert!(note.starts_with(&format!("---\nid: {}\n", ids[0].as_str().unwrap())));
+    assert!(note.contains("title: \"First\"\n"));
+    assert!(note.contains("important: true\ntags: []\n---\n\nBody\n"));
+}
+
+//a `multipart/form-data` body with a `file` field per file
+fn multipart(files: &[(&str, &[u8])]) -> (String, Vec<u8>) {
+    let files: Vec<_> = files
+        .iter()
+        .map(|(name, content)| (*name, None, *content))
+        .collect();
+    typed_multipart(&files)
+}
+
+fn typed_multipart(files: &[(&str, Option<&str>, &[u8])]) -> (String, Vec<u8>) {
+    let boundary = "import-test-boundary";
+    let mut body = Vec::new();
+    for (name, content_type, content) in files {
+        body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\n",
+                boundary, name
+            )
+            .as_bytes(),
+        );
+        if let Some(content_type) = content_type {
+            body.extend_from_slice(format!("Content-Type: {}\r\n", content_type).as_bytes());
+        }
+        body.extend_from_slice(b"\r\n");
+        body.extend_from_slice(content);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{}--\r\n", boundary).as_bytes());
+    (format!("multipart/form-data; boundary={}", boundary), body)
+}
+
+async fn import_reads_the_export_and_other_apps_and_reports_every_note(backend: Backend) {
+    let app = test_app!(backend);
+    let alice = signup_and_login!(app);
+    let alice_workspace = personal_workspace!(app, &alice);
+    for title in ["First", "Second"] {
+        let response = test::call_service(
+            &app,
+            with_cookies(
+                TestRequest::post().uri(&format!("{}/posts", alice_workspace)),
+                &alice,
+            )
+            .set_json(json!({"title": title, "description": "Body"}))
+            .to_request(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+    let archive = test::call_and_read_body(
+        &app,
+        with_cookies(
+            TestRequest::get().uri(&format!("{}/export?format=markdown", alice_workspace)),
+            &alice,
+        )
+        .to_request(),
+    )
+    .await;
+
+    let keep = br#"{"title": "", "textContent": "Milk", "isTrashed": true}"#;
+    let portal = br#"{"version": 1, "notes": [{"title": " ", "description": null,
+        "created_at": "2024-01-01T00:00:00Z", "important": false}]}"#;
+    let enex = br#"<?xml version="1.0" encoding="UTF-8"?>
+<en-export><note><title>Trip</title>
+<content><![CDATA[<?xml version="1.0"?><en-note><div>Pack&nbsp;bags</div><ul><li>Tent</li></ul></en-note>]]></content>
+<created>20230115T093000Z</created></note></en-export>"#;
+    let files: [(&str, &[u8]); 4] = [
+        ("notes.zip", &archive),
+        ("Shopping.json", keep),
+        ("notes.json", portal),
+        ("Evernote.enex", enex),
+    ];
+    let (content_type, body) = multipart(&files);
+
+    let bob = signup_and_login!(app, "bobby42");
+    let bob_workspace = personal_workspace!(app, &bob);
+    let report: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(
+            TestRequest::post().uri(&format!("{}/import?dry_run=true", bob_workspace)),
+            &bob,
+        )
+        .insert_header(("Content-Type", content_type.as_str()))
+        .set_payload(body.clone())
+        .to_request(),
+    )
+    .await;
+    assert_eq!(report["dry_run"], true);
+    assert_eq!(
+        (&report["imported"], &report["skipped"], &report["failed"]),
+        (&json!(3), &json!(1), &json!(1))
+    );
+    let statuses: Vec<&str> = report["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["status"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        statuses,
+        ["imported", "imported", "skipped", "failed", "imported"]
+    );
+    assert_eq!(report["items"][2]["error"]["code"], "import.trashed");
+    assert_eq!(
+        report["items"][3]["error"]["errors"][0]["code"],
+        "title.required"
+    );
+    let export: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(
+            TestRequest::get().uri(&format!("{}/export", bob_workspace)),
+            &bob,
+        )
+        .to_request(),
+    )
+    .await;
+    assert_eq!(export["notes"], json!([]));
+
+    let report: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(
+            TestRequest::post().uri(&format!("{}/import", bob_workspace)),
+            &bob,
+        )
+        .insert_header(("Content-Type", content_type.as_str()))
+        .set_payload(body.clone())
+        .to_request(),
+    )
+    .await;
+    assert_eq!(report["imported"], 3);
+    //oldest first, the note from Evernote keeps its date
+    let export: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(
+            TestRequest::get().uri(&format!("{}/export", bob_workspace)),
+            &bob,
+        )
+        .to_request(),
+    )
+    .await;
+    assert_eq!(export["notes"][0]["title"], "Trip");
+    assert_eq!(export["notes"][0]["description"], "Pack\u{a0}bags\n- Tent");
+    assert_eq!(export["notes"][0]["created_at"], "2023-01-15T09:30:00Z");
+    assert_eq!(export["notes"][1]["title"], "First");
+    assert_eq!(export["notes"][1]["description"], "Body");
+
+    //the same upload again only has duplicates
+    let report: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(
+            TestRequest::post().uri(&format!("{}/import", bob_workspace)),
+            &bob,
+        )
+        .insert_header(("Content-Type", content_type.as_str()))
+        .set_payload(body)
+        .to_request(),
+    )
+    .await;
+    assert_eq!(
+        (&report["imported"], &report["skipped"]),
+        (&json!(0), &json!(4))
+    );
+    assert_eq!(report["items"][0]["error"]["code"], "import.duplicate");
+}
+
+async fn important_toggle_moves_post_to_the_top(backend: Backend) {
+    let app = test_app!(backend);
+    let cookies = signup_and_login!(app);
+    let workspace = personal_workspace!(app, &cookies);
+
+    let mut ids = Vec::new();
+    for title in ["Older", "Newer"] {
+        let response = test::call_service(
+            &app,
+            with_cookies(
+                TestRequest::post().uri(&format!("{}/posts", workspace)),
+                &cookies,
+            )
+            .set_json(json!({"title": title, "description": null}))
+            .to_request(),
+        )
+        .await;
+        let post: Value = test::read_body_json(response).await;
+        ids.push(post["id"].clone());
+    }
+
+    let list = || {
+        with_cookies(
+            TestRequest::get().uri(&format!("{}/posts", workspace)),
+            &cookies,
+        )
+        .to_request()
+    };
+    let toggle = |important: bool| {
+        with_cookies(
+            TestRequest::post().uri(&format!("{}/posts/update", workspace)),
+            &cookies,
+        )
+        .set_json(json!({"id": ids[0], "important": important}))
+        .to_request()
+    };
+
+    let posts: Value = test::call_and_read_body_json(&app, list()).await;
+    assert_eq!(posts[0]["id"], ids[1]);
+
+    let response = test::call_service(&app, toggle(true)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let posts: Value = test::call_and_read_body_json(&app, list()).await;
+    assert_eq!(posts[0]["id"], ids[0]);
+    assert_eq!(posts[0]["important"], true);
+    assert_eq!(posts[1]["important"], false);
+
+    let response = test::call_service(&app, toggle(false)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let posts: Value = test::call_and_read_body_json(&app, list()).await;
+    assert_eq!(posts[0]["id"], ids[1]);
+    assert_eq!(posts[1]["important"], false);
+}
+
+async fn disabled_user_can_not_log_in(backend: Backend) {
+    let state = test_state(backend).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(state.clone())
+            .configure(auth::config)
+            .configure(app::config),
+    )
+    .await;
+    let cookies = signup_and_login!(app);
+
+    let user = state.users.find_user_by_username(USERNAME).await.unwrap();
+    state.users.set_disabled(user.id, true).await.unwrap();
+
+    let response = test::call_service(
+        &app,
+        TestRequest::post()
+            .uri("/auth/login")
+            .set_json(credentials(USERNAME, PASSWORD))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let body: Value = test::read_body_json(response).await;
+    assert_eq!(body["message"], "The account is disabled!");
+
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::get().uri("/auth/refresh"), &cookies).to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+async fn admin_endpoints_require_role_and_moderate_users(backend: Backend) {
+    let state = test_state(backend).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(state.clone())
+            .configure(auth::config)
+            .configure(app::config)
+            .configure(workspaces::config)
+            .configure(admin::config),
+    )
+    .await;
+    let member_cookies = signup_and_login!(app, "bobby42");
+    let admin_cookies = signup_and_login!(app);
+
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::get().uri("/admin/users"), &admin_cookies).to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    //the role is read from the token, so the admin logs in again
+    let admin = state.users.find_user_by_username(USERNAME).await.unwrap();
+    state.users.set_role(admin.id, Role::Admin).await.unwrap();
+    let admin_cookies = login!(app, USERNAME);
+
+    let users: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(TestRequest::get().uri("/admin/users"), &admin_cookies).to_request(),
+    )
+    .await;
+    assert_eq!(users.as_array().map(Vec::len), Some(2));
+    let member_id = users[0]["id"].as_str().unwrap().to_string();
+    assert_eq!(users[0]["username"], "bobby42");
+
+    let response = test::call_service(
+        &app,
+        with_cookies(
+            TestRequest::post().uri(&format!("/admin/users/{}/disable", member_id)),
+            &admin_cookies,
+        )
+        .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    //the sessions of the disabled user end at once
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::get().uri("/workspaces"), &member_cookies).to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = test::call_service(
+        &app,
+        with_cookies(
+            TestRequest::post().uri(&format!("/admin/users/{}/enable", uuid::Uuid::new_v4())),
+            &admin_cookies,
+        )
+        .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let stats: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(TestRequest::get().uri("/admin/stats"), &admin_cookies).to_request(),
+    )
+    .await;
+    assert_eq!(stats["users"], 2);
+    assert_eq!(stats["disabled_users"], 1);
+    assert_eq!(stats["admins"], 1);
+}
+
+async fn audit_log_records_logins_for_admins(backend: Backend) {
+    let state = test_state(backend).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(state.clone())
+            .configure(auth::config)
+            .configure(admin::config),
+    )
+    .await;
+    let member_cookies = signup_and_login!(app, "bobby42");
+    signup_and_login!(app);
+
+    let response = test::call_service(
+        &app,
+        TestRequest::post()
+            .uri("/auth/login")
+            .set_json(credentials("bobby42", "WrongPassword1"))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::get().uri("/admin/audit"), &member_cookies).to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let admin = state.users.find_user_by_username(USERNAME).await.unwrap();
+    state.users.set_role(admin.id, Role::Admin).await.unwrap();
+    let admin_cookies = login!(app, USERNAME);
+
+    let events: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(
+            TestRequest::get().uri("/admin/audit?action=auth.login&success=false"),
+            &admin_cookies,
+        )
+        .to_request(),
+    )
+    .await;
+    assert_eq!(events.as_array().map(Vec::len), Some(1));
+    assert_eq!(events[0]["actor_name"], "bobby42");
+
+    //the newest event comes first
+    let events: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(
+            TestRequest::get().uri("/admin/audit?action=auth.login&limit=1"),
+            &admin_cookies,
+        )
+        .to_request(),
+    )
+    .await;
+    assert_eq!(events.as_array().map(Vec::len), Some(1));
+    assert_eq!(events[0]["actor_id"], admin.id.to_string());
+    assert_eq!(events[0]["success"], true);
+}
+
+async fn attachments_are_stored_once_served_in_ranges_and_only_to_the_owner(backend: Backend) {
+    let app = test_app!(backend);
+    let alice = signup_and_login!(app);
+    let alice_workspace = personal_workspace!(app, &alice);
+    let bob = signup_and_login!(app, "bob4242");
+    let post: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(
+            TestRequest::post().uri(&format!("{}/posts", alice_workspace)),
+            &alice,
+        )
+        .set_json(json!({"title": "Screenshots", "description": null}))
+        .to_request(),
+    )
+    .await;
+    let uri = format!(
+        "{}/posts/{}/attachments",
+        alice_workspace,
+        post["id"].as_str().unwrap()
+    );
+
+    let image: &[u8] = b"\x89PNG fake image";
+    let (content_type, body) = typed_multipart(&[
+        ("shot.png", Some("image/png"), image),
+        ("page.html", Some("text/html"), b"<script>alert(1)</script>"),
+    ]);
+    let added: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(TestRequest::post().uri(&uri), &alice)
+            .insert_header(("content-type", content_type.clone()))
+            .set_payload(body.clone())
+            .to_request(),
+    )
+    .await;
+    assert_eq!(added[0]["file_name"], "shot.png");
+    assert_eq!(added[0]["size"], image.len());
+    let listed: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(TestRequest::get().uri(&uri), &alice).to_request(),
+    )
+    .await;
+    assert_eq!(listed, added);
+
+    //images are shown, other types are downloaded
+    let image_uri = format!("/attachments/{}", added[0]["id"].as_str().unwrap());
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::get().uri(&image_uri), &alice).to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let headers = response.headers().clone();
+    assert_eq!(headers.get("content-type").unwrap(), "image/png");
+    assert!(headers
+        .get("content-disposition")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .starts_with("inline; filename=\"shot.png\""));
+    assert_eq!(headers.get("accept-ranges").unwrap(), "bytes");
+    assert_eq!(test::read_body(response).await, image);
+    let response = test::call_service(
+        &app,
+        with_cookies(
+            TestRequest::get().uri(&format!(
+                "/attachments/{}",
+                added[1]["id"].as_str().unwrap()
+            )),
+            &alice,
+        )
+        .to_request(),
+    )
+    .await;
+    assert!(response
+        .headers()
+        .get("content-disposition")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .starts_with("attachment;"));
+
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::get().uri(&image_uri), &alice)
+            .insert_header(("range", "bytes=1-3"))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(
+        response.headers().get("content-range").unwrap(),
+        format!("bytes 1-3/{}", image.len()).as_str()
+    );
+    assert_eq!(test::read_body(response).await, &image[1..4]);
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::get().uri(&image_uri), &alice)
+            .insert_header(("range", "bytes=100-"))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::get().uri(&image_uri), &alice)
+            .insert_header(("if-none-match", headers.get("etag").unwrap().clone()))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+    //other users can not read or add attachments
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::get().uri(&image_uri), &bob).to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::post().uri(&uri), &bob)
+            .insert_header(("content-type", content_type))
+            .set_payload(body)
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    //deleting one of two records with the same content keeps the content
+    let (content_type, body) = typed_multipart(&[("copy.png", Some("image/png"), image)]);
+    let copy: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(TestRequest::post().uri(&uri), &alice)
+            .insert_header(("content-type", content_type))
+            .set_payload(body)
+            .to_request(),
+    )
+    .await;
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::delete().uri(&image_uri), &alice).to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::get().uri(&image_uri), &alice).to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let copy = test::call_and_read_body(
+        &app,
+        with_cookies(
+            TestRequest::get().uri(&format!("/attachments/{}", copy[0]["id"].as_str().unwrap())),
+            &alice,
+        )
+        .to_request(),
+    )
+    .await;
+    assert_eq!(copy, image);
+
+    //the files are limited to 1 MB each and 2 MB per user
+    let large = vec![b'a'; 1024 * 1024 + 1];
+    let (content_type, body) = typed_multipart(&[("large.bin", None, &large)]);
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::post().uri(&uri), &alice)
+            .insert_header(("content-type", content_type))
+            .set_payload(body)
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    let mut statuses = Vec::new();
+    for part in [b'a', b'b', b'c'] {
+        let file = vec![part; 800 * 1024];
+        let (content_type, body) = typed_multipart(&[("part.bin", None, &file)]);
+        let response = test::call_service(
+            &app,
+            with_cookies(TestRequest::post().uri(&uri), &alice)
+                .insert_header(("content-type", content_type))
+                .set_payload(body)
+                .to_request(),
+        )
+        .await;
+        statuses.push(response.status());
+        if response.status() != StatusCode::OK {
+            let error: Value = test::read_body_json(response).await;
+            assert_eq!(error["code"], "attachment.quota_exceeded");
+        }
+    }
+    assert_eq!(
+        statuses,
+        [
+            StatusCode::OK,
+            StatusCode::OK,
+            StatusCode::PAYLOAD_TOO_LARGE
+        ]
+    );
+}
+
+async fn images_are_stripped_resized_and_embedded_in_their_post(backend: Backend) {
+    use image::{codecs::jpeg::JpegEncoder, ImageEncoder, RgbImage};
+
+    let app = test_app!(backend);
+    let alice = signup_and_login!(app);
+    let alice_workspace = personal_workspace!(app, &alice);
+    let post: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(
+            TestRequest::post().uri(&format!("{}/posts", alice_workspace)),
+            &alice,
+        )
+        .set_json(json!({"title": "Screenshots", "description": "See ![shot](upload:shot.jpg)!"}))
+        .to_request(),
+    )
+    .await;
+    let uri = format!(
+        "{}/posts/{}/attachments",
+        alice_workspace,
+        post["id"].as_str().unwrap()
+    );
+
+    //a photo turned by 90 degrees, with the GPS position in its EXIF
+    let mut exif = b"II*\x00\x08\x00\x00\x00\x01\x00\x12\x01\x03\x00\x01\x00\x00\x00\x06\x00\x00\x00\x00\x00\x00\x00".to_vec();
+    exif.extend_from_slice(b"GPS 50.45N 30.52E");
+    let mut photo = Vec::new();
+    let mut encoder = JpegEncoder::new(&mut photo);
+    encoder.set_exif_metadata(exif).unwrap();
+    let pixels = RgbImage::from_fn(1600, 900, |x, y| {
+        image::Rgb([(x % 256) as u8, (y % 256) as u8, 128])
+    });
+    encoder
+        .write_image(&pixels, 1600, 900, image::ExtendedColorType::Rgb8)
+        .unwrap();
+
+    let mut events = test::call_service(
+        &app,
+        with_cookies(TestRequest::get().uri("/events"), &alice).to_request(),
+    )
+    .await
+    .into_body();
+    let (content_type, body) =
+        typed_multipart(&[("shot.jpg", Some("application/octet-stream"), &photo)]);
+    let added: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(TestRequest::post().uri(&uri), &alice)
+            .insert_header(("content-type", content_type))
+            .set_payload(body)
+            .to_request(),
+    )
+    .await;
+    assert_eq!(added[0]["content_type"], "image/jpeg");
+    let image_uri = format!("/attachments/{}", added[0]["id"].as_str().unwrap());
+
+    //the placeholder of the editor became the address of the image
+    let posts: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(
+            TestRequest::get().uri(&format!("{}/posts", alice_workspace)),
+            &alice,
+        )
+        .to_request(),
+    )
+    .await;
+    assert_eq!(
+        posts[0]["description"],
+        format!("See ![shot]({})!", image_uri)
+    );
+    let event = next_event(&mut events).await.unwrap();
+    assert_eq!(event["type"], "post.updated");
+    assert_eq!(event["post"]["description"], posts[0]["description"]);
+
+    for (size, width, height) in [
+        ("original", 900, 1600),
+        ("medium", 720, 1280),
+        ("thumbnail", 180, 320),
+    ] {
+        let response = test::call_service(
+            &app,
+            with_cookies(
+                TestRequest::get().uri(&format!("{}?size={}", image_uri, size)),
+                &alice,
+            )
+            .to_request(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get("content-type").unwrap(),
+            "image/jpeg"
+        );
+        assert!(response
+            .headers()
+            .get("cache-control")
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .contains("immutable"));
+        let data = test::read_body(response).await;
+        assert!(!data.windows(3).any(|window| window == b"GPS"));
+        let image = image::load_from_memory(&data).unwrap();
+        assert_eq!((image.width(), image.height()), (width, height), "{}", size);
+    }
+
+    let (content_type, body) =
+        typed_multipart(&[("broken.png", Some("image/png"), b"\x89PNG\r\n\x1a\nbroken")]);
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::post().uri(&uri), &alice)
+            .insert_header(("content-type", content_type))
+            .set_payload(body)
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body: Value = test::read_body_json(response).await;
+    assert_eq!(body["errors"][0]["code"], "attachment.invalid_image");
+}
+
+async fn share_links_show_one_post_to_visitors_until_revoked(backend: Backend) {
+    let app = test_app!(backend);
+    let alice = signup_and_login!(app);
+    let alice_workspace = personal_workspace!(app, &alice);
+    let bob = signup_and_login!(app, "bob42");
+    let post: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(
+            TestRequest::post().uri(&format!("{}/posts", alice_workspace)),
+            &alice,
+        )
+        .set_json(json!({"title": "Trip plan", "description": "Day one: Lviv"}))
+        .to_request(),
+    )
+    .await;
+    let share_uri = format!(
+        "{}/posts/{}/share",
+        alice_workspace,
+        post["id"].as_str().unwrap()
+    );
+
+    //only the author shares the post, and not into the past
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::post().uri(&share_uri), &bob)
+            .set_json(json!({}))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::post().uri(&share_uri), &alice)
+            .set_json(json!({"expires_at": "2000-01-01T00:00:00Z"}))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body: Value = test::read_body_json(response).await;
+    assert_eq!(body["errors"][0]["code"], "share.expires_in_past");
+
+    let link: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(TestRequest::post().uri(&share_uri), &alice)
+            .set_json(json!({"password": "open sesame", "expires_at": "2100-01-01T00:00:00Z"}))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(link["has_password"], true);
+    let shared_uri = format!("/s/{}", link["token"].as_str().unwrap());
+
+    //visitors need no account, but the password
+    for (password, code) in [
+        (None, "share.password_required"),
+        (Some("wrong"), "share.wrong_password"),
+    ] {
+        let mut request = TestRequest::get().uri(&shared_uri);
+        if let Some(password) = password {
+            request = request.insert_header(("x-share-password", password));
+        }
+        let response = test::call_service(&app, request.to_request()).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let body: Value = test::read_body_json(response).await;
+        assert_eq!(body["code"], code);
+    }
+    let response = test::call_service(
+        &app,
+        TestRequest::get()
+            .uri(&shared_uri)
+            .insert_header(("x-share-password", "open sesame"))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers().get("cache-control").unwrap(), "no-store");
+    let shared: Value = test::read_body_json(response).await;
+    assert_eq!(shared["title"], "Trip plan");
+    assert_eq!(shared["description"], "Day one: Lviv");
+    assert!(shared.get("id").is_none());
+
+    //the token is returned once, the list only has the link
+    let links: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(TestRequest::get().uri(&share_uri), &alice).to_request(),
+    )
+    .await;
+    assert_eq!(links.as_array().unwrap().len(), 1);
+    assert!(links[0].get("token").is_none());
+
+    let revoke_uri = format!("/share/{}", link["id"].as_str().unwrap());
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::delete().uri(&revoke_uri), &bob).to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::delete().uri(&revoke_uri), &alice).to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = test::call_service(
+        &app,
+        TestRequest::get()
+            .uri(&shared_uri)
+            .insert_header(("x-share-password", "open sesame"))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+async fn share_link_passwords_are_limited_by_link_and_address(backend: Backend) {
+    let app = test_app!(backend);
+    let alice = signup_and_login!(app);
+    let alice_workspace = personal_workspace!(app, &alice);
+    let post: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(
+            TestRequest::post().uri(&format!("{}/posts", alice_workspace)),
+            &alice,
+        )
+        .set_json(json!({"title": "Trip plan", "description": "Day one: Lviv"}))
+        .to_request(),
+    )
+    .await;
+    let share_uri = format!(
+        "{}/posts/{}/share",
+        alice_workspace,
+        post["id"].as_str().unwrap()
+    );
+    let mut shared_uris = Vec::new();
+    for _ in 0..2 {
+        let link: Value = test::call_and_read_body_json(
+            &app,
+            with_cookies(TestRequest::post().uri(&share_uri), &alice)
+                .set_json(json!({"password": "open sesame"}))
+                .to_request(),
+        )
+        .await;
+        shared_uris.push(format!("/s/{}", link["token"].as_str().unwrap()));
+    }
+    let visit = |uri: &str, password: &str, ip: &str| {
+        TestRequest::get()
+            .uri(uri)
+            .insert_header(("x-share-password", password))
+            .peer_addr(format!("{}:4000", ip).parse().unwrap())
+            .to_request()
+    };
+
+    //the right password gives its attempt back
+    let response =
+        test::call_service(&app, visit(&shared_uris[0], "open sesame", "10.0.0.1")).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    for _ in 0..3 {
+        let response = test::call_service(&app, visit(&shared_uris[0], "wrong", "10.0.0.1")).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+    //the link is closed for everyone until the window ends, even with the right password
+    for ip in ["10.0.0.1", "10.0.0.2"] {
+        let response = test::call_service(&app, visit(&shared_uris[0], "open sesame", ip)).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let body: Value = test::read_body_json(response).await;
+        assert_eq!(body["code"], "share.too_many_attempts");
+    }
+
+    //the address used up its attempts on the other link too, other addresses did not
+    let response = test::call_service(&app, visit(&shared_uris[1], "wrong", "10.0.0.1")).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response =
+        test::call_service(&app, visit(&shared_uris[1], "open sesame", "10.0.0.1")).await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    let response =
+        test::call_service(&app, visit(&shared_uris[1], "open sesame", "10.0.0.2")).await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+async fn post_permissions_share_notes_with_viewers_and_editors(backend: Backend) {
+    let app = test_app!(backend);
+    let alice = signup_and_login!(app);
+    let alice_workspace = personal_workspace!(app, &alice);
+    let bob = signup_and_login!(app, "bob42");
+    let bob_workspace = personal_workspace!(app, &bob);
+    let carol = signup_and_login!(app, "carol42");
+    let post: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(
+            TestRequest::post().uri(&format!("{}/posts", alice_workspace)),
+            &alice,
+        )
+        .set_json(json!({"title": "Groceries", "description": "Milk"}))
+        .to_request(),
+    )
+    .await;
+    assert_eq!(post["owner"], USERNAME);
+    assert_eq!(post["permission"], "owner");
+    let post_uri = format!("{}/posts/{}", bob_workspace, post["id"].as_str().unwrap());
+    let permissions_uri = format!(
+        "{}/posts/{}/permissions",
+        alice_workspace,
+        post["id"].as_str().unwrap()
+    );
+    //the grantees reach the post through their personal workspace
+    let bob_permissions_uri = format!(
+        "{}/posts/{}/permissions",
+        bob_workspace,
+        post["id"].as_str().unwrap()
+    );
+    let edit = json!({"title": "Groceries", "description": "Milk, bread"});
+
+    //the other users do not see the post until it is shared
+    let posts: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(
+            TestRequest::get().uri(&format!("{}/posts", bob_workspace)),
+            &bob,
+        )
+        .to_request(),
+    )
+    .await;
+    assert!(posts.as_array().unwrap().is_empty());
+    let response = test::call_service(
+        &app,
+        with_cookies(
+            TestRequest::post().uri(&format!("{}/posts/update", bob_workspace)),
+            &bob,
+        )
+        .set_json(json!({"id": post["id"], "important": true}))
+        .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    //only the owner shares, with other users and as viewer or editor
+    for (cookies, body, status, code) in [
+        (
+            &bob,
+            json!({"username": "carol42", "role": "viewer"}),
+            StatusCode::NOT_FOUND,
+            "workspace.not_found",
+        ),
+        (
+            &alice,
+            json!({"username": USERNAME, "role": "viewer"}),
+            StatusCode::BAD_REQUEST,
+            "permission.self",
+        ),
+        (
+            &alice,
+            json!({"username": "bob42", "role": "owner"}),
+            StatusCode::BAD_REQUEST,
+            "permission.invalid_role",
+        ),
+        (
+            &alice,
+            json!({"username": "nobody", "role": "viewer"}),
+            StatusCode::NOT_FOUND,
+            "user.not_found",
+        ),
+    ] {
+        let response = test::call_service(
+            &app,
+            with_cookies(TestRequest::post().uri(&permissions_uri), cookies)
+                .set_json(body)
+                .to_request(),
+        )
+        .await;
+        assert_eq!(response.status(), status);
+        let body: Value = test::read_body_json(response).await;
+        let error_code = body["errors"][0]["code"].as_str().or(body["code"].as_str());
+        assert_eq!(error_code, Some(code));
+    }
+    let granted: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(TestRequest::post().uri(&permissions_uri), &alice)
+            .set_json(json!({"username": "BOB42", "role": "viewer"}))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(granted["username"], "bob42");
+    assert_eq!(granted["role"], "viewer");
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::post().uri(&permissions_uri), &alice)
+            .set_json(json!({"username": "bob42", "role": "editor"}))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    //a viewer reads and stars the post, but can not change it
+    let posts: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(
+            TestRequest::get().uri(&format!("{}/posts", bob_workspace)),
+            &bob,
+        )
+        .to_request(),
+    )
+    .await;
+    assert_eq!(posts[0]["owner"], USERNAME);
+    assert_eq!(posts[0]["permission"], "viewer");
+    let response = test::call_service(
+        &app,
+        with_cookies(
+            TestRequest::post().uri(&format!("{}/posts/update", bob_workspace)),
+            &bob,
+        )
+        .set_json(json!({"id": post["id"], "important": true}))
+        .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::patch().uri(&post_uri), &bob)
+            .set_json(&edit)
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::get().uri(&bob_permissions_uri), &bob).to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    //an editor changes the post for everybody
+    let response = test::call_service(
+        &app,
+        with_cookies(
+            TestRequest::patch().uri(&format!("{}/bob42", permissions_uri)),
+            &alice,
+        )
+        .set_json(json!({"role": "editor"}))
+        .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let edited: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(TestRequest::patch().uri(&post_uri), &bob)
+            .set_json(&edit)
+            .to_request(),
+    )
+    .await;
+    assert_eq!(edited["description"], "Milk, bread");
+    assert_eq!(edited["permission"], "editor");
+    assert_eq!(edited["important"], true);
+    let posts: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(
+            TestRequest::get().uri(&format!("{}/posts", alice_workspace)),
+            &alice,
+        )
+        .to_request(),
+    )
+    .await;
+    assert_eq!(posts[0]["description"], "Milk, bread");
+    assert_eq!(posts[0]["important"], false);
+    let permissions: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(TestRequest::get().uri(&permissions_uri), &alice).to_request(),
+    )
+    .await;
+    assert_eq!(permissions.as_array().unwrap().len(), 1);
+    assert_eq!(permissions[0]["role"], "editor");
+
+    //other users can not revoke, the grantee can leave
+    let response = test::call_service(
+        &app,
+        with_cookies(
+            TestRequest::delete().uri(&format!("{}/bob42", permissions_uri)),
+            &carol,
+        )
+        .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let response = test::call_service(
+        &app,
+        with_cookies(
+            TestRequest::delete().uri(&format!("{}/bob42", bob_permissions_uri)),
+            &bob,
+        )
+        .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let posts: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(
+            TestRequest::get().uri(&format!("{}/posts", bob_workspace)),
+            &bob,
+        )
+        .to_request(),
+    )
+    .await;
+    assert!(posts.as_array().unwrap().is_empty());
+}
+
+async fn workspaces_scope_the_notes_and_manage_their_members(backend: Backend) {
+    let app = test_app!(backend);
+    let alice = signup_and_login!(app);
+    let bob = signup_and_login!(app, "bob42");
+    let alice_workspace = personal_workspace!(app, &alice);
+    let bob_workspace = personal_workspace!(app, &bob);
+
+    //every user starts with a personal workspace named after them
+    let workspaces: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(TestRequest::get().uri("/workspaces"), &alice).to_request(),
+    )
+    .await;
+    assert_eq!(workspaces.as_array().unwrap().len(), 1);
+    assert_eq!(workspaces[0]["name"], USERNAME);
+    assert_eq!(workspaces[0]["role"], "owner");
+
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::post().uri("/workspaces"), &alice)
+            .set_json(json!({"name": "  "}))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let team: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(TestRequest::post().uri("/workspaces"), &alice)
+            .set_json(json!({"name": "Team"}))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(team["personal"], false);
+    assert_eq!(team["role"], "owner");
+    let team_workspace = format!("/workspaces/{}", team["id"].as_str().unwrap());
+    let members_uri = format!("{}/members", team_workspace);
+    let post: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(
+            TestRequest::post().uri(&format!("{}/posts", team_workspace)),
+            &alice,
+        )
+        .set_json(json!({"title": "Plan", "description": "Ship it"}))
+        .to_request(),
+    )
+    .await;
+    assert_eq!(post["workspace_id"], team["id"]);
+    let post_uri = format!("{}/posts/{}", team_workspace, post["id"].as_str().unwrap());
+    let edit = json!({"title": "Plan", "description": "Ship it today"});
+
+    //the notes of a workspace stay in it, and outsiders do not see that it exists
+    let posts: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(
+            TestRequest::get().uri(&format!("{}/posts", alice_workspace)),
+            &alice,
+        )
+        .to_request(),
+    )
+    .await;
+    assert!(posts.as_array().unwrap().is_empty());
+    let response = test::call_service(
+        &app,
+        with_cookies(
+            TestRequest::get().uri(&format!("{}/posts", team_workspace)),
+            &bob,
+        )
+        .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let body: Value = test::read_body_json(response).await;
+    assert_eq!(body["code"], "workspace.not_found");
+
+    //owners and admins invite by username, nobody else becomes an owner
+    for (uri, body, status, code) in [
+        (
+            &members_uri,
+            json!({"username": "bob42", "role": "owner"}),
+            StatusCode::BAD_REQUEST,
+            "workspace.invalid_role",
+        ),
+        (
+            &format!("{}/members", alice_workspace),
+            json!({"username": "bob42"}),
+            StatusCode::BAD_REQUEST,
+            "workspace.personal",
+        ),
+        (
+            &members_uri,
+            json!({"username": "nobody"}),
+            StatusCode::NOT_FOUND,
+            "user.not_found",
+        ),
+    ] {
+        let response = test::call_service(
+            &app,
+            with_cookies(TestRequest::post().uri(uri), &alice)
+                .set_json(body)
+                .to_request(),
+        )
+        .await;
+        assert_eq!(response.status(), status);
+        let body: Value = test::read_body_json(response).await;
+        let error_code = body["errors"][0]["code"].as_str().or(body["code"].as_str());
+        assert_eq!(error_code, Some(code));
+    }
+    let member: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(TestRequest::post().uri(&members_uri), &alice)
+            .set_json(json!({"username": "BOB42"}))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(member["username"], "bob42");
+    assert_eq!(member["role"], "member");
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::post().uri(&members_uri), &alice)
+            .set_json(json!({"username": "bob42"}))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    //a member reads the notes and writes their own, but can not change the others' or invite
+    let posts: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(
+            TestRequest::get().uri(&format!("{}/posts", team_workspace)),
+            &bob,
+        )
+        .to_request(),
+    )
+    .await;
+    assert_eq!(posts[0]["permission"], "viewer");
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::patch().uri(&post_uri), &bob)
+            .set_json(&edit)
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let response = test::call_service(
+        &app,
+        with_cookies(
+            TestRequest::post().uri(&format!("{}/posts", team_workspace)),
+            &bob,
+        )
+        .set_json(json!({"title": "Notes", "description": null}))
+        .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::post().uri(&members_uri), &bob)
+            .set_json(json!({"username": USERNAME}))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    //a note is only reached through its own workspace
+    let response = test::call_service(
+        &app,
+        with_cookies(
+            TestRequest::patch().uri(&format!(
+                "{}/posts/{}",
+                alice_workspace,
+                post["id"].as_str().unwrap()
+            )),
+            &alice,
+        )
+        .set_json(&edit)
+        .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    //an admin edits every note, but the owner stays
+    let response = test::call_service(
+        &app,
+        with_cookies(
+            TestRequest::patch().uri(&format!("{}/bob42", members_uri)),
+            &alice,
+        )
+        .set_json(json!({"role": "admin"}))
+        .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let edited: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(TestRequest::patch().uri(&post_uri), &bob)
+            .set_json(&edit)
+            .to_request(),
+    )
+    .await;
+    assert_eq!(edited["description"], "Ship it today");
+    assert_eq!(edited["permission"], "editor");
+    let response = test::call_service(
+        &app,
+        with_cookies(
+            TestRequest::delete().uri(&format!("{}/{}", members_uri, USERNAME)),
+            &bob,
+        )
+        .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let members: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(TestRequest::get().uri(&members_uri), &bob).to_request(),
+    )
+    .await;
+    assert_eq!(members.as_array().unwrap().len(), 2);
+    assert_eq!(members[0]["role"], "owner");
+    assert_eq!(members[1]["role"], "admin");
+
+    //a member leaves, the notes they wrote stay in the workspace
+    let response = test::call_service(
+        &app,
+        with_cookies(
+            TestRequest::delete().uri(&format!("{}/bob42", members_uri)),
+            &bob,
+        )
+        .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let workspaces: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(TestRequest::get().uri("/workspaces"), &bob).to_request(),
+    )
+    .await;
+    assert_eq!(workspaces.as_array().unwrap().len(), 1);
+    let posts: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(
+            TestRequest::get().uri(&format!("{}/posts", bob_workspace)),
+            &bob,
+        )
+        .to_request(),
+    )
+    .await;
+    assert!(posts.as_array().unwrap().is_empty());
+    let posts: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(
+            TestRequest::get().uri(&format!("{}/posts", team_workspace)),
+            &alice,
+        )
+        .to_request(),
+    )
+    .await;
+    assert_eq!(posts.as_array().unwrap().len(), 2);
+}
+
+async fn notebooks_nest_and_count_their_notes(backend: Backend) {
+    let app = test_app!(backend);
+    let alice = signup_and_login!(app);
+    let bob = signup_and_login!(app, "bob42");
+    let workspace = personal_workspace!(app, &alice);
+    let bob_workspace = personal_workspace!(app, &bob);
+    let notebooks_uri = format!("{}/notebooks", workspace);
+
+    let notebook = |name: &str, parent: Option<&Value>| {
+        let body = json!({"name": name, "parent_id": parent.map(|p| p["id"].clone())});
+        let request = with_cookies(TestRequest::post().uri(&notebooks_uri), &alice)
+            .set_json(body)
+            .to_request();
+        test::call_and_read_body_json::<_, _, Value>(&app, request)
+    };
+    let work = notebook("Work", None).await;
+    let rust = notebook("Rust", Some(&work)).await;
+    let async_rust = notebook("Async", Some(&rust)).await;
+    let home = notebook("Home", None).await;
+    assert_eq!(rust["parent_id"], work["id"]);
+    assert_eq!(async_rust["total"], 0);
+
+    //a note is moved into a notebook of its workspace and out of the notebooks again
+    let add_post = |title: &str| {
+        let request = with_cookies(
+            TestRequest::post().uri(&format!("{}/posts", workspace)),
+            &alice,
+        )
+        .set_json(json!({"title": title, "description": "text"}))
+        .to_request();
+        test::call_and_read_body_json::<_, _, Value>(&app, request)
+    };
+    let plan = add_post("Plan").await;
+    let tokio = add_post("Tokio").await;
+    let groceries = add_post("Groceries").await;
+    let move_uri = |post: &Value| {
+        format!(
+            "{}/posts/{}/notebook",
+            workspace,
+            post["id"].as_str().unwrap()
+        )
+    };
+    for (post, target) in [(&plan, &work), (&tokio, &async_rust), (&groceries, &home)] {
+        let response = test::call_service(
+            &app,
+            with_cookies(TestRequest::patch().uri(&move_uri(post)), &alice)
+                .set_json(json!({"notebook_id": target["id"]}))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::patch().uri(&move_uri(&groceries)), &alice)
+            .set_json(json!({"notebook_id": null}))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    //the tree holds the notes right in a notebook and all notes inside it
+    let tree: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(TestRequest::get().uri(&notebooks_uri), &alice).to_request(),
+    )
+    .await;
+    let counts: Vec<(&str, i64, i64)> = tree
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|n| {
+            (
+                n["name"].as_str().unwrap(),
+                n["notes"].as_i64().unwrap(),
+                n["total"].as_i64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        counts,
+        vec![
+            ("Work", 1, 2),
+            ("Rust", 0, 1),
+            ("Async", 1, 1),
+            ("Home", 0, 0)
+        ]
+    );
+    let posts: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(
+            TestRequest::get().uri(&format!(
+                "{}/{}/posts",
+                notebooks_uri,
+                work["id"].as_str().unwrap()
+            )),
+            &alice,
+        )
+        .to_request(),
+    )
+    .await;
+    let mut titles: Vec<&str> = posts
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["title"].as_str().unwrap())
+        .collect();
+    titles.sort();
+    assert_eq!(titles, vec!["Plan", "Tokio"]);
+    assert!(posts[0]["notebook_id"].is_string());
+
+    //a notebook can not move into itself or a notebook inside it
+    let work_uri = format!("{}/{}", notebooks_uri, work["id"].as_str().unwrap());
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::patch().uri(&work_uri), &alice)
+            .set_json(json!({"name": "Work", "parent_id": async_rust["id"]}))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body: Value = test::read_body_json(response).await;
+    assert_eq!(body["errors"][0]["code"], "notebook.cycle");
+    let moved: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(TestRequest::patch().uri(&work_uri), &alice)
+            .set_json(json!({"name": "Job", "parent_id": home["id"]}))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(moved["name"], "Job");
+    assert_eq!(moved["total"], 2);
+
+    //the notebooks of a workspace are not reached from another one
+    let response = test::call_service(
+        &app,
+        with_cookies(
+            TestRequest::get().uri(&format!(
+                "{}/notebooks/{}/posts",
+                bob_workspace,
+                work["id"].as_str().unwrap()
+            )),
+            &bob,
+        )
+        .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let body: Value = test::read_body_json(response).await;
+    assert_eq!(body["code"], "notebook.not_found");
+
+    //deleting a notebook deletes the ones inside it, their notes stay in the workspace
+    let response = test::call_service(
+        &app,
+        with_cookies(
+            TestRequest::delete().uri(&format!(
+                "{}/{}",
+                notebooks_uri,
+                home["id"].as_str().unwrap()
+            )),
+            &alice,
+        )
+        .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let tree: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(TestRequest::get().uri(&notebooks_uri), &alice).to_request(),
+    )
+    .await;
+    assert!(tree.as_array().unwrap().is_empty());
+    let posts: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(
+            TestRequest::get().uri(&format!("{}/posts", workspace)),
+            &alice,
+        )
+        .to_request(),
+    )
+    .await;
+    let posts = posts.as_array().unwrap();
+    assert_eq!(posts.len(), 3);
+    assert!(posts.iter().all(|p| p["notebook_id"].is_null()));
+}
+
+async fn events_reach_the_users_who_can_see_the_post(backend: Backend) {
+    let app = test_app!(backend);
+    let alice = signup_and_login!(app);
+    let bob = signup_and_login!(app, "bob42");
+    let carol = signup_and_login!(app, "carol42");
+    let team: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(TestRequest::post().uri("/workspaces"), &alice)
+            .set_json(json!({"name": "Team"}))
+            .to_request(),
+    )
+    .await;
+    let team_workspace = format!("/workspaces/{}", team["id"].as_str().unwrap());
+    let response = test::call_service(
+        &app,
+        with_cookies(
+            TestRequest::post().uri(&format!("{}/members", team_workspace)),
+            &alice,
+        )
+        .set_json(json!({"username": "bob42", "role": "member"}))
+        .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    //the stream needs a login
+    let response = test::call_service(&app, TestRequest::get().uri("/events").to_request()).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let mut streams = Vec::new();
+    for cookies in [&alice, &bob, &carol] {
+        let response = test::call_service(
+            &app,
+            with_cookies(TestRequest::get().uri("/events"), cookies).to_request(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get("content-type").unwrap(),
+            "text/event-stream"
+        );
+        streams.push(response.into_body());
+    }
+    let [alice_events, bob_events, carol_events] = &mut streams[..] else {
+        unreachable!()
+    };
+
+    //the members of the workspace get the new post with their own permission
+    let post: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(
+            TestRequest::post().uri(&format!("{}/posts", team_workspace)),
+            &alice,
+        )
+        .set_json(json!({"title": "Plan", "description": "Ship it"}))
+        .to_request(),
+    )
+    .await;
+    let post_uri = format!("{}/posts/{}", team_workspace, post["id"].as_str().unwrap());
+    let event = next_event(bob_events).await.unwrap();
+    assert_eq!(event["type"], "post.created");
+    assert_eq!(event["post_id"], post["id"]);
+    assert_eq!(event["post"]["title"], "Plan");
+    assert_eq!(event["post"]["permission"], "viewer");
+    let event = next_event(alice_events).await.unwrap();
+    assert_eq!(event["post"]["permission"], "owner");
+    assert!(next_event(carol_events).await.is_none());
+
+    //the important flag is personal, only the user's own streams hear about it
+    let response = test::call_service(
+        &app,
+        with_cookies(
+            TestRequest::post().uri(&format!("{}/posts/update", team_workspace)),
+            &bob,
+        )
+        .set_json(json!({"id": post["id"], "important": true}))
+        .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let event = next_event(bob_events).await.unwrap();
+    assert_eq!(event["type"], "post.important_changed");
+    assert_eq!(event["important"], true);
+    let response = test::call_service(
+        &app,
+        with_cookies(
+            TestRequest::post().uri(&format!("{}/posts/update", team_workspace)),
+            &alice,
+        )
+        .set_json(json!({"id": post["id"], "important": true}))
+        .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let event = next_event(alice_events).await.unwrap();
+    assert_eq!(event["type"], "post.important_changed");
+
+    //the change carries no important flag of its author
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::patch().uri(&post_uri), &alice)
+            .set_json(json!({"title": "Plan B", "description": "Ship it later"}))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let event = next_event(alice_events).await.unwrap();
+    assert_eq!(event["type"], "post.updated");
+    assert_eq!(event["post"]["title"], "Plan B");
+    let event = next_event(bob_events).await.unwrap();
+    assert_eq!(event["type"], "post.updated");
+    assert_eq!(event["post"]["description"], "Ship it later");
+    assert_eq!(event["post"]["important"], false);
+
+    //only the author deletes a post, everybody who saw it is told
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::delete().uri(&post_uri), &bob).to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::delete().uri(&post_uri), &alice).to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    for events in [alice_events, bob_events] {
+        let event = next_event(events).await.unwrap();
+        assert_eq!(event["type"], "post.deleted");
+        assert_eq!(event["post_id"], post["id"]);
+        assert!(event["post"].is_null());
+    }
+    assert!(next_event(carol_events).await.is_none());
+    let posts: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(
+            TestRequest::get().uri(&format!("{}/posts", team_workspace)),
+            &bob,
+        )
+        .to_request(),
+    )
+    .await;
+    assert!(posts.as_array().unwrap().is_empty());
+
+    //the imported notes are created like the others
+    let (content_type, body) = multipart(&[("notes.md", b"# Imported\nFrom another app")]);
+    let report: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(
+            TestRequest::post().uri(&format!("{}/import", team_workspace)),
+            &alice,
+        )
+        .insert_header(("Content-Type", content_type.as_str()))
+        .set_payload(body)
+        .to_request(),
+    )
+    .await;
+    assert_eq!(report["imported"], 1);
+    let event = next_event(bob_events).await.unwrap();
+    assert_eq!(event["type"], "post.created");
+    assert_eq!(event["post"]["title"], "Imported");
+    assert!(next_event(carol_events).await.is_none());
+}
+
+async fn collaborative_edits_merge_and_are_stored(backend: Backend) {
+    let app = test_app!(backend);
+    let alice = signup_and_login!(app);
+    let alice_workspace = personal_workspace!(app, &alice);
+    let bob = signup_and_login!(app, "bob42");
+    let bob_workspace = personal_workspace!(app, &bob);
+    let carol = signup_and_login!(app, "carol42");
+    let carol_workspace = personal_workspace!(app, &carol);
+    let post: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(
+            TestRequest::post().uri(&format!("{}/posts", alice_workspace)),
+            &alice,
+        )
+        .set_json(json!({"title": "Draft", "description": "Hello world"}))
+        .to_request(),
+    )
+    .await;
+    let post_id = post["id"].as_str().unwrap();
+    //the users reach the post through their workspace, the shared post through the personal one
+    let collab_uri = |workspace: &str| format!("{}/posts/{}/collab", workspace, post_id);
+    for (username, role) in [("bob42", "editor"), ("carol42", "viewer")] {
+        let response = test::call_service(
+            &app,
+            with_cookies(
+                TestRequest::post().uri(&format!(
+                    "{}/posts/{}/permissions",
+                    alice_workspace, post_id
+                )),
+                &alice,
+            )
+            .set_json(json!({"username": username, "role": role}))
+            .to_request(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    //the channel is a WebSocket of the users who can see the post
+    let response = test::call_service(
+        &app,
+        with_cookies(
+            TestRequest::get().uri(&collab_uri(&alice_workspace)),
+            &alice,
+        )
+        .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body: Value = test::read_body_json(response).await;
+    assert_eq!(body["code"], "collab.websocket_required");
+    let dave = signup_and_login!(app, "dave42");
+    let dave_workspace = personal_workspace!(app, &dave);
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::get().uri(&collab_uri(&dave_workspace)), &dave).to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::get().uri(&collab_uri(&alice_workspace)), &dave).to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let mut alice_socket = collab_socket!(app, &collab_uri(&alice_workspace), &alice);
+    let welcome = alice_socket.receive().await.unwrap();
+    assert_eq!(welcome["type"], "welcome");
+    assert_eq!(welcome["read_only"], false);
+    let mut alice_doc = collab_doc(&welcome);
+    assert_eq!(alice_doc.text(), "Hello world");
+    let mut bob_socket = collab_socket!(app, &collab_uri(&bob_workspace), &bob);
+    let welcome = bob_socket.receive().await.unwrap();
+    assert_eq!(welcome["peers"][0]["username"], USERNAME);
+    let mut bob_doc = collab_doc(&welcome);
+    let presence = alice_socket.receive().await.unwrap();
+    assert_eq!(presence["type"], "presence");
+    assert_eq!(presence["peer"]["username"], "bob42");
+
+    //the changes made at the same time end in the same text for both
+    let alice_ops: Vec<CollabOp> = alice_doc.insert(0, "Dear ").into_iter().collect();
+    let mut bob_ops = bob_doc.delete(5, 6);
+    bob_ops.extend(bob_doc.insert(5, ", everyone"));
+    alice_socket.send(json!({"type": "update", "ops": alice_ops}));
+    bob_socket.send(json!({"type": "update", "ops": bob_ops}));
+    let (to_alice, to_bob) = futures::join!(alice_socket.receive(), bob_socket.receive());
+    apply_update(&mut alice_doc, &to_alice.unwrap());
+    apply_update(&mut bob_doc, &to_bob.unwrap());
+    assert_eq!(alice_doc.text(), "Dear Hello, everyone");
+    assert_eq!(bob_doc.text(), alice_doc.text());
+
+    //the cursors stay at their characters
+    let cursor = json!({"anchor": bob_doc.position(5), "head": bob_doc.position(10)});
+    bob_socket.send(json!({"type": "presence", "cursor": cursor}));
+    let (presence, _) = futures::join!(alice_socket.receive(), bob_socket.receive());
+    let presence = presence.unwrap();
+    assert_eq!(presence["peer"]["username"], "bob42");
+    let head = serde_json::from_value(presence["peer"]["cursor"]["head"].clone()).unwrap();
+    assert_eq!(alice_doc.index(head), 10);
+
+    //viewers follow the text but can not change it
+    let mut carol_socket = collab_socket!(app, &collab_uri(&carol_workspace), &carol);
+    let welcome = carol_socket.receive().await.unwrap();
+    assert_eq!(welcome["read_only"], true);
+    let mut carol_doc = collab_doc(&welcome);
+    assert_eq!(carol_doc.text(), "Dear Hello, everyone");
+    assert_eq!(welcome["peers"].as_array().map(Vec::len), Some(2));
+    let presence = bob_socket.receive().await.unwrap();
+    assert_eq!(presence["peer"]["username"], "carol42");
+    let carol_ops: Vec<CollabOp> = carol_doc.insert(0, "No. ").into_iter().collect();
+    carol_socket.send(json!({"type": "update", "ops": carol_ops}));
+    let refused = carol_socket.receive().await.unwrap();
+    assert_eq!(refused["type"], "error");
+    assert_eq!(refused["code"], "collab.read_only");
+
+    //an editor may not insert characters with the ids of another one
+    let forged =
+        json!([{"op": "insert", "id": {"clock": 100, "client": 0}, "origin": null, "text": "x"}]);
+    bob_socket.send(json!({"type": "update", "ops": forged}));
+    let refused = bob_socket.receive().await.unwrap();
+    assert_eq!(refused["code"], "collab.invalid_update");
+
+    //the merged text is the description once the last editor left
+    drop(carol_socket);
+    drop(bob_socket);
+    let mut left = Vec::new();
+    while let Some(message) = alice_socket.receive().await {
+        if message["type"] == "left" {
+            left.push(message["client_id"].clone());
+        }
+    }
+    assert_eq!(left.len(), 2);
+    drop(alice_socket);
+    let mut description = Value::Null;
+    for _ in 0..50 {
+        let posts: Value = test::call_and_read_body_json(
+            &app,
+            with_cookies(
+                TestRequest::get().uri(&format!("{}/posts", alice_workspace)),
+                &alice,
+            )
+            .to_request(),
+        )
+        .await;
+        description = posts[0]["description"].clone();
+        if description == "Dear Hello, everyone" {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert_eq!(description, "Dear Hello, everyone");
+}
+
+async fn collab_editors_who_fall_behind_are_dropped(backend: Backend) {
+    let app = test_app!(backend);
+    let alice = signup_and_login!(app);
+    let alice_workspace = personal_workspace!(app, &alice);
+    let post: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(
+            TestRequest::post().uri(&format!("{}/posts", alice_workspace)),
+            &alice,
+        )
+        .set_json(json!({"title": "Draft", "description": "Hello"}))
+        .to_request(),
+    )
+    .await;
+    let collab_uri = format!(
+        "{}/posts/{}/collab",
+        alice_workspace,
+        post["id"].as_str().unwrap()
+    );
+    let mut alice_socket = collab_socket!(app, &collab_uri, &alice);
+    let welcome = alice_socket.receive().await.unwrap();
+    let alice_doc = collab_doc(&welcome);
+    let mut reader_socket = collab_socket!(app, &collab_uri, &alice);
+    let welcome = reader_socket.receive().await.unwrap();
+    let reader_id = welcome["client_id"].clone();
+    assert_eq!(alice_socket.receive().await.unwrap()["type"], "presence");
+
+    //the second session reads nothing, the room drops it once its channel is full
+    let mut left = None;
+    for _ in 0..5 {
+        for _ in 0..100 {
+            let cursor = json!({"anchor": alice_doc.position(1), "head": alice_doc.position(2)});
+            alice_socket.send(json!({"type": "presence", "cursor": cursor}));
+        }
+        if let Some(message) = alice_socket.receive().await {
+            left = Some(message);
+            break;
+        }
+    }
+    let left = left.unwrap();
+    assert_eq!(left["type"], "left");
+    assert_eq!(left["client_id"], reader_id);
+
+    //it gets what fit in the channel, then the WebSocket closes
+    let mut received = 0;
+    while reader_socket.receive().await.is_some() {
+        received += 1;
+    }
+    assert!(received <= 256);
+}
+
+async fn collab_sessions_follow_the_changes_of_access(backend: Backend) {
+    let app = test_app!(backend);
+    let alice = signup_and_login!(app);
+    let alice_workspace = personal_workspace!(app, &alice);
+    let bob = signup_and_login!(app, "bob42");
+    let bob_workspace = personal_workspace!(app, &bob);
+    let carol = signup_and_login!(app, "carol42");
+    let carol_workspace = personal_workspace!(app, &carol);
+    let post: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(
+            TestRequest::post().uri(&format!("{}/posts", alice_workspace)),
+            &alice,
+        )
+        .set_json(json!({"title": "Draft", "description": "Hello"}))
+        .to_request(),
+    )
+    .await;
+    let post_id = post["id"].as_str().unwrap();
+    let permissions_uri = format!("{}/posts/{}/permissions", alice_workspace, post_id);
+    for (username, role) in [("bob42", "editor"), ("carol42", "viewer")] {
+        let response = test::call_service(
+            &app,
+            with_cookies(TestRequest::post().uri(&permissions_uri), &alice)
+                .set_json(json!({"username": username, "role": role}))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+    let collab_uri = |workspace: &str| format!("{}/posts/{}/collab", workspace, post_id);
+    let mut alice_socket = collab_socket!(app, &collab_uri(&alice_workspace), &alice);
+    alice_socket.receive().await.unwrap();
+    let mut bob_socket = collab_socket!(app, &collab_uri(&bob_workspace), &bob);
+    let welcome = bob_socket.receive().await.unwrap();
+    assert_eq!(welcome["read_only"], false);
+    let mut carol_socket = collab_socket!(app, &collab_uri(&carol_workspace), &carol);
+    carol_socket.receive().await.unwrap();
+
+    //an editor who became a viewer is sent away at the next check of the access
+    let response = test::call_service(
+        &app,
+        with_cookies(
+            TestRequest::patch().uri(&format!("{}/bob42", permissions_uri)),
+            &alice,
+        )
+        .set_json(json!({"role": "viewer"}))
+        .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let mut codes = Vec::new();
+    for _ in 0..10 {
+        match bob_socket.receive().await {
+            Some(message) if message["type"] == "error" => codes.push(message["code"].clone()),
+            None if !codes.is_empty() => break,
+            _ => {}
+        }
+    }
+    assert_eq!(codes, vec!["collab.access_changed"]);
+    let mut bob_socket = collab_socket!(app, &collab_uri(&bob_workspace), &bob);
+    let welcome = bob_socket.receive().await.unwrap();
+    assert_eq!(welcome["read_only"], true);
+    assert_eq!(collab_doc(&welcome).text(), "Hello");
+
+    //a viewer whose access was revoked is sent away without changing anything
+    let response = test::call_service(
+        &app,
+        with_cookies(
+            TestRequest::delete().uri(&format!("{}/carol42", permissions_uri)),
+            &alice,
+        )
+        .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let mut codes = Vec::new();
+    for _ in 0..10 {
+        match carol_socket.receive().await {
+            Some(message) if message["type"] == "error" => codes.push(message["code"].clone()),
+            None if !codes.is_empty() => break,
+            _ => {}
+        }
+    }
+    assert_eq!(codes, vec!["collab.access_changed"]);
+    drop(alice_socket);
+    drop(bob_socket);
+}
+
+async fn description_edits_reach_the_open_collab_room(backend: Backend) {
+    let app = test_app!(backend);
+    let alice = signup_and_login!(app);
+    let alice_workspace = personal_workspace!(app, &alice);
+    let post: Value = test::call_and_read_body_json(
+        &app,
+        with_cookies(
+            TestRequest::post().uri(&format!("{}/posts", alice_workspace)),
+            &alice,
+        )
+        .set_json(json!({"title": "Draft", "description": "Hello world"}))
+        .to_request(),
+    )
+    .await;
+    let post_uri = format!("{}/posts/{}", alice_workspace, post["id"].as_str().unwrap());
+    let mut socket = collab_socket!(app, &format!("{}/collab", post_uri), &alice);
+    let mut doc = collab_doc(&socket.receive().await.unwrap());
+
+    //the description changed with `PATCH` while the note is edited is sent to the editors
+    let response = test::call_service(
+        &app,
+        with_cookies(TestRequest::patch().uri(&post_uri), &alice)
+            .set_json(json!({"title": "Draft", "description": "Goodbye world"}))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let update = socket.receive().await.unwrap();
+    apply_update(&mut doc, &update);
+    assert_eq!(doc.text(), "Goodbye world");
+
+    //the changes made after it are merged and stored, the room does not store the old text over it
+    let ops: Vec<CollabOp> = doc.insert(13, "!").into_iter().collect();
+    socket.send(json!({"type": "update", "ops": ops}));
+    //the frames of the editor are read while the socket waits for the server
+    assert!(socket.receive().await.is_none());
+    drop(socket);
+    let mut description = Value::Null;
+    for _ in 0..50 {
+        let posts: Value = test::call_and_read_body_json(
+            &app,
+            with_cookies(
+                TestRequest::get().uri(&format!("{}/posts", alice_workspace)),
+                &alice,
+            )
+            .to_request(),
+        )
+        .await;
+        description = posts[0]["description"].clone();
+        if description == "Goodbye world!" {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert_eq!(description, "Goodbye world!");
+}
+
+backend_tests!(
+    signup_returns_the_user_without_password,
+    signup_rejects_duplicate_username,
+    signup_rejects_invalid_credentials,
+    usernames_are_normalized_and_compared_in_any_case,
+    login_sets_access_and_refresh_cookies,
+    login_rejects_wrong_password_and_unknown_user,
+    error_cause_is_hidden_from_clients,
+    errors_are_translated_to_the_accepted_language,
+    refresh_issues_a_new_access_token,
+    refresh_requires_refresh_token,
+    logout_clears_the_cookies,
+    posts_require_authorization,
+    create_and_list_posts,
+    export_returns_the_notes_as_json_and_markdown_zip,
+    import_reads_the_export_and_other_apps_and_reports_every_note,
+    important_toggle_moves_post_to_the_top,
+    disabled_user_can_not_log_in,
+    admin_endpoints_require_role_and_moderate_users,
+    audit_log_records_logins_for_admins,
+    attachments_are_stored_once_served_in_ranges_and_only_to_the_owner,
+    images_are_stripped_resized_and_embedded_in_their_post,
+    share_links_show_one_post_to_visitors_until_revoked,
+    share_link_passwords_are_limited_by_link_and_address,
+    post_permissions_share_notes_with_viewers_and_editors,
+    workspaces_scope_the_notes_and_manage_their_members,
+    notebooks_nest_and_count_their_notes,
+    events_reach_the_users_who_can_see_the_post,
+    collaborative_edits_merge_and_are_stored,
+    collab_editors_who_fall_behind_are_dropped,
+    collab_sessions_follow_the_changes_of_access,
+    description_edits_reach_the_open_collab_room,
+);
@@ -0,0 +1,530 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// The client of the text a document starts with, the stored description of the post.
+pub const SEED_CLIENT: u64 = 0;
+
+/// The `ItemId` struct identifies a character of a collaborative document. The ids are also ordered:
+/// of the characters inserted at the same place, the one with the greater id comes first.
+///
+/// Properties:
+///
+/// * `clock`: The `clock` property is a Lamport clock, it is greater than the clocks of all characters
+/// the client had seen when the character was inserted.
+/// * `client`: The `client` property is the editing session that inserted the character.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ItemId {
+    pub clock: u64,
+    pub client: u64,
+}
+
+/// The `CollabOp` enum is a change of a collaborative document. The operations of all editors can be
+/// applied in any order that keeps the operations of each editor in theirs, every copy of the
+/// document ends with the same text.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum CollabOp {
+    /// Inserts `text` after the character `origin`, at the start if it is `None`. The characters get
+    /// the ids from `id` on, with the clocks counting up.
+    Insert {
+        id: ItemId,
+        origin: Option<ItemId>,
+        text: String,
+    },
+    /// Deletes the `len` characters of the client of `id` with the clocks from the one of `id` on.
+    Delete { id: ItemId, len: u64 },
+}
+
+/// The `CollabCursor` struct is the selection of an editor. The positions are the characters before
+/// them, `None` is the start of the text, so they stay in place when others edit.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct CollabCursor {
+    pub anchor: Option<ItemId>,
+    pub head: Option<ItemId>,
+}
+
+/// The `CollabPeer` struct is an editor of the document, shown to the others.
+///
+/// Properties:
+///
+/// * `client_id`: The `client_id` property is the editing session.
+/// * `user_id`: The `user_id` property is the user.
+/// * `username`: The `username` property is the username of the user.
+/// * `cursor`: The `cursor` property is the last selection of the editor, `None` before they moved it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CollabPeer {
+    pub client_id: u64,
+    pub user_id: Uuid,
+    pub username: String,
+    pub cursor: Option<CollabCursor>,
+}
+
+/// The `CollabClientMessage` enum is a message of an editor on the collaborative editing channel.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CollabClientMessage {
+    /// The changes of the editor, in the order they were made.
+    Update { ops: Vec<CollabOp> },
+    /// The new selection of the editor.
+    Presence { cursor: Option<CollabCursor> },
+}
+
+/// The `CollabServerMessage` enum is a message of the server on the collaborative editing channel.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CollabServerMessage {
+    /// The first message: the session, the whole document and the other editors. Viewers get
+    /// `read_only` and may not send changes.
+    Welcome {
+        client_id: u64,
+        ops: Vec<CollabOp>,
+        peers: Vec<CollabPeer>,
+        read_only: bool,
+    },
+    /// The changes of another editor.
+    Update { ops: Vec<CollabOp> },
+    /// An editor joined or moved their selection.
+    Presence { peer: CollabPeer },
+    /// An editor left.
+    Left { client_id: u64 },
+    /// A message of the editor was refused, `code` is the translation key of the reason.
+    Error { code: String },
+}
+
+/// The `InvalidOp` error means an operation does not fit the document, for example it refers to a
+/// character the document does not have.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InvalidOp;
+
+impl std::fmt::Display for InvalidOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "The operation does not fit the document")
+    }
+}
+
+impl std::error::Error for InvalidOp {}
+
+#[derive(Debug, Clone)]
+struct Item {
+    id: ItemId,
+    origin: Option<ItemId>,
+    value: char,
+    deleted: bool,
+}
+
+/// The `CollabDoc` struct is a copy of a text that several editors change at the same time, a
+/// replicated growable array. Deleted characters are kept, so the changes made before the deletion
+/// still find their place.
+///
+/// Properties:
+///
+/// * `client`: The `client` property is the editing session of this copy, it owns the ids of the
+/// characters inserted here.
+/// * `clock`: The `clock` property is the greatest clock of the characters in the document.
+/// * `items`: The `items` property holds the characters in the order of the text.
+/// * `known`: The `known` property holds the ids of all characters, the operations that arrive again
+/// are ignored.
+#[derive(Debug, Clone)]
+pub struct CollabDoc {
+    client: u64,
+    clock: u64,
+    items: Vec<Item>,
+    known: HashSet<ItemId>,
+}
+
+impl CollabDoc {
+    pub fn new(client: u64) -> Self {
+        CollabDoc {
+            client,
+            clock: 0,
+            items: Vec::new(),
+            known: HashSet::new(),
+        }
+    }
+
+    /// The function `from_text` returns the document that starts with `text`, inserted by
+    /// `SEED_CLIENT`. The same text always gives the same ids.
+    pub fn from_text(text: &str) -> Self {
+        let mut doc = CollabDoc::new(SEED_CLIENT);
+        doc.insert(0, text);
+        doc
+    }
+
+    /// The function `from_state` returns the copy of an editing session from the operations of
+    /// `state`.
+    ///
+    /// Arguments:
+    ///
+    /// * `client`: The `client` parameter is the session the server assigned.
+    /// * `ops`: The `ops` parameter is the whole document as `state` returns it.
+    ///
+    /// Returns:
+    ///
+    /// the document, or `InvalidOp` if the operations do not make one.
+    pub fn from_state(client: u64, ops: &[CollabOp]) -> Result<Self, InvalidOp> {
+        let mut doc = CollabDoc::new(client);
+        for op in ops {
+            doc.apply_op(op, false)?;
+        }
+        Ok(doc)
+    }
+
+    pub fn client(&self) -> u64 {
+        self.client
+    }
+
+    /// The function `text` returns the current text, without the deleted characters.
+    pub fn text(&self) -> String {
+        self.items
+            .iter()
+            .filter(|item| !item.deleted)
+            .map(|item| item.value)
+            .collect()
+    }
+
+    /// The function `char_count` returns the number of characters of the current text.
+    pub fn char_count(&self) -> usize {
+        self.items.iter().filter(|item| !item.deleted).count()
+    }
+
+    /// The function `state` returns the operations that make the whole document, for the editors who
+    /// join. Runs of characters typed one after the other are one insertion.
+    pub fn state(&self) -> Vec<CollabOp> {
+        let mut inserts: Vec<CollabOp> = Vec::new();
+        let mut deleted: Vec<ItemId> = Vec::new();
+        let mut previous: Option<ItemId> = None;
+        for item in &self.items {
+            let follows = previous.is_some_and(|previous| continues(previous, item.id))
+                && item.origin == previous;
+            match inserts.last_mut() {
+                Some(CollabOp::Insert { text, .. }) if follows => text.push(item.value),
+                _ => inserts.push(CollabOp::Insert {
+                    id: item.id,
+                    origin: item.origin,
+                    text: item.value.to_string(),
+                }),
+            }
+            if item.deleted {
+                deleted.push(item.id);
+            }
+            previous = Some(item.id);
+        }
+        inserts.extend(delete_ops(deleted));
+        inserts
+    }
+
+    /// The function `apply` applies the operation of another editor. The operations that were
+    /// applied already are ignored.
+    ///
+    /// Returns:
+    ///
+    /// `InvalidOp` if the operation refers to unknown characters or to the future, or inserts
+    /// characters with a clock that is not greater than the one of their origin; the document is
+    /// unchanged then.
+    pub fn apply(&mut self, op: &CollabOp) -> Result<(), InvalidOp> {
+        self.apply_op(op, true)
+    }
+
+    /// The function `insert` inserts text at a place of the current text.
+    ///
+    /// Arguments:
+    ///
+    /// * `index`: The `index` parameter is the number of characters before the place.
+    /// * `text`: The `text` parameter is the inserted text.
+    ///
+    /// Returns:
+    ///
+    /// the operation for the other editors, or `None` if `text` is empty.
+    pub fn insert(&mut self, index: usize, text: &str) -> Option<CollabOp> {
+        let len = text.chars().count() as u64;
+        if len == 0 {
+            return None;
+        }
+        let id = ItemId {
+            clock: self.clock + 1,
+            client: self.client,
+        };
+        let origin = self.position(index);
+        let op = CollabOp::Insert {
+            id,
+            origin,
+            text: text.to_owned(),
+        };
+        self.integrate(id, origin, text);
+        self.clock += len;
+        Some(op)
+    }
+
+    /// The function `delete` deletes characters of the current text.
+    ///
+    /// Arguments:
+    ///
+    /// * `index`: The `index` parameter is the number of characters before the first deleted one.
+    /// * `len`: The `len` parameter is the number of deleted characters.
+    ///
+    /// Returns:
+    ///
+    /// the operations for the other editors, one for each run of characters of one editor.
+    pub fn delete(&mut self, index: usize, len: usize) -> Vec<CollabOp> {
+        let mut deleted = Vec::new();
+        for item in self
+            .items
+            .iter_mut()
+            .filter(|item| !item.deleted)
+            .skip(index)
+            .take(len)
+        {
+            item.deleted = true;
+            deleted.push(item.id);
+        }
+        delete_ops(deleted)
+    }
+
+    /// The function `position` returns the position of a place of the current text: the character
+    /// before it, `None` for the start.
+    pub fn position(&self, index: usize) -> Option<ItemId> {
+        if index == 0 {
+            return None;
+        }
+        self.items
+            .iter()
+            .filter(|item| !item.deleted)
+            .nth(index - 1)
+            .or_else(|| self.items.iter().rev().find(|item| !item.deleted))
+            .map(|item| item.id)
+    }
+
+    /// The function `index` returns the place of a position in the current text, the number of
+    /// characters before it. The position of a deleted character is where it was.
+    pub fn index(&self, position: Option<ItemId>) -> usize {
+        let Some(id) = position else {
+            return 0;
+        };
+        match self.items.iter().position(|item| item.id == id) {
+            Some(found) => self.items[..=found]
+                .iter()
+                .filter(|item| !item.deleted)
+                .count(),
+            None => 0,
+        }
+    }
+
+    //the states of new sessions come in the order of the text, not of the clocks
+    fn apply_op(&mut self, op: &CollabOp, check_clock: bool) -> Result<(), InvalidOp> {
+        match op {
+            CollabOp::Insert { id, origin, text } => {
+                let len = text.chars().count() as u64;
+                let last = id.clock.checked_add(len).ok_or(InvalidOp)?;
+                if len == 0 || self.known.contains(id) {
+                    return Ok(());
+                }
+                //an editor has seen at most the characters of this copy
+                if check_clock && id.clock > self.clock + 1 {
+                    return Err(InvalidOp);
+                }
+                if origin.is_some_and(|origin| !self.known.contains(&origin)) {
+                    return Err(InvalidOp);
+                }
+                //the clock counts up from the origin, `integrate` relies on it
+                if origin.is_some_and(|origin| id.clock <= origin.clock) {
+                    return Err(InvalidOp);
+                }
+                let taken = (id.clock..last).any(|clock| {
+                    self.known.contains(&ItemId {
+                        clock,
+                        client: id.client,
+                    })
+                });
+                if taken {
+                    return Err(InvalidOp);
+                }
+                self.integrate(*id, *origin, text);
+                self.clock = self.clock.max(last - 1);
+                Ok(())
+            }
+            CollabOp::Delete { id, len } => {
+                let end = id.clock.checked_add(*len).ok_or(InvalidOp)?;
+                let deleted = |item: &Item| {
+                    item.id.client == id.client && (id.clock..end).contains(&item.id.clock)
+                };
+                let found = self.items.iter().filter(|item| deleted(item)).count();
+                if found as u64 != *len {
+                    return Err(InvalidOp);
+                }
+                for item in self.items.iter_mut().filter(|item| deleted(item)) {
+                    item.deleted = true;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    //the characters after the origin with greater ids were inserted later or by a greater client,
+    //they stay first; a run follows its first character directly
+    fn integrate(&mut self, id: ItemId, origin: Option<ItemId>, text: &str) {
+        let mut index = match origin {
+            Some(origin) => self
+                .items
+                .iter()
+                .position(|item| item.id == origin)
+                .map_or(0, |found| found + 1),
+            None => 0,
+        };
+        while index < self.items.len() && self.items[index].id > id {
+            index += 1;
+        }
+
+        let mut previous = origin;
+        let run: Vec<Item> = text
+            .chars()
+            .zip(id.clock..)
+            .map(|(value, clock)| {
+                let item = Item {
+                    id: ItemId {
+                        clock,
+                        client: id.client,
+                    },
+                    origin: previous,
+                    value,
+                    deleted: false,
+                };
+                previous = Some(item.id);
+                item
+            })
+            .collect();
+        self.known.extend(run.iter().map(|item| item.id));
+        self.items.splice(index..index, run);
+    }
+}
+
+//the next character of the same client
+fn continues(previous: ItemId, id: ItemId) -> bool {
+    id.client == previous.client && Some(id.clock) == previous.clock.checked_add(1)
+}
+
+//the deletions of runs of characters of one client
+fn delete_ops(ids: Vec<ItemId>) -> Vec<CollabOp> {
+    let mut ops: Vec<CollabOp> = Vec::new();
+    let mut previous: Option<ItemId> = None;
+    for id in ids {
+        match ops.last_mut() {
+            Some(CollabOp::Delete { len, .. })
+                if previous.is_some_and(|previous| continues(previous, id)) =>
+            {
+                *len += 1
+            }
+            _ => ops.push(CollabOp::Delete { id, len: 1 }),
+        }
+        previous = Some(id);
+    }
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //the copies of two editors that start with the same text
+    fn editors(text: &str) -> (CollabDoc, CollabDoc) {
+        let state = CollabDoc::from_text(text).state();
+        (
+            CollabDoc::from_state(1, &state).unwrap(),
+            CollabDoc::from_state(2, &state).unwrap(),
+        )
+    }
+
+    fn apply_all(doc: &mut CollabDoc, ops: &[CollabOp]) {
+        for op in ops {
+            doc.apply(op).unwrap();
+        }
+    }
+
+    #[test]
+    fn concurrent_inserts_at_the_same_place_converge() {
+        let (mut alice, mut bob) = editors("ac");
+        let from_alice: Vec<CollabOp> = alice.insert(1, "X").into_iter().collect();
+        let from_bob: Vec<CollabOp> = bob.insert(1, "Y").into_iter().collect();
+
+        apply_all(&mut alice, &from_bob);
+        apply_all(&mut bob, &from_alice);
+        assert_eq!(alice.text(), bob.text());
+        //the greater client comes first at the same clock
+        assert_eq!(alice.text(), "aYXc");
+
+        //at the start of the text too, and with runs of characters
+        let from_alice: Vec<CollabOp> = alice.insert(0, "12").into_iter().collect();
+        let from_bob: Vec<CollabOp> = bob.insert(0, "345").into_iter().collect();
+        apply_all(&mut alice, &from_bob);
+        apply_all(&mut bob, &from_alice);
+        assert_eq!(alice.text(), bob.text());
+        assert_eq!(alice.text().len(), 9);
+    }
+
+    #[test]
+    fn operations_that_arrive_again_are_ignored() {
+        let (mut alice, mut bob) = editors("hello");
+        let mut ops: Vec<CollabOp> = alice.insert(5, " world").into_iter().collect();
+        ops.extend(alice.delete(0, 1));
+
+        apply_all(&mut bob, &ops);
+        apply_all(&mut bob, &ops);
+        apply_all(&mut alice, &ops);
+        assert_eq!(bob.text(), "ello world");
+        assert_eq!(alice.text(), bob.text());
+        assert_eq!(alice.state(), bob.state());
+    }
+
+    #[test]
+    fn operations_of_different_editors_converge_in_any_order() {
+        let (mut alice, mut bob) = editors("shared note");
+        let mut carol =
+            CollabDoc::from_state(3, &CollabDoc::from_text("shared note").state()).unwrap();
+        let mut dave = carol.clone();
+        let mut from_alice: Vec<CollabOp> = alice.insert(0, "A ").into_iter().collect();
+        from_alice.extend(alice.delete(2, 7));
+        let mut from_bob: Vec<CollabOp> = bob.delete(7, 4);
+        from_bob.extend(bob.insert(7, "text"));
+        from_bob.extend(bob.insert(0, "B"));
+
+        //the operations of each editor stay in their order, the editors are interleaved
+        apply_all(&mut carol, &from_alice);
+        apply_all(&mut carol, &from_bob);
+        apply_all(&mut dave, &from_bob[..1]);
+        apply_all(&mut dave, &from_alice[..1]);
+        apply_all(&mut dave, &from_bob[1..]);
+        apply_all(&mut dave, &from_alice[1..]);
+        apply_all(&mut alice, &from_bob);
+        apply_all(&mut bob, &from_alice);
+        assert_eq!(carol.text(), "BA text");
+        assert_eq!(dave.text(), carol.text());
+        assert_eq!(alice.text(), carol.text());
+        assert_eq!(bob.text(), carol.text());
+
+        //a character whose origin did not arrive yet is refused, and fits once it did
+        let mut late = CollabDoc::from_state(4, &CollabDoc::from_text("x").state()).unwrap();
+        let mut writer = late.clone();
+        let first = writer.insert(1, "y").unwrap();
+        let second = writer.insert(2, "z").unwrap();
+        assert_eq!(late.apply(&second), Err(InvalidOp));
+        late.apply(&first).unwrap();
+        late.apply(&second).unwrap();
+        assert_eq!(late.text(), writer.text());
+    }
+
+    #[test]
+    fn inserts_not_newer_than_their_origin_are_refused() {
+        let mut doc = CollabDoc::from_text("abc");
+        let origin = doc.position(3).unwrap();
+        for clock in [origin.clock - 1, origin.clock] {
+            let op = CollabOp::Insert {
+                id: ItemId { clock, client: 7 },
+                origin: Some(origin),
+                text: "x".to_owned(),
+            };
+            assert_eq!(doc.apply(&op), Err(InvalidOp));
+        }
+        assert_eq!(doc.text(), "abc");
+    }
+}
@@ -1,38 +1,139 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
+use crate::i18n::{translate, Locale};
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub enum Auth {
     Authentication,
     Authorization,
 }
 
+/// The `ErrorTypes` enum is the kind of an error. The backend picks the HTTP status from it, the
+/// frontend decides how to show the error.
+///
+/// `InternalError` is a failure of the server that is not about the database or the request.
+/// `RequestError` and `DeserializeError` are only created by the frontend, when the request could not
+/// be sent or the response could not be read.
 #[derive(Debug, Serialize, Deserialize, Default, PartialEq, Clone)]
 pub enum ErrorTypes {
     ValidationError,
     DbError,
     Auth(Auth),
     JwtError,
+    Unavailable,
+    NotFound,
+    Conflict,
+    RateLimited,
+    PayloadTooLarge,
+    InternalError,
     #[default]
     RequestError,
     DeserializeError,
 }
 
+impl ErrorTypes {
+    /// The function `status_code` returns the HTTP status of the error type.
+    pub fn status_code(&self) -> u16 {
+        match self {
+            ErrorTypes::ValidationError => 400,
+            ErrorTypes::Auth(Auth::Authentication) => 401,
+            ErrorTypes::Auth(Auth::Authorization) => 403,
+            ErrorTypes::NotFound => 404,
+            ErrorTypes::Conflict => 409,
+            ErrorTypes::PayloadTooLarge => 413,
+            ErrorTypes::RateLimited => 429,
+            ErrorTypes::Unavailable => 503,
+            ErrorTypes::DbError
+            | ErrorTypes::JwtError
+            | ErrorTypes::InternalError
+            | ErrorTypes::RequestError
+            | ErrorTypes::DeserializeError => 500,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
+/// The `FieldError` struct describes one failed check of a form field.
+///
+/// Properties:
+///
+/// * `field`: The `field` property is the name of the field in the request body, for example
+/// `username`.
+/// * `code`: The `code` property is a stable identifier of the failed check, for example
+/// `password.no_digit`. Clients can rely on it, unlike the message. It is also the key of the message in
+/// the catalogs of `i18n`.
+/// * `message`: The `message` property is a human-readable description of the problem.
+/// * `params`: The `params` property holds the values of the placeholders in the message, for example
+/// `min` for `Password must contain at least {min} characters!`.
+pub struct FieldError {
+    pub field: String,
+    pub code: String,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, String>,
+}
+
+impl FieldError {
+    /// The function `new` creates the error of a failed check, with the English message of the code.
+    pub fn new(field: &str, code: &str) -> Self {
+        FieldError {
+            field: field.to_string(),
+            code: code.to_string(),
+            message: translate(Locale::default(), code),
+            params: BTreeMap::new(),
+        }
+    }
+
+    /// The function `with_param` sets the value of a placeholder and fills it in the message.
+    ///
+    /// Arguments:
+    ///
+    /// * `name`: The `name` parameter is the placeholder without the braces, for example `min`.
+    /// * `value`: The `value` parameter is written in place of the placeholder.
+    pub fn with_param(mut self, name: &str, value: impl ToString) -> Self {
+        self.params.insert(name.to_string(), value.to_string());
+        self.message = self.render(Locale::default());
+        self
+    }
+
+    //the message of the code in the locale, with the placeholders filled in
+    fn render(&self, locale: Locale) -> String {
+        self.params
+            .iter()
+            .fold(translate(locale, &self.code), |message, (name, value)| {
+                message.replace(&format!("{{{}}}", name), value)
+            })
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
 /// The `ErrorResponse` struct represents an error response with optional cause, message, and error type
 /// in Rust.
+/// It is the error type of the backend handlers too, so both sides share the same JSON shape.
 ///
 /// Properties:
 ///
 /// * `cause`: An optional string that represents the cause of the error. It provides additional
-/// information about why the error occurred.
+/// information about why the error occurred. It is internal (for example a database error) and is only
+/// sent to clients when the backend runs with `server.expose_error_cause`.
 /// * `message`: The `message` property is an optional string that represents the error message. It
 /// provides additional information about the error that occurred.
 /// * `error_type`: ErrorTypes is an enum that represents the type of error that occurred. It could have
 /// different variants such as BadRequest, NotFound, InternalServerError, etc.
+/// * `errors`: The `errors` property lists every failed field check of a `ValidationError`. It is empty
+/// for the other error types.
+/// * `code`: The `code` property is the catalog key of the message, for example `auth.disabled`.
+/// Messages with a code are translated to the language of the client, the others are always English.
 pub struct ErrorResponse {
     pub cause: Option<String>,
     pub message: Option<String>,
     pub error_type: ErrorTypes,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
 }
 
 impl ErrorResponse {
@@ -41,6 +142,109 @@ impl ErrorResponse {
             cause,
             message,
             error_type,
+            errors: Vec::new(),
+            code: None,
+        }
+    }
+
+    /// The function `from_code` creates an error with the English message of the code.
+    ///
+    /// Arguments:
+    ///
+    /// * `code`: The `code` parameter is the catalog key of the message, for example `auth.disabled`.
+    /// * `error_type`: The `error_type` parameter is the kind of the error.
+    pub fn from_code(code: &str, error_type: ErrorTypes) -> Self {
+        ErrorResponse {
+            message: Some(translate(Locale::default(), code)),
+            code: Some(code.to_string()),
+            ..ErrorResponse::new(None, None, error_type)
+        }
+    }
+
+    /// The function `with_cause` sets the internal cause of the error.
+    pub fn with_cause(self, cause: impl ToString) -> Self {
+        ErrorResponse {
+            cause: Some(cause.to_string()),
+            ..self
+        }
+    }
+
+    /// The function `localize` translates the message and the messages of the field errors that have a
+    /// code.
+    pub fn localize(&mut self, locale: Locale) {
+        if let Some(code) = &self.code {
+            self.message = Some(translate(locale, code));
+        }
+        for error in self.errors.iter_mut() {
+            error.message = error.render(locale);
+        }
+        if !self.errors.is_empty() {
+            self.message = Some(join_messages(&self.errors));
+        }
+    }
+
+    /// The function `validation` creates a `ValidationError` from the failed field checks. The
+    /// message joins the messages of all checks.
+    ///
+    /// Arguments:
+    ///
+    /// * `errors`: The `errors` parameter is the list of failed checks, it should not be empty.
+    pub fn validation(errors: Vec<FieldError>) -> Self {
+        ErrorResponse {
+            message: Some(join_messages(&errors)),
+            errors,
+            ..ErrorResponse::new(None, None, ErrorTypes::ValidationError)
+        }
+    }
+
+    /// The function `field_errors` returns the failed checks of one field.
+    pub fn field_errors<'a>(&'a self, field: &'a str) -> impl Iterator<Item = &'a FieldError> {
+        self.errors.iter().filter(move |error| error.field == field)
+    }
+}
+
+//the message of a validation error lists the messages of all checks
+fn join_messages(errors: &[FieldError]) -> String {
+    errors
+        .iter()
+        .map(|error| error.message.as_str())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl std::fmt::Display for ErrorResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let json_string = serde_json::to_string_pretty(self).map_err(|_| std::fmt::Error)?;
+        write!(f, "{}", json_string)
+    }
+}
+
+impl std::error::Error for ErrorResponse {}
+
+//the backend returns `ErrorResponse` from its handlers, the frontend builds without actix
+#[cfg(feature = "actix")]
+mod actix {
+    use actix_web::error::BlockingError;
+    use actix_web::http::StatusCode;
+    use actix_web::{HttpResponse, ResponseError};
+
+    use super::{ErrorResponse, ErrorTypes};
+
+    impl ResponseError for ErrorResponse {
+        fn status_code(&self) -> StatusCode {
+            StatusCode::from_u16(self.error_type.status_code())
+                .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+
+        fn error_response(&self) -> HttpResponse {
+            HttpResponse::build(self.status_code()).json(self)
+        }
+    }
+
+    //convert error from 'web::block(||).await' to custom Error
+    impl From<BlockingError> for ErrorResponse {
+        fn from(value: BlockingError) -> Self {
+            ErrorResponse::new(Some(value.to_string()), None, ErrorTypes::DbError)
         }
     }
 }
@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::ResponsePost;
+
+/// The `PostEventKind` enum is what happened to a post. It is also the name of the event in the
+/// `/events` stream.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PostEventKind {
+    #[serde(rename = "post.created")]
+    Created,
+    #[serde(rename = "post.updated")]
+    Updated,
+    #[serde(rename = "post.deleted")]
+    Deleted,
+    #[serde(rename = "post.important_changed")]
+    ImportantChanged,
+}
+
+impl PostEventKind {
+    /// All kinds, for the listeners of the events.
+    pub const ALL: [PostEventKind; 4] = [
+        PostEventKind::Created,
+        PostEventKind::Updated,
+        PostEventKind::Deleted,
+        PostEventKind::ImportantChanged,
+    ];
+
+    /// The function `as_str` returns the name of the event.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PostEventKind::Created => "post.created",
+            PostEventKind::Updated => "post.updated",
+            PostEventKind::Deleted => "post.deleted",
+            PostEventKind::ImportantChanged => "post.important_changed",
+        }
+    }
+}
+
+impl std::fmt::Display for PostEventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+/// The `PostEvent` struct is the data of an event of the `/events` stream. Every user gets the
+/// events of the posts they can see.
+///
+/// Properties:
+///
+/// * `kind`: The `kind` property is what happened, it is sent as `type`.
+/// * `post_id`: The `post_id` property is the id of the post.
+/// * `workspace_id`: The `workspace_id` property is the workspace of the post.
+/// * `post`: The `post` property is the post as the user sees it for `post.created` and
+/// `post.updated`. The `important` flag is the one of the user only in `post.created`.
+/// * `important`: The `important` property is the new important flag of the user for
+/// `post.important_changed`.
+pub struct PostEvent {
+    #[serde(rename = "type")]
+    pub kind: PostEventKind,
+    pub post_id: Uuid,
+    pub workspace_id: Uuid,
+    #[serde(default)]
+    pub post: Option<ResponsePost>,
+    #[serde(default)]
+    pub important: Option<bool>,
+}
@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
 
-#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+use crate::REDACTED;
+
+#[derive(Serialize, Deserialize, Default, Clone)]
 /// The `UserFormData` struct represents user input data including a username and password.
 ///
 /// Properties:
@@ -13,7 +16,16 @@ pub struct UserFormData {
     pub password: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+impl std::fmt::Debug for UserFormData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UserFormData")
+            .field("username", &self.username)
+            .field("password", &REDACTED)
+            .finish()
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, Clone)]
 /// The `PostsFormData` struct represents data for creating a post, including a title and an optional
 /// description.
 ///
@@ -28,6 +40,15 @@ pub struct PostsFormData {
     pub description: Option<String>,
 }
 
+impl std::fmt::Debug for PostsFormData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PostsFormData")
+            .field("title", &self.title)
+            .field("description", &self.description.as_ref().map(|_| REDACTED))
+            .finish()
+    }
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 /// The `PostsUpdateForm` struct represents a form used to update posts, containing an ID and a flag
 /// indicating importance.
@@ -42,3 +63,119 @@ pub struct PostsUpdateForm {
     pub id: uuid::Uuid,
     pub important: bool,
 }
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+/// The `RoleForm` struct is the body of the request that changes the role of a user.
+///
+/// Properties:
+///
+/// * `role`: The `role` property is the new role of the user.
+pub struct RoleForm {
+    pub role: crate::Role,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// The `PermissionForm` struct is the body of the request that gives another user access to a post.
+///
+/// Properties:
+///
+/// * `username`: The `username` property is the username of the user who gets the access.
+/// * `role`: The `role` property is `viewer` or `editor`.
+pub struct PermissionForm {
+    pub username: String,
+    pub role: crate::Permission,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// The `PermissionUpdateForm` struct is the body of the request that changes the access of a user to
+/// a post.
+///
+/// Properties:
+///
+/// * `role`: The `role` property is the new role, `viewer` or `editor`.
+pub struct PermissionUpdateForm {
+    pub role: crate::Permission,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// The `WorkspaceForm` struct is the body of the requests that create and rename a workspace.
+///
+/// Properties:
+///
+/// * `name`: The `name` property is the name of the workspace.
+pub struct WorkspaceForm {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// The `MemberForm` struct is the body of the request that invites a user to a workspace.
+///
+/// Properties:
+///
+/// * `username`: The `username` property is the username of the invited user.
+/// * `role`: The `role` property is `member` or `admin`, `member` if it is left out.
+pub struct MemberForm {
+    pub username: String,
+    #[serde(default)]
+    pub role: crate::WorkspaceRole,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// The `MemberUpdateForm` struct is the body of the request that changes the role of a member.
+///
+/// Properties:
+///
+/// * `role`: The `role` property is the new role, `member` or `admin`.
+pub struct MemberUpdateForm {
+    pub role: crate::WorkspaceRole,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// The `NotebookForm` struct is the body of the requests that create a notebook and that rename or
+/// move it.
+///
+/// Properties:
+///
+/// * `name`: The `name` property is the name of the notebook.
+/// * `parent_id`: The `parent_id` property is the notebook it is in, `None` for a top level notebook.
+pub struct NotebookForm {
+    pub name: String,
+    #[serde(default)]
+    pub parent_id: Option<uuid::Uuid>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+/// The `NotebookMoveForm` struct is the body of the request that moves a post to another notebook.
+///
+/// Properties:
+///
+/// * `notebook_id`: The `notebook_id` property is the new notebook of the post, `None` takes it out of
+/// the notebooks.
+pub struct NotebookMoveForm {
+    #[serde(default)]
+    pub notebook_id: Option<uuid::Uuid>,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+/// The `ShareLinkForm` struct is the body of the request that shares a post with a link.
+///
+/// Properties:
+///
+/// * `expires_at`: The `expires_at` property is the time the link stops working, in RFC 3339. The link
+/// works until it is revoked if there is none.
+/// * `password`: The `password` property is the password the visitors have to enter, if any.
+pub struct ShareLinkForm {
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub expires_at: Option<OffsetDateTime>,
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+impl std::fmt::Debug for ShareLinkForm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ShareLinkForm")
+            .field("expires_at", &self.expires_at)
+            .field("password", &self.password.as_ref().map(|_| REDACTED))
+            .finish()
+    }
+}
@@ -0,0 +1,687 @@
+use serde::{Deserialize, Serialize};
+
+/// The `Locale` enum lists the languages the messages are translated to. English is the default and
+/// the fallback for missing translations.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    Uk,
+}
+
+impl Locale {
+    /// All supported locales, in the order they are offered to the user.
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::Uk];
+
+    /// The function `code` returns the language tag of the locale, for example `uk`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Uk => "uk",
+        }
+    }
+
+    /// The function `name` returns the name of the language in the language itself.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Locale::En => "English",
+            Locale::Uk => "Українська",
+        }
+    }
+
+    /// The function `from_tag` returns the locale of a language tag like `uk-UA`. Only the primary
+    /// language is compared.
+    pub fn from_tag(tag: &str) -> Option<Locale> {
+        let language = tag.split(['-', '_']).next()?.trim();
+        Locale::ALL
+            .into_iter()
+            .find(|locale| locale.code().eq_ignore_ascii_case(language))
+    }
+
+    /// The function `negotiate` picks the supported locale the client prefers most.
+    ///
+    /// Arguments:
+    ///
+    /// * `accept_language`: The `accept_language` parameter is the value of the `Accept-Language`
+    /// header, for example `uk-UA,uk;q=0.9,en;q=0.8`.
+    ///
+    /// Returns:
+    ///
+    /// The supported locale with the highest quality, or `None` if the client accepts none of them.
+    pub fn negotiate(accept_language: &str) -> Option<Locale> {
+        let mut accepted = accept_language
+            .split(',')
+            .filter_map(|range| {
+                let mut parts = range.split(';');
+                let locale = Locale::from_tag(parts.next()?)?;
+                let quality = parts
+                    .find_map(|param| param.trim().strip_prefix("q="))
+                    .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+                Some((locale, quality))
+            })
+            .filter(|(_, quality)| *quality > 0.0)
+            .collect::<Vec<_>>();
+        //stable sort keeps the header order for equal qualities
+        accepted.sort_by(|a, b| b.1.total_cmp(&a.1));
+        accepted.first().map(|(locale, _)| *locale)
+    }
+}
+
+impl std::fmt::Display for Locale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+/// The function `translate` returns the message of a catalog key in the given locale. Keys missing in
+/// the locale fall back to English, unknown keys are returned as they are.
+///
+/// Arguments:
+///
+/// * `locale`: The `locale` parameter is the language of the message.
+/// * `key`: The `key` parameter is an error code like `password.no_digit` or a key of the interface
+/// like `nav.home`.
+pub fn translate(locale: Locale, key: &str) -> String {
+    lookup(locale, key)
+        .or_else(|| lookup(Locale::En, key))
+        .unwrap_or(key)
+        .to_string()
+}
+
+fn lookup(locale: Locale, key: &str) -> Option<&'static str> {
+    let catalog = match locale {
+        Locale::En => EN,
+        Locale::Uk => UK,
+    };
+    catalog
+        .iter()
+        .find(|(catalog_key, _)| *catalog_key == key)
+        .map(|(_, message)| *message)
+}
+
+const EN: &[(&str, &str)] = &[
+    //errors returned by the backend
+    ("auth.username_taken", "The username already exists!"),
+    (
+        "auth.unknown_user",
+        "User not found. Try to check your username!",
+    ),
+    ("auth.wrong_password", "Incorrect password. Try again!"),
+    ("auth.disabled", "The account is disabled!"),
+    ("auth.session_expired", "Session expired. Log in again!"),
+    (
+        "auth.access_token_missing",
+        "Access token not found. Log in first!",
+    ),
+    (
+        "auth.refresh_token_missing",
+        "Refresh token not found. Log in first!",
+    ),
+    ("auth.forbidden", "You don't have permission to do this!"),
+    ("auth.login_expired", "Login expired. Log in again!"),
+    ("auth.refresh_expired", "Session expired. Log in again!"),
+    ("auth.invalid_token", "Invalid login token. Log in again!"),
+    (
+        "auth.token_not_created",
+        "Could not log you in. Try again later!",
+    ),
+    ("admin.disabled", "Admin endpoints are disabled!"),
+    ("admin.invalid_token", "Invalid admin token!"),
+    (
+        "admin.self_moderation",
+        "You can not moderate your own account!",
+    ),
+    ("user.not_found", "User not found!"),
+    ("post.not_found", "Post not found!"),
+    ("attachment.not_found", "Attachment not found!"),
+    ("attachment.no_files", "Choose at least one file to attach!"),
+    ("attachment.too_large", "The file is too large to attach!"),
+    ("attachment.quota_exceeded", "Your storage quota is full!"),
+    (
+        "attachment.invalid_image",
+        "The image is damaged or not valid!",
+    ),
+    (
+        "attachment.image_too_large",
+        "The image is larger than 12000 pixels!",
+    ),
+    ("share.not_found", "The link does not exist or has expired!"),
+    (
+        "share.password_required",
+        "The link is protected, enter its password!",
+    ),
+    ("share.wrong_password", "Incorrect password. Try again!"),
+    (
+        "share.too_many_attempts",
+        "Too many wrong passwords. Try again later!",
+    ),
+    (
+        "share.expires_in_past",
+        "The expiry time has already passed!",
+    ),
+    (
+        "permission.exists",
+        "The note is already shared with this user!",
+    ),
+    (
+        "permission.not_found",
+        "The note is not shared with this user!",
+    ),
+    ("permission.invalid_role", "Choose viewer or editor!"),
+    ("permission.self", "You are the owner of this note!"),
+    (
+        "workspace.not_found",
+        "The workspace does not exist or you are not a member!",
+    ),
+    (
+        "workspace.member_exists",
+        "The user is already a member of this workspace!",
+    ),
+    (
+        "workspace.member_not_found",
+        "The user is not a member of this workspace!",
+    ),
+    ("workspace.invalid_role", "Choose member or admin!"),
+    (
+        "workspace.personal",
+        "A personal workspace can not have other members!",
+    ),
+    (
+        "workspace.name_required",
+        "The workspace's name can not be empty!",
+    ),
+    (
+        "workspace.name_too_long",
+        "The workspace's name can have at most {max} characters!",
+    ),
+    ("notebook.not_found", "The notebook does not exist!"),
+    (
+        "notebook.cycle",
+        "A notebook can not be moved into itself or into a notebook inside it!",
+    ),
+    (
+        "notebook.name_required",
+        "The notebook's name can not be empty!",
+    ),
+    (
+        "notebook.name_too_long",
+        "The notebook's name can have at most {max} characters!",
+    ),
+    (
+        "collab.read_only",
+        "You can only view this note, your changes were not sent!",
+    ),
+    (
+        "collab.invalid_update",
+        "The change does not fit the note, it is loaded again!",
+    ),
+    ("collab.invalid_message", "The message could not be read!"),
+    (
+        "collab.websocket_required",
+        "Open the editing channel with a WebSocket!",
+    ),
+    (
+        "collab.busy",
+        "The note is too busy right now, try again later!",
+    ),
+    (
+        "collab.access_changed",
+        "Your access to this note changed, it is loaded again!",
+    ),
+    (
+        "log.not_reloadable",
+        "The log filter can not be changed at runtime!",
+    ),
+    ("log.invalid_filter", "Invalid log filter directive!"),
+    ("log.read_failed", "The log filter could not be read!"),
+    ("log.reload_failed", "The log filter could not be changed!"),
+    ("server.shutting_down", "Server is shutting down!"),
+    (
+        "request.invalid_json",
+        "The request body is not valid JSON!",
+    ),
+    ("request.too_large", "The request body is too large!"),
+    (
+        "request.invalid_query",
+        "The query parameters are not valid!",
+    ),
+    (
+        "request.invalid_multipart",
+        "The request body is not a valid multipart form!",
+    ),
+    (
+        "username.too_short",
+        "Username must contain at least {min} symbols!",
+    ),
+    (
+        "username.too_long",
+        "Username can contain at most {max} symbols!",
+    ),
+    (
+        "username.invalid_characters",
+        "Username contains characters that are not allowed!",
+    ),
+    (
+        "password.too_short",
+        "Password must contain at least {min} characters!",
+    ),
+    (
+        "password.too_long",
+        "Password can contain at most {max} characters!",
+    ),
+    (
+        "password.no_letter",
+        "Password must contain at least one letter!",
+    ),
+    (
+        "password.no_digit",
+        "Password must contain at least one number!",
+    ),
+    (
+        "password.no_symbol",
+        "Password must contain at least one symbol!",
+    ),
+    (
+        "password.no_space",
+        "Password must contain at least one space!",
+    ),
+    (
+        "password.invalid_characters",
+        "Password contains characters that are not allowed!",
+    ),
+    (
+        "password.breached",
+        "This password was found in a data breach, choose another one!",
+    ),
+    ("title.required", "The post's title can not be empty!"),
+    ("import.no_files", "Choose at least one file to import!"),
+    ("import.duplicate", "The same note already exists."),
+    ("import.trashed", "The note is in the trash."),
+    (
+        "import.unsupported_format",
+        "The format of the file is not supported!",
+    ),
+    (
+        "import.unsupported_version",
+        "The export was made by a newer version and can not be read!",
+    ),
+    ("import.invalid_file", "The file is damaged or not valid!"),
+    (
+        "import.too_large",
+        "The archive is too large when it is extracted!",
+    ),
+    //errors created by the frontend
+    ("request.failed", "Failed to make request"),
+    ("response.invalid", "Failed to deserialize response"),
+    //interface
+    ("locale.label", "Language"),
+    ("nav.home", "Home"),
+    ("nav.sign_up", "Sign Up"),
+    ("nav.log_in", "Log In"),
+    ("nav.log_out", "Log Out"),
+    ("nav.posts", "Posts"),
+    ("nav.add_post", "Add Post"),
+    ("nav.go_to_posts", "Go to posts"),
+    ("nav.admin", "Admin"),
+    ("form.signup.title", "Create an account!"),
+    ("form.login.title", "Welcome back!"),
+    ("form.have_account", "Have an account?"),
+    ("form.no_account", "Don't have an account?"),
+    ("form.username", "Username:"),
+    ("form.password", "Password:"),
+    ("form.title", "Title:"),
+    ("form.description", "Description:"),
+    (
+        "form.description.paste",
+        "Paste screenshots to embed them in the post.",
+    ),
+    ("post.add.title", "Add new post!"),
+    ("alert.signed_up", "You’ve been signed up successfully!"),
+    ("alert.logged_in", "You’ve been signed in successfully!"),
+    ("alert.post_added", "Post added successfully!"),
+    ("alert.not_found", "404! Page not found!"),
+    ("posts.empty", "Haven't gotten any posts yet!"),
+    ("posts.no_description", "No description!"),
+    ("posts.export.json", "Export JSON"),
+    ("posts.export.markdown", "Export Markdown"),
+    ("posts.filter.all", "All"),
+    ("posts.filter.shared", "Shared with me"),
+    ("posts.shared_by", "Shared by"),
+    ("posts.permission.viewer", "Can view"),
+    ("posts.permission.editor", "Can edit"),
+    ("posts.delete", "Delete"),
+    ("workspaces.label", "Workspace"),
+    ("workspaces.personal", "Personal"),
+    ("workspaces.new", "New workspace"),
+    ("workspaces.create", "Create"),
+    ("notebooks.label", "Notebooks"),
+    ("notebooks.all", "All notes"),
+    ("notebooks.new", "New notebook"),
+    ("notebooks.create", "Add"),
+    ("notebooks.delete", "Delete the notebook"),
+    ("notebooks.move", "Notebook"),
+    ("notebooks.none", "No notebook"),
+    ("collab.open", "Edit together"),
+    ("collab.connecting", "Connecting..."),
+    ("collab.disconnected", "Disconnected, connecting again..."),
+    ("collab.viewing", "You can only view this note"),
+    ("collab.editors", "Editing now:"),
+    ("share.open", "Open"),
+    ("import.submit", "Import"),
+    ("import.dry_run", "Dry run"),
+    ("import.report.imported", "Imported"),
+    ("import.report.skipped", "Skipped"),
+    ("import.report.failed", "Failed"),
+    ("admin.stats.users", "Users"),
+    ("admin.stats.disabled", "Disabled"),
+    ("admin.stats.moderators", "Moderators"),
+    ("admin.stats.admins", "Admins"),
+    ("admin.stats.posts", "Posts"),
+    ("admin.stats.uptime", "Uptime"),
+    ("admin.stats.version", "Version"),
+    ("admin.users.username", "Username"),
+    ("admin.users.role", "Role"),
+    ("admin.users.posts", "Posts"),
+    ("admin.users.created", "Created"),
+    ("admin.action.disable", "Disable"),
+    ("admin.action.enable", "Enable"),
+    ("admin.action.logout", "Log Out"),
+    ("admin.action.delete", "Delete"),
+];
+
+const UK: &[(&str, &str)] = &[
+    //errors returned by the backend
+    ("auth.username_taken", "Це ім'я користувача вже зайняте!"),
+    (
+        "auth.unknown_user",
+        "Користувача не знайдено. Перевірте ім'я користувача!",
+    ),
+    (
+        "auth.wrong_password",
+        "Неправильний пароль. Спробуйте ще раз!",
+    ),
+    ("auth.disabled", "Обліковий запис вимкнено!"),
+    ("auth.session_expired", "Сесія завершилася. Увійдіть знову!"),
+    (
+        "auth.access_token_missing",
+        "Токен доступу не знайдено. Спочатку увійдіть!",
+    ),
+    (
+        "auth.refresh_token_missing",
+        "Токен оновлення не знайдено. Спочатку увійдіть!",
+    ),
+    ("auth.forbidden", "У вас немає прав на цю дію!"),
+    ("auth.login_expired", "Час входу минув. Увійдіть знову!"),
+    ("auth.refresh_expired", "Сесія завершилася. Увійдіть знову!"),
+    (
+        "auth.invalid_token",
+        "Недійсний токен входу. Увійдіть знову!",
+    ),
+    (
+        "auth.token_not_created",
+        "Не вдалося виконати вхід. Спробуйте пізніше!",
+    ),
+    ("admin.disabled", "Адміністративні ендпоінти вимкнено!"),
+    ("admin.invalid_token", "Недійсний токен адміністратора!"),
+    (
+        "admin.self_moderation",
+        "Ви не можете модерувати власний обліковий запис!",
+    ),
+    ("user.not_found", "Користувача не знайдено!"),
+    ("post.not_found", "Допис не знайдено!"),
+    ("attachment.not_found", "Вкладення не знайдено!"),
+    (
+        "attachment.no_files",
+        "Виберіть хоча б один файл для вкладення!",
+    ),
+    ("attachment.too_large", "Файл завеликий для вкладення!"),
+    ("attachment.quota_exceeded", "Ваше сховище заповнене!"),
+    (
+        "attachment.invalid_image",
+        "Зображення пошкоджене або некоректне!",
+    ),
+    (
+        "attachment.image_too_large",
+        "Зображення більше за 12000 пікселів!",
+    ),
+    ("share.not_found", "Посилання не існує або застаріло!"),
+    (
+        "share.password_required",
+        "Посилання захищене, введіть його пароль!",
+    ),
+    ("share.wrong_password", "Неправильний пароль. Спробуйте ще!"),
+    (
+        "share.too_many_attempts",
+        "Забагато неправильних паролів. Спробуйте пізніше!",
+    ),
+    ("share.expires_in_past", "Час дії вже минув!"),
+    (
+        "permission.exists",
+        "Нотатка вже доступна цьому користувачу!",
+    ),
+    (
+        "permission.not_found",
+        "Нотатка не доступна цьому користувачу!",
+    ),
+    ("permission.invalid_role", "Оберіть читача або редактора!"),
+    ("permission.self", "Ви власник цієї нотатки!"),
+    (
+        "workspace.not_found",
+        "Робочий простір не існує або ви не є його учасником!",
+    ),
+    (
+        "workspace.member_exists",
+        "Користувач уже є учасником цього робочого простору!",
+    ),
+    (
+        "workspace.member_not_found",
+        "Користувач не є учасником цього робочого простору!",
+    ),
+    (
+        "workspace.invalid_role",
+        "Оберіть учасника або адміністратора!",
+    ),
+    (
+        "workspace.personal",
+        "Особистий робочий простір не може мати інших учасників!",
+    ),
+    (
+        "workspace.name_required",
+        "Назва робочого простору не може бути порожньою!",
+    ),
+    (
+        "workspace.name_too_long",
+        "Назва робочого простору може мати щонайбільше {max} символів!",
+    ),
+    ("notebook.not_found", "Блокнот не існує!"),
+    (
+        "notebook.cycle",
+        "Блокнот не можна перемістити в себе або в блокнот усередині нього!",
+    ),
+    (
+        "notebook.name_required",
+        "Назва блокнота не може бути порожньою!",
+    ),
+    (
+        "notebook.name_too_long",
+        "Назва блокнота може мати щонайбільше {max} символів!",
+    ),
+    (
+        "collab.read_only",
+        "Ви можете лише переглядати цю нотатку, зміни не надіслано!",
+    ),
+    (
+        "collab.invalid_update",
+        "Зміна не відповідає нотатці, її завантажено знову!",
+    ),
+    (
+        "collab.invalid_message",
+        "Не вдалося прочитати повідомлення!",
+    ),
+    (
+        "collab.websocket_required",
+        "Відкрийте канал редагування через WebSocket!",
+    ),
+    (
+        "collab.busy",
+        "Нотатка зараз надто зайнята, спробуйте пізніше!",
+    ),
+    (
+        "collab.access_changed",
+        "Ваш доступ до цієї нотатки змінився, її завантажено знову!",
+    ),
+    (
+        "log.not_reloadable",
+        "Фільтр журналу не можна змінити під час роботи!",
+    ),
+    (
+        "log.invalid_filter",
+        "Некоректна директива фільтра журналу!",
+    ),
+    ("log.read_failed", "Не вдалося прочитати фільтр журналу!"),
+    ("log.reload_failed", "Не вдалося змінити фільтр журналу!"),
+    ("server.shutting_down", "Сервер вимикається!"),
+    ("request.invalid_json", "Тіло запиту не є коректним JSON!"),
+    ("request.too_large", "Тіло запиту завелике!"),
+    ("request.invalid_query", "Некоректні параметри запиту!"),
+    (
+        "request.invalid_multipart",
+        "Тіло запиту не є коректною multipart-формою!",
+    ),
+    (
+        "username.too_short",
+        "Ім'я користувача має містити щонайменше {min} символів!",
+    ),
+    (
+        "username.too_long",
+        "Ім'я користувача може містити щонайбільше {max} символів!",
+    ),
+    (
+        "username.invalid_characters",
+        "Ім'я користувача містить недозволені символи!",
+    ),
+    (
+        "password.too_short",
+        "Пароль має містити щонайменше {min} символів!",
+    ),
+    (
+        "password.too_long",
+        "Пароль може містити щонайбільше {max} символів!",
+    ),
+    (
+        "password.no_letter",
+        "Пароль має містити хоча б одну літеру!",
+    ),
+    ("password.no_digit", "Пароль має містити хоча б одну цифру!"),
+    (
+        "password.no_symbol",
+        "Пароль має містити хоча б один спеціальний символ!",
+    ),
+    (
+        "password.no_space",
+        "Пароль має містити хоча б один пробіл!",
+    ),
+    (
+        "password.invalid_characters",
+        "Пароль містить недозволені символи!",
+    ),
+    (
+        "password.breached",
+        "Цей пароль є у базі зламаних паролів, оберіть інший!",
+    ),
+    ("title.required", "Назва допису не може бути порожньою!"),
+    ("import.no_files", "Оберіть хоча б один файл для імпорту!"),
+    ("import.duplicate", "Така нотатка вже існує."),
+    ("import.trashed", "Нотатка в кошику."),
+    (
+        "import.unsupported_format",
+        "Формат файлу не підтримується!",
+    ),
+    (
+        "import.unsupported_version",
+        "Експорт зроблено новішою версією, його не вдається прочитати!",
+    ),
+    ("import.invalid_file", "Файл пошкоджений або некоректний!"),
+    ("import.too_large", "Архів завеликий після розпакування!"),
+    //errors created by the frontend
+    ("request.failed", "Не вдалося виконати запит"),
+    ("response.invalid", "Не вдалося прочитати відповідь"),
+    //interface
+    ("locale.label", "Мова"),
+    ("nav.home", "Головна"),
+    ("nav.sign_up", "Зареєструватися"),
+    ("nav.log_in", "Увійти"),
+    ("nav.log_out", "Вийти"),
+    ("nav.posts", "Дописи"),
+    ("nav.add_post", "Додати допис"),
+    ("nav.go_to_posts", "До дописів"),
+    ("nav.admin", "Адміністрування"),
+    ("form.signup.title", "Створіть обліковий запис!"),
+    ("form.login.title", "З поверненням!"),
+    ("form.have_account", "Вже маєте обліковий запис?"),
+    ("form.no_account", "Ще не маєте облікового запису?"),
+    ("form.username", "Ім'я користувача:"),
+    ("form.password", "Пароль:"),
+    ("form.title", "Назва:"),
+    ("form.description", "Опис:"),
+    (
+        "form.description.paste",
+        "Вставте знімки екрана, щоб додати їх у допис.",
+    ),
+    ("post.add.title", "Новий допис!"),
+    ("alert.signed_up", "Ви успішно зареєструвалися!"),
+    ("alert.logged_in", "Ви успішно увійшли!"),
+    ("alert.post_added", "Допис успішно додано!"),
+    ("alert.not_found", "404! Сторінку не знайдено!"),
+    ("posts.empty", "Дописів ще немає!"),
+    ("posts.no_description", "Без опису!"),
+    ("posts.export.json", "Експорт у JSON"),
+    ("posts.export.markdown", "Експорт у Markdown"),
+    ("posts.filter.all", "Усі"),
+    ("posts.filter.shared", "Надіслані мені"),
+    ("posts.shared_by", "Від"),
+    ("posts.permission.viewer", "Перегляд"),
+    ("posts.permission.editor", "Редагування"),
+    ("posts.delete", "Видалити"),
+    ("workspaces.label", "Робочий простір"),
+    ("workspaces.personal", "Особистий"),
+    ("workspaces.new", "Новий робочий простір"),
+    ("workspaces.create", "Створити"),
+    ("notebooks.label", "Блокноти"),
+    ("notebooks.all", "Усі нотатки"),
+    ("notebooks.new", "Новий блокнот"),
+    ("notebooks.create", "Додати"),
+    ("notebooks.delete", "Видалити блокнот"),
+    ("notebooks.move", "Блокнот"),
+    ("notebooks.none", "Без блокнота"),
+    ("collab.open", "Редагувати разом"),
+    ("collab.connecting", "Підключення..."),
+    (
+        "collab.disconnected",
+        "Зʼєднання втрачено, підключаємося знову...",
+    ),
+    ("collab.viewing", "Ви можете лише переглядати цю нотатку"),
+    ("collab.editors", "Зараз редагують:"),
+    ("share.open", "Відкрити"),
+    ("import.submit", "Імпортувати"),
+    ("import.dry_run", "Пробний запуск"),
+    ("import.report.imported", "Імпортовано"),
+    ("import.report.skipped", "Пропущено"),
+    ("import.report.failed", "Не вдалося"),
+    ("admin.stats.users", "Користувачі"),
+    ("admin.stats.disabled", "Вимкнені"),
+    ("admin.stats.moderators", "Модератори"),
+    ("admin.stats.admins", "Адміністратори"),
+    ("admin.stats.posts", "Дописи"),
+    ("admin.stats.uptime", "Час роботи"),
+    ("admin.stats.version", "Версія"),
+    ("admin.users.username", "Ім'я користувача"),
+    ("admin.users.role", "Роль"),
+    ("admin.users.posts", "Дописи"),
+    ("admin.users.created", "Створено"),
+    ("admin.action.disable", "Вимкнути"),
+    ("admin.action.enable", "Увімкнути"),
+    ("admin.action.logout", "Завершити сесії"),
+    ("admin.action.delete", "Видалити"),
+];
@@ -1,15 +1,28 @@
+pub mod collab;
 pub mod errors;
+pub mod events;
 pub mod forms;
+pub mod i18n;
+pub mod notebooks;
 pub mod posts;
 pub mod user;
+pub mod workspaces;
 
+pub use collab::*;
 pub use errors::*;
+pub use events::*;
 pub use forms::*;
+pub use i18n::{translate, Locale};
+pub use notebooks::*;
 pub use posts::*;
 pub use user::*;
+pub use workspaces::*;
 
 use serde::{Deserialize, Serialize};
 
+/// Placeholder written to the logs instead of passwords, password hashes, tokens and note bodies.
+pub const REDACTED: &str = "[REDACTED]";
+
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct ApiResponse {
     pub status: String,
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+/// The `ResponseNotebook` struct is a notebook of a workspace with the number of its notes. The
+/// notebooks of a workspace are returned as a flat list, the `parent_id`s make the tree.
+///
+/// Properties:
+///
+/// * `id`: The `id` property is the unique identifier of the notebook.
+/// * `parent_id`: The `parent_id` property is the notebook it is in, `None` for a top level notebook.
+/// * `name`: The `name` property is the name of the notebook.
+/// * `notes`: The `notes` property is the number of the notes right in the notebook.
+/// * `total`: The `total` property is the number of the notes in the notebook and in all notebooks
+/// inside it.
+/// * `created_at`: The `created_at` property is the time the notebook was created.
+pub struct ResponseNotebook {
+    pub id: Uuid,
+    pub parent_id: Option<Uuid>,
+    pub name: String,
+    pub notes: i64,
+    pub total: i64,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
@@ -2,7 +2,9 @@ use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 use uuid::Uuid;
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+use crate::{ErrorResponse, REDACTED};
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
 /// The `ResponsePost` struct represents a post with an ID, user ID, importance flag, title,
 /// description, and creation timestamp.
 ///
@@ -18,6 +20,11 @@ use uuid::Uuid;
 /// * `created_at`: The `created_at` property is of type `OffsetDateTime` and is used to store the
 /// timestamp when the post was created. It is annotated with `#[serde(with="time::serde::rfc3339")]`
 /// which indicates that it should be serialized and deserialized using the `time::
+/// * `owner_id`: The `owner_id` property is the id of the author of the post.
+/// * `owner`: The `owner` property is the username of the author.
+/// * `permission`: The `permission` property is what the user who asked may do with the post.
+/// * `workspace_id`: The `workspace_id` property is the workspace the post belongs to.
+/// * `notebook_id`: The `notebook_id` property is the notebook the post is in, if any.
 pub struct ResponsePost {
     pub id: Uuid,
     pub important: bool,
@@ -25,4 +32,226 @@ pub struct ResponsePost {
     pub description: Option<String>,
     #[serde(with = "time::serde::rfc3339")]
     pub created_at: OffsetDateTime,
+    #[serde(default)]
+    pub owner_id: Uuid,
+    #[serde(default)]
+    pub owner: String,
+    #[serde(default)]
+    pub permission: Permission,
+    #[serde(default)]
+    pub workspace_id: Uuid,
+    #[serde(default)]
+    pub notebook_id: Option<Uuid>,
+}
+
+impl std::fmt::Debug for ResponsePost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResponsePost")
+            .field("id", &self.id)
+            .field("important", &self.important)
+            .field("title", &self.title)
+            .field("description", &self.description.as_ref().map(|_| REDACTED))
+            .field("created_at", &self.created_at)
+            .field("owner_id", &self.owner_id)
+            .field("owner", &self.owner)
+            .field("permission", &self.permission)
+            .field("workspace_id", &self.workspace_id)
+            .field("notebook_id", &self.notebook_id)
+            .finish()
+    }
+}
+
+/// The `Permission` enum is what a user may do with a post. The variants are ordered, so a higher
+/// permission has all the rights of the lower ones: `Owner > Editor > Viewer`. Only `Viewer` and
+/// `Editor` are granted, the author is the only `Owner`.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Permission {
+    Viewer,
+    Editor,
+    #[default]
+    Owner,
+}
+
+impl Permission {
+    /// The function `as_str` returns the name of the permission as it is stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::Viewer => "viewer",
+            Permission::Editor => "editor",
+            Permission::Owner => "owner",
+        }
+    }
+}
+
+impl std::fmt::Display for Permission {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for Permission {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "viewer" => Ok(Permission::Viewer),
+            "editor" => Ok(Permission::Editor),
+            "owner" => Ok(Permission::Owner),
+            _ => Err(format!("Unknown permission {}! Use viewer or editor", s)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+/// The `ResponsePermission` struct is the access of another user to a post.
+///
+/// Properties:
+///
+/// * `post_id`: The `post_id` property is the shared post.
+/// * `user_id`: The `user_id` property is the id of the user who got the access.
+/// * `username`: The `username` property is the username of that user.
+/// * `role`: The `role` property is `viewer` or `editor`.
+/// * `created_at`: The `created_at` property is the time the access was granted.
+pub struct ResponsePermission {
+    pub post_id: Uuid,
+    pub user_id: Uuid,
+    pub username: String,
+    pub role: Permission,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+/// The `ImportStatus` enum is the outcome of one note of an import.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportStatus {
+    Imported,
+    Skipped,
+    Failed,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+/// The `ImportItem` struct reports what happened to one note of an import.
+///
+/// Properties:
+///
+/// * `source`: The `source` property is where the note was found: the name of the uploaded file, the
+/// path in a zip archive, and the position of the note in files with many notes, for example
+/// `keep.zip/Takeout/Keep/Shopping.json` or `notes.enex#3`.
+/// * `title`: The `title` property is the title of the note, if it could be read.
+/// * `status`: The `status` property says if the note was imported, skipped or failed.
+/// * `error`: The `error` property is the reason of a skipped or failed note, with a stable `code` like
+/// `import.duplicate` or the field errors of the validation.
+pub struct ImportItem {
+    pub source: String,
+    pub title: Option<String>,
+    pub status: ImportStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorResponse>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+/// The `ImportReport` struct is the response of `POST /import`.
+///
+/// Properties:
+///
+/// * `dry_run`: The `dry_run` property is `true` if nothing was written, the report then tells what
+/// would have been imported.
+/// * `imported`: The `imported` property is the number of imported notes.
+/// * `skipped`: The `skipped` property is the number of skipped notes.
+/// * `failed`: The `failed` property is the number of notes that could not be imported.
+/// * `items`: The `items` property lists every note of the upload in order.
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub imported: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub items: Vec<ImportItem>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+/// The `ResponseAttachment` struct is a file attached to a post. The content is downloaded from
+/// `GET /attachments/{id}`.
+///
+/// Properties:
+///
+/// * `id`: The `id` property is the unique identifier of the attachment.
+/// * `post_id`: The `post_id` property is the post the file is attached to.
+/// * `file_name`: The `file_name` property is the name of the uploaded file.
+/// * `content_type`: The `content_type` property is the MIME type of the file, for example `image/png`.
+/// * `size`: The `size` property is the size of the file in bytes.
+/// * `created_at`: The `created_at` property is the time of the upload.
+pub struct ResponseAttachment {
+    pub id: Uuid,
+    pub post_id: Uuid,
+    pub file_name: String,
+    pub content_type: String,
+    pub size: i64,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+/// The `ResponseShareLink` struct is a link that shows a post to visitors without an account, at
+/// `/s/{token}`.
+///
+/// Properties:
+///
+/// * `id`: The `id` property is the unique identifier of the link, used to revoke it.
+/// * `post_id`: The `post_id` property is the shared post.
+/// * `token`: The `token` property is the secret part of the address. Only its hash is stored, so it is
+/// returned once, when the link is created.
+/// * `has_password`: The `has_password` property is `true` if the visitors have to enter a password.
+/// * `expires_at`: The `expires_at` property is the time the link stops working, if any.
+/// * `created_at`: The `created_at` property is the time the link was created.
+pub struct ResponseShareLink {
+    pub id: Uuid,
+    pub post_id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    pub has_password: bool,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub expires_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+impl std::fmt::Debug for ResponseShareLink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResponseShareLink")
+            .field("id", &self.id)
+            .field("post_id", &self.post_id)
+            .field("token", &self.token.as_ref().map(|_| REDACTED))
+            .field("has_password", &self.has_password)
+            .field("expires_at", &self.expires_at)
+            .field("created_at", &self.created_at)
+            .finish()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+/// The `SharedPost` struct is a post as the visitors of a share link see it, read-only and without
+/// the ids and the important flag of the author.
+///
+/// Properties:
+///
+/// * `title`: The `title` property is the title of the post.
+/// * `description`: The `description` property is the description of the post, if any.
+/// * `created_at`: The `created_at` property is the time the post was written.
+pub struct SharedPost {
+    pub title: String,
+    pub description: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+impl std::fmt::Debug for SharedPost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SharedPost")
+            .field("title", &self.title)
+            .field("description", &self.description.as_ref().map(|_| REDACTED))
+            .field("created_at", &self.created_at)
+            .finish()
+    }
 }
@@ -18,9 +18,97 @@ use uuid::Uuid;
 /// * `updated_at`: The `updated_at` property is a field that represents the date and time when the user
 /// was last updated. It is of type `OffsetDateTime`, which is a struct that represents a date and time
 /// with an offset from UTC.
+/// * `role`: The `role` property is the role of the user. Older responses without it are read as
+/// `Role::User`.
 pub struct ResponseUser {
     pub id: Uuid,
     pub username: String,
     pub created_at: OffsetDateTime,
     pub updated_at: OffsetDateTime,
+    #[serde(default)]
+    pub role: Role,
+}
+
+/// The `Role` enum is the role of a user. The variants are ordered, so a higher role has all the
+/// rights of the lower ones: `Admin > Moderator > User`.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    #[default]
+    User,
+    Moderator,
+    Admin,
+}
+
+impl Role {
+    /// The function `as_str` returns the name of the role as it is stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::User => "user",
+            Role::Moderator => "moderator",
+            Role::Admin => "admin",
+        }
+    }
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "user" => Ok(Role::User),
+            "moderator" => Ok(Role::Moderator),
+            "admin" => Ok(Role::Admin),
+            _ => Err(format!("Unknown role {}! Use user, moderator or admin", s)),
+        }
+    }
+}
+
+/// The `UserSummary` struct is a user with the number of their posts, used by the admin tools.
+///
+/// Properties:
+///
+/// * `id`: The `id` property is the user's id.
+/// * `username`: The `username` property is the user's name.
+/// * `role`: The `role` property is the user's role.
+/// * `disabled`: The `disabled` property shows if the account is disabled.
+/// * `created_at`: The `created_at` property is the time the account was created.
+/// * `posts`: The `posts` property is the number of posts the user has written.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UserSummary {
+    pub id: Uuid,
+    pub username: String,
+    pub role: Role,
+    pub disabled: bool,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    pub posts: i64,
+}
+
+/// The `SystemStats` struct holds the numbers shown on the admin page.
+///
+/// Properties:
+///
+/// * `users`: The `users` property is the number of accounts.
+/// * `disabled_users`: The `disabled_users` property is the number of disabled accounts.
+/// * `admins`: The `admins` property is the number of admins.
+/// * `moderators`: The `moderators` property is the number of moderators.
+/// * `posts`: The `posts` property is the number of posts.
+/// * `uptime`: The `uptime` property is the number of seconds since the server started.
+/// * `version`: The `version` property is the version of the backend.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SystemStats {
+    pub users: usize,
+    pub disabled_users: usize,
+    pub admins: usize,
+    pub moderators: usize,
+    pub posts: i64,
+    pub uptime: u64,
+    pub version: String,
 }
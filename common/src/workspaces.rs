@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::Permission;
+
+/// The `WorkspaceRole` enum is the role of a member of a workspace. The variants are ordered, so a
+/// higher role has all the rights of the lower ones: `Owner > Admin > Member`.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum WorkspaceRole {
+    #[default]
+    Member,
+    Admin,
+    Owner,
+}
+
+impl WorkspaceRole {
+    /// The function `as_str` returns the name of the role as it is stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WorkspaceRole::Member => "member",
+            WorkspaceRole::Admin => "admin",
+            WorkspaceRole::Owner => "owner",
+        }
+    }
+
+    /// The function `post_permission` returns what the role allows with the posts of the workspace
+    /// other members wrote: owners and admins edit them, members read them.
+    pub fn post_permission(&self) -> Permission {
+        match self {
+            WorkspaceRole::Member => Permission::Viewer,
+            WorkspaceRole::Admin | WorkspaceRole::Owner => Permission::Editor,
+        }
+    }
+}
+
+impl std::fmt::Display for WorkspaceRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for WorkspaceRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "member" => Ok(WorkspaceRole::Member),
+            "admin" => Ok(WorkspaceRole::Admin),
+            "owner" => Ok(WorkspaceRole::Owner),
+            _ => Err(format!("Unknown workspace role {}! Use member or admin", s)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+/// The `ResponseWorkspace` struct is a workspace as one of its members sees it.
+///
+/// Properties:
+///
+/// * `id`: The `id` property is the unique identifier of the workspace, the path segment of its
+/// notes endpoints.
+/// * `name`: The `name` property is the name of the workspace.
+/// * `personal`: The `personal` property is `true` for the workspace every user gets at signup. It
+/// has no other members.
+/// * `role`: The `role` property is the role of the user who asked.
+/// * `created_at`: The `created_at` property is the time the workspace was created.
+pub struct ResponseWorkspace {
+    pub id: Uuid,
+    pub name: String,
+    pub personal: bool,
+    pub role: WorkspaceRole,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+/// The `ResponseMember` struct is a member of a workspace.
+///
+/// Properties:
+///
+/// * `user_id`: The `user_id` property is the id of the member.
+/// * `username`: The `username` property is the username of the member.
+/// * `role`: The `role` property is the role of the member.
+/// * `created_at`: The `created_at` property is the time the member joined.
+pub struct ResponseMember {
+    pub user_id: Uuid,
+    pub username: String,
+    pub role: WorkspaceRole,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
@@ -1,14 +1,23 @@
 use reqwasm::http;
 
-use common::ErrorResponse;
+use common::{translate, ErrorResponse, Locale};
 use serde::{de::DeserializeOwned, Serialize};
+use web_sys::FormData;
 
 use once_cell::sync::Lazy;
 
+use crate::i18n::stored_locale;
+
 //get root part of request address from .env file
 static API_ROOT: Lazy<String> =
     Lazy::new(|| dotenv::var("API_ROOT").unwrap_or("http://127.0.0.1:8000".to_string()));
 
+/// The function `api_url` returns the full address of a backend endpoint, for example for links that
+/// the browser opens itself.
+pub fn api_url(uri: &str) -> String {
+    format!("{}{}", API_ROOT.as_str(), uri)
+}
+
 /// The `request` function in Rust is an asynchronous function that sends an HTTP request, handles the
 /// response, and returns either the deserialized response object or an error response.
 ///
@@ -34,18 +43,50 @@ pub async fn request<B, T>(
     uri: String,
     body: Option<B>,
 ) -> Result<T, ErrorResponse>
+where
+    T: DeserializeOwned + 'static + std::fmt::Debug,
+    B: Serialize + std::fmt::Debug,
+{
+    request_with_headers(method, uri, body, &[]).await
+}
+
+/// The `request_with_headers` function sends a request like `request`, with extra headers, for
+/// example the password of a share link.
+///
+/// Arguments:
+///
+/// * `method`: The `method` parameter is the HTTP method, like in `request`.
+/// * `uri`: The `uri` parameter is the path of the endpoint, like in `request`.
+/// * `body`: The `body` parameter is the optional JSON body, like in `request`.
+/// * `headers`: The `headers` parameter lists the names and the values of the extra headers.
+///
+/// Returns:
+///
+/// the deserialized response, or the `ErrorResponse` like `request`.
+pub async fn request_with_headers<B, T>(
+    method: http::Method,
+    uri: String,
+    body: Option<B>,
+    headers: &[(&str, &str)],
+) -> Result<T, ErrorResponse>
 where
     T: DeserializeOwned + 'static + std::fmt::Debug,
     B: Serialize + std::fmt::Debug,
 {
     //create full url address
-    let url = format!("{}{}", API_ROOT.clone(), uri);
+    let url = api_url(&uri);
     log::debug!("Make request: {}", url);
 
+    //the backend translates the errors to the selected language
+    let locale = stored_locale();
     let mut builder = http::Request::new(&url)
         .method(method)
         .credentials(http::RequestCredentials::Include) //add cookies
-        .header("Content-Type", "application/json");
+        .header("Content-Type", "application/json")
+        .header("Accept-Language", locale.code());
+    for (name, value) in headers {
+        builder = builder.header(name, value);
+    }
 
     //add data if exists
     if let Some(data) = body {
@@ -53,6 +94,42 @@ where
         builder = builder.body(data);
     }
 
+    send(builder, locale).await
+}
+
+/// The `upload` function sends files to the backend as a `multipart/form-data` POST request. The
+/// browser sets the `Content-Type` with the boundary itself.
+///
+/// Arguments:
+///
+/// * `uri`: The `uri` parameter is the path of the endpoint, like in `request`.
+/// * `form`: The `form` parameter holds the files and the other fields of the form.
+///
+/// Returns:
+///
+/// the deserialized response, or the `ErrorResponse` like `request`.
+pub async fn upload<T>(uri: String, form: FormData) -> Result<T, ErrorResponse>
+where
+    T: DeserializeOwned + 'static + std::fmt::Debug,
+{
+    let url = api_url(&uri);
+    log::debug!("Upload files: {}", url);
+
+    let locale = stored_locale();
+    let builder = http::Request::new(&url)
+        .method(http::Method::POST)
+        .credentials(http::RequestCredentials::Include)
+        .header("Accept-Language", locale.code())
+        .body(form);
+
+    send(builder, locale).await
+}
+
+//sends the request and reads the JSON response, or the error of the backend
+async fn send<T>(builder: http::Request, locale: Locale) -> Result<T, ErrorResponse>
+where
+    T: DeserializeOwned + 'static + std::fmt::Debug,
+{
     let response = builder.send().await;
 
     match response {
@@ -63,7 +140,7 @@ where
                     log::error!("Failder to deserialize response!");
                     ErrorResponse::new(
                         Some(e.to_string()),
-                        Some("Failed to deserialize response".to_string()),
+                        Some(translate(locale, "response.invalid")),
                         common::ErrorTypes::DeserializeError,
                     )
                 })
@@ -78,7 +155,7 @@ where
             log::debug!("Error sending the request!");
             Err(ErrorResponse::new(
                 Some(e.to_string()),
-                Some("Failed to make request".to_string()),
+                Some(translate(locale, "request.failed")),
                 common::ErrorTypes::RequestError,
             ))
         }
@@ -1,13 +1,37 @@
 use yew::prelude::*;
 use yew_router::prelude::*;
 
+use crate::components::locale_switcher::LocaleSwitcher;
+use crate::i18n::{stored_locale, LocaleContext};
 use crate::routes::{switch, Route};
+use crate::workspace::{stored_workspace, WorkspaceContext};
 
 #[function_component(App)]
 pub fn app() -> Html {
+    let locale = use_state(stored_locale);
+    let workspace = use_state(stored_workspace);
+
+    //the language of the page for the browser and the screen readers
+    use_effect_with_deps(
+        |locale| {
+            let root = web_sys::window()
+                .and_then(|window| window.document())
+                .and_then(|document| document.document_element());
+            if let Some(root) = root {
+                root.set_attribute("lang", locale.code()).ok();
+            }
+        },
+        *locale,
+    );
+
     html! {
-        <BrowserRouter>
-            <Switch<Route> render={switch} />
-        </BrowserRouter>
+        <ContextProvider<LocaleContext> context={locale}>
+            <ContextProvider<WorkspaceContext> context={workspace}>
+                <BrowserRouter>
+                    <LocaleSwitcher/>
+                    <Switch<Route> render={switch} />
+                </BrowserRouter>
+            </ContextProvider<WorkspaceContext>>
+        </ContextProvider<LocaleContext>>
     }
 }
@@ -0,0 +1,102 @@
+use common::{CollabClientMessage, CollabServerMessage};
+use uuid::Uuid;
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::{CloseEvent, MessageEvent, WebSocket};
+use yew::Callback;
+
+use crate::api::api_url;
+
+/// The `CollabSocket` struct is the collaborative editing channel of a post. The listeners are kept
+/// with the WebSocket, they are freed when it is dropped.
+pub struct CollabSocket {
+    socket: WebSocket,
+    _on_message: Closure<dyn FnMut(MessageEvent)>,
+    _on_close: Closure<dyn FnMut(CloseEvent)>,
+}
+
+impl CollabSocket {
+    /// The function `connect` opens the channel of a post with the cookies of the user.
+    ///
+    /// Arguments:
+    ///
+    /// * `workspace_id`: The `workspace_id` parameter is the workspace the post is reached through.
+    /// * `post_id`: The `post_id` parameter is the id of the edited post.
+    /// * `on_message`: Called with every message of the server.
+    /// * `on_close`: Called when the connection is closed or lost.
+    ///
+    /// Returns:
+    ///
+    /// the channel, or `None` if the browser could not open it.
+    pub fn connect(
+        workspace_id: Uuid,
+        post_id: Uuid,
+        on_message: Callback<CollabServerMessage>,
+        on_close: Callback<()>,
+    ) -> Option<CollabSocket> {
+        //the backend serves the channel on the address of the API, as ws:// or wss://
+        let url = api_url(&format!(
+            "/workspaces/{}/posts/{}/collab",
+            workspace_id, post_id
+        ))
+        .replacen("http", "ws", 1);
+        let socket = match WebSocket::new(&url) {
+            Ok(socket) => socket,
+            Err(e) => {
+                log::error!("Failed to open the editing channel: {:?}", e);
+                return None;
+            }
+        };
+
+        let on_message = Closure::<dyn FnMut(MessageEvent)>::new(move |message: MessageEvent| {
+            let data = message.data().as_string().unwrap_or_default();
+            match serde_json::from_str::<CollabServerMessage>(&data) {
+                Ok(message) => on_message.emit(message),
+                Err(e) => log::error!("Failed to read editing message: {}", e),
+            }
+        });
+        socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        let on_close =
+            Closure::<dyn FnMut(CloseEvent)>::new(move |_: CloseEvent| on_close.emit(()));
+        socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+
+        Some(CollabSocket {
+            socket,
+            _on_message: on_message,
+            _on_close: on_close,
+        })
+    }
+
+    /// The function `send` sends a message to the server. Messages sent before the connection is
+    /// open or after it is closed are dropped.
+    pub fn send(&self, message: &CollabClientMessage) {
+        if self.socket.ready_state() != WebSocket::OPEN {
+            return;
+        }
+        match serde_json::to_string(message) {
+            Ok(text) => {
+                if let Err(e) = self.socket.send_with_str(&text) {
+                    log::error!("Failed to send editing message: {:?}", e);
+                }
+            }
+            Err(e) => log::error!("Failed to encode editing message: {}", e),
+        }
+    }
+}
+
+impl CollabSocket {
+    /// The function `close` closes the connection, `on_close` is called when it is closed.
+    pub fn close(&self) {
+        if let Err(e) = self.socket.close() {
+            log::error!("Failed to close the editing channel: {:?}", e);
+        }
+    }
+}
+
+impl Drop for CollabSocket {
+    //the closed channel does not report back to a component that is gone
+    fn drop(&mut self) {
+        self.socket.set_onmessage(None);
+        self.socket.set_onclose(None);
+        let _ = self.socket.close();
+    }
+}
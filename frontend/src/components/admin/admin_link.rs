@@ -0,0 +1,40 @@
+use common::{ResponseUser, Role};
+use yew::prelude::*;
+use yew_hooks::{use_async, use_effect_once};
+use yew_router::prelude::Link;
+
+use crate::{api::request, i18n::Text, routes::Route};
+use reqwasm::http::Method;
+
+/// The `AdminLink` component renders a link to the admin page, but only for admins.
+///
+/// Returns:
+///
+/// The `AdminLink` component returns a HTML element or nothing.
+#[function_component(AdminLink)]
+pub fn admin_link() -> Html {
+    let api_request = {
+        use_async(async move {
+            request::<(), ResponseUser>(Method::GET, "/auth/me".to_owned(), None).await
+        })
+    };
+
+    {
+        let api_request = api_request.clone();
+        use_effect_once(move || {
+            api_request.run();
+            || log::debug!("Get current user request started!")
+        })
+    }
+
+    match &api_request.data {
+        Some(user) if user.role == Role::Admin => html! {
+            <button class="link">
+                <Link<Route> to={Route::Admin}>
+                    <Text id="nav.admin" />
+                </Link<Route>>
+            </button>
+        },
+        _ => html!(),
+    }
+}
@@ -0,0 +1,7 @@
+pub mod admin_link;
+pub mod stats;
+pub mod users_list;
+
+pub use admin_link::*;
+pub use stats::*;
+pub use users_list::*;
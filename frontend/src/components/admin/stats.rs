@@ -0,0 +1,35 @@
+use common::SystemStats;
+use yew::prelude::*;
+
+use crate::i18n::Text;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub stats: SystemStats,
+}
+
+/// The `AdminStats` component renders the system numbers on the admin page.
+///
+/// Arguments:
+///
+/// * `stats`: The numbers returned by `/admin/stats`.
+///
+/// Returns:
+///
+/// The `AdminStats` component returns a HTML element.
+#[function_component(AdminStats)]
+pub fn admin_stats(props: &Props) -> Html {
+    let stats = &props.stats;
+
+    html! {
+        <div class="admin-stats">
+            <div><h2>{stats.users}</h2><p><Text id="admin.stats.users" /></p></div>
+            <div><h2>{stats.disabled_users}</h2><p><Text id="admin.stats.disabled" /></p></div>
+            <div><h2>{stats.moderators}</h2><p><Text id="admin.stats.moderators" /></p></div>
+            <div><h2>{stats.admins}</h2><p><Text id="admin.stats.admins" /></p></div>
+            <div><h2>{stats.posts}</h2><p><Text id="admin.stats.posts" /></p></div>
+            <div><h2>{format!("{}h {}m", stats.uptime / 3600, stats.uptime % 3600 / 60)}</h2><p><Text id="admin.stats.uptime" /></p></div>
+            <div><h2>{stats.version.clone()}</h2><p><Text id="admin.stats.version" /></p></div>
+        </div>
+    }
+}
@@ -0,0 +1,183 @@
+use common::{ApiResponse, SystemStats, UserSummary};
+use yew::prelude::*;
+use yew_hooks::{use_async, use_effect_once};
+use yew_router::prelude::use_navigator;
+
+use super::AdminStats;
+use crate::{api::request, components::list_erors::ListErrors, i18n::Text, routes::Route};
+use reqwasm::http::Method;
+
+/// The `UserAction` enum is a moderation action that the admin can run from the users table.
+#[derive(Clone, Copy, PartialEq)]
+pub enum UserAction {
+    Disable,
+    Enable,
+    Logout,
+    Delete,
+}
+
+impl UserAction {
+    //catalog key of the button text
+    fn label(&self) -> &'static str {
+        match self {
+            UserAction::Disable => "admin.action.disable",
+            UserAction::Enable => "admin.action.enable",
+            UserAction::Logout => "admin.action.logout",
+            UserAction::Delete => "admin.action.delete",
+        }
+    }
+}
+
+/// The `AdminUsers` component is the admin page. It shows the system stats and the list of users with
+/// the moderation buttons. Users that are not admins are sent to the 404 page.
+///
+/// Returns:
+///
+/// The `AdminUsers` component returns a HTML element.
+#[function_component(AdminUsers)]
+pub fn admin_users() -> Html {
+    let action = use_state(|| None::<(uuid::Uuid, UserAction)>);
+
+    //moderation request
+    let action_request = {
+        let action = action.clone();
+        use_async(async move {
+            match *action {
+                Some((id, UserAction::Delete)) => {
+                    request::<(), ApiResponse>(Method::DELETE, format!("/admin/users/{}", id), None)
+                        .await
+                }
+                Some((id, user_action)) => {
+                    let path = match user_action {
+                        UserAction::Disable => "disable",
+                        UserAction::Enable => "enable",
+                        _ => "logout",
+                    };
+                    request::<(), ApiResponse>(
+                        Method::POST,
+                        format!("/admin/users/{}/{}", id, path),
+                        None,
+                    )
+                    .await
+                }
+                None => Ok(ApiResponse {
+                    status: "success".to_owned(),
+                }),
+            }
+        })
+    };
+
+    let users_request = {
+        use_async(async move {
+            request::<(), Vec<UserSummary>>(Method::GET, "/admin/users".to_owned(), None).await
+        })
+    };
+
+    let stats_request = {
+        use_async(async move {
+            request::<(), SystemStats>(Method::GET, "/admin/stats".to_owned(), None).await
+        })
+    };
+
+    //sand api requests when page is loading
+    {
+        let users_request = users_request.clone();
+        let stats_request = stats_request.clone();
+        use_effect_once(move || {
+            stats_request.run();
+            users_request.run();
+            || log::debug!("Admin requests started!")
+        })
+    }
+
+    //the page is hidden from everybody except admins
+    let navigator = use_navigator();
+    {
+        use_effect_with_deps(
+            move |request| {
+                if let Some(error) = &request.error {
+                    if let common::ErrorTypes::Auth(_e) = &error.error_type {
+                        log::error!("User is not an admin!");
+                        if let Some(navigation) = &navigator {
+                            navigation.push(&Route::NotFound);
+                        }
+                    }
+                }
+            },
+            stats_request.clone(),
+        )
+    }
+
+    //reload the data after every action
+    {
+        let users_request = users_request.clone();
+        let stats_request = stats_request.clone();
+        use_effect_with_deps(
+            move |_| {
+                users_request.run();
+                stats_request.run();
+            },
+            action_request.clone(),
+        )
+    }
+
+    let run_action = {
+        let action_request = action_request.clone();
+        Callback::from(move |next: (uuid::Uuid, UserAction)| {
+            action.set(Some(next));
+            action_request.run();
+        })
+    };
+
+    html! {
+        <div>
+            <ListErrors error={action_request.error.clone()} />
+            {
+                if let Some(stats) = &stats_request.data {
+                    html!(<AdminStats stats={stats.clone()} />)
+                } else {
+                    html!()
+                }
+            }
+            {
+                if let Some(users) = &users_request.data {
+                    html!(
+                        <table class="admin-users">
+                            <tr>
+                                <th><Text id="admin.users.username" /></th>
+                                <th><Text id="admin.users.role" /></th>
+                                <th><Text id="admin.users.posts" /></th>
+                                <th><Text id="admin.users.created" /></th>
+                                <th></th>
+                            </tr>
+                            {for users.iter().map(|user| {
+                                let toggle = if user.disabled { UserAction::Enable } else { UserAction::Disable };
+                                let button = |user_action: UserAction| {
+                                    let run_action = run_action.clone();
+                                    let id = user.id;
+                                    let onclick = Callback::from(move |_| run_action.emit((id, user_action)));
+                                    html!(<button class="link" {onclick}><Text id={user_action.label()} /></button>)
+                                };
+                                html!(
+                                    <tr class={classes!(user.disabled.then_some("disabled"))}>
+                                        <td>{user.username.clone()}</td>
+                                        <td>{user.role.to_string()}</td>
+                                        <td>{user.posts}</td>
+                                        <td>{user.created_at.date().to_string()}</td>
+                                        <td class="flex-container">
+                                            {button(toggle)}
+                                            {button(UserAction::Logout)}
+                                            {button(UserAction::Delete)}
+                                        </td>
+                                    </tr>
+                                )
+                            })}
+                        </table>
+                    )
+                } else {
+                    html!(<ListErrors error={users_request.error.clone()} />)
+                }
+            }
+        </div>
+    }
+}
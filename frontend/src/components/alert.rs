@@ -1,10 +1,11 @@
 use yew::prelude::*;
 use yew_router::prelude::Link;
 
+use crate::i18n::Text;
 use crate::routes::Route;
 #[derive(Properties, PartialEq)]
 pub struct Props {
-    pub message: String,
+    pub message: AttrValue,
     pub route: Route,
 }
 
@@ -15,7 +16,7 @@ pub struct Props {
 ///
 /// * `route`: The route when user will redirect after clicking the button
 ///
-/// * `message`: The message that alert shows
+/// * `message`: The catalog key of the message that alert shows
 ///
 /// Returns:
 ///
@@ -26,7 +27,7 @@ pub fn alert_component(props: &Props) -> Html {
         <div class="alert">
             <div class="alert_message">
                 <h1>
-                    {props.message.clone()}
+                    <Text id={props.message.clone()} />
                 </h1>
             </div>
             <button class="alert_btn">
@@ -34,15 +35,15 @@ pub fn alert_component(props: &Props) -> Html {
                     match props.route{
                         Route::LogIn=>html!(
                             <Link<Route> to={props.route.clone()}>
-                                { "Log In" }
+                                <Text id="nav.log_in" />
                             </Link<Route>>),
                         Route::Posts=>html!(
                             <Link<Route> to={props.route.clone()}>
-                                { "Go to posts" }
+                                <Text id="nav.go_to_posts" />
                             </Link<Route>>),
                         Route::Home=>html!(
                             <Link<Route> to={props.route.clone()}>
-                                { "Home" }
+                                <Text id="nav.home" />
                             </Link<Route>>
                         ),
                         _=>html!()
@@ -1,5 +1,6 @@
 use crate::api::request;
-use crate::components::list_erors::ListErrors;
+use crate::components::list_erors::{FieldErrors, ListErrors};
+use crate::i18n::Text;
 use common::{ResponseUser, UserFormData};
 use reqwasm::http::Method;
 
@@ -91,7 +92,7 @@ pub fn authorization_form(props: &Props) -> Html {
         <div class="form_box">
 
             <header class="form_box_title">
-                <h2>{settings.title.clone()}</h2>
+                <h2><Text id={settings.title} /></h2>
             </header>
             <ListErrors error={api_request.error.clone()} />
             <form {onsubmit} class="form">
@@ -100,13 +101,13 @@ pub fn authorization_form(props: &Props) -> Html {
                         FormType::SignUp=>html!(
                             <div class="form_route">
                                 <Link<Route> to={Route::LogIn}>
-                                    { "Have an account?" }
+                                    <Text id="form.have_account" />
                                 </Link<Route>>
                             </div>),
                         FormType::LogIn=>html!(
                             <div class="form_route">
                                 <Link<Route> to={Route::SignUp}>
-                                    { "Don't have an account?" }
+                                    <Text id="form.no_account" />
                                 </Link<Route>>
                             </div>)
                     }
@@ -114,7 +115,7 @@ pub fn authorization_form(props: &Props) -> Html {
                 <div class="form_field">
                     <label for="username">
                         <svg stroke="currentColor" fill="currentColor" stroke-width="0" viewBox="0 0 16 16" height="1em" width="1em" xmlns="http://www.w3.org/2000/svg"><path d="M13.468 12.37C12.758 11.226 11.195 10 8 10s-4.757 1.225-5.468 2.37A6.987 6.987 0 008 15a6.987 6.987 0 005.468-2.63z"></path><path fill-rule="evenodd" d="M8 9a3 3 0 100-6 3 3 0 000 6z" clip-rule="evenodd"></path><path fill-rule="evenodd" d="M8 1a7 7 0 100 14A7 7 0 008 1zM0 8a8 8 0 1116 0A8 8 0 010 8z" clip-rule="evenodd"></path></svg>
-                        {"Username:"}
+                        <Text id="form.username" />
                     </label>
                     <input
                         type="text"
@@ -124,11 +125,12 @@ pub fn authorization_form(props: &Props) -> Html {
                         placeholder="AdminPro"
                         required=true
                     />
+                    <FieldErrors error={api_request.error.clone()} field="username" />
                 </div>
                 <div class="form_field">
                     <label for="password">
                         <svg stroke="currentColor" fill="currentColor" stroke-width="0" viewBox="0 0 24 24" height="1em" width="1em" xmlns="http://www.w3.org/2000/svg"><g><path fill="none" d="M0 0h24v24H0z"></path><path d="M18 8h2a1 1 0 0 1 1 1v12a1 1 0 0 1-1 1H4a1 1 0 0 1-1-1V9a1 1 0 0 1 1-1h2V7a6 6 0 1 1 12 0v1zm-2 0V7a4 4 0 1 0-8 0v1h8zm-5 6v2h2v-2h-2zm-4 0v2h2v-2H7zm8 0v2h2v-2h-2z"></path></g></svg>
-                        {"Password:"}
+                        <Text id="form.password" />
                     </label>
                     <input
                         type="password"
@@ -138,9 +140,10 @@ pub fn authorization_form(props: &Props) -> Html {
                         placeholder="SecretPas196"
                         required=true
                     />
+                    <FieldErrors error={api_request.error.clone()} field="password" />
                 </div>
                 <button type="submit" class="form_btn">
-                    {settings.value.clone()}
+                    <Text id={settings.value} />
                 </button>
 
             </form>
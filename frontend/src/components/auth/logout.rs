@@ -3,7 +3,7 @@ use yew::prelude::*;
 use yew_hooks::use_async;
 use yew_router::prelude::use_navigator;
 
-use crate::{api::request, routes::Route};
+use crate::{api::request, i18n::Text, routes::Route};
 use reqwasm::http::Method;
 
 /// Component that renders a "Log Out" button and handles the
@@ -39,7 +39,7 @@ pub fn logout_button() -> Html {
 
     html! {
         <div>
-            <button class="link" {onclick}>{"Log Out"}</button>
+            <button class="link" {onclick}><Text id="nav.log_out" /></button>
         </div>
     }
 }
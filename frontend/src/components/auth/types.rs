@@ -22,30 +22,31 @@ impl fmt::Display for FormType {
     }
 }
 
-/// The `FormSettings` struct represents the settings for a form, with a title and a value.
+/// The `FormSettings` struct represents the settings for a form, with a title and a value. Both are
+/// catalog keys, the texts are translated to the selected language.
 ///
 /// Properties:
 ///
-/// * `title`: The `title` property is a string that represents the title of the form. It can be used to
+/// * `title`: The `title` property is the key of the title of the form. It can be used to
 /// display a message or a heading to the user.
-/// * `value`: The `value` property in the `FormSettings` struct represents the value that will be
-/// displayed on the form button. It can be either "Sign Up" or "Log In" depending on the `FormType`
+/// * `value`: The `value` property in the `FormSettings` struct represents the key of the text that
+/// will be displayed on the form button. It can be "Sign Up" or "Log In" depending on the `FormType`
 /// variant.
 pub struct FormSettings {
-    pub title: String,
-    pub value: String,
+    pub title: &'static str,
+    pub value: &'static str,
 }
 
 impl From<FormType> for FormSettings {
     fn from(value: FormType) -> Self {
         match value {
             FormType::SignUp => FormSettings {
-                title: "Create an account!".to_owned(),
-                value: "Sign Up".to_owned(),
+                title: "form.signup.title",
+                value: "nav.sign_up",
             },
             FormType::LogIn => FormSettings {
-                title: "Welcome back!".to_owned(),
-                value: "Log In".to_string(),
+                title: "form.login.title",
+                value: "nav.log_in",
             },
         }
     }
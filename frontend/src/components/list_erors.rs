@@ -11,7 +11,9 @@ pub struct Props {
 ///
 /// Arguments:
 ///
-/// * `error`: Optinal argument that has type `ErrorResponse`. Shows only Auth and Validation erros
+/// * `error`: Optinal argument that has type `ErrorResponse`. Shows the Auth, Validation, NotFound, Conflict,
+/// RateLimited and PayloadTooLarge errors, which have a message for the user.
+/// Validation errors with field errors are shown next to the fields by `FieldErrors` instead.
 ///
 /// Returns:
 ///
@@ -23,10 +25,14 @@ pub fn list_errors(props: &Props) -> Html {
             <ul class="error_messages">
                 {
                     match error.error_type{
-                        ErrorTypes::Auth(_)=>html!(
+                        ErrorTypes::Auth(_)
+                        | ErrorTypes::NotFound
+                        | ErrorTypes::Conflict
+                        | ErrorTypes::RateLimited
+                        | ErrorTypes::PayloadTooLarge=>html!(
                          <li> {error.message.clone()}</li>
                         ),
-                        ErrorTypes::ValidationError=>html!(
+                        ErrorTypes::ValidationError if error.errors.is_empty()=>html!(
                             <li> {error.message.clone()}</li>
                            ),
                         _=>html!()
@@ -38,3 +44,39 @@ pub fn list_errors(props: &Props) -> Html {
         html!()
     }
 }
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct FieldProps {
+    pub error: Option<ErrorResponse>,
+    pub field: AttrValue,
+}
+
+/// The `FieldErrors` component renders the failed checks of one form field.
+///
+/// Arguments:
+///
+/// * `error`: Optinal argument that has type `ErrorResponse`, the response of the form request.
+/// * `field`: The name of the field, for example `username`.
+///
+/// Returns:
+///
+/// The `FieldErrors` function returns a HTML element, empty when the field has no errors.
+#[function_component(FieldErrors)]
+pub fn field_errors(props: &FieldProps) -> Html {
+    let Some(error) = &props.error else {
+        return html!();
+    };
+    if error.field_errors(&props.field).next().is_none() {
+        return html!();
+    }
+    let messages = error
+        .field_errors(&props.field)
+        .map(|field_error| html!(<li data-code={field_error.code.clone()}>{field_error.message.clone()}</li>))
+        .collect::<Html>();
+
+    html! {
+        <ul class="field_errors">
+            {messages}
+        </ul>
+    }
+}
@@ -0,0 +1,39 @@
+use common::{translate, Locale};
+use web_sys::HtmlSelectElement;
+use yew::prelude::*;
+
+use crate::i18n::{store_locale, use_locale};
+
+/// The `LocaleSwitcher` component renders a select with the supported languages. The choice is saved
+/// and sent to the backend with every request, so the errors come in the same language.
+///
+/// Returns:
+///
+/// The `LocaleSwitcher` component returns a HTML element.
+#[function_component(LocaleSwitcher)]
+pub fn locale_switcher() -> Html {
+    let locale = use_locale();
+
+    let onchange = {
+        let locale = locale.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Some(selected) = Locale::from_tag(&select.value()) {
+                store_locale(selected);
+                locale.set(selected);
+            }
+        })
+    };
+
+    html! {
+        <div class="locale_switcher">
+            <select {onchange} aria-label={translate(*locale, "locale.label")}>
+                {for Locale::ALL.iter().map(|option| html!(
+                    <option value={option.code()} selected={*option == *locale}>
+                        {option.name()}
+                    </option>
+                ))}
+            </select>
+        </div>
+    }
+}
@@ -1,4 +1,7 @@
+pub mod admin;
 pub mod alert;
 pub mod auth;
 pub mod list_erors;
+pub mod locale_switcher;
 pub mod posts;
+pub mod workspace_switcher;
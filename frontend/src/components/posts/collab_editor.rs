@@ -0,0 +1,378 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use common::{CollabClientMessage, CollabCursor, CollabDoc, CollabPeer, CollabServerMessage};
+use uuid::Uuid;
+use web_sys::{Element, HtmlTextAreaElement};
+use yew::prelude::*;
+use yew_hooks::use_interval;
+
+use crate::collab::CollabSocket;
+use crate::i18n::Text;
+
+//the time before a lost connection is opened again, in milliseconds
+const RECONNECT_DELAY: u32 = 3000;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub workspace_id: Uuid,
+    pub post_id: Uuid,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Status {
+    Connecting,
+    Editing,
+    Viewing,
+    Disconnected,
+}
+
+/// The `Peers` struct is the other editors of the post by their sessions.
+#[derive(Default, PartialEq)]
+struct Peers(BTreeMap<u64, CollabPeer>);
+
+enum PeersAction {
+    Reset(Vec<CollabPeer>),
+    Update(CollabPeer),
+    Remove(u64),
+}
+
+impl Reducible for Peers {
+    type Action = PeersAction;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let mut peers = self.0.clone();
+        match action {
+            PeersAction::Reset(list) => {
+                peers = list
+                    .into_iter()
+                    .map(|peer| (peer.client_id, peer))
+                    .collect()
+            }
+            PeersAction::Update(peer) => {
+                peers.insert(peer.client_id, peer);
+            }
+            PeersAction::Remove(client_id) => {
+                peers.remove(&client_id);
+            }
+        }
+        Rc::new(Peers(peers))
+    }
+}
+
+/// The `CollabEditor` component edits the description of a post together with the other editors.
+/// The changes are merged by the backend and stored as the description, the carets of the others
+/// are shown in the text. Viewers of the post follow the changes without editing.
+///
+/// Arguments:
+///
+/// * `workspace_id`: The workspace the post is reached through.
+/// * `post_id`: The id of the edited post.
+///
+/// Returns:
+///
+/// The `CollabEditor` component returns a HTML element.
+#[function_component(CollabEditor)]
+pub fn collab_editor(props: &Props) -> Html {
+    let textarea = use_node_ref();
+    let mirror = use_node_ref();
+    let doc = use_mut_ref(|| None::<CollabDoc>);
+    let socket = use_mut_ref(|| None::<CollabSocket>);
+    let text = use_state(String::new);
+    let status = use_state(|| Status::Connecting);
+    let error = use_state(|| None::<String>);
+    let attempt = use_state(|| 0u32);
+    let peers = use_reducer(Peers::default);
+
+    {
+        let textarea = textarea.clone();
+        let doc = doc.clone();
+        let socket = socket.clone();
+        let text = text.clone();
+        let status = status.clone();
+        let error = error.clone();
+        let peers = peers.dispatcher();
+        use_effect_with_deps(
+            move |(workspace_id, post_id, _)| {
+                status.set(Status::Connecting);
+                let on_message = {
+                    let socket = socket.clone();
+                    let status = status.clone();
+                    Callback::from(move |message: CollabServerMessage| {
+                        let editor = textarea.cast::<HtmlTextAreaElement>();
+                        match message {
+                            CollabServerMessage::Welcome {
+                                client_id,
+                                ops,
+                                peers: others,
+                                read_only,
+                            } => match CollabDoc::from_state(client_id, &ops) {
+                                Ok(welcome) => {
+                                    let value = welcome.text();
+                                    if let Some(editor) = &editor {
+                                        editor.set_value(&value);
+                                    }
+                                    *doc.borrow_mut() = Some(welcome);
+                                    text.set(value);
+                                    peers.dispatch(PeersAction::Reset(others));
+                                    error.set(None);
+                                    status.set(match read_only {
+                                        true => Status::Viewing,
+                                        false => Status::Editing,
+                                    });
+                                }
+                                Err(e) => {
+                                    log::error!("Failed to read the edited note: {}", e);
+                                    close(&socket);
+                                }
+                            },
+                            CollabServerMessage::Update { ops } => {
+                                let mut doc = doc.borrow_mut();
+                                let Some(doc) = doc.as_mut() else {
+                                    return;
+                                };
+                                //the selection stays at its characters while the text changes
+                                let selection =
+                                    editor.as_ref().map(|editor| selection(editor, doc));
+                                if ops.iter().any(|op| doc.apply(op).is_err()) {
+                                    log::error!("Failed to merge a change, reloading the note");
+                                    close(&socket);
+                                    return;
+                                }
+                                let value = doc.text();
+                                if let (Some(editor), Some(selection)) = (&editor, selection) {
+                                    editor.set_value(&value);
+                                    let start = utf16_index(&value, doc.index(selection.anchor));
+                                    let end = utf16_index(&value, doc.index(selection.head));
+                                    let _ = editor.set_selection_range(start, end);
+                                }
+                                text.set(value);
+                            }
+                            CollabServerMessage::Presence { peer } => {
+                                peers.dispatch(PeersAction::Update(peer))
+                            }
+                            CollabServerMessage::Left { client_id } => {
+                                peers.dispatch(PeersAction::Remove(client_id))
+                            }
+                            CollabServerMessage::Error { code } => {
+                                //a refused change left the copy behind, it is loaded again
+                                if code == "collab.invalid_update" {
+                                    close(&socket);
+                                }
+                                error.set(Some(code));
+                            }
+                        }
+                    })
+                };
+                let on_close = {
+                    let status = status.clone();
+                    Callback::from(move |_| status.set(Status::Disconnected))
+                };
+                let connection =
+                    CollabSocket::connect(*workspace_id, *post_id, on_message, on_close);
+                if connection.is_none() {
+                    status.set(Status::Disconnected);
+                }
+                *socket.borrow_mut() = connection;
+                move || {
+                    socket.borrow_mut().take();
+                }
+            },
+            (props.workspace_id, props.post_id, *attempt),
+        );
+    }
+
+    {
+        let attempt = attempt.clone();
+        let delay = match *status {
+            Status::Disconnected => RECONNECT_DELAY,
+            _ => 0,
+        };
+        use_interval(move || attempt.set(*attempt + 1), delay);
+    }
+
+    let onselect = {
+        let textarea = textarea.clone();
+        let doc = doc.clone();
+        let socket = socket.clone();
+        Callback::from(move |_: Event| send_presence(&textarea, &doc.borrow(), &socket.borrow()))
+    };
+    let onkeyup = {
+        let onselect = onselect.clone();
+        Callback::from(move |e: KeyboardEvent| onselect.emit(e.into()))
+    };
+    let onmouseup = {
+        let onselect = onselect.clone();
+        Callback::from(move |e: MouseEvent| onselect.emit(e.into()))
+    };
+
+    let oninput = {
+        let textarea = textarea.clone();
+        let doc = doc.clone();
+        let socket = socket.clone();
+        let text = text.clone();
+        Callback::from(move |_: InputEvent| {
+            let Some(editor) = textarea.cast::<HtmlTextAreaElement>() else {
+                return;
+            };
+            let mut doc = doc.borrow_mut();
+            let Some(doc) = doc.as_mut() else {
+                return;
+            };
+            //the browser only tells the new value, the change is what differs from the document
+            let value = editor.value();
+            let (index, removed, inserted) = diff(&doc.text(), &value);
+            let mut ops = doc.delete(index, removed);
+            ops.extend(doc.insert(index, &inserted));
+            if let Some(socket) = socket.borrow().as_ref() {
+                if !ops.is_empty() {
+                    socket.send(&CollabClientMessage::Update { ops });
+                }
+                socket.send(&CollabClientMessage::Presence {
+                    cursor: Some(selection(&editor, doc)),
+                });
+            }
+            text.set(value);
+        })
+    };
+
+    //the mirror behind the text area scrolls with it
+    let onscroll = {
+        let textarea = textarea.clone();
+        let mirror = mirror.clone();
+        Callback::from(move |_: Event| {
+            if let (Some(editor), Some(mirror)) =
+                (textarea.cast::<Element>(), mirror.cast::<Element>())
+            {
+                mirror.set_scroll_top(editor.scroll_top());
+            }
+        })
+    };
+
+    //the carets of the others, in the order of the text
+    let mut carets: Vec<(usize, &CollabPeer)> = match doc.borrow().as_ref() {
+        Some(doc) => peers
+            .0
+            .values()
+            .filter_map(|peer| Some((doc.index(peer.cursor?.head), peer)))
+            .collect(),
+        None => Vec::new(),
+    };
+    carets.sort_by_key(|(index, _)| *index);
+    let chars: Vec<char> = text.chars().collect();
+    let mut start = 0;
+    let mut mirrored = Vec::new();
+    for (index, peer) in carets {
+        let index = index.min(chars.len());
+        mirrored.push(html!({ chars[start..index].iter().collect::<String>() }));
+        mirrored.push(html!(
+            <span class="collab-caret" data-name={peer.username.clone()}></span>
+        ));
+        start = index;
+    }
+    mirrored.push(html!({ chars[start..].iter().collect::<String>() }));
+
+    html! {
+        <div class="collab">
+            <p class="collab-status">
+                {
+                    match *status {
+                        Status::Connecting => html!(<Text id="collab.connecting" />),
+                        Status::Disconnected => html!(<Text id="collab.disconnected" />),
+                        Status::Viewing => html!(<Text id="collab.viewing" />),
+                        Status::Editing => html!(),
+                    }
+                }
+            </p>
+            <div class="collab-editor">
+                <div class="collab-mirror" ref={mirror} aria-hidden="true">
+                    {for mirrored}
+                </div>
+                <textarea
+                    class="collab-input"
+                    ref={textarea}
+                    readonly={*status != Status::Editing}
+                    {oninput}
+                    {onselect}
+                    {onkeyup}
+                    {onmouseup}
+                    {onscroll}
+                />
+            </div>
+            if !peers.0.is_empty() {
+                <p class="collab-peers">
+                    <Text id="collab.editors" />
+                    {for peers.0.values().map(|peer| html!(
+                        <span class="collab-peer">{&peer.username}</span>
+                    ))}
+                </p>
+            }
+            if let Some(code) = &*error {
+                <ul class="error_messages">
+                    <li><Text id={code.clone()} /></li>
+                </ul>
+            }
+        </div>
+    }
+}
+
+fn close(socket: &RefCell<Option<CollabSocket>>) {
+    if let Some(socket) = socket.borrow().as_ref() {
+        socket.close();
+    }
+}
+
+fn send_presence(textarea: &NodeRef, doc: &Option<CollabDoc>, socket: &Option<CollabSocket>) {
+    if let (Some(editor), Some(doc), Some(socket)) =
+        (textarea.cast::<HtmlTextAreaElement>(), doc, socket)
+    {
+        socket.send(&CollabClientMessage::Presence {
+            cursor: Some(selection(&editor, doc)),
+        });
+    }
+}
+
+//the selection of the text area as positions of the document, which has the same text
+fn selection(editor: &HtmlTextAreaElement, doc: &CollabDoc) -> CollabCursor {
+    let value = editor.value();
+    let start = editor.selection_start().ok().flatten().unwrap_or_default();
+    let end = editor.selection_end().ok().flatten().unwrap_or_default();
+    CollabCursor {
+        anchor: doc.position(char_index(&value, start)),
+        head: doc.position(char_index(&value, end)),
+    }
+}
+
+//the browser counts UTF-16 units, the document counts characters
+fn char_index(text: &str, utf16: u32) -> usize {
+    let mut units = 0;
+    text.chars()
+        .take_while(|c| {
+            units += c.len_utf16() as u32;
+            units <= utf16
+        })
+        .count()
+}
+
+fn utf16_index(text: &str, index: usize) -> u32 {
+    text.chars().take(index).map(|c| c.len_utf16() as u32).sum()
+}
+
+//the edit that makes `new` of `old`: the place, the number of deleted characters and the inserted text
+fn diff(old: &str, new: &str) -> (usize, usize, String) {
+    let old: Vec<char> = old.chars().collect();
+    let new: Vec<char> = new.chars().collect();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    (
+        prefix,
+        old.len() - prefix - suffix,
+        new[prefix..new.len() - suffix].iter().collect(),
+    )
+}
@@ -0,0 +1,36 @@
+use yew::prelude::*;
+
+use crate::{api::api_url, i18n::Text, workspace::use_workspace};
+
+/// The `ExportLinks` component renders the buttons that download all notes of the selected workspace,
+/// as one JSON document or as a zip of Markdown files. The browser sends the auth cookies with the
+/// download.
+///
+/// Returns:
+///
+/// The `ExportLinks` component returns a HTML element.
+#[function_component(ExportLinks)]
+pub fn export_links() -> Html {
+    let workspace = use_workspace();
+    //the links are shown after the workspaces are loaded
+    let Some(workspace) = *workspace else {
+        return html!();
+    };
+    let href = |format: &str| {
+        api_url(&format!(
+            "/workspaces/{}/export?format={}",
+            workspace, format
+        ))
+    };
+
+    html! {
+        <div class="export">
+            <a class="export-link" href={href("json")}>
+                <Text id="posts.export.json" />
+            </a>
+            <a class="export-link" href={href("markdown")}>
+                <Text id="posts.export.markdown" />
+            </a>
+        </div>
+    }
+}
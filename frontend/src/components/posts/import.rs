@@ -0,0 +1,147 @@
+use common::{translate, ErrorResponse, ErrorTypes, ImportReport, ImportStatus};
+use web_sys::{FormData, HtmlInputElement};
+use yew::prelude::*;
+use yew_hooks::use_async;
+
+use crate::api::upload;
+use crate::components::list_erors::ListErrors;
+use crate::i18n::{stored_locale, use_locale, Text};
+use crate::workspace::{use_workspace, workspace_uri};
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub on_import: Callback<()>,
+}
+
+/// The `ImportForm` component uploads files with notes from the portal, Markdown, Google Keep or
+/// Evernote, and shows the report of the import. A dry run only shows the report.
+///
+/// Arguments:
+///
+/// * `on_import`: Called after notes were imported, to reload the list.
+///
+/// Returns:
+///
+/// The `ImportForm` component returns a HTML element.
+#[function_component(ImportForm)]
+pub fn import_form(props: &Props) -> Html {
+    let locale = use_locale();
+    let files = use_node_ref();
+    let dry_run = use_state(|| false);
+    let workspace = use_workspace();
+
+    let api_request = {
+        let files = files.clone();
+        let dry_run = dry_run.clone();
+        let workspace = *workspace;
+        use_async(async move {
+            let form = FormData::new().map_err(|e| {
+                ErrorResponse::new(
+                    Some(format!("{:?}", e)),
+                    Some(translate(stored_locale(), "request.failed")),
+                    ErrorTypes::RequestError,
+                )
+            })?;
+            let selected = files
+                .cast::<HtmlInputElement>()
+                .and_then(|input| input.files());
+            if let Some(selected) = selected {
+                for file in (0..selected.length()).filter_map(|index| selected.get(index)) {
+                    if form
+                        .append_with_blob_and_filename("file", &file, &file.name())
+                        .is_err()
+                    {
+                        log::error!("Failed to add the file {}!", file.name());
+                    }
+                }
+            }
+            let uri = workspace_uri(workspace, &format!("/import?dry_run={}", *dry_run)).await?;
+            upload::<ImportReport>(uri, form).await
+        })
+    };
+
+    {
+        let on_import = props.on_import.clone();
+        use_effect_with_deps(
+            move |request| {
+                if let Some(report) = &request.data {
+                    if !report.dry_run && report.imported > 0 {
+                        on_import.emit(());
+                    }
+                }
+            },
+            api_request.clone(),
+        )
+    }
+
+    let onchange_dry_run = {
+        let dry_run = dry_run.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            dry_run.set(input.checked());
+        })
+    };
+
+    let onsubmit = {
+        let api_request = api_request.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            api_request.run();
+        })
+    };
+
+    let report = api_request.data.as_ref().map(|report| {
+        let summary = [
+            ("import.report.imported", report.imported),
+            ("import.report.skipped", report.skipped),
+            ("import.report.failed", report.failed),
+        ]
+        .iter()
+        .map(|(key, count)| format!("{}: {}", translate(*locale, key), count))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+        html! {
+            <div class="import-report">
+                <p>
+                    if report.dry_run {
+                        <strong><Text id="import.dry_run" />{": "}</strong>
+                    }
+                    {summary}
+                </p>
+                <ul>
+                    {for report.items.iter().filter(|item| item.status != ImportStatus::Imported).map(|item| html!(
+                        <li class="import-report-item">
+                            <span class="import-report-source">{item.source.clone()}</span>
+                            {" "}
+                            {item.error.as_ref().and_then(|error| error.message.clone())}
+                        </li>
+                    ))}
+                </ul>
+            </div>
+        }
+    });
+
+    html! {
+        <div class="import">
+            <ListErrors error={api_request.error.clone()} />
+            <form {onsubmit} class="import-form">
+                <input
+                    type="file"
+                    ref={files}
+                    multiple=true
+                    required=true
+                    accept=".json,.md,.markdown,.txt,.enex,.zip"
+                />
+                <label>
+                    <input type="checkbox" checked={*dry_run} onchange={onchange_dry_run} />
+                    <Text id="import.dry_run" />
+                </label>
+                <button type="submit" class="import-btn" disabled={api_request.loading}>
+                    <Text id="import.submit" />
+                </button>
+            </form>
+            {report}
+        </div>
+    }
+}
@@ -1,7 +1,17 @@
+pub mod collab_editor;
+pub mod export;
+pub mod import;
+pub mod notebook_tree;
 pub mod postform;
 pub mod postitem;
 pub mod posts_list;
+pub mod shared_post;
 
+pub use collab_editor::*;
+pub use export::*;
+pub use import::*;
+pub use notebook_tree::*;
 pub use postform::*;
 pub use postitem::*;
 pub use posts_list::*;
+pub use shared_post::*;
@@ -0,0 +1,243 @@
+use std::collections::HashSet;
+
+use common::{translate, ApiResponse, NotebookForm, ResponseNotebook};
+use reqwasm::http::Method;
+use uuid::Uuid;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yew_hooks::use_async;
+
+use crate::api::request;
+use crate::components::list_erors::ListErrors;
+use crate::i18n::{use_locale, Text};
+use crate::workspace::{use_workspace, workspace_uri};
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub notebooks: Vec<ResponseNotebook>,
+    pub selected: Option<Uuid>,
+    pub on_select: Callback<Option<Uuid>>,
+    pub on_change: Callback<()>,
+}
+
+/// The `NotebookTree` component renders the notebooks of the selected workspace as a collapsible
+/// tree with the number of the notes inside every notebook. A new notebook is added inside the
+/// selected one, and the selected notebook can be deleted; its notes stay in the workspace.
+///
+/// Arguments:
+///
+/// * `notebooks`: The notebooks of the workspace, the `parent_id`s make the tree.
+/// * `selected`: The notebook whose notes are shown, `None` for all notes.
+/// * `on_select`: Called with the notebook the user clicked.
+/// * `on_change`: Called after a notebook was added or deleted, to reload the notebooks.
+///
+/// Returns:
+///
+/// The `NotebookTree` component returns a HTML element.
+#[function_component(NotebookTree)]
+pub fn notebook_tree(props: &Props) -> Html {
+    let locale = use_locale();
+    let workspace = use_workspace();
+    let expanded = use_state(HashSet::<Uuid>::new);
+    let name = use_state(String::new);
+
+    let create_request = {
+        let name = name.clone();
+        let parent_id = props.selected;
+        let workspace = *workspace;
+        use_async(async move {
+            let form = NotebookForm {
+                name: (*name).clone(),
+                parent_id,
+            };
+            request::<NotebookForm, ResponseNotebook>(
+                Method::POST,
+                workspace_uri(workspace, "/notebooks").await?,
+                Some(form),
+            )
+            .await
+        })
+    };
+
+    let delete_request = {
+        let selected = props.selected;
+        let workspace = *workspace;
+        use_async(async move {
+            let path = format!("/notebooks/{}", selected.unwrap_or_default());
+            request::<(), ApiResponse>(Method::DELETE, workspace_uri(workspace, &path).await?, None)
+                .await
+        })
+    };
+
+    //show the new notebook inside the open parent
+    {
+        let on_change = props.on_change.clone();
+        let expanded = expanded.clone();
+        let name = name.clone();
+        use_effect_with_deps(
+            move |request| {
+                if let Some(created) = &request.data {
+                    if let Some(parent_id) = created.parent_id {
+                        let mut open = (*expanded).clone();
+                        open.insert(parent_id);
+                        expanded.set(open);
+                    }
+                    name.set(String::new());
+                    on_change.emit(());
+                }
+            },
+            create_request.clone(),
+        )
+    }
+
+    {
+        let on_change = props.on_change.clone();
+        let on_select = props.on_select.clone();
+        use_effect_with_deps(
+            move |request| {
+                if request.data.is_some() {
+                    on_select.emit(None);
+                    on_change.emit(());
+                }
+            },
+            delete_request.clone(),
+        )
+    }
+
+    let toggle = {
+        let expanded = expanded.clone();
+        Callback::from(move |id: Uuid| {
+            let mut open = (*expanded).clone();
+            if !open.remove(&id) {
+                open.insert(id);
+            }
+            expanded.set(open);
+        })
+    };
+
+    let oninput = {
+        let name = name.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            name.set(input.value());
+        })
+    };
+
+    let onsubmit = {
+        let create_request = create_request.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            create_request.run();
+        })
+    };
+
+    let ondelete = {
+        let delete_request = delete_request.clone();
+        Callback::from(move |_: MouseEvent| delete_request.run())
+    };
+
+    let show_all = {
+        let on_select = props.on_select.clone();
+        Callback::from(move |_: MouseEvent| on_select.emit(None))
+    };
+    let all_class = match props.selected {
+        None => "notebook-name active",
+        Some(_) => "notebook-name",
+    };
+
+    html! {
+        <aside class="notebooks">
+            <h3><Text id="notebooks.label" /></h3>
+            <button class={all_class} onclick={show_all}>
+                <Text id="notebooks.all" />
+            </button>
+            {tree_level(props, &expanded, &toggle, None)}
+            <form class="notebooks-form" {onsubmit}>
+                <input
+                    type="text"
+                    value={(*name).clone()}
+                    {oninput}
+                    placeholder={translate(*locale, "notebooks.new")}
+                />
+                <button type="submit"><Text id="notebooks.create" /></button>
+            </form>
+            {
+                if props.selected.is_some() {
+                    html!(
+                        <button class="notebooks-delete" onclick={ondelete}>
+                            <Text id="notebooks.delete" />
+                        </button>
+                    )
+                } else {
+                    html!()
+                }
+            }
+            <ListErrors error={create_request.error.clone()} />
+            <ListErrors error={delete_request.error.clone()} />
+        </aside>
+    }
+}
+
+//renders the notebooks inside `parent`, and the ones inside the open notebooks below them
+fn tree_level(
+    props: &Props,
+    expanded: &HashSet<Uuid>,
+    toggle: &Callback<Uuid>,
+    parent: Option<Uuid>,
+) -> Html {
+    let children: Vec<&ResponseNotebook> = props
+        .notebooks
+        .iter()
+        .filter(|notebook| notebook.parent_id == parent)
+        .collect();
+    if children.is_empty() {
+        return html!();
+    }
+
+    html! {
+        <ul class="notebook-list">
+            {for children.into_iter().map(|notebook| {
+                let id = notebook.id;
+                let has_children = props.notebooks.iter().any(|child| child.parent_id == Some(id));
+                let open = expanded.contains(&id);
+                let onclick = {
+                    let on_select = props.on_select.clone();
+                    Callback::from(move |_: MouseEvent| on_select.emit(Some(id)))
+                };
+                let ontoggle = {
+                    let toggle = toggle.clone();
+                    Callback::from(move |_: MouseEvent| toggle.emit(id))
+                };
+                let class = match props.selected == Some(id) {
+                    true => "notebook-name active",
+                    false => "notebook-name",
+                };
+                html!(
+                    <li key={id.to_string()}>
+                        {
+                            if has_children {
+                                html!(
+                                    <button class="notebook-toggle" onclick={ontoggle}>
+                                        {if open { "▾" } else { "▸" }}
+                                    </button>
+                                )
+                            } else {
+                                html!(<span class="notebook-toggle" />)
+                            }
+                        }
+                        <button {class} {onclick}>
+                            {&notebook.name}{" "}<span class="notebook-count">{notebook.total}</span>
+                        </button>
+                        {
+                            if open {
+                                tree_level(props, expanded, toggle, Some(id))
+                            } else {
+                                html!()
+                            }
+                        }
+                    </li>
+                )
+            })}
+        </ul>
+    }
+}
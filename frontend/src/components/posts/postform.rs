@@ -1,9 +1,14 @@
-use crate::api::request;
-use crate::components::list_erors::ListErrors;
-use common::{PostsFormData, ResponsePost};
+use crate::api::{request, upload};
+use crate::components::list_erors::{FieldErrors, ListErrors};
+use crate::i18n::{stored_locale, Text};
+use crate::workspace::{use_workspace, workspace_uri};
+use common::{
+    translate, ErrorResponse, ErrorTypes, PostsFormData, ResponseAttachment, ResponsePost,
+};
 use reqwasm::http::Method;
 
-use web_sys::HtmlInputElement;
+use wasm_bindgen::JsCast;
+use web_sys::{ClipboardEvent, File, FormData, HtmlInputElement, HtmlTextAreaElement};
 use yew_hooks::prelude::*;
 
 use yew::prelude::*;
@@ -31,13 +36,38 @@ pub struct Props {
 #[function_component(AddPostForm)]
 pub fn add_post_form(props: &Props) -> Html {
     let form_data = use_state(PostsFormData::default);
+    //the pasted images with the names of their placeholders, uploaded after the post is added
+    let pasted = use_mut_ref(Vec::<(String, File)>::new);
+    let workspace = use_workspace();
 
     let api_request = {
         let form_data = form_data.clone();
+        let pasted = pasted.clone();
+        let workspace = *workspace;
         use_async(async move {
             let data = (*form_data).clone();
-            request::<PostsFormData, ResponsePost>(Method::POST, "/posts".to_owned(), Some(data))
-                .await
+            let description = data.description.clone().unwrap_or_default();
+            let post = request::<PostsFormData, ResponsePost>(
+                Method::POST,
+                workspace_uri(workspace, "/posts").await?,
+                Some(data),
+            )
+            .await?;
+
+            //the images whose placeholder was deleted from the description are not uploaded
+            let images: Vec<(String, File)> = pasted
+                .borrow_mut()
+                .drain(..)
+                .filter(|(name, _)| description.contains(&upload_placeholder(name)))
+                .collect();
+            if !images.is_empty() {
+                upload::<Vec<ResponseAttachment>>(
+                    workspace_uri(workspace, &format!("/posts/{}/attachments", post.id)).await?,
+                    images_form(&images)?,
+                )
+                .await?;
+            }
+            Ok(post)
         })
     };
 
@@ -61,6 +91,58 @@ pub fn add_post_form(props: &Props) -> Html {
         })
     };
 
+    let onpaste = {
+        let form_data = form_data.clone();
+        let pasted = pasted.clone();
+        Callback::from(move |e: Event| {
+            let files = e
+                .dyn_ref::<ClipboardEvent>()
+                .and_then(|e| e.clipboard_data())
+                .and_then(|data| data.files());
+            let images: Vec<File> = files
+                .map(|files| {
+                    (0..files.length())
+                        .filter_map(|index| files.get(index))
+                        .filter(|file| file.type_().starts_with("image/"))
+                        .collect()
+                })
+                .unwrap_or_default();
+            //text is pasted by the browser
+            if images.is_empty() {
+                return;
+            }
+            e.prevent_default();
+
+            let mut pasted = pasted.borrow_mut();
+            let mut markdown = String::new();
+            for image in images {
+                let name = format!(
+                    "pasted-{}.{}",
+                    pasted.len() + 1,
+                    image.type_().trim_start_matches("image/")
+                );
+                markdown.push_str(&format!("![{}]{}\n", name, upload_placeholder(&name)));
+                pasted.push((name, image));
+            }
+
+            let textarea: HtmlTextAreaElement = e.target_unchecked_into();
+            let value = textarea.value();
+            let start = utf16_offset(&value, textarea.selection_start().ok().flatten());
+            let end = utf16_offset(&value, textarea.selection_end().ok().flatten());
+            let value = format!(
+                "{}{}{}",
+                &value[..start],
+                markdown,
+                &value[end.max(start)..]
+            );
+            textarea.set_value(&value);
+
+            let mut info = (*form_data).clone();
+            info.description = Some(value);
+            form_data.set(info);
+        })
+    };
+
     let onsubmit = {
         let api_request = api_request.clone();
         Callback::from(move |e: SubmitEvent| {
@@ -83,14 +165,14 @@ pub fn add_post_form(props: &Props) -> Html {
         <div class="form_box">
 
             <header class="form_box_title">
-                <h2>{"Add new post!"}</h2>
+                <h2><Text id="post.add.title" /></h2>
             </header>
             <ListErrors error={api_request.error.clone()} />
             <form {onsubmit} class="form">
                 <div class="form_field">
                     <label for="title">
                         <svg stroke="currentColor" fill="currentColor" stroke-width="0" viewBox="0 0 16 16" height="1em" width="1em" xmlns="http://www.w3.org/2000/svg"><path fill-rule="evenodd" d="M14 9a1 1 0 100-2 1 1 0 000 2zm0 1a2 2 0 100-4 2 2 0 000 4zM2 9a1 1 0 100-2 1 1 0 000 2zm0 1a2 2 0 100-4 2 2 0 000 4z" clip-rule="evenodd"></path><path fill-rule="evenodd" d="M1.5 2.5A1.5 1.5 0 013 1h10a1.5 1.5 0 011.5 1.5v4h-1v-4A.5.5 0 0013 2H3a.5.5 0 00-.5.5v4h-1v-4zm1 7v4a.5.5 0 00.5.5h10a.5.5 0 00.5-.5v-4h1v4A1.5 1.5 0 0113 15H3a1.5 1.5 0 01-1.5-1.5v-4h1z" clip-rule="evenodd"></path><path d="M11.434 4H4.566L4.5 5.994h.386c.21-1.252.612-1.446 2.173-1.495l.343-.011v6.343c0 .537-.116.665-1.049.748V12h3.294v-.421c-.938-.083-1.054-.21-1.054-.748V4.488l.348.01c1.56.05 1.963.244 2.173 1.496h.386L11.434 4z"></path></svg>
-                        {"Title:"}
+                        <Text id="form.title" />
                     </label>
                     <input
                         type="text"
@@ -100,23 +182,27 @@ pub fn add_post_form(props: &Props) -> Html {
                         placeholder="Have to create Actix Web API server"
                         required=true
                     />
+                    <FieldErrors error={api_request.error.clone()} field="title" />
                 </div>
                 <div class="form_field">
                     <label for="description">
                         <svg stroke="currentColor" fill="currentColor" stroke-width="0" viewBox="0 0 24 24" height="1em" width="1em" xmlns="http://www.w3.org/2000/svg"><path d="M14 2H6c-1.1 0-1.99.9-1.99 2L4 20c0 1.1.89 2 1.99 2H18c1.1 0 2-.9 2-2V8l-6-6zm2 16H8v-2h8v2zm0-4H8v-2h8v2zm-3-5V3.5L18.5 9H13z"></path></svg>
-                        {"Description:"}
+                        <Text id="form.description" />
                     </label>
                     <textarea
                         type="text"
                         oninput={oninput_description}
+                        {onpaste}
                         class=""
                         rows="6"
                         name="description"
                         placeholder="If you want to use Rust..."
                     />
+                    <p class="form_hint"><Text id="form.description.paste" /></p>
+                    <FieldErrors error={api_request.error.clone()} field="description" />
                 </div>
                 <button type="submit" class="form_btn">
-                    {"Add Post"}
+                    <Text id="nav.add_post" />
                 </button>
 
             </form>
@@ -124,3 +210,44 @@ pub fn add_post_form(props: &Props) -> Html {
     </div>
     }
 }
+
+//the link of a pasted image until it is uploaded, the backend replaces it with the address of the
+//attachment
+fn upload_placeholder(name: &str) -> String {
+    format!("(upload:{})", name)
+}
+
+//the form with the pasted images, under the names of their placeholders
+fn images_form(images: &[(String, File)]) -> Result<FormData, ErrorResponse> {
+    let form = FormData::new().map_err(|e| {
+        ErrorResponse::new(
+            Some(format!("{:?}", e)),
+            Some(translate(stored_locale(), "request.failed")),
+            ErrorTypes::RequestError,
+        )
+    })?;
+    for (name, image) in images {
+        if form
+            .append_with_blob_and_filename("file", image, name)
+            .is_err()
+        {
+            log::error!("Failed to add the image {}!", name);
+        }
+    }
+    Ok(form)
+}
+
+//the byte offset of a cursor position of a text field, which counts UTF-16 code units
+fn utf16_offset(value: &str, position: Option<u32>) -> usize {
+    let Some(position) = position else {
+        return value.len();
+    };
+    let mut units = 0;
+    for (offset, c) in value.char_indices() {
+        if units >= position as usize {
+            return offset;
+        }
+        units += c.len_utf16();
+    }
+    value.len()
+}
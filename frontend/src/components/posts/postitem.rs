@@ -1,11 +1,25 @@
 use common::PostsUpdateForm;
-use common::ResponsePost;
+use common::{translate, Permission, ResponseNotebook, ResponsePost};
+use uuid::Uuid;
+use web_sys::HtmlSelectElement;
 use yew::prelude::*;
+use yew_router::prelude::*;
+
+use crate::api::api_url;
+use crate::i18n::{use_locale, Text};
+use crate::routes::Route;
+use crate::workspace::use_workspace;
 
 #[derive(Properties, PartialEq)]
 pub struct Props {
     pub post: ResponsePost,
     pub update_callback: Callback<PostsUpdateForm>,
+    #[prop_or_default]
+    pub notebooks: Vec<ResponseNotebook>,
+    #[prop_or_default]
+    pub move_callback: Callback<(Uuid, Option<Uuid>)>,
+    #[prop_or_default]
+    pub delete_callback: Callback<Uuid>,
 }
 
 /// The `PostItem` component renders a post item with a title, description, and
@@ -17,11 +31,20 @@ pub struct Props {
 ///
 /// * `update_callback`: Callback that will be called when user toggle the importance of the post.
 /// Send necessary post data, like: `PostsUpdateData
+///
+/// * `notebooks`: The notebooks the post can be moved to, the select is hidden without them.
+///
+/// * `move_callback`: Callback that will be called with the post and its new notebook.
+///
+/// * `delete_callback`: Callback that will be called with the post its author deletes.
 /// Returns:
 ///
 /// The `PostItem` component returns a HTML element representing a post item.
 #[function_component(PostItem)]
 pub fn post_item(props: &Props) -> Html {
+    let locale = use_locale();
+    //the post is edited through the workspace it is listed in
+    let workspace_id = (*use_workspace()).unwrap_or(props.post.workspace_id);
     let update_callback = props.update_callback.clone();
     let post_id = props.post.id;
     let mut post_important = props.post.important;
@@ -40,6 +63,19 @@ pub fn post_item(props: &Props) -> Html {
         })
     };
 
+    let onmove = {
+        let move_callback = props.move_callback.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            move_callback.emit((post_id, Uuid::parse_str(&select.value()).ok()));
+        })
+    };
+
+    let ondelete = {
+        let delete_callback = props.delete_callback.clone();
+        Callback::from(move |_: MouseEvent| delete_callback.emit(post_id))
+    };
+
     html! {
       <div class="post">
           <div class="post-header">
@@ -52,15 +88,110 @@ pub fn post_item(props: &Props) -> Html {
                 }
               }
           </div >
+          {
+              //the posts of other users show who shared them and what the user may do
+              if props.post.permission != Permission::Owner{
+                  html!(
+                      <p class="post-owner">
+                          <Text id="posts.shared_by" />{" "}{&props.post.owner}{" · "}
+                          <Text id={format!("posts.permission.{}", props.post.permission)} />
+                      </p>
+                  )
+              }else{
+                  html!()
+              }
+          }
           {
               if let Some(desc)=&props.post.description{
-                  html!(<p class="post-desc">{desc.clone()}</p>)
+                  html!(
+                      <p class="post-desc">
+                          {for description_parts(desc).into_iter().map(|part| match part {
+                              Part::Text(text) => html!({text}),
+                              Part::Image { alt, id } => html!(
+                                  <a href={api_url(&format!("/attachments/{}", id))} target="_blank" rel="noopener">
+                                      <img
+                                          class="post-image"
+                                          src={api_url(&format!("/attachments/{}?size=medium", id))}
+                                          alt={alt.to_string()}
+                                          loading="lazy"
+                                      />
+                                  </a>
+                              ),
+                          })}
+                      </p>
+                  )
               }else{
-                  html!(<p class="post-desc">{"No description!"}</p>)
+                  html!(<p class="post-desc"><Text id="posts.no_description" /></p>)
               }
           }
 
+          {
+              //only the editors of the post can move it to another notebook
+              if !props.notebooks.is_empty() && props.post.permission >= Permission::Editor{
+                  html!(
+                      <select class="post-notebook" onchange={onmove} aria-label={translate(*locale, "notebooks.move")}>
+                          <option value="" selected={props.post.notebook_id.is_none()}>
+                              <Text id="notebooks.none" />
+                          </option>
+                          {for props.notebooks.iter().map(|notebook| html!(
+                              <option value={notebook.id.to_string()} selected={props.post.notebook_id == Some(notebook.id)}>
+                                  {&notebook.name}
+                              </option>
+                          ))}
+                      </select>
+                  )
+              }else{
+                  html!()
+              }
+          }
           <p class="post-date">{&props.post.created_at.date()}</p>
+          <Link<Route> classes="post-collab" to={Route::EditPost { workspace_id, id: post_id }}>
+              <Text id="collab.open" />
+          </Link<Route>>
+          {
+              if props.post.permission == Permission::Owner{
+                  html!(
+                      <button class="post-delete" onclick={ondelete}>
+                          <Text id="posts.delete" />
+                      </button>
+                  )
+              }else{
+                  html!()
+              }
+          }
       </div>
     }
 }
+
+/// The `Part` enum is a piece of the description of a post: text, or an image attachment embedded as
+/// `![alt](/attachments/<id>)`.
+enum Part<'a> {
+    Text(&'a str),
+    Image { alt: &'a str, id: uuid::Uuid },
+}
+
+//splits the description at the embedded images, the other Markdown stays text
+fn description_parts(description: &str) -> Vec<Part<'_>> {
+    let mut parts = Vec::new();
+    let mut rest = description;
+    while let Some(start) = rest.find("![") {
+        let image = rest[start + 2..].split_once("](").and_then(|(alt, link)| {
+            let (link, _) = link.split_once(')')?;
+            let id = link.strip_prefix("/attachments/")?.parse().ok()?;
+            Some((alt, id, start + alt.len() + link.len() + 5))
+        });
+        match image {
+            Some((alt, id, end)) if !alt.contains(['[', ']', '\n']) => {
+                parts.push(Part::Text(&rest[..start]));
+                parts.push(Part::Image { alt, id });
+                rest = &rest[end..];
+            }
+            _ => {
+                parts.push(Part::Text(&rest[..start + 2]));
+                rest = &rest[start + 2..];
+            }
+        }
+    }
+    parts.push(Part::Text(rest));
+    parts
+}
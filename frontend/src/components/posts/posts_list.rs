@@ -1,12 +1,102 @@
-use common::{PostsUpdateForm, ResponsePost};
+use std::rc::Rc;
+
+use common::{
+    ApiResponse, NotebookMoveForm, Permission, PostEvent, PostEventKind, PostsUpdateForm,
+    ResponseNotebook, ResponsePost,
+};
+use uuid::Uuid;
 use yew::prelude::*;
-use yew_hooks::{use_async, use_effect_once};
+use yew_hooks::use_async;
 use yew_router::prelude::use_navigator;
 
-use super::PostItem;
-use crate::{api::request, components::list_erors::ListErrors, routes::Route};
+use super::{ExportLinks, ImportForm, NotebookTree, PostItem};
+use crate::components::workspace_switcher::WorkspaceSwitcher;
+use crate::events::use_post_events;
+use crate::workspace::{use_workspace, workspace_uri};
+use crate::{api::request, components::list_erors::ListErrors, i18n::Text, routes::Route};
 use reqwasm::http::Method;
 
+/// The `LivePosts` struct is the shown list of posts, loaded from the backend and changed by the
+/// events of the `/events` stream.
+///
+/// Properties:
+///
+/// * `posts`: The `posts` property is the list, `None` until it is loaded.
+/// * `workspace`: The `workspace` property is the workspace the list was loaded for.
+/// * `notebook`: The `notebook` property is the notebook the list was loaded for, if any.
+#[derive(Default, PartialEq)]
+struct LivePosts {
+    posts: Option<Vec<ResponsePost>>,
+    workspace: Option<Uuid>,
+    notebook: Option<Uuid>,
+}
+
+/// The `LiveAction` enum changes the `LivePosts`: a loaded list or an event.
+enum LiveAction {
+    Loaded {
+        posts: Vec<ResponsePost>,
+        workspace: Option<Uuid>,
+        notebook: Option<Uuid>,
+    },
+    Event(PostEvent),
+}
+
+impl Reducible for LivePosts {
+    type Action = LiveAction;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let event = match action {
+            LiveAction::Loaded {
+                posts,
+                workspace,
+                notebook,
+            } => {
+                return Rc::new(LivePosts {
+                    posts: Some(posts),
+                    workspace,
+                    notebook,
+                })
+            }
+            LiveAction::Event(event) => event,
+        };
+        let Some(shown) = &self.posts else {
+            return self;
+        };
+
+        let mut posts = shown.clone();
+        let index = posts.iter().position(|post| post.id == event.post_id);
+        match (event.kind, index, event.post) {
+            //a new post is in no notebook yet, it is shown after the important ones
+            (PostEventKind::Created, None, Some(post))
+                if Some(post.workspace_id) == self.workspace && self.notebook.is_none() =>
+            {
+                let first = posts.iter().position(|post| !post.important);
+                posts.insert(first.unwrap_or(posts.len()), post);
+            }
+            //the important flag and the permission of the user stay the ones of the list
+            (PostEventKind::Updated, Some(index), Some(post)) => {
+                let shown = &mut posts[index];
+                shown.title = post.title;
+                shown.description = post.description;
+                shown.notebook_id = post.notebook_id;
+            }
+            (PostEventKind::Deleted, Some(index), _) => {
+                posts.remove(index);
+            }
+            (PostEventKind::ImportantChanged, Some(index), _) => {
+                posts[index].important = event.important.unwrap_or_default();
+            }
+            _ => return self,
+        }
+
+        Rc::new(LivePosts {
+            posts: Some(posts),
+            workspace: self.workspace,
+            notebook: self.notebook,
+        })
+    }
+}
+
 /// The `PostsList` component displaysa list of posts and allows for updating individual posts.
 ///
 /// Returns:
@@ -16,25 +106,84 @@ use reqwasm::http::Method;
 #[function_component(PostsList)]
 pub fn posts_list() -> Html {
     let update_post_data = use_state(PostsUpdateForm::default);
+    //show only the posts other users shared with the user
+    let shared_only = use_state(|| false);
+    let workspace = use_workspace();
+    //the notebook whose notes are shown, all notes without it
+    let notebook = use_state(|| None::<Uuid>);
+    let move_post_data = use_state(|| (Uuid::nil(), NotebookMoveForm::default()));
+    let delete_post_id = use_state(Uuid::nil);
+    let live_posts = use_reducer(LivePosts::default);
+
+    //the changes of other users and other tabs are applied as they come
+    {
+        let live_posts = live_posts.dispatcher();
+        use_post_events(Callback::from(move |event| {
+            live_posts.dispatch(LiveAction::Event(event))
+        }));
+    }
 
     //update post important field request
     let update_api_request = {
         let data = update_post_data.clone();
+        let workspace = *workspace;
         use_async(async move {
             let data = (*data).clone();
             request::<PostsUpdateForm, ResponsePost>(
                 Method::POST,
-                "/posts/update".to_owned(),
+                workspace_uri(workspace, "/posts/update").await?,
                 Some(data),
             )
             .await
         })
     };
 
+    //move post to other notebook request
+    let move_api_request = {
+        let data = move_post_data.clone();
+        let workspace = *workspace;
+        use_async(async move {
+            let (id, form) = (*data).clone();
+            let path = format!("/posts/{}/notebook", id);
+            request::<NotebookMoveForm, ApiResponse>(
+                Method::PATCH,
+                workspace_uri(workspace, &path).await?,
+                Some(form),
+            )
+            .await
+        })
+    };
+
+    //delete post request, the list drops the post when its event comes
+    let delete_api_request = {
+        let post_id = delete_post_id.clone();
+        let workspace = *workspace;
+        use_async(async move {
+            let path = format!("/posts/{}", *post_id);
+            request::<(), ApiResponse>(Method::DELETE, workspace_uri(workspace, &path).await?, None)
+                .await
+        })
+    };
+
     //main api requst
     let api_request = {
+        let workspace = *workspace;
+        let notebook = *notebook;
         use_async(async move {
-            request::<(), Vec<ResponsePost>>(Method::GET, "/posts".to_owned(), None).await
+            let path = match notebook {
+                Some(notebook) => format!("/notebooks/{}/posts", notebook),
+                None => "/posts".to_owned(),
+            };
+            let uri = workspace_uri(workspace, &path).await?;
+            request::<(), Vec<ResponsePost>>(Method::GET, uri, None).await
+        })
+    };
+
+    let notebooks_request = {
+        let workspace = *workspace;
+        use_async(async move {
+            let uri = workspace_uri(workspace, "/notebooks").await?;
+            request::<(), Vec<ResponseNotebook>>(Method::GET, uri, None).await
         })
     };
 
@@ -49,13 +198,47 @@ pub fn posts_list() -> Html {
             }
         })
     };
-    //sand api request when page is loading
+    //sand api request when page is loading and when other workspace or notebook is selected
     {
         let api_request = api_request.clone();
-        use_effect_once(move || {
-            api_request.run();
-            || log::debug!("Get posts request started!")
-        })
+        use_effect_with_deps(
+            move |_| {
+                api_request.run();
+                || log::debug!("Get posts request started!")
+            },
+            (*workspace, *notebook),
+        )
+    }
+
+    //the notebooks belong to the workspace, the selected one is gone with other workspace
+    {
+        let notebooks_request = notebooks_request.clone();
+        let notebook = notebook.clone();
+        use_effect_with_deps(
+            move |_| {
+                notebook.set(None);
+                notebooks_request.run();
+            },
+            *workspace,
+        )
+    }
+
+    {
+        let live_posts = live_posts.dispatcher();
+        let workspace = *workspace;
+        let notebook = *notebook;
+        use_effect_with_deps(
+            move |request| {
+                if let Some(posts) = &request.data {
+                    live_posts.dispatch(LiveAction::Loaded {
+                        posts: posts.clone(),
+                        workspace,
+                        notebook,
+                    });
+                }
+            },
+            api_request.clone(),
+        )
     }
 
     {
@@ -85,6 +268,21 @@ pub fn posts_list() -> Html {
         )
     }
 
+    {
+        let api_request = api_request.clone();
+        let notebooks_request = notebooks_request.clone();
+        use_effect_with_deps(
+            //the moved post changes the list and the counts of the notebooks
+            move |request| {
+                if request.data.is_some() {
+                    api_request.run();
+                    notebooks_request.run();
+                }
+            },
+            move_api_request.clone(),
+        )
+    }
+
     //main update callback that moves to children
     let update_post = {
         let update_api_request = update_api_request.clone();
@@ -96,22 +294,102 @@ pub fn posts_list() -> Html {
         })
     };
 
+    let move_post = {
+        let move_api_request = move_api_request.clone();
+        Callback::from(move |(id, notebook_id): (Uuid, Option<Uuid>)| {
+            move_post_data.set((id, NotebookMoveForm { notebook_id }));
+            move_api_request.run();
+        })
+    };
+
+    let delete_post = {
+        let delete_api_request = delete_api_request.clone();
+        Callback::from(move |id: Uuid| {
+            delete_post_id.set(id);
+            delete_api_request.run();
+        })
+    };
+
+    let select_notebook = {
+        let notebook = notebook.clone();
+        Callback::from(move |selected: Option<Uuid>| notebook.set(selected))
+    };
+
+    let reload_notebooks = {
+        let notebooks_request = notebooks_request.clone();
+        Callback::from(move |_| notebooks_request.run())
+    };
+    let notebooks = notebooks_request.data.clone().unwrap_or_default();
+
+    //reload the list after an import
+    let reload = {
+        let api_request = api_request.clone();
+        Callback::from(move |_| api_request.run())
+    };
+
+    let show_filter = |shared: bool| {
+        let shared_only = shared_only.clone();
+        Callback::from(move |_: MouseEvent| shared_only.set(shared))
+    };
+    let filter_class = |shared: bool| match *shared_only == shared {
+        true => "filter-btn active",
+        false => "filter-btn",
+    };
+
     html! {
         <div>
+            <WorkspaceSwitcher />
+            <div class="notes-layout">
+            <NotebookTree
+                notebooks={notebooks.clone()}
+                selected={*notebook}
+                on_select={select_notebook}
+                on_change={reload_notebooks}
+            />
+            <div class="notes-main">
+            <ExportLinks />
+            <ImportForm on_import={reload} />
+            <div class="filter">
+                <button class={filter_class(false)} onclick={show_filter(false)}>
+                    <Text id="posts.filter.all" />
+                </button>
+                <button class={filter_class(true)} onclick={show_filter(true)}>
+                    <Text id="posts.filter.shared" />
+                </button>
+            </div>
             <ListErrors error={api_request.error.clone()} />
+            <ListErrors error={move_api_request.error.clone()} />
+            <ListErrors error={delete_api_request.error.clone()} />
            {
-            if let Some(posts)=&api_request.data{
+            if let Some(posts)=&live_posts.posts{
                 html!(
-                {for posts.iter().map(|post|html!(<PostItem post={post.clone()} update_callback={update_post.clone()}/>))}
+                {for posts
+                    .iter()
+                    .filter(|post| !*shared_only || post.permission != Permission::Owner)
+                    .map(|post|html!(
+                        <PostItem
+                            post={post.clone()}
+                            update_callback={update_post.clone()}
+                            //the posts shared from other workspaces can't be filed here
+                            notebooks={match Some(post.workspace_id) == *workspace {
+                                true => notebooks.clone(),
+                                false => Vec::new(),
+                            }}
+                            move_callback={move_post.clone()}
+                            delete_callback={delete_post.clone()}
+                        />
+                    ))}
                 )
             }else{
                 html!(
                     <div>
-                    <h1>{"Haven't gotten any posts yet!"}</h1>
+                    <h1><Text id="posts.empty" /></h1>
                     </div>
                 )
             }
            }
+            </div>
+            </div>
         </div>
     }
 }
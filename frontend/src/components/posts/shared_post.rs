@@ -0,0 +1,106 @@
+use common::SharedPost;
+use reqwasm::http::Method;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yew_hooks::{use_async, use_effect_once};
+
+use crate::api::request_with_headers;
+use crate::components::list_erors::ListErrors;
+use crate::i18n::Text;
+
+//the header the backend reads the password of a share link from
+const SHARE_PASSWORD_HEADER: &str = "X-Share-Password";
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub token: String,
+}
+
+/// The `SharedPostView` component shows a post that was shared with a link, read-only and without an
+/// account. It asks for the password if the link has one.
+///
+/// Arguments:
+///
+/// * `token`: The token of the share link, from the address `/s/{token}`.
+///
+/// Returns:
+///
+/// The `SharedPostView` component returns a HTML element.
+#[function_component(SharedPostView)]
+pub fn shared_post_view(props: &Props) -> Html {
+    let password = use_node_ref();
+
+    let api_request = {
+        let token = props.token.clone();
+        let password = password.clone();
+        use_async(async move {
+            let password = password
+                .cast::<HtmlInputElement>()
+                .map(|input| input.value())
+                .unwrap_or_default();
+            let headers = match password.is_empty() {
+                true => Vec::new(),
+                false => vec![(SHARE_PASSWORD_HEADER, password.as_str())],
+            };
+            request_with_headers::<(), SharedPost>(
+                Method::GET,
+                format!("/s/{}", token),
+                None,
+                &headers,
+            )
+            .await
+        })
+    };
+
+    //the link is opened without a password first
+    {
+        let api_request = api_request.clone();
+        use_effect_once(move || {
+            api_request.run();
+            || log::debug!("Get shared post request started!")
+        })
+    }
+
+    let onsubmit = {
+        let api_request = api_request.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            api_request.run();
+        })
+    };
+
+    let needs_password = api_request.error.as_ref().is_some_and(|error| {
+        matches!(
+            error.code.as_deref(),
+            Some("share.password_required" | "share.wrong_password")
+        )
+    });
+
+    html! {
+        <>
+            if let Some(post) = &api_request.data {
+                <div class="post">
+                    <div class="post-header">
+                        <h2>{post.title.clone()}</h2>
+                    </div>
+                    if let Some(description) = &post.description {
+                        <p class="post-desc">{description.clone()}</p>
+                    } else {
+                        <p class="post-desc"><Text id="posts.no_description" /></p>
+                    }
+                    <p class="post-date">{post.created_at.date()}</p>
+                </div>
+            }
+            if needs_password {
+                <form class="share-password" {onsubmit}>
+                    <label for="share-password"><Text id="form.password" /></label>
+                    <input id="share-password" type="password" ref={password} />
+                    <button type="submit" disabled={api_request.loading}>
+                        <Text id="share.open" />
+                    </button>
+                </form>
+            }
+            <ListErrors error={api_request.error.clone()} />
+        </>
+    }
+}
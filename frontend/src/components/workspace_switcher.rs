@@ -0,0 +1,143 @@
+use common::{translate, ResponseWorkspace, WorkspaceForm};
+use reqwasm::http::Method;
+use uuid::Uuid;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+use yew_hooks::{use_async, use_effect_once};
+
+use crate::api::request;
+use crate::components::list_erors::ListErrors;
+use crate::i18n::{use_locale, Text};
+use crate::workspace::{store_workspace, use_workspace};
+
+/// The `WorkspaceSwitcher` component renders a select with the workspaces of the user and a form
+/// that creates a new workspace. The notes of the selected workspace are shown, the choice is saved.
+///
+/// Returns:
+///
+/// The `WorkspaceSwitcher` component returns a HTML element.
+#[function_component(WorkspaceSwitcher)]
+pub fn workspace_switcher() -> Html {
+    let locale = use_locale();
+    let workspace = use_workspace();
+    let name = use_state(String::new);
+
+    let api_request = use_async(async move {
+        request::<(), Vec<ResponseWorkspace>>(Method::GET, "/workspaces".to_owned(), None).await
+    });
+
+    let create_request = {
+        let name = name.clone();
+        use_async(async move {
+            let form = WorkspaceForm {
+                name: (*name).clone(),
+            };
+            request::<WorkspaceForm, ResponseWorkspace>(
+                Method::POST,
+                "/workspaces".to_owned(),
+                Some(form),
+            )
+            .await
+        })
+    };
+
+    {
+        let api_request = api_request.clone();
+        use_effect_once(move || {
+            api_request.run();
+            || log::debug!("Get workspaces request started!")
+        })
+    }
+
+    //the saved workspace can be gone, then the personal workspace is selected
+    {
+        let workspace = workspace.clone();
+        use_effect_with_deps(
+            move |request| {
+                if let Some(workspaces) = &request.data {
+                    let known = workspaces
+                        .iter()
+                        .any(|option| Some(option.id) == *workspace);
+                    if !known {
+                        if let Some(first) = workspaces.first() {
+                            workspace.set(Some(first.id));
+                        }
+                    }
+                }
+            },
+            api_request.clone(),
+        )
+    }
+
+    //select the new workspace and reload the list
+    {
+        let workspace = workspace.clone();
+        let name = name.clone();
+        let api_request = api_request.clone();
+        use_effect_with_deps(
+            move |request| {
+                if let Some(created) = &request.data {
+                    store_workspace(created.id);
+                    workspace.set(Some(created.id));
+                    name.set(String::new());
+                    api_request.run();
+                }
+            },
+            create_request.clone(),
+        )
+    }
+
+    let onchange = {
+        let workspace = workspace.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Ok(selected) = Uuid::parse_str(&select.value()) {
+                store_workspace(selected);
+                workspace.set(Some(selected));
+            }
+        })
+    };
+
+    let oninput = {
+        let name = name.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            name.set(input.value());
+        })
+    };
+
+    let onsubmit = {
+        let create_request = create_request.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            create_request.run();
+        })
+    };
+
+    let option_name = |option: &ResponseWorkspace| match option.personal {
+        true => translate(*locale, "workspaces.personal"),
+        false => option.name.clone(),
+    };
+
+    html! {
+        <div class="workspace_switcher">
+            <select {onchange} aria-label={translate(*locale, "workspaces.label")}>
+                {for api_request.data.iter().flatten().map(|option| html!(
+                    <option value={option.id.to_string()} selected={Some(option.id) == *workspace}>
+                        {option_name(option)}
+                    </option>
+                ))}
+            </select>
+            <form {onsubmit}>
+                <input
+                    type="text"
+                    value={(*name).clone()}
+                    {oninput}
+                    placeholder={translate(*locale, "workspaces.new")}
+                />
+                <button type="submit"><Text id="workspaces.create" /></button>
+            </form>
+            <ListErrors error={create_request.error.clone()} />
+        </div>
+    }
+}
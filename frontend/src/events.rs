@@ -0,0 +1,64 @@
+use common::{PostEvent, PostEventKind};
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::{EventSource, EventSourceInit, MessageEvent};
+use yew::prelude::*;
+use yew_hooks::use_effect_once;
+
+use crate::api::api_url;
+
+/// The hook `use_post_events` subscribes to the `/events` stream of the backend while the component
+/// is shown. The browser opens the stream again by itself when the connection is lost.
+///
+/// Arguments:
+///
+/// * `on_event`: Called with every event of the posts the user can see.
+#[hook]
+pub fn use_post_events(on_event: Callback<PostEvent>) {
+    use_effect_once(move || {
+        let subscription = subscribe(on_event);
+        move || {
+            if let Some((source, _listeners)) = subscription {
+                source.close();
+            }
+        }
+    })
+}
+
+//the listeners are kept with the stream, they are freed when it is closed
+type Subscription = (EventSource, Vec<Closure<dyn FnMut(MessageEvent)>>);
+
+//opens the stream with the cookies of the user and listens to every kind of event
+fn subscribe(on_event: Callback<PostEvent>) -> Option<Subscription> {
+    let init = EventSourceInit::new();
+    init.set_with_credentials(true);
+    let source = match EventSource::new_with_event_source_init_dict(&api_url("/events"), &init) {
+        Ok(source) => source,
+        Err(e) => {
+            log::error!("Failed to open the events stream: {:?}", e);
+            return None;
+        }
+    };
+
+    let listeners = PostEventKind::ALL
+        .iter()
+        .map(|kind| {
+            let on_event = on_event.clone();
+            let listener = Closure::<dyn FnMut(MessageEvent)>::new(move |message: MessageEvent| {
+                let data = message.data().as_string().unwrap_or_default();
+                match serde_json::from_str::<PostEvent>(&data) {
+                    Ok(event) => on_event.emit(event),
+                    Err(e) => log::error!("Failed to read event: {}", e),
+                }
+            });
+            if source
+                .add_event_listener_with_callback(kind.as_str(), listener.as_ref().unchecked_ref())
+                .is_err()
+            {
+                log::error!("Failed to listen to {} events", kind);
+            }
+            listener
+        })
+        .collect();
+
+    Some((source, listeners))
+}
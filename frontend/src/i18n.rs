@@ -0,0 +1,59 @@
+use common::{translate, Locale};
+use yew::prelude::*;
+
+//local storage key of the selected language
+const STORAGE_KEY: &str = "locale";
+
+/// The context with the selected language. It is provided by `App`, setting it re-renders the texts.
+pub type LocaleContext = UseStateHandle<Locale>;
+
+/// The function `stored_locale` returns the language selected by the user. Before the first choice it
+/// is the language of the browser, or English if that one is not supported.
+pub fn stored_locale() -> Locale {
+    let window = web_sys::window();
+    let selected = window
+        .as_ref()
+        .and_then(|window| window.local_storage().ok().flatten())
+        .and_then(|storage| storage.get_item(STORAGE_KEY).ok().flatten());
+
+    selected
+        .or_else(|| window.and_then(|window| window.navigator().language()))
+        .and_then(|tag| Locale::from_tag(&tag))
+        .unwrap_or_default()
+}
+
+/// The function `store_locale` saves the language selected by the user, so it is kept after a reload.
+pub fn store_locale(locale: Locale) {
+    let storage = web_sys::window().and_then(|window| window.local_storage().ok().flatten());
+    if let Some(storage) = storage {
+        if storage.set_item(STORAGE_KEY, locale.code()).is_err() {
+            log::error!("Failed to save the language!");
+        }
+    }
+}
+
+/// The hook `use_locale` returns the context with the selected language.
+#[hook]
+pub fn use_locale() -> LocaleContext {
+    use_context::<LocaleContext>().expect("Locale context is not provided")
+}
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub id: AttrValue,
+}
+
+/// The `Text` component renders a message of the catalog in the selected language.
+///
+/// Arguments:
+///
+/// * `id`: The catalog key of the message, for example `nav.home`.
+///
+/// Returns:
+///
+/// The `Text` component returns the translated text.
+#[function_component(Text)]
+pub fn text(props: &Props) -> Html {
+    let locale = use_locale();
+    html!({ translate(*locale, &props.id) })
+}
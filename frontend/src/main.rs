@@ -1,7 +1,11 @@
 mod api;
 mod app;
+mod collab;
 mod components;
+mod events;
+mod i18n;
 mod routes;
+mod workspace;
 
 use app::App;
 
@@ -1,12 +1,16 @@
 use yew::prelude::*;
 use yew_router::prelude::*;
 
+use crate::components::admin::{AdminLink, AdminUsers};
 use crate::components::alert::AlertComponent;
 use crate::components::auth::form::AuthorizationForm;
 use crate::components::auth::logout::LogOut;
 use crate::components::auth::types::FormType;
 use crate::components::posts::AddPostForm;
+use crate::components::posts::CollabEditor;
 use crate::components::posts::PostsList;
+use crate::components::posts::SharedPostView;
+use crate::i18n::Text;
 
 #[derive(Clone, Routable, PartialEq)]
 pub enum Route {
@@ -20,6 +24,15 @@ pub enum Route {
     Posts,
     #[at("/posts/add")]
     AddPost,
+    #[at("/workspaces/:workspace_id/posts/:id/edit")]
+    EditPost {
+        workspace_id: uuid::Uuid,
+        id: uuid::Uuid,
+    },
+    #[at("/admin")]
+    Admin,
+    #[at("/s/:token")]
+    Shared { token: String },
     #[not_found]
     #[at("/404")]
     NotFound,
@@ -45,10 +58,10 @@ pub fn switch(routes: Route) -> Html {
             html! {
                 <div class="container">
                     <div class="flex-container">
-                        <h1>{ "Home" }</h1>
+                        <h1><Text id="nav.home" /></h1>
                         <button class="link">
                             <Link<Route> to={Route::SignUp}>
-                                    { "Sign Up" }
+                                    <Text id="nav.sign_up" />
                             </Link<Route>>
                         </button>
                     </div>
@@ -59,7 +72,7 @@ pub fn switch(routes: Route) -> Html {
             html! {
                 <div class="container">
                     <AuthorizationForm formtype={FormType::SignUp}>
-                        <AlertComponent message="You’ve been signed up successfully!" route={Route::LogIn} />
+                        <AlertComponent message="alert.signed_up" route={Route::LogIn} />
                     </AuthorizationForm>
                 </div>
             }
@@ -68,7 +81,7 @@ pub fn switch(routes: Route) -> Html {
             html! {
                 <div class="container">
                     <AuthorizationForm formtype={FormType::LogIn}>
-                        <AlertComponent message="You’ve been signed in successfully!" route={Route::Posts}/>
+                        <AlertComponent message="alert.logged_in" route={Route::Posts}/>
                     </AuthorizationForm>
                 </div>
             }
@@ -76,11 +89,11 @@ pub fn switch(routes: Route) -> Html {
         Route::Posts => {
             html! {
                 <div class="container">
-                    <h1>{ "Posts" }</h1>
+                    <h1><Text id="nav.posts" /></h1>
                     <div class="flex-container">
                         <button class="link">
                         <Link<Route> to={Route::Home}>
-                                { "Home" }
+                                <Text id="nav.home" />
                         </Link<Route>>
                         </button>
 
@@ -88,9 +101,11 @@ pub fn switch(routes: Route) -> Html {
 
                         <button class="link">
                         <Link<Route> to={Route::AddPost}>
-                                { "Add Post" }
+                                <Text id="nav.add_post" />
                         </Link<Route>>
                         </button>
+
+                        <AdminLink/>
                     </div>
                     <PostsList/>
                 </div>
@@ -100,14 +115,51 @@ pub fn switch(routes: Route) -> Html {
             html! {
                 <div class="container">
                     <AddPostForm>
-                        <AlertComponent message="Pos added successfully!" route={Route::Posts}/>
+                        <AlertComponent message="alert.post_added" route={Route::Posts}/>
                     </AddPostForm>
                 </div>
             }
         }
+        Route::EditPost { workspace_id, id } => {
+            html! {
+                <div class="container">
+                    <h1><Text id="collab.open" /></h1>
+                    <div class="flex-container">
+                        <button class="link">
+                        <Link<Route> to={Route::Posts}>
+                                <Text id="nav.posts" />
+                        </Link<Route>>
+                        </button>
+                    </div>
+                    <CollabEditor {workspace_id} post_id={id} />
+                </div>
+            }
+        }
+        Route::Admin => {
+            html! {
+                <div class="container">
+                    <h1><Text id="nav.admin" /></h1>
+                    <div class="flex-container">
+                        <button class="link">
+                        <Link<Route> to={Route::Posts}>
+                                <Text id="nav.posts" />
+                        </Link<Route>>
+                        </button>
+                    </div>
+                    <AdminUsers/>
+                </div>
+            }
+        }
+        Route::Shared { token } => {
+            html! {
+                <div class="container">
+                    <SharedPostView {token} />
+                </div>
+            }
+        }
         Route::NotFound => html! {
            <div class="container">
-               <AlertComponent message="404! Page not found!" route={Route::Home}/>
+               <AlertComponent message="alert.not_found" route={Route::Home}/>
            </div>
         },
     }
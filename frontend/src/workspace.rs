@@ -0,0 +1,68 @@
+use common::{ErrorResponse, ResponseWorkspace};
+use reqwasm::http::Method;
+use uuid::Uuid;
+use yew::prelude::*;
+
+use crate::api::request;
+
+//local storage key of the selected workspace
+const STORAGE_KEY: &str = "workspace";
+
+/// The context with the selected workspace. It is provided by `App`, setting it reloads the notes.
+/// It is `None` before the workspaces of the user are loaded.
+pub type WorkspaceContext = UseStateHandle<Option<Uuid>>;
+
+/// The function `stored_workspace` returns the workspace selected by the user the last time, if any.
+pub fn stored_workspace() -> Option<Uuid> {
+    web_sys::window()
+        .and_then(|window| window.local_storage().ok().flatten())
+        .and_then(|storage| storage.get_item(STORAGE_KEY).ok().flatten())
+        .and_then(|id| Uuid::parse_str(&id).ok())
+}
+
+/// The function `store_workspace` saves the workspace selected by the user, so it is kept after a
+/// reload.
+pub fn store_workspace(workspace: Uuid) {
+    let storage = web_sys::window().and_then(|window| window.local_storage().ok().flatten());
+    if let Some(storage) = storage {
+        if storage
+            .set_item(STORAGE_KEY, &workspace.to_string())
+            .is_err()
+        {
+            log::error!("Failed to save the workspace!");
+        }
+    }
+}
+
+/// The hook `use_workspace` returns the context with the selected workspace.
+#[hook]
+pub fn use_workspace() -> WorkspaceContext {
+    use_context::<WorkspaceContext>().expect("Workspace context is not provided")
+}
+
+/// The function `workspace_uri` returns the path of an endpoint of the notes in a workspace. Without
+/// a selected workspace the personal workspace of the user is used, it is the first one of the list.
+///
+/// Arguments:
+///
+/// * `workspace`: The selected workspace, if any.
+/// * `path`: The path after the workspace, for example `/posts`.
+///
+/// Returns:
+///
+/// the path like `/workspaces/{id}/posts`, or the `ErrorResponse` of the workspaces request.
+pub async fn workspace_uri(workspace: Option<Uuid>, path: &str) -> Result<String, ErrorResponse> {
+    let workspace = match workspace {
+        Some(workspace) => workspace,
+        None => {
+            let workspaces =
+                request::<(), Vec<ResponseWorkspace>>(Method::GET, "/workspaces".to_owned(), None)
+                    .await?;
+            workspaces
+                .first()
+                .map(|workspace| workspace.id)
+                .unwrap_or_default()
+        }
+    };
+    Ok(format!("/workspaces/{}{}", workspace, path))
+}